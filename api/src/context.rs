@@ -7,8 +7,9 @@ use crate::{
     metrics,
     response::{
         bcs_api_disabled, block_not_found_by_height, block_not_found_by_version,
-        block_pruned_by_height, json_api_disabled, version_not_found, version_pruned,
-        ForbiddenError, InternalError, NotFoundError, ServiceUnavailableError, StdApiError,
+        block_pruned_by_height, internal_indexer_error, json_api_disabled, ledger_data_pruned,
+        version_not_found, version_pruned, ForbiddenError, GoneError, InternalError, NotFoundError,
+        ServiceUnavailableError, StdApiError,
     },
 };
 use anyhow::{anyhow, bail, ensure, format_err, Context as AnyhowContext, Result};
@@ -25,7 +26,7 @@ use aptos_storage_interface::{
     state_store::state_view::db_state_view::{
         DbStateView, DbStateViewAtVersion, LatestDbStateCheckpointView,
     },
-    AptosDbError, DbReader, Order, MAX_REQUEST_LIMIT,
+    AptosDbError, DbReader, LedgerDataCategory, Order, MAX_REQUEST_LIMIT,
 };
 use aptos_types::{
     access_path::{AccessPath, Path},
@@ -54,7 +55,7 @@ use futures::{channel::oneshot, SinkExt};
 use mini_moka::sync::Cache;
 use move_core_types::{
     identifier::Identifier,
-    language_storage::{ModuleId, StructTag},
+    language_storage::{ModuleId, StructTag, TypeTag},
     move_resource::MoveResource,
 };
 use serde::Serialize;
@@ -308,7 +309,8 @@ impl Context {
                 &latest_ledger_info,
             ));
         } else if requested_ledger_version < latest_ledger_info.oldest_ledger_version.0 {
-            return Err(version_pruned(
+            return Err(self.ledger_data_pruned_error(
+                &[LedgerDataCategory::TransactionInfos],
                 requested_ledger_version,
                 &latest_ledger_info,
             ));
@@ -317,6 +319,49 @@ impl Context {
         Ok((latest_ledger_info, requested_ledger_version))
     }
 
+    /// Returns whether all the `categories` of ledger data are kept at `version`, which can be
+    /// the case below the oldest ledger version, or not above it, per the ledger retention
+    /// overrides.
+    pub fn is_ledger_data_available(
+        &self,
+        categories: &[LedgerDataCategory],
+        version: Version,
+    ) -> anyhow::Result<bool> {
+        for &category in categories {
+            let first_version = self.db.get_first_ledger_data_version(category)?;
+            if !matches!(first_version, Some(first_version) if version >= first_version) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns the error for a version whose ledger data is pruned, naming the first of
+    /// `categories` that is pruned at that version, or the version in general if none is.
+    pub fn ledger_data_pruned_error<E: GoneError + InternalError>(
+        &self,
+        categories: &[LedgerDataCategory],
+        version: Version,
+        latest_ledger_info: &LedgerInfo,
+    ) -> E {
+        for &category in categories {
+            let first_version = match self.db.get_first_ledger_data_version(category) {
+                Ok(first_version) => first_version,
+                Err(err) => {
+                    return E::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        latest_ledger_info,
+                    )
+                },
+            };
+            if first_version.map_or(true, |first_version| version < first_version) {
+                return ledger_data_pruned(category.name(), version, latest_ledger_info);
+            }
+        }
+        version_pruned(version, latest_ledger_info)
+    }
+
     pub fn get_latest_internal_indexer_ledger_info<E: ServiceUnavailableError>(
         &self,
     ) -> Result<LedgerInfo, E> {
@@ -659,7 +704,12 @@ impl Context {
         with_transactions: bool,
     ) -> Result<BcsBlock, E> {
         if version < latest_ledger_info.oldest_ledger_version.0 {
-            return Err(version_pruned(version, latest_ledger_info));
+            let categories: &[LedgerDataCategory] = if with_transactions {
+                &[LedgerDataCategory::Events, LedgerDataCategory::Transactions]
+            } else {
+                &[LedgerDataCategory::Events]
+            };
+            return Err(self.ledger_data_pruned_error(categories, version, latest_ledger_info));
         } else if version > latest_ledger_info.version() {
             return Err(version_not_found(version, latest_ledger_info));
         }
@@ -938,6 +988,36 @@ impl Context {
             .map_err(|err| E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info))
     }
 
+    /// Returns the transactions that involve the account in any way (sender, signer, event,
+    /// write set or object transfer), as recorded by the internal indexer.
+    pub fn get_account_involved_transactions<
+        E: NotFoundError + InternalError + ServiceUnavailableError,
+    >(
+        &self,
+        address: AccountAddress,
+        start_version: Option<u64>,
+        limit: u16,
+        ledger_version: u64,
+        ledger_info: &LedgerInfo,
+    ) -> Result<Vec<TransactionOnChainData>, E> {
+        let txns = self
+            .indexer_reader
+            .as_ref()
+            .ok_or_else(|| anyhow!("Indexer reader is None"))
+            .map_err(|err| E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info))?
+            .get_account_transactions(address, start_version, limit as u64, true, ledger_version)
+            .context("Failed to retrieve account involved transactions")
+            .map_err(|err| internal_indexer_error(err, ledger_info))?;
+        txns.into_iter()
+            .map(|t| -> Result<TransactionOnChainData> {
+                let txn = self.convert_into_transaction_on_chain_data(t)?;
+                Ok(self.maybe_translate_v2_to_v1_events(txn))
+            })
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to parse account involved transactions")
+            .map_err(|err| E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info))
+    }
+
     pub fn get_account_transaction_summaries<E: NotFoundError + InternalError>(
         &self,
         address: AccountAddress,
@@ -1111,6 +1191,38 @@ impl Context {
         }
     }
 
+    pub fn is_account_transaction_index_enabled(&self) -> bool {
+        self.indexer_reader.is_some()
+            && self
+                .node_config
+                .indexer_db_config
+                .enable_account_transactions()
+    }
+
+    pub fn is_event_by_type_index_enabled(&self) -> bool {
+        self.indexer_reader.is_some() && self.node_config.indexer_db_config.enable_event_by_type()
+    }
+
+    pub fn get_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Option<u64>,
+        end_version: Option<u64>,
+        limit: u16,
+        ledger_version: u64,
+    ) -> Result<Vec<EventWithVersion>> {
+        self.indexer_reader
+            .as_ref()
+            .ok_or_else(|| anyhow!("Internal indexer reader doesn't exist"))?
+            .get_events_by_type(
+                type_tag,
+                start_version,
+                end_version,
+                limit as u64,
+                ledger_version,
+            )
+    }
+
     pub fn get_indexer_reader(&self) -> Option<&Arc<dyn IndexerReader>> {
         self.indexer_reader.as_ref()
     }
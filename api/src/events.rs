@@ -9,8 +9,8 @@ use crate::{
     failpoint::fail_point_poem,
     page::Page,
     response::{
-        BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
-        InternalError,
+        api_forbidden, internal_indexer_error, BadRequestError, BasicErrorWith404, BasicResponse,
+        BasicResponseStatus, BasicResultWith404, InternalError,
     },
     ApiTags,
 };
@@ -19,12 +19,13 @@ use aptos_api_types::{
     verify_field_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper, LedgerInfo,
     MoveStructTag, VerifyInputWithRecursion, VersionedEvent, U64,
 };
-use aptos_types::event::EventKey;
+use aptos_types::{contract_event::EventWithVersion, event::EventKey};
+use move_core_types::language_storage::{StructTag, TypeTag};
 use poem_openapi::{
     param::{Path, Query},
     OpenApi,
 };
-use std::sync::Arc;
+use std::{cmp::min, sync::Arc};
 
 #[derive(Clone)]
 pub struct EventsApi {
@@ -149,6 +150,100 @@ impl EventsApi {
         })
         .await
     }
+
+    /// Get events by type
+    ///
+    /// Retrieves the events of the given type (e.g. `0x1::coin::CoinDeposit`), regardless of
+    /// the account or event handle they were emitted to. Both handle events and module events
+    /// are supported. This API requires the internal event by type index to be enabled on the
+    /// node, and is forbidden otherwise.
+    ///
+    /// If start_version is provided, the output consists of events emitted starting from that
+    /// version.
+    ///
+    /// If start_version is not provided, the output consists of the most recent events up to
+    /// end_version, or up to the latest ledger version if end_version is not provided either.
+    ///
+    /// The output is always ordered in ascending order by version and event index. Since a
+    /// single transaction may emit several events of the same type, a page can end in the middle
+    /// of a transaction.
+    #[oai(
+        path = "/events/by_type/:type_tag",
+        method = "get",
+        operation_id = "get_events_by_type",
+        tag = "ApiTags::Events"
+    )]
+    async fn get_events_by_type(
+        &self,
+        accept_type: AcceptType,
+        /// Fully qualified type of the events e.g. `0x1::coin::CoinDeposit`
+        type_tag: Path<MoveStructTag>,
+        /// Transaction version to start list of events
+        ///
+        /// If unspecified, by default will retrieve the most recent events
+        start_version: Query<Option<U64>>,
+        /// Transaction version to end list of events (inclusive)
+        ///
+        /// If unspecified, defaults to the latest ledger version
+        end_version: Query<Option<U64>>,
+        /// Max number of events to retrieve.
+        ///
+        /// If unspecified, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<VersionedEvent>> {
+        type_tag
+            .0
+            .verify(0)
+            .context("'type_tag' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_events_by_type")?;
+        self.context
+            .check_api_output_enabled("Get events by type", &accept_type)?;
+        if !self.context.is_event_by_type_index_enabled() {
+            return Err(api_forbidden(
+                "Get events by type",
+                "The internal event by type index is not enabled on this node.",
+            ));
+        }
+        let limit = if let Some(limit) = limit.0 {
+            min(limit, self.context.max_events_page_size())
+        } else {
+            self.context.max_events_page_size()
+        };
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            let (latest_ledger_info, ledger_version) = api
+                .context
+                .get_latest_ledger_info_and_verify_lookup_version(None)?;
+            let struct_tag: StructTag = (&type_tag.0)
+                .try_into()
+                .context("Given event type was invalid")
+                .map_err(|err| {
+                    BasicErrorWith404::bad_request_with_code(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                        &latest_ledger_info,
+                    )
+                })?;
+            let event_type = TypeTag::Struct(Box::new(struct_tag));
+            let events = api
+                .context
+                .get_events_by_type(
+                    &event_type,
+                    start_version.0.map(|v| v.0),
+                    end_version.0.map(|v| v.0),
+                    limit,
+                    ledger_version,
+                )
+                .context(format!("Failed to find events by type {}", event_type))
+                .map_err(|err| internal_indexer_error(err, &latest_ledger_info))?;
+            api.render(latest_ledger_info, accept_type, events)
+        })
+        .await
+    }
 }
 
 impl EventsApi {
@@ -177,7 +272,16 @@ impl EventsApi {
                     &latest_ledger_info,
                 )
             })?;
+        self.render(latest_ledger_info, accept_type, events)
+    }
 
+    /// Render events in the requested format
+    fn render(
+        &self,
+        latest_ledger_info: LedgerInfo,
+        accept_type: AcceptType,
+        events: Vec<EventWithVersion>,
+    ) -> BasicResultWith404<Vec<VersionedEvent>> {
         match accept_type {
             AcceptType::Json => {
                 let events = self
@@ -31,6 +31,7 @@
 
 use super::{accept_type::AcceptType, bcs_payload::Bcs};
 use aptos_api_types::{Address, AptosError, AptosErrorCode, HashValue, LedgerInfo};
+use aptos_storage_interface::AptosDbError;
 use move_core_types::{
     identifier::{IdentStr, Identifier},
     language_storage::StructTag,
@@ -669,6 +670,35 @@ pub fn version_pruned<E: GoneError>(ledger_version: u64, ledger_info: &LedgerInf
     )
 }
 
+pub fn ledger_data_pruned<E: GoneError>(
+    category: &str,
+    ledger_version: u64,
+    ledger_info: &LedgerInfo,
+) -> E {
+    E::gone_with_code(
+        format!(
+            "{} at ledger version({}) has been pruned",
+            category, ledger_version
+        ),
+        AptosErrorCode::VersionPruned,
+        ledger_info,
+    )
+}
+
+/// Returns a 503 if the internal indexer failed because the index is still being backfilled,
+/// and a 500 otherwise.
+pub fn internal_indexer_error<E: InternalError + ServiceUnavailableError>(
+    error: anyhow::Error,
+    ledger_info: &LedgerInfo,
+) -> E {
+    match error.downcast_ref::<AptosDbError>() {
+        Some(AptosDbError::IndexBackfilling(_)) => {
+            E::service_unavailable_with_code(error, AptosErrorCode::InternalError, ledger_info)
+        },
+        _ => E::internal_with_code(error, AptosErrorCode::InternalError, ledger_info),
+    }
+}
+
 pub fn account_not_found<E: NotFoundError>(
     address: Address,
     ledger_version: u64,
@@ -5,18 +5,20 @@ use crate::{
     accept_type::AcceptType,
     context::api_spawn_blocking,
     failpoint::fail_point_poem,
+    page::Page,
     response::{
         api_forbidden, build_not_found, module_not_found, resource_not_found, table_item_not_found,
-        BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
-        InternalError,
+        version_not_found, version_pruned, BadRequestError, BasicErrorWith404, BasicResponse,
+        BasicResponseStatus, BasicResultWith404, InternalError,
     },
     ApiTags, Context,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
-    verify_module_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper,
+    verify_module_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper, LedgerInfo,
     MoveModuleBytecode, MoveResource, MoveStructTag, MoveValue, RawStateValueRequest,
-    RawTableItemRequest, TableItemRequest, VerifyInput, VerifyInputWithRecursion, U64,
+    RawTableItemRequest, TableItemRequest, VerifyInput, VerifyInputWithRecursion,
+    VersionedMoveResource, U64,
 };
 use aptos_types::state_store::{state_key::StateKey, table::TableHandle, TStateView};
 use move_core_types::language_storage::StructTag;
@@ -83,6 +85,62 @@ impl StateApi {
         .await
     }
 
+    /// Get account resource history
+    ///
+    /// Retrieves the versions at which a resource of a given account was changed, along with the
+    /// resource after each change, in increasing order of versions. A `null` resource means the
+    /// resource was deleted at that version. For a resource stored in a resource group, every
+    /// change to the group is listed.
+    ///
+    /// To retrieve the next page, set `start` to one past the version of the last change returned.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested start version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/accounts/:address/resource/:resource_type/history",
+        method = "get",
+        operation_id = "get_account_resource_history",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_account_resource_history(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Name of struct to retrieve e.g. `0x1::account::Account`
+        resource_type: Path<MoveStructTag>,
+        /// Ledger version to start looking for changes at (inclusive)
+        ///
+        /// If not provided, defaults to the oldest version that has not been pruned
+        start: Query<Option<U64>>,
+        /// Max number of changes to retrieve
+        ///
+        /// If not provided, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<VersionedMoveResource>> {
+        resource_type
+            .0
+            .verify(0)
+            .context("'resource_type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_account_resource_history")?;
+        self.context
+            .check_api_output_enabled("Get account resource history", &accept_type)?;
+        let page = Page::new(
+            start.0.map(|v| v.0),
+            limit.0,
+            self.context.max_account_resources_page_size(),
+        );
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.resource_history(&accept_type, address.0, resource_type.0, page)
+        })
+        .await
+    }
+
     /// Get account module
     ///
     /// Retrieves an individual module from a given account and at a specific ledger version. If the
@@ -327,6 +385,147 @@ impl StateApi {
         }
     }
 
+    fn get_first_state_kv_version(
+        &self,
+        ledger_info: &LedgerInfo,
+    ) -> Result<u64, BasicErrorWith404> {
+        self.context
+            .db
+            .get_first_state_kv_version()
+            .context("Failed to get the first version of the state value history")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })
+            .map(|version| version.unwrap_or(0))
+    }
+
+    /// Read the changes of a resource, starting at the page start version
+    ///
+    /// JSON: Convert to VersionedMoveResource
+    /// BCS: Leave the resources encoded, as pairs of versions and optional resources
+    fn resource_history(
+        &self,
+        accept_type: &AcceptType,
+        address: Address,
+        resource_type: MoveStructTag,
+        page: Page,
+    ) -> BasicResultWith404<Vec<VersionedMoveResource>> {
+        let tag: StructTag = (&resource_type)
+            .try_into()
+            .context("Failed to parse given resource type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let ledger_version = ledger_info.version();
+        // The history of state values is pruned separately from the ledger.
+        let first_version = self.get_first_state_kv_version(&ledger_info)?;
+        let start_version = page.start_option().unwrap_or(first_version);
+        if start_version > ledger_version {
+            return Err(version_not_found(start_version, &ledger_info));
+        } else if start_version < first_version {
+            return Err(version_pruned(start_version, &ledger_info));
+        }
+        let limit = page.limit(&ledger_info)?;
+
+        let state_view = self.context.latest_state_view_poem(&ledger_info)?;
+        let converter =
+            state_view.as_converter(self.context.db.clone(), self.context.indexer_reader.clone());
+        let (state_key, in_resource_group) = converter
+            .resource_state_key(address, &tag)
+            .context("Failed to compute the state key of the resource")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                )
+            })?;
+        let history = match self.context.db.get_state_value_history(
+            &state_key,
+            start_version,
+            limit as u64,
+            ledger_version,
+        ) {
+            Ok(history) => history,
+            Err(err) => {
+                // The pruner might have moved past the start version since checked above.
+                if start_version < self.get_first_state_kv_version(&ledger_info)? {
+                    return Err(version_pruned(start_version, &ledger_info));
+                }
+                return Err(BasicErrorWith404::internal_with_code(
+                    anyhow::Error::from(err).context(format!(
+                        "Failed to query DB for the history of {} at {}",
+                        tag.to_canonical_string(),
+                        address
+                    )),
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                ));
+            },
+        };
+        let changes = history
+            .into_iter()
+            .map(|(version, state_value)| {
+                let bytes = match state_value {
+                    Some(state_value) => converter.extract_resource(
+                        &tag,
+                        in_resource_group,
+                        state_value.bytes().clone(),
+                    )?,
+                    None => None,
+                };
+                Ok((version, bytes))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .context("Failed to extract resource data retrieved from DB")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+
+        match accept_type {
+            AcceptType::Json => {
+                let resources = changes
+                    .into_iter()
+                    .map(|(version, bytes)| {
+                        Ok(VersionedMoveResource {
+                            version: version.into(),
+                            resource: bytes
+                                .map(|bytes| converter.try_into_resource(&tag, &bytes))
+                                .transpose()?,
+                        })
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()
+                    .context("Failed to deserialize resource data retrieved from DB")
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &ledger_info,
+                        )
+                    })?;
+
+                BasicResponse::try_from_json((resources, &ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Bcs => {
+                let changes: Vec<(u64, Option<Vec<u8>>)> = changes
+                    .into_iter()
+                    .map(|(version, bytes)| (version, bytes.map(|bytes| bytes.to_vec())))
+                    .collect();
+                BasicResponse::try_from_bcs((changes, &ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+
     /// Retrieve the module
     ///
     /// JSON: Parse ABI and bytecode
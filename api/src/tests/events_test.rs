@@ -2,9 +2,10 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use super::new_test_context;
+use super::{new_test_context, super_new_test_context};
 use crate::tests::new_test_context_with_orderless_flags;
 use aptos_api_test_context::{current_function_name, TestContext};
+use aptos_config::config::{internal_indexer_db_config::InternalIndexerDBConfig, NodeConfig};
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use rstest::rstest;
 use serde_json::json;
@@ -278,6 +279,62 @@ async fn test_module_events(use_txn_payload_v2_format: bool, use_orderless_trans
     assert_eq!(events.len(), 8);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_by_type() {
+    let mut context = new_test_context(current_function_name!());
+    context.api_create_account().await;
+    context.wait_for_internal_indexer_caught_up().await;
+
+    // Every block metadata transaction emits an event of the same type.
+    let txns = context.get("/transactions?start=1&limit=1").await;
+    let event_type = txns[0]["events"][0]["type"].as_str().unwrap().to_owned();
+
+    let resp = context
+        .get(&format!("/events/by_type/{}?start_version=0", event_type))
+        .await;
+    let events = resp.as_array().unwrap();
+    assert!(!events.is_empty());
+    assert!(events.iter().all(|e| e["type"] == event_type.as_str()));
+    let versions: Vec<u64> = events
+        .iter()
+        .map(|e| e["version"].as_str().unwrap().parse().unwrap())
+        .collect();
+    assert!(versions.windows(2).all(|w| w[0] <= w[1]));
+
+    // Without a start version, the most recent events are returned.
+    let resp = context
+        .get(&format!("/events/by_type/{}?limit=1", event_type))
+        .await;
+    assert_eq!(
+        resp.as_array().unwrap().as_slice(),
+        &events[events.len() - 1..]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_by_type_index_disabled() {
+    let mut node_config = NodeConfig::default();
+    node_config.indexer_db_config = InternalIndexerDBConfig {
+        enable_account_transactions: true,
+        ..InternalIndexerDBConfig::new(true, true, true, 0, true, 10)
+    };
+    let mut context = super_new_test_context(
+        current_function_name!(),
+        node_config,
+        false,
+        None,
+        false,
+        false,
+        None,
+    );
+
+    let resp = context
+        .expect_status_code(403)
+        .get("/events/by_type/0x1::block::NewBlockEvent")
+        .await;
+    assert_eq!(resp["error_code"], "api_disabled");
+}
+
 // until we have generics in the genesis
 #[ignore]
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -39,7 +39,11 @@ fn new_test_context_with_config(
     use_txn_payload_v2_format: bool,
     use_orderless_transactions: bool,
 ) -> TestContext {
-    node_config.indexer_db_config = InternalIndexerDBConfig::new(true, true, true, 0, true, 10);
+    node_config.indexer_db_config = InternalIndexerDBConfig {
+        enable_account_transactions: true,
+        enable_event_by_type: true,
+        ..InternalIndexerDBConfig::new(true, true, true, 0, true, 10)
+    };
     let test_context = super_new_test_context(
         test_name,
         node_config,
@@ -47,6 +51,7 @@ fn new_test_context_with_config(
         None,
         use_txn_payload_v2_format,
         use_orderless_transactions,
+        None,
     );
     let _ = test_context
         .get_indexer_reader()
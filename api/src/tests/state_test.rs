@@ -1,8 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{new_test_context, new_test_context_with_orderless_flags};
+use super::{new_test_context, new_test_context_with_orderless_flags, super_new_test_context};
 use aptos_api_test_context::{current_function_name, TestContext};
+use aptos_config::config::{LedgerPrunerConfig, NodeConfig};
 use aptos_sdk::{transaction_builder::aptos_stdlib::aptos_token_stdlib, types::LocalAccount};
 use aptos_storage_interface::DbReader;
 use move_core_types::account_address::AccountAddress;
@@ -95,6 +96,57 @@ async fn test_get_account_resource_with_version_too_large() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_history_pruned() {
+    let mut storage_pruner_config = NodeConfig::default().storage.storage_pruner_config;
+    // No history is kept, so the state values are pruned once they are overwritten.
+    storage_pruner_config.ledger_pruner_config = LedgerPrunerConfig {
+        enable: true,
+        prune_window: 0,
+        prune_window_secs: None,
+        batch_size: 1,
+        user_pruning_window_offset: 0,
+        retention_overrides: Default::default(),
+    };
+    let mut context = super_new_test_context(
+        current_function_name!(),
+        NodeConfig::default(),
+        false,
+        None,
+        false,
+        false,
+        Some(storage_pruner_config),
+    );
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn]).await;
+
+    let resp = context
+        .expect_status_code(410)
+        .get(&get_account_resource_history(
+            "0xA550C18",
+            "0x1::account::Account",
+            Some(0),
+        ))
+        .await;
+    assert_eq!(resp["error_code"], "version_pruned");
+
+    // Starts at the oldest version that has not been pruned by default.
+    let ledger_version = context.get_latest_ledger_info().version();
+    let resp = context
+        .expect_status_code(200)
+        .get(&get_account_resource_history(
+            "0xA550C18",
+            "0x1::account::Account",
+            None,
+        ))
+        .await;
+    for change in resp.as_array().unwrap() {
+        let version: u64 = change["version"].as_str().unwrap().parse().unwrap();
+        assert!(version >= ledger_version);
+    }
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_module() {
     let mut context = new_test_context(current_function_name!());
@@ -347,6 +399,14 @@ fn get_account_resource_with_version(address: &str, struct_tag: &str, version: u
     )
 }
 
+fn get_account_resource_history(address: &str, struct_tag: &str, start: Option<u64>) -> String {
+    let path = format!("/accounts/{}/resource/{}/history", address, struct_tag);
+    match start {
+        Some(start) => format!("{}?start={}", path, start),
+        None => path,
+    }
+}
+
 fn get_account_module(address: &str, name: &str) -> String {
     format!("/accounts/{}/module/{}", address, name)
 }
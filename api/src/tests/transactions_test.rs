@@ -2,10 +2,13 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use super::new_test_context;
+use super::{new_test_context, super_new_test_context};
 use crate::tests::{new_test_context_with_config, new_test_context_with_orderless_flags};
 use aptos_api_test_context::{assert_json, current_function_name, pretty, TestContext};
-use aptos_config::config::{GasEstimationStaticOverride, NodeConfig, TransactionFilterConfig};
+use aptos_config::config::{
+    internal_indexer_db_config::InternalIndexerDBConfig, GasEstimationStaticOverride, NodeConfig,
+    TransactionFilterConfig,
+};
 use aptos_crypto::{
     ed25519::{Ed25519PrivateKey, Ed25519Signature},
     multi_ed25519::{MultiEd25519PrivateKey, MultiEd25519PublicKey},
@@ -1144,6 +1147,72 @@ async fn test_get_account_transactions(
     test_account_transaction_with_context(context).await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_involved_transactions() {
+    let mut context = new_test_context(current_function_name!());
+    let sender = &mut context.api_create_account().await;
+    let receiver = context.api_create_account().await;
+    context
+        .api_execute_aptos_account_transfer(sender, receiver.address(), 10)
+        .await;
+    context.wait_for_internal_indexer_caught_up().await;
+
+    // The receiver never sends a transaction, it is involved through the deposits only.
+    let txns = context
+        .get(&format!(
+            "/accounts/{}/involved_transactions?start=0",
+            receiver.address()
+        ))
+        .await;
+    let txns = txns.as_array().unwrap();
+    assert_eq!(txns.len(), 2);
+    assert_eq!(
+        txns[0]["sender"],
+        context.root_account().await.address().to_hex_literal()
+    );
+    assert_eq!(txns[1]["sender"], sender.address().to_hex_literal());
+
+    let version = |txn: &Value| txn["version"].as_str().unwrap().parse::<u64>().unwrap();
+    let last_version = version(&txns[1]);
+    assert!(version(&txns[0]) < last_version);
+
+    // Pagination starts at the given version.
+    let txns = context
+        .get(&format!(
+            "/accounts/{}/involved_transactions?start={}&limit=1",
+            receiver.address(),
+            last_version
+        ))
+        .await;
+    let txns = txns.as_array().unwrap();
+    assert_eq!(txns.len(), 1);
+    assert_eq!(version(&txns[0]), last_version);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_involved_transactions_index_disabled() {
+    let mut node_config = NodeConfig::default();
+    node_config.indexer_db_config = InternalIndexerDBConfig {
+        enable_event_by_type: true,
+        ..InternalIndexerDBConfig::new(true, true, true, 0, true, 10)
+    };
+    let mut context = super_new_test_context(
+        current_function_name!(),
+        node_config,
+        false,
+        None,
+        false,
+        false,
+        None,
+    );
+
+    let resp = context
+        .expect_status_code(403)
+        .get("/accounts/0x1/involved_transactions")
+        .await;
+    assert_eq!(resp["error_code"], "api_disabled");
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 #[rstest(
     use_txn_payload_v2_format,
@@ -13,9 +13,9 @@ use crate::{
     page::Page,
     response::{
         api_disabled, api_forbidden, transaction_not_found_by_hash,
-        transaction_not_found_by_version, version_pruned, BadRequestError, BasicError,
-        BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResult, BasicResultWith404,
-        ForbiddenError, InsufficientStorageError, InternalError,
+        transaction_not_found_by_version, BadRequestError, BasicError, BasicErrorWith404,
+        BasicResponse, BasicResponseStatus, BasicResult, BasicResultWith404, ForbiddenError,
+        InsufficientStorageError, InternalError,
     },
     view_function::convert_view_function_error,
     ApiTags,
@@ -31,6 +31,7 @@ use aptos_api_types::{
 };
 use aptos_crypto::{hash::CryptoHash, signing_message};
 use aptos_logger::error;
+use aptos_storage_interface::LedgerDataCategory;
 use aptos_types::{
     account_address::AccountAddress,
     mempool_status::MempoolStatusCode,
@@ -387,6 +388,55 @@ impl TransactionsApi {
             .await
     }
 
+    /// Get account involved transactions
+    ///
+    /// Retrieves on-chain committed transactions that involve an account in any way, not only
+    /// those sent by it. This includes transactions where the account is a secondary signer or
+    /// fee payer, receives an event, has a resource written, or is the source or destination of
+    /// an object transfer. Transactions are returned in ascending order of version.
+    ///
+    /// This endpoint requires the internal account transaction index to be enabled on the node.
+    ///
+    /// If no start version is given, the most recent transactions are returned.
+    #[oai(
+        path = "/accounts/:address/involved_transactions",
+        method = "get",
+        operation_id = "get_account_involved_transactions",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn get_accounts_involved_transactions(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Transaction version to start list of transactions
+        ///
+        /// If not provided, defaults to showing the latest transactions
+        start: Query<Option<U64>>,
+        /// Max number of transactions to retrieve.
+        ///
+        /// If not provided, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<Transaction>> {
+        fail_point_poem("endpoint_get_accounts_involved_transactions")?;
+        self.context
+            .check_api_output_enabled("Get account involved transactions", &accept_type)?;
+        if !self.context.is_account_transaction_index_enabled() {
+            return Err(api_forbidden(
+                "Get account involved transactions",
+                "The internal account transaction index is not enabled on this node.",
+            ));
+        }
+        let page = Page::new(
+            start.0.map(|v| v.0),
+            limit.0,
+            self.context.max_transactions_page_size(),
+        );
+        let api = self.clone();
+        api_spawn_blocking(move || api.list_involved_txns_by_account(&accept_type, page, address.0))
+            .await
+    }
+
     /// Get account transaction summaries
     ///
     /// Retrieves summaries of on-chain committed transactions (both sequence number based
@@ -1000,7 +1050,15 @@ impl TransactionsApi {
             GetByVersionResponse::VersionTooNew => {
                 Err(transaction_not_found_by_version(version.0, &ledger_info))
             },
-            GetByVersionResponse::VersionTooOld => Err(version_pruned(version.0, &ledger_info)),
+            GetByVersionResponse::VersionTooOld => Err(self.context.ledger_data_pruned_error(
+                &[
+                    LedgerDataCategory::Transactions,
+                    LedgerDataCategory::TransactionInfos,
+                    LedgerDataCategory::Events,
+                ],
+                version.0,
+                &ledger_info,
+            )),
         }
     }
 
@@ -1065,7 +1123,16 @@ impl TransactionsApi {
         if version > ledger_info.version() {
             return Ok(GetByVersionResponse::VersionTooNew);
         }
-        if version < ledger_info.oldest_version() {
+        // The transaction is served along with its info, proof and events, which are kept per
+        // their own retention rather than the oldest ledger version.
+        if !self.context.is_ledger_data_available(
+            &[
+                LedgerDataCategory::Transactions,
+                LedgerDataCategory::TransactionInfos,
+                LedgerDataCategory::Events,
+            ],
+            version,
+        )? {
             return Ok(GetByVersionResponse::VersionTooOld);
         }
         Ok(GetByVersionResponse::Found(
@@ -1142,6 +1209,37 @@ impl TransactionsApi {
         }
     }
 
+    /// List transactions involving an account
+    fn list_involved_txns_by_account(
+        &self,
+        accept_type: &AcceptType,
+        page: Page,
+        address: Address,
+    ) -> BasicResultWith404<Vec<Transaction>> {
+        let (latest_ledger_info, ledger_version) = self
+            .context
+            .get_latest_ledger_info_and_verify_lookup_version(None)?;
+
+        let data = self.context.get_account_involved_transactions(
+            address.into(),
+            page.start_option(),
+            page.limit(&latest_ledger_info)?,
+            ledger_version,
+            &latest_ledger_info,
+        )?;
+        match accept_type {
+            AcceptType::Json => BasicResponse::try_from_json((
+                self.context
+                    .render_transactions_non_sequential(&latest_ledger_info, data)?,
+                &latest_ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((data, &latest_ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+
     /// List transaction summaries of committed transactions of an account
     fn list_txn_summaries_by_account(
         &self,
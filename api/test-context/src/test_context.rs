@@ -10,8 +10,9 @@ use aptos_api_types::{
 use aptos_cached_packages::aptos_stdlib;
 use aptos_config::{
     config::{
-        NodeConfig, RocksdbConfigs, StorageDirPaths, BUFFERED_STATE_TARGET_ITEMS_FOR_TEST,
-        DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD, NO_OP_STORAGE_PRUNER_CONFIG,
+        NodeConfig, PrunerConfig, RocksdbConfigs, StorageDirPaths,
+        BUFFERED_STATE_TARGET_ITEMS_FOR_TEST, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        NO_OP_STORAGE_PRUNER_CONFIG,
     },
     keys::ConfigKey,
 };
@@ -116,9 +117,12 @@ pub fn new_test_context(
         None,
         false,
         false,
+        None,
     )
 }
 
+/// `storage_pruner_config` enables the pruners, which are disabled if it's `None`. It's ignored if
+/// `use_db_with_indexer` is set.
 pub fn new_test_context_inner(
     test_name: String,
     mut node_config: NodeConfig,
@@ -126,6 +130,7 @@ pub fn new_test_context_inner(
     end_version: Option<u64>,
     use_txn_payload_v2_format: bool,
     use_orderless_transactions: bool,
+    storage_pruner_config: Option<PrunerConfig>,
 ) -> TestContext {
     // Speculative logging uses a global variable and when many instances use it together, they
     // panic, so we disable this to run tests.
@@ -163,8 +168,8 @@ pub fn new_test_context_inner(
     } else {
         let mut aptos_db = AptosDB::open(
             StorageDirPaths::from_path(&tmp_dir),
-            false,                       /* readonly */
-            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
+            false, /* readonly */
+            storage_pruner_config.unwrap_or(NO_OP_STORAGE_PRUNER_CONFIG),
             RocksdbConfigs {
                 enable_storage_sharding: node_config
                     .storage
@@ -116,24 +116,42 @@ impl<'a, S: StateView> MoveConverter<'a, S> {
         address: Address,
         tag: &StructTag,
     ) -> Result<Option<Bytes>> {
+        let (key, in_resource_group) = self.resource_state_key(address, tag)?;
+        match state_view.get_state_value_bytes(&key)? {
+            Some(bytes) => self.extract_resource(tag, in_resource_group, bytes),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the state key under which the resource is stored, and whether that is the key of
+    /// the resource group the resource is a member of.
+    pub fn resource_state_key(
+        &self,
+        address: Address,
+        tag: &StructTag,
+    ) -> Result<(StateKey, bool)> {
         Ok(match self.inner.view_resource_group_member(tag) {
-            Some(group_tag) => {
-                let key = StateKey::resource_group(&address.into(), &group_tag);
-                match state_view.get_state_value_bytes(&key)? {
-                    Some(group_bytes) => {
-                        let group: BTreeMap<StructTag, Bytes> = bcs::from_bytes(&group_bytes)?;
-                        group.get(tag).cloned()
-                    },
-                    None => None,
-                }
-            },
-            None => {
-                let key = StateKey::resource(&address.into(), tag)?;
-                state_view.get_state_value_bytes(&key)?
-            },
+            Some(group_tag) => (StateKey::resource_group(&address.into(), &group_tag), true),
+            None => (StateKey::resource(&address.into(), tag)?, false),
         })
     }
 
+    /// Extracts the resource from the bytes stored under the key returned by
+    /// [`Self::resource_state_key`].
+    pub fn extract_resource(
+        &self,
+        tag: &StructTag,
+        in_resource_group: bool,
+        bytes: Bytes,
+    ) -> Result<Option<Bytes>> {
+        if in_resource_group {
+            let group: BTreeMap<StructTag, Bytes> = bcs::from_bytes(&bytes)?;
+            Ok(group.get(tag).cloned())
+        } else {
+            Ok(Some(bytes))
+        }
+    }
+
     pub fn try_into_resources_from_resource_group(
         &self,
         bytes: &[u8],
@@ -38,7 +38,8 @@ pub use move_types::{
     HexEncodedBytes, MoveAbility, MoveFunction, MoveFunctionGenericTypeParam,
     MoveFunctionVisibility, MoveModule, MoveModuleBytecode, MoveModuleId, MoveResource,
     MoveScriptBytecode, MoveStruct, MoveStructField, MoveStructTag, MoveType, MoveValue,
-    ResourceGroup, I128, I256, I64, MAX_RECURSIVE_TYPES_ALLOWED, U128, U256, U64,
+    ResourceGroup, VersionedMoveResource, I128, I256, I64, MAX_RECURSIVE_TYPES_ALLOWED, U128, U256,
+    U64,
 };
 use serde::{Deserialize, Deserializer};
 pub use state::RawStateValueRequest;
@@ -44,6 +44,15 @@ pub struct MoveResource {
     pub data: MoveStructValue,
 }
 
+/// A Move resource as of a version at which it was changed
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct VersionedMoveResource {
+    /// The version at which the resource was changed
+    pub version: U64,
+    /// The resource after the change, or `null` if it was deleted
+    pub resource: Option<MoveResource>,
+}
+
 impl TryFrom<AnnotatedMoveStruct> for MoveResource {
     type Error = anyhow::Error;
 
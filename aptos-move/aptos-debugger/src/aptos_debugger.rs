@@ -1,24 +1,32 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::overrides::{OverriddenStateView, OverrideConfig};
 use anyhow::{bail, format_err};
 use aptos_block_executor::txn_provider::{default::DefaultTxnProvider, TxnProvider};
 use aptos_gas_profiling::{GasProfiler, TransactionGasLog};
 use aptos_rest_client::Client;
 use aptos_types::{
+    access_path,
     account_address::AccountAddress,
     block_executor::{
         config::{BlockExecutorConfig, BlockExecutorConfigFromOnchain, BlockExecutorLocalConfig},
         transaction_slice_metadata::TransactionSliceMetadata,
     },
     contract_event::ContractEvent,
-    state_store::TStateView,
+    state_store::{
+        state_key::{inner::StateKeyInner, StateKey},
+        state_slot::StateSlot,
+        state_storage_usage::StateStorageUsage,
+        StateView, StateViewId, StateViewResult, TStateView,
+    },
     transaction::{
         signature_verified_transaction::SignatureVerifiedTransaction, AuxiliaryInfo, BlockOutput,
         PersistedAuxiliaryInfo, SignedTransaction, Transaction, TransactionExecutableRef,
         TransactionInfo, TransactionOutput, TransactionPayload, Version,
     },
     vm_status::VMStatus,
+    write_set::{TransactionWrite, WriteOpKind},
 };
 use aptos_validator_interface::{
     AptosValidatorInterface, DBDebuggerInterface, DebuggerStateView, RestDebuggerInterface,
@@ -29,8 +37,15 @@ use aptos_vm::{
 use aptos_vm_environment::environment::AptosEnvironment;
 use aptos_vm_logging::log_schema::AdapterLogSchema;
 use aptos_vm_types::{module_and_script_storage::AsAptosCodeStorage, output::VMOutput};
+use indexmap::IndexSet;
 use itertools::Itertools;
-use std::{path::Path, sync::Arc, time::Instant};
+use move_execution_trace::{ExecutionTrace, TraceEvent, TraceResource, TraceWrite, WriteKind};
+use move_vm_runtime::tracing;
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
 
 pub struct AptosDebugger {
     debugger: Arc<dyn AptosValidatorInterface + Send>,
@@ -180,6 +195,70 @@ impl AptosDebugger {
         Ok((status, output, gas_profiler.finish()))
     }
 
+    /// Executes a transaction while recording a trace of the executed instructions, together
+    /// with the resources read and written and the events emitted. Instructions are only
+    /// recorded if the VM is built with the `debugging` feature.
+    pub fn execute_transaction_at_version_with_trace(
+        &self,
+        version: Version,
+        txn: SignedTransaction,
+        auxiliary_info: AuxiliaryInfo,
+    ) -> anyhow::Result<(VMStatus, VMOutput, ExecutionTrace)> {
+        let state_view =
+            ReadRecordingStateView::new(DebuggerStateView::new(self.debugger.clone(), version));
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+
+        let env = AptosEnvironment::new(&state_view);
+        let vm = AptosVM::new(&env, &state_view);
+        let resolver = state_view.as_move_resolver();
+        let code_storage = state_view.as_aptos_code_storage(&env);
+
+        // Reads of on-chain configs when setting up the environment are not part of the trace.
+        state_view.clear_reads();
+        tracing::start_trace_recording();
+        let (status, output) = vm.execute_user_transaction(
+            &resolver,
+            &code_storage,
+            &txn,
+            &log_context,
+            &auxiliary_info,
+        );
+        let recording = tracing::take_trace_recording();
+        let mut trace = ExecutionTrace::from_instructions(&recording.instructions);
+        trace.truncated = recording.truncated;
+        trace.resources_read = state_view.resources_read();
+
+        let txn_output = output
+            .clone()
+            .try_materialize_into_transaction_output(&resolver)
+            .map_err(|err| format_err!("Failed to materialize transaction output: {:?}", err))?;
+        trace.resources_written = txn_output
+            .write_set()
+            .write_op_iter()
+            .filter_map(|(state_key, write_op)| {
+                let kind = match write_op.write_op_kind() {
+                    WriteOpKind::Creation => WriteKind::Create,
+                    WriteOpKind::Modification => WriteKind::Modify,
+                    WriteOpKind::Deletion => WriteKind::Delete,
+                };
+                Some(TraceWrite {
+                    resource: trace_resource(state_key)?,
+                    kind,
+                })
+            })
+            .collect();
+        trace.events = txn_output
+            .events()
+            .iter()
+            .map(|event| TraceEvent {
+                type_tag: event.type_tag().to_canonical_string(),
+                data: hex::encode(event.event_data()),
+            })
+            .collect();
+
+        Ok((status, output, trace))
+    }
+
     pub async fn execute_past_transactions(
         &self,
         begin: Version,
@@ -217,6 +296,97 @@ impl AptosDebugger {
         }
     }
 
+    /// Replays committed transactions as a single block twice: on the historical state, and on
+    /// the historical state with overrides applied. Returns each transaction with its original
+    /// output and its output with overrides. Replay stops after the first reconfiguration,
+    /// because the following transactions are executed in a new epoch.
+    pub async fn execute_past_transactions_with_overrides(
+        &self,
+        begin: Version,
+        limit: u64,
+        override_config: &OverrideConfig,
+        concurrency_level: usize,
+    ) -> anyhow::Result<Vec<(Transaction, TransactionOutput, TransactionOutput)>> {
+        let (txns, txn_infos, auxiliary_infos) =
+            self.get_committed_transactions(begin, limit).await?;
+
+        let sig_verified_txns = txns
+            .iter()
+            .map(|txn| txn.clone().into())
+            .collect::<Vec<SignatureVerifiedTransaction>>();
+        let auxiliary_infos = auxiliary_infos
+            .into_iter()
+            .map(|persisted_info| AuxiliaryInfo::new(persisted_info, None))
+            .collect::<Vec<_>>();
+        let txn_provider = DefaultTxnProvider::new(sig_verified_txns, auxiliary_infos);
+
+        let state_view = DebuggerStateView::new(self.debugger.clone(), begin);
+        let state_override = override_config.get_state_override(&state_view)?;
+        let overridden_state_view = OverriddenStateView::new(&state_view, state_override);
+
+        let original_outputs =
+            execute_block_no_limit(&txn_provider, &state_view, concurrency_level)
+                .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+        Self::print_mismatches(&original_outputs, &txn_infos, begin);
+        let overridden_outputs =
+            execute_block_no_limit(&txn_provider, &overridden_state_view, concurrency_level)
+                .map_err(|err| format_err!("Unexpected VM Error with overrides: {:?}", err))?;
+
+        let num_txns = original_outputs
+            .iter()
+            .position(is_reconfiguration)
+            .map_or(original_outputs.len(), |idx| idx + 1);
+        if num_txns < original_outputs.len() {
+            println!(
+                "Stopping replay after reconfiguration at version {}",
+                begin + num_txns as Version - 1
+            );
+        }
+
+        Ok(txns
+            .into_iter()
+            .zip(original_outputs)
+            .zip(overridden_outputs)
+            .take(num_txns)
+            .map(|((txn, original_output), overridden_output)| {
+                (txn, original_output, overridden_output)
+            })
+            .collect())
+    }
+
+    /// Executes the transactions as a single block on top of the state at the given version, with
+    /// overrides applied to that state. Unlike other replay functions, block-level VM errors are
+    /// returned as is so that callers can compare them, while failures to apply the overrides are
+    /// returned as the outer error. Every call uses a new block executor, so no module or layout
+    /// caches are shared between calls.
+    pub fn execute_transactions_at_version_with_overrides(
+        &self,
+        version: Version,
+        txns: Vec<Transaction>,
+        auxiliary_infos: Vec<PersistedAuxiliaryInfo>,
+        override_config: &OverrideConfig,
+        concurrency_level: usize,
+    ) -> anyhow::Result<Result<Vec<TransactionOutput>, VMStatus>> {
+        let sig_verified_txns = txns
+            .into_iter()
+            .map(|txn| txn.into())
+            .collect::<Vec<SignatureVerifiedTransaction>>();
+        let auxiliary_infos = auxiliary_infos
+            .into_iter()
+            .map(|persisted_info| AuxiliaryInfo::new(persisted_info, None))
+            .collect::<Vec<_>>();
+        let txn_provider = DefaultTxnProvider::new(sig_verified_txns, auxiliary_infos);
+
+        let state_view = DebuggerStateView::new(self.debugger.clone(), version);
+        let state_override = override_config.get_state_override(&state_view)?;
+        let overridden_state_view = OverriddenStateView::new(&state_view, state_override);
+        Ok(execute_block_no_limit(
+            &txn_provider,
+            &overridden_state_view,
+            concurrency_level,
+        ))
+    }
+
     fn print_mismatches(
         txn_outputs: &[TransactionOutput],
         expected_txn_infos: &[TransactionInfo],
@@ -397,6 +567,76 @@ impl AptosDebugger {
     }
 }
 
+/// Returns the resource or resource group stored under a state key, if it is one.
+fn trace_resource(state_key: &StateKey) -> Option<TraceResource> {
+    let StateKeyInner::AccessPath(ap) = state_key.inner() else {
+        return None;
+    };
+    match ap.get_path() {
+        access_path::Path::Resource(struct_tag) | access_path::Path::ResourceGroup(struct_tag) => {
+            Some(TraceResource {
+                address: ap.address.to_hex_literal(),
+                resource: struct_tag.to_canonical_string(),
+            })
+        },
+        access_path::Path::Code(_) => None,
+    }
+}
+
+/// A state view which records the resources read through it, in the order of first access.
+struct ReadRecordingStateView {
+    state_view: DebuggerStateView,
+    /// Keys read so far, in the order of their first read.
+    reads: Mutex<IndexSet<StateKey>>,
+}
+
+impl ReadRecordingStateView {
+    fn new(state_view: DebuggerStateView) -> Self {
+        Self {
+            state_view,
+            reads: Mutex::new(IndexSet::new()),
+        }
+    }
+
+    fn clear_reads(&self) {
+        self.reads.lock().unwrap().clear();
+    }
+
+    fn resources_read(&self) -> Vec<TraceResource> {
+        self.reads
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(trace_resource)
+            .collect()
+    }
+}
+
+impl TStateView for ReadRecordingStateView {
+    type Key = StateKey;
+
+    fn id(&self) -> StateViewId {
+        self.state_view.id()
+    }
+
+    fn get_state_slot(&self, state_key: &StateKey) -> StateViewResult<StateSlot> {
+        let mut reads = self.reads.lock().unwrap();
+        if !reads.contains(state_key) {
+            reads.insert(state_key.clone());
+        }
+        drop(reads);
+        self.state_view.get_state_slot(state_key)
+    }
+
+    fn get_usage(&self) -> StateViewResult<StateStorageUsage> {
+        self.state_view.get_usage()
+    }
+
+    fn next_version(&self) -> Version {
+        self.state_view.next_version()
+    }
+}
+
 fn print_transaction_stats(sig_verified_txns: &[SignatureVerifiedTransaction], version: u64) {
     let transaction_types = sig_verified_txns
         .iter()
@@ -473,7 +713,7 @@ fn is_reconfiguration(vm_output: &TransactionOutput) -> bool {
 
 fn execute_block_no_limit(
     txn_provider: &DefaultTxnProvider<SignatureVerifiedTransaction, AuxiliaryInfo>,
-    state_view: &DebuggerStateView,
+    state_view: &(impl StateView + Sync),
     concurrency_level: usize,
 ) -> Result<Vec<TransactionOutput>, VMStatus> {
     let executor = AptosVMBlockExecutor::new();
@@ -8,11 +8,10 @@ use aptos_types::{
     contract_event::ContractEvent,
     fee_statement::FeeStatement,
     state_store::state_key::StateKey,
-    transaction::{ExecutionStatus, TransactionOutput},
+    transaction::{ExecutionStatus, TransactionOutput, TransactionStatus},
     write_set::{TransactionWrite, WriteOp, WriteSet, TOTAL_SUPPLY_STATE_KEY},
     AptosCoinType,
 };
-use claims::assert_ok;
 use move_core_types::{
     account_address::AccountAddress, language_storage::TypeTag, move_resource::MoveStructType,
 };
@@ -20,7 +19,7 @@ use std::{collections::BTreeMap, str::FromStr};
 
 /// Different parts of [TransactionOutput] that can be different:
 ///   1. gas used,
-///   2. status (must be kept since transactions are replayed),
+///   2. status (kept, or discarded if the state was overridden),
 ///   3. events,
 ///   4. writes.
 /// Note that fine-grained comparison allows for some differences to be okay, e.g., using more gas
@@ -36,6 +35,10 @@ enum Diff {
         left: ExecutionStatus,
         right: ExecutionStatus,
     },
+    TransactionStatus {
+        left: TransactionStatus,
+        right: TransactionStatus,
+    },
     Event {
         left: Option<ContractEvent>,
         right: Option<ContractEvent>,
@@ -48,16 +51,16 @@ enum Diff {
 }
 
 /// Holds all differences for a pair of transaction outputs.
-pub(crate) struct TransactionDiff {
+pub struct TransactionDiff {
     diffs: Vec<Diff>,
 }
 
 impl TransactionDiff {
-    pub(crate) fn is_empty(&self) -> bool {
+    pub fn is_empty(&self) -> bool {
         self.diffs.is_empty()
     }
 
-    pub(crate) fn println(&self) {
+    pub fn println(&self) {
         if self.is_empty() {
             return;
         }
@@ -77,6 +80,11 @@ impl TransactionDiff {
                     println!("{}", "========".yellow());
                     println!("{}", format!("execution_status: {:?}", right).red());
                 },
+                Diff::TransactionStatus { left, right } => {
+                    println!("{}", format!("status: {:?}", left).green());
+                    println!("{}", "========".yellow());
+                    println!("{}", format!("status: {:?}", right).red());
+                },
                 Diff::Event { left, right } => {
                     let left = left.as_ref();
                     let right = right.as_ref();
@@ -143,7 +151,7 @@ impl TransactionDiff {
 
 /// Builds [TransactionDiff]s for transaction outputs. The builder can be configured to ignore the
 /// differences in outputs sometimes.
-pub(crate) struct TransactionDiffBuilder {
+pub struct TransactionDiffBuilder {
     /// If true, differences related to the gas usage are ignored. These include:
     ///   - total gas used is not compared,
     ///   - `EmitFeeStatement` event is not compared,
@@ -153,7 +161,7 @@ pub(crate) struct TransactionDiffBuilder {
 }
 
 impl TransactionDiffBuilder {
-    pub(crate) fn new(allow_different_gas_usage: bool) -> Self {
+    pub fn new(allow_different_gas_usage: bool) -> Self {
         Self {
             allow_different_gas_usage,
         }
@@ -161,7 +169,7 @@ impl TransactionDiffBuilder {
 
     /// Given a pair of transaction outputs, computes its [TransactionDiff] that includes the gas
     /// used, execution status, events and write sets.
-    pub(crate) fn build_from_outputs(
+    pub fn build_from_outputs(
         &self,
         left: TransactionOutput,
         right: TransactionOutput,
@@ -174,14 +182,28 @@ impl TransactionDiffBuilder {
 
         let mut diffs = vec![];
 
-        // All statuses must be kept, since we are replaying transactions.
-        let left_execution_status = assert_ok!(left_transaction_status.as_kept_status());
-        let right_execution_status = assert_ok!(right_transaction_status.as_kept_status());
-        if left_execution_status != right_execution_status {
-            diffs.push(Diff::ExecutionStatus {
-                left: left_execution_status,
-                right: right_execution_status,
-            });
+        // Statuses of replayed transactions are kept, unless the state was overridden in a way
+        // that makes the transaction invalid.
+        match (
+            left_transaction_status.as_kept_status(),
+            right_transaction_status.as_kept_status(),
+        ) {
+            (Ok(left_execution_status), Ok(right_execution_status)) => {
+                if left_execution_status != right_execution_status {
+                    diffs.push(Diff::ExecutionStatus {
+                        left: left_execution_status,
+                        right: right_execution_status,
+                    });
+                }
+            },
+            _ => {
+                if left_transaction_status != right_transaction_status {
+                    diffs.push(Diff::TransactionStatus {
+                        left: left_transaction_status,
+                        right: right_transaction_status,
+                    });
+                }
+            },
         }
 
         if left_gas_used != right_gas_used && !self.allow_different_gas_usage {
@@ -335,11 +357,10 @@ where
 mod tests {
     use super::*;
     use aptos_types::{
-        on_chain_config::CurrentTimeMicroseconds,
-        state_store::state_value::StateValueMetadata,
-        transaction::{TransactionAuxiliaryData, TransactionStatus},
-        write_set::WriteSetMut,
+        on_chain_config::CurrentTimeMicroseconds, state_store::state_value::StateValueMetadata,
+        transaction::TransactionAuxiliaryData, write_set::WriteSetMut,
     };
+    use move_core_types::vm_status::StatusCode;
 
     #[test]
     fn test_diff_gas_used() {
@@ -402,6 +423,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_diff_discarded_status() {
+        let output_1 = TransactionOutput::new(
+            WriteSet::new(vec![]).unwrap(),
+            vec![],
+            1,
+            TransactionStatus::Keep(ExecutionStatus::Success),
+            TransactionAuxiliaryData::None,
+        );
+        let output_2 = TransactionOutput::new(
+            WriteSet::new(vec![]).unwrap(),
+            vec![],
+            1,
+            TransactionStatus::Discard(StatusCode::SEQUENCE_NUMBER_TOO_OLD),
+            TransactionAuxiliaryData::None,
+        );
+
+        let diff = TransactionDiffBuilder::new(false).build_from_outputs(output_1, output_2, None);
+        assert_eq!(diff.diffs.len(), 1);
+        assert!(
+            diff.diffs[0].clone()
+                == Diff::TransactionStatus {
+                    left: TransactionStatus::Keep(ExecutionStatus::Success),
+                    right: TransactionStatus::Discard(StatusCode::SEQUENCE_NUMBER_TOO_OLD)
+                }
+        );
+    }
+
     #[test]
     fn test_diff_events() {
         let events_1 = vec![
@@ -2,9 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{aptos_debugger::AptosDebugger, common::Opts};
+#[cfg(feature = "debug-adapter")]
+use anyhow::bail;
 use anyhow::Result;
 use aptos_rest_client::Client;
 use clap::Parser;
+#[cfg(feature = "debug-adapter")]
+use move_debug_adapter::{DebugServer, SourceIndex};
+#[cfg(feature = "debug-adapter")]
+use move_vm_runtime::tracing;
 use url::Url;
 
 #[derive(Parser)]
@@ -26,6 +32,18 @@ pub struct Command {
 
     #[clap(long)]
     use_same_block_boundaries: bool,
+
+    /// Serve the Debug Adapter Protocol on this address, e.g. `127.0.0.1:4711`, and replay
+    /// the transactions under the control of the debugger which connects to it.
+    ///
+    /// Source maps of on-chain modules are not available, so frames are shown at the bytecode
+    /// level and source breakpoints are rejected. Use `stopOnEntry` in the launch configuration
+    /// to stop at the first instruction.
+    /// Requires a concurrency level of 1. Only available if built with the `debug-adapter`
+    /// feature.
+    #[cfg(feature = "debug-adapter")]
+    #[clap(long)]
+    debug_adapter: Option<String>,
 }
 
 impl Command {
@@ -38,6 +56,20 @@ impl Command {
             unreachable!("Must provide one target.");
         };
 
+        #[cfg(feature = "debug-adapter")]
+        let debug_server = match &self.debug_adapter {
+            Some(addr) => {
+                if self.opts.concurrency_level != [1] {
+                    bail!("Debugging requires a concurrency level of 1");
+                }
+                println!("Waiting for a debugger to connect on {}", addr);
+                let server = DebugServer::listen(addr.as_str(), SourceIndex::new())?;
+                tracing::set_debugger(Some(server.debugger()));
+                Some(server)
+            },
+            None => None,
+        };
+
         let result = debugger
             .execute_past_transactions(
                 self.begin_version,
@@ -46,7 +78,13 @@ impl Command {
                 self.repeat_execution_times.unwrap_or(1),
                 &self.opts.concurrency_level,
             )
-            .await?;
+            .await;
+        #[cfg(feature = "debug-adapter")]
+        if let Some(server) = debug_server {
+            tracing::set_debugger(None);
+            server.terminate(if result.is_ok() { 0 } else { 1 });
+        }
+        let result = result?;
 
         if !self.skip_result {
             println!("{result:#?}",);
@@ -4,5 +4,8 @@
 pub mod aptos_debugger;
 pub mod bcs_txn_decoder;
 pub mod common;
+pub mod diff;
 pub mod execute_past_transactions;
 pub mod execute_pending_block;
+pub mod overrides;
+pub mod replay_with_overrides;
@@ -0,0 +1,583 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Defines different overrides for on-chain state used for replay and benchmarking. With
+//! overrides, past transactions can be replayed on top of a modified state, and we can evaluate
+//! how it impacts performance, or test a fix against the transactions which triggered a bug.
+//! Supported overrides include:
+//!   1. enabling feature flags,
+//!   2. disabling feature flags,
+//!   3. overriding gas feature version,
+//!   4. changing modules (bytecode, metadata, etc.) and package information,
+//!   5. replacing the values of resources.
+
+use anyhow::{anyhow, bail, Context};
+use aptos_framework::{natives::code::PackageRegistry, BuildOptions, BuiltPackage};
+use aptos_gas_schedule::LATEST_GAS_FEATURE_VERSION;
+use aptos_logger::{error, warn};
+use aptos_types::{
+    account_address::AccountAddress,
+    on_chain_config::{FeatureFlag, Features, GasScheduleV2, OnChainConfig},
+    state_store::{
+        state_key::StateKey, state_slot::StateSlot, state_storage_usage::StateStorageUsage,
+        state_value::StateValue, StateView, StateViewId, StateViewResult, TStateView,
+    },
+    transaction::Version,
+};
+use move_core_types::language_storage::StructTag;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::PathBuf,
+    str::FromStr,
+};
+
+/// Stores information about compiled Move packages and the build options used to create them. Used
+/// by the override configuration to shadow existing on-chain modules with modules defined in these
+/// packages.
+struct PackageOverride {
+    packages: Vec<BuiltPackage>,
+    build_options: BuildOptions,
+}
+
+impl PackageOverride {
+    /// Uses the provided build options to build multiple packages from the specified paths.
+    fn new(package_paths: Vec<String>, build_options: BuildOptions) -> anyhow::Result<Self> {
+        let packages = package_paths
+            .into_iter()
+            .map(|path| BuiltPackage::build(PathBuf::from(&path), build_options.clone()))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            packages,
+            build_options,
+        })
+    }
+}
+
+/// A resource whose value is replaced, specified as `<ADDRESS>/<RESOURCE_TYPE>=<FILE>`, or as
+/// `<ADDRESS>/<GROUP_TYPE>/<RESOURCE_TYPE>=<FILE>` for a member of a resource group. The file
+/// contains the BCS-encoded value of the resource.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResourceOverride {
+    pub address: AccountAddress,
+    /// The resource group containing the resource, if any.
+    pub group: Option<StructTag>,
+    pub resource: StructTag,
+    pub value_file: PathBuf,
+}
+
+impl FromStr for ResourceOverride {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let malformed = || {
+            anyhow!(
+                "Malformed resource override '{}', expected <ADDRESS>/[<GROUP_TYPE>/]<RESOURCE_TYPE>=<FILE>",
+                s
+            )
+        };
+        let (location, value_file) = s.split_once('=').ok_or_else(malformed)?;
+        let mut parts = location.split('/');
+        let address = AccountAddress::from_str(parts.next().ok_or_else(malformed)?)?;
+        let mut tags = parts
+            .map(StructTag::from_str)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let resource = tags.pop().ok_or_else(malformed)?;
+        let group = tags.pop();
+        if !tags.is_empty() || value_file.is_empty() {
+            return Err(malformed());
+        }
+        Ok(Self {
+            address,
+            group,
+            resource,
+            value_file: PathBuf::from(value_file),
+        })
+    }
+}
+
+/// Stores all state overrides.
+pub struct OverrideConfig {
+    /// Feature flags to enable. Invariant: does not overlap with disabled features.
+    additional_enabled_features: Vec<FeatureFlag>,
+    /// Feature flags to disable. Invariant: does not overlap with enabled features.
+    additional_disabled_features: Vec<FeatureFlag>,
+    /// Gas feature version to use. Invariant: must be at most the latest version.
+    gas_feature_version: Option<u64>,
+    /// Information about overridden packages.
+    package_override: PackageOverride,
+    /// Resources to replace, with their new BCS-encoded values.
+    resource_overrides: Vec<(ResourceOverride, Vec<u8>)>,
+}
+
+impl OverrideConfig {
+    pub fn new(
+        additional_enabled_features: Vec<FeatureFlag>,
+        additional_disabled_features: Vec<FeatureFlag>,
+        gas_feature_version: Option<u64>,
+        override_packages: Vec<String>,
+        resource_overrides: Vec<ResourceOverride>,
+    ) -> anyhow::Result<Self> {
+        let build_options = BuildOptions::move_2();
+        let package_override = PackageOverride::new(override_packages, build_options)?;
+
+        if !additional_enabled_features
+            .iter()
+            .all(|f| !additional_disabled_features.contains(f))
+        {
+            bail!("Enabled and disabled feature flags cannot overlap")
+        }
+        if matches!(gas_feature_version, Some(v) if v > LATEST_GAS_FEATURE_VERSION) {
+            warn!(
+                "Gas feature version is greater than the latest one: {}",
+                LATEST_GAS_FEATURE_VERSION
+            );
+        }
+
+        let resource_overrides = resource_overrides
+            .into_iter()
+            .map(|resource_override| {
+                let bytes = std::fs::read(&resource_override.value_file).map_err(|err| {
+                    anyhow!(
+                        "Failed to read value of {} from {}: {}",
+                        resource_override.resource.to_canonical_string(),
+                        resource_override.value_file.display(),
+                        err
+                    )
+                })?;
+                Ok((resource_override, bytes))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            additional_enabled_features,
+            additional_disabled_features,
+            gas_feature_version,
+            package_override,
+            resource_overrides,
+        })
+    }
+
+    /// Returns the overridden state values. Fails if a resource override cannot be applied, e.g.,
+    /// if the resource group it patches is malformed.
+    pub fn get_state_override(
+        &self,
+        state_view: &impl StateView,
+    ) -> anyhow::Result<HashMap<StateKey, StateValue>> {
+        let mut state_override = HashMap::new();
+
+        // Enable/disable features.
+        if !self.additional_enabled_features.is_empty()
+            || !self.additional_disabled_features.is_empty()
+        {
+            let (features_state_key, features_state_value) =
+                config_override::<Features, _>(state_view, |features| {
+                    for feature in &self.additional_enabled_features {
+                        if features.is_enabled(*feature) {
+                            error!("Feature {:?} is already enabled", feature);
+                        }
+                        features.enable(*feature);
+                    }
+                    for feature in &self.additional_disabled_features {
+                        if !features.is_enabled(*feature) {
+                            error!("Feature {:?} is already disabled", feature);
+                        }
+                        features.disable(*feature);
+                    }
+                });
+            state_override.insert(features_state_key, features_state_value);
+        }
+
+        // Gas feature override.
+        if let Some(gas_feature_version) = self.gas_feature_version {
+            // Only support V2 gas schedule which has gas feature versions. Otherwise, V1 has 0
+            // version at all times, and most likely it has been so long ago we will not replay
+            // these transactions.
+            let (gas_schedule_state_key, gas_schedule_state_value) =
+                config_override::<GasScheduleV2, _>(state_view, |gas_schedule| {
+                    gas_schedule.feature_version = gas_feature_version;
+                });
+            state_override.insert(gas_schedule_state_key, gas_schedule_state_value);
+        }
+
+        // Override packages.
+        let mut overridden_package_registries = HashMap::new();
+        for package in &self.package_override.packages {
+            // Modify existing package metadata or add new one.
+            let addresses = package
+                .modules()
+                .map(|m| *m.self_addr())
+                .collect::<BTreeSet<_>>();
+            assert_eq!(
+                addresses.len(),
+                1,
+                "Modules in the same package must have the same address"
+            );
+
+            let package_address = addresses
+                .last()
+                .expect("Package must contain at least one module");
+            let package_registry_state_key =
+                StateKey::resource(package_address, &PackageRegistry::struct_tag())
+                    .expect("Should always be able to create state key for package registry");
+
+            let old_package_registry_state_value =
+                match overridden_package_registries.remove(&package_registry_state_key) {
+                    Some(state_value) => state_value,
+                    None => state_view
+                        .get_state_value(&package_registry_state_key)
+                        .unwrap_or_else(|err| {
+                            panic!(
+                                "Failed to fetch package registry at {}: {:?}",
+                                package_address, err
+                            )
+                        })
+                        .expect("Package registry for override must always exist"),
+                };
+
+            let metadata = package.extract_metadata().unwrap_or_else(|err| {
+                panic!(
+                    "Failed to extract metadata for package {}: {:?}",
+                    package.name(),
+                    err
+                )
+            });
+            let new_package_registry_state_value = old_package_registry_state_value
+                .map_bytes(|bytes| {
+                    let mut package_registry = bcs::from_bytes::<PackageRegistry>(&bytes)
+                        .expect("Package registry should deserialize");
+
+                    let mut metadata_idx = None;
+                    for (idx, package_metadata) in package_registry.packages.iter().enumerate() {
+                        if package_metadata.name == metadata.name {
+                            metadata_idx = Some(idx);
+                            break;
+                        }
+                    }
+                    match metadata_idx {
+                        Some(idx) => {
+                            package_registry.packages[idx] = metadata;
+                        },
+                        None => {
+                            package_registry.packages.push(metadata);
+                        },
+                    }
+
+                    let bytes = bcs::to_bytes(&package_registry)
+                        .expect("Package registry should serialize");
+                    Ok(bytes.into())
+                })
+                .expect("Modifying package never returns an error");
+
+            overridden_package_registries
+                .insert(package_registry_state_key, new_package_registry_state_value);
+
+            // Modify all existing modules or add new ones.
+            let bytecode_version = self.package_override.build_options.bytecode_version;
+            for module in package.modules() {
+                let mut module_bytes = vec![];
+                module
+                    .serialize_for_version(bytecode_version, &mut module_bytes)
+                    .unwrap_or_else(|err| {
+                        panic!(
+                            "Failed to serialize module {}::{}: {:?}",
+                            module.self_addr(),
+                            module.self_name(),
+                            err
+                        )
+                    });
+
+                let state_key = StateKey::module(module.self_addr(), module.self_name());
+                let onchain_state_value =
+                    state_view
+                        .get_state_value(&state_key)
+                        .unwrap_or_else(|err| {
+                            panic!(
+                                "Failed to fetch module {}::{}: {:?}",
+                                module.self_addr(),
+                                module.self_name(),
+                                err
+                            )
+                        });
+                let state_value = match onchain_state_value {
+                    Some(state_value) => {
+                        state_value.map_bytes(|_| Ok(module_bytes.into())).unwrap()
+                    },
+
+                    None => StateValue::new_legacy(module_bytes.into()),
+                };
+                if state_override.insert(state_key, state_value).is_some() {
+                    panic!(
+                        "Overriding module {}::{} more than once",
+                        module.self_addr(),
+                        module.self_name()
+                    );
+                }
+            }
+        }
+        state_override.extend(overridden_package_registries);
+
+        // Replace resources. Members of resource groups are patched in the group, which may
+        // already have been overridden.
+        for (resource_override, bytes) in &self.resource_overrides {
+            let address = &resource_override.address;
+            let resource = resource_override.resource.to_canonical_string();
+            match &resource_override.group {
+                None => {
+                    let state_key = StateKey::resource(address, &resource_override.resource)
+                        .with_context(|| {
+                            format!("Failed to create state key for {} at {}", resource, address)
+                        })?;
+                    let state_value =
+                        match state_override_base(&state_override, state_view, &state_key)? {
+                            Some(state_value) => {
+                                state_value.map_bytes(|_| Ok(bytes.clone().into()))?
+                            },
+                            None => StateValue::new_legacy(bytes.clone().into()),
+                        };
+                    state_override.insert(state_key, state_value);
+                },
+                Some(group) => {
+                    let state_key = StateKey::resource_group(address, group);
+                    let patch_group = |group_bytes: &[u8]| -> anyhow::Result<Vec<u8>> {
+                        let mut members = if group_bytes.is_empty() {
+                            BTreeMap::new()
+                        } else {
+                            bcs::from_bytes::<BTreeMap<StructTag, Vec<u8>>>(group_bytes)
+                                .with_context(|| {
+                                    format!(
+                                        "Failed to deserialize resource group {} at {} to override {}",
+                                        group.to_canonical_string(),
+                                        address,
+                                        resource
+                                    )
+                                })?
+                        };
+                        members.insert(resource_override.resource.clone(), bytes.clone());
+                        Ok(bcs::to_bytes(&members)?)
+                    };
+                    let state_value =
+                        match state_override_base(&state_override, state_view, &state_key)? {
+                            Some(state_value) => state_value
+                                .map_bytes(|group_bytes| Ok(patch_group(&group_bytes)?.into()))?,
+                            None => StateValue::new_legacy(patch_group(&[])?.into()),
+                        };
+                    state_override.insert(state_key, state_value);
+                },
+            }
+        }
+
+        Ok(state_override)
+    }
+}
+
+/// A state view which returns overridden values for some state keys, and otherwise falls back to
+/// the underlying state view.
+pub struct OverriddenStateView<'s, S> {
+    state_view: &'s S,
+    state_override: HashMap<StateKey, StateValue>,
+}
+
+impl<'s, S: StateView> OverriddenStateView<'s, S> {
+    pub fn new(state_view: &'s S, state_override: HashMap<StateKey, StateValue>) -> Self {
+        Self {
+            state_view,
+            state_override,
+        }
+    }
+}
+
+impl<S: StateView> TStateView for OverriddenStateView<'_, S> {
+    type Key = StateKey;
+
+    fn id(&self) -> StateViewId {
+        self.state_view.id()
+    }
+
+    fn get_state_slot(&self, state_key: &StateKey) -> StateViewResult<StateSlot> {
+        match self.state_override.get(state_key) {
+            Some(state_value) => Ok(StateSlot::ColdOccupied {
+                value_version: 0,
+                value: state_value.clone(),
+            }),
+            None => self.state_view.get_state_slot(state_key),
+        }
+    }
+
+    fn get_usage(&self) -> StateViewResult<StateStorageUsage> {
+        self.state_view.get_usage()
+    }
+
+    fn next_version(&self) -> Version {
+        self.state_view.next_version()
+    }
+}
+
+/// Returns the value to apply an override to: the value from an earlier override of the same
+/// state key, or the on-chain value.
+fn state_override_base(
+    state_override: &HashMap<StateKey, StateValue>,
+    state_view: &impl StateView,
+    state_key: &StateKey,
+) -> anyhow::Result<Option<StateValue>> {
+    match state_override.get(state_key) {
+        Some(state_value) => Ok(Some(state_value.clone())),
+        None => state_view
+            .get_state_value(state_key)
+            .with_context(|| format!("Failed to fetch {:?}", state_key)),
+    }
+}
+
+/// Returns the state key for on-chain config type.
+fn config_state_key<T: OnChainConfig>() -> StateKey {
+    StateKey::resource(T::address(), &T::struct_tag())
+        .expect("Constructing state key for on-chain config must succeed")
+}
+
+/// Fetches the config from the storage, and modifies it based on the passed function. Panics if
+/// there is a storage error, config does not exist or fails to (de-)serialize.
+fn config_override<T: OnChainConfig + Serialize, F: FnOnce(&mut T)>(
+    state_view: &impl StateView,
+    override_func: F,
+) -> (StateKey, StateValue) {
+    let state_key = config_state_key::<T>();
+    let state_value = state_view
+        .get_state_value(&state_key)
+        .unwrap_or_else(|err| {
+            panic!(
+                "Failed to fetch on-chain config for {:?}: {:?}",
+                state_key, err
+            )
+        })
+        .unwrap_or_else(|| panic!("On-chain config for {:?} must always exist", state_key));
+
+    let mut config = T::deserialize_into_config(state_value.bytes())
+        .expect("On-chain config must be deserializable");
+    override_func(&mut config);
+    let config_bytes = bcs::to_bytes(&config).expect("On-chain config must be serializable");
+
+    let new_state_value = state_value.map_bytes(|_| Ok(config_bytes.into())).unwrap();
+    (state_key, new_state_value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_types::state_store::MockStateView;
+
+    fn resource_override(dir: &std::path::Path, spec: &str, value: &[u8]) -> ResourceOverride {
+        // Numbered in the order of creation, so every override gets its own file.
+        let index = std::fs::read_dir(dir).unwrap().count();
+        let file = dir.join(format!("{}.bcs", index));
+        std::fs::write(&file, value).unwrap();
+        ResourceOverride::from_str(&format!("{}={}", spec, file.display())).unwrap()
+    }
+
+    fn override_config(resource_overrides: Vec<ResourceOverride>) -> OverrideConfig {
+        OverrideConfig::new(vec![], vec![], None, vec![], resource_overrides).unwrap()
+    }
+
+    #[test]
+    fn test_parse_resource_override() {
+        let resource_override =
+            ResourceOverride::from_str("0xcafe/0x1::account::Account=account.bcs").unwrap();
+        assert_eq!(
+            resource_override.address,
+            AccountAddress::from_str("0xcafe").unwrap()
+        );
+        assert_eq!(resource_override.group, None);
+        assert_eq!(
+            resource_override.resource,
+            StructTag::from_str("0x1::account::Account").unwrap()
+        );
+        assert_eq!(resource_override.value_file, PathBuf::from("account.bcs"));
+
+        let resource_override = ResourceOverride::from_str(
+            "0xa/0x1::object::ObjectGroup/0x1::fungible_asset::FungibleStore=store.bcs",
+        )
+        .unwrap();
+        assert_eq!(
+            resource_override.group,
+            Some(StructTag::from_str("0x1::object::ObjectGroup").unwrap())
+        );
+
+        assert!(ResourceOverride::from_str("0xcafe/0x1::account::Account").is_err());
+        assert!(ResourceOverride::from_str("0xcafe=account.bcs").is_err());
+        assert!(ResourceOverride::from_str("0xcafe/0x1::a::A/0x1::b::B/0x1::c::C=c.bcs").is_err());
+    }
+
+    #[test]
+    fn test_replay_with_resource_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = AccountAddress::from_str("0xcafe").unwrap();
+        let resource = StructTag::from_str("0x1::account::Account").unwrap();
+        let group = StructTag::from_str("0x1::object::ObjectGroup").unwrap();
+        let object = StructTag::from_str("0x1::object::ObjectCore").unwrap();
+        let store = StructTag::from_str("0x1::fungible_asset::FungibleStore").unwrap();
+
+        let resource_key = StateKey::resource(&address, &resource).unwrap();
+        let group_key = StateKey::resource_group(&address, &group);
+        let on_chain_group = BTreeMap::from([(object.clone(), vec![1]), (store.clone(), vec![2])]);
+        let state_view = MockStateView::new(HashMap::from([
+            (resource_key.clone(), StateValue::new_legacy(vec![0].into())),
+            (
+                group_key.clone(),
+                StateValue::new_legacy(bcs::to_bytes(&on_chain_group).unwrap().into()),
+            ),
+        ]));
+
+        let config = override_config(vec![
+            resource_override(dir.path(), "0xcafe/0x1::account::Account", &[3]),
+            resource_override(
+                dir.path(),
+                "0xcafe/0x1::object::ObjectGroup/0x1::fungible_asset::FungibleStore",
+                &[4, 4],
+            ),
+        ]);
+        let state_override = config.get_state_override(&state_view).unwrap();
+        let overridden_state_view = OverriddenStateView::new(&state_view, state_override);
+
+        // The resource is replaced, and only the overridden member of the group changes.
+        assert_eq!(
+            overridden_state_view
+                .get_state_value_bytes(&resource_key)
+                .unwrap()
+                .unwrap()
+                .to_vec(),
+            vec![3]
+        );
+        let group_bytes = overridden_state_view
+            .get_state_value_bytes(&group_key)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            bcs::from_bytes::<BTreeMap<StructTag, Vec<u8>>>(&group_bytes).unwrap(),
+            BTreeMap::from([(object, vec![1]), (store, vec![4, 4])])
+        );
+        // Other state is read from the underlying state view.
+        let other_key = StateKey::resource(&address, &group).unwrap();
+        assert!(overridden_state_view
+            .get_state_value(&other_key)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_malformed_resource_group_override() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = AccountAddress::from_str("0xcafe").unwrap();
+        let group = StructTag::from_str("0x1::object::ObjectGroup").unwrap();
+        let state_view = MockStateView::new(HashMap::from([(
+            StateKey::resource_group(&address, &group),
+            StateValue::new_legacy(vec![0xFF, 0xFF].into()),
+        )]));
+
+        let config = override_config(vec![resource_override(
+            dir.path(),
+            "0xcafe/0x1::object::ObjectGroup/0x1::fungible_asset::FungibleStore",
+            &[1],
+        )]);
+        let err = config.get_state_override(&state_view).unwrap_err();
+        assert!(format!("{:#}", err).contains("Failed to deserialize resource group"));
+    }
+}
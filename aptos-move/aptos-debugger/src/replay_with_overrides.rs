@@ -0,0 +1,122 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    aptos_debugger::AptosDebugger,
+    common::Opts,
+    diff::TransactionDiffBuilder,
+    overrides::{OverrideConfig, ResourceOverride},
+};
+use anyhow::{bail, Result};
+use aptos_rest_client::Client;
+use aptos_types::{on_chain_config::FeatureFlag, transaction::Version};
+use clap::Parser;
+use url::Url;
+
+/// Replays committed transactions on top of the historical state with modules, resources or
+/// feature flags overridden, and reports how the outputs differ from the original ones.
+#[derive(Parser)]
+pub struct Command {
+    #[clap(flatten)]
+    opts: Opts,
+
+    #[clap(long)]
+    begin_version: u64,
+
+    #[clap(long, default_value_t = 1)]
+    limit: u64,
+
+    /// List of space-separated feature flags to enable, in capital letters, e.g.
+    /// GAS_PAYER_ENABLED.
+    #[clap(long, num_args = 1.., value_delimiter = ' ')]
+    enable_features: Vec<FeatureFlag>,
+
+    /// List of space-separated feature flags to disable, in capital letters, e.g.
+    /// GAS_PAYER_ENABLED.
+    #[clap(long, num_args = 1.., value_delimiter = ' ')]
+    disable_features: Vec<FeatureFlag>,
+
+    /// If set, overrides the gas feature version used by the gas schedule.
+    #[clap(long)]
+    gas_feature_version: Option<u64>,
+
+    /// List of space-separated paths to Move packages, whose modules replace the on-chain ones.
+    #[clap(long, num_args = 1.., value_delimiter = ' ')]
+    override_packages: Vec<String>,
+
+    /// List of space-separated resources to replace, each given as
+    /// `<ADDRESS>/<RESOURCE_TYPE>=<FILE>`, or as `<ADDRESS>/<GROUP_TYPE>/<RESOURCE_TYPE>=<FILE>`
+    /// for a member of a resource group. The file contains the BCS-encoded value.
+    #[clap(long, num_args = 1.., value_delimiter = ' ')]
+    override_resources: Vec<ResourceOverride>,
+
+    /// If set, differences caused by different gas usage are ignored: the gas used, the fee
+    /// statement, the fee payer's balance and the total supply.
+    #[clap(long)]
+    allow_different_gas_usage: bool,
+}
+
+impl Command {
+    pub async fn run(self) -> Result<()> {
+        let debugger = if let Some(rest_endpoint) = self.opts.target.rest_endpoint {
+            AptosDebugger::rest_client(Client::new(Url::parse(&rest_endpoint)?))?
+        } else if let Some(db_path) = self.opts.target.db_path {
+            AptosDebugger::db(db_path)?
+        } else {
+            unreachable!("Must provide one target.");
+        };
+        let &[concurrency_level] = self.opts.concurrency_level.as_slice() else {
+            bail!("Replay with overrides requires a single concurrency level");
+        };
+
+        let override_config = OverrideConfig::new(
+            self.enable_features,
+            self.disable_features,
+            self.gas_feature_version,
+            self.override_packages,
+            self.override_resources,
+        )?;
+        let outputs = debugger
+            .execute_past_transactions_with_overrides(
+                self.begin_version,
+                self.limit,
+                &override_config,
+                concurrency_level,
+            )
+            .await?;
+
+        let diff_builder = TransactionDiffBuilder::new(self.allow_different_gas_usage);
+        let mut diffs = vec![];
+        println!("version, original (gas), with overrides (gas)");
+        for (idx, (txn, original_output, overridden_output)) in outputs.into_iter().enumerate() {
+            let version = self.begin_version + idx as Version;
+            println!(
+                "{}, {}, {}",
+                version,
+                original_output.gas_used(),
+                overridden_output.gas_used()
+            );
+
+            let fee_payer = txn.try_as_signed_user_txn().map(|txn| {
+                txn.authenticator_ref()
+                    .fee_payer_address()
+                    .unwrap_or_else(|| txn.sender())
+            });
+            let diff =
+                diff_builder.build_from_outputs(original_output, overridden_output, fee_payer);
+            if !diff.is_empty() {
+                diffs.push((version, diff));
+            }
+        }
+
+        if diffs.is_empty() {
+            println!("Outputs with overrides are the same as the original ones");
+        }
+        for (version, diff) in diffs {
+            println!("Non-empty output diff for transaction {}:", version);
+            diff.println();
+        }
+
+        Ok(())
+    }
+}
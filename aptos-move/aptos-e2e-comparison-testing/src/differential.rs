@@ -0,0 +1,779 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Differential execution of the same transactions under two VM configurations. Transactions are
+//! read from a local AptosDB, generated on top of its state, or read from a file, and executed on
+//! top of the historical state twice: once with the baseline configuration, and once with the
+//! candidate one. Any difference in outputs, gas or error status is reported, and the smallest
+//! block of transactions still reproducing it is saved so that it can be replayed on its own.
+//!
+//! A configuration is expressed in terms of on-chain state (feature flags, gas feature version and
+//! framework or package code) and process-wide VM settings (paranoid type and reference checks,
+//! layout caches), because this is what [VMConfig] is derived from. The process-wide settings of a
+//! side are applied before each of its executions, and every execution uses a new block executor
+//! so that no caches are shared between the sides.
+//!
+//! [VMConfig]: move_vm_runtime::config::VMConfig
+
+use anyhow::{anyhow, bail, Result};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey};
+use aptos_move_debugger::{
+    aptos_debugger::AptosDebugger, diff::TransactionDiffBuilder, overrides::OverrideConfig,
+};
+use aptos_transaction_simulation::Account;
+use aptos_types::{
+    account_config::AccountResource,
+    chain_id::ChainId,
+    on_chain_config::{FeatureFlag, OnChainConfig},
+    state_store::MoveResourceExt,
+    transaction::{PersistedAuxiliaryInfo, Transaction, TransactionOutput, Version},
+    vm_status::VMStatus,
+};
+use aptos_vm_environment::prod_configs::override_vm_flags;
+use clap::ValueEnum;
+use move_core_types::account_address::AccountAddress;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+const REPRODUCERS_DIR: &str = "reproducers";
+const REPRODUCER_BATCH_FILE: &str = "txns.bcs";
+const REPRODUCER_SUMMARY_FILE: &str = "summary.yaml";
+
+/// One side of a differential run, loaded from a YAML file. All fields are optional, and an empty
+/// configuration executes transactions on the unmodified historical state.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExecutionConfig {
+    /// Feature flags to enable, in capital letters, e.g. `ENABLE_FUNCTION_VALUES`.
+    pub enable_features: Vec<String>,
+    /// Feature flags to disable, in capital letters.
+    pub disable_features: Vec<String>,
+    /// Gas feature version to use instead of the on-chain one.
+    pub gas_feature_version: Option<u64>,
+    /// Paths to Move packages whose modules replace the on-chain ones.
+    pub override_packages: Vec<String>,
+    /// Process-wide VM settings.
+    pub vm_settings: VmSettings,
+}
+
+/// Process-wide VM settings, which are not derived from on-chain state. Defaults are the same as
+/// when the settings are not set in production.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VmSettings {
+    /// Whether types are checked at runtime, see `runtime_type_checks.rs`.
+    pub paranoid_type_checks: bool,
+    /// Whether references are checked at runtime, see `runtime_ref_checks.rs`.
+    pub paranoid_ref_checks: bool,
+    /// Whether type layouts are cached in the loader.
+    pub layout_caches: bool,
+}
+
+impl Default for VmSettings {
+    fn default() -> Self {
+        Self {
+            paranoid_type_checks: true,
+            paranoid_ref_checks: false,
+            layout_caches: false,
+        }
+    }
+}
+
+impl VmSettings {
+    fn apply(&self) {
+        override_vm_flags(
+            self.paranoid_type_checks,
+            self.paranoid_ref_checks,
+            self.layout_caches,
+        );
+    }
+}
+
+impl ExecutionConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .map_err(|err| anyhow!("Failed to open config {}: {}", path.display(), err))?;
+        serde_yaml::from_reader(file)
+            .map_err(|err| anyhow!("Failed to parse config {}: {}", path.display(), err))
+    }
+
+    fn to_override_config(&self) -> Result<OverrideConfig> {
+        let parse_features = |features: &[String]| {
+            features
+                .iter()
+                .map(|feature| {
+                    FeatureFlag::from_str(feature)
+                        .map_err(|_| anyhow!("Unknown feature flag {}", feature))
+                })
+                .collect::<Result<Vec<_>>>()
+        };
+        OverrideConfig::new(
+            parse_features(&self.enable_features)?,
+            parse_features(&self.disable_features)?,
+            self.gas_feature_version,
+            self.override_packages.clone(),
+            vec![],
+        )
+    }
+}
+
+/// Transactions executed as a single block on top of the state at the given version.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TransactionBatch {
+    pub version: Version,
+    pub txns: Vec<Transaction>,
+    pub auxiliary_infos: Vec<PersistedAuxiliaryInfo>,
+}
+
+impl TransactionBatch {
+    /// Creates a batch of transactions which were not committed, e.g., generated by a workload.
+    pub fn new_uncommitted(version: Version, txns: Vec<Transaction>) -> Self {
+        let auxiliary_infos = vec![PersistedAuxiliaryInfo::None; txns.len()];
+        Self {
+            version,
+            txns,
+            auxiliary_infos,
+        }
+    }
+
+    /// Returns the sub-batch of transactions in `[begin, end)`, where indices are relative to the
+    /// start of this batch.
+    fn slice(&self, begin: usize, end: usize) -> Self {
+        Self {
+            version: self.version + begin as Version,
+            txns: self.txns[begin..end].to_vec(),
+            auxiliary_infos: self.auxiliary_infos[begin..end].to_vec(),
+        }
+    }
+}
+
+/// Where the transactions to execute come from.
+pub enum TransactionSource {
+    /// Committed transactions in the DB, split into the blocks they were committed in.
+    Committed { begin: Version, limit: u64 },
+    /// Versions of transactions stored as JSON files in a directory, such as the ones from
+    /// `indexer-test-transactions`. Each transaction is replayed alone from the DB, and versions
+    /// the DB does not have are skipped.
+    JsonVersions {
+        dir: PathBuf,
+        begin: Version,
+        limit: u64,
+    },
+    /// Transactions generated on top of the state at the given version, sent by an account whose
+    /// private key is known, e.g., the root account of a local testnet.
+    Generated {
+        version: Version,
+        workload: GeneratedWorkload,
+        num_txns: u64,
+        /// File with the BCS-encoded Ed25519 private key of the sender, e.g., `mint.key`.
+        sender_key: PathBuf,
+        /// Address of the sender, if it is not derived from the key (e.g., `0xa550c18`).
+        sender: Option<AccountAddress>,
+    },
+    /// A BCS-encoded [TransactionBatch], e.g., a saved reproducer.
+    BatchFile { path: PathBuf },
+}
+
+/// Kinds of generated transactions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum GeneratedWorkload {
+    /// Transfers of APT to new accounts, each of which creates the account.
+    AccountCreation,
+    /// Transfers of APT from the sender to itself.
+    SelfTransfer,
+}
+
+/// Generates signed transactions of the workload, starting at the given sequence number of the
+/// sender.
+fn generate_workload(
+    sender: &Account,
+    sequence_number: u64,
+    chain_id: ChainId,
+    workload: GeneratedWorkload,
+    num_txns: u64,
+) -> Vec<Transaction> {
+    (0..num_txns)
+        .map(|idx| {
+            let payload = match workload {
+                GeneratedWorkload::AccountCreation => {
+                    aptos_stdlib::aptos_account_transfer(AccountAddress::random(), 1)
+                },
+                GeneratedWorkload::SelfTransfer => {
+                    aptos_stdlib::aptos_account_transfer(*sender.address(), 1)
+                },
+            };
+            Transaction::UserTransaction(
+                sender
+                    .transaction()
+                    .sequence_number(sequence_number + idx)
+                    .chain_id(chain_id)
+                    .gas_unit_price(100)
+                    .max_gas_amount(100_000)
+                    // Never expires, regardless of the timestamp of the state.
+                    .ttl(u64::MAX)
+                    .payload(payload)
+                    .sign(),
+            )
+        })
+        .collect()
+}
+
+impl TransactionSource {
+    async fn load_batches(&self, debugger: &AptosDebugger) -> Result<Vec<TransactionBatch>> {
+        match self {
+            Self::Committed { begin, limit } => {
+                let (txns, _, auxiliary_infos) =
+                    debugger.get_committed_transactions(*begin, *limit).await?;
+                Ok(split_into_blocks(*begin, txns, auxiliary_infos))
+            },
+            Self::JsonVersions { dir, begin, limit } => {
+                let versions = read_json_versions(dir)?;
+                let mut batches = vec![];
+                for version in versions
+                    .into_iter()
+                    .filter(|version| version >= begin)
+                    .take(*limit as usize)
+                {
+                    match debugger.get_committed_transactions(version, 1).await {
+                        Ok((txns, _, auxiliary_infos)) => batches.push(TransactionBatch {
+                            version,
+                            txns,
+                            auxiliary_infos,
+                        }),
+                        Err(err) => {
+                            println!("Skipping version {} missing from the DB: {}", version, err)
+                        },
+                    }
+                }
+                Ok(batches)
+            },
+            Self::Generated {
+                version,
+                workload,
+                num_txns,
+                sender_key,
+                sender,
+            } => {
+                let key: Ed25519PrivateKey =
+                    bcs::from_bytes(&std::fs::read(sender_key)?).map_err(|err| {
+                        anyhow!("Malformed private key {}: {}", sender_key.display(), err)
+                    })?;
+                let public_key = key.public_key();
+                let mut account = match sender {
+                    Some(address) => Account::new_genesis_account(*address),
+                    None => Account::with_keypair(key.clone(), public_key.clone()),
+                };
+                account.rotate_key(key, public_key);
+
+                let state_view = debugger.state_view_at_version(*version);
+                let sequence_number =
+                    AccountResource::fetch_move_resource(&state_view, account.address())?
+                        .ok_or_else(|| {
+                            anyhow!(
+                                "Sender {} does not exist at version {}",
+                                account.address(),
+                                version
+                            )
+                        })?
+                        .sequence_number();
+                let chain_id = ChainId::fetch_config(&state_view)
+                    .ok_or_else(|| anyhow!("Chain ID does not exist at version {}", version))?;
+                let txns =
+                    generate_workload(&account, sequence_number, chain_id, *workload, *num_txns);
+                Ok(vec![TransactionBatch::new_uncommitted(*version, txns)])
+            },
+            Self::BatchFile { path } => {
+                let bytes = std::fs::read(path)
+                    .map_err(|err| anyhow!("Failed to read {}: {}", path.display(), err))?;
+                Ok(vec![bcs::from_bytes(&bytes)?])
+            },
+        }
+    }
+}
+
+/// Splits committed transactions into the blocks they were committed in, so that a new epoch
+/// always starts a new batch.
+fn split_into_blocks(
+    begin: Version,
+    txns: Vec<Transaction>,
+    auxiliary_infos: Vec<PersistedAuxiliaryInfo>,
+) -> Vec<TransactionBatch> {
+    let mut batches: Vec<TransactionBatch> = vec![];
+    for (idx, (txn, auxiliary_info)) in txns.into_iter().zip(auxiliary_infos).enumerate() {
+        match batches.last_mut() {
+            Some(batch) if !txn.is_block_start() => {
+                batch.txns.push(txn);
+                batch.auxiliary_infos.push(auxiliary_info);
+            },
+            _ => batches.push(TransactionBatch {
+                version: begin + idx as Version,
+                txns: vec![txn],
+                auxiliary_infos: vec![auxiliary_info],
+            }),
+        }
+    }
+    batches
+}
+
+/// Returns the sorted versions of all JSON transactions in the directory and its subdirectories.
+fn read_json_versions(dir: &Path) -> Result<Vec<Version>> {
+    #[derive(Deserialize)]
+    struct JsonTransaction {
+        version: String,
+    }
+
+    let mut versions = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            versions.extend(read_json_versions(&path)?);
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            let txn: JsonTransaction = serde_json::from_reader(File::open(&path)?)
+                .map_err(|err| anyhow!("Malformed transaction {}: {}", path.display(), err))?;
+            versions.push(txn.version.parse()?);
+        }
+    }
+    versions.sort();
+    versions.dedup();
+    Ok(versions)
+}
+
+/// A difference between the executions under the two configurations.
+struct Divergence {
+    /// Version of the first diverging transaction, or of the batch if the block failed.
+    version: Version,
+    /// The smallest batch reproducing the divergence.
+    reproducer: TransactionBatch,
+    baseline: ExecutionResult,
+    candidate: ExecutionResult,
+}
+
+/// Outputs of a block, or the block-level error if the block failed.
+type ExecutionResult = Result<Vec<TransactionOutput>, VMStatus>;
+
+/// Finds the shortest block ending with the diverging transaction at `idx` which still diverges.
+/// The transaction is tried alone first, and preceding transactions from its original block are
+/// added one by one until the divergence reproduces, e.g., if it depends on caches warmed up by
+/// earlier transactions. Blocks are executed under both configurations by `execute`, and outputs
+/// are compared by `first_diverging_txn`.
+fn minimize(
+    batch: &TransactionBatch,
+    idx: usize,
+    mut execute: impl FnMut(&TransactionBatch) -> Result<(ExecutionResult, ExecutionResult)>,
+    first_diverging_txn: impl Fn(
+        &TransactionBatch,
+        &[TransactionOutput],
+        &[TransactionOutput],
+    ) -> Option<usize>,
+) -> Result<Divergence> {
+    for begin in (0..=idx).rev() {
+        let reproducer = batch.slice(begin, idx + 1);
+        let (baseline, candidate) = execute(&reproducer)?;
+        let diverges = match (&baseline, &candidate) {
+            (Ok(left), Ok(right)) => first_diverging_txn(&reproducer, left, right)
+                .is_some_and(|diverging_idx| diverging_idx == idx - begin),
+            (Err(left), Err(right)) => left != right,
+            _ => true,
+        };
+        if diverges {
+            return Ok(Divergence {
+                version: batch.version + idx as Version,
+                reproducer,
+                baseline,
+                candidate,
+            });
+        }
+    }
+
+    // Only reachable if execution is not deterministic, so keep the whole block.
+    let reproducer = batch.slice(0, batch.txns.len());
+    let (baseline, candidate) = execute(&reproducer)?;
+    Ok(Divergence {
+        version: batch.version + idx as Version,
+        reproducer,
+        baseline,
+        candidate,
+    })
+}
+
+/// One side of a differential run, with the state overrides derived from its configuration.
+struct Side {
+    config: ExecutionConfig,
+    overrides: OverrideConfig,
+}
+
+impl Side {
+    fn new(config: ExecutionConfig) -> Result<Self> {
+        Ok(Self {
+            overrides: config.to_override_config()?,
+            config,
+        })
+    }
+}
+
+pub struct DifferentialExecutor {
+    debugger: AptosDebugger,
+    baseline: Side,
+    candidate: Side,
+    diff_builder: TransactionDiffBuilder,
+    concurrency_level: usize,
+    output_path: Option<PathBuf>,
+}
+
+impl DifferentialExecutor {
+    pub fn new(
+        db_path: PathBuf,
+        baseline_config: ExecutionConfig,
+        candidate_config: ExecutionConfig,
+        allow_different_gas_usage: bool,
+        concurrency_level: usize,
+        output_path: Option<PathBuf>,
+    ) -> Result<Self> {
+        Ok(Self {
+            debugger: AptosDebugger::db(db_path)?,
+            baseline: Side::new(baseline_config)?,
+            candidate: Side::new(candidate_config)?,
+            diff_builder: TransactionDiffBuilder::new(allow_different_gas_usage),
+            concurrency_level,
+            output_path,
+        })
+    }
+
+    /// Executes all transactions from the source under both configurations, and fails if any of
+    /// them diverged.
+    pub async fn execute(&self, source: TransactionSource) -> Result<()> {
+        let batches = source.load_batches(&self.debugger).await?;
+        let num_txns: usize = batches.iter().map(|batch| batch.txns.len()).sum();
+
+        let mut num_divergences = 0;
+        for batch in batches {
+            if let Some(divergence) = self.execute_batch(&batch)? {
+                num_divergences += 1;
+                self.report(&divergence)?;
+            }
+        }
+
+        println!(
+            "Executed {} transactions, {} divergences found",
+            num_txns, num_divergences
+        );
+        if num_divergences > 0 {
+            bail!("Executions under baseline and candidate configurations diverged");
+        }
+        Ok(())
+    }
+
+    /// Executes the batch under the configuration of the side. Block-level VM errors are part of
+    /// the result to compare, while failures to apply the configuration are returned as the outer
+    /// error.
+    fn execute_with(&self, batch: &TransactionBatch, side: &Side) -> Result<ExecutionResult> {
+        side.config.vm_settings.apply();
+        self.debugger
+            .execute_transactions_at_version_with_overrides(
+                batch.version,
+                batch.txns.clone(),
+                batch.auxiliary_infos.clone(),
+                &side.overrides,
+                self.concurrency_level,
+            )
+    }
+
+    /// Returns the index of the first transaction in the batch with different outputs, if any.
+    fn first_diverging_txn(
+        &self,
+        batch: &TransactionBatch,
+        baseline: &[TransactionOutput],
+        candidate: &[TransactionOutput],
+    ) -> Option<usize> {
+        batch
+            .txns
+            .iter()
+            .zip(baseline.iter().zip(candidate))
+            .position(|(txn, (left, right))| {
+                !self
+                    .diff_builder
+                    .build_from_outputs(left.clone(), right.clone(), fee_payer(txn))
+                    .is_empty()
+            })
+    }
+
+    fn execute_batch(&self, batch: &TransactionBatch) -> Result<Option<Divergence>> {
+        let (baseline, candidate) = self.execute_both(batch)?;
+
+        Ok(match (&baseline, &candidate) {
+            (Ok(left), Ok(right)) => match self.first_diverging_txn(batch, left, right) {
+                Some(idx) => Some(minimize(
+                    batch,
+                    idx,
+                    |reproducer| self.execute_both(reproducer),
+                    |reproducer, left, right| self.first_diverging_txn(reproducer, left, right),
+                )?),
+                None => None,
+            },
+            (Err(left), Err(right)) if left == right => None,
+            _ => Some(Divergence {
+                version: batch.version,
+                reproducer: batch.clone(),
+                baseline,
+                candidate,
+            }),
+        })
+    }
+
+    /// Executes the batch under the baseline and the candidate configurations.
+    fn execute_both(&self, batch: &TransactionBatch) -> Result<(ExecutionResult, ExecutionResult)> {
+        Ok((
+            self.execute_with(batch, &self.baseline)?,
+            self.execute_with(batch, &self.candidate)?,
+        ))
+    }
+
+    fn report(&self, divergence: &Divergence) -> Result<()> {
+        println!(
+            "Divergence at version {}, reproduced by {} transaction(s) from version {}:",
+            divergence.version,
+            divergence.reproducer.txns.len(),
+            divergence.reproducer.version
+        );
+        match (&divergence.baseline, &divergence.candidate) {
+            (Ok(left), Ok(right)) => {
+                let txn = divergence.reproducer.txns.last();
+                let diff = self.diff_builder.build_from_outputs(
+                    left.last().cloned().expect("Reproducer is never empty"),
+                    right.last().cloned().expect("Reproducer is never empty"),
+                    txn.and_then(fee_payer),
+                );
+                diff.println();
+            },
+            (left, right) => {
+                println!("baseline: {}", summarize(left));
+                println!("candidate: {}", summarize(right));
+            },
+        }
+
+        if let Some(output_path) = &self.output_path {
+            let path = self.save_reproducer(output_path, divergence)?;
+            println!("Saved reproducer to {}", path.display());
+        }
+        Ok(())
+    }
+
+    /// Saves the reproducer batch, which can be replayed as a [TransactionSource::BatchFile],
+    /// together with both configurations and a summary of both executions.
+    fn save_reproducer(&self, output_path: &Path, divergence: &Divergence) -> Result<PathBuf> {
+        #[derive(Serialize)]
+        struct Summary<'a> {
+            version: Version,
+            baseline_config: &'a ExecutionConfig,
+            candidate_config: &'a ExecutionConfig,
+            baseline: String,
+            candidate: String,
+        }
+
+        let dir = output_path
+            .join(REPRODUCERS_DIR)
+            .join(divergence.version.to_string());
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join(REPRODUCER_BATCH_FILE),
+            bcs::to_bytes(&divergence.reproducer)?,
+        )?;
+
+        let summary = Summary {
+            version: divergence.version,
+            baseline_config: &self.baseline.config,
+            candidate_config: &self.candidate.config,
+            baseline: summarize(&divergence.baseline),
+            candidate: summarize(&divergence.candidate),
+        };
+        File::create(dir.join(REPRODUCER_SUMMARY_FILE))?
+            .write_all(serde_yaml::to_string(&summary)?.as_bytes())?;
+        Ok(dir)
+    }
+}
+
+fn fee_payer(txn: &Transaction) -> Option<AccountAddress> {
+    txn.try_as_signed_user_txn().map(|txn| {
+        txn.authenticator_ref()
+            .fee_payer_address()
+            .unwrap_or_else(|| txn.sender())
+    })
+}
+
+/// Summarizes the status and gas of the last transaction executed, or the block-level error.
+fn summarize(result: &ExecutionResult) -> String {
+    match result {
+        Ok(outputs) => match outputs.last() {
+            Some(output) => format!(
+                "status: {:?}, gas used: {}",
+                output.status(),
+                output.gas_used()
+            ),
+            None => "no outputs".to_string(),
+        },
+        Err(vm_status) => format!("block failed: {}", vm_status),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_crypto::HashValue;
+    use aptos_types::block_metadata::BlockMetadata;
+    use move_core_types::vm_status::StatusCode;
+    use std::cell::Cell;
+
+    fn block_start(round: u64) -> Transaction {
+        Transaction::BlockMetadata(BlockMetadata::new(
+            HashValue::zero(),
+            0,
+            round,
+            AccountAddress::ZERO,
+            vec![],
+            vec![],
+            0,
+        ))
+    }
+
+    fn checkpoint() -> Transaction {
+        Transaction::StateCheckpoint(HashValue::zero())
+    }
+
+    fn batch_lengths(batches: &[TransactionBatch]) -> Vec<(Version, usize)> {
+        batches
+            .iter()
+            .map(|batch| {
+                assert_eq!(batch.txns.len(), batch.auxiliary_infos.len());
+                (batch.version, batch.txns.len())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_split_into_blocks() {
+        let txns = vec![
+            block_start(1),
+            checkpoint(),
+            checkpoint(),
+            block_start(2),
+            checkpoint(),
+        ];
+        let auxiliary_infos = vec![PersistedAuxiliaryInfo::None; txns.len()];
+        let batches = split_into_blocks(100, txns, auxiliary_infos);
+        assert_eq!(batch_lengths(&batches), vec![(100, 3), (103, 2)]);
+        assert!(batches[1].txns[0].is_block_start());
+
+        // Transactions before the first block start form their own batch.
+        let txns = vec![checkpoint(), block_start(1), checkpoint()];
+        let auxiliary_infos = vec![PersistedAuxiliaryInfo::None; txns.len()];
+        let batches = split_into_blocks(7, txns, auxiliary_infos);
+        assert_eq!(batch_lengths(&batches), vec![(7, 1), (8, 2)]);
+
+        assert!(split_into_blocks(0, vec![], vec![]).is_empty());
+    }
+
+    #[test]
+    fn test_read_json_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |path: &str, content: &str| {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write("a.json", r#"{"version": "5", "hash": "0x1"}"#);
+        write("nested/b.json", r#"{"version": "3"}"#);
+        write("nested/c.json", r#"{"version": "5"}"#);
+        write("notes.txt", "not a transaction");
+        assert_eq!(read_json_versions(dir.path()).unwrap(), vec![3, 5]);
+
+        write("nested/malformed.json", r#"{"hash": "0x1"}"#);
+        let err = read_json_versions(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("Malformed transaction"));
+    }
+
+    fn failure(status_code: StatusCode) -> ExecutionResult {
+        Err(VMStatus::error(status_code, None))
+    }
+
+    #[test]
+    fn test_minimize_adds_preceding_transactions() {
+        let batch = TransactionBatch::new_uncommitted(10, vec![checkpoint(); 6]);
+        let num_executions = Cell::new(0);
+        // The divergence only reproduces if the two transactions before it are executed first.
+        let divergence = minimize(
+            &batch,
+            4,
+            |reproducer| {
+                num_executions.set(num_executions.get() + 1);
+                let candidate = if reproducer.txns.len() >= 3 {
+                    failure(StatusCode::OUT_OF_GAS)
+                } else {
+                    failure(StatusCode::ABORTED)
+                };
+                Ok((failure(StatusCode::ABORTED), candidate))
+            },
+            |_, _, _| unreachable!("Blocks always fail"),
+        )
+        .unwrap();
+        assert_eq!(num_executions.get(), 3);
+        assert_eq!(divergence.version, 14);
+        assert_eq!(divergence.reproducer.version, 12);
+        assert_eq!(divergence.reproducer.txns.len(), 3);
+        assert_eq!(
+            divergence.candidate.unwrap_err(),
+            VMStatus::error(StatusCode::OUT_OF_GAS, None)
+        );
+    }
+
+    #[test]
+    fn test_minimize_checks_diverging_transaction() {
+        let batch = TransactionBatch::new_uncommitted(10, vec![checkpoint(); 6]);
+        // Only blocks where the last transaction diverges reproduce the divergence.
+        let divergence = minimize(
+            &batch,
+            3,
+            |_| Ok((Ok(vec![]), Ok(vec![]))),
+            |reproducer, _, _| Some(reproducer.txns.len() - 1),
+        )
+        .unwrap();
+        assert_eq!(divergence.version, 13);
+        assert_eq!(divergence.reproducer.version, 13);
+        assert_eq!(divergence.reproducer.txns.len(), 1);
+
+        // If an earlier transaction diverges instead, the whole block is kept.
+        let divergence =
+            minimize(&batch, 3, |_| Ok((Ok(vec![]), Ok(vec![]))), |_, _, _| None).unwrap();
+        assert_eq!(divergence.version, 13);
+        assert_eq!(divergence.reproducer.version, 10);
+        assert_eq!(divergence.reproducer.txns.len(), 6);
+    }
+
+    #[test]
+    fn test_generate_workload() {
+        let sender = Account::new();
+        let txns = generate_workload(
+            &sender,
+            7,
+            ChainId::test(),
+            GeneratedWorkload::SelfTransfer,
+            3,
+        );
+        let sequence_numbers = txns
+            .iter()
+            .map(|txn| {
+                let txn = txn.try_as_signed_user_txn().unwrap();
+                assert_eq!(txn.sender(), *sender.address());
+                assert_eq!(txn.chain_id(), ChainId::test());
+                assert!(txn.clone().check_signature().is_ok());
+                txn.sequence_number()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(sequence_numbers, vec![7, 8, 9]);
+    }
+}
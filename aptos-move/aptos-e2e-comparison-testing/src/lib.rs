@@ -25,10 +25,12 @@ use tempfile::TempDir;
 
 mod data_collection;
 mod data_state_view;
+mod differential;
 mod execution;
 mod online_execution;
 
 pub use data_collection::*;
+pub use differential::*;
 pub use execution::*;
 use legacy_move_compiler::compiled_unit::CompiledUnitEnum;
 use move_core_types::language_storage::ModuleId;
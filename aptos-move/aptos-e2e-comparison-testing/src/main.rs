@@ -3,7 +3,8 @@
 
 use anyhow::Result;
 use aptos_comparison_testing::{
-    prepare_aptos_packages, DataCollection, Execution, ExecutionMode, OnlineExecutor, APTOS_COMMONS,
+    prepare_aptos_packages, DataCollection, DifferentialExecutor, Execution, ExecutionConfig,
+    ExecutionMode, GeneratedWorkload, OnlineExecutor, TransactionSource, APTOS_COMMONS,
 };
 use aptos_rest_client::Client;
 use clap::{Parser, Subcommand};
@@ -67,6 +68,42 @@ pub enum Cmd {
         #[clap(long)]
         execution_mode: Option<ExecutionMode>,
     },
+    /// Execute txns from a local DB under two VM configurations and report divergences
+    Differential {
+        /// Path to the local AptosDB
+        db_path: PathBuf,
+        /// YAML file with the baseline configuration, the on-chain one is used if not set
+        #[clap(long)]
+        baseline_config: Option<PathBuf>,
+        /// YAML file with the candidate configuration, the on-chain one is used if not set
+        #[clap(long)]
+        candidate_config: Option<PathBuf>,
+        /// Only execute versions of the JSON txns in this directory, e.g. the ones from
+        /// `indexer-test-transactions`
+        #[clap(long, conflicts_with_all = ["batch_file", "generate"])]
+        json_txns_dir: Option<PathBuf>,
+        /// Execute a BCS-encoded batch of txns instead, e.g. a reproducer
+        #[clap(long, conflicts_with = "generate")]
+        batch_file: Option<PathBuf>,
+        /// Generate `limit` txns of this workload on top of the state at the begin version instead
+        #[clap(long, value_enum, requires = "sender_key")]
+        generate: Option<GeneratedWorkload>,
+        /// File with the BCS-encoded private key of the sender of generated txns, e.g. `mint.key`
+        /// of a local testnet
+        #[clap(long)]
+        sender_key: Option<PathBuf>,
+        /// Address of the sender of generated txns if not derived from its key, e.g. `0xa550c18`
+        #[clap(long)]
+        sender: Option<AccountAddress>,
+        /// Ignore differences caused by different gas usage
+        #[clap(long, default_value_t = false)]
+        allow_different_gas_usage: bool,
+        #[clap(long, default_value_t = 1)]
+        concurrency_level: usize,
+        /// Path to save minimized reproducers of divergences to
+        #[clap(long)]
+        output_path: Option<PathBuf>,
+    },
 }
 
 #[derive(Parser)]
@@ -166,6 +203,55 @@ async fn main() -> Result<()> {
                 .execute_txns(args.begin_version, args.limit)
                 .await?;
         },
+        Cmd::Differential {
+            db_path,
+            baseline_config,
+            candidate_config,
+            json_txns_dir,
+            batch_file,
+            generate,
+            sender_key,
+            sender,
+            allow_different_gas_usage,
+            concurrency_level,
+            output_path,
+        } => {
+            let load_config = |path: Option<PathBuf>| match path {
+                Some(path) => ExecutionConfig::load(&path),
+                None => Ok(ExecutionConfig::default()),
+            };
+            let source = if let Some(path) = batch_file {
+                TransactionSource::BatchFile { path }
+            } else if let Some(workload) = generate {
+                TransactionSource::Generated {
+                    version: args.begin_version,
+                    workload,
+                    num_txns: args.limit,
+                    sender_key: sender_key.expect("Sender key is required by clap"),
+                    sender,
+                }
+            } else if let Some(dir) = json_txns_dir {
+                TransactionSource::JsonVersions {
+                    dir,
+                    begin: args.begin_version,
+                    limit: args.limit,
+                }
+            } else {
+                TransactionSource::Committed {
+                    begin: args.begin_version,
+                    limit: args.limit,
+                }
+            };
+            let executor = DifferentialExecutor::new(
+                db_path,
+                load_config(baseline_config)?,
+                load_config(candidate_config)?,
+                allow_different_gas_usage,
+                concurrency_level,
+                output_path,
+            )?;
+            executor.execute(source).await?;
+        },
     };
     Ok(())
 }
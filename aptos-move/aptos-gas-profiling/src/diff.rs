@@ -0,0 +1,699 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    log::{CallFrame, ExecutionGasEvent, TransactionGasLog},
+    render::Render,
+    report::{ensure_dirs_exist, render_table},
+};
+use anyhow::{bail, Result};
+use handlebars::Handlebars;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+const TEMPLATE: &str = include_str!("../templates/diff.html");
+
+/// A summary of a [`TransactionGasLog`], which keeps the gas charged per call frame and per
+/// ledger operation.
+///
+/// Unlike the log itself, the profile can be stored, e.g., to compare the gas usage of a later
+/// version of a package against it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasProfile {
+    pub entry_point: String,
+    pub gas_scaling_factor: u64,
+    pub call_graph: FrameProfile,
+    /// IO gas not charged by a call frame, in internal gas units, e.g., for dependencies and
+    /// ledger writes.
+    pub io: BTreeMap<String, u64>,
+    /// Storage fees in Octa, by state key or event type, or `None` if storage fees were not
+    /// charged, e.g., for unit tests.
+    pub storage: Option<BTreeMap<String, u64>>,
+}
+
+/// The gas charged by a call frame, in internal gas units.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameProfile {
+    pub name: String,
+    /// Whether this is a call to a native function.
+    pub native: bool,
+    /// Execution gas charged by the frame itself, excluding its calls.
+    pub execution: u64,
+    /// IO gas for reading resources charged by the frame itself, excluding its calls.
+    pub io: u64,
+    pub calls: Vec<FrameProfile>,
+}
+
+/// The amount of gas charged in the old and new version of a transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Delta {
+    pub old: u64,
+    pub new: u64,
+}
+
+/// Whether a call frame appears in both versions of a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameChange {
+    Matched,
+    Added,
+    Removed,
+}
+
+/// The difference in gas charged by matching call frames of two versions of a transaction, in
+/// internal gas units.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameDiff {
+    pub name: String,
+    pub native: bool,
+    pub change: FrameChange,
+    /// Execution gas charged by the frame itself, excluding its calls.
+    pub execution: Delta,
+    /// IO gas charged by the frame itself, excluding its calls.
+    pub io: Delta,
+    pub calls: Vec<FrameDiff>,
+}
+
+/// The difference in gas usage between two versions of a transaction with the same entry point.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasProfileDiff {
+    pub entry_point: String,
+    pub gas_scaling_factor: u64,
+    pub call_graph: FrameDiff,
+    /// IO gas not charged by a call frame, in internal gas units.
+    pub io: BTreeMap<String, Delta>,
+    /// Storage fees in Octa, or `None` if either version was not charged storage fees.
+    pub storage: Option<BTreeMap<String, Delta>>,
+}
+
+impl Delta {
+    pub fn diff(&self) -> i128 {
+        self.new as i128 - self.old as i128
+    }
+
+    /// The change relative to the old amount in percent, or `None` if the old amount is zero.
+    pub fn percentage(&self) -> Option<f64> {
+        (self.old != 0).then(|| self.diff() as f64 / self.old as f64 * 100.0)
+    }
+
+    fn exceeds(&self, max_increase_percent: f64) -> bool {
+        self.new > self.old
+            && self
+                .percentage()
+                .is_none_or(|percentage| percentage > max_increase_percent)
+    }
+}
+
+impl std::ops::Add for Delta {
+    type Output = Delta;
+
+    fn add(self, rhs: Delta) -> Delta {
+        Delta {
+            old: self.old + rhs.old,
+            new: self.new + rhs.new,
+        }
+    }
+}
+
+impl CallFrame {
+    fn to_profile(&self) -> FrameProfile {
+        use ExecutionGasEvent::*;
+
+        let mut profile = FrameProfile {
+            name: format!("{}", self.name),
+            native: false,
+            execution: 0,
+            io: 0,
+            calls: vec![],
+        };
+        for event in &self.events {
+            match event {
+                Loc(..) => (),
+                Bytecode { cost, .. } | CreateTy { cost } => profile.execution += u64::from(*cost),
+                LoadResource { cost, .. } => profile.io += u64::from(*cost),
+                Call(frame) => profile.calls.push(frame.to_profile()),
+                CallNative {
+                    module_id,
+                    fn_name,
+                    ty_args,
+                    cost,
+                } => profile.calls.push(FrameProfile {
+                    name: format!(
+                        "{}",
+                        Render(&(module_id, fn_name.as_ident_str(), ty_args.as_slice()))
+                    ),
+                    native: true,
+                    execution: u64::from(*cost),
+                    io: 0,
+                    calls: vec![],
+                }),
+            }
+        }
+        profile
+    }
+}
+
+fn add_to(map: &mut BTreeMap<String, u64>, key: String, amount: u64) {
+    if amount != 0 {
+        *map.entry(key).or_default() += amount;
+    }
+}
+
+impl TransactionGasLog {
+    /// Summarizes the log into a profile which can be stored and compared.
+    pub fn to_profile(&self) -> GasProfile {
+        let mut io = BTreeMap::new();
+        add_to(
+            &mut io,
+            "intrinsic".to_string(),
+            u64::from(self.exec_io.intrinsic_cost),
+        );
+        add_to(
+            &mut io,
+            "keyless".to_string(),
+            u64::from(self.exec_io.keyless_cost),
+        );
+        for dep in &self.exec_io.dependencies {
+            add_to(
+                &mut io,
+                format!("dependency {}", dep.render()),
+                u64::from(dep.cost),
+            );
+        }
+        if let Some(cost) = self.exec_io.transaction_transient {
+            add_to(&mut io, "transaction".to_string(), u64::from(cost));
+        }
+        for event in &self.exec_io.events_transient {
+            add_to(
+                &mut io,
+                format!("event {}", Render(&event.ty)),
+                u64::from(event.cost),
+            );
+        }
+        for write in &self.exec_io.write_set_transient {
+            add_to(
+                &mut io,
+                format!("write {}", Render(&write.key)),
+                u64::from(write.cost),
+            );
+        }
+
+        let mut storage = BTreeMap::new();
+        add_to(
+            &mut storage,
+            "transaction".to_string(),
+            u64::from(self.storage.txn_storage),
+        );
+        for write in &self.storage.write_set_storage {
+            add_to(
+                &mut storage,
+                format!("write {}", Render(&write.key)),
+                u64::from(write.cost),
+            );
+        }
+        for event in &self.storage.events {
+            add_to(
+                &mut storage,
+                format!("event {}", Render(&event.ty)),
+                u64::from(event.cost),
+            );
+        }
+
+        GasProfile {
+            entry_point: format!("{}", self.entry_point()),
+            gas_scaling_factor: u64::from(self.exec_io.gas_scaling_factor),
+            call_graph: self.exec_io.call_graph.to_profile(),
+            io,
+            storage: Some(storage),
+        }
+    }
+
+    /// Compares the gas usage of this log with the one of a newer version of the same
+    /// transaction.
+    pub fn compare(&self, new: &TransactionGasLog) -> Result<GasProfileDiff> {
+        GasProfileDiff::new(&self.to_profile(), &new.to_profile())
+    }
+}
+
+fn diff_maps(old: &BTreeMap<String, u64>, new: &BTreeMap<String, u64>) -> BTreeMap<String, Delta> {
+    old.keys()
+        .chain(new.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|key| {
+            (key.clone(), Delta {
+                old: old.get(key).copied().unwrap_or_default(),
+                new: new.get(key).copied().unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Identifies each call by the name of the callee, and the number of calls to it made before by
+/// the same frame, so that calls in loops and repeated calls line up.
+fn call_keys(calls: &[FrameProfile]) -> Vec<(&str, usize)> {
+    let mut counts = BTreeMap::<&str, usize>::new();
+    calls
+        .iter()
+        .map(|call| {
+            let count = counts.entry(call.name.as_str()).or_default();
+            *count += 1;
+            (call.name.as_str(), *count)
+        })
+        .collect()
+}
+
+impl FrameDiff {
+    fn new(old: Option<&FrameProfile>, new: Option<&FrameProfile>) -> Self {
+        let frame = new.or(old).expect("at least one frame must exist");
+        let change = match (old, new) {
+            (Some(_), Some(_)) => FrameChange::Matched,
+            (None, _) => FrameChange::Added,
+            (_, None) => FrameChange::Removed,
+        };
+
+        let old_calls = old.map(|frame| frame.calls.as_slice()).unwrap_or_default();
+        let new_calls = new.map(|frame| frame.calls.as_slice()).unwrap_or_default();
+        let new_keys = call_keys(new_calls);
+        let mut matched_new = BTreeSet::new();
+        let mut calls = vec![];
+        for (old_call, key) in old_calls.iter().zip(call_keys(old_calls)) {
+            let new_idx = new_keys.iter().position(|new_key| *new_key == key);
+            if let Some(idx) = new_idx {
+                matched_new.insert(idx);
+            }
+            calls.push(FrameDiff::new(
+                Some(old_call),
+                new_idx.map(|idx| &new_calls[idx]),
+            ));
+        }
+        for (idx, new_call) in new_calls.iter().enumerate() {
+            if !matched_new.contains(&idx) {
+                calls.push(FrameDiff::new(None, Some(new_call)));
+            }
+        }
+
+        Self {
+            name: frame.name.clone(),
+            native: frame.native,
+            change,
+            execution: Delta {
+                old: old.map(|frame| frame.execution).unwrap_or_default(),
+                new: new.map(|frame| frame.execution).unwrap_or_default(),
+            },
+            io: Delta {
+                old: old.map(|frame| frame.io).unwrap_or_default(),
+                new: new.map(|frame| frame.io).unwrap_or_default(),
+            },
+            calls,
+        }
+    }
+
+    /// Returns the execution and IO gas charged by the frame including its calls.
+    pub fn inclusive(&self) -> (Delta, Delta) {
+        self.calls.iter().map(FrameDiff::inclusive).fold(
+            (self.execution, self.io),
+            |(execution, io), (call_execution, call_io)| (execution + call_execution, io + call_io),
+        )
+    }
+
+    fn preorder_traversal(&self, depth: usize, f: &mut impl FnMut(usize, &FrameDiff)) {
+        f(depth, self);
+        for call in &self.calls {
+            call.preorder_traversal(depth + 1, f);
+        }
+    }
+}
+
+impl GasProfileDiff {
+    /// Compares two profiles of a transaction. Both must have the same entry point.
+    pub fn new(old: &GasProfile, new: &GasProfile) -> Result<Self> {
+        if old.entry_point != new.entry_point {
+            bail!(
+                "Cannot compare gas profiles of different entry points: {} and {}",
+                old.entry_point,
+                new.entry_point
+            );
+        }
+        if old.gas_scaling_factor != new.gas_scaling_factor {
+            bail!(
+                "Cannot compare gas profiles with different gas scaling factors: {} and {}",
+                old.gas_scaling_factor,
+                new.gas_scaling_factor
+            );
+        }
+        Ok(Self {
+            entry_point: new.entry_point.clone(),
+            gas_scaling_factor: new.gas_scaling_factor,
+            call_graph: FrameDiff::new(Some(&old.call_graph), Some(&new.call_graph)),
+            io: diff_maps(&old.io, &new.io),
+            storage: old
+                .storage
+                .as_ref()
+                .zip(new.storage.as_ref())
+                .map(|(old, new)| diff_maps(old, new)),
+        })
+    }
+
+    /// Total execution gas, in internal gas units.
+    pub fn execution(&self) -> Delta {
+        self.call_graph.inclusive().0
+    }
+
+    /// Total IO gas, in internal gas units.
+    pub fn io(&self) -> Delta {
+        self.io
+            .values()
+            .fold(self.call_graph.inclusive().1, |total, delta| total + *delta)
+    }
+
+    /// Total storage fees, in Octa, or `None` if storage fees were not charged.
+    pub fn storage(&self) -> Option<Delta> {
+        self.storage.as_ref().map(|storage| {
+            storage
+                .values()
+                .fold(Delta::default(), |total, delta| total + *delta)
+        })
+    }
+
+    /// Returns a description of every total (execution, IO, or storage, if charged) which
+    /// increased by more than the given percentage.
+    pub fn regressions(&self, max_increase_percent: f64) -> Vec<String> {
+        let mut totals = vec![
+            ("execution gas", self.execution(), self.gas_scaling_factor),
+            ("IO gas", self.io(), self.gas_scaling_factor),
+        ];
+        if let Some(storage) = self.storage() {
+            totals.push(("storage fee", storage, 1_0000_0000));
+        }
+        totals
+            .into_iter()
+            .filter(|(_, delta, _)| delta.exceeds(max_increase_percent))
+            .map(|(name, delta, scaling_factor)| {
+                format!(
+                    "{} of {} increased from {} to {} ({}), more than the allowed {}%",
+                    name,
+                    self.entry_point,
+                    fmt_scaled(delta.old, scaling_factor),
+                    fmt_scaled(delta.new, scaling_factor),
+                    fmt_percentage(&delta),
+                    max_increase_percent
+                )
+            })
+            .collect()
+    }
+
+    /// Renders a table with the totals, one row per kind of cost.
+    pub fn summary(&self) -> Result<String> {
+        let mut table = vec![[
+            "".to_string(),
+            "Old".to_string(),
+            "New".to_string(),
+            "Change".to_string(),
+        ]];
+        let mut totals = vec![
+            (
+                "Execution (gas units)",
+                self.execution(),
+                self.gas_scaling_factor,
+            ),
+            ("IO (gas units)", self.io(), self.gas_scaling_factor),
+        ];
+        if let Some(storage) = self.storage() {
+            totals.push(("Storage (APT)", storage, 1_0000_0000));
+        }
+        for (name, delta, scaling_factor) in totals {
+            table.push([
+                name.to_string(),
+                fmt_scaled(delta.old, scaling_factor),
+                fmt_scaled(delta.new, scaling_factor),
+                fmt_percentage(&delta),
+            ]);
+        }
+        let mut output = String::new();
+        render_table(&mut output, &table, 4)?;
+        Ok(output)
+    }
+
+    /// Renders an HTML report of the differences, with per-frame deltas for execution and IO gas,
+    /// and per-operation deltas for IO gas and storage fees.
+    pub fn generate_html_report(&self, path: impl AsRef<Path>, header: String) -> Result<()> {
+        let scaling_factor = self.gas_scaling_factor;
+        let convert_delta = |name: String, delta: Delta, scaling_factor: u64| {
+            json!({
+                "name": name,
+                "old": fmt_scaled(delta.old, scaling_factor),
+                "new": fmt_scaled(delta.new, scaling_factor),
+                "diff": fmt_diff(delta.diff(), scaling_factor),
+                "percentage": fmt_percentage(&delta),
+            })
+        };
+
+        let mut data = Map::new();
+        data.insert("title".to_string(), Value::String(header));
+        data.insert(
+            "totals".to_string(),
+            Value::Array(vec![
+                convert_delta("Execution".to_string(), self.execution(), scaling_factor),
+                convert_delta("IO".to_string(), self.io(), scaling_factor),
+            ]),
+        );
+        if let Some(storage) = self.storage() {
+            data.insert(
+                "storage-total".to_string(),
+                convert_delta("Storage".to_string(), storage, 1_0000_0000),
+            );
+        }
+
+        let mut frames = vec![];
+        self.call_graph.preorder_traversal(0, &mut |depth, frame| {
+            let (execution, io) = frame.inclusive();
+            let marker = match frame.change {
+                FrameChange::Matched => "",
+                FrameChange::Added => " (added)",
+                FrameChange::Removed => " (removed)",
+            };
+            let native = if frame.native { " (native)" } else { "" };
+            let mut row = convert_delta(
+                format!(
+                    "{}{}{}{}",
+                    " ".repeat(depth * 4),
+                    frame.name,
+                    native,
+                    marker
+                ),
+                execution,
+                scaling_factor,
+            );
+            row["io"] = convert_delta(String::new(), io, scaling_factor);
+            frames.push(row);
+        });
+        data.insert("frames".to_string(), Value::Array(frames));
+
+        let changed = |deltas: &BTreeMap<String, Delta>, scaling_factor: u64| {
+            Value::Array(
+                deltas
+                    .iter()
+                    .filter(|(_, delta)| delta.old != delta.new)
+                    .map(|(name, delta)| convert_delta(name.clone(), *delta, scaling_factor))
+                    .collect(),
+            )
+        };
+        data.insert("io".to_string(), changed(&self.io, scaling_factor));
+        if let Some(storage) = &self.storage {
+            data.insert("storage-charged".to_string(), Value::Bool(true));
+            data.insert("storage".to_string(), changed(storage, 1_0000_0000));
+        }
+
+        let mut handlebars = Handlebars::new();
+        handlebars.register_template_string("diff", TEMPLATE)?;
+        let html = handlebars.render("diff", &data)?;
+
+        let path_root = path.as_ref();
+        ensure_dirs_exist(path_root)?;
+        fs::write(path_root.join("index.html"), html)?;
+
+        Ok(())
+    }
+}
+
+fn fmt_scaled(amount: u64, scaling_factor: u64) -> String {
+    let scaled = format!("{:.8}", amount as f64 / scaling_factor as f64);
+    crate::misc::strip_trailing_zeros_and_decimal_point(&scaled).to_string()
+}
+
+fn fmt_diff(diff: i128, scaling_factor: u64) -> String {
+    let sign = if diff > 0 {
+        "+"
+    } else if diff < 0 {
+        "-"
+    } else {
+        ""
+    };
+    format!(
+        "{}{}",
+        sign,
+        fmt_scaled(diff.unsigned_abs() as u64, scaling_factor)
+    )
+}
+
+fn fmt_percentage(delta: &Delta) -> String {
+    match delta.percentage() {
+        Some(percentage) => format!("{:+.2}%", percentage),
+        None if delta.new == 0 => "/".to_string(),
+        None => "new".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(name: &str, execution: u64, io: u64, calls: Vec<FrameProfile>) -> FrameProfile {
+        FrameProfile {
+            name: name.to_string(),
+            native: false,
+            execution,
+            io,
+            calls,
+        }
+    }
+
+    fn profile(call_graph: FrameProfile, storage: Option<u64>) -> GasProfile {
+        GasProfile {
+            entry_point: "0x1::m::f".to_string(),
+            gas_scaling_factor: 100,
+            call_graph,
+            io: BTreeMap::from([("dependency 0x1::m".to_string(), 100)]),
+            storage: storage.map(|fee| BTreeMap::from([("transaction".to_string(), fee)])),
+        }
+    }
+
+    fn summarize(diff: &FrameDiff) -> Vec<(String, FrameChange, Delta)> {
+        diff.calls
+            .iter()
+            .map(|call| (call.name.clone(), call.change, call.execution))
+            .collect()
+    }
+
+    #[test]
+    fn test_repeated_calls() {
+        let old = profile(
+            frame("0x1::m::f", 10, 0, vec![
+                frame("0x1::m::g", 1, 0, vec![]),
+                frame("0x1::m::h", 2, 0, vec![]),
+                frame("0x1::m::g", 3, 0, vec![]),
+            ]),
+            None,
+        );
+        let new = profile(
+            frame("0x1::m::f", 10, 0, vec![
+                frame("0x1::m::g", 4, 0, vec![]),
+                frame("0x1::m::g", 5, 0, vec![]),
+                frame("0x1::m::h", 6, 0, vec![]),
+            ]),
+            None,
+        );
+        let diff = GasProfileDiff::new(&old, &new).unwrap();
+        assert_eq!(diff.call_graph.change, FrameChange::Matched);
+        assert_eq!(summarize(&diff.call_graph), vec![
+            ("0x1::m::g".to_string(), FrameChange::Matched, Delta {
+                old: 1,
+                new: 4
+            }),
+            ("0x1::m::h".to_string(), FrameChange::Matched, Delta {
+                old: 2,
+                new: 6
+            }),
+            ("0x1::m::g".to_string(), FrameChange::Matched, Delta {
+                old: 3,
+                new: 5
+            }),
+        ]);
+        assert_eq!(diff.execution(), Delta { old: 16, new: 25 });
+    }
+
+    #[test]
+    fn test_added_and_removed_frames() {
+        let old = profile(
+            frame("0x1::m::f", 10, 5, vec![
+                frame("0x1::m::g", 1, 0, vec![]),
+                frame("0x1::m::g", 2, 0, vec![frame("0x1::m::h", 3, 7, vec![])]),
+            ]),
+            None,
+        );
+        let new = profile(
+            frame("0x1::m::f", 10, 5, vec![
+                frame("0x1::m::g", 1, 0, vec![]),
+                frame("0x1::m::k", 4, 0, vec![]),
+            ]),
+            None,
+        );
+        let diff = GasProfileDiff::new(&old, &new).unwrap();
+        assert_eq!(summarize(&diff.call_graph), vec![
+            ("0x1::m::g".to_string(), FrameChange::Matched, Delta {
+                old: 1,
+                new: 1
+            }),
+            ("0x1::m::g".to_string(), FrameChange::Removed, Delta {
+                old: 2,
+                new: 0
+            }),
+            ("0x1::m::k".to_string(), FrameChange::Added, Delta {
+                old: 0,
+                new: 4
+            }),
+        ]);
+        // The calls of a removed frame are removed as well.
+        let removed = &diff.call_graph.calls[1];
+        assert_eq!(removed.calls[0].change, FrameChange::Removed);
+        assert_eq!(
+            removed.inclusive(),
+            (Delta { old: 5, new: 0 }, Delta { old: 7, new: 0 })
+        );
+        assert_eq!(diff.execution(), Delta { old: 16, new: 15 });
+        assert_eq!(diff.io(), Delta { old: 112, new: 105 });
+    }
+
+    #[test]
+    fn test_regressions() {
+        let old = profile(frame("0x1::m::f", 100, 0, vec![]), Some(1000));
+        let new = profile(frame("0x1::m::f", 120, 0, vec![]), Some(2000));
+        let diff = GasProfileDiff::new(&old, &new).unwrap();
+        assert_eq!(diff.regressions(25.0), vec![
+            "storage fee of 0x1::m::f increased from 0.00001 to 0.00002 (+100.00%), more than \
+             the allowed 25%"
+                .to_string()
+        ]);
+        assert_eq!(diff.regressions(10.0).len(), 2);
+        assert!(diff.regressions(100.0).is_empty());
+
+        // Without storage fees on either side, only execution and IO gas are compared.
+        let diff = GasProfileDiff::new(&old, &GasProfile {
+            storage: None,
+            ..new.clone()
+        })
+        .unwrap();
+        assert_eq!(diff.storage(), None);
+        assert_eq!(diff.regressions(10.0), vec![
+            "execution gas of 0x1::m::f increased from 1 to 1.2 (+20.00%), more than the \
+             allowed 10%"
+                .to_string()
+        ]);
+
+        // A cost which was not charged before always counts as a regression.
+        let old = profile(frame("0x1::m::f", 0, 0, vec![]), None);
+        let new = profile(frame("0x1::m::f", 10, 0, vec![]), None);
+        let diff = GasProfileDiff::new(&old, &new).unwrap();
+        assert_eq!(diff.regressions(1000.0).len(), 1);
+        assert!(GasProfileDiff::new(&new, &old)
+            .unwrap()
+            .regressions(0.0)
+            .is_empty());
+    }
+}
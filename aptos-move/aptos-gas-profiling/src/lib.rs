@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod aggregate;
+mod diff;
 mod erased;
 mod flamegraph;
 mod log;
@@ -11,5 +12,6 @@ mod render;
 mod report;
 mod unique_stack;
 
+pub use diff::{Delta, FrameChange, FrameDiff, FrameProfile, GasProfile, GasProfileDiff};
 pub use log::{FrameName, TransactionGasLog};
 pub use profiler::GasProfiler;
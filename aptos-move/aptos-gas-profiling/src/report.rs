@@ -14,7 +14,7 @@ use std::{
 
 const TEMPLATE: &str = include_str!("../templates/index.html");
 
-fn ensure_dirs_exist(path: impl AsRef<Path>) -> Result<()> {
+pub(crate) fn ensure_dirs_exist(path: impl AsRef<Path>) -> Result<()> {
     if let Err(err) = fs::create_dir_all(&path) {
         match err.kind() {
             std::io::ErrorKind::AlreadyExists => (),
@@ -32,7 +32,11 @@ fn indent(output: &mut impl Write, count: usize) -> fmt::Result {
     write!(output, "{}", " ".repeat(count))
 }
 
-fn render_table<R, S>(output: &mut impl Write, table: &[R], spacing: usize) -> fmt::Result
+pub(crate) fn render_table<R, S>(
+    output: &mut impl Write,
+    table: &[R],
+    spacing: usize,
+) -> fmt::Result
 where
     R: AsRef<[S]>,
     S: AsRef<str>,
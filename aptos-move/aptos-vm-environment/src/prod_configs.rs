@@ -21,33 +21,80 @@ use move_vm_types::{
     loaded_data::runtime_types::TypeBuilder, values::DEFAULT_MAX_VM_VALUE_NESTED_DEPTH,
 };
 use once_cell::sync::OnceCell;
+use std::sync::atomic::{AtomicU8, Ordering};
 
-static PARANOID_TYPE_CHECKS: OnceCell<bool> = OnceCell::new();
-static PARANOID_REF_CHECKS: OnceCell<bool> = OnceCell::new();
+static PARANOID_TYPE_CHECKS: ProcessFlag = ProcessFlag::new();
+static PARANOID_REF_CHECKS: ProcessFlag = ProcessFlag::new();
 static TIMED_FEATURE_OVERRIDE: OnceCell<TimedFeatureOverride> = OnceCell::new();
 
 /// If enabled, types layouts are cached in a global long-living cache. Caches ensure the behavior
 /// is the same as without caches, and so, using node config suffices.
-static LAYOUT_CACHES: OnceCell<bool> = OnceCell::new();
+static LAYOUT_CACHES: ProcessFlag = ProcessFlag::new();
+
+/// A process-wide flag which keeps the first value it is set to, like a [OnceCell]. With the
+/// `testing` feature, tools which compare executions under different settings within the same
+/// process can still override it between executions.
+struct ProcessFlag(AtomicU8);
+
+impl ProcessFlag {
+    const DISABLED: u8 = 1;
+    const ENABLED: u8 = 2;
+    const UNSET: u8 = 0;
+
+    const fn new() -> Self {
+        Self(AtomicU8::new(Self::UNSET))
+    }
+
+    fn encode(enable: bool) -> u8 {
+        if enable {
+            Self::ENABLED
+        } else {
+            Self::DISABLED
+        }
+    }
+
+    fn set(&self, enable: bool) {
+        self.0
+            .compare_exchange(
+                Self::UNSET,
+                Self::encode(enable),
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            )
+            .ok();
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    fn set_override(&self, enable: bool) {
+        self.0.store(Self::encode(enable), Ordering::SeqCst);
+    }
+
+    fn get_or(&self, default: bool) -> bool {
+        match self.0.load(Ordering::SeqCst) {
+            Self::UNSET => default,
+            value => value == Self::ENABLED,
+        }
+    }
+}
 
 /// Set the paranoid type check flag.
 pub fn set_paranoid_type_checks(enable: bool) {
-    PARANOID_TYPE_CHECKS.set(enable).ok();
+    PARANOID_TYPE_CHECKS.set(enable);
 }
 
 /// Returns the paranoid type check flag if already set, and true otherwise.
 pub fn get_paranoid_type_checks() -> bool {
-    PARANOID_TYPE_CHECKS.get().cloned().unwrap_or(true)
+    PARANOID_TYPE_CHECKS.get_or(true)
 }
 
 /// Set the paranoid reference check flag.
 pub fn set_paranoid_ref_checks(enable: bool) {
-    PARANOID_REF_CHECKS.set(enable).ok();
+    PARANOID_REF_CHECKS.set(enable);
 }
 
 /// Returns the paranoid reference check flag if already set, and false otherwise.
 pub fn get_paranoid_ref_checks() -> bool {
-    PARANOID_REF_CHECKS.get().cloned().unwrap_or(false)
+    PARANOID_REF_CHECKS.get_or(false)
 }
 
 /// Set the timed feature override.
@@ -62,12 +109,28 @@ pub fn get_timed_feature_override() -> Option<TimedFeatureOverride> {
 
 /// Set the layout cache flag.
 pub fn set_layout_caches(enable: bool) {
-    LAYOUT_CACHES.set(enable).ok();
+    LAYOUT_CACHES.set(enable);
 }
 
 /// Returns the layout cache flag if already set, and false otherwise.
 pub fn get_layout_caches() -> bool {
-    LAYOUT_CACHES.get().cloned().unwrap_or(false)
+    LAYOUT_CACHES.get_or(false)
+}
+
+/// Overrides the paranoid type check, paranoid reference check and layout cache flags, even if
+/// they have already been set. Only meant for tools executing the same transactions under
+/// different settings, and must not be called while transactions are being executed. Settings
+/// are read when the execution environment is created, so each execution has to use a new block
+/// executor, which also comes with its own module and layout caches.
+#[cfg(any(test, feature = "testing"))]
+pub fn override_vm_flags(
+    paranoid_type_checks: bool,
+    paranoid_ref_checks: bool,
+    layout_caches: bool,
+) {
+    PARANOID_TYPE_CHECKS.set_override(paranoid_type_checks);
+    PARANOID_REF_CHECKS.set_override(paranoid_ref_checks);
+    LAYOUT_CACHES.set_override(layout_caches);
 }
 
 /// Returns [TypeBuilder] used by the Aptos blockchain in production.
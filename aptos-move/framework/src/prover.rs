@@ -45,6 +45,11 @@ pub struct ProverOptions {
     #[clap(long, short)]
     pub trace: bool,
 
+    /// Whether to render the counterexample of a verification error as a Move unit test,
+    /// which can be added to the module of the verified function to reproduce the error.
+    #[clap(long)]
+    pub counterexample_tests: bool,
+
     /// Whether to use cvc5 as the smt solver backend. The environment variable
     /// `CVC5_EXE` should point to the binary.
     #[clap(long)]
@@ -256,6 +261,8 @@ impl ProverOptions {
                 global_timeout_overwrite: !self.disallow_global_timeout_to_be_overwritten,
                 keep_artifacts: self.dump || base_opts.backend.keep_artifacts,
                 stable_test_output: self.stable_test_output || base_opts.backend.stable_test_output,
+                counterexample_tests: self.counterexample_tests
+                    || base_opts.backend.counterexample_tests,
                 z3_trace_file: if self.dump {
                     Some("z3.trace".to_string())
                 } else {
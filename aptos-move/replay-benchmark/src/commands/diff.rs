@@ -3,13 +3,13 @@
 
 use crate::{
     commands::init_logger_and_metrics,
-    diff::TransactionDiffBuilder,
     execution::execute_workload,
     state_view::ReadSet,
     workload::{TransactionBlock, Workload},
 };
 use anyhow::{anyhow, bail};
 use aptos_logger::Level;
+use aptos_move_debugger::diff::TransactionDiffBuilder;
 use aptos_types::transaction::TransactionOutput;
 use aptos_vm::{aptos_vm::AptosVMBlockExecutor, VMBlockExecutor};
 use clap::Parser;
@@ -4,11 +4,11 @@
 use crate::{
     commands::{build_debugger, init_logger_and_metrics, RestAPI},
     generator::InputOutputDiffGenerator,
-    overrides::OverrideConfig,
     workload::TransactionBlock,
 };
 use anyhow::anyhow;
 use aptos_logger::Level;
+use aptos_move_debugger::overrides::OverrideConfig;
 use aptos_types::on_chain_config::FeatureFlag;
 use clap::Parser;
 use std::path::PathBuf;
@@ -86,6 +86,7 @@ impl InitializeCommand {
             self.disable_features,
             self.gas_feature_version,
             self.override_packages,
+            vec![],
         )?;
 
         let debugger = build_debugger(self.rest_api.rest_endpoint, self.rest_api.api_key)?;
@@ -3,12 +3,11 @@
 
 use crate::{
     execution::execute_workload,
-    overrides::OverrideConfig,
     state_view::{ReadSet, ReadSetCapturingStateView},
     workload::{TransactionBlock, Workload},
 };
 use aptos_logger::error;
-use aptos_move_debugger::aptos_debugger::AptosDebugger;
+use aptos_move_debugger::{aptos_debugger::AptosDebugger, overrides::OverrideConfig};
 use aptos_types::transaction::Version;
 use aptos_vm::{aptos_vm::AptosVMBlockExecutor, VMBlockExecutor};
 use std::{
@@ -62,7 +61,7 @@ impl InputOutputDiffGenerator {
 
         let mut all_inputs = Vec::with_capacity(tasks.len());
         for task in tasks {
-            all_inputs.push(task.await?);
+            all_inputs.push(task.await??);
         }
 
         Ok(all_inputs)
@@ -73,9 +72,9 @@ impl InputOutputDiffGenerator {
     ///
     /// Note: transaction execution is sequential, so that multiple inputs can be constructed in
     /// parallel.
-    fn generate_inputs(&self, txn_block: TransactionBlock) -> ReadSet {
+    fn generate_inputs(&self, txn_block: TransactionBlock) -> anyhow::Result<ReadSet> {
         let state_view = self.debugger.state_view_at_version(txn_block.begin_version);
-        let state_override = self.override_config.get_state_override(&state_view);
+        let state_override = self.override_config.get_state_override(&state_view)?;
         let workload = Workload::from(txn_block);
 
         // First, we execute transactions without overrides.
@@ -109,6 +108,6 @@ impl InputOutputDiffGenerator {
             &state_view_with_override,
             1,
         );
-        state_view_with_override.into_read_set()
+        Ok(state_view_with_override.into_read_set())
     }
 }
@@ -2,10 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod commands;
-mod diff;
 mod execution;
 mod generator;
-mod overrides;
 mod runner;
 mod state_view;
 mod workload;
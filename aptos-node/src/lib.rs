@@ -750,7 +750,7 @@ pub fn setup_environment_and_start_node(
         chain_id,
         peers_and_metadata.clone(),
         &mut event_subscription_service,
-    );
+    )?;
 
     // Start the peer monitoring service
     let peer_monitoring_service_runtime = services::start_peer_monitoring_service(
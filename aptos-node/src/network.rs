@@ -247,7 +247,7 @@ pub fn setup_networks_and_get_interfaces(
     chain_id: ChainId,
     peers_and_metadata: Arc<PeersAndMetadata>,
     event_subscription_service: &mut EventSubscriptionService,
-) -> (
+) -> anyhow::Result<(
     Vec<Runtime>,
     Option<ApplicationNetworkInterfaces<ConsensusMsg>>,
     Option<ApplicationNetworkInterfaces<ConsensusObserverMessage>>,
@@ -256,7 +256,7 @@ pub fn setup_networks_and_get_interfaces(
     ApplicationNetworkInterfaces<MempoolSyncMsg>,
     ApplicationNetworkInterfaces<PeerMonitoringServiceMessage>,
     ApplicationNetworkInterfaces<StorageServiceMessage>,
-) {
+)> {
     // Gather all network configs
     let network_configs = extract_network_configs(node_config);
 
@@ -287,7 +287,14 @@ pub fn setup_networks_and_get_interfaces(
             TimeService::real(),
             Some(event_subscription_service),
             peers_and_metadata.clone(),
-        );
+        )
+        .map_err(|error| {
+            anyhow::anyhow!(
+                "Failed to create the network for {}: {}",
+                network_config.network_id,
+                error
+            )
+        })?;
 
         // Register consensus (both client and server) with the network
         let network_id = network_config.network_id;
@@ -443,7 +450,7 @@ pub fn setup_networks_and_get_interfaces(
         network_runtimes.push(netbench_runtime);
     }
 
-    (
+    Ok((
         network_runtimes,
         consensus_interfaces,
         consensus_observer_interfaces,
@@ -452,7 +459,7 @@ pub fn setup_networks_and_get_interfaces(
         mempool_interfaces,
         peer_monitoring_service_interfaces,
         storage_service_interfaces,
-    )
+    ))
 }
 
 /// Creates a network runtime for the given network config
@@ -4,7 +4,9 @@
 use anyhow::{anyhow, Result};
 use aptos_backup_service::start_backup_service;
 use aptos_config::{config::NodeConfig, utils::get_genesis_txn};
-use aptos_db::{fast_sync_storage_wrapper::FastSyncStorageWrapper, AptosDB};
+use aptos_db::{
+    fast_sync_storage_wrapper::FastSyncStorageWrapper, tiered_storage::ColdLedgerStore, AptosDB,
+};
 use aptos_db_indexer::db_indexer::InternalIndexerDB;
 use aptos_executor::db_bootstrapper::maybe_bootstrap;
 use aptos_indexer_grpc_table_info::internal_indexer_db_service::InternalIndexerDBService;
@@ -60,10 +62,17 @@ pub(crate) fn bootstrap_db(
         (None, None)
     };
 
+    let cold_ledger_store = if node_config.storage.tiered_storage_config.enable {
+        Some(create_cold_ledger_store(node_config)?)
+    } else {
+        None
+    };
+
     let (aptos_db_reader, db_rw, backup_service) = match FastSyncStorageWrapper::initialize_dbs(
         node_config,
         internal_indexer_db.clone(),
         update_sender,
+        cold_ledger_store,
     )? {
         Either::Left(db) => {
             let (db_arc, db_rw) = DbReaderWriter::wrap(db);
@@ -106,6 +115,24 @@ pub(crate) fn bootstrap_db(
     ))
 }
 
+/// Creates the cold tier of the tiered ledger storage on the configured backup storage
+#[cfg(feature = "tiered-storage")]
+fn create_cold_ledger_store(node_config: &NodeConfig) -> Result<Arc<dyn ColdLedgerStore>> {
+    use aptos_backup_cli::backup_types::transaction::cold_storage::BackupStorageColdLedgerStore;
+
+    let store =
+        BackupStorageColdLedgerStore::new_from_config(&node_config.storage.tiered_storage_config)
+            .map_err(|err| anyhow!("Cold ledger storage failed to initialize {}", err))?;
+    Ok(Arc::new(store))
+}
+
+#[cfg(not(feature = "tiered-storage"))]
+fn create_cold_ledger_store(_node_config: &NodeConfig) -> Result<Arc<dyn ColdLedgerStore>> {
+    Err(anyhow!(
+        "Tiered storage is enabled, but the node is built without the tiered-storage feature."
+    ))
+}
+
 /// In consensus-only mode, return a in-memory based [FakeAptosDB] and
 /// do not run the backup service.
 #[cfg(feature = "consensus-only-perf-test")]
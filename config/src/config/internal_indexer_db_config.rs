@@ -15,6 +15,10 @@ pub struct InternalIndexerDBConfig {
     pub enable_event_v2_translation: bool,
     pub event_v2_translation_ignores_below_version: u64,
     pub enable_statekeys: bool,
+    /// Whether to index every transaction that touched an account (not only those sent by it)
+    pub enable_account_transactions: bool,
+    /// Whether to index events by their type (e.g., `0x1::coin::DepositEvent`)
+    pub enable_event_by_type: bool,
     pub batch_size: usize,
 }
 
@@ -33,6 +37,8 @@ impl InternalIndexerDBConfig {
             enable_event_v2_translation,
             event_v2_translation_ignores_below_version,
             enable_statekeys,
+            enable_account_transactions: false,
+            enable_event_by_type: false,
             batch_size,
         }
     }
@@ -57,8 +63,20 @@ impl InternalIndexerDBConfig {
         self.enable_statekeys
     }
 
+    pub fn enable_account_transactions(&self) -> bool {
+        self.enable_account_transactions
+    }
+
+    pub fn enable_event_by_type(&self) -> bool {
+        self.enable_event_by_type
+    }
+
     pub fn is_internal_indexer_db_enabled(&self) -> bool {
-        self.enable_transaction || self.enable_event || self.enable_statekeys
+        self.enable_transaction
+            || self.enable_event
+            || self.enable_statekeys
+            || self.enable_account_transactions
+            || self.enable_event_by_type
     }
 
     pub fn batch_size(&self) -> usize {
@@ -74,6 +92,8 @@ impl Default for InternalIndexerDBConfig {
             enable_event_v2_translation: false,
             event_v2_translation_ignores_below_version: 0,
             enable_statekeys: false,
+            enable_account_transactions: false,
+            enable_event_by_type: false,
             batch_size: 10_000,
         }
     }
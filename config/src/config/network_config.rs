@@ -52,6 +52,9 @@ pub const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024; /* 64 MiB */
 pub const CONNECTION_BACKOFF_BASE: u64 = 2;
 pub const IP_BYTE_BUCKET_RATE: usize = 102400 /* 100 KiB */;
 pub const IP_BYTE_BUCKET_SIZE: usize = IP_BYTE_BUCKET_RATE;
+pub const OUTBOUND_SCHEDULER_QUANTUM_BYTES: u64 = 64 * 1024; /* 64 KiB: bytes written per unit of protocol weight each round */
+pub const MAX_PENDING_OUTBOUND_MESSAGES_PER_PROTOCOL: usize = 1024;
+pub const MAX_PENDING_OUTBOUND_MESSAGES: usize = 4096; /* Across all protocols of a single connection */
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
@@ -124,6 +127,12 @@ pub struct NetworkConfig {
     pub max_parallel_deserialization_tasks: Option<usize>,
     /// Whether or not to enable latency aware peer dialing
     pub enable_latency_aware_dialing: bool,
+    /// The configuration for scheduling outbound messages on each connection
+    pub outbound_scheduler_config: OutboundSchedulerConfig,
+    /// If set, the (decrypted) traffic of each connection is recorded to a file in
+    /// this directory. This is intended for debugging only: recordings grow without
+    /// bound and contain all messages exchanged with peers.
+    pub traffic_recording_dir: Option<PathBuf>,
 }
 
 impl Default for NetworkConfig {
@@ -165,6 +174,8 @@ impl NetworkConfig {
             outbound_tx_buffer_size_bytes: None,
             max_parallel_deserialization_tasks: None,
             enable_latency_aware_dialing: true,
+            outbound_scheduler_config: OutboundSchedulerConfig::default(),
+            traffic_recording_dir: None,
         };
 
         // Configure the number of parallel deserialization tasks
@@ -347,6 +358,7 @@ pub enum DiscoveryMethod {
     Onchain,
     File(FileDiscovery),
     Rest(RestDiscovery),
+    Dns(DnsDiscovery),
     None,
 }
 
@@ -364,6 +376,21 @@ pub struct RestDiscovery {
     pub interval_secs: u64,
 }
 
+/// Discovers seed peers from DNS records, so that seed lists can be rotated without
+/// redeploying configs. Peers are read from:
+/// - TXT records on `domain`, each of the form `addr=<network address> [peer_id=<peer id>]`.
+/// - SRV records on `_aptosnet._tcp.<domain>`, where each SRV target also holds
+///   a TXT record of the form `key=<x25519 public key>`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct DnsDiscovery {
+    pub domain: String,
+    pub interval_secs: u64,
+    /// Seed addresses that are always merged into the discovered peers
+    #[serde(default)]
+    pub static_seed_addrs: Vec<NetworkAddress>,
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct RateLimitConfig {
@@ -388,6 +415,58 @@ impl Default for RateLimitConfig {
     }
 }
 
+/// The configuration for scheduling outbound messages on a single connection.
+/// Consensus messages can be given strict priority, while all other protocols
+/// share the connection according to their weights (and optional bandwidth caps).
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutboundSchedulerConfig {
+    /// Whether consensus messages are always written before those of other protocols
+    pub strict_consensus_priority: bool,
+    /// The number of bytes a protocol with a weight of 1 may write in each scheduling round
+    pub quantum_bytes: u64,
+    /// The maximum number of pending messages per protocol (the oldest messages are dropped)
+    pub max_pending_messages_per_protocol: usize,
+    /// The maximum number of pending messages across all protocols. Once exceeded,
+    /// the oldest message of the protocol with the most pending messages is dropped.
+    pub max_pending_messages: usize,
+    /// Per-protocol scheduling overrides, keyed by protocol name (e.g., "MempoolDirectSend").
+    /// Protocols without an entry use `ProtocolSchedulingConfig::default()`. Unknown
+    /// protocol names are rejected when the network is built.
+    pub protocol_configs: HashMap<String, ProtocolSchedulingConfig>,
+}
+
+impl Default for OutboundSchedulerConfig {
+    fn default() -> Self {
+        Self {
+            strict_consensus_priority: true,
+            quantum_bytes: OUTBOUND_SCHEDULER_QUANTUM_BYTES,
+            max_pending_messages_per_protocol: MAX_PENDING_OUTBOUND_MESSAGES_PER_PROTOCOL,
+            max_pending_messages: MAX_PENDING_OUTBOUND_MESSAGES,
+            protocol_configs: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProtocolSchedulingConfig {
+    /// The relative share of the connection given to the protocol
+    pub weight: u32,
+    /// The maximum number of bytes/s the protocol may write to a single peer.
+    /// If not specified, the protocol is not capped.
+    pub max_bytes_per_sec: Option<u64>,
+}
+
+impl Default for ProtocolSchedulingConfig {
+    fn default() -> Self {
+        Self {
+            weight: 1,
+            max_bytes_per_sec: None,
+        }
+    }
+}
+
 pub type PeerSet = HashMap<PeerId, Peer>;
 
 // TODO: Combine with RoleType?
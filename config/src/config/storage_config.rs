@@ -248,14 +248,28 @@ pub struct StorageConfig {
     pub ensure_rlimit_nofile: u64,
     /// panic if failed to ensure `ulimit -n`
     pub assert_rlimit_nofile: bool,
+    /// Background verification of the data already committed to the DB
+    pub consistency_checker_config: ConsistencyCheckerConfig,
+    /// Moving the ledger history pruned from the DB to a backup storage
+    pub tiered_storage_config: TieredStorageConfig,
 }
 
 pub const NO_OP_STORAGE_PRUNER_CONFIG: PrunerConfig = PrunerConfig {
     ledger_pruner_config: LedgerPrunerConfig {
         enable: false,
         prune_window: 0,
+        prune_window_secs: None,
         batch_size: 0,
         user_pruning_window_offset: 0,
+        retention_overrides: LedgerRetentionOverrides {
+            events: None,
+            transactions: None,
+            transaction_by_hash: None,
+            transaction_infos: None,
+            transaction_auxiliary_data: None,
+            persisted_auxiliary_info: None,
+            write_sets: None,
+        },
     },
     state_merkle_pruner_config: StateMerklePrunerConfig {
         enable: false,
@@ -279,12 +293,95 @@ pub struct LedgerPrunerConfig {
     /// being big in size, we might want to configure a smaller window for state store vs other
     /// store.
     pub prune_window: u64,
+    /// If set, the ledger data is pruned by age instead of by `prune_window`, i.e. this many
+    /// seconds of history are kept in terms of block timestamps. Requires storage sharding, which
+    /// is what keeps the block info of the whole history.
+    pub prune_window_secs: Option<u64>,
     /// Batch size of the versions to be sent to the ledger pruner - this is to avoid slowdown due to
     /// issuing too many DB calls and batch prune instead. For ledger pruner, this means the number
     /// of versions to prune a time.
     pub batch_size: usize,
     /// The offset for user pruning window to adjust
     pub user_pruning_window_offset: u64,
+    /// Per category retention policies. A category without an override is pruned along with
+    /// everything else according to `prune_window` or `prune_window_secs`.
+    pub retention_overrides: LedgerRetentionOverrides,
+}
+
+impl LedgerPrunerConfig {
+    /// Returns the retention of the categories without an override.
+    pub fn retention(&self) -> LedgerRetention {
+        match self.prune_window_secs {
+            Some(window_secs) => LedgerRetention::Seconds(window_secs),
+            None => LedgerRetention::Versions(self.prune_window),
+        }
+    }
+}
+
+/// How long a category of ledger data is kept.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerRetention {
+    /// Window size in versions.
+    Versions(u64),
+    /// Window size in seconds, in terms of block timestamps. Requires storage sharding, which is
+    /// what keeps the block info of the whole history.
+    Seconds(u64),
+    /// Never prune.
+    Forever,
+}
+
+impl LedgerRetention {
+    /// Returns whether this is known to keep at least as much history as `other`. Windows in
+    /// versions and in seconds can't be compared.
+    pub fn keeps_at_least(self, other: Self) -> bool {
+        match (self, other) {
+            (Self::Forever, _) => true,
+            (_, Self::Forever) => false,
+            (Self::Versions(a), Self::Versions(b)) | (Self::Seconds(a), Self::Seconds(b)) => a >= b,
+            _ => false,
+        }
+    }
+}
+
+/// Overrides the ledger prune window for individual categories of ledger data.
+///
+/// A category with an override is pruned according to it alone, so it can be kept for longer or
+/// shorter than the rest. APIs serving several categories at once (e.g. transactions along with
+/// their events) need all of them to be kept. The transaction infos and the transaction
+/// accumulator, which prove the data of every category, are kept for the longest of all the
+/// windows, including the override of `transaction_infos` itself.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LedgerRetentionOverrides {
+    pub events: Option<LedgerRetention>,
+    pub transactions: Option<LedgerRetention>,
+    /// The index is pruned by hashing the pruned transactions, so the only supported override
+    /// is `forever`, and only along with keeping the transactions forever.
+    pub transaction_by_hash: Option<LedgerRetention>,
+    pub transaction_infos: Option<LedgerRetention>,
+    pub transaction_auxiliary_data: Option<LedgerRetention>,
+    pub persisted_auxiliary_info: Option<LedgerRetention>,
+    pub write_sets: Option<LedgerRetention>,
+}
+
+impl LedgerRetentionOverrides {
+    fn iter(&self) -> impl Iterator<Item = (&'static str, LedgerRetention)> {
+        [
+            ("events", self.events),
+            ("transactions", self.transactions),
+            ("transaction_by_hash", self.transaction_by_hash),
+            ("transaction_infos", self.transaction_infos),
+            (
+                "transaction_auxiliary_data",
+                self.transaction_auxiliary_data,
+            ),
+            ("persisted_auxiliary_info", self.persisted_auxiliary_info),
+            ("write_sets", self.write_sets),
+        ]
+        .into_iter()
+        .filter_map(|(name, retention)| retention.map(|r| (name, r)))
+    }
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -336,8 +433,10 @@ impl Default for LedgerPrunerConfig {
         LedgerPrunerConfig {
             enable: true,
             prune_window: 90_000_000,
+            prune_window_secs: None,
             batch_size: 5_000,
             user_pruning_window_offset: 200_000,
+            retention_overrides: LedgerRetentionOverrides::default(),
         }
     }
 }
@@ -377,6 +476,60 @@ impl Default for EpochSnapshotPrunerConfig {
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConsistencyCheckerConfig {
+    /// Boolean to enable/disable the consistency checker. When enabled, a background thread
+    /// continuously samples committed version ranges and recomputes their hashes, catching disk
+    /// corruption before it spreads to backups.
+    pub enable: bool,
+    /// Time to wait between two samples.
+    pub sample_interval_ms: u64,
+    /// Number of consecutive versions verified in each sample.
+    pub num_versions_per_sample: usize,
+    /// Number of state tree leaves spot-checked against the state values in each sample.
+    pub num_state_leaves_per_sample: usize,
+}
+
+impl Default for ConsistencyCheckerConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            sample_interval_ms: 10_000,
+            num_versions_per_sample: 1_000,
+            num_state_leaves_per_sample: 100,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TieredStorageConfig {
+    /// Boolean to enable/disable tiered storage. When enabled, the ledger pruner uploads the
+    /// transactions it's about to prune to the backup storage, in the transaction backup format,
+    /// and reads below the prune window are served from there. Requires the node to be built
+    /// with the `tiered-storage` feature.
+    pub enable: bool,
+    /// Directory on a local file system to hold the archived transactions.
+    pub local_fs_dir: Option<PathBuf>,
+    /// Config file of a command adapter backup storage, e.g. one for an object store. Exactly one
+    /// of this and `local_fs_dir` needs to be set.
+    pub command_adapter_config: Option<PathBuf>,
+    /// Number of archived chunks to keep in memory.
+    pub chunk_cache_size: usize,
+}
+
+impl Default for TieredStorageConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            local_fs_dir: None,
+            command_adapter_config: None,
+            chunk_cache_size: 16,
+        }
+    }
+}
+
 impl Default for StorageConfig {
     fn default() -> StorageConfig {
         StorageConfig {
@@ -397,6 +550,8 @@ impl Default for StorageConfig {
             max_num_nodes_per_lru_cache_shard: DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
             ensure_rlimit_nofile: 0,
             assert_rlimit_nofile: false,
+            consistency_checker_config: ConsistencyCheckerConfig::default(),
+            tiered_storage_config: TieredStorageConfig::default(),
         }
     }
 }
@@ -693,6 +848,87 @@ impl ConfigSanitizer for StorageConfig {
             }
         }
 
+        let ledger_retention = config
+            .storage_pruner_config
+            .ledger_pruner_config
+            .retention();
+        if matches!(ledger_retention, LedgerRetention::Seconds(_))
+            && !config.rocksdb_configs.enable_storage_sharding
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "Ledger prune_window_secs requires storage sharding to be enabled.".to_string(),
+            ));
+        }
+        let retention_overrides = &config
+            .storage_pruner_config
+            .ledger_pruner_config
+            .retention_overrides;
+        for (category, retention) in retention_overrides.iter() {
+            match retention {
+                LedgerRetention::Seconds(_) if !config.rocksdb_configs.enable_storage_sharding => {
+                    return Err(Error::ConfigSanitizerFailed(
+                        sanitizer_name,
+                        format!("Ledger retention override for {category} in seconds requires storage sharding to be enabled."),
+                    ));
+                },
+                // The data is archived to the cold tier right before the ledger pruner prunes it.
+                _ if config.tiered_storage_config.enable
+                    && !retention.keeps_at_least(ledger_retention) =>
+                {
+                    return Err(Error::ConfigSanitizerFailed(
+                        sanitizer_name,
+                        format!("With tiered storage enabled, the ledger retention override for {category} must be known to be no shorter than the ledger prune window."),
+                    ));
+                },
+                _ => {},
+            }
+        }
+        if retention_overrides
+            .transaction_by_hash
+            .is_some_and(|retention| retention != LedgerRetention::Forever)
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The only supported ledger retention override for transaction_by_hash is forever."
+                    .to_string(),
+            ));
+        }
+        // The index points to the transactions, so it's of no use once they are pruned.
+        if retention_overrides.transaction_by_hash.is_some()
+            && retention_overrides.transactions != Some(LedgerRetention::Forever)
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The ledger retention override for transaction_by_hash requires transactions to be kept forever as well."
+                    .to_string(),
+            ));
+        }
+
+        let tiered_storage_config = &config.tiered_storage_config;
+        if tiered_storage_config.enable {
+            if tiered_storage_config.local_fs_dir.is_some()
+                == tiered_storage_config.command_adapter_config.is_some()
+            {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "Exactly one of local_fs_dir and command_adapter_config needs to be set for tiered storage.".to_string(),
+                ));
+            }
+            if !config.storage_pruner_config.ledger_pruner_config.enable {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "Tiered storage requires the ledger pruner to be enabled.".to_string(),
+                ));
+            }
+            if tiered_storage_config.chunk_cache_size == 0 {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "chunk_cache_size of tiered storage must be positive.".to_string(),
+                ));
+            }
+        }
+
         Ok(())
     }
 }
@@ -700,8 +936,10 @@ impl ConfigSanitizer for StorageConfig {
 #[cfg(test)]
 mod test {
     use crate::config::{
-        config_optimizer::ConfigOptimizer, NodeConfig, NodeType, PersistableConfig, PrunerConfig,
-        RocksdbConfig, ShardPathConfig, ShardedDbPathConfig, StorageConfig,
+        config_optimizer::ConfigOptimizer, config_sanitizer::ConfigSanitizer, Error,
+        LedgerRetention, LedgerRetentionOverrides, NodeConfig, NodeType, PersistableConfig,
+        PrunerConfig, RocksdbConfig, ShardPathConfig, ShardedDbPathConfig, StorageConfig,
+        TieredStorageConfig,
     };
     use aptos_types::chain_id::ChainId;
 
@@ -780,6 +1018,155 @@ mod test {
         assert!(path_overrides.get_shard_paths().is_err());
     }
 
+    #[test]
+    fn test_sanitize_tiered_storage() {
+        let mut node_config = NodeConfig {
+            storage: StorageConfig {
+                tiered_storage_config: TieredStorageConfig {
+                    enable: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that sanitization fails without a backup storage
+        let error = StorageConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Verify that sanitization succeeds with exactly one backup storage
+        node_config.storage.tiered_storage_config.local_fs_dir = Some("/opt/aptos/archive".into());
+        StorageConfig::sanitize(&node_config, NodeType::Validator, None).unwrap();
+
+        // Verify that sanitization fails with the ledger pruner disabled
+        node_config
+            .storage
+            .storage_pruner_config
+            .ledger_pruner_config
+            .enable = false;
+        let error = StorageConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+        node_config
+            .storage
+            .storage_pruner_config
+            .ledger_pruner_config
+            .enable = true;
+
+        // Verify that sanitization succeeds with a retention override longer than the prune
+        // window, and fails with one that is shorter or can't be compared
+        let ledger_pruner_config = &mut node_config
+            .storage
+            .storage_pruner_config
+            .ledger_pruner_config;
+        ledger_pruner_config.retention_overrides.events = Some(LedgerRetention::Forever);
+        StorageConfig::sanitize(&node_config, NodeType::Validator, None).unwrap();
+        for retention in [
+            LedgerRetention::Versions(1_000),
+            LedgerRetention::Seconds(90 * 24 * 3600),
+        ] {
+            node_config
+                .storage
+                .storage_pruner_config
+                .ledger_pruner_config
+                .retention_overrides
+                .events = Some(retention);
+            let error =
+                StorageConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
+            assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+        }
+    }
+
+    #[test]
+    fn test_sanitize_ledger_retention_overrides() {
+        let mut node_config = NodeConfig::default();
+        node_config
+            .storage
+            .storage_pruner_config
+            .ledger_pruner_config
+            .retention_overrides = LedgerRetentionOverrides {
+            events: Some(LedgerRetention::Seconds(90 * 24 * 3600)),
+            write_sets: Some(LedgerRetention::Seconds(7 * 24 * 3600)),
+            transactions: Some(LedgerRetention::Forever),
+            transaction_by_hash: Some(LedgerRetention::Forever),
+            ..Default::default()
+        };
+
+        // Verify that sanitization succeeds with sharding enabled
+        StorageConfig::sanitize(&node_config, NodeType::Validator, None).unwrap();
+
+        // Verify that sanitization fails for age based retention without sharding
+        node_config.storage.rocksdb_configs.enable_storage_sharding = false;
+        let error = StorageConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+        node_config.storage.rocksdb_configs.enable_storage_sharding = true;
+
+        // Verify that sanitization succeeds for an age based ledger prune window with sharding
+        // enabled, and fails without
+        node_config
+            .storage
+            .storage_pruner_config
+            .ledger_pruner_config
+            .prune_window_secs = Some(30 * 24 * 3600);
+        StorageConfig::sanitize(&node_config, NodeType::Validator, None).unwrap();
+        node_config.storage.rocksdb_configs.enable_storage_sharding = false;
+        let error = StorageConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+        node_config.storage.rocksdb_configs.enable_storage_sharding = true;
+
+        // Verify that sanitization fails if the transaction by hash index is not kept forever
+        node_config
+            .storage
+            .storage_pruner_config
+            .ledger_pruner_config
+            .retention_overrides
+            .transaction_by_hash = Some(LedgerRetention::Versions(1_000_000_000));
+        let error = StorageConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Verify that sanitization fails if the transactions are not kept forever along with
+        // the transaction by hash index
+        let retention_overrides = &mut node_config
+            .storage
+            .storage_pruner_config
+            .ledger_pruner_config
+            .retention_overrides;
+        retention_overrides.transaction_by_hash = Some(LedgerRetention::Forever);
+        retention_overrides.transactions = Some(LedgerRetention::Versions(1_000_000_000));
+        let error = StorageConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    #[test]
+    fn test_ledger_retention_overrides_from_yaml() {
+        let config: PrunerConfig = serde_yaml::from_str(
+            r#"
+            ledger_pruner_config:
+              prune_window_secs: 2592000
+              retention_overrides:
+                events:
+                  seconds: 7776000
+                transactions:
+                  versions: 500000000
+                transaction_by_hash: forever
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.ledger_pruner_config.retention(),
+            LedgerRetention::Seconds(2_592_000)
+        );
+        assert_eq!(
+            config.ledger_pruner_config.retention_overrides,
+            LedgerRetentionOverrides {
+                events: Some(LedgerRetention::Seconds(7_776_000)),
+                transactions: Some(LedgerRetention::Versions(500_000_000)),
+                transaction_by_hash: Some(LedgerRetention::Forever),
+                ..Default::default()
+            }
+        );
+    }
+
     #[test]
     fn test_optimize_ensure_rlimit_nofile() {
         let mut node_config = NodeConfig::default();
@@ -13,6 +13,13 @@ use prometheus::TextEncoder;
 // The metric key for the consensus execution gauge
 const CONSENSUS_EXECUTION_GAUGE: &str = "aptos_state_sync_consensus_executing_gauge{}";
 
+// The metric key prefix for the storage consistency check failure counters
+const STORAGE_CONSISTENCY_CHECK_FAILURES: &str = "aptos_storage_consistency_check_failures{";
+
+// Useful string constants
+pub const STORAGE_CONSISTENCY_CHECK_DISABLED_MESSAGE: &str =
+    "The storage consistency checker is disabled!";
+
 /// Handles a consensus health check request. This method returns
 /// 200 if the node is currently participating in consensus.
 ///
@@ -47,6 +54,48 @@ pub async fn handle_consensus_health_check(node_config: &NodeConfig) -> (StatusC
     )
 }
 
+/// Handles a storage consistency check request. This method returns
+/// 200 if the background consistency checker has not found any
+/// inconsistencies in the DB since the node started.
+pub fn handle_storage_consistency_check(node_config: &NodeConfig) -> (StatusCode, Body, String) {
+    // Verify the consistency checker is enabled. If not, return an error.
+    if !node_config.storage.consistency_checker_config.enable {
+        return (
+            StatusCode::BAD_REQUEST,
+            Body::from(STORAGE_CONSISTENCY_CHECK_DISABLED_MESSAGE),
+            CONTENT_TYPE_TEXT.into(),
+        );
+    }
+
+    // Collect all the checks that have reported failures
+    let mut failures: Vec<String> = utils::get_all_metrics()
+        .into_iter()
+        .filter(|(name, value)| {
+            name.starts_with(STORAGE_CONSISTENCY_CHECK_FAILURES)
+                && value.parse::<f64>().is_ok_and(|value| value > 0.0)
+        })
+        .map(|(name, value)| format!("{} {}", name, value))
+        .collect();
+    if failures.is_empty() {
+        return (
+            StatusCode::OK,
+            Body::from("Storage consistency check passed!"),
+            CONTENT_TYPE_TEXT.into(),
+        );
+    }
+
+    // Otherwise, inconsistencies were found
+    failures.sort();
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Body::from(format!(
+            "Storage consistency check failed! Failures:\n{}",
+            failures.join("\n")
+        )),
+        CONTENT_TYPE_TEXT.into(),
+    )
+}
+
 /// Handles a new forge metrics request
 pub fn handle_forge_metrics() -> (StatusCode, Body, String) {
     // Get and encode the metrics
@@ -38,6 +38,7 @@ pub const INDEX_PATH: &str = "/";
 pub const JSON_METRICS_PATH: &str = "/json_metrics";
 pub const METRICS_PATH: &str = "/metrics";
 pub const PEER_INFORMATION_PATH: &str = "/peer_information";
+pub const STORAGE_CONSISTENCY_CHECK_PATH: &str = "/storage_consistency_check";
 pub const SYSTEM_INFORMATION_PATH: &str = "/system_information";
 
 // Useful string constants
@@ -153,6 +154,11 @@ async fn serve_requests(
                 peers_and_metadata,
             )
         },
+        STORAGE_CONSISTENCY_CHECK_PATH => {
+            // /storage_consistency_check
+            // Exposes the result of the background storage consistency checker
+            metrics::handle_storage_consistency_check(&node_config)
+        },
         SYSTEM_INFORMATION_PATH => {
             // /system_information
             // Exposes the system and build information
@@ -5,11 +5,13 @@ use crate::{
     server::{
         configuration::CONFIGURATION_DISABLED_MESSAGE,
         identity_information::IDENTITY_INFO_DISABLED_MESSAGE,
+        metrics::STORAGE_CONSISTENCY_CHECK_DISABLED_MESSAGE,
         peer_information::PEER_INFO_DISABLED_MESSAGE, serve_requests,
         system_information::SYS_INFO_DISABLED_MESSAGE, utils::get_all_metrics,
     },
     CONFIGURATION_PATH, FORGE_METRICS_PATH, IDENTITY_INFORMATION_PATH, INDEX_PATH,
-    JSON_METRICS_PATH, METRICS_PATH, PEER_INFORMATION_PATH, SYSTEM_INFORMATION_PATH,
+    JSON_METRICS_PATH, METRICS_PATH, PEER_INFORMATION_PATH, STORAGE_CONSISTENCY_CHECK_PATH,
+    SYSTEM_INFORMATION_PATH,
 };
 use aptos_config::config::{AptosDataClientConfig, BaseConfig, Identity, NodeConfig};
 use aptos_data_client::client::AptosDataClient;
@@ -21,7 +23,10 @@ use assert_approx_eq::assert_approx_eq;
 use futures::executor::block_on;
 use hyper::{body, Body, Method, Request, Response, StatusCode};
 use once_cell::sync::Lazy;
-use prometheus::{proto::MetricFamily, register_int_counter, Counter, IntCounter, Opts, Registry};
+use prometheus::{
+    proto::MetricFamily, register_int_counter, register_int_counter_vec, Counter, IntCounter,
+    IntCounterVec, Opts, Registry,
+};
 use rusty_fork::rusty_fork_test;
 use std::{collections::HashMap, io::read_to_string, string::String, sync::Arc};
 
@@ -31,6 +36,16 @@ const INT_COUNTER_NAME: &str = "INT_COUNTER";
 static INT_COUNTER: Lazy<IntCounter> =
     Lazy::new(|| register_int_counter!(INT_COUNTER_NAME, "An integer counter").unwrap());
 
+// Mirrors the failure counter reported by the storage consistency checker
+static STORAGE_CONSISTENCY_CHECK_FAILURES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_storage_consistency_check_failures",
+        "Storage consistency check failures",
+        &["check"]
+    )
+    .unwrap()
+});
+
 #[tokio::test]
 async fn test_inspect_configuration() {
     // Create a validator config
@@ -92,6 +107,7 @@ async fn test_inspect_index() {
     assert!(response_body_string.contains(JSON_METRICS_PATH));
     assert!(response_body_string.contains(METRICS_PATH));
     assert!(response_body_string.contains(PEER_INFORMATION_PATH));
+    assert!(response_body_string.contains(STORAGE_CONSISTENCY_CHECK_PATH));
     assert!(response_body_string.contains(SYSTEM_INFORMATION_PATH));
 }
 
@@ -159,6 +175,40 @@ async fn test_inspect_metrics() {
     assert!(response_body_string.contains(INT_COUNTER_NAME));
 }
 
+#[tokio::test]
+async fn test_inspect_storage_consistency_check() {
+    // Create a validator config
+    let mut config = NodeConfig::get_default_validator_config();
+
+    // Disable the consistency checker and ping the endpoint
+    config.storage.consistency_checker_config.enable = false;
+    let mut response = send_get_request_to_path(&config, STORAGE_CONSISTENCY_CHECK_PATH).await;
+    let response_body = body::to_bytes(response.body_mut()).await.unwrap();
+
+    // Verify that the response contains an error
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(response_body, STORAGE_CONSISTENCY_CHECK_DISABLED_MESSAGE);
+
+    // Enable the consistency checker and ping the endpoint
+    config.storage.consistency_checker_config.enable = true;
+    let response = send_get_request_to_path(&config, STORAGE_CONSISTENCY_CHECK_PATH).await;
+
+    // Verify that the check passes (no failures have been reported)
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Report a failure and ping the endpoint again
+    STORAGE_CONSISTENCY_CHECK_FAILURES
+        .with_label_values(&["write_set"])
+        .inc();
+    let mut response = send_get_request_to_path(&config, STORAGE_CONSISTENCY_CHECK_PATH).await;
+    let response_body = body::to_bytes(response.body_mut()).await.unwrap();
+    let response_body_string = read_to_string(response_body.as_ref()).unwrap();
+
+    // Verify that the check fails and reports the failing check
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(response_body_string.contains("write_set"));
+}
+
 #[tokio::test]
 async fn test_inspect_system_information() {
     // Create a validator node config
@@ -244,7 +244,7 @@ impl TxnOptions {
     }
 
     /// Simulates a transaction locally, using the debugger to fetch required data from remote.
-    async fn simulate_using_debugger<F>(
+    pub(crate) async fn simulate_using_debugger<F>(
         &self,
         payload: TransactionPayload,
         execute: F,
@@ -99,6 +99,7 @@ pub const MOVE_FOLDER_GIT_IGNORE: &str = indoc! {"
   .aptos/
   build/
   .coverage_map.mvcov
+  .mutation_cache.json
   .trace"
 };
 
@@ -0,0 +1,594 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::types::{
+        CliCommand, CliError, CliTypedResult, MovePackageOptions, ProfileOptions, RestOptions,
+    },
+    move_tool::{CachedPackageRegistry, IncludedArtifacts},
+};
+use aptos_api_types::AptosErrorCode;
+use aptos_framework::{natives::code::UpgradePolicy, BuiltPackage};
+use aptos_rest_client::{error::RestError, Client};
+use aptos_types::account_address::AccountAddress;
+use async_trait::async_trait;
+use clap::Parser;
+use legacy_move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
+use move_binary_format::{
+    access::ModuleAccess, binary_views::BinaryIndexedView, file_format::Bytecode, CompiledModule,
+};
+use move_bytecode_source_map::mapping::SourceMapping;
+use move_core_types::language_storage::ModuleId;
+use move_disassembler::disassembler::{Disassembler, DisassemblerOptions};
+use move_ir_types::location::Spanned;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Audits the dependencies of a package against their on-chain versions
+///
+/// Every dependency is looked up in the package registry of its address. The audit flags
+/// dependencies which are not on-chain, upgrade policies which make publishing this package fail,
+/// and dependencies whose on-chain source digest differs from the one of the locally resolved
+/// sources, e.g., because they were upgraded since the version this package pins. For changed
+/// dependencies, it lists the functions of this package calling dependency functions whose
+/// bytecode changed.
+#[derive(Parser)]
+pub struct AuditPackage {
+    /// Fail if the audit reports any finding
+    #[clap(long)]
+    pub(crate) fail_on_findings: bool,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageOptions,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+/// Result of auditing all dependencies of a package.
+#[derive(Debug, Serialize)]
+pub struct AuditReport {
+    pub package: String,
+    pub upgrade_policy: String,
+    pub dependencies: Vec<DependencyAudit>,
+}
+
+impl AuditReport {
+    pub fn num_findings(&self) -> usize {
+        self.dependencies.iter().map(|dep| dep.findings.len()).sum()
+    }
+}
+
+/// Result of auditing a single dependency.
+#[derive(Debug, Serialize)]
+pub struct DependencyAudit {
+    pub package: String,
+    pub address: AccountAddress,
+    /// The on-chain upgrade policy, if the package is on-chain.
+    pub upgrade_policy: Option<String>,
+    /// The number of times the package was upgraded on-chain, if the package is on-chain.
+    pub upgrade_number: Option<u64>,
+    pub findings: Vec<Finding>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Finding {
+    /// The package is not published at its address.
+    NotOnChain,
+    /// The on-chain upgrade policy is weaker than the one of this package, so publishing fails.
+    WeakerUpgradePolicy,
+    /// The package can be changed arbitrarily, so it is not safe to depend on, and publishing fails
+    /// unless this package is published at the same address.
+    ArbitraryUpgradePolicy,
+    /// The on-chain package was built from different sources than the locally resolved one.
+    SourceChanged {
+        local_source_digest: String,
+        /// Empty if the package was published without a source digest, in which case bytecode is
+        /// compared instead.
+        on_chain_source_digest: String,
+        added_modules: Vec<String>,
+        removed_modules: Vec<String>,
+    },
+    /// Functions of this package call dependency functions whose bytecode changed on-chain, or
+    /// which no longer exist.
+    CallsChangedFunctions { calls: Vec<AffectedCall> },
+}
+
+#[derive(Debug, Serialize)]
+pub struct AffectedCall {
+    pub caller: String,
+    pub callee: String,
+    pub removed: bool,
+}
+
+#[async_trait]
+impl CliCommand<AuditReport> for AuditPackage {
+    fn command_name(&self) -> &'static str {
+        "AuditPackage"
+    }
+
+    async fn execute(self) -> CliTypedResult<AuditReport> {
+        let build_options = IncludedArtifacts::None.build_options(&self.move_options)?;
+        let build_config = BuiltPackage::create_build_config(&build_options)
+            .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+        let resolved_graph = BuiltPackage::prepare_resolution_graph(
+            self.move_options.get_package_path()?,
+            build_config.clone(),
+        )
+        .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+        // The same digests are stored in the on-chain metadata of packages when published.
+        let source_digests = resolved_graph
+            .package_table
+            .iter()
+            .map(|(name, package)| (name.to_string(), package.source_digest.to_string()))
+            .collect::<BTreeMap<_, _>>();
+        let pack = BuiltPackage::build_with_external_checks(
+            resolved_graph,
+            build_options,
+            build_config,
+            vec![],
+        )
+        .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+        let root_modules = pack.modules().collect::<Vec<_>>();
+        let metadata = pack.extract_metadata()?;
+        let package_address = pack.modules().next().map(|module| *module.self_addr());
+
+        // Group the locally resolved dependency modules by package.
+        let mut local_deps: BTreeMap<(AccountAddress, String), Vec<&CompiledModule>> =
+            BTreeMap::new();
+        for (package_name, unit) in &pack.package.deps_compiled_units {
+            if let CompiledUnit::Module(NamedCompiledModule { module, .. }) = &unit.unit {
+                local_deps
+                    .entry((*module.self_addr(), package_name.as_str().to_string()))
+                    .or_default()
+                    .push(module);
+            }
+        }
+
+        let url = self.rest_options.url(&self.profile_options)?;
+        let client = Client::new(url.clone());
+        let mut registries = BTreeMap::new();
+        let mut dependencies = vec![];
+        for ((address, package_name), local_modules) in local_deps {
+            if !registries.contains_key(&address) {
+                let registry =
+                    match CachedPackageRegistry::create(url.clone(), address, false).await {
+                        Ok(registry) => Some(registry),
+                        Err(err) if is_not_found(&err) => None,
+                        Err(err) => {
+                            return Err(CliError::ApiError(format!(
+                                "Failed to fetch package registry at {}: {:#}",
+                                address, err
+                            )))
+                        },
+                    };
+                registries.insert(address, registry);
+            }
+            let on_chain_package = match &registries[&address] {
+                Some(registry) => registry.get_package(&package_name).await.ok(),
+                None => None,
+            };
+            let Some(on_chain_package) = on_chain_package else {
+                dependencies.push(DependencyAudit {
+                    package: package_name,
+                    address,
+                    upgrade_policy: None,
+                    upgrade_number: None,
+                    findings: vec![Finding::NotOnChain],
+                });
+                continue;
+            };
+
+            // Mirrors the checks done by `code::publish_package` on dependencies.
+            let mut findings = vec![];
+            let policy = on_chain_package.upgrade_policy();
+            if policy.policy < metadata.upgrade_policy.policy {
+                findings.push(Finding::WeakerUpgradePolicy);
+            }
+            if policy == UpgradePolicy::arbitrary() && package_address != Some(address) {
+                findings.push(Finding::ArbitraryUpgradePolicy);
+            }
+
+            let on_chain_modules =
+                fetch_modules(&client, address, on_chain_package.module_names()).await?;
+            let local_source_digest = source_digests
+                .get(&package_name)
+                .cloned()
+                .unwrap_or_default();
+            findings.extend(diff_dependency(
+                &root_modules,
+                &local_source_digest,
+                on_chain_package.source_digest(),
+                &local_modules,
+                &on_chain_modules,
+            )?);
+
+            dependencies.push(DependencyAudit {
+                package: package_name,
+                address,
+                upgrade_policy: Some(policy.to_string()),
+                upgrade_number: Some(on_chain_package.upgrade_number()),
+                findings,
+            });
+        }
+
+        let report = AuditReport {
+            package: metadata.name,
+            upgrade_policy: metadata.upgrade_policy.to_string(),
+            dependencies,
+        };
+        if self.fail_on_findings && report.num_findings() > 0 {
+            return Err(CliError::UnexpectedError(format!(
+                "Audit found {} issue(s) in dependencies: {}",
+                report.num_findings(),
+                serde_json::to_string_pretty(&report)
+                    .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+            )));
+        }
+        Ok(report)
+    }
+}
+
+/// Downloads and deserializes the given modules published at an address.
+async fn fetch_modules(
+    client: &Client,
+    address: AccountAddress,
+    module_names: Vec<&str>,
+) -> CliTypedResult<BTreeMap<String, CompiledModule>> {
+    let mut modules = BTreeMap::new();
+    for name in module_names {
+        let bytes = client
+            .get_account_module(address, name)
+            .await?
+            .into_inner()
+            .bytecode
+            .0;
+        let module = CompiledModule::deserialize(&bytes).map_err(|err| {
+            CliError::UnexpectedError(format!(
+                "Failed to deserialize module {}::{}: {}",
+                address, name, err
+            ))
+        })?;
+        modules.insert(name.to_string(), module);
+    }
+    Ok(modules)
+}
+
+/// Returns whether a registry lookup failed because the account or its registry does not exist,
+/// as opposed to, e.g., a transient network failure.
+fn is_not_found(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<RestError>(),
+        Some(RestError::Api(response)) if matches!(
+            response.error.error_code,
+            AptosErrorCode::ResourceNotFound | AptosErrorCode::AccountNotFound
+        )
+    )
+}
+
+/// Compares a dependency with its on-chain version. Whether it changed is decided by its source
+/// digest, which does not depend on the compiler used, while bytecode is only compared to find the
+/// calls from the root package to functions which changed. Packages published without a source
+/// digest are compared by bytecode.
+fn diff_dependency(
+    root_modules: &[&CompiledModule],
+    local_source_digest: &str,
+    on_chain_source_digest: &str,
+    local_modules: &[&CompiledModule],
+    on_chain_modules: &BTreeMap<String, CompiledModule>,
+) -> CliTypedResult<Vec<Finding>> {
+    if !on_chain_source_digest.is_empty() && on_chain_source_digest == local_source_digest {
+        return Ok(vec![]);
+    }
+
+    // Functions which changed, mapped to whether they were removed.
+    let mut changed_functions = BTreeMap::new();
+    let mut removed_modules = vec![];
+    for local_module in local_modules {
+        let name = local_module.self_name().to_string();
+        let Some(on_chain_module) = on_chain_modules.get(&name) else {
+            removed_modules.push(name);
+            continue;
+        };
+        let on_chain_functions = disassemble_functions(on_chain_module)?;
+        for (function, code) in disassemble_functions(local_module)? {
+            match on_chain_functions.get(&function) {
+                Some(on_chain_code) if on_chain_code == &code => {},
+                on_chain_code => {
+                    changed_functions
+                        .insert((local_module.self_id(), function), on_chain_code.is_none());
+                },
+            }
+        }
+    }
+    let local_names = local_modules
+        .iter()
+        .map(|module| module.self_name().to_string())
+        .collect::<BTreeSet<_>>();
+    let added_modules = on_chain_modules
+        .keys()
+        .filter(|name| !local_names.contains(*name))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut findings = vec![];
+    if on_chain_source_digest.is_empty()
+        && changed_functions.is_empty()
+        && added_modules.is_empty()
+        && removed_modules.is_empty()
+    {
+        return Ok(findings);
+    }
+    findings.push(Finding::SourceChanged {
+        local_source_digest: local_source_digest.to_string(),
+        on_chain_source_digest: on_chain_source_digest.to_string(),
+        added_modules,
+        removed_modules,
+    });
+
+    let calls = root_modules
+        .iter()
+        .flat_map(|module| find_calls(module, &changed_functions))
+        .collect::<Vec<_>>();
+    if !calls.is_empty() {
+        findings.push(Finding::CallsChangedFunctions { calls });
+    }
+    Ok(findings)
+}
+
+/// Disassembles every function of the module. Indices into the module's pools are removed, since
+/// they change whenever a module is recompiled and are not part of what a function does.
+fn disassemble_functions(module: &CompiledModule) -> CliTypedResult<BTreeMap<String, String>> {
+    let pool_index = Regex::new(r"\[\d+\]|/\* def_idx: \d+ \*/").expect("regex is valid");
+    let disassemble = || {
+        let no_loc = Spanned::unsafe_no_loc(()).loc;
+        let source_mapping =
+            SourceMapping::new_from_view(BinaryIndexedView::Module(module), no_loc)?;
+        Disassembler::new(source_mapping, DisassemblerOptions::new()).disassemble_function_defs()
+    };
+    let functions = disassemble().map_err(|err| {
+        CliError::UnexpectedError(format!(
+            "Unable to disassemble {}: {}",
+            module.self_id(),
+            err
+        ))
+    })?;
+    Ok(functions
+        .into_iter()
+        .map(|(name, code)| (name, pool_index.replace_all(&code, "").into_owned()))
+        .collect())
+}
+
+/// Returns the calls, including closures, from functions of the module to changed functions.
+fn find_calls(
+    module: &CompiledModule,
+    changed_functions: &BTreeMap<(ModuleId, String), bool>,
+) -> Vec<AffectedCall> {
+    let mut calls = BTreeSet::new();
+    for function_def in module.function_defs() {
+        let Some(code) = &function_def.code else {
+            continue;
+        };
+        for instruction in &code.code {
+            let handle = match instruction {
+                Bytecode::Call(idx) | Bytecode::PackClosure(idx, _) => {
+                    module.function_handle_at(*idx)
+                },
+                Bytecode::CallGeneric(idx) | Bytecode::PackClosureGeneric(idx, _) => {
+                    module.function_handle_at(module.function_instantiation_at(*idx).handle)
+                },
+                _ => continue,
+            };
+            let callee = (
+                module.module_id_for_handle(module.module_handle_at(handle.module)),
+                module.identifier_at(handle.name).to_string(),
+            );
+            if let Some(removed) = changed_functions.get(&callee) {
+                let caller = module
+                    .identifier_at(module.function_handle_at(function_def.function).name)
+                    .to_string();
+                calls.insert((caller, callee, *removed));
+            }
+        }
+    }
+    calls
+        .into_iter()
+        .map(|(caller, (callee_module, callee), removed)| AffectedCall {
+            caller: format!("{}::{}", module.self_id().short_str_lossless(), caller),
+            callee: format!("{}::{}", callee_module.short_str_lossless(), callee),
+            removed,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_framework::BuildOptions;
+    use serde_json::json;
+    use std::{fs, path::Path};
+
+    const LIB_V1: &str = "module 0xd::lib {
+    public fun stable(): u64 { 1 }
+    public fun changing(): u64 { 2 }
+    public fun removed(): u64 { 3 }
+    public fun id<T>(x: T): T { x }
+}
+";
+
+    const LIB_V2: &str = "module 0xd::lib {
+    public fun stable(): u64 { 1 }
+    public fun changing(): u64 { 20 }
+    public fun id<T>(x: T): T { x }
+}
+
+module 0xd::extra {
+    public fun f() {}
+}
+";
+
+    const APP: &str = "module 0x42::app {
+    use 0xd::lib;
+    public fun uses_stable(): u64 { lib::stable() }
+    public fun uses_changing(): u64 { lib::changing() }
+    public fun uses_removed(): u64 { lib::removed() + lib::changing() }
+    public fun uses_generic(): u64 { lib::id(1) }
+}
+";
+
+    fn build_package(
+        dir: &Path,
+        name: &str,
+        source: &str,
+        dependency: Option<&str>,
+    ) -> BuiltPackage {
+        let package_dir = dir.join(name);
+        fs::create_dir_all(package_dir.join("sources")).unwrap();
+        let dependencies = dependency
+            .map(|dep| format!("\n[dependencies]\nlib = {{ local = \"../{}\" }}\n", dep))
+            .unwrap_or_default();
+        fs::write(
+            package_dir.join("Move.toml"),
+            format!(
+                "[package]\nname = \"{}\"\nversion = \"0.0.0\"\n{}",
+                name, dependencies
+            ),
+        )
+        .unwrap();
+        fs::write(package_dir.join("sources").join("m.move"), source).unwrap();
+        BuiltPackage::build(package_dir, BuildOptions::move_2()).unwrap()
+    }
+
+    fn modules_by_name(pack: &BuiltPackage) -> BTreeMap<String, CompiledModule> {
+        pack.modules()
+            .map(|module| (module.self_name().to_string(), module.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_dependency() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        // Packages are named after the dependency, as their on-chain versions would be.
+        let lib_v1 = build_package(temp_dir.path(), "lib", LIB_V1, None);
+        let app = build_package(temp_dir.path(), "app", APP, Some("lib"));
+        let lib_v2 = build_package(&temp_dir.path().join("v2"), "lib", LIB_V2, None);
+        let root_modules = app.modules().collect::<Vec<_>>();
+        let local_modules = lib_v1.modules().collect::<Vec<_>>();
+
+        // Same sources, regardless of the bytecode.
+        let findings = diff_dependency(
+            &root_modules,
+            "digest",
+            "digest",
+            &local_modules,
+            &modules_by_name(&lib_v2),
+        )
+        .unwrap();
+        assert!(findings.is_empty());
+
+        let findings = diff_dependency(
+            &root_modules,
+            "digest",
+            "upgraded",
+            &local_modules,
+            &modules_by_name(&lib_v2),
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&findings).unwrap(),
+            json!([
+                {
+                    "kind": "source_changed",
+                    "local_source_digest": "digest",
+                    "on_chain_source_digest": "upgraded",
+                    "added_modules": ["extra"],
+                    "removed_modules": [],
+                },
+                {
+                    "kind": "calls_changed_functions",
+                    "calls": [
+                        {
+                            "caller": "0x42::app::uses_changing",
+                            "callee": "0xd::lib::changing",
+                            "removed": false,
+                        },
+                        {
+                            "caller": "0x42::app::uses_removed",
+                            "callee": "0xd::lib::changing",
+                            "removed": false,
+                        },
+                        {
+                            "caller": "0x42::app::uses_removed",
+                            "callee": "0xd::lib::removed",
+                            "removed": true,
+                        },
+                    ],
+                },
+            ])
+        );
+
+        // Without an on-chain source digest, identical bytecode means the package did not change.
+        let findings = diff_dependency(
+            &root_modules,
+            "digest",
+            "",
+            &local_modules,
+            &modules_by_name(&lib_v1),
+        )
+        .unwrap();
+        assert!(findings.is_empty());
+        let findings = diff_dependency(
+            &root_modules,
+            "digest",
+            "",
+            &local_modules,
+            &modules_by_name(&lib_v2),
+        )
+        .unwrap();
+        assert_eq!(findings.len(), 2);
+    }
+
+    #[test]
+    fn test_find_calls() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        build_package(temp_dir.path(), "lib", LIB_V1, None);
+        let app = build_package(temp_dir.path(), "app", APP, Some("lib"));
+        let app_module = app.modules().next().unwrap();
+        let lib_id = ModuleId::new(
+            AccountAddress::from_hex_literal("0xd").unwrap(),
+            "lib".parse().unwrap(),
+        );
+
+        let changed_functions = BTreeMap::from([
+            ((lib_id.clone(), "id".to_string()), false),
+            ((lib_id.clone(), "removed".to_string()), true),
+        ]);
+        let calls = find_calls(app_module, &changed_functions)
+            .into_iter()
+            .map(|call| (call.caller, call.callee, call.removed))
+            .collect::<Vec<_>>();
+        assert_eq!(calls, vec![
+            (
+                "0x42::app::uses_generic".to_string(),
+                "0xd::lib::id".to_string(),
+                false
+            ),
+            (
+                "0x42::app::uses_removed".to_string(),
+                "0xd::lib::removed".to_string(),
+                true
+            ),
+        ]);
+
+        // Functions of other modules with the same name are not affected.
+        let other_id = ModuleId::new(
+            AccountAddress::from_hex_literal("0xe").unwrap(),
+            "lib".parse().unwrap(),
+        );
+        let changed_functions = BTreeMap::from([((other_id, "stable".to_string()), false)]);
+        assert!(find_calls(app_module, &changed_functions).is_empty());
+    }
+}
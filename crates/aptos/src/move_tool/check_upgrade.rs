@@ -0,0 +1,650 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{
+            CliCommand, CliError, CliTypedResult, MovePackageOptions, ProfileOptions, RestOptions,
+        },
+        utils::get_feature_flag,
+    },
+    move_tool::{CachedPackageRegistry, IncludedArtifacts},
+};
+use aptos_framework::{build_model, natives::code::UpgradePolicy, BuiltPackage};
+use aptos_rest_client::Client;
+use aptos_types::{
+    account_address::AccountAddress,
+    on_chain_config::{FeatureFlag, Features},
+};
+use async_trait::async_trait;
+use clap::Parser;
+use move_binary_format::{
+    access::ModuleAccess,
+    compatibility::{Compatibility, FunctionChange, Incompatibility, LayoutChange},
+    file_format::StructFieldInformation,
+    CompiledModule,
+};
+use move_core_types::identifier::IdentStr;
+use move_model::{
+    model::{FunctionEnv, GlobalEnv, Loc, ModuleEnv, StructEnv},
+    symbol::Symbol,
+};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use url::Url;
+
+/// Checks whether a package can be upgraded to a new version, without publishing it
+///
+/// Runs the checks done on-chain when publishing an upgrade: the upgrade policy must allow the
+/// upgrade, no module may be removed, and every module must stay compatible with its old version,
+/// i.e., keep the layout of its structs and enums, and the signatures and visibility of the
+/// functions other code can call. Each violation is explained with its location in the sources.
+///
+/// If the old version is published on-chain, friend functions and declarations are checked
+/// according to the on-chain feature flags, otherwise according to the default ones.
+#[derive(Parser)]
+pub struct CheckUpgrade {
+    /// The old version of the package: either the path to its sources, or the address it is
+    /// published at
+    #[clap(long)]
+    pub(crate) old: String,
+
+    /// Path to the new version of the package. Defaults to the package directory
+    #[clap(long, value_parser)]
+    pub(crate) new: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageOptions,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+/// Result of checking an upgrade of a package.
+#[derive(Debug, Serialize)]
+pub struct UpgradeCheckReport {
+    pub package: String,
+    pub old_upgrade_policy: String,
+    pub new_upgrade_policy: String,
+    pub violations: Vec<Violation>,
+}
+
+/// A reason why the new version of a package cannot replace the old one.
+#[derive(Debug, Serialize)]
+pub struct Violation {
+    /// The module the violation is in, if it is not about the package as a whole.
+    pub module: Option<String>,
+    pub message: String,
+    /// The source location of the declaration in the new package, or in the old one if the
+    /// declaration was removed.
+    pub location: Option<String>,
+    pub old_declaration: Option<String>,
+    pub new_declaration: Option<String>,
+}
+
+/// A version of the package, with the model of its sources if they are available.
+struct PackageVersion {
+    name: String,
+    upgrade_policy: UpgradePolicy,
+    modules: BTreeMap<String, CompiledModule>,
+    env: Option<GlobalEnv>,
+}
+
+#[async_trait]
+impl CliCommand<UpgradeCheckReport> for CheckUpgrade {
+    fn command_name(&self) -> &'static str {
+        "CheckUpgrade"
+    }
+
+    async fn execute(self) -> CliTypedResult<UpgradeCheckReport> {
+        let new_path = match &self.new {
+            Some(path) => path.clone(),
+            None => self.move_options.get_package_path()?,
+        };
+        let new = build_package_version(&self.move_options, new_path)?;
+        let (old, treat_friend_as_private) = if Path::new(&self.old).exists() {
+            (
+                build_package_version(&self.move_options, PathBuf::from(&self.old))?,
+                Features::default().is_enabled(FeatureFlag::TREAT_FRIEND_AS_PRIVATE),
+            )
+        } else {
+            let address = AccountAddress::from_str(&self.old).map_err(|err| {
+                CliError::CommandArgumentError(format!(
+                    "--old must be a package directory or an address: {}",
+                    err
+                ))
+            })?;
+            let url = self.rest_options.url(&self.profile_options)?;
+            let treat_friend_as_private = get_feature_flag(
+                &Client::new(url.clone()),
+                FeatureFlag::TREAT_FRIEND_AS_PRIVATE,
+            )
+            .await?;
+            (
+                fetch_package_version(url, address, &new.name).await?,
+                treat_friend_as_private,
+            )
+        };
+
+        let report = UpgradeCheckReport {
+            package: new.name.clone(),
+            old_upgrade_policy: old.upgrade_policy.to_string(),
+            new_upgrade_policy: new.upgrade_policy.to_string(),
+            violations: check_upgrade(&old, &new, treat_friend_as_private),
+        };
+        if !report.violations.is_empty() {
+            return Err(CliError::UnexpectedError(format!(
+                "Package `{}` cannot be upgraded, found {} violation(s): {}",
+                report.package,
+                report.violations.len(),
+                serde_json::to_string_pretty(&report)
+                    .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+            )));
+        }
+        Ok(report)
+    }
+}
+
+/// Fetches the version of the package published at the address. Its sources are not needed, so
+/// the model is not available.
+async fn fetch_package_version(
+    url: Url,
+    address: AccountAddress,
+    package_name: &str,
+) -> CliTypedResult<PackageVersion> {
+    let registry = CachedPackageRegistry::create(url, address, true).await?;
+    let package = registry.get_package(package_name).await?;
+    let mut modules = BTreeMap::new();
+    for name in package.module_names() {
+        let bytes = registry.get_bytecode(name).await?.ok_or_else(|| {
+            CliError::UnexpectedError(format!("Bytecode of module {} not found", name))
+        })?;
+        let module = CompiledModule::deserialize(bytes).map_err(|err| {
+            CliError::UnexpectedError(format!(
+                "Failed to deserialize module {}::{}: {}",
+                address, name, err
+            ))
+        })?;
+        modules.insert(name.to_string(), module);
+    }
+    Ok(PackageVersion {
+        name: package.name().to_string(),
+        upgrade_policy: package.upgrade_policy(),
+        modules,
+        env: None,
+    })
+}
+
+/// Compiles the package at the path, and builds the model of its sources.
+fn build_package_version(
+    move_options: &MovePackageOptions,
+    path: PathBuf,
+) -> CliTypedResult<PackageVersion> {
+    let options = IncludedArtifacts::None.build_options(move_options)?;
+    let pack = BuiltPackage::build(path.clone(), options.clone())
+        .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+    let env = build_model(
+        options.dev,
+        &path,
+        options.named_addresses,
+        None,
+        options.bytecode_version,
+        options.compiler_version,
+        options.language_version,
+        options.skip_attribute_checks,
+        options.known_attributes,
+        options.experiments,
+    )
+    .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+    let metadata = pack.extract_metadata()?;
+    Ok(PackageVersion {
+        name: metadata.name,
+        upgrade_policy: metadata.upgrade_policy,
+        modules: pack
+            .modules()
+            .map(|module| (module.self_name().to_string(), module.clone()))
+            .collect(),
+        env: Some(env),
+    })
+}
+
+/// Runs the checks of `code::check_upgradability` on the package, and the compatibility checks of
+/// the VM on each of its modules. Friends are only checked if they are not treated as private, as
+/// controlled by the `TREAT_FRIEND_AS_PRIVATE` feature flag.
+fn check_upgrade(
+    old: &PackageVersion,
+    new: &PackageVersion,
+    treat_friend_as_private: bool,
+) -> Vec<Violation> {
+    let package_violation = |message: String| Violation {
+        module: None,
+        message,
+        location: None,
+        old_declaration: None,
+        new_declaration: None,
+    };
+    let mut violations = vec![];
+    if old.upgrade_policy == UpgradePolicy::immutable() {
+        violations.push(package_violation(
+            "the old package is immutable and cannot be upgraded".to_string(),
+        ));
+    }
+    if new.upgrade_policy.policy < old.upgrade_policy.policy {
+        violations.push(package_violation(format!(
+            "the upgrade policy cannot be weakened from `{}` to `{}`",
+            old.upgrade_policy, new.upgrade_policy
+        )));
+    }
+
+    // Mirrors the configuration the VM uses for publishing.
+    let compatibility = Compatibility::new(true, !treat_friend_as_private, true, false);
+    for (name, old_module) in &old.modules {
+        let old_env = old
+            .env
+            .as_ref()
+            .and_then(|env| env.find_module_by_language_storage_id(&old_module.self_id()));
+        let Some(new_module) = new.modules.get(name) else {
+            violations.push(Violation {
+                module: Some(name.clone()),
+                message: format!(
+                    "removed module `{}`, modules cannot be removed from a package",
+                    name
+                ),
+                location: old_env
+                    .as_ref()
+                    .and_then(|env| location(env.env, &env.get_loc())),
+                old_declaration: None,
+                new_declaration: None,
+            });
+            continue;
+        };
+        let new_env = new
+            .env
+            .as_ref()
+            .and_then(|env| env.find_module_by_language_storage_id(&new_module.self_id()));
+        for incompatibility in compatibility.incompatibilities(old_module, new_module) {
+            violations.push(explain(
+                name,
+                &incompatibility,
+                (old_module, old_env.as_ref()),
+                (new_module, new_env.as_ref()),
+            ));
+        }
+    }
+    violations
+}
+
+/// Explains an incompatibility of a module in terms of the declarations in its sources.
+fn explain(
+    module_name: &str,
+    incompatibility: &Incompatibility,
+    (old_module, old_env): (&CompiledModule, Option<&ModuleEnv>),
+    (new_module, new_env): (&CompiledModule, Option<&ModuleEnv>),
+) -> Violation {
+    let reason = match incompatibility {
+        Incompatibility::ModuleAddressChanged(_) | Incompatibility::ModuleNameChanged(_) => {
+            "modules cannot be moved".to_string()
+        },
+        Incompatibility::StructRemoved(_) => "structs cannot be removed".to_string(),
+        Incompatibility::StructAbilitiesRemoved { .. } => {
+            "abilities can be added to a struct, but not removed".to_string()
+        },
+        Incompatibility::StructTypeParametersChanged(_) => "type parameters cannot be added or \
+            removed, and their constraints and phantom declarations cannot be made stricter"
+            .to_string(),
+        Incompatibility::StructLayoutChanged {
+            struct_name,
+            change,
+        } => match change {
+            LayoutChange::StructKindChanged => {
+                "a struct cannot become an enum, nor an enum a struct".to_string()
+            },
+            LayoutChange::VariantChanged(variant) => format!(
+                "variant `{}` was removed, renamed or moved; new variants can only be added \
+                after the existing ones",
+                variant
+            ),
+            LayoutChange::FieldsChanged { variant, position } => {
+                let variant = variant.as_deref();
+                let field = field_name(old_module, struct_name, variant, *position)
+                    .or_else(|| field_name(new_module, struct_name, variant, *position))
+                    .map(|field| format!(" at field `{}`", field))
+                    .unwrap_or_default();
+                let fields = match variant {
+                    Some(variant) => format!("fields of variant `{}`", variant),
+                    None => "fields".to_string(),
+                };
+                format!(
+                    "the {} differ{}; fields cannot be added, removed, renamed, reordered or \
+                    change their type",
+                    fields, field
+                )
+            },
+        },
+        Incompatibility::FunctionRemoved(_) => {
+            "functions callable from other modules or transactions cannot be removed".to_string()
+        },
+        Incompatibility::FunctionChanged { change, .. } => match change {
+            FunctionChange::Visibility => {
+                "functions callable from other modules cannot be made less visible".to_string()
+            },
+            FunctionChange::EntryRemoved => "entry functions must stay entry functions".to_string(),
+            FunctionChange::AttributesRemoved => {
+                "attributes required by callers cannot be removed".to_string()
+            },
+            FunctionChange::ParameterTypes => "parameter types cannot change".to_string(),
+            FunctionChange::ReturnType => "the return type cannot change".to_string(),
+            FunctionChange::TypeParameters => "type parameters cannot be added or removed, and \
+                their constraints cannot be made stricter"
+                .to_string(),
+        },
+        Incompatibility::FriendsRemoved(_) => "friend declarations cannot be removed".to_string(),
+    };
+
+    let (location, old_declaration, new_declaration) = match incompatibility {
+        Incompatibility::StructRemoved(name)
+        | Incompatibility::StructAbilitiesRemoved {
+            struct_name: name, ..
+        }
+        | Incompatibility::StructTypeParametersChanged(name)
+        | Incompatibility::StructLayoutChanged {
+            struct_name: name, ..
+        } => {
+            let old_struct = old_env.and_then(|env| find_struct(env, name));
+            let new_struct = new_env.and_then(|env| find_struct(env, name));
+            let variant = match incompatibility {
+                Incompatibility::StructLayoutChanged {
+                    change: LayoutChange::VariantChanged(variant),
+                    ..
+                } => Some(variant.as_ident_str()),
+                _ => None,
+            };
+            (
+                new_struct
+                    .as_ref()
+                    .or(old_struct.as_ref())
+                    .and_then(|s| struct_location(s, variant)),
+                old_struct.as_ref().map(struct_declaration),
+                new_struct.as_ref().map(struct_declaration),
+            )
+        },
+        Incompatibility::FunctionRemoved(name)
+        | Incompatibility::FunctionChanged {
+            function_name: name,
+            ..
+        } => {
+            let old_function = old_env.and_then(|env| find_function(env, name));
+            let new_function = new_env.and_then(|env| find_function(env, name));
+            (
+                new_function
+                    .as_ref()
+                    .or(old_function.as_ref())
+                    .and_then(|f| location(f.module_env.env, &f.get_loc())),
+                old_function.as_ref().map(FunctionEnv::get_header_string),
+                new_function.as_ref().map(FunctionEnv::get_header_string),
+            )
+        },
+        Incompatibility::ModuleAddressChanged(_)
+        | Incompatibility::ModuleNameChanged(_)
+        | Incompatibility::FriendsRemoved(_) => (
+            new_env.and_then(|env| location(env.env, &env.get_loc())),
+            None,
+            None,
+        ),
+    };
+
+    Violation {
+        module: Some(module_name.to_string()),
+        message: format!("{}: {}", incompatibility, reason),
+        location,
+        old_declaration,
+        new_declaration,
+    }
+}
+
+/// Returns the name of the field at the position in the struct or enum variant.
+fn field_name(
+    module: &CompiledModule,
+    struct_name: &IdentStr,
+    variant: Option<&IdentStr>,
+    position: usize,
+) -> Option<String> {
+    let def = module.struct_defs().iter().find(|def| {
+        module.identifier_at(module.struct_handle_at(def.struct_handle).name) == struct_name
+    })?;
+    let fields = match (&def.field_information, variant) {
+        (StructFieldInformation::Declared(fields), None) => fields,
+        (StructFieldInformation::DeclaredVariants(variants), Some(variant)) => {
+            &variants
+                .iter()
+                .find(|v| module.identifier_at(v.name) == variant)?
+                .fields
+        },
+        _ => return None,
+    };
+    fields
+        .get(position)
+        .map(|field| module.identifier_at(field.name).to_string())
+}
+
+fn find_struct<'env>(env: &'env ModuleEnv, name: &IdentStr) -> Option<StructEnv<'env>> {
+    env.find_struct(env.symbol_pool().make(name.as_str()))
+}
+
+fn find_function<'env>(env: &ModuleEnv<'env>, name: &IdentStr) -> Option<FunctionEnv<'env>> {
+    env.find_function(env.symbol_pool().make(name.as_str()))
+}
+
+/// Returns the location of the struct, or of the given variant if the struct declares it.
+fn struct_location(struct_env: &StructEnv, variant: Option<&IdentStr>) -> Option<String> {
+    let env = struct_env.module_env.env;
+    if let Some(variant) = variant.filter(|_| struct_env.has_variants()) {
+        let variant = struct_env.symbol_pool().make(variant.as_str());
+        if struct_env.get_variants().any(|v| v == variant) {
+            return location(env, struct_env.get_variant_loc(variant));
+        }
+    }
+    location(env, &struct_env.get_loc())
+}
+
+/// Formats a location as `file:line`.
+fn location(env: &GlobalEnv, loc: &Loc) -> Option<String> {
+    env.get_file_and_location(loc)
+        .map(|(file, location)| format!("{}:{}", file, location.line.0 + 1))
+}
+
+/// Renders the declaration of a struct or enum as it is written in Move.
+fn struct_declaration(struct_env: &StructEnv) -> String {
+    let spool = struct_env.symbol_pool();
+    let tctx = struct_env.get_type_display_ctx();
+    let mut declaration = format!(
+        "{} {}",
+        if struct_env.has_variants() {
+            "enum"
+        } else {
+            "struct"
+        },
+        struct_env.get_name().display(spool)
+    );
+    let type_params = struct_env
+        .get_type_parameters()
+        .iter()
+        .map(|param| {
+            let mut s = format!(
+                "{}{}",
+                if param.1.is_phantom { "phantom " } else { "" },
+                param.0.display(spool)
+            );
+            if !param.1.abilities.is_empty() {
+                s.push_str(&format!(": {}", param.1.abilities));
+            }
+            s
+        })
+        .collect::<Vec<_>>();
+    if !type_params.is_empty() {
+        declaration.push_str(&format!("<{}>", type_params.join(", ")));
+    }
+    let abilities = struct_env
+        .get_abilities()
+        .iter()
+        .map(|ability| ability.to_string())
+        .collect::<Vec<_>>();
+    if !abilities.is_empty() {
+        declaration.push_str(&format!(" has {}", abilities.join(", ")));
+    }
+    let fields = |variant: Option<Symbol>| {
+        struct_env
+            .get_fields_optional_variant(variant)
+            .map(|field| {
+                format!(
+                    "{}: {}",
+                    field.get_name().display(spool),
+                    field.get_type().display(&tctx)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if struct_env.has_variants() {
+        let variants = struct_env
+            .get_variants()
+            .map(|variant| {
+                let variant_fields = fields(Some(variant));
+                if variant_fields.is_empty() {
+                    variant.display(spool).to_string()
+                } else {
+                    format!("{} {{ {} }}", variant.display(spool), variant_fields)
+                }
+            })
+            .collect::<Vec<_>>();
+        declaration.push_str(&format!(" {{ {} }}", variants.join(", ")));
+    } else {
+        declaration.push_str(&format!(" {{ {} }}", fields(None)));
+    }
+    declaration
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const COLOR: &str = "module 0x42::m {
+    enum Color has store, drop { Red, Green, Blue }
+}
+";
+
+    const SHAPE: &str = "module 0x42::m {
+    struct Shape has store, drop { width: u64, height: u64 }
+}
+";
+
+    const FRIENDS: &str = "module 0x42::m {
+    friend 0x42::n;
+    public(friend) fun f(): u64 { 1 }
+}
+
+module 0x42::n {
+    public fun g(): u64 { 2 }
+}
+";
+
+    fn write_package(dir: &Path, version: &str, source: &str) -> PathBuf {
+        let package_dir = dir.join(version);
+        fs::create_dir_all(package_dir.join("sources")).unwrap();
+        fs::write(
+            package_dir.join("Move.toml"),
+            "[package]\nname = \"upgradable\"\nversion = \"0.0.0\"\n",
+        )
+        .unwrap();
+        fs::write(package_dir.join("sources").join("m.move"), source).unwrap();
+        package_dir
+    }
+
+    async fn check(old: &str, new: &str) -> CliTypedResult<UpgradeCheckReport> {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let old = write_package(temp_dir.path(), "old", old);
+        let new = write_package(temp_dir.path(), "new", new);
+        CheckUpgrade::parse_from([
+            "check-upgrade",
+            "--old",
+            old.to_str().unwrap(),
+            "--new",
+            new.to_str().unwrap(),
+        ])
+        .execute()
+        .await
+    }
+
+    async fn check_violations(old: &str, new: &str) -> String {
+        match check(old, new).await {
+            Ok(report) => panic!("expected violations, got {:?}", report),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enum_variants() {
+        // New variants can be added after the existing ones.
+        let report = check(COLOR, &COLOR.replace("Blue }", "Blue, Yellow }"))
+            .await
+            .unwrap();
+        assert!(report.violations.is_empty());
+
+        let removed = check_violations(COLOR, &COLOR.replace("Green, ", "")).await;
+        assert!(removed.contains("changed layout of struct `Color`"));
+        assert!(removed.contains("variant `Green` was removed, renamed or moved"));
+        assert!(removed.contains("enum Color has drop, store { Red, Blue }"));
+
+        let reordered = check_violations(COLOR, &COLOR.replace("Green, Blue", "Blue, Green")).await;
+        assert!(reordered.contains("variant `Green` was removed, renamed or moved"));
+        // The variant still exists, so the violation points to it.
+        assert!(reordered.contains("m.move:2"));
+    }
+
+    #[tokio::test]
+    async fn test_struct_layout() {
+        let renamed = check_violations(SHAPE, &SHAPE.replace("height", "depth")).await;
+        assert!(renamed.contains("changed layout of struct `Shape`"));
+        assert!(renamed.contains("the fields differ at field `height`"));
+        assert!(renamed.contains("struct Shape has drop, store { width: u64, height: u64 }"));
+
+        let added = check_violations(SHAPE, &SHAPE.replace(" }", ", depth: u64 }")).await;
+        assert!(added.contains("the fields differ at field `depth`"));
+    }
+
+    #[tokio::test]
+    async fn test_friend_removal() {
+        let new_source = FRIENDS.replace("    friend 0x42::n;\n", "");
+        // Friends are treated as private by default, so they can be removed.
+        let report = check(FRIENDS, &new_source).await.unwrap();
+        assert!(report.violations.is_empty());
+
+        // Networks where friends are not treated as private reject the removal.
+        let temp_dir = tempfile::tempdir().unwrap();
+        let move_options = MovePackageOptions::new();
+        let old = build_package_version(
+            &move_options,
+            write_package(temp_dir.path(), "old", FRIENDS),
+        )
+        .unwrap();
+        let new = build_package_version(
+            &move_options,
+            write_package(temp_dir.path(), "new", &new_source),
+        )
+        .unwrap();
+        let violations = check_upgrade(&old, &new, false);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].module.as_deref(), Some("m"));
+        assert!(violations[0]
+            .message
+            .starts_with("removed friend declaration"));
+        assert!(violations[0].message.contains("42::n`"));
+        assert!(check_upgrade(&old, &new, true).is_empty());
+    }
+}
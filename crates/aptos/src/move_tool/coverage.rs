@@ -11,6 +11,7 @@ use clap::{Parser, Subcommand};
 use legacy_move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
 use move_coverage::{
     coverage_map::CoverageMap,
+    export::{write_cobertura, write_lcov, ExportFormat, ModuleLineCoverage},
     format_csv_summary, format_human_summary,
     source_coverage::{ColorChoice, SourceCoverageBuilder, TextIndicator},
     summary::summarize_inst_cov,
@@ -18,6 +19,11 @@ use move_coverage::{
 use move_disassembler::disassembler::Disassembler;
 use move_model::metadata::{CompilerVersion, LanguageVersion};
 use move_package::{compilation::compiled_package::CompiledPackage, BuildConfig, CompilerConfig};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
 
 /// Display a coverage summary for all modules in a package
 ///
@@ -166,6 +172,75 @@ impl CliCommand<()> for BytecodeCoverage {
     }
 }
 
+/// Export coverage information of all modules in a package for CI coverage dashboards
+///
+/// Execution counts are mapped to source lines and conditional branches. File paths are relative
+/// to the package directory.
+#[derive(Debug, Parser)]
+pub struct ExportCoverage {
+    /// Format of the exported coverage
+    #[clap(long, default_value_t = ExportFormat::Lcov)]
+    pub format: ExportFormat,
+
+    /// File to write the coverage to, e.g. `lcov.info` or `coverage.xml`
+    #[clap(long, value_parser)]
+    pub output_file: PathBuf,
+
+    #[clap(flatten)]
+    pub move_options: MovePackageOptions,
+}
+
+#[async_trait]
+impl CliCommand<()> for ExportCoverage {
+    fn command_name(&self) -> &'static str {
+        "ExportCoverage"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let root = self.move_options.get_package_path()?;
+        let (coverage_map, package) = compile_coverage(self.move_options)?;
+        let coverage_map = coverage_map.to_unified_exec_map();
+        let modules = package
+            .root_modules()
+            .filter_map(|unit| match &unit.unit {
+                CompiledUnit::Module(NamedCompiledModule {
+                    module, source_map, ..
+                }) => Some(ModuleLineCoverage::new(
+                    module,
+                    source_map,
+                    &unit.source_path,
+                    &coverage_map,
+                )),
+                _ => None,
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(|err| {
+                CliError::UnexpectedError(format!("Failed to map coverage to sources: {:#}", err))
+            })?;
+
+        let file = File::create(&self.output_file)
+            .map_err(|err| CliError::IO(self.output_file.display().to_string(), err))?;
+        let mut writer = BufWriter::new(file);
+        match self.format {
+            ExportFormat::Lcov => write_lcov(&modules, &root, &mut writer),
+            ExportFormat::Cobertura => write_cobertura(
+                package.compiled_package_info.package_name.as_str(),
+                &modules,
+                &root,
+                &mut writer,
+            ),
+        }
+        .and_then(|()| writer.flush())
+        .map_err(|err| CliError::IO(self.output_file.display().to_string(), err))?;
+        println!(
+            "Coverage exported to {} in {} format",
+            self.output_file.display(),
+            self.format
+        );
+        Ok(())
+    }
+}
+
 fn compile_coverage(
     move_options: MovePackageOptions,
 ) -> CliTypedResult<(CoverageMap, CompiledPackage)> {
@@ -215,6 +290,7 @@ pub enum CoveragePackage {
     Summary(SummaryCoverage),
     Source(SourceCoverage),
     Bytecode(BytecodeCoverage),
+    Export(ExportCoverage),
 }
 
 impl CoveragePackage {
@@ -223,6 +299,7 @@ impl CoveragePackage {
             Self::Summary(tool) => tool.execute_serialized_success().await,
             Self::Source(tool) => tool.execute_serialized_success().await,
             Self::Bytecode(tool) => tool.execute_serialized_success().await,
+            Self::Export(tool) => tool.execute_serialized_success().await,
         }
     }
 }
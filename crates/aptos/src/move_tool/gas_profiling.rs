@@ -0,0 +1,183 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{CliError, CliTypedResult};
+use aptos_gas_profiling::{FrameName, GasProfile, GasProfileDiff, GasProfiler, TransactionGasLog};
+use aptos_gas_schedule::{AptosGasParameters, LATEST_GAS_FEATURE_VERSION};
+use aptos_vm::gas::{make_prod_gas_meter, ProdGasMeter};
+use aptos_vm_types::{resolver::NoopBlockSynchronizationKillSwitch, storage::StorageGasParameters};
+use move_core_types::{effects::ChangeSet, identifier::Identifier, language_storage::ModuleId};
+use move_unit_test::test_reporter::{TestRunInfo, UnitTestFactory};
+use move_vm_runtime::native_extensions::NativeContextExtensions;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+static KILL_SWITCH: NoopBlockSynchronizationKillSwitch = NoopBlockSynchronizationKillSwitch {};
+
+/// Runs unit tests with the production gas meter and the latest gas schedule, and records the gas
+/// log of every test.
+///
+/// A test can use at most the maximum number of gas units of a transaction. Unit tests do not
+/// produce a transaction output, so neither storage fees nor IO gas for writes and events are
+/// charged, i.e., the logs only contain execution gas and IO gas for reads. For the same reason,
+/// the call frames have no storage deltas.
+pub(crate) struct GasProfilingFactory {
+    gas_params: AptosGasParameters,
+    storage_gas_params: StorageGasParameters,
+    logs: Arc<Mutex<BTreeMap<String, TransactionGasLog>>>,
+}
+
+impl GasProfilingFactory {
+    pub(crate) fn new(gas_params: AptosGasParameters) -> Self {
+        Self {
+            gas_params,
+            storage_gas_params: StorageGasParameters::latest(),
+            logs: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+
+    /// The logs of the tests run so far, by test name.
+    pub(crate) fn logs(&self) -> Arc<Mutex<BTreeMap<String, TransactionGasLog>>> {
+        self.logs.clone()
+    }
+
+    fn new_prod_gas_meter(&self) -> ProdGasMeter<'static, NoopBlockSynchronizationKillSwitch> {
+        make_prod_gas_meter(
+            LATEST_GAS_FEATURE_VERSION,
+            self.gas_params.vm.clone(),
+            self.storage_gas_params.clone(),
+            false,
+            self.gas_params.vm.txn.maximum_number_of_gas_units,
+            &KILL_SWITCH,
+        )
+    }
+}
+
+impl UnitTestFactory for GasProfilingFactory {
+    type GasMeter = GasProfiler<ProdGasMeter<'static, NoopBlockSynchronizationKillSwitch>>;
+
+    fn new_gas_meter(&self) -> Self::GasMeter {
+        GasProfiler::new_script(self.new_prod_gas_meter())
+    }
+
+    fn new_gas_meter_for_test(&self, module_id: &ModuleId, function_name: &str) -> Self::GasMeter {
+        match Identifier::new(function_name) {
+            Ok(name) => GasProfiler::new_function(
+                self.new_prod_gas_meter(),
+                module_id.clone(),
+                name,
+                vec![],
+            ),
+            Err(_) => self.new_gas_meter(),
+        }
+    }
+
+    fn finalize_test_run_info(
+        &self,
+        _: &ChangeSet,
+        _: &mut NativeContextExtensions,
+        gas_meter: Self::GasMeter,
+        mut test_run_info: TestRunInfo,
+    ) -> TestRunInfo {
+        let log = gas_meter.finish();
+        test_run_info.gas_used =
+            u64::from(log.exec_io.total).div_ceil(u64::from(log.exec_io.gas_scaling_factor));
+        // A fuzzed test runs many times under the same name, of which only the first is kept.
+        self.logs
+            .lock()
+            .unwrap()
+            .entry(test_name(&log))
+            .or_insert(log);
+        test_run_info
+    }
+}
+
+/// Names a test after its entry point, so that it can be used as a directory name.
+fn test_name(log: &TransactionGasLog) -> String {
+    match log.entry_point() {
+        FrameName::Function {
+            module_id, name, ..
+        } => format!(
+            "{}-{}-{}",
+            module_id.address().short_str_lossless(),
+            module_id.name(),
+            name
+        ),
+        FrameName::Script => "script".to_string(),
+        FrameName::TransactionBatch => "transaction-batch".to_string(),
+    }
+}
+
+/// Writes an HTML report and a profile for every test into `<output_dir>/<test>`. If a baseline is
+/// given, i.e., the output directory of an earlier run, the profiles are compared with the ones of
+/// the baseline and a report of the differences is written into `<output_dir>/<test>/diff`.
+///
+/// As storage fees are not charged for unit tests, the profiles contain no storage fees, and only
+/// the execution and IO gas are compared.
+///
+/// Returns the regressions exceeding the maximum increase, if one is given.
+pub(crate) fn write_gas_reports(
+    logs: &BTreeMap<String, TransactionGasLog>,
+    output_dir: &Path,
+    baseline: Option<&Path>,
+    max_increase_percent: Option<f64>,
+) -> CliTypedResult<Vec<String>> {
+    let mut regressions = vec![];
+    for (name, log) in logs {
+        let dir = output_dir.join(name);
+        log.generate_html_report(&dir, format!("Gas Report - {}", name))?;
+        let profile = GasProfile {
+            storage: None,
+            ..log.to_profile()
+        };
+        let profile_path = dir.join("profile.json");
+        fs::write(
+            &profile_path,
+            serde_json::to_string_pretty(&profile)
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))?,
+        )
+        .map_err(|err| CliError::IO(profile_path.display().to_string(), err))?;
+
+        let Some(baseline) = baseline else {
+            continue;
+        };
+        let Some(old) = read_profile(&baseline.join(name).join("profile.json"))? else {
+            println!(
+                "Gas profile of {} has no baseline, skipping comparison.",
+                name
+            );
+            continue;
+        };
+        let diff = GasProfileDiff::new(&old, &profile)?;
+        diff.generate_html_report(dir.join("diff"), format!("Gas Diff - {}", name))?;
+        println!(
+            "Gas usage of {} compared to the baseline:\n{}",
+            name,
+            diff.summary()?
+        );
+        if let Some(max_increase_percent) = max_increase_percent {
+            regressions.extend(diff.regressions(max_increase_percent));
+        }
+    }
+    println!("Gas reports saved to {}.", output_dir.display());
+    Ok(regressions)
+}
+
+fn read_profile(path: &Path) -> CliTypedResult<Option<GasProfile>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents =
+        fs::read_to_string(path).map_err(|err| CliError::IO(path.display().to_string(), err))?;
+    serde_json::from_str(&contents).map(Some).map_err(|err| {
+        CliError::UnexpectedError(format!(
+            "Failed to parse gas profile {}: {}",
+            path.display(),
+            err
+        ))
+    })
+}
@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    common::types::{AccountAddressWrapper, CliCommand, CliTypedResult, MovePackageOptions},
+    common::types::{
+        AccountAddressWrapper, CliCommand, CliError, CliTypedResult, MovePackageOptions,
+    },
     move_tool::IncludedArtifacts,
 };
 use anyhow::bail;
@@ -10,10 +12,14 @@ use aptos_framework::{BuildOptions, BuiltPackage};
 use async_trait::async_trait;
 use clap::Parser;
 use move_compiler_v2::Experiment;
-use move_linter::MoveLintChecks;
+use move_linter::{known_checker_names, make_lint_set, registered_lint_sets, MoveLintChecks};
 use move_model::metadata::{CompilerVersion, LanguageVersion, LATEST_STABLE_LANGUAGE_VERSION};
 use move_package::source_package::std_lib::StdVersion;
-use std::{collections::BTreeMap, path::PathBuf, str::FromStr};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    str::FromStr,
+};
 
 /// Run a Lint tool to show additional warnings about the current package, in addition to ordinary
 /// warnings and/or errors generated by the Move 2 compiler.
@@ -85,6 +91,13 @@ pub struct LintPackage {
     ///   Also runs the strict and default checks.
     #[clap(long, verbatim_doc_comment, value_parser = clap::value_parser!(LintOptions))]
     pub checks: Option<LintOptions>,
+
+    /// Names of externally registered lint sets to run, in addition to the built-in lint checks
+    ///
+    /// These are added to the lint sets listed in the `[lint]` section of Move.toml.
+    /// Example: my_team,other_team
+    #[clap(long, value_delimiter = ',')]
+    pub lint_sets: Vec<String>,
 }
 
 impl LintPackage {
@@ -99,7 +112,8 @@ impl LintPackage {
             language_version,
             skip_attribute_checks,
             experiments,
-            checks: _, // these are passed directly to the lint maker
+            checks: _,    // these are passed directly to the lint maker
+            lint_sets: _, // these are resolved from the lint set registry
         } = self.clone();
         MovePackageOptions {
             dev,
@@ -184,15 +198,82 @@ impl CliCommand<&'static str> for LintPackage {
         let build_config = BuiltPackage::create_build_config(&build_options)?;
         let resolved_graph =
             BuiltPackage::prepare_resolution_graph(package_path, build_config.clone())?;
+
+        let config = self.checks.unwrap_or_default().to_config();
+        let manifest_lint = resolved_graph.root_package.lint.clone().unwrap_or_default();
+        let mut external_checks = vec![MoveLintChecks::make(config.clone())];
+        let lint_sets: BTreeSet<_> = manifest_lint.sets.iter().chain(&self.lint_sets).collect();
+        for name in lint_sets {
+            let checks = make_lint_set(name, &config).ok_or_else(|| {
+                CliError::CommandArgumentError(format!(
+                    "unknown lint set `{}` (registered lint sets: [{}])",
+                    name,
+                    registered_lint_sets().join(", ")
+                ))
+            })?;
+            external_checks.push(checks);
+        }
+        let known_lints = known_checker_names(&external_checks);
+        for name in manifest_lint.levels.keys() {
+            if !known_lints.contains(name) {
+                eprintln!(
+                    "Warning: level configured in Move.toml for unknown lint `{}`",
+                    name
+                );
+            }
+        }
+
         BuiltPackage::build_with_external_checks(
             resolved_graph,
             build_options,
             build_config,
-            vec![MoveLintChecks::make(
-                self.checks.unwrap_or_default().to_config(),
-            )],
+            external_checks,
         )?;
 
         Ok("succeeded")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    // How the lint sets and levels are applied is tested in `move-linter`; these tests cover how
+    // they are passed in by the CLI.
+
+    #[test]
+    fn test_parse_lint_sets() {
+        let lint = LintPackage::parse_from(["lint", "--lint-sets", "first,second"]);
+        assert_eq!(lint.lint_sets, vec![
+            "first".to_string(),
+            "second".to_string()
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_lint_set() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join("sources")).unwrap();
+        fs::write(
+            temp_dir.path().join("Move.toml"),
+            "[package]\nname = \"unknown_lint_set\"\nversion = \"0.0.0\"\n",
+        )
+        .unwrap();
+
+        let package_dir = temp_dir.path().display().to_string();
+        let err = LintPackage::parse_from([
+            "lint",
+            "--package-dir",
+            package_dir.as_str(),
+            "--lint-sets",
+            "cli_test_unknown",
+        ])
+        .execute()
+        .await
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("unknown lint set `cli_test_unknown`"));
+    }
+}
@@ -20,11 +20,15 @@ use crate::{
     },
     governance::CompileScriptFunction,
     move_tool::{
+        audit::AuditPackage,
         bytecode::{Decompile, Disassemble},
+        check_upgrade::CheckUpgrade,
         coverage::SummaryCoverage,
         fmt::Fmt,
+        gas_profiling::{write_gas_reports, GasProfilingFactory},
         lint::LintPackage,
         manifest::{Dependency, ManifestNamedAddress, MovePackageManifest, PackageInfo},
+        mutation_test::MutationTestPackage,
         sim::Sim,
     },
     CliCommand, CliResult,
@@ -41,7 +45,9 @@ use aptos_framework::{
     prover::ProverOptions,
     BuildOptions, BuiltPackage,
 };
-use aptos_gas_schedule::{MiscGasParameters, NativeGasParameters};
+use aptos_gas_schedule::{
+    AptosGasParameters, InitialGasSchedule, MiscGasParameters, NativeGasParameters,
+};
 use aptos_move_debugger::aptos_debugger::AptosDebugger;
 use aptos_rest_client::{
     aptos_api_types::{EntryFunctionId, HexEncodedBytes, IdentifierWrapper, MoveModuleId},
@@ -81,15 +87,20 @@ pub use stored_package::*;
 use tokio::task;
 use url::Url;
 pub mod aptos_debug_natives;
+mod audit;
 mod bytecode;
+mod check_upgrade;
 pub mod coverage;
 mod fmt;
+mod gas_profiling;
 mod lint;
 mod manifest;
+mod mutation_test;
 pub mod package_hooks;
 mod show;
 mod sim;
 pub mod stored_package;
+mod trace;
 
 const HELLO_BLOCKCHAIN_EXAMPLE: &str = include_str!(
     "../../../../aptos-move/move-examples/hello_blockchain/sources/hello_blockchain.move"
@@ -102,7 +113,9 @@ const HELLO_BLOCKCHAIN_EXAMPLE: &str = include_str!(
 /// about this code.
 #[derive(Subcommand)]
 pub enum MoveTool {
+    Audit(AuditPackage),
     BuildPublishPayload(BuildPublishPayload),
+    CheckUpgrade(CheckUpgrade),
     Clean(CleanPackage),
     ClearStagingArea(ClearStagingArea),
     #[clap(alias = "build")]
@@ -124,6 +137,7 @@ pub enum MoveTool {
     Init(InitPackage),
     Lint(LintPackage),
     List(ListPackage),
+    MutationTest(MutationTestPackage),
     Prove(ProvePackage),
     #[clap(alias = "deploy")]
     Publish(PublishPackage),
@@ -133,6 +147,8 @@ pub enum MoveTool {
     #[clap(subcommand, hide = true)]
     Show(show::ShowTool),
     Test(TestPackage),
+    #[clap(subcommand)]
+    Trace(trace::TraceTool),
     VerifyPackage(VerifyPackage),
     View(ViewFunction),
     Replay(Replay),
@@ -144,7 +160,9 @@ pub enum MoveTool {
 impl MoveTool {
     pub async fn execute(self) -> CliResult {
         match self {
+            MoveTool::Audit(tool) => tool.execute_serialized().await,
             MoveTool::BuildPublishPayload(tool) => tool.execute_serialized().await,
+            MoveTool::CheckUpgrade(tool) => tool.execute_serialized().await,
             MoveTool::Clean(tool) => tool.execute_serialized().await,
             MoveTool::ClearStagingArea(tool) => tool.execute_serialized().await,
             MoveTool::Compile(tool) => tool.execute_serialized().await,
@@ -165,6 +183,7 @@ impl MoveTool {
             MoveTool::Download(tool) => tool.execute_serialized().await,
             MoveTool::Init(tool) => tool.execute_serialized_success().await,
             MoveTool::List(tool) => tool.execute_serialized().await,
+            MoveTool::MutationTest(tool) => tool.execute_serialized().await,
             MoveTool::Prove(tool) => tool.execute_serialized().await,
             MoveTool::Publish(tool) => tool.execute_serialized().await,
             MoveTool::Run(tool) => tool.execute_serialized().await,
@@ -172,6 +191,7 @@ impl MoveTool {
             MoveTool::Simulate(tool) => tool.execute_serialized().await,
             MoveTool::Show(tool) => tool.execute_serialized().await,
             MoveTool::Test(tool) => tool.execute_serialized().await,
+            MoveTool::Trace(tool) => tool.execute().await,
             MoveTool::VerifyPackage(tool) => tool.execute_serialized().await,
             MoveTool::View(tool) => tool.execute_serialized().await,
             MoveTool::Replay(tool) => tool.execute_serialized().await,
@@ -543,6 +563,54 @@ pub struct TestPackage {
     /// Dump storage state on failure.
     #[clap(long = "dump")]
     pub dump_state: bool,
+
+    /// Number of runs with generated arguments for each `#[test_fuzz]` test
+    #[clap(long, default_value_t = 256)]
+    pub fuzz_runs: u64,
+
+    /// Seed for generating arguments of `#[test_fuzz]` tests
+    ///
+    /// A random seed is used if not set. The seed is printed when running fuzzed tests, and
+    /// failures report the seed to use to reproduce them.
+    #[clap(long)]
+    pub fuzz_seed: Option<u64>,
+
+    /// Serve the Debug Adapter Protocol on this address, e.g. `127.0.0.1:4711`
+    ///
+    /// Tests wait until a debugger (e.g. an editor with a `debugServer` launch configuration)
+    /// connects, and then run on a single thread under its control: execution stops at
+    /// breakpoints set in the Move sources, and locals and global resources can be inspected.
+    /// Only available if the CLI is built with the `debug-adapter` feature.
+    #[clap(long)]
+    pub debug_adapter: Option<String>,
+
+    /// Run tests with the production gas meter and the latest gas schedule, and save a gas report
+    /// for every test to `gas-profiling/tests` in the package directory
+    ///
+    /// Next to the HTML report, a `profile.json` summarizing the gas usage is saved for comparing
+    /// later runs against it with `--gas-baseline`. Unit tests produce no transaction output, so
+    /// the reports contain no storage fees and no per-frame storage deltas.
+    ///
+    /// For `#[test_fuzz]` tests, only the first run is profiled, and the fuzz seed is 0 unless
+    /// set with `--fuzz-seed`, so that the same arguments are profiled every time.
+    #[clap(long)]
+    pub profile_gas: bool,
+
+    /// Compare the gas usage of every test with the one saved by an earlier run with
+    /// `--profile-gas`, i.e., a copy of its `gas-profiling/tests` directory
+    ///
+    /// A report of the differences, matching call frames and native calls of both runs, is saved
+    /// to the `diff` directory of every test's gas report.
+    #[clap(long, requires = "profile_gas")]
+    pub gas_baseline: Option<PathBuf>,
+
+    /// Fail if the execution gas or IO gas of a test increased by more than this percentage
+    /// compared to the baseline
+    ///
+    /// Unit tests are not charged storage fees or IO gas for writes and events, so only execution
+    /// gas and IO gas for reading resources and loading modules are compared.
+    #[clap(long, requires = "gas_baseline")]
+    pub max_gas_increase: Option<f64>,
 }
 
 pub(crate) fn fix_bytecode_version(
@@ -560,6 +628,81 @@ pub(crate) fn fix_bytecode_version(
     }
 }
 
+/// The build configuration for running the unit tests of a package.
+pub(crate) fn unit_test_build_config(move_options: &MovePackageOptions) -> BuildConfig {
+    BuildConfig {
+        dev_mode: move_options.dev,
+        additional_named_addresses: move_options.named_addresses(),
+        test_mode: true,
+        full_model_generation: !move_options.skip_checks_on_test_code,
+        install_dir: move_options.output_dir.clone(),
+        skip_fetch_latest_git_deps: move_options.skip_fetch_latest_git_deps,
+        compiler_config: CompilerConfig {
+            known_attributes: extended_checks::get_all_attribute_names().clone(),
+            skip_attribute_checks: move_options.skip_attribute_checks,
+            bytecode_version: fix_bytecode_version(
+                move_options.bytecode_version,
+                move_options.language_version,
+            ),
+            compiler_version: move_options
+                .compiler_version
+                .or_else(|| Some(CompilerVersion::latest_stable())),
+            language_version: move_options
+                .language_version
+                .or_else(|| Some(LanguageVersion::latest_stable())),
+            experiments: move_options.compute_experiments(),
+        },
+        ..Default::default()
+    }
+}
+
+impl TestPackage {
+    /// Runs the tests with the production gas meter, and saves the gas reports of all tests.
+    fn run_tests_with_gas_profiling(
+        &self,
+        path: &Path,
+        config: BuildConfig,
+        mut unit_test_config: UnitTestingConfig,
+    ) -> CliTypedResult<UnitTestResult> {
+        // Fuzzed tests are profiled with the same arguments every time, so that the profiles can
+        // be compared across runs.
+        unit_test_config.fuzz_seed.get_or_insert(0);
+        let gas_params = AptosGasParameters::initial();
+        let natives = aptos_debug_natives::aptos_debug_natives(
+            gas_params.natives.clone(),
+            gas_params.vm.misc.clone(),
+        );
+        let factory = GasProfilingFactory::new(gas_params);
+        let logs = factory.logs();
+        let result = move_cli::base::test::run_move_unit_tests_with_factory(
+            path,
+            config,
+            unit_test_config,
+            natives,
+            aptos_test_feature_flags_genesis(),
+            self.compute_coverage,
+            &mut std::io::stdout(),
+            factory,
+            true,
+        )
+        .map_err(|err| CliError::UnexpectedError(format!("Failed to run tests: {:#}", err)))?;
+
+        let regressions = write_gas_reports(
+            &logs.lock().unwrap(),
+            &path.join("gas-profiling").join("tests"),
+            self.gas_baseline.as_deref(),
+            self.max_gas_increase,
+        )?;
+        if !regressions.is_empty() {
+            return Err(CliError::UnexpectedError(format!(
+                "Gas usage increased beyond the threshold:\n{}",
+                regressions.join("\n")
+            )));
+        }
+        Ok(result)
+    }
+}
+
 #[async_trait]
 impl CliCommand<&'static str> for TestPackage {
     fn command_name(&self) -> &'static str {
@@ -567,68 +710,50 @@ impl CliCommand<&'static str> for TestPackage {
     }
 
     async fn execute(self) -> CliTypedResult<&'static str> {
-        let known_attributes = extended_checks::get_all_attribute_names();
-        let mut config = BuildConfig {
-            dev_mode: self.move_options.dev,
-            additional_named_addresses: self.move_options.named_addresses(),
-            test_mode: true,
-            full_model_generation: !self.move_options.skip_checks_on_test_code,
-            install_dir: self.move_options.output_dir.clone(),
-            skip_fetch_latest_git_deps: self.move_options.skip_fetch_latest_git_deps,
-            compiler_config: CompilerConfig {
-                known_attributes: known_attributes.clone(),
-                skip_attribute_checks: self.move_options.skip_attribute_checks,
-                bytecode_version: fix_bytecode_version(
-                    self.move_options.bytecode_version,
-                    self.move_options.language_version,
-                ),
-                compiler_version: self
-                    .move_options
-                    .compiler_version
-                    .or_else(|| Some(CompilerVersion::latest_stable())),
-                language_version: self
-                    .move_options
-                    .language_version
-                    .or_else(|| Some(LanguageVersion::latest_stable())),
-                experiments: self.move_options.compute_experiments(),
-            },
-            ..Default::default()
-        };
+        let mut config = unit_test_build_config(&self.move_options);
 
         let path = self.move_options.get_package_path()?;
-        let result = move_cli::base::test::run_move_unit_tests(
-            path.as_path(),
-            config.clone(),
-            UnitTestingConfig {
-                filter: self.filter.clone(),
-                report_storage_on_error: self.dump_state,
-                ignore_compile_warnings: self.ignore_compile_warnings,
-                named_address_values: self
-                    .move_options
-                    .named_addresses
-                    .iter()
-                    .map(|(name, addr_wrap)| {
-                        (
-                            name.clone(),
-                            NumericalAddress::from_account_address(addr_wrap.account_address),
-                        )
-                    })
-                    .collect(),
-                ..UnitTestingConfig::default()
-            },
-            // TODO(Gas): we may want to switch to non-zero costs in the future
-            aptos_debug_natives::aptos_debug_natives(
-                NativeGasParameters::zeros(),
-                MiscGasParameters::zeros(),
-            ),
-            aptos_test_feature_flags_genesis(),
-            None,
-            None,
-            self.compute_coverage,
-            &mut std::io::stdout(),
-            true,
-        )
-        .map_err(|err| CliError::UnexpectedError(format!("Failed to run tests: {:#}", err)))?;
+        let unit_test_config = UnitTestingConfig {
+            filter: self.filter.clone(),
+            report_storage_on_error: self.dump_state,
+            ignore_compile_warnings: self.ignore_compile_warnings,
+            fuzz_runs: self.fuzz_runs,
+            fuzz_seed: self.fuzz_seed,
+            debug_adapter: self.debug_adapter.clone(),
+            named_address_values: self
+                .move_options
+                .named_addresses
+                .iter()
+                .map(|(name, addr_wrap)| {
+                    (
+                        name.clone(),
+                        NumericalAddress::from_account_address(addr_wrap.account_address),
+                    )
+                })
+                .collect(),
+            ..UnitTestingConfig::default()
+        };
+        let result = if self.profile_gas {
+            self.run_tests_with_gas_profiling(&path, config.clone(), unit_test_config)?
+        } else {
+            move_cli::base::test::run_move_unit_tests(
+                path.as_path(),
+                config.clone(),
+                unit_test_config,
+                // TODO(Gas): we may want to switch to non-zero costs in the future
+                aptos_debug_natives::aptos_debug_natives(
+                    NativeGasParameters::zeros(),
+                    MiscGasParameters::zeros(),
+                ),
+                aptos_test_feature_flags_genesis(),
+                None,
+                None,
+                self.compute_coverage,
+                &mut std::io::stdout(),
+                true,
+            )
+            .map_err(|err| CliError::UnexpectedError(format!("Failed to run tests: {:#}", err)))?
+        };
 
         // Print coverage summary if --coverage is set
         if self.compute_coverage {
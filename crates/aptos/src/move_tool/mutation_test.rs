@@ -0,0 +1,830 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::types::{CliCommand, CliError, CliTypedResult, MovePackageOptions},
+    move_tool::{aptos_debug_natives, unit_test_build_config, IncludedArtifacts},
+};
+use aptos_crypto::HashValue;
+use aptos_framework::build_model;
+use aptos_gas_schedule::{MiscGasParameters, NativeGasParameters};
+use aptos_types::on_chain_config::aptos_test_feature_flags_genesis;
+use async_trait::async_trait;
+use clap::Parser;
+use move_cli::base::test::{run_move_unit_tests, UnitTestResult};
+use move_model::{
+    ast::{ExpData, Operation, Value},
+    model::{GlobalEnv, Loc},
+};
+use move_package::{
+    compilation::build_plan::BuildPlan, resolution::resolution_graph::ResolvedGraph, BuildConfig,
+};
+use move_unit_test::UnitTestingConfig;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+use tempfile::TempDir;
+use termcolor::NoColor;
+
+/// File in the package directory the outcomes of tested mutants are saved to.
+const CACHE_FILE: &str = ".mutation_cache.json";
+
+/// Runs mutation testing on a package
+///
+/// Mutants of the functions of the package are derived from the Move model of its sources, by
+/// swapping operators, changing constants, and removing `assert!`s and negations. The unit tests
+/// are run against every mutant. Mutants for which all tests still pass are reported per function,
+/// as they point to behavior which is not checked by the tests.
+///
+/// Outcomes are saved to `.mutation_cache.json` in the package directory, and reused as long as
+/// neither the sources of the package and its dependencies nor the test filter changed.
+#[derive(Parser)]
+pub struct MutationTestPackage {
+    /// Only mutate functions of the given module, e.g. `coin`. Can be repeated.
+    #[clap(long = "module")]
+    pub(crate) modules: Vec<String>,
+
+    /// Only mutate the given function, e.g. `coin::transfer`. Can be repeated.
+    #[clap(long = "function")]
+    pub(crate) functions: Vec<String>,
+
+    /// A filter string to determine which unit tests to run against each mutant
+    #[clap(long, short)]
+    pub(crate) filter: Option<String>,
+
+    /// The number of mutants to test in parallel
+    ///
+    /// Defaults to the number of available CPUs.
+    #[clap(long)]
+    pub(crate) jobs: Option<usize>,
+
+    /// Test all mutants, instead of reusing the outcomes of earlier runs
+    #[clap(long)]
+    pub(crate) force: bool,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageOptions,
+}
+
+/// Result of mutation testing a package.
+#[derive(Debug, Serialize)]
+pub struct MutationReport {
+    pub mutants: usize,
+    pub killed: usize,
+    pub survived: usize,
+    /// Mutants which do not compile, e.g., because a changed constant does not fit its type.
+    pub invalid: usize,
+    /// Mutants whose outcome was taken from an earlier run.
+    pub cached: usize,
+    pub functions: Vec<FunctionReport>,
+}
+
+/// Result of mutation testing a function.
+#[derive(Debug, Serialize)]
+pub struct FunctionReport {
+    pub function: String,
+    pub mutants: usize,
+    pub killed: usize,
+    pub surviving: Vec<SurvivingMutant>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SurvivingMutant {
+    pub location: String,
+    pub mutation: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Outcome {
+    /// At least one test failed.
+    Killed,
+    /// All tests passed.
+    Survived,
+    /// The mutant is rejected by the compiler.
+    Invalid,
+}
+
+/// Outcomes of tested mutants, keyed by the hash of the mutant, the sources it was tested against,
+/// and the test filter.
+#[derive(Default, Serialize, Deserialize)]
+struct MutationCache {
+    outcomes: BTreeMap<String, Outcome>,
+}
+
+/// A change to the source of a function.
+#[derive(Clone, Debug)]
+struct Mutant {
+    function: String,
+    /// The mutated file, relative to the package directory.
+    file: PathBuf,
+    /// The mutated location, as `file:line:column`.
+    location: String,
+    /// The byte range of the mutated source.
+    start: usize,
+    end: usize,
+    original: String,
+    replacement: String,
+}
+
+impl Mutant {
+    fn description(&self) -> String {
+        if self.replacement.is_empty() {
+            format!("removed `{}`", self.original)
+        } else if self.original.starts_with("assert!") {
+            format!("removed `{}`", abbreviate(&self.original))
+        } else {
+            format!("replaced `{}` with `{}`", self.original, self.replacement)
+        }
+    }
+
+    /// Returns the source of the mutated file, with the mutation applied.
+    fn apply(&self, source: &str) -> CliTypedResult<String> {
+        if source.get(self.start..self.end) != Some(self.original.as_str()) {
+            return Err(CliError::UnexpectedError(format!(
+                "{} changed while mutation testing",
+                self.file.display()
+            )));
+        }
+        Ok(format!(
+            "{}{}{}",
+            &source[..self.start],
+            self.replacement,
+            &source[self.end..]
+        ))
+    }
+}
+
+#[async_trait]
+impl CliCommand<MutationReport> for MutationTestPackage {
+    fn command_name(&self) -> &'static str {
+        "MutationTestPackage"
+    }
+
+    async fn execute(self) -> CliTypedResult<MutationReport> {
+        let package_dir = self.move_options.get_package_path()?;
+        let package_dir = package_dir
+            .canonicalize()
+            .map_err(|err| CliError::IO(package_dir.display().to_string(), err))?;
+        let options = IncludedArtifacts::None.build_options(&self.move_options)?;
+        let env = build_model(
+            options.dev,
+            &package_dir,
+            options.named_addresses,
+            None,
+            options.bytecode_version,
+            options.compiler_version,
+            options.language_version,
+            options.skip_attribute_checks,
+            options.known_attributes,
+            options.experiments,
+        )
+        .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+        let mutants = self.collect_mutants(&env, &package_dir);
+
+        let mut config = unit_test_build_config(&self.move_options);
+        let resolved_graph = config
+            .clone()
+            .resolution_graph_for_package(&package_dir, &mut io::sink())
+            .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+        // Dependencies are fetched by now, so neither the baseline run nor the mutants, which are
+        // built in parallel, need to resolve them again.
+        config.skip_fetch_latest_git_deps = true;
+
+        let cache_path = package_dir.join(CACHE_FILE);
+        let cache = if self.force {
+            MutationCache::default()
+        } else {
+            fs::read_to_string(&cache_path)
+                .ok()
+                .and_then(|contents| serde_json::from_str(&contents).ok())
+                .unwrap_or_default()
+        };
+        let sources_digest = sources_digest(&resolved_graph, self.filter.as_deref());
+        let keys = mutants
+            .iter()
+            .map(|mutant| cache_key(mutant, &sources_digest))
+            .collect::<Vec<_>>();
+        let mut outcomes = keys
+            .iter()
+            .map(|key| cache.outcomes.get(key).copied())
+            .collect::<Vec<_>>();
+        let cached = outcomes.iter().filter(|outcome| outcome.is_some()).count();
+
+        let pending = (0..mutants.len())
+            .filter(|idx| outcomes[*idx].is_none())
+            .collect::<Vec<_>>();
+        if !pending.is_empty() {
+            let result = self
+                .run_tests(&package_dir, config.clone())
+                .map_err(|err| {
+                    CliError::UnexpectedError(format!("Failed to run tests: {:#}", err))
+                })?;
+            if result != UnitTestResult::Success {
+                return Err(CliError::UnexpectedError(
+                    "Tests fail without mutations, fix them before mutation testing".to_string(),
+                ));
+            }
+            // Every mutant is built in its own copy of the package.
+            config.install_dir = None;
+            for (idx, outcome) in self.test_mutants(&package_dir, &config, &mutants, &pending)? {
+                outcomes[idx] = Some(outcome);
+            }
+        }
+
+        let outcomes = outcomes
+            .into_iter()
+            .map(|outcome| outcome.expect("all mutants are tested"))
+            .collect::<Vec<_>>();
+        let cache = MutationCache {
+            outcomes: keys.into_iter().zip(outcomes.iter().copied()).collect(),
+        };
+        fs::write(
+            &cache_path,
+            serde_json::to_string_pretty(&cache)
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))?,
+        )
+        .map_err(|err| CliError::IO(cache_path.display().to_string(), err))?;
+
+        Ok(report(&mutants, &outcomes, cached))
+    }
+}
+
+impl MutationTestPackage {
+    /// Derives the mutants of all functions of the package which are selected for mutation.
+    fn collect_mutants(&self, env: &GlobalEnv, package_dir: &Path) -> Vec<Mutant> {
+        let mut mutants = vec![];
+        for module in env.get_modules() {
+            if !module.is_target() || module.is_script_module() {
+                continue;
+            }
+            let module_name = module.get_name().display(env).to_string();
+            if !self.modules.is_empty() && !self.modules.contains(&module_name) {
+                continue;
+            }
+            let file_id = module.get_loc().file_id();
+            let Some(file) = Path::new(env.get_file(file_id))
+                .canonicalize()
+                .ok()
+                .and_then(|path| {
+                    path.strip_prefix(package_dir)
+                        .ok()
+                        .map(|path| path.to_path_buf())
+                })
+            else {
+                continue;
+            };
+            for function in module.get_functions() {
+                let function_name = function.get_full_name_str();
+                if !self.functions.is_empty() && !self.functions.contains(&function_name) {
+                    continue;
+                }
+                let Some(def) = function.get_def() else {
+                    continue;
+                };
+                def.visit_pre_order(&mut |exp| {
+                    for (loc, start, end, replacement) in mutations(env, exp) {
+                        // Skip code of inline functions from other files.
+                        if loc.file_id() != file_id {
+                            continue;
+                        }
+                        let source = env.get_file_source(loc.file_id());
+                        let location = env
+                            .get_location(&loc)
+                            .map(|location| {
+                                format!(
+                                    "{}:{}:{}",
+                                    file.display(),
+                                    location.line.0 + 1,
+                                    location.column.0 + 1
+                                )
+                            })
+                            .unwrap_or_else(|| file.display().to_string());
+                        mutants.push(Mutant {
+                            function: function_name.clone(),
+                            file: file.clone(),
+                            location,
+                            start,
+                            end,
+                            original: source[start..end].to_string(),
+                            replacement,
+                        });
+                    }
+                    true
+                });
+            }
+        }
+        mutants
+    }
+
+    /// Tests the pending mutants, on up to `jobs` threads.
+    fn test_mutants(
+        &self,
+        package_dir: &Path,
+        config: &BuildConfig,
+        mutants: &[Mutant],
+        pending: &[usize],
+    ) -> CliTypedResult<Vec<(usize, Outcome)>> {
+        let jobs = self
+            .jobs
+            .or_else(|| thread::available_parallelism().ok().map(|jobs| jobs.get()))
+            .unwrap_or(1)
+            .max(1);
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(vec![]);
+        thread::scope(|scope| {
+            for _ in 0..jobs.min(pending.len()) {
+                scope.spawn(|| {
+                    while let Some(idx) = pending.get(next.fetch_add(1, Ordering::SeqCst)) {
+                        let mutant = &mutants[*idx];
+                        let result = self.test_mutant(package_dir, config.clone(), mutant);
+                        if let Ok(outcome) = &result {
+                            eprintln!(
+                                "{:?}: {} at {}",
+                                outcome,
+                                mutant.description(),
+                                mutant.location
+                            );
+                        }
+                        results
+                            .lock()
+                            .unwrap()
+                            .push(result.map(|outcome| (*idx, outcome)));
+                    }
+                });
+            }
+        });
+        results.into_inner().unwrap().into_iter().collect()
+    }
+
+    /// Runs the tests against a copy of the package with the mutation applied.
+    fn test_mutant(
+        &self,
+        package_dir: &Path,
+        config: BuildConfig,
+        mutant: &Mutant,
+    ) -> CliTypedResult<Outcome> {
+        let temp_dir =
+            TempDir::new().map_err(|err| CliError::IO("temporary directory".to_string(), err))?;
+        copy_package(package_dir, temp_dir.path())?;
+        let source_path = package_dir.join(&mutant.file);
+        let source = fs::read_to_string(&source_path)
+            .map_err(|err| CliError::IO(source_path.display().to_string(), err))?;
+        let mutated_path = temp_dir.path().join(&mutant.file);
+        fs::write(&mutated_path, mutant.apply(&source)?)
+            .map_err(|err| CliError::IO(mutated_path.display().to_string(), err))?;
+        Ok(match self.run_tests(temp_dir.path(), config.clone()) {
+            Ok(UnitTestResult::Success) => Outcome::Survived,
+            Ok(UnitTestResult::Failure) => Outcome::Killed,
+            Err(_) if is_rejected_by_compiler(temp_dir.path(), config) => Outcome::Invalid,
+            Err(err) => {
+                return Err(CliError::UnexpectedError(format!(
+                    "Failed to run tests with {} at {}: {:#}",
+                    mutant.description(),
+                    mutant.location,
+                    err
+                )))
+            },
+        })
+    }
+
+    fn run_tests(&self, path: &Path, config: BuildConfig) -> anyhow::Result<UnitTestResult> {
+        run_move_unit_tests(
+            path,
+            config,
+            UnitTestingConfig {
+                filter: self.filter.clone(),
+                ..UnitTestingConfig::default()
+            },
+            aptos_debug_natives::aptos_debug_natives(
+                NativeGasParameters::zeros(),
+                MiscGasParameters::zeros(),
+            ),
+            aptos_test_feature_flags_genesis(),
+            None,
+            None,
+            false,
+            &mut io::sink(),
+            true,
+        )
+    }
+}
+
+/// Returns the mutations of an expression, as the location of the expression, the byte range of
+/// the source to replace, and its replacement.
+///
+/// Mutations are only derived if the source has the expected shape, so that code generated by the
+/// compiler, e.g., for loops over ranges, is not mutated.
+fn mutations(env: &GlobalEnv, exp: &ExpData) -> Vec<(Loc, usize, usize, String)> {
+    let loc = env.get_node_loc(exp.node_id());
+    let source = env.get_file_source(loc.file_id());
+    let (start, end) = (loc.span().start().to_usize(), loc.span().end().to_usize());
+    let Some(text) = source.get(start..end) else {
+        return vec![];
+    };
+    match exp {
+        ExpData::Call(_, Operation::Not, _) if text.starts_with('!') => {
+            vec![(loc, start, start + 1, String::new())]
+        },
+        ExpData::Call(_, op, args) if args.len() == 2 => {
+            let Some((token, swapped)) = swapped_operator(op) else {
+                return vec![];
+            };
+            let lhs_end = env.get_node_loc(args[0].node_id()).span().end().to_usize();
+            let rhs_start = env
+                .get_node_loc(args[1].node_id())
+                .span()
+                .start()
+                .to_usize();
+            let Some(between) = source.get(lhs_end..rhs_start) else {
+                return vec![];
+            };
+            // Also covers compound assignments, e.g. `x += 1`.
+            let operator = between.trim();
+            if operator != token && operator != format!("{}=", token) {
+                return vec![];
+            }
+            let offset = lhs_end + between.find(token).expect("operator is in between");
+            vec![(loc, offset, offset + token.len(), swapped.to_string())]
+        },
+        ExpData::Value(_, Value::Number(n))
+            if text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+        {
+            let replacement = if n.bits() == 0 {
+                "1".to_string()
+            } else {
+                (n + 1u32).to_string()
+            };
+            vec![(loc, start, end, replacement)]
+        },
+        ExpData::Value(_, Value::Bool(b)) if text == b.to_string() => {
+            vec![(loc, start, end, (!b).to_string())]
+        },
+        ExpData::IfElse(_, _, _, else_branch)
+            if matches!(else_branch.as_ref(), ExpData::Call(_, Operation::Abort, _))
+                && text.starts_with("assert!")
+                && text.ends_with(')') =>
+        {
+            vec![(loc, start, end, "()".to_string())]
+        },
+        _ => vec![],
+    }
+}
+
+/// Returns the source token of a binary operator, and the token it is swapped with.
+fn swapped_operator(op: &Operation) -> Option<(&'static str, &'static str)> {
+    use Operation::*;
+    Some(match op {
+        Add => ("+", "-"),
+        Sub => ("-", "+"),
+        Mul => ("*", "/"),
+        Div => ("/", "*"),
+        Mod => ("%", "*"),
+        BitOr => ("|", "&"),
+        BitAnd => ("&", "|"),
+        Xor => ("^", "|"),
+        Shl => ("<<", ">>"),
+        Shr => (">>", "<<"),
+        And => ("&&", "||"),
+        Or => ("||", "&&"),
+        Eq => ("==", "!="),
+        Neq => ("!=", "=="),
+        Lt => ("<", "<="),
+        Le => ("<=", "<"),
+        Gt => (">", ">="),
+        Ge => (">=", ">"),
+        _ => return None,
+    })
+}
+
+fn report(mutants: &[Mutant], outcomes: &[Outcome], cached: usize) -> MutationReport {
+    let mut functions = BTreeMap::<&str, FunctionReport>::new();
+    for (mutant, outcome) in mutants.iter().zip(outcomes) {
+        let function = functions
+            .entry(&mutant.function)
+            .or_insert_with(|| FunctionReport {
+                function: mutant.function.clone(),
+                mutants: 0,
+                killed: 0,
+                surviving: vec![],
+            });
+        match outcome {
+            Outcome::Killed => function.killed += 1,
+            Outcome::Survived => function.surviving.push(SurvivingMutant {
+                location: mutant.location.clone(),
+                mutation: mutant.description(),
+            }),
+            Outcome::Invalid => continue,
+        }
+        function.mutants += 1;
+    }
+    let count = |expected| {
+        outcomes
+            .iter()
+            .filter(|outcome| **outcome == expected)
+            .count()
+    };
+    MutationReport {
+        mutants: mutants.len(),
+        killed: count(Outcome::Killed),
+        survived: count(Outcome::Survived),
+        invalid: count(Outcome::Invalid),
+        cached,
+        functions: functions.into_values().collect(),
+    }
+}
+
+/// Whether the compiler rejects the package, as opposed to it failing to build for other reasons,
+/// e.g., because a file cannot be read. Diagnostics are not reported, as the tests already did.
+fn is_rejected_by_compiler(path: &Path, config: BuildConfig) -> bool {
+    let compiler_config = config.compiler_config.clone();
+    let mut rejected = false;
+    let _ = config
+        .resolution_graph_for_package(path, &mut io::sink())
+        .and_then(BuildPlan::create)
+        .and_then(|plan| {
+            plan.compile_with_driver(&mut io::sink(), &compiler_config, vec![], |options| {
+                let mut output = NoColor::new(io::sink());
+                let mut emitter = options.error_emitter(&mut output);
+                let (env, units) = move_compiler_v2::run_move_compiler(emitter.as_mut(), options)
+                    .inspect_err(|_| rejected = true)?;
+                Ok((move_compiler_v2::make_files_source_text(&env), units, env))
+            })
+        });
+    rejected
+}
+
+/// Identifies a mutant together with the sources its outcome depends on.
+fn cache_key(mutant: &Mutant, sources_digest: &str) -> String {
+    let key = format!(
+        "{}:{}:{}:{}:{}",
+        sources_digest,
+        mutant.file.display(),
+        mutant.start,
+        mutant.end,
+        mutant.replacement
+    );
+    HashValue::sha3_256_of(key.as_bytes()).to_hex()
+}
+
+/// Hashes the sources and manifests of the package and all its dependencies, which covers the
+/// revisions of git dependencies, together with the test filter.
+fn sources_digest(resolved_graph: &ResolvedGraph, filter: Option<&str>) -> String {
+    let mut digest = String::new();
+    for (name, package) in &resolved_graph.package_table {
+        digest.push_str(&format!("{}:{};", name, package.source_digest));
+    }
+    digest.push_str(&format!("filter:{:?}", filter));
+    HashValue::sha3_256_of(digest.as_bytes()).to_hex()
+}
+
+/// Lists the files of the package, in a stable order, leaving out build outputs and hidden files.
+fn package_files(package_dir: &Path) -> CliTypedResult<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut dirs = vec![package_dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries =
+            fs::read_dir(&dir).map_err(|err| CliError::IO(dir.display().to_string(), err))?;
+        for entry in entries {
+            let path = entry
+                .map_err(|err| CliError::IO(dir.display().to_string(), err))?
+                .path();
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if name.starts_with('.') || (dir == package_dir && name == "build") {
+                continue;
+            }
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Copies the package into the target directory. Local dependencies are made absolute, so that
+/// they still resolve from the copy.
+fn copy_package(package_dir: &Path, target_dir: &Path) -> CliTypedResult<()> {
+    for path in package_files(package_dir)? {
+        let target = target_dir.join(path.strip_prefix(package_dir).expect("file is in package"));
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| CliError::IO(parent.display().to_string(), err))?;
+        }
+        if path == package_dir.join("Move.toml") {
+            let manifest = fs::read_to_string(&path)
+                .map_err(|err| CliError::IO(path.display().to_string(), err))?;
+            fs::write(
+                &target,
+                absolutize_local_dependencies(&manifest, package_dir)?,
+            )
+            .map_err(|err| CliError::IO(target.display().to_string(), err))?;
+        } else {
+            fs::copy(&path, &target)
+                .map_err(|err| CliError::IO(target.display().to_string(), err))?;
+        }
+    }
+    Ok(())
+}
+
+fn absolutize_local_dependencies(manifest: &str, package_dir: &Path) -> CliTypedResult<String> {
+    let mut manifest: toml::Value = toml::from_str(manifest)
+        .map_err(|err| CliError::UnexpectedError(format!("Invalid Move.toml: {}", err)))?;
+    for section in ["dependencies", "dev-dependencies"] {
+        let Some(dependencies) = manifest
+            .get_mut(section)
+            .and_then(|dependencies| dependencies.as_table_mut())
+        else {
+            continue;
+        };
+        for dependency in dependencies.values_mut() {
+            if let Some(toml::Value::String(local)) = dependency.get_mut("local") {
+                *local = package_dir.join(&*local).display().to_string();
+            }
+        }
+    }
+    toml::to_string(&manifest).map_err(|err| CliError::UnexpectedError(err.to_string()))
+}
+
+/// Shortens source spanning multiple lines to its first line.
+fn abbreviate(source: &str) -> String {
+    match source.split_once('\n') {
+        Some((first_line, _)) => format!("{} ...", first_line.trim_end()),
+        None => source.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_model::metadata::LanguageVersion;
+    use std::collections::BTreeSet;
+
+    const SOURCE: &str = "module 0x42::m {
+    public fun f(x: u64, flag: bool): u64 {
+        assert!(x < 100, 1);
+        let y = x + 2;
+        if (!flag) y * 3 else y
+    }
+
+    public fun sum(n: u64): u64 {
+        let sum = 0;
+        for (i in 0..n) {
+            sum += i;
+        };
+        sum
+    }
+}
+";
+
+    fn mutant(function: &str, original: &str, replacement: &str) -> Mutant {
+        Mutant {
+            function: function.to_string(),
+            file: PathBuf::from("sources/m.move"),
+            location: "sources/m.move:2:5".to_string(),
+            start: 0,
+            end: original.len(),
+            original: original.to_string(),
+            replacement: replacement.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_mutations() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let package_dir = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir_all(package_dir.join("sources")).unwrap();
+        fs::write(
+            package_dir.join("Move.toml"),
+            "[package]\nname = \"m\"\nversion = \"0.0.0\"\n",
+        )
+        .unwrap();
+        fs::write(package_dir.join("sources").join("m.move"), SOURCE).unwrap();
+        let env = build_model(
+            true,
+            &package_dir,
+            BTreeMap::new(),
+            None,
+            None,
+            None,
+            Some(LanguageVersion::latest_stable()),
+            false,
+            BTreeSet::new(),
+            vec![],
+        )
+        .unwrap();
+
+        let command = MutationTestPackage::parse_from([
+            "mutation-test",
+            "--package-dir",
+            package_dir.to_str().unwrap(),
+        ]);
+        let mut mutations = command
+            .collect_mutants(&env, &package_dir)
+            .into_iter()
+            .map(|mutant| {
+                assert_eq!(mutant.file, Path::new("sources/m.move"));
+                assert_eq!(&SOURCE[mutant.start..mutant.end], mutant.original);
+                (mutant.original, mutant.replacement)
+            })
+            .collect::<Vec<_>>();
+        mutations.sort();
+        // The code generated for the loop over the range is not mutated, only its bounds.
+        let expected = [
+            ("!", ""),
+            ("*", "/"),
+            ("+", "-"),
+            ("+", "-"),
+            ("0", "1"),
+            ("0", "1"),
+            ("1", "2"),
+            ("100", "101"),
+            ("2", "3"),
+            ("3", "4"),
+            ("<", "<="),
+            ("assert!(x < 100, 1)", "()"),
+        ]
+        .map(|(original, replacement)| (original.to_string(), replacement.to_string()));
+        assert_eq!(mutations, expected);
+    }
+
+    #[test]
+    fn test_absolutize_local_dependencies() {
+        let manifest = r#"[package]
+name = "app"
+version = "0.0.0"
+
+[dependencies]
+lib = { local = "../lib" }
+AptosFramework = { git = "https://github.com/aptos-labs/aptos-framework.git", rev = "mainnet", subdir = "aptos-framework" }
+
+[dev-dependencies]
+test_utils = { local = "test_utils" }
+"#;
+        let package_dir = Path::new("/work/app");
+        let manifest: toml::Value =
+            toml::from_str(&absolutize_local_dependencies(manifest, package_dir).unwrap()).unwrap();
+        assert_eq!(
+            manifest["dependencies"]["lib"]["local"].as_str(),
+            Some(package_dir.join("../lib").to_str().unwrap())
+        );
+        assert_eq!(
+            manifest["dev-dependencies"]["test_utils"]["local"].as_str(),
+            Some(package_dir.join("test_utils").to_str().unwrap())
+        );
+        let framework = &manifest["dependencies"]["AptosFramework"];
+        assert_eq!(framework["rev"].as_str(), Some("mainnet"));
+        assert_eq!(framework.get("local"), None);
+        assert_eq!(manifest["package"]["name"].as_str(), Some("app"));
+
+        assert!(absolutize_local_dependencies("[package", package_dir).is_err());
+    }
+
+    #[test]
+    fn test_report() {
+        let mutants = [
+            mutant("m::f", "+", "-"),
+            mutant("m::f", "<", "<="),
+            mutant("m::f", "255", "256"),
+            mutant("m::g", "true", "false"),
+        ];
+        let outcomes = [
+            Outcome::Killed,
+            Outcome::Survived,
+            Outcome::Invalid,
+            Outcome::Killed,
+        ];
+        let report = report(&mutants, &outcomes, 1);
+        assert_eq!(
+            (
+                report.mutants,
+                report.killed,
+                report.survived,
+                report.invalid,
+                report.cached
+            ),
+            (4, 2, 1, 1, 1)
+        );
+
+        let [f, g] = report.functions.as_slice() else {
+            panic!("expected two functions, got {:?}", report.functions)
+        };
+        // Invalid mutants are not counted per function.
+        assert_eq!((f.function.as_str(), f.mutants, f.killed), ("m::f", 2, 1));
+        assert_eq!(f.surviving.len(), 1);
+        assert_eq!(f.surviving[0].location, "sources/m.move:2:5");
+        assert_eq!(f.surviving[0].mutation, "replaced `<` with `<=`");
+        assert_eq!((g.function.as_str(), g.mutants, g.killed), ("m::g", 1, 1));
+        assert!(g.surviving.is_empty());
+    }
+}
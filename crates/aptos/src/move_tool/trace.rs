@@ -0,0 +1,308 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        transactions::TxnOptions,
+        types::{
+            CliCommand, CliError, CliResult, CliTypedResult, EntryFunctionArguments,
+            TransactionSummary,
+        },
+    },
+    move_tool::ReplayNetworkSelection,
+};
+use aptos_move_debugger::aptos_debugger::AptosDebugger;
+use aptos_rest_client::Client;
+use aptos_types::transaction::{
+    AuxiliaryInfo, ReplayProtector, Transaction, TransactionPayload, TransactionStatus,
+};
+use async_trait::async_trait;
+use clap::{Parser, Subcommand};
+use legacy_move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
+use move_bytecode_source_map::source_index::SourceIndex;
+use move_command_line_common::files::FileHash;
+use move_execution_trace::{render, ExecutionTrace, RenderOptions};
+use move_package::compilation::compiled_package::OnDiskCompiledPackage;
+use move_vm_runtime::tracing::is_trace_recording_supported;
+use std::path::PathBuf;
+
+/// Record and view structured traces of Move execution
+///
+/// A trace contains the call frames and bytecode instructions executed by a transaction, the gas
+/// charged for each instruction, and the resources read and written and the events emitted.
+/// Traces are rendered against Move sources if the compiled packages are provided with
+/// `--source-package`.
+#[derive(Subcommand)]
+pub enum TraceTool {
+    Run(TraceRun),
+    Replay(TraceReplay),
+    Show(TraceShow),
+}
+
+impl TraceTool {
+    pub async fn execute(self) -> CliResult {
+        match self {
+            Self::Run(tool) => tool.execute_serialized().await,
+            Self::Replay(tool) => tool.execute_serialized().await,
+            Self::Show(tool) => tool.execute_serialized_success().await,
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct TraceRenderOptions {
+    /// Path to a compiled package whose sources the trace is rendered against, i.e. a
+    /// `build/<PackageName>` directory created by `aptos move compile`. Can be repeated.
+    #[clap(long, value_parser)]
+    pub(crate) source_package: Vec<PathBuf>,
+
+    /// Only show frames of functions in the given module, e.g. `0x1::coin` or `coin`. Calls made
+    /// from other modules are still shown. Can be repeated.
+    #[clap(long = "module")]
+    pub(crate) modules: Vec<String>,
+
+    /// Show calls into the given module as a single line. Can be repeated.
+    #[clap(long)]
+    pub(crate) fold: Vec<String>,
+
+    /// Show the bytecode instructions executed for each source line
+    #[clap(long)]
+    pub(crate) bytecode: bool,
+}
+
+impl TraceRenderOptions {
+    fn render(&self, trace: &ExecutionTrace) -> CliTypedResult<String> {
+        let mut sources = SourceIndex::new();
+        for path in &self.source_package {
+            let package = OnDiskCompiledPackage::from_path(path)
+                .and_then(|package| package.into_compiled_package())
+                .map_err(|err| {
+                    CliError::UnexpectedError(format!(
+                        "Failed to load compiled package at {}: {}",
+                        path.display(),
+                        err
+                    ))
+                })?;
+            for unit in package.all_modules() {
+                if let CompiledUnit::Module(NamedCompiledModule {
+                    module, source_map, ..
+                }) = &unit.unit
+                {
+                    // Sources of dependencies may not have been saved with the package.
+                    if let Ok(text) = std::fs::read_to_string(&unit.source_path) {
+                        sources.add_file(FileHash::new(&text), &unit.source_path, &text);
+                    }
+                    sources.add_module(module.clone(), source_map.clone());
+                }
+            }
+        }
+        let options = RenderOptions {
+            modules: self.modules.clone(),
+            fold: self.fold.clone(),
+            bytecode: self.bytecode,
+        };
+        Ok(render(trace, &sources, &options))
+    }
+}
+
+/// Prints the trace and saves it to the output file, if any.
+fn emit_trace(
+    trace: &ExecutionTrace,
+    output_file: Option<&PathBuf>,
+    render_options: &TraceRenderOptions,
+) -> CliTypedResult<()> {
+    if let Some(path) = output_file {
+        trace.save(path).map_err(|err| {
+            CliError::UnexpectedError(format!(
+                "Failed to save trace to {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+    }
+    println!("{}", render_options.render(trace)?);
+    Ok(())
+}
+
+fn ensure_trace_recording_supported() -> CliTypedResult<()> {
+    if !is_trace_recording_supported() {
+        return Err(CliError::UnexpectedError(
+            "Recording traces requires a CLI built with the Move VM `debugging` feature"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Simulate a Move function locally and trace its execution
+///
+/// The transaction is executed against the current state of the network, which is fetched on
+/// demand, and is not submitted.
+#[derive(Parser)]
+pub struct TraceRun {
+    #[clap(flatten)]
+    pub(crate) entry_function_args: EntryFunctionArguments,
+
+    #[clap(flatten)]
+    pub(crate) txn_options: TxnOptions,
+
+    /// File to save the trace to, in JSON
+    #[clap(long, value_parser)]
+    pub(crate) output_file: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) render_options: TraceRenderOptions,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for TraceRun {
+    fn command_name(&self) -> &'static str {
+        "TraceRun"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        ensure_trace_recording_supported()?;
+        let payload = TransactionPayload::EntryFunction(self.entry_function_args.try_into()?);
+
+        let mut trace = None;
+        let summary = self
+            .txn_options
+            .simulate_using_debugger(payload, |debugger, version, txn, _hash, aux_info| {
+                let (vm_status, vm_output, txn_trace) = debugger
+                    .execute_transaction_at_version_with_trace(
+                        version,
+                        txn,
+                        AuxiliaryInfo::new(aux_info, None),
+                    )
+                    .map_err(|err| {
+                        CliError::UnexpectedError(format!(
+                            "Failed to simulate transaction with tracing: {}",
+                            err
+                        ))
+                    })?;
+                trace = Some(txn_trace);
+                Ok((vm_status, vm_output))
+            })
+            .await?;
+
+        let trace = trace.expect("trace is recorded by the simulation");
+        emit_trace(&trace, self.output_file.as_ref(), &self.render_options)?;
+        Ok(summary)
+    }
+}
+
+/// Replay a committed transaction using a local VM and trace its execution
+#[derive(Parser)]
+pub struct TraceReplay {
+    /// The network to replay on.
+    ///
+    /// Possible values:
+    ///     mainnet, testnet, <REST_ENDPOINT_URL>
+    #[clap(long)]
+    pub(crate) network: ReplayNetworkSelection,
+
+    /// The id of the transaction to replay. Also being referred to as "version" in some contexts.
+    #[clap(long)]
+    pub(crate) txn_id: u64,
+
+    /// Key to use for ratelimiting purposes with the node API. This value will be used
+    /// as `Authorization: Bearer <key>`
+    #[clap(long)]
+    pub(crate) node_api_key: Option<String>,
+
+    /// File to save the trace to, in JSON
+    #[clap(long, value_parser)]
+    pub(crate) output_file: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) render_options: TraceRenderOptions,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for TraceReplay {
+    fn command_name(&self) -> &'static str {
+        "TraceReplay"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        ensure_trace_recording_supported()?;
+
+        let client = Client::builder(self.network.to_base_url()?);
+        let client = if let Some(api_key) = &self.node_api_key {
+            client.api_key(api_key).unwrap().build()
+        } else {
+            client.build()
+        };
+        let debugger = AptosDebugger::rest_client(client)?;
+
+        let (txn, _, aux_info) = debugger
+            .get_committed_transaction_at_version(self.txn_id)
+            .await?;
+        let Transaction::UserTransaction(txn) = txn else {
+            return Err(CliError::UnexpectedError(
+                "Unsupported transaction type. Only user transactions are supported.".to_string(),
+            ));
+        };
+
+        let (vm_status, vm_output, trace) = debugger
+            .execute_transaction_at_version_with_trace(
+                self.txn_id,
+                txn.clone(),
+                AuxiliaryInfo::new(aux_info, None),
+            )
+            .map_err(|err| {
+                CliError::UnexpectedError(format!(
+                    "Failed to replay transaction with tracing: {}",
+                    err
+                ))
+            })?;
+        emit_trace(&trace, self.output_file.as_ref(), &self.render_options)?;
+
+        let success = match vm_output.status() {
+            TransactionStatus::Keep(exec_status) => Some(exec_status.is_success()),
+            TransactionStatus::Discard(_) | TransactionStatus::Retry => None,
+        };
+        Ok(TransactionSummary {
+            transaction_hash: txn.committed_hash().into(),
+            gas_used: Some(vm_output.gas_used()),
+            gas_unit_price: Some(txn.gas_unit_price()),
+            pending: None,
+            sender: Some(txn.sender()),
+            sequence_number: match txn.replay_protector() {
+                ReplayProtector::SequenceNumber(sequence_number) => Some(sequence_number),
+                _ => None,
+            },
+            replay_protector: Some(txn.replay_protector()),
+            success,
+            timestamp_us: None,
+            version: Some(self.txn_id),
+            vm_status: Some(vm_status.to_string()),
+        })
+    }
+}
+
+/// Render a trace saved with `--output-file`
+#[derive(Parser)]
+pub struct TraceShow {
+    /// The trace file to render
+    #[clap(value_parser)]
+    pub(crate) trace_file: PathBuf,
+
+    #[clap(flatten)]
+    pub(crate) render_options: TraceRenderOptions,
+}
+
+#[async_trait]
+impl CliCommand<()> for TraceShow {
+    fn command_name(&self) -> &'static str {
+        "TraceShow"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let trace = ExecutionTrace::load(&self.trace_file).map_err(|err| {
+            CliError::UnableToReadFile(self.trace_file.display().to_string(), err.to_string())
+        })?;
+        println!("{}", self.render_options.render(&trace)?);
+        Ok(())
+    }
+}
@@ -858,6 +858,12 @@ impl CliTestFramework {
             ignore_compile_warnings: false,
             compute_coverage: false,
             dump_state: false,
+            fuzz_runs: 256,
+            fuzz_seed: None,
+            debug_adapter: None,
+            profile_gas: false,
+            gas_baseline: None,
+            max_gas_increase: None,
         }
         .execute()
         .await
@@ -9,7 +9,9 @@ use aptos_db_indexer::{
     db_ops::open_internal_indexer_db,
     indexer_reader::IndexerReaders,
 };
+use aptos_db_indexer_schemas::metadata::MetadataKey;
 use aptos_indexer_grpc_utils::counters::{log_grpc_step, IndexerGrpcStep};
+use aptos_logger::{error, info};
 use aptos_storage_interface::DbReader;
 use aptos_types::{indexer::indexer_db_reader::IndexerReader, transaction::Version};
 use std::{
@@ -51,8 +53,11 @@ impl InternalIndexerDBService {
                 .expect("Failed to open internal indexer db"),
         );
 
-        let internal_indexer_db_config =
-            InternalIndexerDBConfig::new(true, true, true, 0, true, 10_000);
+        let internal_indexer_db_config = InternalIndexerDBConfig {
+            enable_account_transactions: true,
+            enable_event_by_type: true,
+            ..InternalIndexerDBConfig::new(true, true, true, 0, true, 10_000)
+        };
         Some(InternalIndexerDB::new(arc_db, internal_indexer_db_config))
     }
 
@@ -138,6 +143,46 @@ impl InternalIndexerDBService {
             }
         }
 
+        if node_config.indexer_db_config.enable_event_by_type() {
+            let event_by_type_start_version = self
+                .db_indexer
+                .indexer_db
+                .get_event_by_type_version()?
+                .map_or(0, |v| v + 1);
+            if event_by_type_start_version < start_version {
+                self.spawn_backfill(
+                    "event by type",
+                    MetadataKey::EventByTypeVersion,
+                    event_by_type_start_version,
+                    start_version,
+                    DBIndexer::backfill_events_by_type,
+                )?;
+            } else if event_by_type_start_version > start_version {
+                panic!("Cannot start event by type indexer because the progress doesn't match.");
+            }
+        }
+
+        if node_config.indexer_db_config.enable_account_transactions() {
+            let account_transaction_start_version = self
+                .db_indexer
+                .indexer_db
+                .get_account_transaction_version()?
+                .map_or(0, |v| v + 1);
+            if account_transaction_start_version < start_version {
+                self.spawn_backfill(
+                    "account transaction",
+                    MetadataKey::AccountTransactionVersion,
+                    account_transaction_start_version,
+                    start_version,
+                    DBIndexer::backfill_account_transactions,
+                )?;
+            } else if account_transaction_start_version > start_version {
+                panic!(
+                    "Cannot start account transaction indexer because the progress doesn't match."
+                );
+            }
+        }
+
         if node_config.indexer_db_config.enable_event_v2_translation() {
             let event_v2_translation_start_version = self
                 .db_indexer
@@ -164,6 +209,43 @@ impl InternalIndexerDBService {
         Ok(start_version)
     }
 
+    /// Catches up in the background with an index that was enabled on an existing indexer db,
+    /// from `index_start_version` (or the lowest version left in the main db) to
+    /// `start_version`. Until then, the index reports being backfilled (or, if the backfill
+    /// fails, it reports the failure).
+    fn spawn_backfill(
+        &self,
+        index_name: &'static str,
+        progress_key: MetadataKey,
+        index_start_version: Version,
+        start_version: Version,
+        backfill: fn(&DBIndexer, Version, Version) -> aptos_storage_interface::Result<Version>,
+    ) -> Result<()> {
+        let backfill_start_version = std::cmp::max(
+            index_start_version,
+            self.db_indexer.get_main_db_lowest_viable_version()?,
+        );
+        info!(
+            backfill_start_version = backfill_start_version,
+            end_version = start_version,
+            "Backfilling {} index.",
+            index_name
+        );
+        self.db_indexer
+            .start_backfill(progress_key.clone(), start_version);
+        let db_indexer = self.db_indexer.clone();
+        tokio::task::spawn_blocking(move || {
+            match backfill(&db_indexer, backfill_start_version, start_version) {
+                Ok(version) => info!(version = version, "Backfilled {} index.", index_name),
+                Err(err) => {
+                    error!(error = ?err, "Failed to backfill {} index.", index_name);
+                    db_indexer.fail_backfill(progress_key, err.to_string());
+                },
+            }
+        });
+        Ok(())
+    }
+
     pub async fn run(&mut self, node_config: &NodeConfig) -> Result<()> {
         let mut start_version = self.get_start_version(node_config).await?;
         let mut target_version = self.db_indexer.main_db_reader.ensure_synced_version()?;
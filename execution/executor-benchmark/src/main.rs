@@ -102,8 +102,10 @@ impl PrunerOpt {
             ledger_pruner_config: LedgerPrunerConfig {
                 enable: self.enable_ledger_pruner,
                 prune_window: self.ledger_prune_window,
+                prune_window_secs: None,
                 batch_size: self.ledger_pruning_batch_size,
                 user_pruning_window_offset: 0,
+                retention_overrides: Default::default(),
             },
         }
     }
@@ -3,7 +3,8 @@
 
 use aptos_cached_packages::aptos_stdlib;
 use aptos_db::AptosDB;
-use aptos_db_indexer::db_indexer::DBIndexer;
+use aptos_db_indexer::db_indexer::{DBIndexer, InternalIndexerDB};
+use aptos_db_indexer_schemas::metadata::MetadataKey;
 use aptos_executor_test_helpers::{
     gen_block_id, gen_ledger_info_with_sigs, integration_test_impl::create_db_and_executor,
 };
@@ -13,7 +14,7 @@ use aptos_sdk::{
     transaction_builder::TransactionFactory,
     types::{AccountKey, LocalAccount},
 };
-use aptos_storage_interface::DbReader;
+use aptos_storage_interface::{AptosDbError, DbReader};
 use aptos_temppath::TempPath;
 use aptos_types::{
     account_address::AccountAddress,
@@ -35,7 +36,7 @@ use std::{fmt::Debug, str::FromStr, sync::Arc};
 const B: u64 = 1_000_000_000;
 
 #[cfg(test)]
-pub fn create_test_db() -> (Arc<AptosDB>, LocalAccount) {
+pub fn create_test_db() -> (Arc<AptosDB>, LocalAccount, Vec<AccountAddress>) {
     // create test db
     let path = aptos_temppath::TempPath::new();
     let (genesis, validators) = aptos_vm_genesis::test_genesis_change_set_and_validators(Some(1));
@@ -129,14 +130,18 @@ pub fn create_test_db() -> (Arc<AptosDB>, LocalAccount) {
         .unwrap();
     let li1 = gen_ledger_info_with_sigs(1, &output1, block1_id, std::slice::from_ref(&signer));
     executor.commit_blocks(vec![block1_id], li1).unwrap();
-    (aptos_db, core_resources_account)
+    (aptos_db, core_resources_account, vec![
+        account1.address(),
+        account2.address(),
+        account3.address(),
+    ])
 }
 
 #[test]
 fn test_db_indexer_data() {
     use std::{thread, time::Duration};
     // create test db
-    let (aptos_db, core_account) = create_test_db();
+    let (aptos_db, core_account, _) = create_test_db();
     let total_version = aptos_db.expect_synced_version();
     assert_eq!(total_version, 11);
     let temp_path = TempPath::new();
@@ -420,6 +425,225 @@ fn test_db_indexer_data() {
     assert_vec_eq(&resources, &expected_resources);
 }
 
+#[test]
+fn test_db_indexer_account_transactions() {
+    use std::{thread, time::Duration};
+    let (aptos_db, _core_account, accounts) = create_test_db();
+    let total_version = aptos_db.expect_synced_version();
+    let account2 = accounts[1];
+
+    let temp_path = TempPath::new();
+    let mut node_config = aptos_config::config::NodeConfig::default();
+    node_config.storage.dir = temp_path.path().to_path_buf();
+    node_config.indexer_db_config.enable_account_transactions = true;
+    let internal_indexer_db = InternalIndexerDBService::get_indexer_db(&node_config).unwrap();
+    let db_indexer = DBIndexer::new(internal_indexer_db.clone(), aptos_db.clone());
+    db_indexer.process_a_batch(0, total_version + 1).unwrap();
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(
+        internal_indexer_db
+            .get_account_transaction_version()
+            .unwrap(),
+        Some(total_version)
+    );
+
+    // account2 is created at version 3, minted to at version 6, receives a transfer from
+    // account1 at version 8 and sends a transfer to account3 at version 9.
+    let versions: Vec<_> = db_indexer
+        .get_account_transactions(account2, Some(0), 100, false, total_version)
+        .unwrap()
+        .into_iter()
+        .map(|txn| txn.version)
+        .collect();
+    assert_eq!(versions, vec![3, 6, 8, 9]);
+
+    // Without a start version, the latest transactions are returned in ascending order.
+    let versions: Vec<_> = db_indexer
+        .get_account_transactions(account2, None, 2, false, total_version)
+        .unwrap()
+        .into_iter()
+        .map(|txn| txn.version)
+        .collect();
+    assert_eq!(versions, vec![8, 9]);
+
+    // Enabling the index on an existing indexer db backfills it from the main db.
+    let temp_path = TempPath::new();
+    node_config.storage.dir = temp_path.path().to_path_buf();
+    node_config.indexer_db_config.enable_account_transactions = false;
+    node_config.indexer_db_config.enable_transaction = true;
+    let internal_indexer_db = InternalIndexerDBService::get_indexer_db(&node_config).unwrap();
+    let db_indexer = DBIndexer::new(internal_indexer_db.clone(), aptos_db.clone());
+    db_indexer.process_a_batch(0, total_version + 1).unwrap();
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(
+        internal_indexer_db
+            .get_account_transaction_version()
+            .unwrap(),
+        None
+    );
+    drop(db_indexer);
+
+    node_config.indexer_db_config.enable_account_transactions = true;
+    let internal_indexer_db = InternalIndexerDB::new(
+        internal_indexer_db.get_inner_db_ref().clone(),
+        node_config.indexer_db_config,
+    );
+    let db_indexer = DBIndexer::new(internal_indexer_db.clone(), aptos_db);
+    assert!(matches!(
+        db_indexer.get_account_transactions(account2, None, 2, false, total_version),
+        Err(AptosDbError::IndexBackfilling(_))
+    ));
+    assert_eq!(
+        db_indexer
+            .backfill_account_transactions(0, total_version + 1)
+            .unwrap(),
+        total_version + 1
+    );
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(
+        internal_indexer_db
+            .get_account_transaction_version()
+            .unwrap(),
+        Some(total_version)
+    );
+    assert_eq!(
+        internal_indexer_db
+            .get_account_transaction_versions(account2, Some(0), 100, 0, total_version)
+            .unwrap(),
+        vec![3, 6, 8, 9]
+    );
+
+    // Versions below the minimum version (e.g., pruned versions) don't count towards the limit.
+    assert_eq!(
+        internal_indexer_db
+            .get_account_transaction_versions(account2, Some(0), 2, 7, total_version)
+            .unwrap(),
+        vec![8, 9]
+    );
+    assert_eq!(
+        internal_indexer_db
+            .get_account_transaction_versions(account2, None, 4, 7, total_version)
+            .unwrap(),
+        vec![8, 9]
+    );
+
+    // A failed backfill is reported instead of the index being backfilled forever.
+    db_indexer.fail_backfill(MetadataKey::AccountTransactionVersion, "error".to_string());
+    assert!(matches!(
+        db_indexer.get_account_transactions(account2, None, 2, false, total_version),
+        Err(AptosDbError::Other(_))
+    ));
+}
+
+#[test]
+fn test_db_indexer_events_by_type() {
+    use std::{thread, time::Duration};
+    let (aptos_db, _core_account, _accounts) = create_test_db();
+    let total_version = aptos_db.expect_synced_version();
+
+    let temp_path = TempPath::new();
+    let mut node_config = aptos_config::config::NodeConfig::default();
+    node_config.storage.dir = temp_path.path().to_path_buf();
+    node_config.indexer_db_config.enable_event_by_type = true;
+    let internal_indexer_db = InternalIndexerDBService::get_indexer_db(&node_config).unwrap();
+    let db_indexer = DBIndexer::new(internal_indexer_db.clone(), aptos_db.clone());
+    db_indexer.process_a_batch(0, total_version).unwrap();
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(
+        internal_indexer_db.get_event_by_type_version().unwrap(),
+        Some(total_version)
+    );
+
+    // Every block metadata transaction emits an event of the same type.
+    let type_tag = aptos_db
+        .get_event_by_version_and_index(1, 0)
+        .unwrap()
+        .type_tag()
+        .clone();
+    let events = db_indexer
+        .get_events_by_type(&type_tag, Some(0), None, 100, total_version)
+        .unwrap();
+    assert!(!events.is_empty());
+    assert_eq!(events[0].transaction_version, 1);
+    assert!(events.iter().all(|e| e.event.type_tag() == &type_tag));
+    assert!(events
+        .windows(2)
+        .all(|w| w[0].transaction_version <= w[1].transaction_version));
+
+    // Without a start version, the latest events are returned.
+    let latest = db_indexer
+        .get_events_by_type(&type_tag, None, None, 1, total_version)
+        .unwrap();
+    assert_eq!(latest.as_slice(), &events[events.len() - 1..]);
+
+    // The version range is inclusive on both ends.
+    let ranged = db_indexer
+        .get_events_by_type(&type_tag, Some(1), Some(1), 100, total_version)
+        .unwrap();
+    assert!(ranged.iter().all(|e| e.transaction_version == 1));
+    assert!(!ranged.is_empty());
+    drop(db_indexer);
+
+    // Enabling the index on an existing indexer db backfills it in the background, while new
+    // transactions keep being indexed.
+    let temp_path = TempPath::new();
+    node_config.storage.dir = temp_path.path().to_path_buf();
+    node_config.indexer_db_config.enable_event_by_type = false;
+    node_config.indexer_db_config.enable_transaction = true;
+    let internal_indexer_db = InternalIndexerDBService::get_indexer_db(&node_config).unwrap();
+    let db_indexer = DBIndexer::new(internal_indexer_db.clone(), aptos_db.clone());
+    let backfill_end_version = db_indexer.process_a_batch(0, total_version / 2).unwrap();
+    drop(db_indexer);
+
+    node_config.indexer_db_config.enable_event_by_type = true;
+    let internal_indexer_db = InternalIndexerDB::new(
+        internal_indexer_db.get_inner_db_ref().clone(),
+        node_config.indexer_db_config,
+    );
+    let db_indexer = DBIndexer::new(internal_indexer_db.clone(), aptos_db);
+    db_indexer.start_backfill(MetadataKey::EventByTypeVersion, backfill_end_version);
+    db_indexer
+        .process_a_batch(backfill_end_version, total_version)
+        .unwrap();
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(
+        internal_indexer_db.get_event_by_type_version().unwrap(),
+        None
+    );
+    assert!(matches!(
+        db_indexer.get_events_by_type(&type_tag, Some(0), None, 100, total_version),
+        Err(AptosDbError::IndexBackfilling(_))
+    ));
+
+    assert_eq!(
+        db_indexer
+            .backfill_events_by_type(0, backfill_end_version)
+            .unwrap(),
+        backfill_end_version
+    );
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(
+        internal_indexer_db.get_event_by_type_version().unwrap(),
+        Some(total_version)
+    );
+    assert_eq!(
+        db_indexer
+            .get_events_by_type(&type_tag, Some(0), None, 100, total_version)
+            .unwrap(),
+        events
+    );
+
+    // Versions below the minimum version (e.g., pruned versions) don't count towards the limit.
+    let last_version = events.last().unwrap().transaction_version;
+    let versions: Vec<_> = internal_indexer_db
+        .lookup_events_by_type(&type_tag, Some(0), 1, last_version, total_version)
+        .unwrap()
+        .into_iter()
+        .map(|(version, _idx)| version)
+        .collect();
+    assert_eq!(versions, vec![last_version]);
+}
+
 fn assert_vec_eq<T: Eq + Debug>(left: &[T], right: &[T]) {
     for i in 0..left.len().min(right.len()) {
         assert_eq!(left[i], right[i], "difference at position {}", i);
@@ -12,9 +12,10 @@
 //! long as the latter is in its trusted peers set.
 use aptos_config::{
     config::{
-        DiscoveryMethod, NetworkConfig, Peer, PeerRole, PeerSet, RoleType, CONNECTION_BACKOFF_BASE,
-        CONNECTIVITY_CHECK_INTERVAL_MS, MAX_CONNECTION_DELAY_MS, MAX_FRAME_SIZE,
-        MAX_FULLNODE_OUTBOUND_CONNECTIONS, MAX_INBOUND_CONNECTIONS, NETWORK_CHANNEL_SIZE,
+        DiscoveryMethod, Error, NetworkConfig, OutboundSchedulerConfig, Peer, PeerRole, PeerSet,
+        RoleType, CONNECTION_BACKOFF_BASE, CONNECTIVITY_CHECK_INTERVAL_MS, MAX_CONNECTION_DELAY_MS,
+        MAX_FRAME_SIZE, MAX_FULLNODE_OUTBOUND_CONNECTIONS, MAX_INBOUND_CONNECTIONS,
+        NETWORK_CHANNEL_SIZE,
     },
     network_id::NetworkContext,
 };
@@ -26,6 +27,7 @@ use aptos_network::{
     connectivity_manager::{builder::ConnectivityManagerBuilder, ConnectivityRequest},
     constants::MAX_MESSAGE_SIZE,
     logging::NetworkSchema,
+    peer::verify_outbound_scheduler_config,
     peer_manager::{
         builder::{AuthenticationMode, PeerManagerBuilder},
         ConnectionRequestSender,
@@ -38,7 +40,7 @@ use aptos_network::{
         },
     },
 };
-use aptos_network_discovery::DiscoveryChangeListener;
+use aptos_network_discovery::{DiscoveryChangeListener, SystemSeedResolver};
 use aptos_time_service::TimeService;
 use aptos_types::{chain_id::ChainId, network_address::NetworkAddress};
 use std::{clone::Clone, collections::HashSet, sync::Arc, time::Duration};
@@ -85,6 +87,7 @@ impl NetworkBuilder {
         network_channel_size: usize,
         inbound_connection_limit: usize,
         tcp_buffer_cfg: TCPBufferCfg,
+        outbound_scheduler_config: OutboundSchedulerConfig,
     ) -> Self {
         // A network cannot exist without a PeerManager
         // TODO:  construct this in create and pass it to new() as a parameter. The complication is manual construction of NetworkBuilder in various tests.
@@ -101,6 +104,7 @@ impl NetworkBuilder {
             enable_proxy_protocol,
             inbound_connection_limit,
             tcp_buffer_cfg,
+            outbound_scheduler_config,
         );
 
         NetworkBuilder {
@@ -140,6 +144,7 @@ impl NetworkBuilder {
             NETWORK_CHANNEL_SIZE,
             MAX_INBOUND_CONNECTIONS,
             TCPBufferCfg::default(),
+            OutboundSchedulerConfig::default(),
         );
 
         builder.add_connectivity_manager(
@@ -158,6 +163,7 @@ impl NetworkBuilder {
     }
 
     /// Create a new NetworkBuilder based on the provided configuration.
+    /// Returns an error if the configuration is invalid, or if discovery can't be set up.
     pub fn create(
         chain_id: ChainId,
         role: RoleType,
@@ -165,7 +171,9 @@ impl NetworkBuilder {
         time_service: TimeService,
         reconfig_subscription_service: Option<&mut EventSubscriptionService>,
         peers_and_metadata: Arc<PeersAndMetadata>,
-    ) -> NetworkBuilder {
+    ) -> Result<NetworkBuilder, Error> {
+        verify_outbound_scheduler_config(&config.outbound_scheduler_config)?;
+
         let peer_id = config.peer_id();
         let identity_key = config.identity_key();
 
@@ -195,8 +203,15 @@ impl NetworkBuilder {
                 config.outbound_rx_buffer_size_bytes,
                 config.outbound_tx_buffer_size_bytes,
             ),
+            config.outbound_scheduler_config.clone(),
         );
 
+        if let Some(traffic_recording_dir) = &config.traffic_recording_dir {
+            network_builder
+                .peer_manager_builder
+                .set_traffic_recording_dir(traffic_recording_dir.clone());
+        }
+
         network_builder.add_connection_monitoring(
             config.ping_interval_ms,
             config.ping_timeout_ms,
@@ -220,7 +235,7 @@ impl NetworkBuilder {
         );
 
         network_builder.discovery_listeners = Some(Vec::new());
-        network_builder.setup_discovery(config, reconfig_subscription_service);
+        network_builder.setup_discovery(config, reconfig_subscription_service)?;
 
         // Ensure there are no duplicate source types
         let set: HashSet<_> = network_builder
@@ -235,7 +250,7 @@ impl NetworkBuilder {
             network_builder.discovery_listeners.as_ref().unwrap().len()
         );
 
-        network_builder
+        Ok(network_builder)
     }
 
     /// Create the configured Networking components.
@@ -349,7 +364,7 @@ impl NetworkBuilder {
         &mut self,
         config: &NetworkConfig,
         mut reconfig_subscription_service: Option<&mut EventSubscriptionService>,
-    ) {
+    ) -> Result<(), Error> {
         let conn_mgr_reqs_tx = self
             .conn_mgr_reqs_tx()
             .expect("ConnectivityManager must exist");
@@ -384,6 +399,23 @@ impl NetworkBuilder {
                     Duration::from_secs(rest_discovery.interval_secs),
                     self.time_service.clone(),
                 ),
+                DiscoveryMethod::Dns(dns_discovery) => {
+                    let resolver = SystemSeedResolver::new().map_err(|error| {
+                        Error::InvariantViolation(format!(
+                            "DNS discovery is unable to create a resolver: {:?}",
+                            error
+                        ))
+                    })?;
+                    DiscoveryChangeListener::dns(
+                        self.network_context,
+                        conn_mgr_reqs_tx.clone(),
+                        dns_discovery.domain.clone(),
+                        dns_discovery.static_seed_addrs.clone(),
+                        Arc::new(resolver),
+                        Duration::from_secs(dns_discovery.interval_secs),
+                        self.time_service.clone(),
+                    )
+                },
                 DiscoveryMethod::None => {
                     continue;
                 },
@@ -393,6 +425,7 @@ impl NetworkBuilder {
                 .expect("Can only add listeners before starting")
                 .push(listener);
         }
+        Ok(())
     }
 
     /// Add a HealthChecker to the network.
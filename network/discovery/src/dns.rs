@@ -0,0 +1,658 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{counters::DISCOVERY_COUNTS, DiscoveryError};
+use aptos_config::{
+    config::{Peer, PeerRole, PeerSet, HANDSHAKE_VERSION},
+    network_id::NetworkContext,
+};
+use aptos_crypto::{x25519, ValidCryptoMaterialStringExt};
+use aptos_logger::prelude::*;
+use aptos_network::{counters::inc_by_with_context, logging::NetworkSchema};
+use aptos_time_service::{Interval, TimeService, TimeServiceTrait};
+use aptos_types::{
+    account_address::from_identity_public_key,
+    network_address::{DnsName, NetworkAddress, Protocol},
+    PeerId,
+};
+use futures::{
+    future::{BoxFuture, FutureExt},
+    Stream,
+};
+use hickory_resolver::{error::ResolveErrorKind, TokioAsyncResolver};
+use std::{
+    pin::Pin,
+    str::FromStr,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// The prefix of the SRV records that hold seed peers (relative to the seed domain)
+const SRV_RECORD_PREFIX: &str = "_aptosnet._tcp";
+
+/// A single SRV record, i.e., a target host and port
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SrvRecord {
+    pub target: String,
+    pub port: u16,
+}
+
+/// A DNS resolver for seed records. This is a trait so that tests can stub out DNS.
+pub trait SeedResolver: Send + Sync {
+    /// Returns the contents of all TXT records for the given name
+    fn lookup_txt(&self, name: String) -> BoxFuture<'static, Result<Vec<String>, DiscoveryError>>;
+
+    /// Returns all SRV records for the given name
+    fn lookup_srv(
+        &self,
+        name: String,
+    ) -> BoxFuture<'static, Result<Vec<SrvRecord>, DiscoveryError>>;
+}
+
+/// A [`SeedResolver`] that uses the system DNS configuration
+pub struct SystemSeedResolver {
+    resolver: TokioAsyncResolver,
+}
+
+impl SystemSeedResolver {
+    pub fn new() -> Result<Self, DiscoveryError> {
+        let resolver = TokioAsyncResolver::tokio_from_system_conf()
+            .map_err(|error| DiscoveryError::Dns(error.to_string()))?;
+        Ok(Self { resolver })
+    }
+}
+
+/// Converts a resolution error into a discovery error. Names without any
+/// records are treated as empty (rather than failed) lookups.
+fn empty_if_no_records<T>(
+    error: hickory_resolver::error::ResolveError,
+) -> Result<Vec<T>, DiscoveryError> {
+    match error.kind() {
+        ResolveErrorKind::NoRecordsFound { .. } => Ok(vec![]),
+        _ => Err(DiscoveryError::Dns(error.to_string())),
+    }
+}
+
+impl SeedResolver for SystemSeedResolver {
+    fn lookup_txt(&self, name: String) -> BoxFuture<'static, Result<Vec<String>, DiscoveryError>> {
+        let resolver = self.resolver.clone();
+        async move {
+            match resolver.txt_lookup(name).await {
+                Ok(lookup) => Ok(lookup
+                    .iter()
+                    .map(|txt| {
+                        // Long TXT records are split into multiple character strings
+                        txt.txt_data()
+                            .iter()
+                            .map(|data| String::from_utf8_lossy(data))
+                            .collect::<String>()
+                    })
+                    .collect()),
+                Err(error) => empty_if_no_records(error),
+            }
+        }
+        .boxed()
+    }
+
+    fn lookup_srv(
+        &self,
+        name: String,
+    ) -> BoxFuture<'static, Result<Vec<SrvRecord>, DiscoveryError>> {
+        let resolver = self.resolver.clone();
+        async move {
+            match resolver.srv_lookup(name).await {
+                Ok(lookup) => Ok(lookup
+                    .iter()
+                    .map(|srv| SrvRecord {
+                        target: srv.target().to_utf8(),
+                        port: srv.port(),
+                    })
+                    .collect()),
+                Err(error) => empty_if_no_records(error),
+            }
+        }
+        .boxed()
+    }
+}
+
+/// A discovery stream that periodically resolves seed peers from DNS records
+/// (see [`DnsDiscovery`](aptos_config::config::DnsDiscovery) for the record format).
+pub struct DnsStream {
+    network_context: NetworkContext,
+    domain: String,
+    static_seed_addrs: Vec<NetworkAddress>,
+    resolver: Arc<dyn SeedResolver>,
+    interval: Pin<Box<Interval>>,
+    pending_lookup: Option<BoxFuture<'static, Result<PeerSet, DiscoveryError>>>,
+}
+
+impl DnsStream {
+    pub(crate) fn new(
+        network_context: NetworkContext,
+        domain: String,
+        static_seed_addrs: Vec<NetworkAddress>,
+        resolver: Arc<dyn SeedResolver>,
+        interval_duration: Duration,
+        time_service: TimeService,
+    ) -> Self {
+        DnsStream {
+            network_context,
+            domain,
+            static_seed_addrs,
+            resolver,
+            interval: Box::pin(time_service.interval(interval_duration)),
+            pending_lookup: None,
+        }
+    }
+}
+
+impl Stream for DnsStream {
+    type Item = Result<PeerSet, DiscoveryError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // Wait for delay, and start a new lookup
+        if self.pending_lookup.is_none() {
+            futures::ready!(self.interval.as_mut().poll_next(cx));
+            let lookup = resolve_seed_peers(
+                self.network_context,
+                self.resolver.clone(),
+                self.domain.clone(),
+                self.static_seed_addrs.clone(),
+            );
+            self.pending_lookup = Some(lookup.boxed());
+        }
+
+        // Wait for the lookup to complete
+        let result = futures::ready!(self
+            .pending_lookup
+            .as_mut()
+            .expect("The pending lookup must exist!")
+            .poll_unpin(cx));
+        self.pending_lookup = None;
+        Poll::Ready(Some(result))
+    }
+}
+
+/// Resolves the seed peers from the TXT and SRV records of the given domain, and
+/// merges them with the static seed addresses. Invalid records and failed lookups
+/// are logged and skipped. If all lookups of the domain itself fail, only the static
+/// seeds are returned. If there are no static seeds either, an error is returned
+/// instead (so that a DNS outage doesn't clear the known seed peers).
+async fn resolve_seed_peers(
+    network_context: NetworkContext,
+    resolver: Arc<dyn SeedResolver>,
+    domain: String,
+    static_seed_addrs: Vec<NetworkAddress>,
+) -> Result<PeerSet, DiscoveryError> {
+    let mut seed_peers = vec![];
+
+    // Add the static seeds
+    for address in static_seed_addrs {
+        match peer_from_address(address, None) {
+            Ok(peer) => seed_peers.push(peer),
+            Err(error) => log_invalid_record(&network_context, &domain, &error),
+        }
+    }
+
+    // Add the peers from the TXT records
+    let txt_records = resolver.lookup_txt(domain.clone()).await;
+    if let Err(error) = &txt_records {
+        log_failed_lookup(&network_context, &domain, error);
+    }
+    for record in txt_records.as_deref().unwrap_or_default() {
+        match parse_txt_record(record) {
+            Ok(peer) => seed_peers.push(peer),
+            Err(error) => log_invalid_record(&network_context, &domain, &error),
+        }
+    }
+
+    // Add the peers from the SRV records (the keys are stored on the targets)
+    let srv_name = format!("{}.{}", SRV_RECORD_PREFIX, domain);
+    let srv_records = resolver.lookup_srv(srv_name.clone()).await;
+    if let Err(error) = &srv_records {
+        log_failed_lookup(&network_context, &srv_name, error);
+    }
+    for srv_record in srv_records.as_deref().unwrap_or_default() {
+        let target = srv_record.target.trim_end_matches('.').to_string();
+        let key_records = match resolver.lookup_txt(target.clone()).await {
+            Ok(key_records) => key_records,
+            Err(error) => {
+                log_failed_lookup(&network_context, &target, &error);
+                continue;
+            },
+        };
+        match parse_srv_record(&target, srv_record.port, &key_records) {
+            Ok(peer) => seed_peers.push(peer),
+            Err(error) => log_invalid_record(&network_context, &domain, &error),
+        }
+    }
+
+    // Fall back to the static seeds if the domain couldn't be resolved at all, and
+    // keep the previously discovered peers if there are no static seeds.
+    if let (Err(error), Err(_)) = (txt_records, srv_records) {
+        if seed_peers.is_empty() {
+            return Err(error);
+        }
+    }
+
+    // Merge the peers by peer id
+    let mut peer_set = PeerSet::new();
+    for (peer_id, peer) in seed_peers {
+        match peer_set.get_mut(&peer_id) {
+            Some(existing_peer) => {
+                existing_peer.addresses.extend(peer.addresses);
+                existing_peer.keys.extend(peer.keys);
+            },
+            None => {
+                peer_set.insert(peer_id, peer);
+            },
+        }
+    }
+    Ok(peer_set)
+}
+
+/// Logs and counts an invalid seed record
+fn log_invalid_record(network_context: &NetworkContext, domain: &str, error: &DiscoveryError) {
+    inc_by_with_context(
+        &DISCOVERY_COUNTS,
+        network_context,
+        "dns_invalid_seed_record",
+        1,
+    );
+    warn!(
+        NetworkSchema::new(network_context),
+        "{} Skipping invalid seed record for domain {}: {:?}", network_context, domain, error
+    );
+}
+
+/// Logs and counts a failed DNS lookup
+fn log_failed_lookup(network_context: &NetworkContext, name: &str, error: &DiscoveryError) {
+    inc_by_with_context(&DISCOVERY_COUNTS, network_context, "dns_failed_lookup", 1);
+    warn!(
+        NetworkSchema::new(network_context),
+        "{} Failed to look up seed records for {}: {:?}", network_context, name, error
+    );
+}
+
+/// Creates a seed peer from the given address. The address must be a valid
+/// AptosNet address (i.e., it must contain the peer's x25519 key). If no peer
+/// id is given, it is derived from the key.
+fn peer_from_address(
+    address: NetworkAddress,
+    peer_id: Option<PeerId>,
+) -> Result<(PeerId, Peer), DiscoveryError> {
+    if !address.is_aptosnet_addr() {
+        return Err(DiscoveryError::Parsing(format!(
+            "Seed address is not a valid AptosNet address: {}",
+            address
+        )));
+    }
+    let pubkey = address.find_noise_proto().ok_or_else(|| {
+        DiscoveryError::Parsing(format!("Seed address is missing a key: {}", address))
+    })?;
+    let peer_id = peer_id.unwrap_or_else(|| from_identity_public_key(pubkey));
+    let peer = Peer::from_addrs(PeerRole::Upstream, vec![address]);
+    Ok((peer_id, peer))
+}
+
+/// Parses a TXT seed record of the form `addr=<network address> [peer_id=<peer id>]`
+fn parse_txt_record(record: &str) -> Result<(PeerId, Peer), DiscoveryError> {
+    let mut address = None;
+    let mut peer_id = None;
+    for field in record.split_whitespace() {
+        match field.split_once('=') {
+            Some(("addr", value)) => {
+                address = Some(NetworkAddress::from_str(value).map_err(|error| {
+                    DiscoveryError::Parsing(format!("Invalid seed address {}: {}", value, error))
+                })?);
+            },
+            Some(("peer_id", value)) => {
+                peer_id = Some(PeerId::from_str(value).map_err(|error| {
+                    DiscoveryError::Parsing(format!("Invalid seed peer id {}: {}", value, error))
+                })?);
+            },
+            _ => {
+                return Err(DiscoveryError::Parsing(format!(
+                    "Unexpected field in seed record: {}",
+                    field
+                )))
+            },
+        }
+    }
+
+    let address = address.ok_or_else(|| {
+        DiscoveryError::Parsing(format!("Seed record is missing an address: {}", record))
+    })?;
+    peer_from_address(address, peer_id)
+}
+
+/// Parses an SRV seed record, using the key held in the TXT records of the
+/// target (of the form `key=<x25519 public key>`).
+fn parse_srv_record(
+    target: &str,
+    port: u16,
+    key_records: &[String],
+) -> Result<(PeerId, Peer), DiscoveryError> {
+    let encoded_key = key_records
+        .iter()
+        .find_map(|record| record.trim().strip_prefix("key="))
+        .ok_or_else(|| {
+            DiscoveryError::Parsing(format!("Seed target {} is missing a key record", target))
+        })?;
+    let pubkey = x25519::PublicKey::from_encoded_string(encoded_key).map_err(|error| {
+        DiscoveryError::Parsing(format!("Invalid seed key {}: {}", encoded_key, error))
+    })?;
+
+    let dns_name = DnsName::from_str(target).map_err(|error| {
+        DiscoveryError::Parsing(format!("Invalid seed target {}: {}", target, error))
+    })?;
+    let address =
+        NetworkAddress::from_protocols(vec![Protocol::Dns(dns_name), Protocol::Tcp(port)])
+            .map_err(|error| DiscoveryError::Parsing(error.to_string()))?
+            .append_prod_protos(pubkey, HANDSHAKE_VERSION);
+    peer_from_address(address, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DiscoveryChangeListener;
+    use aptos_event_notifications::DbBackedOnChainConfig;
+    use aptos_network::connectivity_manager::{ConnectivityRequest, DiscoverySource};
+    use futures::StreamExt;
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Mutex,
+    };
+
+    const KEY: &str = "080e287879c918794170e258bfaddd75acac5b3e350419044655e4983a487120";
+    const OTHER_KEY: &str = "ca9a2e5bba2a2d46d7a1bfe2ea9ba7e55dcf2f0cd5bda01bbd2d46bac39cc858";
+
+    /// A stub resolver that serves records from memory
+    #[derive(Default)]
+    struct StubResolver {
+        txt_records: Mutex<HashMap<String, Vec<String>>>,
+        srv_records: Mutex<HashMap<String, Vec<SrvRecord>>>,
+        failing_names: Mutex<HashSet<String>>,
+    }
+
+    impl StubResolver {
+        fn set_failing(&self, name: &str) {
+            self.failing_names.lock().unwrap().insert(name.to_string());
+        }
+
+        fn check_failing(&self, name: &str) -> Result<(), DiscoveryError> {
+            if self.failing_names.lock().unwrap().contains(name) {
+                return Err(DiscoveryError::Dns(format!("Lookup timed out: {}", name)));
+            }
+            Ok(())
+        }
+
+        fn set_txt_records(&self, name: &str, records: Vec<&str>) {
+            let records = records
+                .into_iter()
+                .map(|record| record.to_string())
+                .collect();
+            self.txt_records
+                .lock()
+                .unwrap()
+                .insert(name.to_string(), records);
+        }
+
+        fn set_srv_records(&self, name: &str, records: Vec<SrvRecord>) {
+            self.srv_records
+                .lock()
+                .unwrap()
+                .insert(name.to_string(), records);
+        }
+    }
+
+    impl SeedResolver for StubResolver {
+        fn lookup_txt(
+            &self,
+            name: String,
+        ) -> BoxFuture<'static, Result<Vec<String>, DiscoveryError>> {
+            let result = self.check_failing(&name).map(|_| {
+                let records = self.txt_records.lock().unwrap().get(&name).cloned();
+                records.unwrap_or_default()
+            });
+            async move { result }.boxed()
+        }
+
+        fn lookup_srv(
+            &self,
+            name: String,
+        ) -> BoxFuture<'static, Result<Vec<SrvRecord>, DiscoveryError>> {
+            let result = self.check_failing(&name).map(|_| {
+                let records = self.srv_records.lock().unwrap().get(&name).cloned();
+                records.unwrap_or_default()
+            });
+            async move { result }.boxed()
+        }
+    }
+
+    fn seed_address(host: &str, key: &str) -> NetworkAddress {
+        NetworkAddress::from_str(&format!(
+            "/dns/{}/tcp/6182/noise-ik/{}/handshake/0",
+            host, key
+        ))
+        .unwrap()
+    }
+
+    fn seed_peer_id(key: &str) -> PeerId {
+        from_identity_public_key(x25519::PublicKey::from_encoded_string(key).unwrap())
+    }
+
+    async fn resolve(
+        resolver: Arc<StubResolver>,
+        static_seed_addrs: Vec<NetworkAddress>,
+    ) -> PeerSet {
+        resolve_seed_peers(
+            NetworkContext::mock(),
+            resolver,
+            "seeds.example.com".into(),
+            static_seed_addrs,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_txt_records() {
+        let resolver = Arc::new(StubResolver::default());
+        let peer_id = PeerId::random();
+        resolver.set_txt_records("seeds.example.com", vec![
+            &format!("addr={}", seed_address("seed1.example.com", KEY)),
+            &format!(
+                "addr={} peer_id={}",
+                seed_address("seed2.example.com", OTHER_KEY),
+                peer_id.to_hex_literal()
+            ),
+        ]);
+
+        // Verify both peers are discovered (with the expected peer ids)
+        let peer_set = resolve(resolver, vec![]).await;
+        assert_eq!(peer_set.len(), 2);
+        assert_eq!(peer_set.get(&seed_peer_id(KEY)).unwrap().addresses, vec![
+            seed_address("seed1.example.com", KEY)
+        ]);
+        let peer = peer_set.get(&peer_id).unwrap();
+        assert_eq!(peer.role, PeerRole::Upstream);
+        assert!(peer
+            .keys
+            .contains(&x25519::PublicKey::from_encoded_string(OTHER_KEY).unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_srv_records() {
+        let resolver = Arc::new(StubResolver::default());
+        resolver.set_srv_records("_aptosnet._tcp.seeds.example.com", vec![
+            SrvRecord {
+                target: "seed1.example.com.".into(),
+                port: 6182,
+            },
+            SrvRecord {
+                target: "seed2.example.com.".into(),
+                port: 6182,
+            },
+        ]);
+        resolver.set_txt_records("seed1.example.com", vec![&format!("key={}", KEY)]);
+
+        // Verify only the target with a key record is discovered
+        let peer_set = resolve(resolver, vec![]).await;
+        assert_eq!(peer_set.len(), 1);
+        assert_eq!(peer_set.get(&seed_peer_id(KEY)).unwrap().addresses, vec![
+            seed_address("seed1.example.com", KEY)
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_records_are_skipped() {
+        let resolver = Arc::new(StubResolver::default());
+        resolver.set_txt_records("seeds.example.com", vec![
+            "addr=/dns/seed1.example.com/tcp/6182",
+            "addr=not-an-address",
+            &format!(
+                "peer_id=0x1 addr={}",
+                seed_address("seed2.example.com", KEY)
+            ),
+            &format!(
+                "addr={} role=validator",
+                seed_address("seed3.example.com", KEY)
+            ),
+            "v=spf1 -all",
+        ]);
+
+        // Verify only the valid record is discovered
+        let peer_set = resolve(resolver, vec![]).await;
+        assert_eq!(peer_set.len(), 1);
+        assert_eq!(
+            peer_set
+                .get(&PeerId::from_str("0x1").unwrap())
+                .unwrap()
+                .addresses,
+            vec![seed_address("seed2.example.com", KEY)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_lookups_are_skipped() {
+        let resolver = Arc::new(StubResolver::default());
+        resolver.set_failing("seeds.example.com");
+        resolver.set_srv_records("_aptosnet._tcp.seeds.example.com", vec![
+            SrvRecord {
+                target: "seed1.example.com.".into(),
+                port: 6182,
+            },
+            SrvRecord {
+                target: "seed2.example.com.".into(),
+                port: 6182,
+            },
+        ]);
+        resolver.set_failing("seed1.example.com");
+        resolver.set_txt_records("seed2.example.com", vec![&format!("key={}", OTHER_KEY)]);
+
+        // Verify the failed TXT lookups are skipped, and the remaining target is discovered
+        let peer_set = resolve(resolver, vec![]).await;
+        assert_eq!(peer_set.len(), 1);
+        assert_eq!(
+            peer_set.get(&seed_peer_id(OTHER_KEY)).unwrap().addresses,
+            vec![seed_address("seed2.example.com", OTHER_KEY)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_domain_lookups() {
+        let resolver = Arc::new(StubResolver::default());
+        resolver.set_failing("seeds.example.com");
+        resolver.set_failing("_aptosnet._tcp.seeds.example.com");
+
+        // Verify the refresh fails if the domain can't be resolved at all
+        let result = resolve_seed_peers(
+            NetworkContext::mock(),
+            resolver.clone(),
+            "seeds.example.com".into(),
+            vec![],
+        )
+        .await;
+        assert!(matches!(result, Err(DiscoveryError::Dns(_))));
+
+        // Verify the static seeds are returned instead, if there are any
+        let peer_set = resolve(resolver, vec![seed_address("static.example.com", KEY)]).await;
+        assert_eq!(peer_set.len(), 1);
+        assert_eq!(peer_set.get(&seed_peer_id(KEY)).unwrap().addresses, vec![
+            seed_address("static.example.com", KEY)
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_merge_with_static_seeds() {
+        let resolver = Arc::new(StubResolver::default());
+        resolver.set_txt_records("seeds.example.com", vec![&format!(
+            "addr={}",
+            seed_address("seed1.example.com", KEY)
+        )]);
+
+        // Verify the static seeds are merged with the DNS seeds
+        let static_seed_addrs = vec![
+            seed_address("static.example.com", KEY),
+            seed_address("other.example.com", OTHER_KEY),
+        ];
+        let peer_set = resolve(resolver, static_seed_addrs).await;
+        assert_eq!(peer_set.len(), 2);
+        assert_eq!(peer_set.get(&seed_peer_id(KEY)).unwrap().addresses, vec![
+            seed_address("static.example.com", KEY),
+            seed_address("seed1.example.com", KEY),
+        ]);
+        assert!(peer_set.contains_key(&seed_peer_id(OTHER_KEY)));
+    }
+
+    #[tokio::test]
+    async fn test_dns_listener_refreshes() {
+        let resolver = Arc::new(StubResolver::default());
+        let (conn_mgr_reqs_tx, mut conn_mgr_reqs_rx) = aptos_channels::new(
+            1,
+            &aptos_network::counters::PENDING_CONNECTIVITY_MANAGER_REQUESTS,
+        );
+        let listener = DiscoveryChangeListener::<DbBackedOnChainConfig>::dns(
+            NetworkContext::mock(),
+            conn_mgr_reqs_tx,
+            "seeds.example.com".into(),
+            vec![],
+            resolver.clone(),
+            Duration::from_millis(5),
+            TimeService::real(),
+        );
+        spawn_named!("[Network] Listener Task", Box::pin(listener).run());
+
+        // Verify the initial (empty) update is sent
+        let expected_update = |peer_set: PeerSet| {
+            move |request: Option<ConnectivityRequest>| match request {
+                Some(ConnectivityRequest::UpdateDiscoveredPeers(DiscoverySource::Dns, peers)) => {
+                    peers == peer_set
+                },
+                _ => false,
+            }
+        };
+        assert!(expected_update(PeerSet::new())(
+            conn_mgr_reqs_rx.next().await
+        ));
+
+        // Rotate the seed records and verify the new seeds are eventually sent
+        resolver.set_txt_records("seeds.example.com", vec![&format!(
+            "addr={}",
+            seed_address("seed1.example.com", KEY)
+        )]);
+        let mut expected_peers = PeerSet::new();
+        expected_peers.insert(
+            seed_peer_id(KEY),
+            Peer::from_addrs(PeerRole::Upstream, vec![seed_address(
+                "seed1.example.com",
+                KEY,
+            )]),
+        );
+        let is_expected_update = expected_update(expected_peers);
+        while !is_expected_update(conn_mgr_reqs_rx.next().await) {}
+    }
+}
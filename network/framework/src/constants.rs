@@ -14,6 +14,9 @@ pub const INBOUND_RPC_TIMEOUT_MS: u64 = 10_000;
 pub const MAX_CONCURRENT_OUTBOUND_RPCS: u32 = 100;
 /// Limit on concurrent Inbound RPC requests before backpressure is applied
 pub const MAX_CONCURRENT_INBOUND_RPCS: u32 = 100;
+/// Limit on scheduled messages buffered ahead of the socket writer. This is kept small so that
+/// the outbound scheduler (rather than the buffer) determines the order of messages on the wire.
+pub const MAX_BUFFERED_OUTBOUND_MESSAGES: usize = 16;
 
 // These are only used in tests
 // TODO: Fix this so the tests and the defaults in config are the same
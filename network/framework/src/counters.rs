@@ -469,6 +469,26 @@ pub static PENDING_MULTIPLEX_STREAM: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counter of outbound messages dropped by the scheduler because the protocol queue was full
+pub static OUTBOUND_SCHEDULER_DROPPED_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_outbound_scheduler_dropped_messages",
+        "Number of outbound messages dropped by the scheduler",
+        &["protocol_id"],
+    )
+    .unwrap()
+});
+
+/// Counter of times a protocol was skipped by the scheduler for exceeding its bandwidth cap
+pub static OUTBOUND_SCHEDULER_THROTTLED: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_outbound_scheduler_throttled",
+        "Number of times a protocol was throttled by the outbound scheduler",
+        &["protocol_id"],
+    )
+    .unwrap()
+});
+
 /// Counter of pending requests in Direct Send
 pub static PENDING_DIRECT_SEND_REQUESTS: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
@@ -9,6 +9,7 @@
 //!
 //! [handshake]: crate::noise::handshake
 
+use crate::transport::recording::{TrafficDirection, TrafficRecorder};
 use aptos_crypto::{noise, x25519};
 use aptos_logger::prelude::*;
 use futures::{
@@ -43,6 +44,8 @@ pub struct NoiseStream<TSocket> {
     read_state: ReadState,
     /// an enum used for progressively writing a noise payload
     write_state: WriteState,
+    /// records the decrypted traffic (if traffic recording is enabled)
+    recorder: Option<TrafficRecorder>,
 }
 
 impl<TSocket> NoiseStream<TSocket> {
@@ -54,9 +57,15 @@ impl<TSocket> NoiseStream<TSocket> {
             buffers: Box::new(NoiseBuffers::new()),
             read_state: ReadState::Init,
             write_state: WriteState::Init,
+            recorder: None,
         }
     }
 
+    /// Records all (decrypted) bytes read from and written to the stream
+    pub fn record_traffic(&mut self, recorder: TrafficRecorder) {
+        self.recorder = Some(recorder);
+    }
+
     /// Pull out the static public key of the remote
     pub fn get_remote_static(&self) -> x25519::PublicKey {
         self.session.get_remote_static()
@@ -371,7 +380,12 @@ where
         context: &mut Context,
         buf: &mut [u8],
     ) -> Poll<io::Result<usize>> {
-        self.get_mut().poll_read(context, buf)
+        let this = self.get_mut();
+        let result = this.poll_read(context, buf);
+        if let (Poll::Ready(Ok(bytes_read)), Some(recorder)) = (&result, &mut this.recorder) {
+            recorder.record(TrafficDirection::Inbound, &buf[..*bytes_read]);
+        }
+        result
     }
 }
 
@@ -384,7 +398,12 @@ where
         context: &mut Context,
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
-        self.get_mut().poll_write(context, buf)
+        let this = self.get_mut();
+        let result = this.poll_write(context, buf);
+        if let (Poll::Ready(Ok(bytes_written)), Some(recorder)) = (&result, &mut this.recorder) {
+            recorder.record(TrafficDirection::Outbound, &buf[..*bytes_written]);
+        }
+        result
     }
 
     fn poll_flush(self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
@@ -16,6 +16,7 @@
 //! [`PeerManager`]: crate::peer_manager::PeerManager
 
 use crate::{
+    constants::MAX_BUFFERED_OUTBOUND_MESSAGES,
     counters::{
         self, network_application_inbound_traffic, network_application_outbound_traffic,
         DECLINED_LABEL, FAILED_LABEL, RECEIVED_LABEL, SENT_LABEL, UNKNOWN_LABEL,
@@ -36,7 +37,10 @@ use crate::{
     ProtocolId,
 };
 use aptos_channels::{aptos_channel, message_queues::QueueStyle};
-use aptos_config::network_id::{NetworkContext, PeerNetworkId};
+use aptos_config::{
+    config::OutboundSchedulerConfig,
+    network_id::{NetworkContext, PeerNetworkId},
+};
 use aptos_logger::prelude::*;
 use aptos_short_hex_str::AsShortHexStr;
 use aptos_time_service::{TimeService, TimeServiceTrait};
@@ -46,7 +50,7 @@ use futures::{
     channel::oneshot,
     io::{AsyncRead, AsyncWrite},
     stream::StreamExt,
-    SinkExt,
+    FutureExt, SinkExt,
 };
 use futures_util::stream::select;
 use serde::Serialize;
@@ -56,12 +60,16 @@ use tokio_util::compat::{
     FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt,
 };
 
+mod outbound_scheduler;
 #[cfg(test)]
 mod test;
 
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod fuzzing;
 
+use outbound_scheduler::OutboundScheduler;
+pub use outbound_scheduler::{verify_outbound_scheduler_config, OutboundMessage};
+
 /// Requests [`Peer`] receives from the [`PeerManager`](crate::peer_manager::PeerManager).
 #[derive(Debug)]
 pub enum PeerRequest {
@@ -138,6 +146,8 @@ pub struct Peer<TSocket> {
     max_message_size: usize,
     /// Inbound stream buffer
     inbound_stream: InboundStreamBuffer,
+    /// The config for scheduling outbound messages across protocols
+    outbound_scheduler_config: OutboundSchedulerConfig,
 }
 
 impl<TSocket> Peer<TSocket>
@@ -160,6 +170,7 @@ where
         max_concurrent_outbound_rpcs: u32,
         max_frame_size: usize,
         max_message_size: usize,
+        outbound_scheduler_config: OutboundSchedulerConfig,
     ) -> Self {
         let Connection {
             metadata: connection_metadata,
@@ -193,6 +204,7 @@ where
             max_frame_size,
             max_message_size,
             inbound_stream: InboundStreamBuffer::new(max_fragments),
+            outbound_scheduler_config,
         }
     }
 
@@ -220,7 +232,7 @@ where
 
         // Start writer "process" as a separate task. We receive two handles to
         // communicate with the task:
-        //   1. `write_reqs_tx`: Queue of pending NetworkMessages to write.
+        //   1. `write_reqs_tx`: Queue of pending OutboundMessages to write.
         //   2. `close_tx`: Handle to close the task and underlying connection.
         let (mut write_reqs_tx, writer_close_tx) = Self::start_writer_task(
             &self.executor,
@@ -230,6 +242,7 @@ where
             writer,
             self.max_frame_size,
             self.max_message_size,
+            self.outbound_scheduler_config.clone(),
         );
 
         // Start main Peer event loop.
@@ -321,10 +334,11 @@ where
     // Start a new task on the given executor which is responsible for writing outbound messages on
     // the wire. The function returns two channels which can be used to send instructions to the
     // task:
-    // 1. The first channel is used to send outbound NetworkMessages to the task
+    // 1. The first channel is used to send OutboundMessages to the task
     // 2. The second channel is used to instruct the task to close the connection and terminate.
     // If outbound messages are queued when the task receives a close instruction, it discards
     // them and immediately closes the connection.
+    #[allow(clippy::too_many_arguments)]
     fn start_writer_task(
         executor: &Handle,
         time_service: TimeService,
@@ -333,12 +347,13 @@ where
         mut writer: MultiplexMessageSink<impl AsyncWrite + Unpin + Send + 'static>,
         max_frame_size: usize,
         max_message_size: usize,
+        outbound_scheduler_config: OutboundSchedulerConfig,
     ) -> (
-        aptos_channel::Sender<(), NetworkMessage>,
+        aptos_channel::Sender<(), OutboundMessage>,
         oneshot::Sender<()>,
     ) {
         let remote_peer_id = connection_metadata.remote_peer_id;
-        let (write_reqs_tx, mut write_reqs_rx): (aptos_channel::Sender<(), NetworkMessage>, _) =
+        let (write_reqs_tx, mut write_reqs_rx): (aptos_channel::Sender<(), OutboundMessage>, _) =
             aptos_channel::new(
                 QueueStyle::KLAST,
                 1024,
@@ -346,9 +361,15 @@ where
             );
         let (close_tx, mut close_rx) = oneshot::channel();
 
-        let (mut msg_tx, msg_rx) = aptos_channels::new(1024, &counters::PENDING_MULTIPLEX_MESSAGE);
+        // The multiplex channel is kept small so that the outbound scheduler
+        // (rather than the channel) determines the order of messages on the wire.
+        let (mut msg_tx, msg_rx) = aptos_channels::new(
+            MAX_BUFFERED_OUTBOUND_MESSAGES,
+            &counters::PENDING_MULTIPLEX_MESSAGE,
+        );
         let (stream_msg_tx, stream_msg_rx) =
             aptos_channels::new(1024, &counters::PENDING_MULTIPLEX_STREAM);
+        let scheduler_time_service = time_service.clone();
 
         // this task ends when the multiplex task ends (by dropping the senders) or receiving a close instruction
         let writer_task = async move {
@@ -416,11 +437,59 @@ where
                 },
             }
         };
-        // the task ends when the write_reqs_tx is dropped
+        // the task ends when the write_reqs_tx is dropped (and all scheduled messages are sent)
         let multiplex_task = async move {
             let mut outbound_stream =
                 OutboundStream::new(max_frame_size, max_message_size, stream_msg_tx);
-            while let Some(message) = write_reqs_rx.next().await {
+            let mut outbound_scheduler = OutboundScheduler::new(&outbound_scheduler_config);
+            let mut write_reqs_closed = false;
+            loop {
+                // Move all pending write requests into the scheduler
+                while !write_reqs_closed {
+                    match write_reqs_rx.next().now_or_never() {
+                        Some(Some(message)) => {
+                            outbound_scheduler.push(message, scheduler_time_service.now());
+                        },
+                        Some(None) => write_reqs_closed = true,
+                        None => break,
+                    }
+                }
+
+                // Get the next message to send. If there is none, wait for a new
+                // write request (or for a throttled protocol to become writable).
+                let message = match outbound_scheduler.pop(scheduler_time_service.now()) {
+                    Some(message) => message,
+                    None if write_reqs_closed && outbound_scheduler.is_empty() => break,
+                    None => {
+                        let throttle_delay = outbound_scheduler
+                            .time_until_next_message(scheduler_time_service.now());
+                        let wait_for_throttle = async {
+                            match throttle_delay {
+                                Some(delay) => {
+                                    // Sleep operates at millisecond granularity
+                                    scheduler_time_service
+                                        .sleep(delay.max(Duration::from_millis(1)))
+                                        .await
+                                },
+                                None => futures::future::pending().await,
+                            }
+                        };
+                        futures::select! {
+                            maybe_message = write_reqs_rx.next() => {
+                                match maybe_message {
+                                    Some(message) => {
+                                        let now = scheduler_time_service.now();
+                                        outbound_scheduler.push(message, now);
+                                    },
+                                    None => write_reqs_closed = true,
+                                }
+                            },
+                            _ = wait_for_throttle.fuse() => {},
+                        }
+                        continue;
+                    },
+                };
+
                 // either channel full would block the other one
                 let result = if outbound_stream.should_stream(&message) {
                     outbound_stream.stream_message(message).await
@@ -561,7 +630,7 @@ where
     fn handle_inbound_message(
         &mut self,
         message: Result<MultiplexMessage, ReadError>,
-        write_reqs_tx: &mut aptos_channel::Sender<(), NetworkMessage>,
+        write_reqs_tx: &mut aptos_channel::Sender<(), OutboundMessage>,
     ) -> Result<(), PeerManagerError> {
         trace!(
             NetworkSchema::new(&self.network_context)
@@ -583,7 +652,7 @@ where
                     let error_code = ErrorCode::parsing_error(*message_type, *protocol_id);
                     let message = NetworkMessage::Error(error_code);
 
-                    write_reqs_tx.push((), message)?;
+                    write_reqs_tx.push((), OutboundMessage::new(None, message))?;
                     return Err(err.into());
                 },
                 ReadError::IoError(_) => {
@@ -603,7 +672,7 @@ where
     fn handle_outbound_request(
         &mut self,
         request: PeerRequest,
-        write_reqs_tx: &mut aptos_channel::Sender<(), NetworkMessage>,
+        write_reqs_tx: &mut aptos_channel::Sender<(), OutboundMessage>,
     ) {
         trace!(
             "Peer {} PeerRequest::{:?}",
@@ -623,7 +692,7 @@ where
                     raw_msg: Vec::from(message.mdata.as_ref()),
                 });
 
-                match write_reqs_tx.push((), message) {
+                match write_reqs_tx.push((), OutboundMessage::new(Some(protocol_id), message)) {
                     Ok(_) => {
                         self.update_outbound_direct_send_metrics(protocol_id, message_len as u64);
                     },
@@ -682,7 +751,7 @@ where
 
     async fn do_shutdown(
         mut self,
-        write_req_tx: aptos_channel::Sender<(), NetworkMessage>,
+        write_req_tx: aptos_channel::Sender<(), OutboundMessage>,
         writer_close_tx: oneshot::Sender<()>,
         reason: DisconnectReason,
     ) {
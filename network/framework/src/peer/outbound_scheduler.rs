@@ -0,0 +1,789 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The [`OutboundScheduler`] decides the order in which outbound messages are
+//! written to a single peer connection.
+//!
+//! Messages are queued per [`ProtocolId`]. If strict priority is enabled, consensus
+//! messages are always written first, followed by network-level messages (e.g.,
+//! errors). Both classes have their own queues, so they can't evict each other.
+//! All other protocols share the connection using deficit round robin (DRR), where
+//! each protocol may write `weight * quantum_bytes` in every round. Protocols can
+//! also be capped to a maximum number of bytes per second.
+//!
+//! The number of pending messages is bounded per protocol and per connection.
+//! Dropped messages notify their sender (see [`OutboundMessage::with_write_notifier`]),
+//! e.g., so that RPCs fail fast instead of waiting for their timeout.
+//!
+//! Note: the scheduler only reorders whole messages, so the wire format (and the
+//! negotiated `MessagingProtocolVersion`) is unaffected.
+
+use crate::{counters, protocols::wire::messaging::v1::NetworkMessage, ProtocolId};
+use aptos_config::config::{Error, OutboundSchedulerConfig, ProtocolSchedulingConfig};
+use futures::channel::oneshot;
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+/// A message waiting to be written to the wire, along with the protocol that
+/// produced it. Network-level messages (e.g., errors) have no protocol.
+#[derive(Debug)]
+pub struct OutboundMessage {
+    pub protocol_id: Option<ProtocolId>,
+    pub message: NetworkMessage,
+    /// Notified once the message is handed to the writer. If the message is dropped
+    /// before that (e.g., because its queue is full), the notifier is dropped instead.
+    write_notifier: Option<oneshot::Sender<()>>,
+}
+
+impl OutboundMessage {
+    pub fn new(protocol_id: Option<ProtocolId>, message: NetworkMessage) -> Self {
+        Self {
+            protocol_id,
+            message,
+            write_notifier: None,
+        }
+    }
+
+    /// Attaches a notifier that is sent to once the message is written. If the
+    /// message is dropped instead, the receiver observes `oneshot::Canceled`.
+    pub fn with_write_notifier(mut self, write_notifier: oneshot::Sender<()>) -> Self {
+        self.write_notifier = Some(write_notifier);
+        self
+    }
+
+    /// Returns the message to write, notifying the sender (if required)
+    fn into_written_message(self) -> NetworkMessage {
+        if let Some(write_notifier) = self.write_notifier {
+            let _ = write_notifier.send(());
+        }
+        self.message
+    }
+}
+
+/// Returns true iff the given protocol carries consensus traffic
+fn is_consensus_protocol(protocol_id: ProtocolId) -> bool {
+    matches!(
+        protocol_id,
+        ProtocolId::ConsensusRpcBcs
+            | ProtocolId::ConsensusRpcJson
+            | ProtocolId::ConsensusRpcCompressed
+            | ProtocolId::ConsensusDirectSendBcs
+            | ProtocolId::ConsensusDirectSendJson
+            | ProtocolId::ConsensusDirectSendCompressed
+    )
+}
+
+/// Returns the protocol with the given name (as used in the config)
+fn protocol_id_from_name(protocol_name: &str) -> Option<ProtocolId> {
+    ProtocolId::all()
+        .iter()
+        .find(|protocol_id| protocol_id.as_str() == protocol_name)
+        .copied()
+}
+
+/// Verifies the given outbound scheduler config, i.e., that every per-protocol
+/// override refers to a known protocol.
+pub fn verify_outbound_scheduler_config(config: &OutboundSchedulerConfig) -> Result<(), Error> {
+    let mut unknown_protocol_names: Vec<_> = config
+        .protocol_configs
+        .keys()
+        .filter(|protocol_name| protocol_id_from_name(protocol_name).is_none())
+        .cloned()
+        .collect();
+    if unknown_protocol_names.is_empty() {
+        return Ok(());
+    }
+
+    unknown_protocol_names.sort();
+    Err(Error::InvariantViolation(format!(
+        "The outbound scheduler config contains unknown protocols: {:?}",
+        unknown_protocol_names
+    )))
+}
+
+/// A simple token bucket that limits the number of bytes written per second.
+/// The bucket is allowed to go into debt, so that messages larger than the
+/// bucket size are not starved (the debt must be repaid before the next write).
+#[derive(Debug)]
+struct ByteRateLimiter {
+    max_bytes_per_sec: u64,
+    available_bytes: f64,
+    last_refill_time: Instant,
+}
+
+impl ByteRateLimiter {
+    fn new(max_bytes_per_sec: u64, now: Instant) -> Self {
+        Self {
+            max_bytes_per_sec,
+            available_bytes: max_bytes_per_sec as f64,
+            last_refill_time: now,
+        }
+    }
+
+    /// Refills the bucket based on the time elapsed since the last refill
+    fn refill(&mut self, now: Instant) {
+        let elapsed_secs = now
+            .saturating_duration_since(self.last_refill_time)
+            .as_secs_f64();
+        self.available_bytes = (self.available_bytes
+            + elapsed_secs * self.max_bytes_per_sec as f64)
+            .min(self.max_bytes_per_sec as f64);
+        self.last_refill_time = now;
+    }
+
+    /// Returns true iff a message can currently be written
+    fn can_write(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.available_bytes > 0.0
+    }
+
+    /// Consumes the given number of bytes from the bucket
+    fn consume(&mut self, num_bytes: usize) {
+        self.available_bytes -= num_bytes as f64;
+    }
+
+    /// Returns the time until a message can be written again
+    fn time_until_writable(&mut self, now: Instant) -> Duration {
+        self.refill(now);
+        if self.available_bytes > 0.0 {
+            Duration::ZERO
+        } else {
+            // Wait until the debt has been repaid (plus a single byte)
+            let missing_bytes = 1.0 - self.available_bytes;
+            Duration::from_secs_f64(missing_bytes / self.max_bytes_per_sec.max(1) as f64)
+        }
+    }
+}
+
+/// The pending messages (and scheduling state) of a single protocol
+#[derive(Debug)]
+struct ProtocolQueue {
+    messages: VecDeque<OutboundMessage>,
+    quantum_bytes: u64,
+    deficit_bytes: u64,
+    has_turn: bool,
+    rate_limiter: Option<ByteRateLimiter>,
+}
+
+impl ProtocolQueue {
+    fn new(config: ProtocolSchedulingConfig, quantum_bytes: u64, now: Instant) -> Self {
+        Self {
+            messages: VecDeque::new(),
+            quantum_bytes: quantum_bytes.saturating_mul(config.weight.max(1) as u64),
+            deficit_bytes: 0,
+            has_turn: false,
+            rate_limiter: config
+                .max_bytes_per_sec
+                .map(|max_bytes_per_sec| ByteRateLimiter::new(max_bytes_per_sec, now)),
+        }
+    }
+
+    /// Returns true iff the protocol has exceeded its bandwidth cap
+    fn is_throttled(&mut self, now: Instant) -> bool {
+        self.rate_limiter
+            .as_mut()
+            .is_some_and(|rate_limiter| !rate_limiter.can_write(now))
+    }
+
+    /// Resets the scheduling state of the protocol once it becomes idle.
+    /// Idle protocols don't accumulate credit.
+    fn reset_turn(&mut self) {
+        self.deficit_bytes = 0;
+        self.has_turn = false;
+    }
+}
+
+/// Schedules outbound messages for a single connection (see the module docs)
+#[derive(Debug)]
+pub struct OutboundScheduler {
+    strict_consensus_priority: bool,
+    quantum_bytes: u64,
+    max_pending_messages_per_protocol: usize,
+    max_pending_messages: usize,
+    protocol_configs: HashMap<ProtocolId, ProtocolSchedulingConfig>,
+
+    /// Consensus messages, written before all others (if strict priority is enabled)
+    consensus_messages: VecDeque<OutboundMessage>,
+    /// Network-level messages (e.g., errors), written before all protocol queues
+    network_messages: VecDeque<OutboundMessage>,
+    /// The pending messages of each (non-priority) protocol
+    protocol_queues: HashMap<ProtocolId, ProtocolQueue>,
+    /// The round-robin order of protocols with pending messages
+    active_protocols: VecDeque<ProtocolId>,
+    /// The total number of pending messages (across all queues)
+    num_pending_messages: usize,
+}
+
+impl OutboundScheduler {
+    pub fn new(config: &OutboundSchedulerConfig) -> Self {
+        // Unknown protocols are rejected by `verify_outbound_scheduler_config`
+        // when the network is built, so they can safely be ignored here.
+        let protocol_configs = config
+            .protocol_configs
+            .iter()
+            .filter_map(|(protocol_name, protocol_config)| {
+                protocol_id_from_name(protocol_name)
+                    .map(|protocol_id| (protocol_id, *protocol_config))
+            })
+            .collect();
+
+        Self {
+            strict_consensus_priority: config.strict_consensus_priority,
+            quantum_bytes: config.quantum_bytes.max(1),
+            max_pending_messages_per_protocol: config.max_pending_messages_per_protocol.max(1),
+            max_pending_messages: config.max_pending_messages.max(1),
+            protocol_configs,
+            consensus_messages: VecDeque::new(),
+            network_messages: VecDeque::new(),
+            protocol_queues: HashMap::new(),
+            active_protocols: VecDeque::new(),
+            num_pending_messages: 0,
+        }
+    }
+
+    /// Returns true iff there are no pending messages
+    pub fn is_empty(&self) -> bool {
+        self.num_pending_messages == 0
+    }
+
+    /// Adds a new message to the scheduler. If the queue of the message's protocol
+    /// is full (or the scheduler as a whole is full), the oldest message of the
+    /// affected queue is dropped and returned. Dropping a message drops its write
+    /// notifier, so the sender learns that the message will never be written.
+    pub fn push(&mut self, message: OutboundMessage, now: Instant) -> Option<NetworkMessage> {
+        let max_pending_messages = self.max_pending_messages_per_protocol;
+
+        let protocol_id = message.protocol_id;
+        let dropped_message = match protocol_id {
+            Some(protocol_id)
+                if self.strict_consensus_priority && is_consensus_protocol(protocol_id) =>
+            {
+                push_bounded(&mut self.consensus_messages, message, max_pending_messages)
+            },
+            Some(protocol_id) => {
+                let protocol_config = self
+                    .protocol_configs
+                    .get(&protocol_id)
+                    .copied()
+                    .unwrap_or_default();
+                let quantum_bytes = self.quantum_bytes;
+                let queue = self
+                    .protocol_queues
+                    .entry(protocol_id)
+                    .or_insert_with(|| ProtocolQueue::new(protocol_config, quantum_bytes, now));
+
+                // Add the protocol to the round-robin order if it's not already there
+                if queue.messages.is_empty() {
+                    self.active_protocols.push_back(protocol_id);
+                }
+                push_bounded(&mut queue.messages, message, max_pending_messages)
+            },
+            None => push_bounded(&mut self.network_messages, message, max_pending_messages),
+        };
+
+        // Enforce the global limit (a message dropped above keeps the count unchanged)
+        let dropped_message = match dropped_message {
+            Some(dropped_message) => Some(dropped_message),
+            None if self.num_pending_messages >= self.max_pending_messages => {
+                self.drop_from_longest_queue()
+            },
+            None => {
+                self.num_pending_messages += 1;
+                None
+            },
+        };
+
+        dropped_message.map(|dropped_message| {
+            let protocol_label = dropped_message
+                .protocol_id
+                .map_or("none", |protocol_id| protocol_id.as_str());
+            counters::OUTBOUND_SCHEDULER_DROPPED_MESSAGES
+                .with_label_values(&[protocol_label])
+                .inc();
+            dropped_message.message
+        })
+    }
+
+    /// Drops the oldest message of the protocol with the most pending messages.
+    /// Consensus and network-level messages are only dropped if no other
+    /// protocol has pending messages.
+    fn drop_from_longest_queue(&mut self) -> Option<OutboundMessage> {
+        let longest_protocol = self
+            .active_protocols
+            .iter()
+            .copied()
+            .max_by_key(|protocol_id| self.protocol_queues[protocol_id].messages.len());
+        if let Some(protocol_id) = longest_protocol {
+            let queue = self
+                .protocol_queues
+                .get_mut(&protocol_id)
+                .expect("Active protocols must have a queue!");
+            let dropped_message = queue.messages.pop_front();
+            if queue.messages.is_empty() {
+                queue.reset_turn();
+                self.active_protocols
+                    .retain(|active_protocol| *active_protocol != protocol_id);
+            }
+            return dropped_message;
+        }
+
+        self.network_messages
+            .pop_front()
+            .or_else(|| self.consensus_messages.pop_front())
+    }
+
+    /// Returns the next message to write to the wire. Returns None if there are
+    /// no pending messages, or if all pending messages are currently throttled.
+    pub fn pop(&mut self, now: Instant) -> Option<NetworkMessage> {
+        let message = self.pop_message(now)?;
+        self.num_pending_messages -= 1;
+        Some(message.into_written_message())
+    }
+
+    /// Returns the next message according to the scheduling policy
+    fn pop_message(&mut self, now: Instant) -> Option<OutboundMessage> {
+        if let Some(message) = self.consensus_messages.pop_front() {
+            return Some(message);
+        }
+        if let Some(message) = self.network_messages.pop_front() {
+            return Some(message);
+        }
+
+        // Run deficit round robin over the active protocols. We stop once every
+        // active protocol has been found to be throttled (in a row).
+        let mut num_throttled_protocols = 0;
+        while num_throttled_protocols < self.active_protocols.len() {
+            let protocol_id = *self.active_protocols.front()?;
+            let queue = self
+                .protocol_queues
+                .get_mut(&protocol_id)
+                .expect("Active protocols must have a queue!");
+
+            // Skip the protocol if it has exceeded its bandwidth cap
+            if queue.is_throttled(now) {
+                counters::OUTBOUND_SCHEDULER_THROTTLED
+                    .with_label_values(&[protocol_id.as_str()])
+                    .inc();
+                queue.has_turn = false;
+                self.active_protocols.rotate_left(1);
+                num_throttled_protocols += 1;
+                continue;
+            }
+            num_throttled_protocols = 0;
+
+            // Give the protocol its quantum at the start of its turn
+            if !queue.has_turn {
+                queue.deficit_bytes = queue.deficit_bytes.saturating_add(queue.quantum_bytes);
+                queue.has_turn = true;
+            }
+
+            let message_len = queue
+                .messages
+                .front()
+                .expect("Active protocols must have pending messages!")
+                .message
+                .data_len() as u64;
+            if queue.deficit_bytes < message_len {
+                // The protocol has used up its quantum for this round
+                queue.has_turn = false;
+                self.active_protocols.rotate_left(1);
+                continue;
+            }
+
+            // Write the message and charge the protocol for it
+            let message = queue.messages.pop_front()?;
+            queue.deficit_bytes -= message_len;
+            if let Some(rate_limiter) = queue.rate_limiter.as_mut() {
+                rate_limiter.consume(message_len as usize);
+            }
+            if queue.messages.is_empty() {
+                queue.reset_turn();
+                self.active_protocols.pop_front();
+            }
+            return Some(message);
+        }
+
+        None
+    }
+
+    /// Returns the time until the next throttled message may be written, or
+    /// None if there are no pending messages.
+    pub fn time_until_next_message(&mut self, now: Instant) -> Option<Duration> {
+        if !self.consensus_messages.is_empty() || !self.network_messages.is_empty() {
+            return Some(Duration::ZERO);
+        }
+        self.active_protocols
+            .iter()
+            .filter_map(|protocol_id| {
+                let queue = self.protocol_queues.get_mut(protocol_id)?;
+                Some(match queue.rate_limiter.as_mut() {
+                    Some(rate_limiter) => rate_limiter.time_until_writable(now),
+                    None => Duration::ZERO,
+                })
+            })
+            .min()
+    }
+}
+
+/// Pushes the message to the back of the queue. If the queue is full, the
+/// oldest message is dropped and returned.
+fn push_bounded(
+    queue: &mut VecDeque<OutboundMessage>,
+    message: OutboundMessage,
+    max_queue_size: usize,
+) -> Option<OutboundMessage> {
+    let dropped_message = if queue.len() >= max_queue_size {
+        queue.pop_front()
+    } else {
+        None
+    };
+    queue.push_back(message);
+    dropped_message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::wire::messaging::v1::{DirectSendMsg, ErrorCode, Priority};
+
+    /// Creates a direct send message of the given protocol and size
+    fn direct_send(protocol_id: ProtocolId, size: usize) -> OutboundMessage {
+        OutboundMessage::new(
+            Some(protocol_id),
+            NetworkMessage::DirectSendMsg(DirectSendMsg {
+                protocol_id,
+                priority: Priority::default(),
+                raw_msg: vec![0; size],
+            }),
+        )
+    }
+
+    /// Returns the protocol of the given direct send message
+    fn protocol_of(message: &NetworkMessage) -> ProtocolId {
+        match message {
+            NetworkMessage::DirectSendMsg(message) => message.protocol_id,
+            message => panic!("Unexpected message: {:?}", message),
+        }
+    }
+
+    /// Creates a scheduler config with the given protocol configs
+    fn scheduler_config(
+        quantum_bytes: u64,
+        protocol_configs: &[(ProtocolId, ProtocolSchedulingConfig)],
+    ) -> OutboundSchedulerConfig {
+        OutboundSchedulerConfig {
+            quantum_bytes,
+            protocol_configs: protocol_configs
+                .iter()
+                .map(|(protocol_id, config)| (protocol_id.as_str().to_string(), *config))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_consensus_strict_priority() {
+        let mut scheduler = OutboundScheduler::new(&OutboundSchedulerConfig::default());
+        let now = Instant::now();
+
+        // Queue a burst of mempool messages, followed by a consensus message
+        for _ in 0..10 {
+            scheduler.push(direct_send(ProtocolId::MempoolDirectSend, 100), now);
+        }
+        scheduler.push(direct_send(ProtocolId::ConsensusDirectSendBcs, 100), now);
+        scheduler.push(
+            OutboundMessage::new(None, NetworkMessage::Error(ErrorCode::parsing_error(0, 0))),
+            now,
+        );
+
+        // Verify the consensus message (and the error) are written first
+        let message = scheduler.pop(now).unwrap();
+        assert_eq!(protocol_of(&message), ProtocolId::ConsensusDirectSendBcs);
+        assert!(matches!(
+            scheduler.pop(now).unwrap(),
+            NetworkMessage::Error(_)
+        ));
+        for _ in 0..10 {
+            let message = scheduler.pop(now).unwrap();
+            assert_eq!(protocol_of(&message), ProtocolId::MempoolDirectSend);
+        }
+        assert!(scheduler.pop(now).is_none());
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn test_consensus_without_priority() {
+        let config = OutboundSchedulerConfig {
+            strict_consensus_priority: false,
+            ..Default::default()
+        };
+        let mut scheduler = OutboundScheduler::new(&config);
+        let now = Instant::now();
+
+        // Queue the messages and verify they are written in round-robin order
+        scheduler.push(direct_send(ProtocolId::MempoolDirectSend, 100), now);
+        scheduler.push(direct_send(ProtocolId::ConsensusDirectSendBcs, 100), now);
+        assert_eq!(
+            protocol_of(&scheduler.pop(now).unwrap()),
+            ProtocolId::MempoolDirectSend
+        );
+        assert_eq!(
+            protocol_of(&scheduler.pop(now).unwrap()),
+            ProtocolId::ConsensusDirectSendBcs
+        );
+    }
+
+    #[test]
+    fn test_weighted_fair_queueing() {
+        let config = scheduler_config(100, &[(
+            ProtocolId::StateSyncDirectSend,
+            ProtocolSchedulingConfig {
+                weight: 3,
+                max_bytes_per_sec: None,
+            },
+        )]);
+        let mut scheduler = OutboundScheduler::new(&config);
+        let now = Instant::now();
+
+        // Queue many equally sized messages for two protocols
+        for _ in 0..40 {
+            scheduler.push(direct_send(ProtocolId::MempoolDirectSend, 100), now);
+            scheduler.push(direct_send(ProtocolId::StateSyncDirectSend, 100), now);
+        }
+
+        // Verify state sync gets three times the share of mempool
+        let mut num_state_sync_messages = 0;
+        for _ in 0..40 {
+            if protocol_of(&scheduler.pop(now).unwrap()) == ProtocolId::StateSyncDirectSend {
+                num_state_sync_messages += 1;
+            }
+        }
+        assert_eq!(num_state_sync_messages, 30);
+    }
+
+    #[test]
+    fn test_large_messages_are_not_starved() {
+        let mut scheduler = OutboundScheduler::new(&scheduler_config(10, &[]));
+        let now = Instant::now();
+
+        // Queue a message much larger than the quantum, and many small ones
+        scheduler.push(direct_send(ProtocolId::StateSyncDirectSend, 1_000), now);
+        for _ in 0..1_000 {
+            scheduler.push(direct_send(ProtocolId::MempoolDirectSend, 10), now);
+        }
+
+        // Verify the large message is written once it has accumulated enough credit
+        let position = (0..1_001)
+            .position(|_| {
+                protocol_of(&scheduler.pop(now).unwrap()) == ProtocolId::StateSyncDirectSend
+            })
+            .unwrap();
+        assert_eq!(position, 99);
+    }
+
+    #[test]
+    fn test_bandwidth_cap() {
+        let config = scheduler_config(1_000, &[(
+            ProtocolId::MempoolDirectSend,
+            ProtocolSchedulingConfig {
+                weight: 1,
+                max_bytes_per_sec: Some(1_000),
+            },
+        )]);
+        let mut scheduler = OutboundScheduler::new(&config);
+        let now = Instant::now();
+
+        // Queue several messages that exceed the bandwidth cap
+        for _ in 0..3 {
+            scheduler.push(direct_send(ProtocolId::MempoolDirectSend, 800), now);
+        }
+
+        // Verify only the first two messages can be written immediately (the
+        // second one puts the bucket into debt).
+        assert!(scheduler.pop(now).is_some());
+        assert!(scheduler.pop(now).is_some());
+        assert!(scheduler.pop(now).is_none());
+        assert!(!scheduler.is_empty());
+
+        // Verify the remaining message is written once the debt has been repaid
+        let wait_time = scheduler.time_until_next_message(now).unwrap();
+        assert!(wait_time > Duration::from_millis(500));
+        assert!(scheduler.pop(now + wait_time).is_some());
+        assert!(scheduler.time_until_next_message(now + wait_time).is_none());
+    }
+
+    #[test]
+    fn test_throttled_protocols_do_not_block_others() {
+        let config = scheduler_config(1_000, &[(
+            ProtocolId::MempoolDirectSend,
+            ProtocolSchedulingConfig {
+                weight: 1,
+                max_bytes_per_sec: Some(100),
+            },
+        )]);
+        let mut scheduler = OutboundScheduler::new(&config);
+        let now = Instant::now();
+
+        // Exhaust the mempool bandwidth and queue state sync messages
+        for _ in 0..2 {
+            scheduler.push(direct_send(ProtocolId::MempoolDirectSend, 200), now);
+        }
+        for _ in 0..2 {
+            scheduler.push(direct_send(ProtocolId::StateSyncDirectSend, 200), now);
+        }
+
+        // Verify the state sync messages are written while mempool is throttled
+        let mut written_protocols = vec![];
+        while let Some(message) = scheduler.pop(now) {
+            written_protocols.push(protocol_of(&message));
+        }
+        assert_eq!(written_protocols, vec![
+            ProtocolId::MempoolDirectSend,
+            ProtocolId::StateSyncDirectSend,
+            ProtocolId::StateSyncDirectSend,
+        ]);
+    }
+
+    #[test]
+    fn test_full_queue_drops_oldest() {
+        let config = OutboundSchedulerConfig {
+            max_pending_messages_per_protocol: 2,
+            ..Default::default()
+        };
+        let mut scheduler = OutboundScheduler::new(&config);
+        let now = Instant::now();
+
+        // Fill the mempool queue and verify the oldest message is dropped
+        assert!(scheduler
+            .push(direct_send(ProtocolId::MempoolDirectSend, 1), now)
+            .is_none());
+        assert!(scheduler
+            .push(direct_send(ProtocolId::MempoolDirectSend, 2), now)
+            .is_none());
+        let dropped_message = scheduler
+            .push(direct_send(ProtocolId::MempoolDirectSend, 3), now)
+            .unwrap();
+        assert_eq!(dropped_message.data_len(), 1);
+
+        // Verify other protocols are unaffected by the full queue
+        assert!(scheduler
+            .push(direct_send(ProtocolId::ConsensusDirectSendBcs, 4), now)
+            .is_none());
+    }
+
+    #[test]
+    fn test_consensus_and_network_messages_do_not_evict_each_other() {
+        let config = OutboundSchedulerConfig {
+            max_pending_messages_per_protocol: 1,
+            ..Default::default()
+        };
+        let mut scheduler = OutboundScheduler::new(&config);
+        let now = Instant::now();
+
+        // Queue an error followed by a consensus message (each queue holds a single message)
+        let error = NetworkMessage::Error(ErrorCode::parsing_error(0, 0));
+        assert!(scheduler
+            .push(OutboundMessage::new(None, error), now)
+            .is_none());
+        assert!(scheduler
+            .push(direct_send(ProtocolId::ConsensusDirectSendBcs, 1), now)
+            .is_none());
+
+        // Verify both messages are written (consensus first)
+        assert_eq!(
+            protocol_of(&scheduler.pop(now).unwrap()),
+            ProtocolId::ConsensusDirectSendBcs
+        );
+        assert!(matches!(
+            scheduler.pop(now).unwrap(),
+            NetworkMessage::Error(_)
+        ));
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn test_global_limit_drops_from_longest_queue() {
+        let config = OutboundSchedulerConfig {
+            max_pending_messages: 4,
+            ..Default::default()
+        };
+        let mut scheduler = OutboundScheduler::new(&config);
+        let now = Instant::now();
+
+        // Fill the scheduler with mempool messages and a single state sync message
+        for size in 1..=3 {
+            assert!(scheduler
+                .push(direct_send(ProtocolId::MempoolDirectSend, size), now)
+                .is_none());
+        }
+        assert!(scheduler
+            .push(direct_send(ProtocolId::StateSyncDirectSend, 10), now)
+            .is_none());
+
+        // Verify a new consensus message evicts the oldest mempool message
+        let dropped_message = scheduler
+            .push(direct_send(ProtocolId::ConsensusDirectSendBcs, 20), now)
+            .unwrap();
+        assert_eq!(protocol_of(&dropped_message), ProtocolId::MempoolDirectSend);
+        assert_eq!(dropped_message.data_len(), 1);
+
+        // Verify the remaining messages are all written
+        let mut written_protocols = vec![];
+        while let Some(message) = scheduler.pop(now) {
+            written_protocols.push(protocol_of(&message));
+        }
+        assert_eq!(written_protocols.len(), 4);
+        assert_eq!(written_protocols[0], ProtocolId::ConsensusDirectSendBcs);
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn test_write_notifications() {
+        let config = OutboundSchedulerConfig {
+            max_pending_messages_per_protocol: 1,
+            ..Default::default()
+        };
+        let mut scheduler = OutboundScheduler::new(&config);
+        let now = Instant::now();
+
+        // Queue two messages with write notifiers (the first one is dropped)
+        let (dropped_tx, mut dropped_rx) = oneshot::channel();
+        let (written_tx, mut written_rx) = oneshot::channel();
+        scheduler.push(
+            direct_send(ProtocolId::MempoolDirectSend, 1).with_write_notifier(dropped_tx),
+            now,
+        );
+        scheduler.push(
+            direct_send(ProtocolId::MempoolDirectSend, 2).with_write_notifier(written_tx),
+            now,
+        );
+
+        // Verify the dropped message notifies its sender
+        assert_eq!(dropped_rx.try_recv(), Err(oneshot::Canceled));
+
+        // Verify the written message notifies its sender once it is popped
+        assert_eq!(written_rx.try_recv(), Ok(None));
+        assert!(scheduler.pop(now).is_some());
+        assert_eq!(written_rx.try_recv(), Ok(Some(())));
+    }
+
+    #[test]
+    fn test_verify_config() {
+        // Verify known protocols are accepted
+        let config = scheduler_config(100, &[(
+            ProtocolId::MempoolDirectSend,
+            ProtocolSchedulingConfig::default(),
+        )]);
+        assert!(verify_outbound_scheduler_config(&config).is_ok());
+
+        // Verify unknown protocols are rejected
+        let mut config = OutboundSchedulerConfig::default();
+        config.protocol_configs.insert(
+            "MempoolDirectSnd".into(),
+            ProtocolSchedulingConfig::default(),
+        );
+        assert!(verify_outbound_scheduler_config(&config).is_err());
+    }
+}
@@ -14,11 +14,16 @@ use crate::{
         network::{NetworkClientConfig, NetworkServiceConfig, ReceivedMessage},
         wire::handshake::v1::ProtocolIdSet,
     },
-    transport::{self, AptosNetTransport, Connection, APTOS_TCP_TRANSPORT},
+    transport::{
+        self, recording::TrafficRecording, AptosNetTransport, Connection, APTOS_TCP_TRANSPORT,
+    },
     ProtocolId,
 };
 use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
-use aptos_config::{config::HANDSHAKE_VERSION, network_id::NetworkContext};
+use aptos_config::{
+    config::{OutboundSchedulerConfig, HANDSHAKE_VERSION},
+    network_id::NetworkContext,
+};
 use aptos_crypto::x25519;
 use aptos_logger::prelude::*;
 #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
@@ -29,7 +34,7 @@ use aptos_netcore::transport::{
 };
 use aptos_time_service::TimeService;
 use aptos_types::{chain_id::ChainId, network_address::NetworkAddress, PeerId};
-use std::{clone::Clone, collections::HashMap, fmt::Debug, sync::Arc};
+use std::{clone::Clone, collections::HashMap, fmt::Debug, path::PathBuf, sync::Arc};
 use tokio::runtime::Handle;
 
 /// Inbound and Outbound connections are always secured with NoiseIK.  The dialer
@@ -52,6 +57,7 @@ struct TransportContext {
     authentication_mode: AuthenticationMode,
     peers_and_metadata: Arc<PeersAndMetadata>,
     enable_proxy_protocol: bool,
+    traffic_recording_dir: Option<PathBuf>,
 }
 
 impl TransportContext {
@@ -78,6 +84,7 @@ struct PeerManagerContext {
     max_message_size: usize,
     inbound_connection_limit: usize,
     tcp_buffer_cfg: TCPBufferCfg,
+    outbound_scheduler_config: OutboundSchedulerConfig,
 }
 
 impl PeerManagerContext {
@@ -100,6 +107,7 @@ impl PeerManagerContext {
         max_message_size: usize,
         inbound_connection_limit: usize,
         tcp_buffer_cfg: TCPBufferCfg,
+        outbound_scheduler_config: OutboundSchedulerConfig,
     ) -> Self {
         Self {
             pm_reqs_tx,
@@ -116,6 +124,7 @@ impl PeerManagerContext {
             max_message_size,
             inbound_connection_limit,
             tcp_buffer_cfg,
+            outbound_scheduler_config,
         }
     }
 
@@ -173,6 +182,7 @@ impl PeerManagerBuilder {
         enable_proxy_protocol: bool,
         inbound_connection_limit: usize,
         tcp_buffer_cfg: TCPBufferCfg,
+        outbound_scheduler_config: OutboundSchedulerConfig,
     ) -> Self {
         // Setup channel to send requests to peer manager.
         let (pm_reqs_tx, pm_reqs_rx) = aptos_channel::new(
@@ -193,6 +203,7 @@ impl PeerManagerBuilder {
                 authentication_mode,
                 peers_and_metadata: peers_and_metadata.clone(),
                 enable_proxy_protocol,
+                traffic_recording_dir: None,
             }),
             peer_manager_context: Some(PeerManagerContext::new(
                 pm_reqs_tx,
@@ -207,6 +218,7 @@ impl PeerManagerBuilder {
                 max_message_size,
                 inbound_connection_limit,
                 tcp_buffer_cfg,
+                outbound_scheduler_config,
             )),
             peer_manager: None,
             listen_address,
@@ -225,6 +237,11 @@ impl PeerManagerBuilder {
             .clone()
     }
 
+    /// Records the traffic of all connections to files in the given directory
+    pub fn set_traffic_recording_dir(&mut self, traffic_recording_dir: PathBuf) {
+        self.transport_context().traffic_recording_dir = Some(traffic_recording_dir);
+    }
+
     fn transport_context(&mut self) -> &mut TransportContext {
         self.transport_context
             .as_mut()
@@ -250,6 +267,10 @@ impl PeerManagerBuilder {
         let protos = transport_context.supported_protocols;
         let chain_id = transport_context.chain_id;
         let enable_proxy_protocol = transport_context.enable_proxy_protocol;
+        let max_frame_size = self.peer_manager_context().max_frame_size;
+        let traffic_recording = transport_context
+            .traffic_recording_dir
+            .map(|dir| TrafficRecording::new(dir, self.time_service.clone(), max_frame_size));
 
         let (key, auth_mode) = match transport_context.authentication_mode {
             AuthenticationMode::MaybeMutual(key) => (
@@ -267,8 +288,8 @@ impl PeerManagerBuilder {
         aptos_tcp_transport.set_tcp_buffers(&tcp_cfg);
 
         self.peer_manager = match self.listen_address.as_slice() {
-            [Ip4(_), Tcp(_)] | [Ip6(_), Tcp(_)] => {
-                Some(TransportPeerManager::Tcp(self.build_with_transport(
+            [Ip4(_), Tcp(_)] | [Ip6(_), Tcp(_)] => Some(TransportPeerManager::Tcp(
+                self.build_with_transport(
                     AptosNetTransport::new(
                         aptos_tcp_transport,
                         self.network_context,
@@ -279,25 +300,29 @@ impl PeerManagerBuilder {
                         chain_id,
                         protos,
                         enable_proxy_protocol,
-                    ),
+                    )
+                    .with_traffic_recording(traffic_recording),
                     executor,
-                )))
-            },
+                ),
+            )),
             #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
-            [Memory(_)] => Some(TransportPeerManager::Memory(self.build_with_transport(
-                AptosNetTransport::new(
-                    MemoryTransport,
-                    self.network_context,
-                    self.time_service.clone(),
-                    key,
-                    auth_mode,
-                    HANDSHAKE_VERSION,
-                    chain_id,
-                    protos,
-                    enable_proxy_protocol,
+            [Memory(_)] => Some(TransportPeerManager::Memory(
+                self.build_with_transport(
+                    AptosNetTransport::new(
+                        MemoryTransport,
+                        self.network_context,
+                        self.time_service.clone(),
+                        key,
+                        auth_mode,
+                        HANDSHAKE_VERSION,
+                        chain_id,
+                        protos,
+                        enable_proxy_protocol,
+                    )
+                    .with_traffic_recording(traffic_recording),
+                    executor,
                 ),
-                executor,
-            ))),
+            )),
             _ => panic!(
                 "{} Unsupported listen_address: '{}', expected '/memory/<port>', \
                  '/ip4/<addr>/tcp/<port>', or '/ip6/<addr>/tcp/<port>'.",
@@ -340,6 +365,7 @@ impl PeerManagerBuilder {
             pm_context.max_frame_size,
             pm_context.max_message_size,
             pm_context.inbound_connection_limit,
+            pm_context.outbound_scheduler_config,
         );
 
         // PeerManager constructor appends a public key to the listen_address.
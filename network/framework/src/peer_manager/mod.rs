@@ -58,7 +58,7 @@ use crate::{
     peer_manager::transport::{TransportHandler, TransportRequest},
     protocols::network::{ReceivedMessage, SerializedRequest},
 };
-use aptos_config::config::PeerRole;
+use aptos_config::config::{OutboundSchedulerConfig, PeerRole};
 use aptos_types::account_address::AccountAddress;
 pub use senders::*;
 pub use types::*;
@@ -117,6 +117,8 @@ where
     max_message_size: usize,
     /// Inbound connection limit separate of outbound connections
     inbound_connection_limit: usize,
+    /// The config for scheduling outbound messages on each connection
+    outbound_scheduler_config: OutboundSchedulerConfig,
 }
 
 impl<TTransport, TSocket> PeerManager<TTransport, TSocket>
@@ -144,6 +146,7 @@ where
         max_frame_size: usize,
         max_message_size: usize,
         inbound_connection_limit: usize,
+        outbound_scheduler_config: OutboundSchedulerConfig,
     ) -> Self {
         let (transport_notifs_tx, transport_notifs_rx) = aptos_channels::new(
             channel_size,
@@ -185,6 +188,7 @@ where
             max_frame_size,
             max_message_size,
             inbound_connection_limit,
+            outbound_scheduler_config,
         }
     }
 
@@ -676,6 +680,7 @@ where
             constants::MAX_CONCURRENT_OUTBOUND_RPCS,
             self.max_frame_size,
             self.max_message_size,
+            self.outbound_scheduler_config.clone(),
         );
         self.executor.spawn(peer.start());
 
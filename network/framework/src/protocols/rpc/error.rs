@@ -42,6 +42,9 @@ pub enum RpcError {
 
     #[error("Rpc timed out")]
     TimedOut,
+
+    #[error("Rpc request was dropped before being written, the connection is likely overloaded")]
+    RequestDropped,
 }
 
 impl From<PeerManagerError> for RpcError {
@@ -51,6 +51,7 @@ use crate::{
         RECEIVED_LABEL, REQUEST_LABEL, RESPONSE_LABEL, SENT_LABEL,
     },
     logging::NetworkSchema,
+    peer::OutboundMessage,
     protocols::{
         network::{ReceivedMessage, SerializedRequest},
         wire::messaging::v1::{NetworkMessage, Priority, RequestId, RpcRequest, RpcResponse},
@@ -324,7 +325,7 @@ impl InboundRpcs {
     /// the outbound write queue.
     pub fn send_outbound_response(
         &mut self,
-        write_reqs_tx: &mut aptos_channel::Sender<(), NetworkMessage>,
+        write_reqs_tx: &mut aptos_channel::Sender<(), OutboundMessage>,
         maybe_response: Result<(RpcResponse, ProtocolId), RpcError>,
     ) -> Result<(), RpcError> {
         let network_context = &self.network_context;
@@ -352,7 +353,7 @@ impl InboundRpcs {
             response.request_id,
         );
         let message = NetworkMessage::RpcResponse(response);
-        write_reqs_tx.push((), message)?;
+        write_reqs_tx.push((), OutboundMessage::new(Some(protocol_id), message))?;
 
         // Update the outbound RPC response metrics
         self.update_outbound_rpc_response_metrics(protocol_id, res_len);
@@ -434,7 +435,7 @@ impl OutboundRpcs {
     pub fn handle_outbound_request(
         &mut self,
         request: OutboundRpcRequest,
-        write_reqs_tx: &mut aptos_channel::Sender<(), NetworkMessage>,
+        write_reqs_tx: &mut aptos_channel::Sender<(), OutboundMessage>,
     ) -> Result<(), RpcError> {
         let network_context = &self.network_context;
         let peer_id = &self.remote_peer_id;
@@ -497,6 +498,11 @@ impl OutboundRpcs {
             priority: Priority::default(),
             raw_request: Vec::from(request_data.as_ref()),
         });
+        // The write notifier is dropped if the request is dropped before being
+        // written (e.g., by the outbound scheduler of an overloaded connection).
+        let (write_notifier, request_written) = oneshot::channel();
+        let message =
+            OutboundMessage::new(Some(protocol_id), message).with_write_notifier(write_notifier);
         write_reqs_tx.push((), message)?;
 
         // Update the outbound RPC request metrics
@@ -529,8 +535,17 @@ impl OutboundRpcs {
         let notify_application = async move {
             // This future will complete if the application layer cancels the request.
             let mut cancellation = application_response_tx.cancellation().fuse();
-            // Pin the response future to the stack so we don't have to box it.
+            // This future will complete if the request is dropped before being written.
+            let request_dropped = request_written
+                .then(|result| async move {
+                    if result.is_ok() {
+                        futures::future::pending::<()>().await;
+                    }
+                })
+                .fuse();
+            // Pin the futures to the stack so we don't have to box them.
             tokio::pin!(wait_for_response);
+            tokio::pin!(request_dropped);
 
             futures::select! {
                 maybe_response = wait_for_response => {
@@ -545,6 +560,11 @@ impl OutboundRpcs {
                     application_response_tx.send(maybe_response).map_err(|_| RpcError::UnexpectedResponseChannelCancel)?;
                     result_copy
                 }
+                _ = request_dropped => {
+                    // Notify the application that the request will never be answered
+                    let _ = application_response_tx.send(Err(RpcError::RequestDropped));
+                    Err(RpcError::RequestDropped)
+                },
                 _ = cancellation => Err(RpcError::UnexpectedResponseChannelCancel),
             }
         };
@@ -12,6 +12,7 @@ pub mod builder;
 pub mod fake_socket;
 pub mod test_framework;
 pub mod test_node;
+pub mod traffic;
 
 /// Creates a network context for a client and server, and returns the
 /// contexts alongside peers and metadata.
@@ -0,0 +1,350 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//!
+//! This module allows recorded network traffic (see `transport::recording`) to be
+//! replayed in tests:
+//! - TrafficReplayer: feeds the inbound frames of a recording into a `Peer` actor
+//!   (over a `MemorySocket`), and collects the frames sent by the peer.
+//!
+
+pub use crate::transport::recording::{
+    read_recording, recording_path, TrafficDirection, TrafficRecord,
+};
+use crate::{
+    constants::{
+        INBOUND_RPC_TIMEOUT_MS, MAX_CONCURRENT_INBOUND_RPCS, MAX_CONCURRENT_OUTBOUND_RPCS,
+        MAX_MESSAGE_SIZE, NETWORK_CHANNEL_SIZE,
+    },
+    peer::{Peer, PeerRequest},
+    peer_manager::TransportNotification,
+    protocols::{network::ReceivedMessage, wire::messaging::v1::network_message_frame_codec},
+    transport::{Connection, ConnectionMetadata},
+    ProtocolId,
+};
+use aptos_channels::{aptos_channel, message_queues::QueueStyle};
+use aptos_config::{config::OutboundSchedulerConfig, network_id::NetworkContext};
+use aptos_memsocket::MemorySocket;
+use aptos_time_service::{MockTimeService, TimeService};
+use aptos_types::PeerId;
+use bytes::{BufMut, Bytes, BytesMut};
+use futures::{
+    io::{AsyncReadExt, AsyncWriteExt, ReadHalf, WriteHalf},
+    stream::TryStreamExt,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::runtime::Handle;
+use tokio_util::{codec::FramedRead, compat::FuturesAsyncReadCompatExt};
+
+//
+// TrafficReplayer
+// ===============
+//
+
+/// Builds a peer actor (over a `MemorySocket`) that a recording can be replayed
+/// into. Returns the peer, its request sender, the remote end of the socket
+/// and the connection notification receiver.
+pub fn build_replay_peer(
+    executor: Handle,
+    time_service: TimeService,
+    connection_metadata: ConnectionMetadata,
+    upstream_handlers: Arc<
+        HashMap<ProtocolId, aptos_channel::Sender<(PeerId, ProtocolId), ReceivedMessage>>,
+    >,
+    max_frame_size: usize,
+) -> (
+    Peer<MemorySocket>,
+    aptos_channel::Sender<ProtocolId, PeerRequest>,
+    MemorySocket,
+    aptos_channels::Receiver<TransportNotification<MemorySocket>>,
+) {
+    let (local_socket, remote_socket) = MemorySocket::new_pair();
+    let connection = Connection {
+        metadata: connection_metadata,
+        socket: local_socket,
+    };
+
+    let (connection_notifs_tx, connection_notifs_rx) = aptos_channels::new_test(1);
+    let (peer_reqs_tx, peer_reqs_rx) =
+        aptos_channel::new(QueueStyle::FIFO, NETWORK_CHANNEL_SIZE, None);
+    let peer = Peer::new(
+        NetworkContext::mock(),
+        executor,
+        time_service,
+        connection,
+        connection_notifs_tx,
+        peer_reqs_rx,
+        upstream_handlers,
+        Duration::from_millis(INBOUND_RPC_TIMEOUT_MS),
+        MAX_CONCURRENT_INBOUND_RPCS,
+        MAX_CONCURRENT_OUTBOUND_RPCS,
+        max_frame_size,
+        MAX_MESSAGE_SIZE,
+        OutboundSchedulerConfig::default(),
+    );
+
+    (peer, peer_reqs_tx, remote_socket, connection_notifs_rx)
+}
+
+/// Replays the inbound frames of a recording into a peer. Time is driven by a
+/// mock time service (which should also be given to the peer), so that timeouts
+/// are reproduced deterministically.
+pub struct TrafficReplayer {
+    inbound_records: VecDeque<TrafficRecord>,
+    mock_time: MockTimeService,
+    last_elapsed_micros: u64,
+    read_half: ReadHalf<MemorySocket>,
+    write_half: WriteHalf<MemorySocket>,
+    max_frame_size: usize,
+    write_chunk_size: Option<usize>,
+}
+
+impl TrafficReplayer {
+    /// Creates a replayer that writes to the given socket (i.e., the
+    /// remote end of the peer's socket).
+    pub fn new(
+        records: Vec<TrafficRecord>,
+        mock_time: MockTimeService,
+        socket: MemorySocket,
+        max_frame_size: usize,
+    ) -> Self {
+        let inbound_records = records
+            .into_iter()
+            .filter(|record| record.direction == TrafficDirection::Inbound)
+            .collect();
+        let (read_half, write_half) = socket.split();
+        Self {
+            inbound_records,
+            mock_time,
+            last_elapsed_micros: 0,
+            read_half,
+            write_half,
+            max_frame_size,
+            write_chunk_size: None,
+        }
+    }
+
+    /// Writes each frame in chunks of the given size (e.g., to
+    /// reproduce partial reads on the peer's socket).
+    pub fn with_write_chunk_size(mut self, write_chunk_size: usize) -> Self {
+        self.write_chunk_size = Some(write_chunk_size.max(1));
+        self
+    }
+
+    /// Returns the number of inbound frames that have not yet been replayed
+    pub fn num_remaining(&self) -> usize {
+        self.inbound_records.len()
+    }
+
+    /// Replays the next inbound frame: time is first advanced by the recorded
+    /// gap, and then the frame is written. Returns the replayed record (or
+    /// `None` if there are no frames left).
+    ///
+    /// Note: the peer processes frames asynchronously, so tests that depend on
+    /// the peer handling a frame before time advances should wait for the frame
+    /// (e.g., on the upstream handlers) before replaying the next one.
+    pub async fn replay_next(&mut self) -> io::Result<Option<TrafficRecord>> {
+        let record = match self.inbound_records.pop_front() {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+
+        // Advance time to when the frame was originally received
+        let gap_micros = record
+            .elapsed_micros
+            .saturating_sub(self.last_elapsed_micros);
+        if gap_micros > 0 {
+            self.mock_time
+                .advance_async(Duration::from_micros(gap_micros))
+                .await;
+        }
+        self.last_elapsed_micros = self.last_elapsed_micros.max(record.elapsed_micros);
+
+        // Write the length prefixed frame
+        let mut bytes = BytesMut::with_capacity(4 + record.frame.len());
+        bytes.put_u32(record.frame.len() as u32);
+        bytes.extend_from_slice(&record.frame);
+        let chunk_size = self.write_chunk_size.unwrap_or(bytes.len()).max(1);
+        for chunk in bytes.chunks(chunk_size) {
+            self.write_half.write_all(chunk).await?;
+            self.write_half.flush().await?;
+        }
+
+        Ok(Some(record))
+    }
+
+    /// Replays all remaining inbound frames
+    pub async fn replay_all(&mut self) -> io::Result<()> {
+        while self.replay_next().await?.is_some() {}
+        Ok(())
+    }
+
+    /// Closes the socket (causing the peer to disconnect) and returns
+    /// all frames sent by the peer.
+    pub async fn finish(mut self) -> io::Result<Vec<Bytes>> {
+        self.write_half.close().await?;
+        FramedRead::new(
+            self.read_half.compat(),
+            network_message_frame_codec(self.max_frame_size),
+        )
+        .map_ok(BytesMut::freeze)
+        .try_collect()
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::MAX_FRAME_SIZE,
+        protocols::wire::{
+            handshake::v1::{MessagingProtocolVersion, ProtocolIdSet},
+            messaging::v1::{DirectSendMsg, MultiplexMessage, NetworkMessage, RpcRequest},
+        },
+        transport::ConnectionId,
+    };
+    use aptos_config::config::PeerRole;
+    use aptos_netcore::transport::ConnectionOrigin;
+    use aptos_types::network_address::NetworkAddress;
+    use futures::{future, StreamExt};
+    use std::str::FromStr;
+    use tokio::runtime::Runtime;
+
+    static PROTOCOL: ProtocolId = ProtocolId::MempoolDirectSend;
+
+    fn connection_metadata() -> ConnectionMetadata {
+        ConnectionMetadata::new(
+            PeerId::random(),
+            ConnectionId::default(),
+            NetworkAddress::from_str("/ip4/127.0.0.1/tcp/8081").unwrap(),
+            ConnectionOrigin::Inbound,
+            MessagingProtocolVersion::V1,
+            ProtocolIdSet::empty(),
+            PeerRole::Unknown,
+        )
+    }
+
+    fn direct_send_message(raw_msg: &str) -> MultiplexMessage {
+        MultiplexMessage::Message(NetworkMessage::DirectSendMsg(DirectSendMsg {
+            protocol_id: PROTOCOL,
+            priority: 0,
+            raw_msg: Vec::from(raw_msg),
+        }))
+    }
+
+    fn test_upstream_handlers() -> (
+        Arc<HashMap<ProtocolId, aptos_channel::Sender<(PeerId, ProtocolId), ReceivedMessage>>>,
+        aptos_channel::Receiver<(PeerId, ProtocolId), ReceivedMessage>,
+    ) {
+        let mut upstream_handlers = HashMap::new();
+        let (sender, receiver) = aptos_channel::new(QueueStyle::FIFO, 100, None);
+        upstream_handlers.insert(PROTOCOL, sender);
+        (Arc::new(upstream_handlers), receiver)
+    }
+
+    #[test]
+    fn test_replay_direct_sends() {
+        ::aptos_logger::Logger::init_for_testing();
+        let rt = Runtime::new().unwrap();
+        let mock_time = MockTimeService::new();
+        let (upstream_handlers, mut receiver) = test_upstream_handlers();
+        let (peer, _peer_reqs_tx, remote_socket, _connection_notifs_rx) = build_replay_peer(
+            rt.handle().clone(),
+            mock_time.clone().into(),
+            connection_metadata(),
+            upstream_handlers,
+            MAX_FRAME_SIZE,
+        );
+
+        // Create a recording with several direct sends
+        let records = (0..3)
+            .map(|i| TrafficRecord {
+                direction: TrafficDirection::Inbound,
+                elapsed_micros: i * 1_000,
+                frame: bcs::to_bytes(&direct_send_message(&format!("message {}", i))).unwrap(),
+            })
+            .collect();
+
+        // Replay the recording (with fragmented writes) and verify the messages are received
+        let mut replayer = TrafficReplayer::new(records, mock_time, remote_socket, MAX_FRAME_SIZE)
+            .with_write_chunk_size(3);
+        let test = async move {
+            replayer.replay_all().await.unwrap();
+            assert_eq!(replayer.num_remaining(), 0);
+            for i in 0..3 {
+                let received = receiver.next().await.unwrap();
+                assert_eq!(
+                    MultiplexMessage::Message(received.message),
+                    direct_send_message(&format!("message {}", i))
+                );
+            }
+
+            // The peer shouldn't have sent any frames
+            assert!(replayer.finish().await.unwrap().is_empty());
+        };
+        rt.block_on(future::join(peer.start(), test));
+    }
+
+    #[test]
+    fn test_replay_rpc_timeout() {
+        ::aptos_logger::Logger::init_for_testing();
+        let rt = Runtime::new().unwrap();
+        let mock_time = MockTimeService::new();
+        let (upstream_handlers, mut receiver) = test_upstream_handlers();
+        let (peer, _peer_reqs_tx, remote_socket, _connection_notifs_rx) = build_replay_peer(
+            rt.handle().clone(),
+            mock_time.clone().into(),
+            connection_metadata(),
+            upstream_handlers,
+            MAX_FRAME_SIZE,
+        );
+
+        // Create a recording with an rpc request, followed by a frame after the rpc timeout
+        let rpc_request = MultiplexMessage::Message(NetworkMessage::RpcRequest(RpcRequest {
+            request_id: 123,
+            protocol_id: PROTOCOL,
+            priority: 0,
+            raw_request: Vec::from("hello world"),
+        }));
+        let records = vec![
+            TrafficRecord {
+                direction: TrafficDirection::Inbound,
+                elapsed_micros: 0,
+                frame: bcs::to_bytes(&rpc_request).unwrap(),
+            },
+            TrafficRecord {
+                direction: TrafficDirection::Inbound,
+                elapsed_micros: INBOUND_RPC_TIMEOUT_MS * 1_000,
+                frame: bcs::to_bytes(&direct_send_message("late")).unwrap(),
+            },
+        ];
+
+        // Replay the recording and verify the rpc times out before the late frame arrives
+        let mut replayer = TrafficReplayer::new(records, mock_time, remote_socket, MAX_FRAME_SIZE);
+        let test = async move {
+            replayer.replay_next().await.unwrap().unwrap();
+            let received = receiver.next().await.unwrap();
+            let mut res_tx = Arc::into_inner(received.rpc_replier.unwrap()).unwrap();
+            assert!(!res_tx.is_canceled());
+
+            // Replaying the late frame advances time past the rpc timeout
+            replayer.replay_next().await.unwrap().unwrap();
+            res_tx.cancellation().await;
+            let received = receiver.next().await.unwrap();
+            assert_eq!(
+                MultiplexMessage::Message(received.message),
+                direct_send_message("late")
+            );
+
+            // The peer shouldn't have sent any frames
+            assert!(replayer.finish().await.unwrap().is_empty());
+        };
+        rt.block_on(future::join(peer.start(), test));
+    }
+}
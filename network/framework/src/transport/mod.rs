@@ -9,6 +9,7 @@ use crate::{
         identity::exchange_handshake,
         wire::handshake::v1::{HandshakeMsg, MessagingProtocolVersion, ProtocolIdSet},
     },
+    transport::recording::TrafficRecording,
 };
 use aptos_config::{
     config::{PeerRole, HANDSHAKE_VERSION},
@@ -35,6 +36,7 @@ use futures::{
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, convert::TryFrom, fmt, io, pin::Pin, sync::Arc, time::Duration};
 
+pub mod recording;
 #[cfg(test)]
 mod test;
 
@@ -425,6 +427,7 @@ pub struct AptosNetTransport<TTransport> {
     time_service: TimeService,
     identity_pubkey: x25519::PublicKey,
     enable_proxy_protocol: bool,
+    traffic_recording: Option<TrafficRecording>,
 }
 
 impl<TTransport> AptosNetTransport<TTransport>
@@ -466,9 +469,16 @@ where
             time_service,
             identity_pubkey,
             enable_proxy_protocol,
+            traffic_recording: None,
         }
     }
 
+    /// Records the traffic of all upgraded connections (if `traffic_recording` is set)
+    pub fn with_traffic_recording(mut self, traffic_recording: Option<TrafficRecording>) -> Self {
+        self.traffic_recording = traffic_recording;
+        self
+    }
+
     fn parse_dial_addr(
         addr: &NetworkAddress,
     ) -> io::Result<(NetworkAddress, x25519::PublicKey, u8)> {
@@ -566,7 +576,7 @@ where
         // outbound dial upgrade task
         let upgrade_fut = upgrade_outbound(self.ctxt.clone(), fut_socket, addr, peer_id, pubkey);
         let upgrade_fut = timeout_io(self.time_service.clone(), TRANSPORT_TIMEOUT, upgrade_fut);
-        Ok(upgrade_fut)
+        Ok(record_upgraded(self.traffic_recording.clone(), upgrade_fut))
     }
 
     /// Listen on address `addr`. If the `addr` is not supported or formatted correctly,
@@ -616,6 +626,7 @@ where
         let ctxt = self.ctxt.clone();
         let time_service = self.time_service.clone();
         let enable_proxy_protocol = self.enable_proxy_protocol;
+        let traffic_recording = self.traffic_recording.clone();
         // stream of inbound upgrade tasks
         let inbounds = listener.map_ok(move |(fut_socket, addr)| {
             // inbound upgrade task
@@ -626,6 +637,7 @@ where
                 enable_proxy_protocol,
             );
             let fut_upgrade = timeout_io(time_service.clone(), TRANSPORT_TIMEOUT, fut_upgrade);
+            let fut_upgrade = record_upgraded(traffic_recording.clone(), fut_upgrade);
             (fut_upgrade, addr)
         });
 
@@ -633,6 +645,21 @@ where
     }
 }
 
+/// Starts recording the connection once it has been upgraded (if traffic recording is enabled)
+fn record_upgraded<T: TSocket>(
+    traffic_recording: Option<TrafficRecording>,
+    fut_upgrade: impl Future<Output = io::Result<Connection<NoiseStream<T>>>>,
+) -> impl Future<Output = io::Result<Connection<NoiseStream<T>>>> {
+    fut_upgrade.map(move |result| {
+        result.map(|mut connection| {
+            if let Some(traffic_recording) = &traffic_recording {
+                traffic_recording.record(&mut connection);
+            }
+            connection
+        })
+    })
+}
+
 // If using `AptosNetTransport` as a `Transport` trait, then all upgrade futures
 // and listening streams must be boxed, since `upgrade_inbound` and `upgrade_outbound`
 // are async fns (and therefore unnamed types).
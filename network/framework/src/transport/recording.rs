@@ -0,0 +1,371 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//!
+//! This module allows the traffic of upgraded connections to be recorded (e.g.,
+//! to debug a misbehaving peer, or to replay the traffic in tests):
+//! - TrafficRecording: attaches a recorder to each connection upgraded by the
+//!   transport. Each connection is recorded to a new file in the recording directory.
+//! - TrafficRecorder: records the (decrypted) bytes read from and written to a
+//!   `NoiseStream`. Framing and file writes are done by a dedicated writer thread,
+//!   so the stream only copies the bytes into a bounded channel.
+//!
+
+use crate::{
+    noise::stream::NoiseStream,
+    protocols::wire::messaging::v1::{network_message_frame_codec, MultiplexMessage},
+    transport::{Connection, ConnectionMetadata},
+};
+use aptos_logger::prelude::*;
+use aptos_short_hex_str::AsShortHexStr;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use bytes::BytesMut;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, SyncSender, TrySendError},
+    thread,
+    time::Instant,
+};
+use tokio_util::codec::{Decoder, LengthDelimitedCodec};
+
+/// The file extension used for traffic recordings
+pub const RECORDING_FILE_EXTENSION: &str = "netrec";
+
+/// The maximum number of reads and writes buffered for the writer thread.
+/// If the writer falls behind, the recording of that direction stops.
+const RECORDING_CHANNEL_SIZE: usize = 1024;
+
+/// The direction of a recorded frame (relative to the local peer)
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum TrafficDirection {
+    Inbound,
+    Outbound,
+}
+
+/// A single frame captured on a connection
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TrafficRecord {
+    pub direction: TrafficDirection,
+    /// The time since the recording started
+    pub elapsed_micros: u64,
+    #[serde(with = "serde_bytes")]
+    pub frame: Vec<u8>,
+}
+
+impl TrafficRecord {
+    /// Deserializes the frame into a multiplex message. This may fail,
+    /// e.g., if the recording captured a malformed frame.
+    pub fn message(&self) -> Result<MultiplexMessage, bcs::Error> {
+        bcs::from_bytes(&self.frame)
+    }
+}
+
+/// Returns the recording file path for the given connection
+pub fn recording_path(dir: &Path, metadata: &ConnectionMetadata) -> PathBuf {
+    dir.join(format!(
+        "{}-{}.{}",
+        metadata.remote_peer_id.short_str(),
+        metadata.connection_id.get_inner(),
+        RECORDING_FILE_EXTENSION
+    ))
+}
+
+/// Reads all traffic records from the given recording file
+pub fn read_recording(path: &Path) -> io::Result<Vec<TrafficRecord>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut records = vec![];
+    loop {
+        // Each record is prefixed by its length (as a big-endian u32)
+        let mut length_bytes = [0u8; 4];
+        match reader.read_exact(&mut length_bytes) {
+            Ok(()) => (),
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(records),
+            Err(error) => return Err(error),
+        }
+        let mut record_bytes = vec![0u8; u32::from_be_bytes(length_bytes) as usize];
+        reader.read_exact(&mut record_bytes)?;
+        let record = bcs::from_bytes(&record_bytes)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        records.push(record);
+    }
+}
+
+//
+// TrafficRecording
+// ================
+//
+
+/// Records the traffic of every connection upgraded by a transport
+#[derive(Clone, Debug)]
+pub struct TrafficRecording {
+    dir: PathBuf,
+    time_service: TimeService,
+    max_frame_size: usize,
+}
+
+impl TrafficRecording {
+    pub fn new(dir: PathBuf, time_service: TimeService, max_frame_size: usize) -> Self {
+        Self {
+            dir,
+            time_service,
+            max_frame_size,
+        }
+    }
+
+    /// Starts recording the given connection. Recording is best effort,
+    /// so failures are logged and the connection is left unrecorded.
+    pub fn record<TSocket>(&self, connection: &mut Connection<NoiseStream<TSocket>>) {
+        let path = recording_path(&self.dir, &connection.metadata);
+        let recorder = File::create(&path).and_then(|file| {
+            TrafficRecorder::new(file, self.time_service.clone(), self.max_frame_size)
+        });
+        match recorder {
+            Ok(recorder) => connection.socket.record_traffic(recorder),
+            Err(error) => warn!(
+                "Failed to start recording connection traffic to {}: {}",
+                path.display(),
+                error
+            ),
+        }
+    }
+}
+
+//
+// TrafficRecorder
+// ===============
+//
+
+/// The bytes read or written by a single poll, along with the time since the
+/// recording started
+type RecordedBytes = (TrafficDirection, u64, Vec<u8>);
+
+/// Records the bytes read from and written to a stream. The bytes are sent to
+/// a writer thread, which splits them into frames and appends the frames to the
+/// recording file.
+pub struct TrafficRecorder {
+    sender: SyncSender<RecordedBytes>,
+    time_service: TimeService,
+    start_time: Instant,
+    inbound_stopped: bool,
+    outbound_stopped: bool,
+}
+
+impl fmt::Debug for TrafficRecorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrafficRecorder")
+            .field("inbound_stopped", &self.inbound_stopped)
+            .field("outbound_stopped", &self.outbound_stopped)
+            .finish()
+    }
+}
+
+impl TrafficRecorder {
+    /// Creates a recorder that writes to the given file. The writer thread
+    /// exits (after flushing the file) once the recorder is dropped.
+    pub fn new(file: File, time_service: TimeService, max_frame_size: usize) -> io::Result<Self> {
+        let (sender, receiver) = mpsc::sync_channel(RECORDING_CHANNEL_SIZE);
+        thread::Builder::new()
+            .name("traffic-recorder".into())
+            .spawn(move || write_recording(file, receiver, max_frame_size))?;
+        Ok(Self {
+            sender,
+            start_time: time_service.now(),
+            time_service,
+            inbound_stopped: false,
+            outbound_stopped: false,
+        })
+    }
+
+    /// Records the given bytes. This never blocks: if the writer thread has
+    /// fallen behind, the recording of the given direction is stopped (as
+    /// dropping bytes would corrupt the framing of all later records).
+    pub fn record(&mut self, direction: TrafficDirection, bytes: &[u8]) {
+        let stopped = match direction {
+            TrafficDirection::Inbound => &mut self.inbound_stopped,
+            TrafficDirection::Outbound => &mut self.outbound_stopped,
+        };
+        if *stopped || bytes.is_empty() {
+            return;
+        }
+
+        let elapsed_micros = self
+            .time_service
+            .now()
+            .duration_since(self.start_time)
+            .as_micros() as u64;
+        match self
+            .sender
+            .try_send((direction, elapsed_micros, bytes.to_vec()))
+        {
+            Ok(()) => (),
+            Err(TrySendError::Full(_)) => {
+                warn!(
+                    "The traffic recorder has fallen behind! Stopping the {:?} recording.",
+                    direction
+                );
+                *stopped = true;
+            },
+            Err(TrySendError::Disconnected(_)) => *stopped = true,
+        }
+    }
+}
+
+/// Splits the bytes of a single direction into frames
+struct FrameDecoder {
+    codec: LengthDelimitedCodec,
+    buffer: BytesMut,
+    failed: bool,
+}
+
+impl FrameDecoder {
+    fn new(max_frame_size: usize) -> Self {
+        Self {
+            codec: network_message_frame_codec(max_frame_size),
+            buffer: BytesMut::new(),
+            failed: false,
+        }
+    }
+
+    /// Appends the given bytes, and returns all frames that are now complete
+    fn decode(&mut self, bytes: &[u8]) -> Vec<BytesMut> {
+        let mut frames = vec![];
+        if self.failed {
+            return frames;
+        }
+
+        self.buffer.extend_from_slice(bytes);
+        loop {
+            match self.codec.decode(&mut self.buffer) {
+                Ok(Some(frame)) => frames.push(frame),
+                Ok(None) => return frames,
+                Err(error) => {
+                    // The stream can no longer be framed, so stop recording it
+                    warn!("Unable to decode frame for recording: {}", error);
+                    self.failed = true;
+                    return frames;
+                },
+            }
+        }
+    }
+}
+
+/// The writer thread of a recorder: frames the received bytes and appends
+/// them to the file. The file is flushed whenever the channel is drained.
+fn write_recording(file: File, receiver: Receiver<RecordedBytes>, max_frame_size: usize) {
+    let mut writer = BufWriter::new(file);
+    let mut inbound_decoder = FrameDecoder::new(max_frame_size);
+    let mut outbound_decoder = FrameDecoder::new(max_frame_size);
+    while let Ok(first) = receiver.recv() {
+        for (direction, elapsed_micros, bytes) in std::iter::once(first).chain(receiver.try_iter())
+        {
+            let frames = match direction {
+                TrafficDirection::Inbound => inbound_decoder.decode(&bytes),
+                TrafficDirection::Outbound => outbound_decoder.decode(&bytes),
+            };
+            for frame in frames {
+                let record = TrafficRecord {
+                    direction,
+                    elapsed_micros,
+                    frame: frame.to_vec(),
+                };
+                if let Err(error) = write_record(&mut writer, &record) {
+                    warn!(
+                        "Failed to write traffic record! Stopping the recording: {}",
+                        error
+                    );
+                    return;
+                }
+            }
+        }
+        if let Err(error) = writer.flush() {
+            warn!(
+                "Failed to flush traffic recording! Stopping the recording: {}",
+                error
+            );
+            return;
+        }
+    }
+}
+
+fn write_record(writer: &mut impl Write, record: &TrafficRecord) -> io::Result<()> {
+    let record_bytes =
+        bcs::to_bytes(record).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    writer.write_all(&(record_bytes.len() as u32).to_be_bytes())?;
+    writer.write_all(&record_bytes)
+}
+
+/// Reads the recording once the writer thread has written the
+/// expected number of records.
+#[cfg(test)]
+pub(crate) fn wait_for_recording(path: &Path, num_records: usize) -> Vec<TrafficRecord> {
+    for _ in 0..100 {
+        if let Ok(records) = read_recording(path) {
+            if records.len() >= num_records {
+                return records;
+            }
+        }
+        thread::sleep(std::time::Duration::from_millis(50));
+    }
+    panic!("Timed out waiting for {} records", num_records);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::MAX_FRAME_SIZE,
+        protocols::wire::messaging::v1::{DirectSendMsg, NetworkMessage},
+        ProtocolId,
+    };
+    use aptos_temppath::TempPath;
+    use std::time::Duration;
+
+    fn direct_send_frame(raw_msg: &str) -> Vec<u8> {
+        let message = MultiplexMessage::Message(NetworkMessage::DirectSendMsg(DirectSendMsg {
+            protocol_id: ProtocolId::MempoolDirectSend,
+            priority: 0,
+            raw_msg: Vec::from(raw_msg),
+        }));
+        let frame = bcs::to_bytes(&message).unwrap();
+        let mut bytes = (frame.len() as u32).to_be_bytes().to_vec();
+        bytes.extend(frame);
+        bytes
+    }
+
+    #[test]
+    fn test_record_fragmented_frames() {
+        let path = TempPath::new();
+        let time_service = TimeService::mock();
+        let mut recorder = TrafficRecorder::new(
+            File::create(path.path()).unwrap(),
+            time_service.clone(),
+            MAX_FRAME_SIZE,
+        )
+        .unwrap();
+
+        // Record an inbound frame (in several reads) and an outbound frame
+        let inbound = direct_send_frame("ping");
+        let (first, second) = inbound.split_at(3);
+        recorder.record(TrafficDirection::Inbound, first);
+        time_service
+            .into_mock()
+            .advance(Duration::from_micros(1_000));
+        recorder.record(TrafficDirection::Inbound, second);
+        recorder.record(TrafficDirection::Outbound, &direct_send_frame("pong"));
+        drop(recorder);
+
+        // Verify the recording contains both frames (timestamped on completion)
+        let records = wait_for_recording(path.path(), 2);
+        let directions: Vec<_> = records.iter().map(|record| record.direction).collect();
+        assert_eq!(directions, vec![
+            TrafficDirection::Inbound,
+            TrafficDirection::Outbound
+        ]);
+        assert_eq!(records[0].elapsed_micros, 1_000);
+        assert_eq!(records[0].frame, inbound[4..]);
+    }
+}
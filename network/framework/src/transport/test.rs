@@ -4,9 +4,19 @@
 
 use crate::{
     application::storage::PeersAndMetadata,
-    protocols::wire::handshake::v1::{MessagingProtocolVersion, ProtocolId, ProtocolIdSet},
+    constants::MAX_FRAME_SIZE,
+    protocols::wire::{
+        handshake::v1::{MessagingProtocolVersion, ProtocolId, ProtocolIdSet},
+        messaging::v1::{
+            DirectSendMsg, MultiplexMessage, MultiplexMessageSink, MultiplexMessageStream,
+            NetworkMessage,
+        },
+    },
     testutils,
-    transport::*,
+    transport::{
+        recording::{recording_path, wait_for_recording, TrafficDirection, TrafficRecording},
+        *,
+    },
 };
 use aptos_config::config::{Peer, PeerRole, PeerSet, HANDSHAKE_VERSION};
 use aptos_crypto::{test_utils::TEST_SEED, traits::Uniform, x25519, x25519::PrivateKey};
@@ -14,6 +24,7 @@ use aptos_netcore::{
     framing::{read_u16frame, write_u16frame},
     transport::{memory, ConnectionOrigin, Transport},
 };
+use aptos_temppath::TempPath;
 use aptos_time_service::MockTimeService;
 use aptos_types::{
     account_address::AccountAddress,
@@ -22,7 +33,7 @@ use aptos_types::{
     PeerId,
 };
 use bytes::{Bytes, BytesMut};
-use futures::{future, io::AsyncWriteExt, stream::StreamExt};
+use futures::{future, io::AsyncWriteExt, sink::SinkExt, stream::StreamExt};
 use rand::{rngs::StdRng, SeedableRng};
 use std::{io, iter::FromIterator, sync::Arc};
 use tokio::runtime::Runtime;
@@ -554,6 +565,63 @@ fn test_memory_transport_maybe_mutual() {
     );
 }
 
+#[test]
+fn test_memory_transport_traffic_recording() {
+    let (
+        rt,
+        mock_time,
+        (listener_peer_id, listener_transport),
+        (_dialer_peer_id, dialer_transport),
+        _,
+        _supported_protocols,
+    ) = setup(memory::MemoryTransport, Auth::Mutual);
+
+    // Record the traffic of all connections accepted by the listener
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let listener_transport = listener_transport.with_traffic_recording(Some(
+        TrafficRecording::new(dir.path().to_path_buf(), mock_time.into(), MAX_FRAME_SIZE),
+    ));
+
+    let _guard = rt.enter();
+    let (mut inbounds, listener_addr) = listener_transport
+        .listen_on("/memory/0".parse().unwrap())
+        .unwrap();
+    let message = MultiplexMessage::Message(NetworkMessage::DirectSendMsg(DirectSendMsg {
+        protocol_id: ProtocolId::DiscoveryDirectSend,
+        priority: 0,
+        raw_msg: Vec::from("hello"),
+    }));
+
+    // Receive a single message on the recorded connection
+    let expected_message = message.clone();
+    let sent_message = message.clone();
+    let listener_task = async move {
+        let (inbound, _dialer_addr) = inbounds.next().await.unwrap().unwrap();
+        let mut conn = inbound.await.unwrap();
+        let mut stream = MultiplexMessageStream::new(&mut conn.socket, MAX_FRAME_SIZE);
+        assert_eq!(stream.next().await.unwrap().unwrap(), expected_message);
+        conn.metadata
+    };
+    let dialer_task = async move {
+        let mut conn = dialer_transport
+            .dial(listener_peer_id, listener_addr)
+            .unwrap()
+            .await
+            .unwrap();
+        let mut sink = MultiplexMessageSink::new(&mut conn.socket, MAX_FRAME_SIZE);
+        sink.send(&sent_message).await.unwrap();
+        conn.socket.close().await.unwrap();
+    };
+    let (metadata, ()) = rt.block_on(future::join(listener_task, dialer_task));
+
+    // Verify the message was recorded as an inbound frame
+    let records = wait_for_recording(&recording_path(dir.path(), &metadata), 1);
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].direction, TrafficDirection::Inbound);
+    assert_eq!(records[0].message().unwrap(), message);
+}
+
 /////////////////////////////////////
 // AptosNetTransport<TcpTransport> //
 /////////////////////////////////////
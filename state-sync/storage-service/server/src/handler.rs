@@ -22,8 +22,9 @@ use aptos_network::protocols::wire::handshake::v1::ProtocolId;
 use aptos_storage_service_types::{
     requests::{
         DataRequest, EpochEndingLedgerInfoRequest, GetTransactionDataWithProofRequest,
-        StateValuesWithProofRequest, StorageServiceRequest, TransactionOutputsWithProofRequest,
-        TransactionsOrOutputsWithProofRequest, TransactionsWithProofRequest,
+        StateValuesWithBatchProofRequest, StateValuesWithProofRequest, StorageServiceRequest,
+        TransactionOutputsWithProofRequest, TransactionsOrOutputsWithProofRequest,
+        TransactionsWithProofRequest,
     },
     responses::{
         DataResponse, ServerProtocolVersion, StorageServerSummary, StorageServiceResponse,
@@ -426,6 +427,9 @@ impl<T: StorageReaderInterface> Handler<T> {
             DataRequest::GetTransactionDataWithProof(request) => {
                 self.get_transaction_data_with_proof(request)
             },
+            DataRequest::GetStateValuesWithBatchProof(request) => {
+                self.get_state_values_with_batch_proof(request)
+            },
             _ => Err(Error::UnexpectedErrorEncountered(format!(
                 "Received an unexpected request: {:?}",
                 request
@@ -475,6 +479,19 @@ impl<T: StorageReaderInterface> Handler<T> {
         ))
     }
 
+    fn get_state_values_with_batch_proof(
+        &self,
+        request: &StateValuesWithBatchProofRequest,
+    ) -> aptos_storage_service_types::Result<DataResponse, Error> {
+        let state_values_with_batch_proof = self
+            .storage
+            .get_state_values_with_batch_proof(request.version, &request.state_keys)?;
+
+        Ok(DataResponse::StateValuesWithBatchProof(
+            state_values_with_batch_proof,
+        ))
+    }
+
     fn get_epoch_ending_ledger_infos(
         &self,
         request: &EpochEndingLedgerInfoRequest,
@@ -22,7 +22,7 @@ use aptos_types::{
     },
     state_store::{
         state_key::StateKey,
-        state_value::{StateValue, StateValueChunkWithProof},
+        state_value::{StateValue, StateValueChunkWithProof, StateValuesWithBatchProof},
     },
     transaction::{
         PersistedAuxiliaryInfo, Transaction, TransactionAuxiliaryData, TransactionInfo,
@@ -113,6 +113,16 @@ pub trait StorageReaderInterface: Clone + Send + 'static {
         start_index: u64,
         end_index: u64,
     ) -> aptos_storage_service_types::Result<StateValueChunkWithProof, Error>;
+
+    /// Returns the state values of the given `state_keys` at the specified
+    /// version, together with a single proof for all of them. Unlike the
+    /// other requests, the response is never truncated: if it doesn't fit
+    /// into a single network frame, an error is returned.
+    fn get_state_values_with_batch_proof(
+        &self,
+        version: u64,
+        state_keys: &[StateKey],
+    ) -> aptos_storage_service_types::Result<StateValuesWithBatchProof, Error>;
 }
 
 /// The underlying implementation of the StorageReaderInterface, used by the
@@ -1213,6 +1223,42 @@ impl StorageReaderInterface for StorageReader {
             self.config.enable_size_and_time_aware_chunking,
         )
     }
+
+    fn get_state_values_with_batch_proof(
+        &self,
+        version: u64,
+        state_keys: &[StateKey],
+    ) -> aptos_storage_service_types::Result<StateValuesWithBatchProof, Error> {
+        // Verify the number of requested keys
+        let num_state_keys = state_keys.len() as u64;
+        if num_state_keys == 0 || num_state_keys > self.config.max_state_chunk_size {
+            return Err(Error::InvalidRequest(format!(
+                "The number of requested state keys is invalid: {:?} (max: {:?})",
+                num_state_keys, self.config.max_state_chunk_size
+            )));
+        }
+
+        // Fetch the state values and the batch proof
+        let state_values_with_batch_proof = self
+            .storage
+            .get_state_values_with_batch_proof(version, state_keys)?;
+
+        // Verify the response fits into a single network frame
+        let (overflow_frame, num_bytes) = check_overflow_network_frame(
+            &state_values_with_batch_proof,
+            self.config.max_network_chunk_bytes,
+        )?;
+        if overflow_frame {
+            return Err(Error::UnexpectedErrorEncountered(format!(
+                "Unable to serve the get_state_values_with_batch_proof request! Version: {:?}, \
+                number of keys: {:?}. The data cannot fit into a single network frame! \
+                Num bytes: {:?}, limit: {:?}.",
+                version, num_state_keys, num_bytes, self.config.max_network_chunk_bytes
+            )));
+        }
+
+        Ok(state_values_with_batch_proof)
+    }
 }
 
 // A simple macro that wraps each storage read call with a timer
@@ -1294,6 +1340,12 @@ impl DbReader for TimedStorageReader {
             chunk_size: usize,
         ) -> StorageResult<StateValueChunkWithProof>;
 
+        fn get_state_values_with_batch_proof(
+            &self,
+            version: Version,
+            state_keys: &[StateKey],
+        ) -> StorageResult<StateValuesWithBatchProof>;
+
         fn get_epoch_ending_ledger_info_iterator(
             &self,
             start_epoch: u64,
@@ -42,7 +42,7 @@ use aptos_types::{
     state_proof::StateProof,
     state_store::{
         state_key::StateKey,
-        state_value::{StateValue, StateValueChunkWithProof},
+        state_value::{StateValue, StateValueChunkWithProof, StateValuesWithBatchProof},
     },
     transaction::{
         AccountOrderedTransactionsWithProof, PersistedAuxiliaryInfo, Transaction, TransactionInfo,
@@ -357,6 +357,12 @@ mock! {
             chunk_size: usize,
         ) -> aptos_storage_interface::Result<StateValueChunkWithProof>;
 
+        fn get_state_values_with_batch_proof(
+            &self,
+            version: Version,
+            state_keys: &[StateKey],
+        ) -> aptos_storage_interface::Result<StateValuesWithBatchProof>;
+
         fn get_epoch_snapshot_prune_window(&self) -> aptos_storage_interface::Result<usize>;
 
         fn is_state_merkle_pruner_enabled(&self) -> aptos_storage_interface::Result<bool>;
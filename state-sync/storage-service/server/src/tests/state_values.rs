@@ -9,15 +9,15 @@ use crate::tests::{
 use aptos_config::config::StorageServiceConfig;
 use aptos_crypto::hash::HashValue;
 use aptos_storage_service_types::{
-    requests::{DataRequest, StateValuesWithProofRequest},
+    requests::{DataRequest, StateValuesWithBatchProofRequest, StateValuesWithProofRequest},
     responses::{DataResponse, StorageServiceResponse},
     StorageServiceError,
 };
 use aptos_types::{
-    proof::definition::SparseMerkleRangeProof,
+    proof::definition::{SparseMerkleBatchProof, SparseMerkleRangeProof},
     state_store::{
         state_key::StateKey,
-        state_value::{StateValue, StateValueChunkWithProof},
+        state_value::{StateValue, StateValueChunkWithProof, StateValuesWithBatchProof},
     },
     transaction::Version,
 };
@@ -202,6 +202,93 @@ async fn test_get_states_with_proof_not_serviceable() {
     }
 }
 
+#[tokio::test]
+async fn test_get_states_with_batch_proof() {
+    // Create test data
+    let version = 101;
+    let state_keys: Vec<_> = (0..10u8).map(|i| StateKey::raw(&[i])).collect();
+    let state_values_with_batch_proof = StateValuesWithBatchProof {
+        state_values: state_keys
+            .iter()
+            .map(|state_key| {
+                (
+                    state_key.clone(),
+                    Some(StateValue::new_legacy(vec![1].into())),
+                )
+            })
+            .collect(),
+        proof: SparseMerkleBatchProof::new(vec![], vec![]),
+        root_hash: HashValue::random(),
+    };
+
+    // Create the mock db reader
+    let mut db_reader = mock::create_mock_db_reader();
+    let expected_state_keys = state_keys.clone();
+    let response_data = state_values_with_batch_proof.clone();
+    db_reader
+        .expect_get_state_values_with_batch_proof()
+        .times(1)
+        .withf(move |given_version, given_state_keys| {
+            *given_version == version && given_state_keys.to_vec() == expected_state_keys
+        })
+        .returning(move |_, _| Ok(response_data.clone()));
+
+    // Create the storage client and server
+    let (mut mock_client, mut service, _, _, _) = MockClient::new(Some(db_reader), None);
+    utils::update_storage_server_summary(&mut service, version, 10);
+    tokio::spawn(service.start());
+
+    // Process a request to fetch the states with a batch proof
+    let response = get_state_values_with_batch_proof(&mut mock_client, version, state_keys)
+        .await
+        .unwrap();
+
+    // Verify the response is correct
+    assert_eq!(
+        response.get_data_response().unwrap(),
+        DataResponse::StateValuesWithBatchProof(state_values_with_batch_proof)
+    );
+}
+
+#[tokio::test]
+async fn test_get_states_with_batch_proof_invalid() {
+    // Create the storage client and server
+    let version = 101;
+    let (mut mock_client, mut service, _, _, _) = MockClient::new(None, None);
+    utils::update_storage_server_summary(&mut service, version, 10);
+    tokio::spawn(service.start());
+
+    // Test an empty request and a request with too many keys
+    let max_state_chunk_size = StorageServiceConfig::default().max_state_chunk_size;
+    for num_keys in [0, max_state_chunk_size + 1] {
+        let state_keys = (0..num_keys)
+            .map(|i| StateKey::raw(&i.to_le_bytes()))
+            .collect();
+        let response = get_state_values_with_batch_proof(&mut mock_client, version, state_keys)
+            .await
+            .unwrap_err();
+        assert_matches!(response, StorageServiceError::InvalidRequest(_));
+    }
+}
+
+#[tokio::test]
+async fn test_get_states_with_batch_proof_not_serviceable() {
+    // Create the storage client and server (that cannot service the request)
+    let version = 101;
+    let (mut mock_client, mut service, _, _, _) = MockClient::new(None, None);
+    utils::update_storage_server_summary(&mut service, version - 1, 10);
+    tokio::spawn(service.start());
+
+    // Process a request to fetch the states with a batch proof
+    let response =
+        get_state_values_with_batch_proof(&mut mock_client, version, vec![StateKey::raw(&[])])
+            .await
+            .unwrap_err();
+
+    // Verify the request is not serviceable
+    assert_matches!(response, StorageServiceError::InvalidRequest(_));
+}
+
 /// Creates a set of state keys and values using the specified number and size
 fn create_state_keys_and_values(
     num_keys_and_values: u64,
@@ -309,6 +396,20 @@ async fn get_state_values_with_proof(
     utils::send_storage_request(mock_client, use_compression, data_request).await
 }
 
+/// Sends a state values with batch proof request and processes the response
+async fn get_state_values_with_batch_proof(
+    mock_client: &mut MockClient,
+    version: u64,
+    state_keys: Vec<StateKey>,
+) -> Result<StorageServiceResponse, StorageServiceError> {
+    let data_request =
+        DataRequest::GetStateValuesWithBatchProof(StateValuesWithBatchProofRequest {
+            version,
+            state_keys,
+        });
+    utils::send_storage_request(mock_client, false, data_request).await
+}
+
 /// A helper method to request a states with proof chunk using the
 /// the specified network limit.
 async fn get_states_with_proof_network_limit(network_limit_bytes: u64) {
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::COMPRESSION_SUFFIX_LABEL;
-use aptos_types::transaction::Version;
+use aptos_types::{state_store::state_key::StateKey, transaction::Version};
 use serde::{Deserialize, Serialize};
 
 /// A storage service request.
@@ -53,6 +53,8 @@ pub enum DataRequest {
     GetTransactionDataWithProof(GetTransactionDataWithProofRequest), // Fetches transaction data with a proof
     GetNewTransactionDataWithProof(GetNewTransactionDataWithProofRequest), // Optimistically fetches new transaction data with a proof
     SubscribeTransactionDataWithProof(SubscribeTransactionDataWithProofRequest), // Subscribes to transaction data with a proof
+
+    GetStateValuesWithBatchProof(StateValuesWithBatchProofRequest), // Fetches a set of states by key with a single proof
 }
 
 impl DataRequest {
@@ -118,6 +120,8 @@ impl DataRequest {
                     },
                 }
             },
+
+            Self::GetStateValuesWithBatchProof(_) => "get_state_values_with_batch_proof",
         }
     }
 
@@ -347,6 +351,14 @@ pub struct StateValuesWithProofRequest {
     pub end_index: u64,   // The index to stop fetching state values (inclusive)
 }
 
+/// A storage service request for fetching the state values of a
+/// set of keys at a specified version, with a single batch proof.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct StateValuesWithBatchProofRequest {
+    pub version: u64,              // The version to fetch the state values at
+    pub state_keys: Vec<StateKey>, // The keys of the state values to fetch
+}
+
 /// A storage service request for fetching a transaction output list with a
 /// corresponding proof.
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -0,0 +1,366 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A background verifier that re-derives the hashes committed to by the ledger from the raw data
+//! stored in the DB, so that silent corruption is noticed on a live node instead of when the data
+//! is served to a peer.
+
+use crate::{
+    ledger_db::LedgerDb,
+    metrics::{
+        CONSISTENCY_CHECKED_STATE_LEAVES, CONSISTENCY_CHECKED_VERSIONS, CONSISTENCY_CHECK_FAILURES,
+    },
+    schema::transaction_accumulator::TransactionAccumulatorSchema,
+    state_kv_db::StateKvDb,
+    state_merkle_db::StateMerkleDb,
+};
+use aptos_config::config::ConsistencyCheckerConfig;
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_infallible::Mutex;
+use aptos_jellyfish_merkle::iterator::JellyfishMerkleIterator;
+use aptos_logger::prelude::*;
+use aptos_storage_interface::{AptosDbError, Result};
+use aptos_types::{
+    proof::{accumulator::InMemoryEventAccumulator, position::Position},
+    transaction::Version,
+};
+use rand::Rng;
+use std::{
+    sync::{mpsc, Arc},
+    thread,
+    thread::JoinHandle,
+    time::Duration,
+};
+
+const TXN_INFO_CHECK: &str = "txn_info";
+const TXN_CHECK: &str = "transaction";
+const EVENT_ROOT_CHECK: &str = "event_root";
+const WRITE_SET_CHECK: &str = "write_set";
+const STATE_LEAF_CHECK: &str = "state_leaf";
+const READ_CHECK: &str = "read";
+
+pub(crate) struct ConsistencyChecker {
+    sender: Mutex<mpsc::Sender<()>>,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+impl ConsistencyChecker {
+    pub(crate) fn new(
+        ledger_db: Arc<LedgerDb>,
+        state_merkle_db: Arc<StateMerkleDb>,
+        state_kv_db: Arc<StateKvDb>,
+        config: ConsistencyCheckerConfig,
+    ) -> Self {
+        let verifier = Verifier {
+            ledger_db,
+            state_merkle_db,
+            state_kv_db,
+            config,
+        };
+        let (send, recv) = mpsc::channel();
+        let join_handle = Some(thread::spawn(move || loop {
+            match recv.recv_timeout(Duration::from_millis(config.sample_interval_ms)) {
+                Ok(_) => break,
+                Err(mpsc::RecvTimeoutError::Timeout) => (),
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+            if let Err(e) = verifier.check_sample() {
+                warn!(
+                    error = ?e,
+                    "Storage consistency check failed to run."
+                );
+            }
+        }));
+        Self {
+            sender: Mutex::new(send),
+            join_handle,
+        }
+    }
+}
+
+impl Drop for ConsistencyChecker {
+    fn drop(&mut self) {
+        // Notify the checker thread to exit
+        self.sender.lock().send(()).unwrap();
+        self.join_handle
+            .take()
+            .expect("Consistency checker thread must exist.")
+            .join()
+            .expect("Consistency checker thread should join peacefully.");
+    }
+}
+
+struct Verifier {
+    ledger_db: Arc<LedgerDb>,
+    state_merkle_db: Arc<StateMerkleDb>,
+    state_kv_db: Arc<StateKvDb>,
+    config: ConsistencyCheckerConfig,
+}
+
+impl Verifier {
+    /// Verifies a random range of committed versions and a random range of leaves of the latest
+    /// state snapshot.
+    fn check_sample(&self) -> Result<()> {
+        let Some(committed_version) = self.ledger_db.metadata_db().get_committed_version() else {
+            return Ok(());
+        };
+
+        let min_readable_version = self.min_readable_version()?;
+        if min_readable_version <= committed_version {
+            let start_version =
+                rand::thread_rng().gen_range(min_readable_version..=committed_version);
+            let end_version = std::cmp::min(
+                start_version.saturating_add(self.config.num_versions_per_sample as u64),
+                committed_version + 1,
+            );
+            for version in start_version..end_version {
+                if let Err(e) = self.check_version(version) {
+                    if version < self.min_readable_version()? {
+                        // Pruned while we were reading it, the rest of the range is likely gone too.
+                        break;
+                    }
+                    report_failure(READ_CHECK, version, format!("{:?}", e));
+                }
+                CONSISTENCY_CHECKED_VERSIONS.inc();
+            }
+        }
+
+        self.check_state_leaves(committed_version)
+    }
+
+    fn min_readable_version(&self) -> Result<Version> {
+        match self.ledger_db.metadata_db().get_pruner_progress() {
+            Ok(version) => Ok(version),
+            Err(AptosDbError::NotFound(_)) => Ok(0),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Recomputes the hashes that the transaction info at `version` commits to and compares them
+    /// with the transaction accumulator leaf.
+    fn check_version(&self, version: Version) -> Result<()> {
+        let txn_info = self
+            .ledger_db
+            .transaction_info_db()
+            .get_transaction_info(version)?;
+
+        let leaf_hash = self
+            .ledger_db
+            .transaction_accumulator_db_raw()
+            .get::<TransactionAccumulatorSchema>(&Position::from_leaf_index(version))?
+            .ok_or_else(|| {
+                AptosDbError::NotFound(format!("Accumulator leaf for version {}", version))
+            })?;
+        if txn_info.hash() != leaf_hash {
+            report_failure(
+                TXN_INFO_CHECK,
+                version,
+                format!("txn info hash {} != leaf {}", txn_info.hash(), leaf_hash),
+            );
+        }
+
+        let txn_hash = self
+            .ledger_db
+            .transaction_db()
+            .get_transaction(version)?
+            .hash();
+        if txn_hash != txn_info.transaction_hash() {
+            report_failure(
+                TXN_CHECK,
+                version,
+                format!(
+                    "transaction hash {} != {}",
+                    txn_hash,
+                    txn_info.transaction_hash()
+                ),
+            );
+        }
+
+        let event_hashes: Vec<_> = self
+            .ledger_db
+            .event_db()
+            .get_events_by_version(version)?
+            .iter()
+            .map(CryptoHash::hash)
+            .collect();
+        let event_root_hash = InMemoryEventAccumulator::from_leaves(&event_hashes).root_hash();
+        if event_root_hash != txn_info.event_root_hash() {
+            report_failure(
+                EVENT_ROOT_CHECK,
+                version,
+                format!(
+                    "event root hash {} != {}",
+                    event_root_hash,
+                    txn_info.event_root_hash()
+                ),
+            );
+        }
+
+        let write_set_hash = self.ledger_db.write_set_db().get_write_set(version)?.hash();
+        if write_set_hash != txn_info.state_change_hash() {
+            report_failure(
+                WRITE_SET_CHECK,
+                version,
+                format!(
+                    "write set hash {} != {}",
+                    write_set_hash,
+                    txn_info.state_change_hash()
+                ),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Walks leaves of the latest state snapshot starting from a random key and checks that the
+    /// value hash in each leaf matches the value stored in the state kv db.
+    fn check_state_leaves(&self, committed_version: Version) -> Result<()> {
+        let Some(snapshot_version) = self
+            .state_merkle_db
+            .get_state_snapshot_version_before(committed_version + 1)?
+        else {
+            return Ok(());
+        };
+
+        let iter = JellyfishMerkleIterator::new(
+            Arc::clone(&self.state_merkle_db),
+            snapshot_version,
+            HashValue::random(),
+        )?;
+        for leaf in iter.take(self.config.num_state_leaves_per_sample) {
+            let (key_hash, (state_key, value_version)) = leaf?;
+            CONSISTENCY_CHECKED_STATE_LEAVES.inc();
+
+            if state_key.hash() != key_hash {
+                report_failure(
+                    STATE_LEAF_CHECK,
+                    value_version,
+                    format!("key hash {} doesn't match key {:?}", key_hash, state_key),
+                );
+                continue;
+            }
+
+            let (leaf_opt, _proof) =
+                self.state_merkle_db
+                    .get_with_proof_ext(&key_hash, snapshot_version, 0)?;
+            let Some((value_hash, _)) = leaf_opt else {
+                report_failure(
+                    STATE_LEAF_CHECK,
+                    value_version,
+                    format!("leaf {} not found by proof lookup", key_hash),
+                );
+                continue;
+            };
+
+            match self
+                .state_kv_db
+                .get_state_value_with_version_by_version(&state_key, value_version)?
+            {
+                Some((version, value))
+                    if version == value_version && value.hash() == value_hash => {},
+                Some((version, value)) => report_failure(
+                    STATE_LEAF_CHECK,
+                    value_version,
+                    format!(
+                        "key {:?}: value at version {} with hash {} doesn't match leaf hash {}",
+                        state_key,
+                        version,
+                        value.hash(),
+                        value_hash
+                    ),
+                ),
+                None => report_failure(
+                    STATE_LEAF_CHECK,
+                    value_version,
+                    format!("key {:?}: value missing from state kv db", state_key),
+                ),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn report_failure(check: &'static str, version: Version, detail: String) {
+    error!(
+        check = check,
+        version = version,
+        detail = detail,
+        "Storage consistency check failed."
+    );
+    CONSISTENCY_CHECK_FAILURES.with_label_values(&[check]).inc();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        db::{test_helper::arb_blocks_to_commit, AptosDB},
+        schema::write_set::WriteSetSchema,
+    };
+    use aptos_temppath::TempPath;
+    use aptos_types::write_set::WriteSet;
+    use proptest::prelude::*;
+
+    fn num_failures(check: &str) -> u64 {
+        CONSISTENCY_CHECK_FAILURES.with_label_values(&[check]).get()
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(1))]
+
+        #[test]
+        fn test_consistency_checker(input in arb_blocks_to_commit()) {
+            let tmp_dir = TempPath::new();
+            let db = AptosDB::new_for_test(&tmp_dir);
+            let mut version = 0;
+            for (txns_to_commit, ledger_info_with_sigs) in input.iter() {
+                db.save_transactions_for_test(
+                    txns_to_commit,
+                    version,
+                    Some(ledger_info_with_sigs),
+                    true, /* sync_commit */
+                )
+                .unwrap();
+                version += txns_to_commit.len() as u64;
+            }
+
+            let verifier = Verifier {
+                ledger_db: Arc::clone(&db.ledger_db),
+                state_merkle_db: Arc::clone(&db.state_store.state_merkle_db),
+                state_kv_db: Arc::clone(&db.state_kv_db),
+                config: ConsistencyCheckerConfig {
+                    num_state_leaves_per_sample: usize::MAX,
+                    ..Default::default()
+                },
+            };
+
+            let checks = [
+                TXN_INFO_CHECK,
+                TXN_CHECK,
+                EVENT_ROOT_CHECK,
+                WRITE_SET_CHECK,
+                STATE_LEAF_CHECK,
+            ];
+            let failures_before: Vec<_> = checks.iter().map(|c| num_failures(c)).collect();
+            for v in 0..version {
+                verifier.check_version(v).unwrap();
+            }
+            verifier.check_state_leaves(version - 1).unwrap();
+            let failures_after: Vec<_> = checks.iter().map(|c| num_failures(c)).collect();
+            prop_assert_eq!(failures_before, failures_after);
+
+            // Corrupt a write set and make sure it gets reported.
+            let corrupted_version = (0..version)
+                .find(|v| db.ledger_db.write_set_db().get_write_set(*v).unwrap() != WriteSet::default())
+                .unwrap();
+            db.ledger_db
+                .write_set_db_raw()
+                .put::<WriteSetSchema>(&corrupted_version, &WriteSet::default())
+                .unwrap();
+            let write_set_failures = num_failures(WRITE_SET_CHECK);
+            verifier.check_version(corrupted_version).unwrap();
+            prop_assert_eq!(num_failures(WRITE_SET_CHECK), write_set_failures + 1);
+        }
+    }
+}
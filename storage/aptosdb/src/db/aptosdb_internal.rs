@@ -8,9 +8,11 @@ use crate::{
     metrics::{API_LATENCY_SECONDS, CONCURRENCY_GAUGE},
     pruner::{LedgerPrunerManager, PrunerManager, StateKvPrunerManager, StateMerklePrunerManager},
     rocksdb_property_reporter::RocksdbPropertyReporter,
+    schema_migration::{self, MigrationDbs, SchemaMigrationWorker, SchemaMigrator},
     state_kv_db::StateKvDb,
     state_merkle_db::StateMerkleDb,
     state_store::StateStore,
+    tiered_storage::{ArchivedTransaction, TieredLedgerStore},
     transaction_store::TransactionStore,
 };
 use aptos_config::config::{
@@ -22,7 +24,7 @@ use aptos_metrics_core::{IntGaugeVecHelper, TimerHelper};
 use aptos_resource_viewer::AptosValueAnnotator;
 use aptos_storage_interface::{
     block_info::BlockInfo, db_ensure as ensure, db_other_bail as bail, AptosDbError, DbReader,
-    Order, Result,
+    LedgerDataCategory, Order, Result,
 };
 use aptos_types::{
     account_config::{new_block_event_key, NewBlockEvent},
@@ -99,6 +101,8 @@ impl AptosDB {
             indexer: None,
             skip_index_and_usage,
             update_subscriber: None,
+            consistency_checker: None,
+            _schema_migration_worker: None,
         }
     }
 
@@ -112,18 +116,36 @@ impl AptosDB {
         max_num_nodes_per_lru_cache_shard: usize,
         empty_buffered_state_for_restore: bool,
         internal_indexer_db: Option<InternalIndexerDB>,
+        tiered_store: Option<TieredLedgerStore>,
     ) -> Result<Self> {
         ensure!(
             pruner_config.eq(&NO_OP_STORAGE_PRUNER_CONFIG) || !readonly,
             "Do not set prune_window when opening readonly.",
         );
 
-        let (ledger_db, state_merkle_db, state_kv_db) = Self::open_dbs(
+        let (mut ledger_db, state_merkle_db, state_kv_db) = Self::open_dbs(
             db_paths,
             rocksdb_configs,
             readonly,
             max_num_nodes_per_lru_cache_shard,
         )?;
+        // Applied before anything else reads the DB.
+        let migrations = schema_migration::all_migrations();
+        let has_pending_backfills = {
+            let migrator = SchemaMigrator::new(
+                MigrationDbs {
+                    ledger_db: &ledger_db,
+                    state_merkle_db: &state_merkle_db,
+                    state_kv_db: &state_kv_db,
+                },
+                &migrations,
+            );
+            migrator.prepare(readonly)?;
+            !readonly && !migrator.get_pending_backfills()?.is_empty()
+        };
+        if let Some(tiered_store) = tiered_store {
+            ledger_db.set_tiered_store(tiered_store);
+        }
 
         let mut myself = Self::new_with_dbs(
             ledger_db,
@@ -159,6 +181,15 @@ impl AptosDB {
             }
         }
 
+        if has_pending_backfills {
+            myself._schema_migration_worker = Some(SchemaMigrationWorker::new(
+                Arc::clone(&myself.ledger_db),
+                Arc::clone(&myself.state_store.state_merkle_db),
+                Arc::clone(&myself.state_kv_db),
+                migrations,
+            ));
+        }
+
         if !readonly && enable_indexer {
             myself.open_indexer(
                 db_paths.default_root_path(),
@@ -247,6 +278,104 @@ impl AptosDB {
         Ok(())
     }
 
+    /// Same as `error_if_ledger_pruned`, but for a category of ledger data, which can be kept
+    /// longer or shorter than the rest per the retention overrides.
+    pub(super) fn error_if_ledger_category_pruned(
+        &self,
+        category: LedgerDataCategory,
+        version: Version,
+    ) -> Result<()> {
+        let min_readable_version = self.ledger_pruner.get_min_readable_version_for(category);
+        ensure!(
+            version >= min_readable_version,
+            "{} at version {} is pruned, min available version is {}.",
+            category.name(),
+            version,
+            min_readable_version
+        );
+        Ok(())
+    }
+
+    /// Same as `error_if_ledger_pruned`, except that with tiered storage enabled the pruned
+    /// versions archived in the cold tier can still be fetched from there.
+    pub(super) fn error_if_ledger_unavailable(
+        &self,
+        data_type: &str,
+        version: Version,
+    ) -> Result<()> {
+        if let Some(tiered_store) = self.ledger_db.tiered_store() {
+            // The pruner waits for the versions to be archived, so those below the min readable
+            // version can still be in the DB.
+            if version < self.ledger_pruner.get_min_readable_version()
+                && (version >= self.ledger_pruner.get_pruner_progress()
+                    || tiered_store.store().is_archived(version)?)
+            {
+                return Ok(());
+            }
+        }
+        self.error_if_ledger_pruned(data_type, version)
+    }
+
+    /// Checks that all the `categories` of ledger data are available at `version`. With tiered
+    /// storage enabled, the overrides can't shorten the retention of any category, so the checks
+    /// are the same as for the ledger data in general.
+    pub(super) fn error_if_ledger_categories_unavailable(
+        &self,
+        categories: &[LedgerDataCategory],
+        version: Version,
+    ) -> Result<()> {
+        if self.ledger_db.tiered_store().is_some() {
+            return self.error_if_ledger_unavailable(categories[0].name(), version);
+        }
+        categories
+            .iter()
+            .try_for_each(|category| self.error_if_ledger_category_pruned(*category, version))
+    }
+
+    /// Checks that the transaction accumulator is available at `version`. It's pruned along with
+    /// the transaction infos, and not at all with tiered storage enabled.
+    pub(super) fn error_if_accumulator_unavailable(&self, version: Version) -> Result<()> {
+        if self.ledger_db.tiered_store().is_some() {
+            return self.error_if_ledger_unavailable("Transaction accumulator", version);
+        }
+        self.error_if_ledger_category_pruned(LedgerDataCategory::TransactionInfos, version)
+    }
+
+    /// Returns the first version of the ledger history that can be read, including what's been
+    /// moved to the cold tier. The pruner archives before pruning, so there is no gap in between.
+    pub(super) fn get_first_available_ledger_version(&self) -> Result<Version> {
+        let min_readable_version = self.ledger_pruner.get_min_readable_version();
+        let first_archived_version = match self.ledger_db.tiered_store() {
+            Some(tiered_store) => tiered_store.store().first_version()?,
+            None => None,
+        };
+        Ok(first_archived_version.map_or(min_readable_version, |v| {
+            std::cmp::min(v, min_readable_version)
+        }))
+    }
+
+    /// Returns the ledger data of the longest prefix of `[start_version, end_version)` that is
+    /// pruned from the DB and available in the cold tier. Empty if tiered storage is disabled.
+    pub(super) fn get_archived_transactions(
+        &self,
+        start_version: Version,
+        end_version: Version,
+    ) -> Result<Vec<ArchivedTransaction>> {
+        let Some(tiered_store) = self.ledger_db.tiered_store() else {
+            return Ok(Vec::new());
+        };
+        let end_version = std::cmp::min(end_version, self.ledger_pruner.get_min_readable_version());
+        let mut txns = Vec::new();
+        for version in start_version..end_version {
+            match tiered_store.get(version)? {
+                Some(txn) => txns.push(txn),
+                // Not archived yet, still in the DB.
+                None => break,
+            }
+        }
+        Ok(txns)
+    }
+
     pub(super) fn error_if_state_merkle_pruned(
         &self,
         data_type: &str,
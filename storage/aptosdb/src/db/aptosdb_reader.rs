@@ -16,7 +16,8 @@ use aptos_storage_interface::{
     state_store::{
         state::State, state_summary::StateSummary, state_view::hot_state_view::HotStateView,
     },
-    AptosDbError, BlockHeight, DbReader, LedgerSummary, Order, Result, MAX_REQUEST_LIMIT,
+    AptosDbError, BlockHeight, DbReader, LedgerDataCategory, LedgerSummary, Order, Result,
+    MAX_REQUEST_LIMIT,
 };
 use aptos_types::{
     account_address::AccountAddress,
@@ -29,13 +30,13 @@ use aptos_types::{
     proof::{
         accumulator::InMemoryAccumulator, AccumulatorConsistencyProof, SparseMerkleProofExt,
         TransactionAccumulatorRangeProof, TransactionAccumulatorSummary,
-        TransactionInfoListWithProof,
+        TransactionInfoListWithProof, TransactionInfoWithProof,
     },
     state_proof::StateProof,
     state_store::{
         state_key::{prefix::StateKeyPrefix, StateKey},
         state_storage_usage::StateStorageUsage,
-        state_value::{StateValue, StateValueChunkWithProof},
+        state_value::{StateValue, StateValueChunkWithProof, StateValuesWithBatchProof},
         table::{TableHandle, TableInfo},
     },
     transaction::{
@@ -101,7 +102,15 @@ impl DbReader for AptosDB {
         version: Version,
     ) -> Result<Option<TransactionAuxiliaryData>> {
         gauged_api("get_transaction_auxiliary_data_by_version", || {
-            self.error_if_ledger_pruned("Transaction", version)?;
+            if self.ledger_db.tiered_store().is_some() {
+                // Kept in the DB for the versions archived in the cold tier.
+                self.error_if_ledger_unavailable("TransactionAuxiliaryData", version)?;
+            } else {
+                self.error_if_ledger_category_pruned(
+                    LedgerDataCategory::TransactionAuxiliaryData,
+                    version,
+                )?;
+            }
             self.ledger_db
                 .transaction_auxiliary_data_db()
                 .get_transaction_auxiliary_data(version)
@@ -113,7 +122,13 @@ impl DbReader for AptosDB {
         version: Version,
     ) -> Result<PersistedAuxiliaryInfo> {
         gauged_api("get_persisted_auxiliary_info_by_version", || {
-            self.error_if_ledger_pruned("PersistedAuxiliaryInfo", version)?;
+            self.error_if_ledger_categories_unavailable(
+                &[LedgerDataCategory::PersistedAuxiliaryInfo],
+                version,
+            )?;
+            if let Some(archived) = self.get_archived_transactions(version, version + 1)?.pop() {
+                return Ok(archived.persisted_info);
+            }
             Ok(self
                 .ledger_db
                 .persisted_auxiliary_info_db()
@@ -277,37 +292,68 @@ impl DbReader for AptosDB {
             if start_version > ledger_version || limit == 0 {
                 return Ok(TransactionListWithProofV2::new_empty());
             }
-            self.error_if_ledger_pruned("Transaction", start_version)?;
+            self.error_if_ledger_categories_unavailable(
+                &[
+                    LedgerDataCategory::Transactions,
+                    LedgerDataCategory::TransactionInfos,
+                    LedgerDataCategory::PersistedAuxiliaryInfo,
+                ],
+                start_version,
+            )?;
+            if fetch_events {
+                self.error_if_ledger_categories_unavailable(
+                    &[LedgerDataCategory::Events],
+                    start_version,
+                )?;
+            }
 
             let limit = std::cmp::min(limit, ledger_version - start_version + 1);
 
-            let txns = (start_version..start_version + limit)
-                .map(|version| self.ledger_db.transaction_db().get_transaction(version))
+            // Versions pruned to the cold tier come first, the rest is read from the DB.
+            let archived = self.get_archived_transactions(start_version, start_version + limit)?;
+            let hot_versions = start_version + archived.len() as Version..start_version + limit;
+
+            let txns = archived
+                .iter()
+                .map(|txn| Ok(txn.transaction.clone()))
+                .chain(
+                    hot_versions
+                        .clone()
+                        .map(|version| self.ledger_db.transaction_db().get_transaction(version)),
+                )
                 .collect::<Result<Vec<_>>>()?;
-            let txn_infos = (start_version..start_version + limit)
-                .map(|version| {
+            let txn_infos = archived
+                .iter()
+                .map(|txn| Ok(txn.transaction_info.clone()))
+                .chain(hot_versions.clone().map(|version| {
                     self.ledger_db
                         .transaction_info_db()
                         .get_transaction_info(version)
-                })
+                }))
                 .collect::<Result<Vec<_>>>()?;
             let events = if fetch_events {
                 Some(
-                    (start_version..start_version + limit)
-                        .map(|version| self.ledger_db.event_db().get_events_by_version(version))
+                    archived
+                        .iter()
+                        .map(|txn| Ok(txn.events.clone()))
+                        .chain(hot_versions.clone().map(|version| {
+                            self.ledger_db.event_db().get_events_by_version(version)
+                        }))
                         .collect::<Result<Vec<_>>>()?,
                 )
             } else {
                 None
             };
-            let persisted_aux_info = (start_version..start_version + limit)
-                .map(|version| {
+            let persisted_aux_info = archived
+                .iter()
+                .map(|txn| Ok(txn.persisted_info))
+                .chain(hot_versions.map(|version| {
                     Ok(self
                         .ledger_db
                         .persisted_auxiliary_info_db()
                         .get_persisted_auxiliary_info(version)?
                         .unwrap_or(PersistedAuxiliaryInfo::None))
-                })
+                }))
                 .collect::<Result<Vec<_>>>()?;
             let proof = TransactionInfoListWithProof::new(
                 self.ledger_db
@@ -328,7 +374,7 @@ impl DbReader for AptosDB {
     /// Get the first version that txn starts existent.
     fn get_first_txn_version(&self) -> Result<Option<Version>> {
         gauged_api("get_first_txn_version", || {
-            Ok(Some(self.ledger_pruner.get_min_readable_version()))
+            Ok(Some(self.get_first_available_ledger_version()?))
         })
     }
 
@@ -361,7 +407,36 @@ impl DbReader for AptosDB {
     /// Get the first version that write set starts existent.
     fn get_first_write_set_version(&self) -> Result<Option<Version>> {
         gauged_api("get_first_write_set_version", || {
-            Ok(Some(self.ledger_pruner.get_min_readable_version()))
+            self.get_first_ledger_data_version(LedgerDataCategory::WriteSets)
+        })
+    }
+
+    /// Get the first version that a category of ledger data starts existent, which can be
+    /// earlier or later than `get_first_txn_version` per the ledger retention overrides.
+    fn get_first_ledger_data_version(
+        &self,
+        category: LedgerDataCategory,
+    ) -> Result<Option<Version>> {
+        gauged_api("get_first_ledger_data_version", || {
+            let min_readable_version = self.ledger_pruner.get_min_readable_version_for(category);
+            let first_archived_version = match self.ledger_db.tiered_store() {
+                Some(tiered_store) => tiered_store.store().first_version()?,
+                None => None,
+            };
+            Ok(Some(
+                first_archived_version.map_or(min_readable_version, |v| {
+                    std::cmp::min(v, min_readable_version)
+                }),
+            ))
+        })
+    }
+
+    /// Get the first version that the history of state values starts existent.
+    fn get_first_state_kv_version(&self) -> Result<Option<Version>> {
+        gauged_api("get_first_state_kv_version", || {
+            Ok(Some(
+                self.state_store.state_kv_pruner.get_min_readable_version(),
+            ))
         })
     }
 
@@ -384,13 +459,38 @@ impl DbReader for AptosDB {
                 return Ok(TransactionOutputListWithProofV2::new_empty());
             }
 
-            self.error_if_ledger_pruned("Transaction", start_version)?;
+            self.error_if_ledger_categories_unavailable(&LedgerDataCategory::ALL, start_version)?;
 
             let limit = std::cmp::min(limit, ledger_version - start_version + 1);
 
-            let (txn_infos, txns_and_outputs, persisted_aux_info) = (start_version
-                ..start_version + limit)
-                .map(|version| {
+            // Versions pruned to the cold tier come first, the rest is read from the DB.
+            let archived = self.get_archived_transactions(start_version, start_version + limit)?;
+            let hot_versions = start_version + archived.len() as Version..start_version + limit;
+
+            let (txn_infos, txns_and_outputs, persisted_aux_info) = archived
+                .into_iter()
+                .zip(start_version..)
+                .map(|(txn, version)| {
+                    // Auxiliary data is not archived, but kept in the DB with tiered storage.
+                    let auxiliary_data = self
+                        .ledger_db
+                        .transaction_auxiliary_data_db()
+                        .get_transaction_auxiliary_data(version)?
+                        .unwrap_or_default();
+                    let txn_output = TransactionOutput::new(
+                        txn.write_set,
+                        txn.events,
+                        txn.transaction_info.gas_used(),
+                        txn.transaction_info.status().clone().into(),
+                        auxiliary_data,
+                    );
+                    Ok((
+                        txn.transaction_info,
+                        (txn.transaction, txn_output),
+                        txn.persisted_info,
+                    ))
+                })
+                .chain(hot_versions.map(|version| {
                     let txn_info = self
                         .ledger_db
                         .transaction_info_db()
@@ -416,7 +516,7 @@ impl DbReader for AptosDB {
                         .get_persisted_auxiliary_info(version)?
                         .unwrap_or(PersistedAuxiliaryInfo::None);
                     Ok((txn_info, (txn, txn_output), persisted_aux_info))
-                })
+                }))
                 .collect::<Result<Vec<_>>>()?
                 .into_iter()
                 .multiunzip();
@@ -481,7 +581,7 @@ impl DbReader for AptosDB {
     ) -> Result<Box<dyn Iterator<Item = Result<Transaction>> + '_>> {
         gauged_api("get_transaction_iterator", || {
             error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
-            self.error_if_ledger_pruned("Transaction", start_version)?;
+            self.error_if_ledger_category_pruned(LedgerDataCategory::Transactions, start_version)?;
 
             let iter = self
                 .ledger_db
@@ -498,7 +598,10 @@ impl DbReader for AptosDB {
     ) -> Result<Box<dyn Iterator<Item = Result<TransactionInfo>> + '_>> {
         gauged_api("get_transaction_info_iterator", || {
             error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
-            self.error_if_ledger_pruned("Transaction", start_version)?;
+            self.error_if_ledger_category_pruned(
+                LedgerDataCategory::TransactionInfos,
+                start_version,
+            )?;
 
             let iter = self
                 .ledger_db
@@ -515,7 +618,7 @@ impl DbReader for AptosDB {
     ) -> Result<Box<dyn Iterator<Item = Result<Vec<ContractEvent>>> + '_>> {
         gauged_api("get_events_iterator", || {
             error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
-            self.error_if_ledger_pruned("Transaction", start_version)?;
+            self.error_if_ledger_category_pruned(LedgerDataCategory::Events, start_version)?;
 
             let iter = self
                 .ledger_db
@@ -535,7 +638,7 @@ impl DbReader for AptosDB {
     ) -> Result<Box<dyn Iterator<Item = Result<WriteSet>> + '_>> {
         gauged_api("get_write_set_iterator", || {
             error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
-            self.error_if_ledger_pruned("Transaction", start_version)?;
+            self.error_if_ledger_category_pruned(LedgerDataCategory::WriteSets, start_version)?;
 
             let iter = self
                 .ledger_db
@@ -552,7 +655,7 @@ impl DbReader for AptosDB {
         ledger_version: Version,
     ) -> Result<TransactionAccumulatorRangeProof> {
         gauged_api("get_transaction_accumulator_range_proof", || {
-            self.error_if_ledger_pruned("Transaction", first_version)?;
+            self.error_if_accumulator_unavailable(first_version)?;
 
             self.ledger_db
                 .transaction_accumulator_db()
@@ -654,6 +757,32 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_state_value_history(
+        &self,
+        state_key: &StateKey,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<(Version, Option<StateValue>)>> {
+        gauged_api("get_state_value_history", || {
+            error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+            ensure!(
+                start_version <= ledger_version,
+                "start_version {} is greater than ledger_version {}.",
+                start_version,
+                ledger_version
+            );
+            self.error_if_state_kv_pruned("StateValue history", start_version)?;
+
+            self.state_kv_db.get_state_value_history(
+                state_key,
+                start_version,
+                ledger_version,
+                limit as usize,
+            )
+        })
+    }
+
     /// Returns the proof of the given state key and version.
     fn get_state_proof_by_version_ext(
         &self,
@@ -821,7 +950,7 @@ impl DbReader for AptosDB {
 
     fn get_accumulator_root_hash(&self, version: Version) -> Result<HashValue> {
         gauged_api("get_accumulator_root_hash", || {
-            self.error_if_ledger_pruned("Transaction accumulator", version)?;
+            self.error_if_accumulator_unavailable(version)?;
             self.ledger_db
                 .transaction_accumulator_db()
                 .get_root_hash(version)
@@ -834,10 +963,7 @@ impl DbReader for AptosDB {
         ledger_version: Version,
     ) -> Result<AccumulatorConsistencyProof> {
         gauged_api("get_accumulator_consistency_proof", || {
-            self.error_if_ledger_pruned(
-                "Transaction accumulator",
-                client_known_version.unwrap_or(0),
-            )?;
+            self.error_if_accumulator_unavailable(client_known_version.unwrap_or(0))?;
             self.ledger_db
                 .transaction_accumulator_db()
                 .get_consistency_proof(client_known_version, ledger_version)
@@ -880,6 +1006,18 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_state_values_with_batch_proof(
+        &self,
+        version: Version,
+        state_keys: &[StateKey],
+    ) -> Result<StateValuesWithBatchProof> {
+        gauged_api("get_state_values_with_batch_proof", || {
+            self.error_if_state_merkle_pruned("State merkle", version)?;
+            self.state_store
+                .get_values_with_batch_proof(version, state_keys)
+        })
+    }
+
     fn get_state_value_chunk_iter(
         &self,
         version: Version,
@@ -970,7 +1108,7 @@ impl DbReader for AptosDB {
         index: u64,
     ) -> Result<ContractEvent> {
         gauged_api("get_event_by_version_and_index", || {
-            self.error_if_ledger_pruned("Event", version)?;
+            self.error_if_ledger_category_pruned(LedgerDataCategory::Events, version)?;
             self.event_store
                 .get_event_by_version_and_index(version, index)
         })
@@ -1061,7 +1199,33 @@ impl AptosDB {
         ledger_version: Version,
         fetch_events: bool,
     ) -> Result<TransactionWithProof> {
-        self.error_if_ledger_pruned("Transaction", version)?;
+        self.error_if_ledger_categories_unavailable(
+            &[
+                LedgerDataCategory::Transactions,
+                LedgerDataCategory::TransactionInfos,
+            ],
+            version,
+        )?;
+        if fetch_events {
+            self.error_if_ledger_categories_unavailable(&[LedgerDataCategory::Events], version)?;
+        }
+        // The accumulator is kept along with the transaction infos for the proof.
+        self.error_if_accumulator_unavailable(version)?;
+
+        if let Some(archived) = self.get_archived_transactions(version, version + 1)?.pop() {
+            let proof = TransactionInfoWithProof::new(
+                self.ledger_db
+                    .transaction_accumulator_db()
+                    .get_transaction_proof(version, ledger_version)?,
+                archived.transaction_info,
+            );
+            return Ok(TransactionWithProof {
+                version,
+                transaction: archived.transaction,
+                events: fetch_events.then_some(archived.events),
+                proof,
+            });
+        }
 
         let proof = self
             .ledger_db
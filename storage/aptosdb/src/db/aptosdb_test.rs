@@ -10,18 +10,25 @@ use crate::{
         },
         AptosDB,
     },
+    ledger_db::ledger_metadata_db::LedgerMetadataDb,
     pruner::{LedgerPrunerManager, PrunerManager, StateMerklePrunerManager},
     schema::stale_node_index::StaleNodeIndexSchema,
+    tiered_storage::{test_helper::MockColdLedgerStore, ColdLedgerStore},
 };
 use aptos_config::config::{
-    EpochSnapshotPrunerConfig, LedgerPrunerConfig, PrunerConfig, RocksdbConfigs,
-    StateMerklePrunerConfig, StorageDirPaths, BUFFERED_STATE_TARGET_ITEMS_FOR_TEST,
-    DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    EpochSnapshotPrunerConfig, LedgerPrunerConfig, LedgerRetention, LedgerRetentionOverrides,
+    PrunerConfig, RocksdbConfigs, StateMerklePrunerConfig, StorageDirPaths,
+    BUFFERED_STATE_TARGET_ITEMS_FOR_TEST, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
 };
 use aptos_crypto::{hash::CryptoHash, HashValue};
-use aptos_storage_interface::{DbReader, Order};
+use aptos_schemadb::batch::SchemaBatch;
+use aptos_storage_interface::{DbReader, LedgerDataCategory, Order};
 use aptos_temppath::TempPath;
 use aptos_types::{
+    account_address::AccountAddress,
+    account_config::{new_block_event_key, NewBlockEvent},
+    contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
     proof::SparseMerkleLeafNode,
     state_store::{state_key::StateKey, state_value::StateValue},
@@ -32,8 +39,9 @@ use aptos_types::{
     vm_status::StatusCode,
     write_set::WriteSet,
 };
+use move_core_types::{language_storage::TypeTag, move_resource::MoveStructType};
 use proptest::prelude::*;
-use std::{collections::HashSet, sync::Arc};
+use std::{collections::HashSet, num::NonZeroUsize, sync::Arc};
 
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(10))]
@@ -112,8 +120,10 @@ fn test_pruner_config() {
             LedgerPrunerConfig {
                 enable,
                 prune_window: 100,
+                prune_window_secs: None,
                 batch_size: 1,
                 user_pruning_window_offset: 0,
+                retention_overrides: Default::default(),
             },
             None,
         );
@@ -225,6 +235,418 @@ fn test_get_latest_ledger_summary() {
     assert_eq!(bootstrapped.state_summary.root_hash(), state_hash);
 }
 
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1))]
+
+    #[test]
+    fn test_tiered_ledger_storage(input in arb_blocks_to_commit()) {
+        test_tiered_ledger_storage_impl(input);
+    }
+
+    #[test]
+    fn test_ledger_retention_overrides(input in arb_blocks_to_commit()) {
+        test_ledger_retention_overrides_impl(input);
+    }
+
+    #[test]
+    fn test_ledger_retention_shorter_overrides(input in arb_blocks_to_commit()) {
+        test_ledger_retention_shorter_overrides_impl(input);
+    }
+}
+
+fn test_tiered_ledger_storage_impl(
+    input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+) {
+    let tmp_dir = TempPath::new();
+    let cold_store = Arc::new(MockColdLedgerStore::default());
+    let db = AptosDB::open_tiered(
+        StorageDirPaths::from_path(&tmp_dir),
+        /*readonly=*/ false,
+        PrunerConfig {
+            ledger_pruner_config: LedgerPrunerConfig {
+                enable: true,
+                prune_window: 0,
+                prune_window_secs: None,
+                batch_size: 1,
+                user_pruning_window_offset: 0,
+                retention_overrides: Default::default(),
+            },
+            ..NO_OP_STORAGE_PRUNER_CONFIG
+        },
+        RocksdbConfigs::default(),
+        false, /* enable_indexer */
+        BUFFERED_STATE_TARGET_ITEMS_FOR_TEST,
+        DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        None,
+        cold_store.clone(),
+        NonZeroUsize::new(2).unwrap(),
+    )
+    .unwrap();
+
+    let mut next_ver: Version = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in input.iter() {
+        db.save_transactions_for_test(
+            txns_to_commit,
+            next_ver, /* first_version */
+            Some(ledger_info_with_sigs),
+            true, /* sync_commit */
+        )
+        .unwrap();
+        next_ver += txns_to_commit.len() as u64;
+    }
+    db.ledger_pruner.wait_for_pruner().unwrap();
+
+    // Pruned versions are gone from the DB, but archived.
+    let latest_version = next_ver - 1;
+    let ledger_info = input.last().unwrap().1.ledger_info();
+    let min_readable_version = db.ledger_pruner.get_min_readable_version();
+    assert_eq!(
+        cold_store.next_version().unwrap().unwrap_or(0),
+        min_readable_version
+    );
+    for version in 0..min_readable_version {
+        assert!(db
+            .ledger_db
+            .transaction_db()
+            .get_transaction(version)
+            .is_err());
+    }
+
+    // All versions are still served.
+    assert_eq!(db.get_first_txn_version().unwrap(), Some(0));
+    let txns: Vec<_> = input
+        .iter()
+        .flat_map(|(txns_to_commit, _)| txns_to_commit.iter().map(|t| t.transaction()))
+        .collect();
+    for (version, txn) in txns.iter().enumerate() {
+        let txn_with_proof = db
+            .get_transaction_by_version(version as Version, latest_version, true)
+            .unwrap();
+        assert_eq!(&txn_with_proof.transaction, *txn);
+        txn_with_proof
+            .proof
+            .verify(ledger_info, version as Version)
+            .unwrap();
+    }
+    db.get_transactions(0, next_ver, latest_version, true)
+        .unwrap()
+        .consume_transaction_list_with_proof()
+        .verify(ledger_info, Some(0))
+        .unwrap();
+    let outputs = db
+        .get_transaction_outputs(0, next_ver, latest_version)
+        .unwrap()
+        .consume_output_list_with_proof();
+    outputs.verify(ledger_info, Some(0)).unwrap();
+
+    // The auxiliary data of archived versions is kept in the DB.
+    let auxiliary_data: Vec<_> = input
+        .iter()
+        .flat_map(|(txns_to_commit, _)| {
+            txns_to_commit
+                .iter()
+                .map(|t| t.transaction_auxiliary_data().clone())
+        })
+        .collect();
+    for ((_txn, output), expected) in outputs.transactions_and_outputs.iter().zip(&auxiliary_data) {
+        assert_eq!(output.auxiliary_data(), expected);
+    }
+    drop(db);
+
+    // Versions pruned without being archived are reported as pruned.
+    let db = AptosDB::open_tiered(
+        StorageDirPaths::from_path(&tmp_dir),
+        /*readonly=*/ false,
+        NO_OP_STORAGE_PRUNER_CONFIG,
+        RocksdbConfigs::default(),
+        false, /* enable_indexer */
+        BUFFERED_STATE_TARGET_ITEMS_FOR_TEST,
+        DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        None,
+        Arc::new(MockColdLedgerStore::default()),
+        NonZeroUsize::new(2).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        db.get_first_txn_version().unwrap(),
+        Some(min_readable_version)
+    );
+    if min_readable_version > 0 {
+        assert!(db
+            .get_transaction_by_version(0, latest_version, true)
+            .is_err());
+        assert!(db.get_transactions(0, 1, latest_version, false).is_err());
+    }
+}
+
+fn open_db_with_ledger_pruner(
+    tmp_dir: &TempPath,
+    ledger_pruner_config: LedgerPrunerConfig,
+    input: &[(Vec<TransactionToCommit>, LedgerInfoWithSignatures)],
+) -> (AptosDB, Version) {
+    let db = AptosDB::open(
+        StorageDirPaths::from_path(tmp_dir),
+        /*readonly=*/ false,
+        PrunerConfig {
+            ledger_pruner_config,
+            ..NO_OP_STORAGE_PRUNER_CONFIG
+        },
+        RocksdbConfigs::default(),
+        false, /* enable_indexer */
+        BUFFERED_STATE_TARGET_ITEMS_FOR_TEST,
+        DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        None,
+    )
+    .unwrap();
+
+    let mut next_ver: Version = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in input.iter() {
+        db.save_transactions_for_test(
+            txns_to_commit,
+            next_ver, /* first_version */
+            Some(ledger_info_with_sigs),
+            true, /* sync_commit */
+        )
+        .unwrap();
+        next_ver += txns_to_commit.len() as u64;
+    }
+    db.ledger_pruner.wait_for_pruner().unwrap();
+    (db, next_ver)
+}
+
+fn test_ledger_retention_overrides_impl(
+    input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+) {
+    let tmp_dir = TempPath::new();
+    let (db, next_ver) = open_db_with_ledger_pruner(
+        &tmp_dir,
+        LedgerPrunerConfig {
+            enable: true,
+            prune_window: 0,
+            prune_window_secs: None,
+            batch_size: 1,
+            user_pruning_window_offset: 0,
+            retention_overrides: LedgerRetentionOverrides {
+                events: Some(LedgerRetention::Forever),
+                transactions: Some(LedgerRetention::Forever),
+                ..Default::default()
+            },
+        },
+        &input,
+    );
+    let latest_version = next_ver - 1;
+    let ledger_info = input.last().unwrap().1.ledger_info();
+
+    // Write sets are pruned per the prune window.
+    let min_readable_version = db.ledger_pruner.get_min_readable_version();
+    assert_eq!(min_readable_version, latest_version);
+    assert_eq!(
+        db.get_write_set_iterator(0, 1).err().unwrap().to_string(),
+        format!(
+            "AptosDB Other Error: WriteSet at version 0 is pruned, min available version is {}.",
+            min_readable_version
+        )
+    );
+    assert!(db.get_transaction_outputs(0, 1, latest_version).is_err());
+
+    // Events and transactions are kept, along with the transaction infos and the accumulator to
+    // prove them.
+    let txns_to_commit: Vec<_> = input
+        .iter()
+        .flat_map(|(txns_to_commit, _)| txns_to_commit.iter())
+        .collect();
+    for category in [
+        LedgerDataCategory::Events,
+        LedgerDataCategory::Transactions,
+        LedgerDataCategory::TransactionInfos,
+    ] {
+        assert_eq!(db.ledger_pruner.get_min_readable_version_for(category), 0);
+    }
+    let events = db
+        .get_events_iterator(0, next_ver)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        events,
+        txns_to_commit
+            .iter()
+            .map(|t| t.events().to_vec())
+            .collect::<Vec<_>>()
+    );
+    for (version, txn_to_commit) in txns_to_commit.iter().enumerate() {
+        let txn_with_proof = db
+            .get_transaction_by_version(version as Version, latest_version, true)
+            .unwrap();
+        assert_eq!(&txn_with_proof.transaction, txn_to_commit.transaction());
+        txn_with_proof
+            .proof
+            .verify(ledger_info, version as Version)
+            .unwrap();
+    }
+}
+
+fn test_ledger_retention_shorter_overrides_impl(
+    input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+) {
+    let tmp_dir = TempPath::new();
+    let (db, next_ver) = open_db_with_ledger_pruner(
+        &tmp_dir,
+        LedgerPrunerConfig {
+            enable: true,
+            prune_window: 1_000_000,
+            prune_window_secs: None,
+            batch_size: 1,
+            user_pruning_window_offset: 0,
+            retention_overrides: LedgerRetentionOverrides {
+                events: Some(LedgerRetention::Versions(0)),
+                ..Default::default()
+            },
+        },
+        &input,
+    );
+    let latest_version = next_ver - 1;
+    let ledger_info = input.last().unwrap().1.ledger_info();
+
+    // Events are pruned ahead of the rest.
+    assert_eq!(db.ledger_pruner.get_min_readable_version(), 0);
+    assert_eq!(
+        db.ledger_pruner
+            .get_min_readable_version_for(LedgerDataCategory::Events),
+        latest_version
+    );
+    assert_eq!(
+        db.get_events_iterator(0, 1).err().unwrap().to_string(),
+        format!(
+            "AptosDB Other Error: Event at version 0 is pruned, min available version is {}.",
+            latest_version
+        )
+    );
+    assert!(db
+        .get_transaction_by_version(0, latest_version, true)
+        .is_err());
+
+    // The rest is served with proofs.
+    let txn_with_proof = db
+        .get_transaction_by_version(0, latest_version, false)
+        .unwrap();
+    txn_with_proof.proof.verify(ledger_info, 0).unwrap();
+}
+
+fn new_db_with_blocks_100_secs_apart(tmp_dir: &TempPath) -> AptosDB {
+    let db = AptosDB::new_for_test(tmp_dir);
+
+    // Blocks start at versions 0, 10, 20 and 30, and are committed 100 seconds apart.
+    let mut batch = SchemaBatch::new();
+    for height in 0..4 {
+        let new_block_event = NewBlockEvent::new(
+            AccountAddress::random(),
+            0,
+            height,
+            height,
+            vec![],
+            AccountAddress::random(),
+            vec![],
+            height * 100_000_000,
+        );
+        LedgerMetadataDb::put_block_info(
+            height * 10,
+            &ContractEvent::new_v1(
+                new_block_event_key(),
+                height,
+                TypeTag::from(NewBlockEvent::struct_tag()),
+                bcs::to_bytes(&new_block_event).unwrap(),
+            )
+            .unwrap(),
+            &mut batch,
+        )
+        .unwrap();
+    }
+    db.ledger_db.metadata_db().write_schemas(batch).unwrap();
+    db
+}
+
+#[test]
+fn test_ledger_retention_by_age() {
+    let tmp_dir = TempPath::new();
+    let db = new_db_with_blocks_100_secs_apart(&tmp_dir);
+
+    let ledger_pruner = LedgerPrunerManager::new(
+        Arc::clone(&db.ledger_db),
+        LedgerPrunerConfig {
+            enable: true,
+            prune_window: 0,
+            prune_window_secs: None,
+            batch_size: 1,
+            user_pruning_window_offset: 0,
+            retention_overrides: LedgerRetentionOverrides {
+                events: Some(LedgerRetention::Seconds(150)),
+                write_sets: Some(LedgerRetention::Seconds(1_000)),
+                ..Default::default()
+            },
+        },
+        None,
+    );
+
+    // The block of the latest version is committed at 300s, so the events are kept from the
+    // first block committed at or after 150s, and the write sets are all kept, as are the
+    // transaction infos to prove them.
+    ledger_pruner.maybe_set_pruner_target_db_version(35);
+    assert_eq!(ledger_pruner.get_min_readable_version(), 35);
+    assert_eq!(
+        ledger_pruner.get_min_readable_version_for(LedgerDataCategory::Events),
+        20
+    );
+    assert_eq!(
+        ledger_pruner.get_min_readable_version_for(LedgerDataCategory::WriteSets),
+        0
+    );
+    assert_eq!(
+        ledger_pruner.get_min_readable_version_for(LedgerDataCategory::Transactions),
+        35
+    );
+    assert_eq!(
+        ledger_pruner.get_min_readable_version_for(LedgerDataCategory::TransactionInfos),
+        0
+    );
+}
+
+#[test]
+fn test_ledger_prune_window_by_age() {
+    let tmp_dir = TempPath::new();
+    let db = new_db_with_blocks_100_secs_apart(&tmp_dir);
+
+    let ledger_pruner = LedgerPrunerManager::new(
+        Arc::clone(&db.ledger_db),
+        LedgerPrunerConfig {
+            enable: true,
+            prune_window: 0,
+            prune_window_secs: Some(150),
+            batch_size: 1,
+            user_pruning_window_offset: 0,
+            retention_overrides: LedgerRetentionOverrides {
+                events: Some(LedgerRetention::Seconds(50)),
+                ..Default::default()
+            },
+        },
+        None,
+    );
+
+    // The ledger data is kept from the first block committed at or after 150s, and the events
+    // from the one at or after 250s.
+    ledger_pruner.maybe_set_pruner_target_db_version(35);
+    assert_eq!(ledger_pruner.get_min_readable_version(), 20);
+    assert_eq!(ledger_pruner.get_min_viable_version(), 20);
+    assert_eq!(
+        ledger_pruner.get_min_readable_version_for(LedgerDataCategory::Events),
+        30
+    );
+    assert_eq!(
+        ledger_pruner.get_min_readable_version_for(LedgerDataCategory::TransactionInfos),
+        20
+    );
+}
+
 pub fn test_state_merkle_pruning_impl(
     input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
 ) {
@@ -237,8 +659,10 @@ pub fn test_state_merkle_pruning_impl(
             ledger_pruner_config: LedgerPrunerConfig {
                 enable: true,
                 prune_window: 10,
+                prune_window_secs: None,
                 batch_size: 1,
                 user_pruning_window_offset: 0,
+                retention_overrides: Default::default(),
             },
             state_merkle_pruner_config: StateMerklePrunerConfig {
                 enable: true,
@@ -21,7 +21,7 @@ use aptos_storage_interface::{
         state_delta::StateDelta, state_update_refs::BatchedStateUpdateRefs,
         state_view::cached_state_view::ShardedStateCache,
     },
-    AptosDbError, DbReader, DbWriter, LedgerSummary, MAX_REQUEST_LIMIT,
+    AptosDbError, DbReader, DbWriter, LedgerDataCategory, LedgerSummary, MAX_REQUEST_LIMIT,
 };
 use aptos_types::{
     access_path::AccessPath,
@@ -603,6 +603,17 @@ impl DbReader for FakeAptosDB {
         self.inner.get_first_write_set_version()
     }
 
+    fn get_first_ledger_data_version(
+        &self,
+        category: LedgerDataCategory,
+    ) -> Result<Option<Version>> {
+        self.inner.get_first_ledger_data_version(category)
+    }
+
+    fn get_first_state_kv_version(&self) -> Result<Option<Version>> {
+        self.inner.get_first_state_kv_version()
+    }
+
     fn get_transaction_outputs(
         &self,
         start_version: Version,
@@ -3,18 +3,28 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    backup::backup_handler::BackupHandler, event_store::EventStore, ledger_db::LedgerDb,
-    pruner::LedgerPrunerManager, rocksdb_property_reporter::RocksdbPropertyReporter,
-    state_kv_db::StateKvDb, state_merkle_db::StateMerkleDb, state_store::StateStore,
+    backup::backup_handler::BackupHandler,
+    consistency_checker::ConsistencyChecker,
+    event_store::EventStore,
+    ledger_db::LedgerDb,
+    pruner::LedgerPrunerManager,
+    rocksdb_property_reporter::RocksdbPropertyReporter,
+    schema_migration::SchemaMigrationWorker,
+    state_kv_db::StateKvDb,
+    state_merkle_db::StateMerkleDb,
+    state_store::StateStore,
+    tiered_storage::{ColdLedgerStore, TieredLedgerStore},
     transaction_store::TransactionStore,
 };
-use aptos_config::config::{PrunerConfig, RocksdbConfigs, StorageDirPaths};
+use aptos_config::config::{
+    ConsistencyCheckerConfig, PrunerConfig, RocksdbConfigs, StorageDirPaths,
+};
 use aptos_db_indexer::{db_indexer::InternalIndexerDB, Indexer};
 use aptos_logger::prelude::*;
 use aptos_schemadb::{batch::SchemaBatch, Env};
 use aptos_storage_interface::{db_ensure as ensure, AptosDbError, Result};
 use aptos_types::{ledger_info::LedgerInfoWithSignatures, transaction::Version};
-use std::{path::Path, sync::Arc, time::Instant};
+use std::{num::NonZeroUsize, path::Path, sync::Arc, time::Instant};
 use tokio::sync::watch::Sender;
 
 #[cfg(test)]
@@ -39,6 +49,8 @@ pub struct AptosDB {
     indexer: Option<Indexer>,
     skip_index_and_usage: bool,
     update_subscriber: Option<Sender<(Instant, Version)>>,
+    consistency_checker: Option<ConsistencyChecker>,
+    _schema_migration_worker: Option<SchemaMigrationWorker>,
 }
 
 // DbReader implementations and private functions used by them.
@@ -75,6 +87,35 @@ impl AptosDB {
             max_num_nodes_per_lru_cache_shard,
             false,
             internal_indexer_db,
+            None,
+        )
+    }
+
+    /// Opens the DB with tiered ledger storage: ledger history pruned from the DB is moved to
+    /// `cold_ledger_store` and is still served from there.
+    pub fn open_tiered(
+        db_paths: StorageDirPaths,
+        readonly: bool,
+        pruner_config: PrunerConfig,
+        rocksdb_configs: RocksdbConfigs,
+        enable_indexer: bool,
+        buffered_state_target_items: usize,
+        max_num_nodes_per_lru_cache_shard: usize,
+        internal_indexer_db: Option<InternalIndexerDB>,
+        cold_ledger_store: Arc<dyn ColdLedgerStore>,
+        chunk_cache_size: NonZeroUsize,
+    ) -> Result<Self> {
+        Self::open_internal(
+            &db_paths,
+            readonly,
+            pruner_config,
+            rocksdb_configs,
+            enable_indexer,
+            buffered_state_target_items,
+            max_num_nodes_per_lru_cache_shard,
+            false,
+            internal_indexer_db,
+            Some(TieredLedgerStore::new(cold_ledger_store, chunk_cache_size)),
         )
     }
 
@@ -98,6 +139,7 @@ impl AptosDB {
             max_num_nodes_per_lru_cache_shard,
             true,
             internal_indexer_db,
+            None,
         )
     }
 
@@ -144,6 +186,17 @@ impl AptosDB {
         Ok(())
     }
 
+    /// Starts a background thread that continuously verifies samples of the committed data.
+    pub fn start_consistency_checker(&mut self, config: ConsistencyCheckerConfig) -> Result<()> {
+        self.consistency_checker = Some(ConsistencyChecker::new(
+            Arc::clone(&self.ledger_db),
+            Arc::clone(&self.state_store.state_merkle_db),
+            Arc::clone(&self.state_kv_db),
+            config,
+        ));
+        Ok(())
+    }
+
     /// Gets an instance of `BackupHandler` for data backup purpose.
     pub fn get_backup_handler(&self) -> BackupHandler {
         BackupHandler::new(Arc::clone(&self.state_store), Arc::clone(&self.ledger_db))
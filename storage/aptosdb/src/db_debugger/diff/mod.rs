@@ -0,0 +1,714 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    db_debugger::ShardingConfig,
+    ledger_db::LedgerDb,
+    schema::{
+        db_metadata::DbMetadataKey, transaction_info::TransactionInfoSchema,
+        write_set::WriteSetSchema,
+    },
+    state_kv_db::StateKvDb,
+    state_merkle_db::StateMerkleDb,
+    utils::get_progress,
+    AptosDB,
+};
+use aptos_config::config::{RocksdbConfigs, StorageDirPaths};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_jellyfish_merkle::{
+    node_type::{Node, NodeKey},
+    TreeReader,
+};
+use aptos_storage_interface::{db_ensure as ensure, AptosDbError, Result};
+use aptos_types::{nibble::Nibble, state_store::state_key::StateKey, transaction::Version};
+use clap::{Parser, ValueEnum};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Parser)]
+#[clap(about = "Compare two DBs and report where they diverge.")]
+pub struct Cmd {
+    #[clap(long, value_parser)]
+    db_dir_a: PathBuf,
+
+    #[clap(long, value_parser)]
+    db_dir_b: PathBuf,
+
+    #[clap(flatten)]
+    sharding_config: ShardingConfig,
+
+    /// First version to compare the ledger data from. Versions pruned in either DB are skipped.
+    #[clap(long, default_value_t = 0)]
+    start_version: Version,
+
+    /// Last version (inclusive) to compare the ledger data to, and the version at which the state
+    /// trees are compared. Defaults to the latest version synced by both DBs, in which case the
+    /// state trees are compared at the latest snapshot not newer than that which exists in both
+    /// DBs.
+    #[clap(long)]
+    version: Option<Version>,
+
+    /// Stop after this many differing state keys are found.
+    #[clap(long, default_value_t = 100)]
+    max_state_diffs: usize,
+
+    #[clap(long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+}
+
+struct DbHandles {
+    ledger_db: LedgerDb,
+    state_merkle_db: StateMerkleDb,
+    state_kv_db: StateKvDb,
+}
+
+#[derive(Debug, Serialize)]
+struct DiffReport {
+    ledger: LedgerDiff,
+    state: Option<StateDiff>,
+}
+
+#[derive(Debug, Serialize)]
+struct LedgerDiff {
+    synced_version_a: Option<Version>,
+    synced_version_b: Option<Version>,
+    /// Oldest version not pruned from the ledger of each DB.
+    min_readable_version_a: Version,
+    min_readable_version_b: Version,
+    start_version: Version,
+    /// First version actually compared. Versions in [`start_version`, `first_compared_version`)
+    /// are skipped because they were pruned in at least one of the DBs.
+    first_compared_version: Version,
+    end_version: Version,
+    first_divergent_version: Option<Version>,
+    /// Which kinds of data differ at `first_divergent_version`.
+    divergent_data: Vec<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+struct StateDiff {
+    version: Version,
+    root_hash_a: HashValue,
+    root_hash_b: HashValue,
+    differences: Vec<StateKeyDiff>,
+    /// Set if the search stopped at `max_state_diffs`.
+    truncated: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct StateKeyDiff {
+    key_hash: HashValue,
+    state_key: String,
+    a: Option<StateLeaf>,
+    b: Option<StateLeaf>,
+}
+
+#[derive(Debug, Serialize)]
+struct StateLeaf {
+    value_version: Version,
+    value_hash: HashValue,
+    /// Hex encoded value bytes, if the value could be found in the state kv db.
+    value: Option<String>,
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        let db_a = self.open_dbs(&self.db_dir_a)?;
+        let db_b = self.open_dbs(&self.db_dir_b)?;
+
+        let ledger = self.diff_ledger(&db_a, &db_b)?;
+        let state = self.diff_state(&db_a, &db_b, ledger.end_version)?;
+        let report = DiffReport { ledger, state };
+
+        match self.output {
+            OutputFormat::Text => print_report(&report),
+            OutputFormat::Json => println!(
+                "{}",
+                serde_json::to_string_pretty(&report)
+                    .map_err(|e| AptosDbError::Other(e.to_string()))?
+            ),
+        }
+
+        Ok(())
+    }
+
+    fn open_dbs(&self, db_dir: &Path) -> Result<DbHandles> {
+        let (ledger_db, state_merkle_db, state_kv_db) = AptosDB::open_dbs(
+            &StorageDirPaths::from_path(db_dir),
+            RocksdbConfigs {
+                enable_storage_sharding: self.sharding_config.enable_storage_sharding,
+                ..Default::default()
+            },
+            /*readonly=*/ true,
+            /*max_num_nodes_per_lru_cache_shard=*/ 0,
+        )?;
+        Ok(DbHandles {
+            ledger_db,
+            state_merkle_db,
+            state_kv_db,
+        })
+    }
+
+    fn diff_ledger(&self, db_a: &DbHandles, db_b: &DbHandles) -> Result<LedgerDiff> {
+        let synced_version_a = db_a.ledger_db.metadata_db().get_synced_version()?;
+        let synced_version_b = db_b.ledger_db.metadata_db().get_synced_version()?;
+        let end_version = match self.version {
+            Some(version) => version,
+            None => match (synced_version_a, synced_version_b) {
+                (Some(a), Some(b)) => std::cmp::min(a, b),
+                _ => {
+                    return Err(AptosDbError::Other(
+                        "At least one of the DBs is empty.".to_string(),
+                    ))
+                },
+            },
+        };
+        ensure!(
+            self.start_version <= end_version,
+            "start_version {} is after end version {}.",
+            self.start_version,
+            end_version,
+        );
+
+        let min_readable_version_a = get_min_readable_version(&db_a.ledger_db)?;
+        let min_readable_version_b = get_min_readable_version(&db_b.ledger_db)?;
+        let first_compared_version = self
+            .start_version
+            .max(min_readable_version_a)
+            .max(min_readable_version_b);
+
+        let mut first_divergent_version = None;
+        let mut divergent_data = vec![];
+        for version in first_compared_version..=end_version {
+            let (a, b) = (&db_a.ledger_db, &db_b.ledger_db);
+            if a.transaction_info_db_raw()
+                .get::<TransactionInfoSchema>(&version)?
+                != b.transaction_info_db_raw()
+                    .get::<TransactionInfoSchema>(&version)?
+            {
+                divergent_data.push("transaction_info");
+            }
+            if a.write_set_db_raw().get::<WriteSetSchema>(&version)?
+                != b.write_set_db_raw().get::<WriteSetSchema>(&version)?
+            {
+                divergent_data.push("write_set");
+            }
+            if a.event_db().get_events_by_version(version)?
+                != b.event_db().get_events_by_version(version)?
+            {
+                divergent_data.push("events");
+            }
+
+            if !divergent_data.is_empty() {
+                first_divergent_version = Some(version);
+                break;
+            }
+            if version % 10_000 == 0 && self.output == OutputFormat::Text {
+                println!("Ledger data matches until version {}.", version);
+            }
+        }
+
+        Ok(LedgerDiff {
+            synced_version_a,
+            synced_version_b,
+            min_readable_version_a,
+            min_readable_version_b,
+            start_version: self.start_version,
+            first_compared_version,
+            end_version,
+            first_divergent_version,
+            divergent_data,
+        })
+    }
+
+    fn diff_state(
+        &self,
+        db_a: &DbHandles,
+        db_b: &DbHandles,
+        end_version: Version,
+    ) -> Result<Option<StateDiff>> {
+        let version = match self.version {
+            Some(version) => version,
+            None => {
+                // Walk back until both DBs have a snapshot at the same version, since either of
+                // them may have pruned or skipped snapshots the other one has.
+                let mut next_version = end_version + 1;
+                loop {
+                    let version_a = db_a
+                        .state_merkle_db
+                        .get_state_snapshot_version_before(next_version)?;
+                    let version_b = db_b
+                        .state_merkle_db
+                        .get_state_snapshot_version_before(next_version)?;
+                    match (version_a, version_b) {
+                        (Some(a), Some(b)) if a == b => break a,
+                        (Some(a), Some(b)) => next_version = std::cmp::max(a, b),
+                        _ => return Ok(None),
+                    }
+                }
+            },
+        };
+
+        let root_key = NodeKey::new_empty_path(version);
+        let root_a = db_a.state_merkle_db.get_node_option(&root_key, "diff")?;
+        let root_b = db_b.state_merkle_db.get_node_option(&root_key, "diff")?;
+        ensure!(
+            root_a.is_some() && root_b.is_some(),
+            "Both DBs must have a state snapshot at version {} (found in A: {}, in B: {}).",
+            version,
+            root_a.is_some(),
+            root_b.is_some(),
+        );
+
+        let mut differ = StateDiffer {
+            db_a,
+            db_b,
+            max_diffs: self.max_state_diffs,
+            differences: vec![],
+            truncated: false,
+        };
+        differ.diff_subtree(Some(root_key.clone()), Some(root_key))?;
+
+        Ok(Some(StateDiff {
+            version,
+            root_hash_a: root_a.expect("Checked above.").hash(),
+            root_hash_b: root_b.expect("Checked above.").hash(),
+            differences: differ.differences,
+            truncated: differ.truncated,
+        }))
+    }
+}
+
+struct StateDiffer<'a> {
+    db_a: &'a DbHandles,
+    db_b: &'a DbHandles,
+    max_diffs: usize,
+    differences: Vec<StateKeyDiff>,
+    truncated: bool,
+}
+
+impl StateDiffer<'_> {
+    /// Compares the subtrees rooted at the same position of both trees, descending only into
+    /// children whose hashes differ.
+    fn diff_subtree(&mut self, key_a: Option<NodeKey>, key_b: Option<NodeKey>) -> Result<()> {
+        if self.truncated {
+            return Ok(());
+        }
+        let node_a = get_node(&self.db_a.state_merkle_db, key_a.as_ref())?;
+        let node_b = get_node(&self.db_b.state_merkle_db, key_b.as_ref())?;
+        if node_a.as_ref().map(Node::hash) == node_b.as_ref().map(Node::hash) {
+            return Ok(());
+        }
+
+        if let (Some(Node::Internal(internal_a)), Some(Node::Internal(internal_b))) =
+            (&node_a, &node_b)
+        {
+            let (key_a, key_b) = (key_a.expect("Node exists."), key_b.expect("Node exists."));
+            for n in 0..16 {
+                let nibble = Nibble::from(n);
+                let child_a = internal_a.child(nibble);
+                let child_b = internal_b.child(nibble);
+                if child_a.map(|c| c.hash) == child_b.map(|c| c.hash) {
+                    continue;
+                }
+                self.diff_subtree(
+                    child_a.map(|c| key_a.gen_child_node_key(c.version, nibble)),
+                    child_b.map(|c| key_b.gen_child_node_key(c.version, nibble)),
+                )?;
+            }
+            return Ok(());
+        }
+
+        // The shapes differ (e.g. a leaf on one side and a subtree on the other), so compare all
+        // the leaves below this position.
+        let mut leaves_a = BTreeMap::new();
+        collect_leaves(&self.db_a.state_merkle_db, key_a, &mut leaves_a)?;
+        let mut leaves_b = BTreeMap::new();
+        collect_leaves(&self.db_b.state_merkle_db, key_b, &mut leaves_b)?;
+
+        let key_hashes: BTreeSet<_> = leaves_a.keys().chain(leaves_b.keys()).copied().collect();
+        for key_hash in key_hashes {
+            let leaf_a = leaves_a.remove(&key_hash);
+            let leaf_b = leaves_b.remove(&key_hash);
+            if leaf_a.as_ref().map(|(_, v, h)| (*v, *h))
+                == leaf_b.as_ref().map(|(_, v, h)| (*v, *h))
+            {
+                continue;
+            }
+            self.push_diff(key_hash, leaf_a, leaf_b)?;
+            if self.truncated {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn push_diff(
+        &mut self,
+        key_hash: HashValue,
+        leaf_a: Option<(StateKey, Version, HashValue)>,
+        leaf_b: Option<(StateKey, Version, HashValue)>,
+    ) -> Result<()> {
+        if self.differences.len() >= self.max_diffs {
+            self.truncated = true;
+            return Ok(());
+        }
+        let state_key = leaf_a
+            .as_ref()
+            .or(leaf_b.as_ref())
+            .map(|(state_key, _, _)| format!("{:?}", state_key))
+            .unwrap_or_default();
+        let a = leaf_a
+            .map(|leaf| to_state_leaf(&self.db_a.state_kv_db, leaf))
+            .transpose()?;
+        let b = leaf_b
+            .map(|leaf| to_state_leaf(&self.db_b.state_kv_db, leaf))
+            .transpose()?;
+        self.differences.push(StateKeyDiff {
+            key_hash,
+            state_key,
+            a,
+            b,
+        });
+        Ok(())
+    }
+}
+
+fn get_min_readable_version(ledger_db: &LedgerDb) -> Result<Version> {
+    Ok(get_progress(
+        ledger_db.metadata_db().db(),
+        &DbMetadataKey::LedgerPrunerProgress,
+    )?
+    .unwrap_or(0))
+}
+
+fn get_node(db: &StateMerkleDb, node_key: Option<&NodeKey>) -> Result<Option<Node<StateKey>>> {
+    match node_key {
+        Some(node_key) => Ok(Some(db.get_node_with_tag(node_key, "diff")?)),
+        None => Ok(None),
+    }
+}
+
+fn collect_leaves(
+    db: &StateMerkleDb,
+    node_key: Option<NodeKey>,
+    leaves: &mut BTreeMap<HashValue, (StateKey, Version, HashValue)>,
+) -> Result<()> {
+    let Some(node_key) = node_key else {
+        return Ok(());
+    };
+    match db.get_node_with_tag(&node_key, "diff")? {
+        Node::Internal(internal) => {
+            for (nibble, child) in internal.children_sorted() {
+                collect_leaves(
+                    db,
+                    Some(node_key.gen_child_node_key(child.version, *nibble)),
+                    leaves,
+                )?;
+            }
+        },
+        Node::Leaf(leaf) => {
+            let (state_key, value_version) = leaf.value_index().clone();
+            leaves.insert(
+                *leaf.account_key(),
+                (state_key, value_version, leaf.value_hash()),
+            );
+        },
+        Node::Null => (),
+    }
+    Ok(())
+}
+
+fn to_state_leaf(
+    db: &StateKvDb,
+    (state_key, value_version, value_hash): (StateKey, Version, HashValue),
+) -> Result<StateLeaf> {
+    let value = db
+        .get_state_value_with_version_by_version(&state_key, value_version)?
+        .filter(|(version, value)| *version == value_version && value.hash() == value_hash)
+        .map(|(_, value)| hex::encode(value.bytes()));
+    Ok(StateLeaf {
+        value_version,
+        value_hash,
+        value,
+    })
+}
+
+fn print_report(report: &DiffReport) {
+    let ledger = &report.ledger;
+    println!(
+        "Synced versions: A: {:?}, B: {:?}",
+        ledger.synced_version_a, ledger.synced_version_b
+    );
+    if ledger.first_compared_version > ledger.start_version {
+        println!(
+            "Skipped versions [{}, {}) pruned in at least one DB (min readable versions: A: {}, B: {}).",
+            ledger.start_version,
+            ledger.first_compared_version,
+            ledger.min_readable_version_a,
+            ledger.min_readable_version_b,
+        );
+    }
+    match ledger.first_divergent_version {
+        Some(version) => println!(
+            "Ledger data diverges at version {} ({}).",
+            version,
+            ledger.divergent_data.join(", ")
+        ),
+        None if ledger.first_compared_version > ledger.end_version => {
+            println!("No ledger data left to compare.")
+        },
+        None => println!(
+            "Ledger data matches in versions [{}, {}].",
+            ledger.first_compared_version, ledger.end_version
+        ),
+    }
+
+    let Some(state) = &report.state else {
+        println!("No state snapshot to compare.");
+        return;
+    };
+    if state.root_hash_a == state.root_hash_b {
+        println!(
+            "State trees match at version {} (root hash {}).",
+            state.version, state.root_hash_a
+        );
+        return;
+    }
+    println!(
+        "State trees differ at version {}: root hash A: {}, root hash B: {}",
+        state.version, state.root_hash_a, state.root_hash_b
+    );
+    for diff in &state.differences {
+        println!("\n  key hash: {}", diff.key_hash);
+        println!("  state key: {}", diff.state_key);
+        for (name, leaf) in [("A", &diff.a), ("B", &diff.b)] {
+            match leaf {
+                Some(leaf) => println!(
+                    "    {}: version {}, value hash {}, value: {}",
+                    name,
+                    leaf.value_version,
+                    leaf.value_hash,
+                    leaf.value.as_deref().unwrap_or("<not found>")
+                ),
+                None => println!("    {}: <missing>", name),
+            }
+        }
+    }
+    if state.truncated {
+        println!(
+            "\nStopped after {} differences, there may be more.",
+            state.differences.len()
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        db::test_helper::arb_blocks_to_commit,
+        schema::db_metadata::{DbMetadataSchema, DbMetadataValue},
+    };
+    use aptos_temppath::TempPath;
+    use aptos_types::{
+        ledger_info::LedgerInfoWithSignatures, state_store::state_value::StateValue,
+        transaction::TransactionToCommit, write_set::WriteSet,
+    };
+    use proptest::prelude::*;
+
+    fn diff_cmd(tmp_dir_a: &TempPath, tmp_dir_b: &TempPath, max_state_diffs: usize) -> Cmd {
+        Cmd {
+            db_dir_a: tmp_dir_a.path().to_path_buf(),
+            db_dir_b: tmp_dir_b.path().to_path_buf(),
+            sharding_config: ShardingConfig {
+                enable_storage_sharding: false,
+            },
+            start_version: 0,
+            version: None,
+            max_state_diffs,
+            output: OutputFormat::Json,
+        }
+    }
+
+    fn save_blocks(
+        db: &AptosDB,
+        input: &[(Vec<TransactionToCommit>, LedgerInfoWithSignatures)],
+    ) -> Version {
+        let mut version = 0;
+        for (txns_to_commit, ledger_info_with_sigs) in input.iter() {
+            db.save_transactions_for_test(
+                txns_to_commit,
+                version,
+                Some(ledger_info_with_sigs),
+                true, /* sync_commit */
+            )
+            .unwrap();
+            version += txns_to_commit.len() as u64;
+        }
+        version
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(1))]
+
+        #[test]
+        fn test_diff(input in arb_blocks_to_commit()) {
+            let tmp_dir_a = TempPath::new();
+            let tmp_dir_b = TempPath::new();
+            let mut corrupted_version = None;
+            for tmp_dir in [&tmp_dir_a, &tmp_dir_b] {
+                let db = AptosDB::new_for_test(tmp_dir);
+                let version = save_blocks(&db, &input);
+
+                // Corrupt a write set in DB B.
+                if tmp_dir == &tmp_dir_b {
+                    let v = (0..version)
+                        .rev()
+                        .find(|v| db.ledger_db.write_set_db().get_write_set(*v).unwrap() != WriteSet::default())
+                        .unwrap();
+                    db.ledger_db
+                        .write_set_db_raw()
+                        .put::<WriteSetSchema>(&v, &WriteSet::default())
+                        .unwrap();
+                    corrupted_version = Some(v);
+                }
+            }
+
+            let cmd = diff_cmd(&tmp_dir_a, &tmp_dir_b, 100);
+            let db_a = cmd.open_dbs(tmp_dir_a.path()).unwrap();
+            let db_b = cmd.open_dbs(tmp_dir_b.path()).unwrap();
+
+            let ledger = cmd.diff_ledger(&db_a, &db_b).unwrap();
+            prop_assert_eq!(ledger.first_divergent_version, corrupted_version);
+            prop_assert_eq!(ledger.divergent_data, vec!["write_set"]);
+
+            // The state trees themselves are identical.
+            let state = cmd.diff_state(&db_a, &db_b, ledger.end_version).unwrap().unwrap();
+            prop_assert_eq!(state.root_hash_a, state.root_hash_b);
+            prop_assert!(state.differences.is_empty());
+        }
+
+        #[test]
+        fn test_diff_skips_pruned_versions(input in arb_blocks_to_commit()) {
+            let tmp_dir_a = TempPath::new();
+            let tmp_dir_b = TempPath::new();
+            for tmp_dir in [&tmp_dir_a, &tmp_dir_b] {
+                let db = AptosDB::new_for_test(tmp_dir);
+                save_blocks(&db, &input);
+
+                // Make DB B look like version 0 was pruned from its ledger.
+                if tmp_dir == &tmp_dir_b {
+                    db.ledger_db
+                        .write_set_db_raw()
+                        .delete::<WriteSetSchema>(&0)
+                        .unwrap();
+                    db.ledger_db
+                        .metadata_db()
+                        .db()
+                        .put::<DbMetadataSchema>(
+                            &DbMetadataKey::LedgerPrunerProgress,
+                            &DbMetadataValue::Version(1),
+                        )
+                        .unwrap();
+                }
+            }
+
+            let cmd = diff_cmd(&tmp_dir_a, &tmp_dir_b, 100);
+            let db_a = cmd.open_dbs(tmp_dir_a.path()).unwrap();
+            let db_b = cmd.open_dbs(tmp_dir_b.path()).unwrap();
+
+            let ledger = cmd.diff_ledger(&db_a, &db_b).unwrap();
+            prop_assert_eq!(ledger.min_readable_version_a, 0);
+            prop_assert_eq!(ledger.min_readable_version_b, 1);
+            prop_assert_eq!(ledger.first_compared_version, 1);
+            prop_assert_eq!(ledger.first_divergent_version, None);
+        }
+    }
+
+    #[test]
+    fn test_diff_state() {
+        let key1 = StateKey::raw(b"test_key1");
+        let key2 = StateKey::raw(b"test_key2");
+        let key3 = StateKey::raw(b"test_key3");
+        let value = |v: &str| Some(StateValue::from(v.as_bytes().to_vec()));
+
+        let tmp_dir_a = TempPath::new();
+        let tmp_dir_b = TempPath::new();
+        {
+            let db_a = AptosDB::new_for_test_no_cache(&tmp_dir_a);
+            let db_b = AptosDB::new_for_test_no_cache(&tmp_dir_b);
+            for db in [&db_a, &db_b] {
+                db.state_store.commit_block_for_test(0, [vec![
+                    (key1.clone(), value("val1")),
+                    (key2.clone(), value("val2")),
+                    (key3.clone(), value("val3")),
+                ]]);
+            }
+            db_a.state_store.commit_block_for_test(1, [vec![
+                (key1.clone(), value("val1_a")),
+                (key2.clone(), value("val2_update")),
+            ]]);
+            db_b.state_store.commit_block_for_test(1, [vec![
+                (key1.clone(), value("val1_b")),
+                (key2.clone(), value("val2_update")),
+                (key3.clone(), None),
+            ]]);
+        }
+
+        let cmd = diff_cmd(&tmp_dir_a, &tmp_dir_b, 100);
+        let db_a = cmd.open_dbs(tmp_dir_a.path()).unwrap();
+        let db_b = cmd.open_dbs(tmp_dir_b.path()).unwrap();
+
+        let state = cmd.diff_state(&db_a, &db_b, 1).unwrap().unwrap();
+        assert_eq!(state.version, 1);
+        assert_ne!(state.root_hash_a, state.root_hash_b);
+        assert!(!state.truncated);
+        let mut differences: Vec<_> = state
+            .differences
+            .iter()
+            .map(|diff| {
+                (
+                    diff.key_hash,
+                    diff.a.as_ref().map(|leaf| leaf.value_version),
+                    diff.b.as_ref().map(|leaf| leaf.value_version),
+                )
+            })
+            .collect();
+        differences.sort();
+        let mut expected = vec![
+            (key1.hash(), Some(1), Some(1)),
+            (key3.hash(), Some(0), None),
+        ];
+        expected.sort();
+        assert_eq!(differences, expected);
+        let diff1 = state
+            .differences
+            .iter()
+            .find(|diff| diff.key_hash == key1.hash())
+            .unwrap();
+        assert_eq!(
+            diff1.a.as_ref().unwrap().value,
+            Some(hex::encode(b"val1_a"))
+        );
+        assert_eq!(
+            diff1.b.as_ref().unwrap().value,
+            Some(hex::encode(b"val1_b"))
+        );
+
+        // The search stops at `max_state_diffs`.
+        let cmd = diff_cmd(&tmp_dir_a, &tmp_dir_b, 1);
+        let state = cmd.diff_state(&db_a, &db_b, 1).unwrap().unwrap();
+        assert!(state.truncated);
+        assert_eq!(state.differences.len(), 1);
+    }
+}
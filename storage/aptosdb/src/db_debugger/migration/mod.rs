@@ -0,0 +1,140 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    db::AptosDB,
+    db_debugger::common::DbDir,
+    ledger_db::LedgerDb,
+    schema_migration::{
+        all_migrations, MigrationDbs, MigrationState, SchemaMigrator, BACKFILL_BATCH_SIZE,
+    },
+    state_kv_db::StateKvDb,
+    state_merkle_db::StateMerkleDb,
+};
+use aptos_config::config::{RocksdbConfigs, StorageDirPaths};
+use aptos_storage_interface::Result;
+use clap::Parser;
+
+#[derive(clap::Subcommand)]
+#[clap(about = "List or run the schema migrations of the DB.")]
+pub enum Cmd {
+    List(ListCmd),
+    Run(RunCmd),
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        match self {
+            Self::List(cmd) => cmd.run(),
+            Self::Run(cmd) => cmd.run(),
+        }
+    }
+}
+
+#[derive(Parser)]
+#[clap(about = "Print the schema version of the DB and the state of each known migration.")]
+pub struct ListCmd {
+    #[clap(flatten)]
+    db_dir: DbDir,
+}
+
+impl ListCmd {
+    pub fn run(self) -> Result<()> {
+        let (ledger_db, state_merkle_db, state_kv_db) =
+            open_dbs(&self.db_dir, /*readonly=*/ true)?;
+        let migrations = all_migrations();
+        let migrator = SchemaMigrator::new(
+            MigrationDbs {
+                ledger_db: &ledger_db,
+                state_merkle_db: &state_merkle_db,
+                state_kv_db: &state_kv_db,
+            },
+            &migrations,
+        );
+
+        println!(
+            "Schema version: {:?}, latest known: {}",
+            migrator.get_schema_version()?,
+            migrator.latest_schema_version()
+        );
+        for migration in &migrations {
+            let state = match migrator.get_state(migration.as_ref())? {
+                MigrationState::Pending => "pending".to_string(),
+                MigrationState::Backfilling(progress) => format!(
+                    "backfilling {} / {}",
+                    progress.next_version, progress.target_version
+                ),
+                MigrationState::Done => "done".to_string(),
+            };
+            println!(
+                "{:>5} {:<40} {}",
+                migration.version(),
+                migration.name(),
+                state
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Parser)]
+#[clap(about = "Apply the pending schema migrations and run their backfills to completion.")]
+pub struct RunCmd {
+    #[clap(flatten)]
+    db_dir: DbDir,
+
+    #[clap(long, default_value_t = BACKFILL_BATCH_SIZE)]
+    batch_size: usize,
+}
+
+impl RunCmd {
+    pub fn run(self) -> Result<()> {
+        let (ledger_db, state_merkle_db, state_kv_db) =
+            open_dbs(&self.db_dir, /*readonly=*/ false)?;
+        let migrations = all_migrations();
+        let migrator = SchemaMigrator::new(
+            MigrationDbs {
+                ledger_db: &ledger_db,
+                state_merkle_db: &state_merkle_db,
+                state_kv_db: &state_kv_db,
+            },
+            &migrations,
+        );
+
+        println!("Applying pending migrations...");
+        migrator.prepare(/*readonly=*/ false)?;
+        for migration in migrator.get_pending_backfills()? {
+            println!(
+                "Backfilling migration {} ({})...",
+                migration.version(),
+                migration.name()
+            );
+            loop {
+                let progress = migrator.backfill_batch(migration.as_ref(), self.batch_size)?;
+                println!(
+                    "Backfilled until version {} / {}.",
+                    progress.next_version, progress.target_version
+                );
+                if progress.is_done() {
+                    break;
+                }
+            }
+        }
+        println!("Done, schema version: {:?}", migrator.get_schema_version()?);
+
+        Ok(())
+    }
+}
+
+fn open_dbs(db_dir: &DbDir, readonly: bool) -> Result<(LedgerDb, StateMerkleDb, StateKvDb)> {
+    AptosDB::open_dbs(
+        &StorageDirPaths::from_path(db_dir),
+        RocksdbConfigs {
+            enable_storage_sharding: db_dir.sharding_config.enable_storage_sharding,
+            ..Default::default()
+        },
+        readonly,
+        /*max_num_nodes_per_lru_cache_shard=*/ 0,
+    )
+}
@@ -3,8 +3,10 @@
 
 pub mod checkpoint;
 mod common;
+pub mod diff;
 mod examine;
 pub mod ledger;
+pub mod migration;
 pub mod state_kv;
 pub mod state_tree;
 pub mod truncate;
@@ -38,11 +40,16 @@ pub enum Cmd {
     #[clap(subcommand)]
     Examine(examine::Cmd),
 
+    Diff(diff::Cmd),
+
     #[clap(subcommand)]
     IndexerValidation(validation::Cmd),
 
     #[clap(subcommand)]
     Watch(watch::Cmd),
+
+    #[clap(subcommand)]
+    Migration(migration::Cmd),
 }
 
 impl Cmd {
@@ -54,8 +61,10 @@ impl Cmd {
             Cmd::Ledger(cmd) => cmd.run(),
             Cmd::Truncate(cmd) => cmd.run(),
             Cmd::Examine(cmd) => cmd.run(),
+            Cmd::Diff(cmd) => cmd.run(),
             Cmd::IndexerValidation(cmd) => cmd.run(),
             Cmd::Watch(cmd) => cmd.run(),
+            Cmd::Migration(cmd) => cmd.run(),
         }
     }
 }
@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::AptosDB;
+use crate::{tiered_storage::ColdLedgerStore, AptosDB};
 use anyhow::anyhow;
 use aptos_config::config::{NodeConfig, StorageDirPaths};
 use aptos_crypto::HashValue;
@@ -16,7 +16,7 @@ use aptos_types::{
     transaction::{TransactionOutputListWithProofV2, Version},
 };
 use either::Either;
-use std::{sync::Arc, time::Instant};
+use std::{num::NonZeroUsize, sync::Arc, time::Instant};
 use tokio::sync::watch::Sender;
 pub const SECONDARY_DB_DIR: &str = "fast_sync_secondary";
 
@@ -44,21 +44,40 @@ impl FastSyncStorageWrapper {
         config: &NodeConfig,
         internal_indexer_db: Option<InternalIndexerDB>,
         update_sender: Option<Sender<(Instant, Version)>>,
+        cold_ledger_store: Option<Arc<dyn ColdLedgerStore>>,
     ) -> Result<Either<AptosDB, Self>> {
-        let mut db_main = AptosDB::open(
-            config.storage.get_dir_paths(),
-            /*readonly=*/ false,
-            config.storage.storage_pruner_config,
-            config.storage.rocksdb_configs,
-            config.storage.enable_indexer,
-            config.storage.buffered_state_target_items,
-            config.storage.max_num_nodes_per_lru_cache_shard,
-            internal_indexer_db,
-        )
+        let mut db_main = match cold_ledger_store {
+            Some(cold_ledger_store) => AptosDB::open_tiered(
+                config.storage.get_dir_paths(),
+                /*readonly=*/ false,
+                config.storage.storage_pruner_config,
+                config.storage.rocksdb_configs,
+                config.storage.enable_indexer,
+                config.storage.buffered_state_target_items,
+                config.storage.max_num_nodes_per_lru_cache_shard,
+                internal_indexer_db,
+                cold_ledger_store,
+                NonZeroUsize::new(config.storage.tiered_storage_config.chunk_cache_size)
+                    .ok_or_else(|| anyhow!("Tiered storage chunk_cache_size must be positive."))?,
+            ),
+            None => AptosDB::open(
+                config.storage.get_dir_paths(),
+                /*readonly=*/ false,
+                config.storage.storage_pruner_config,
+                config.storage.rocksdb_configs,
+                config.storage.enable_indexer,
+                config.storage.buffered_state_target_items,
+                config.storage.max_num_nodes_per_lru_cache_shard,
+                internal_indexer_db,
+            ),
+        }
         .map_err(|err| anyhow!("fast sync DB failed to open {}", err))?;
         if let Some(sender) = update_sender {
             db_main.add_version_update_subscriber(sender)?;
         }
+        if config.storage.consistency_checker_config.enable {
+            db_main.start_consistency_checker(config.storage.consistency_checker_config)?;
+        }
 
         let mut db_dir = config.storage.dir();
         // when the db is empty and configured to do fast sync, we will create a second DB
@@ -293,6 +293,41 @@ impl LedgerMetadataDb {
         Ok((block_version, block_height))
     }
 
+    /// Returns the first version of the first block at or below `max_block_height` with a
+    /// timestamp no earlier than `timestamp_usecs`, or None if there isn't such a block.
+    pub(crate) fn get_first_version_at_or_after_timestamp(
+        &self,
+        timestamp_usecs: u64,
+        max_block_height: BlockHeight,
+    ) -> Result<Option<Version>> {
+        let mut iter = self.db.iter::<BlockInfoSchema>()?;
+        iter.seek_to_first();
+        let Some((first_block_height, _)) = iter.next().transpose()? else {
+            return Ok(None);
+        };
+
+        // Finds the first block height in `[begin, end)` with a timestamp at or after the given
+        // one, given the timestamps are non-decreasing.
+        let mut begin = first_block_height;
+        let mut end = max_block_height + 1;
+        while begin < end {
+            let mid = begin + (end - begin) / 2;
+            let block_info = self
+                .get_block_info(mid)?
+                .ok_or_else(|| AptosDbError::NotFound(format!("BlockInfo at height {mid}")))?;
+            if block_info.timestamp_usecs() < timestamp_usecs {
+                begin = mid + 1;
+            } else {
+                end = mid;
+            }
+        }
+
+        if begin > max_block_height {
+            return Ok(None);
+        }
+        Ok(self.get_block_info(begin)?.map(|b| b.first_version()))
+    }
+
     pub(crate) fn put_block_info(
         version: Version,
         event: &ContractEvent,
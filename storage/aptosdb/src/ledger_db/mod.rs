@@ -23,6 +23,7 @@ use crate::{
         transaction_info_db::TransactionInfoDb, write_set_db::WriteSetDb,
     },
     schema::db_metadata::{DbMetadataKey, DbMetadataSchema},
+    tiered_storage::TieredLedgerStore,
 };
 use aptos_config::config::{RocksdbConfig, RocksdbConfigs};
 use aptos_experimental_runtimes::thread_manager::THREAD_MANAGER;
@@ -116,6 +117,7 @@ pub struct LedgerDb {
     transaction_info_db: TransactionInfoDb,
     write_set_db: WriteSetDb,
     enable_storage_sharding: bool,
+    tiered_store: Option<TieredLedgerStore>,
 }
 
 impl LedgerDb {
@@ -172,6 +174,7 @@ impl LedgerDb {
                 transaction_info_db: TransactionInfoDb::new(Arc::clone(&ledger_metadata_db)),
                 write_set_db: WriteSetDb::new(Arc::clone(&ledger_metadata_db)),
                 enable_storage_sharding: false,
+                tiered_store: None,
             });
         }
 
@@ -294,6 +297,7 @@ impl LedgerDb {
             transaction_info_db: transaction_info_db.unwrap(),
             write_set_db: write_set_db.unwrap(),
             enable_storage_sharding: true,
+            tiered_store: None,
         })
     }
 
@@ -301,6 +305,14 @@ impl LedgerDb {
         self.enable_storage_sharding
     }
 
+    pub(crate) fn set_tiered_store(&mut self, tiered_store: TieredLedgerStore) {
+        self.tiered_store = Some(tiered_store);
+    }
+
+    pub(crate) fn tiered_store(&self) -> Option<&TieredLedgerStore> {
+        self.tiered_store.as_ref()
+    }
+
     pub(crate) fn get_in_progress_state_kv_snapshot_version(&self) -> Result<Option<Version>> {
         let mut iter = self.ledger_metadata_db.db().iter::<DbMetadataSchema>()?;
         iter.seek_to_first();
@@ -16,12 +16,15 @@ pub use crate::db::AptosDB;
 
 pub mod backup;
 pub mod common;
+pub(crate) mod consistency_checker;
 pub mod db;
 pub mod get_restore_handler;
 pub mod metrics;
 pub(crate) mod rocksdb_property_reporter;
 pub mod schema;
+pub(crate) mod schema_migration;
 pub mod state_restore;
+pub mod tiered_storage;
 pub mod utils;
 
 #[cfg(feature = "db-debugger")]
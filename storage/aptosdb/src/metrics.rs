@@ -4,8 +4,8 @@
 
 use aptos_metrics_core::{
     exponential_buckets, make_thread_local_histogram_vec, make_thread_local_int_counter_vec,
-    register_histogram_vec, register_int_counter, register_int_gauge, register_int_gauge_vec,
-    HistogramVec, IntCounter, IntGauge, IntGaugeVec,
+    register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge,
+    register_int_gauge_vec, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -242,6 +242,46 @@ pub static GAUGE: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!("aptos_storage_gauge", "Various gauges", &["name"]).unwrap()
 });
 
+pub static CONSISTENCY_CHECK_FAILURES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "aptos_storage_consistency_check_failures",
+        // metric description
+        "Number of inconsistencies found by the background consistency checker.",
+        // metric labels (dimensions)
+        &["check"]
+    )
+    .unwrap()
+});
+
+pub static CONSISTENCY_CHECKED_VERSIONS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_storage_consistency_checked_versions",
+        "Number of versions verified by the background consistency checker."
+    )
+    .unwrap()
+});
+
+pub static CONSISTENCY_CHECKED_STATE_LEAVES: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_storage_consistency_checked_state_leaves",
+        "Number of state merkle leaves verified by the background consistency checker."
+    )
+    .unwrap()
+});
+
+pub static SCHEMA_MIGRATION_BACKFILL_PROGRESS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        // metric name
+        "aptos_storage_schema_migration_backfill_progress",
+        // metric description
+        "The first version not yet backfilled by a schema migration.",
+        // metric labels (dimensions)
+        &["migration"]
+    )
+    .unwrap()
+});
+
 make_thread_local_int_counter_vec!(
     pub,
     COUNTER,
@@ -37,4 +37,10 @@ pub trait DBPruner: Send + Sync {
     fn is_pruning_pending(&self) -> bool {
         self.target_version() > self.progress()
     }
+
+    /// True if the pending pruning work can't be done for now, because it waits for something
+    /// else to happen first.
+    fn is_waiting(&self) -> bool {
+        false
+    }
 }
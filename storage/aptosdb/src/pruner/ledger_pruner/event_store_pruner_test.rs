@@ -75,6 +75,7 @@ fn verify_event_store_pruner(events: Vec<Vec<ContractEvent>>) {
     let pruner = LedgerPrunerManager::new(Arc::clone(&aptos_db.ledger_db), LedgerPrunerConfig {
         enable: true,
         prune_window: 0,
+        prune_window_secs: None,
         batch_size: 1,
         user_pruning_window_offset: 0,
     });
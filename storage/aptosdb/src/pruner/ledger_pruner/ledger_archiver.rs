@@ -0,0 +1,159 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{ledger_db::LedgerDb, tiered_storage::ArchivedTransaction};
+use aptos_logger::{
+    error, info,
+    prelude::{sample, SampleRate},
+};
+use aptos_storage_interface::Result;
+use aptos_types::transaction::{AtomicVersion, PersistedAuxiliaryInfo, Version};
+use std::{
+    cmp::min,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{sleep, JoinHandle},
+    time::Duration,
+};
+
+/// Moves the ledger data to the cold tier ahead of the ledger pruner, on its own thread, so that
+/// slow uploads don't hold up the pruner. The ledger pruner only prunes versions that have been
+/// archived.
+pub(crate) struct LedgerArchiver {
+    worker_thread: Option<JoinHandle<()>>,
+    inner: Arc<LedgerArchiverInner>,
+}
+
+struct LedgerArchiverInner {
+    ledger_db: Arc<LedgerDb>,
+    /// Versions below this are to be archived.
+    target_version: AtomicVersion,
+    /// The max number of versions archived in one chunk.
+    batch_size: usize,
+    quit_worker: AtomicBool,
+}
+
+impl LedgerArchiver {
+    /// The archiver sleeps for this period of time when there is nothing to archive, or after an
+    /// error.
+    const IDLE_INTERVAL: Duration = Duration::from_millis(10);
+
+    pub(crate) fn new(ledger_db: Arc<LedgerDb>, progress: Version, batch_size: usize) -> Self {
+        assert!(
+            ledger_db.tiered_store().is_some(),
+            "Tiered storage must be enabled."
+        );
+        let inner = Arc::new(LedgerArchiverInner {
+            ledger_db,
+            target_version: AtomicVersion::new(progress),
+            batch_size,
+            quit_worker: AtomicBool::new(false),
+        });
+        let inner_cloned = Arc::clone(&inner);
+
+        let worker_thread = std::thread::Builder::new()
+            .name("ledger_archiver".into())
+            .spawn(move || inner_cloned.work(progress))
+            .expect("Creating archiver thread should succeed.");
+
+        Self {
+            worker_thread: Some(worker_thread),
+            inner,
+        }
+    }
+
+    pub(crate) fn set_target_version(&self, target_version: Version) {
+        self.inner
+            .target_version
+            .store(target_version, Ordering::SeqCst);
+    }
+}
+
+impl Drop for LedgerArchiver {
+    fn drop(&mut self) {
+        self.inner.quit_worker.store(true, Ordering::SeqCst);
+        self.worker_thread
+            .take()
+            .expect("Archiver thread must exist.")
+            .join()
+            .unwrap_or_else(|e| panic!("Archiver thread should join peacefully: {e:?}"));
+    }
+}
+
+impl LedgerArchiverInner {
+    fn work(&self, mut next_version: Version) {
+        while !self.quit_worker.load(Ordering::SeqCst) {
+            match self.archive_next_batch(&mut next_version) {
+                Ok(true) => (),
+                Ok(false) => sleep(LedgerArchiver::IDLE_INTERVAL),
+                Err(err) => {
+                    sample!(
+                        SampleRate::Duration(Duration::from_secs(1)),
+                        error!(error = ?err, "Ledger archiver has error.")
+                    );
+                    sleep(LedgerArchiver::IDLE_INTERVAL);
+                },
+            }
+        }
+    }
+
+    /// Archives the next batch of versions below the target that are not archived yet, starting
+    /// the search at `next_version`. Returns whether there was anything to archive.
+    fn archive_next_batch(&self, next_version: &mut Version) -> Result<bool> {
+        let tiered_store = self
+            .ledger_db
+            .tiered_store()
+            .expect("Tiered storage must be enabled.");
+        // Versions already archived (e.g., by a previous run) are skipped.
+        *next_version = tiered_store.store().archived_until(*next_version)?;
+        let target_version = self.target_version.load(Ordering::SeqCst);
+        if *next_version >= target_version {
+            return Ok(false);
+        }
+
+        let end = min(*next_version + self.batch_size as Version, target_version);
+        self.archive(*next_version, end)?;
+        *next_version = end;
+        Ok(true)
+    }
+
+    /// Moves the ledger data in `[begin, end)` to the cold tier.
+    fn archive(&self, begin: Version, end: Version) -> Result<()> {
+        let txns = (begin..end)
+            .map(|version| {
+                Ok(ArchivedTransaction {
+                    transaction: self.ledger_db.transaction_db().get_transaction(version)?,
+                    persisted_info: self
+                        .ledger_db
+                        .persisted_auxiliary_info_db()
+                        .get_persisted_auxiliary_info(version)?
+                        .unwrap_or(PersistedAuxiliaryInfo::None),
+                    transaction_info: self
+                        .ledger_db
+                        .transaction_info_db()
+                        .get_transaction_info(version)?,
+                    events: self.ledger_db.event_db().get_events_by_version(version)?,
+                    write_set: self.ledger_db.write_set_db().get_write_set(version)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let ledger_info = self.ledger_db.metadata_db().get_latest_ledger_info()?;
+        let range_proof = self
+            .ledger_db
+            .transaction_accumulator_db()
+            .get_transaction_range_proof(
+                Some(begin),
+                end - begin,
+                ledger_info.ledger_info().version(),
+            )?;
+
+        info!(begin = begin, end = end, "Archiving ledger data.");
+        self.ledger_db
+            .tiered_store()
+            .expect("Tiered storage must be enabled.")
+            .store()
+            .archive(begin, txns, (range_proof, ledger_info))
+    }
+}
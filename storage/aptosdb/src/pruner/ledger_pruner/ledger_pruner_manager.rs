@@ -5,16 +5,26 @@ use crate::{
     ledger_db::LedgerDb,
     metrics::{PRUNER_BATCH_SIZE, PRUNER_VERSIONS, PRUNER_WINDOW},
     pruner::{
-        ledger_pruner::LedgerPruner, pruner_manager::PrunerManager, pruner_utils,
+        db_pruner::DBPruner,
+        ledger_pruner::{category_retention, LedgerPruner},
+        pruner_manager::PrunerManager,
+        pruner_utils,
         pruner_worker::PrunerWorker,
     },
 };
-use aptos_config::config::LedgerPrunerConfig;
+use aptos_config::config::{LedgerPrunerConfig, LedgerRetention};
 use aptos_db_indexer::db_indexer::InternalIndexerDB;
 use aptos_infallible::Mutex;
-use aptos_storage_interface::Result;
+use aptos_logger::{
+    prelude::{sample, SampleRate},
+    warn,
+};
+use aptos_storage_interface::{AptosDbError, LedgerDataCategory, Result};
 use aptos_types::transaction::{AtomicVersion, Version};
-use std::sync::{atomic::Ordering, Arc};
+use std::{
+    sync::{atomic::Ordering, Arc},
+    time::Duration,
+};
 
 /// The `PrunerManager` for `LedgerPruner`.
 pub(crate) struct LedgerPrunerManager {
@@ -22,6 +32,10 @@ pub(crate) struct LedgerPrunerManager {
     /// DB version window, which dictates how many version of other stores like transaction, ledger
     /// info, events etc to keep.
     prune_window: Version,
+    /// How long the ledger data is kept, by versions or by age.
+    retention: LedgerRetention,
+    /// The latest version as of the last time the pruner target versions were updated.
+    last_target_update_version: AtomicVersion,
     /// It is None iff the pruner is not enabled.
     pruner_worker: Option<PrunerWorker>,
     /// Ideal batch size of the versions to be sent to the ledger pruner
@@ -32,6 +46,19 @@ pub(crate) struct LedgerPrunerManager {
     user_pruning_window_offset: u64,
     /// The minimal readable version for the ledger data.
     min_readable_version: AtomicVersion,
+    /// The pruner, to set the target versions of the categories with retention overrides.
+    pruner: Option<Arc<LedgerPruner>>,
+    /// The categories pruned on their own schedule per the retention overrides. Empty if the
+    /// pruner is not enabled.
+    category_retentions: Vec<CategoryRetention>,
+}
+
+struct CategoryRetention {
+    category: LedgerDataCategory,
+    retention: LedgerRetention,
+    /// The minimal readable version for this category, which can be smaller or larger than that
+    /// of the ledger data in general.
+    min_readable_version: AtomicVersion,
 }
 
 impl PrunerManager for LedgerPrunerManager {
@@ -51,7 +78,8 @@ impl PrunerManager for LedgerPrunerManager {
 
     fn get_min_viable_version(&self) -> Version {
         let min_version = self.get_min_readable_version();
-        if self.is_pruner_enabled() {
+        // There's no telling how many versions an age-based window will hold.
+        if self.is_pruner_enabled() && matches!(self.retention, LedgerRetention::Versions(_)) {
             let adjusted_window = self
                 .prune_window
                 .saturating_sub(self.user_pruning_window_offset);
@@ -66,13 +94,26 @@ impl PrunerManager for LedgerPrunerManager {
     fn maybe_set_pruner_target_db_version(&self, latest_version: Version) {
         *self.latest_version.lock() = latest_version;
 
-        let min_readable_version = self.get_min_readable_version();
-        // Only wake up the ledger pruner if there are `ledger_pruner_pruning_batch_size` pending
-        // versions.
-        if self.is_pruner_enabled()
-            && latest_version
-                >= min_readable_version + self.pruning_batch_size as u64 + self.prune_window
+        if !self.is_pruner_enabled() {
+            return;
+        }
+        let should_set_target = if matches!(self.retention, LedgerRetention::Versions(_))
+            && self.category_retentions.is_empty()
         {
+            // Only wake up the ledger pruner if there are `ledger_pruner_pruning_batch_size`
+            // pending versions.
+            latest_version
+                >= self.get_min_readable_version()
+                    + self.pruning_batch_size as u64
+                    + self.prune_window
+        } else {
+            // The windows of the categories and the age-based ones can't be told from the min
+            // readable version, so the targets are updated every batch of versions instead.
+            latest_version
+                >= self.last_target_update_version.load(Ordering::SeqCst)
+                    + self.pruning_batch_size as u64
+        };
+        if should_set_target {
             self.set_pruner_target_db_version(latest_version);
         }
     }
@@ -85,6 +126,12 @@ impl PrunerManager for LedgerPrunerManager {
             .with_label_values(&["ledger_pruner", "min_readable"])
             .set(min_readable_version as i64);
 
+        for category_retention in &self.category_retentions {
+            category_retention
+                .min_readable_version
+                .store(min_readable_version, Ordering::SeqCst);
+        }
+
         self.ledger_db.write_pruner_progress(min_readable_version)
     }
 
@@ -110,15 +157,20 @@ impl LedgerPrunerManager {
         ledger_pruner_config: LedgerPrunerConfig,
         internal_indexer_db: Option<InternalIndexerDB>,
     ) -> Self {
-        let pruner_worker = if ledger_pruner_config.enable {
-            Some(Self::init_pruner(
-                Arc::clone(&ledger_db),
-                ledger_pruner_config,
-                internal_indexer_db,
-            ))
-        } else {
-            None
-        };
+        let pruner = ledger_pruner_config.enable.then(|| {
+            Arc::new(
+                LedgerPruner::new(
+                    Arc::clone(&ledger_db),
+                    internal_indexer_db,
+                    ledger_pruner_config.retention_overrides,
+                    ledger_pruner_config.batch_size,
+                )
+                .expect("Failed to create ledger pruner."),
+            )
+        });
+        let pruner_worker = pruner
+            .as_ref()
+            .map(|pruner| Self::init_pruner(Arc::clone(pruner), ledger_pruner_config));
 
         let min_readable_version =
             pruner_utils::get_ledger_pruner_progress(&ledger_db).expect("Must succeed.");
@@ -127,27 +179,67 @@ impl LedgerPrunerManager {
             .with_label_values(&["ledger_pruner", "min_readable"])
             .set(min_readable_version as i64);
 
+        let category_retentions = pruner
+            .as_ref()
+            .map(|pruner| {
+                pruner
+                    .get_category_progress()
+                    .into_iter()
+                    .map(|(category, progress)| CategoryRetention {
+                        category,
+                        // Only the transaction infos can be pruned separately without an
+                        // override, when they are kept for the longest of all the windows.
+                        retention: category_retention(
+                            category,
+                            &ledger_pruner_config.retention_overrides,
+                        )
+                        .unwrap_or_else(|| ledger_pruner_config.retention()),
+                        min_readable_version: AtomicVersion::new(progress),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
         Self {
             ledger_db,
             prune_window: ledger_pruner_config.prune_window,
+            retention: ledger_pruner_config.retention(),
+            last_target_update_version: AtomicVersion::new(min_readable_version),
             pruner_worker,
             pruning_batch_size: ledger_pruner_config.batch_size,
             latest_version: Arc::new(Mutex::new(min_readable_version)),
             user_pruning_window_offset: ledger_pruner_config.user_pruning_window_offset,
             min_readable_version: AtomicVersion::new(min_readable_version),
+            pruner,
+            category_retentions,
         }
     }
 
+    /// Returns the version below which the ledger data is actually gone from the DB, which lags
+    /// behind the min readable version while pruning is pending.
+    pub(crate) fn get_pruner_progress(&self) -> Version {
+        self.pruner.as_ref().map_or_else(
+            || self.get_min_readable_version(),
+            |pruner| pruner.progress(),
+        )
+    }
+
+    /// Returns the minimal readable version of a category of ledger data, which can differ from
+    /// `get_min_readable_version()` if the category has a retention override.
+    pub(crate) fn get_min_readable_version_for(&self, category: LedgerDataCategory) -> Version {
+        self.category_retentions
+            .iter()
+            .find(|r| r.category == category)
+            .map_or_else(
+                || self.get_min_readable_version(),
+                |r| r.min_readable_version.load(Ordering::SeqCst),
+            )
+    }
+
     fn init_pruner(
-        ledger_db: Arc<LedgerDb>,
+        pruner: Arc<LedgerPruner>,
         ledger_pruner_config: LedgerPrunerConfig,
-        internal_indexer_db: Option<InternalIndexerDB>,
     ) -> PrunerWorker {
-        let pruner = Arc::new(
-            LedgerPruner::new(ledger_db, internal_indexer_db)
-                .expect("Failed to create ledger pruner."),
-        );
-
         PRUNER_WINDOW
             .with_label_values(&["ledger_pruner"])
             .set(ledger_pruner_config.prune_window as i64);
@@ -161,7 +253,15 @@ impl LedgerPrunerManager {
 
     fn set_pruner_target_db_version(&self, latest_version: Version) {
         assert!(self.pruner_worker.is_some());
-        let min_readable_version = latest_version.saturating_sub(self.prune_window);
+        self.last_target_update_version
+            .store(latest_version, Ordering::SeqCst);
+
+        let current_min_readable_version = self.get_min_readable_version();
+        let min_readable_version = self
+            .get_target_version(self.retention, latest_version, "ledger")
+            .map_or(current_min_readable_version, |target_version| {
+                std::cmp::max(target_version, current_min_readable_version)
+            });
         self.min_readable_version
             .store(min_readable_version, Ordering::SeqCst);
 
@@ -169,9 +269,113 @@ impl LedgerPrunerManager {
             .with_label_values(&["ledger_pruner", "min_readable"])
             .set(min_readable_version as i64);
 
+        // Set before the target of the ledger pruner, which wakes up the categories as well.
+        self.set_category_target_versions(latest_version, min_readable_version);
+
         self.pruner_worker
             .as_ref()
             .unwrap()
             .set_target_db_version(min_readable_version);
     }
+
+    fn set_category_target_versions(&self, latest_version: Version, min_readable_version: Version) {
+        let pruner = self.pruner.as_ref().expect("Pruner must be enabled.");
+        let mut target_versions = Vec::with_capacity(self.category_retentions.len());
+        for category_retention in &self.category_retentions {
+            target_versions.push(self.get_target_version(
+                category_retention.retention,
+                latest_version,
+                category_retention.category.name(),
+            ));
+        }
+
+        for (category_retention, target_version) in
+            self.category_retentions.iter().zip(&target_versions)
+        {
+            let Some(mut target_version) = *target_version else {
+                continue;
+            };
+            if category_retention.category == LedgerDataCategory::TransactionInfos {
+                // The transaction infos and the accumulator prove the data of all the categories,
+                // so they are kept for the longest of all the windows.
+                if target_versions.iter().any(Option::is_none) {
+                    continue;
+                }
+                target_version = target_versions
+                    .iter()
+                    .flatten()
+                    .fold(min_readable_version, |min, v| std::cmp::min(min, *v));
+            }
+            if target_version
+                <= category_retention
+                    .min_readable_version
+                    .load(Ordering::SeqCst)
+            {
+                continue;
+            }
+
+            category_retention
+                .min_readable_version
+                .store(target_version, Ordering::SeqCst);
+            PRUNER_VERSIONS
+                .with_label_values(&[category_retention.category.name(), "min_readable"])
+                .set(target_version as i64);
+            pruner.set_category_target_version(category_retention.category, target_version);
+        }
+    }
+
+    /// Returns the version below which the data kept per `retention` can be pruned, or `None` if
+    /// nothing can be pruned.
+    fn get_target_version(
+        &self,
+        retention: LedgerRetention,
+        latest_version: Version,
+        name: &str,
+    ) -> Option<Version> {
+        match retention {
+            LedgerRetention::Versions(window) => Some(latest_version.saturating_sub(window)),
+            LedgerRetention::Seconds(window_secs) => {
+                match self.get_first_version_in_time_window(latest_version, window_secs) {
+                    Ok(version) => Some(version),
+                    Err(err) => {
+                        sample!(
+                            SampleRate::Duration(Duration::from_secs(60)),
+                            warn!(
+                                category = name,
+                                error = ?err,
+                                "Failed to determine the ledger pruner target by age."
+                            )
+                        );
+                        None
+                    },
+                }
+            },
+            LedgerRetention::Forever => None,
+        }
+    }
+
+    /// Returns the first version of the first block committed within `window_secs` before the
+    /// block containing `latest_version`, according to the block timestamps.
+    fn get_first_version_in_time_window(
+        &self,
+        latest_version: Version,
+        window_secs: u64,
+    ) -> Result<Version> {
+        let metadata_db = self.ledger_db.metadata_db();
+        let latest_block_height = metadata_db.get_block_height_by_version(latest_version)?;
+        let latest_timestamp_usecs = metadata_db
+            .get_block_info(latest_block_height)?
+            .ok_or_else(|| {
+                AptosDbError::NotFound(format!("BlockInfo at height {latest_block_height}"))
+            })?
+            .timestamp_usecs();
+        let timestamp_usecs =
+            latest_timestamp_usecs.saturating_sub(window_secs.saturating_mul(1_000_000));
+
+        metadata_db
+            .get_first_version_at_or_after_timestamp(timestamp_usecs, latest_block_height)?
+            .ok_or_else(|| {
+                AptosDbError::NotFound(format!("Block at or after timestamp {timestamp_usecs}"))
+            })
+    }
 }
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod event_store_pruner;
+mod ledger_archiver;
 mod ledger_metadata_pruner;
 pub(crate) mod ledger_pruner_manager;
 mod persisted_auxiliary_info_pruner;
@@ -18,21 +19,25 @@ use crate::{
         db_pruner::DBPruner,
         db_sub_pruner::DBSubPruner,
         ledger_pruner::{
-            event_store_pruner::EventStorePruner, ledger_metadata_pruner::LedgerMetadataPruner,
+            event_store_pruner::EventStorePruner, ledger_archiver::LedgerArchiver,
+            ledger_metadata_pruner::LedgerMetadataPruner,
             persisted_auxiliary_info_pruner::PersistedAuxiliaryInfoPruner,
             transaction_accumulator_pruner::TransactionAccumulatorPruner,
             transaction_auxiliary_data_pruner::TransactionAuxiliaryDataPruner,
             transaction_info_pruner::TransactionInfoPruner, transaction_pruner::TransactionPruner,
             write_set_pruner::WriteSetPruner,
         },
+        pruner_utils::get_or_initialize_subpruner_progress,
     },
+    schema::db_metadata::DbMetadataKey,
     transaction_store::TransactionStore,
 };
 use anyhow::anyhow;
+use aptos_config::config::{LedgerRetention, LedgerRetentionOverrides};
 use aptos_db_indexer::db_indexer::InternalIndexerDB;
 use aptos_experimental_runtimes::thread_manager::THREAD_MANAGER;
 use aptos_logger::info;
-use aptos_storage_interface::Result;
+use aptos_storage_interface::{LedgerDataCategory, Result};
 use aptos_types::transaction::{AtomicVersion, Version};
 use rayon::prelude::*;
 use std::{
@@ -49,9 +54,111 @@ pub(crate) struct LedgerPruner {
 
     progress: AtomicVersion,
 
+    ledger_db: Arc<LedgerDb>,
+
     ledger_metadata_pruner: Box<LedgerMetadataPruner>,
 
     sub_pruners: Vec<Box<dyn DBSubPruner + Send + Sync>>,
+
+    /// Sub pruners of the categories pruned on their own schedule per the retention overrides.
+    category_sub_pruners: Vec<CategorySubPruner>,
+
+    /// Archives the ledger data before it's pruned, iff tiered storage is enabled.
+    archiver: Option<LedgerArchiver>,
+}
+
+/// Returns the retention override of a category, if any.
+pub(crate) fn category_retention(
+    category: LedgerDataCategory,
+    overrides: &LedgerRetentionOverrides,
+) -> Option<LedgerRetention> {
+    match category {
+        LedgerDataCategory::Events => overrides.events,
+        LedgerDataCategory::Transactions => overrides.transactions,
+        LedgerDataCategory::TransactionInfos => overrides.transaction_infos,
+        LedgerDataCategory::TransactionAuxiliaryData => overrides.transaction_auxiliary_data,
+        LedgerDataCategory::PersistedAuxiliaryInfo => overrides.persisted_auxiliary_info,
+        LedgerDataCategory::WriteSets => overrides.write_sets,
+    }
+}
+
+fn get_category_pruner_progress(
+    category: LedgerDataCategory,
+    ledger_db: &LedgerDb,
+    metadata_progress: Version,
+) -> Result<Version> {
+    let (db, key) = match category {
+        LedgerDataCategory::Events => {
+            (ledger_db.event_db_raw(), DbMetadataKey::EventPrunerProgress)
+        },
+        LedgerDataCategory::Transactions => (
+            ledger_db.transaction_db_raw(),
+            DbMetadataKey::TransactionPrunerProgress,
+        ),
+        LedgerDataCategory::TransactionInfos => (
+            ledger_db.transaction_info_db_raw(),
+            DbMetadataKey::TransactionInfoPrunerProgress,
+        ),
+        LedgerDataCategory::TransactionAuxiliaryData => (
+            ledger_db.transaction_auxiliary_data_db_raw(),
+            DbMetadataKey::TransactionAuxiliaryDataPrunerProgress,
+        ),
+        LedgerDataCategory::PersistedAuxiliaryInfo => (
+            ledger_db.persisted_auxiliary_info_db_raw(),
+            DbMetadataKey::PersistedAuxiliaryInfoPrunerProgress,
+        ),
+        LedgerDataCategory::WriteSets => (
+            ledger_db.write_set_db_raw(),
+            DbMetadataKey::WriteSetPrunerProgress,
+        ),
+    };
+    Ok(get_or_initialize_subpruner_progress(
+        db,
+        &key,
+        metadata_progress,
+    )?)
+}
+
+/// Sub pruners that keep their own progress, apart from the ledger pruner, because their category
+/// of data is retained for longer or shorter than the ledger prune window.
+struct CategorySubPruner {
+    category: LedgerDataCategory,
+    sub_pruners: Vec<Box<dyn DBSubPruner + Send + Sync>>,
+    progress: AtomicVersion,
+    target_version: AtomicVersion,
+}
+
+impl CategorySubPruner {
+    /// Prunes towards the target version, but never beyond `max_target_version`.
+    fn prune(&self, max_versions: usize, max_target_version: Version) -> Result<()> {
+        let mut progress = self.progress.load(Ordering::SeqCst);
+        let target_version = min(
+            self.target_version.load(Ordering::SeqCst),
+            max_target_version,
+        );
+
+        while progress < target_version {
+            let current_batch_target_version =
+                min(progress + max_versions as Version, target_version);
+            for sub_pruner in &self.sub_pruners {
+                sub_pruner
+                    .prune(progress, current_batch_target_version)
+                    .map_err(|err| anyhow!("{} failed to prune: {err}", sub_pruner.name()))?;
+            }
+
+            progress = current_batch_target_version;
+            self.progress.store(progress, Ordering::SeqCst);
+            PRUNER_VERSIONS
+                .with_label_values(&[self.category.name(), "progress"])
+                .set(progress as i64);
+        }
+
+        Ok(())
+    }
+
+    fn is_pruning_pending(&self) -> bool {
+        self.target_version.load(Ordering::SeqCst) > self.progress.load(Ordering::SeqCst)
+    }
 }
 
 impl DBPruner for LedgerPruner {
@@ -61,7 +168,7 @@ impl DBPruner for LedgerPruner {
 
     fn prune(&self, max_versions: usize) -> Result<Version> {
         let mut progress = self.progress();
-        let target_version = self.target_version();
+        let target_version = min(self.target_version(), self.archived_until(progress)?);
 
         while progress < target_version {
             let current_batch_target_version =
@@ -88,6 +195,21 @@ impl DBPruner for LedgerPruner {
             info!(progress = progress, "Pruning ledger data is done.");
         }
 
+        // With tiered storage, nothing can be pruned before it's archived, which happens in
+        // the order of the versions, so the categories kept longer can't go beyond the rest.
+        let max_category_target_version = if self.ledger_db.tiered_store().is_some() {
+            progress
+        } else {
+            Version::MAX
+        };
+        THREAD_MANAGER.get_background_pool().install(|| {
+            self.category_sub_pruners
+                .par_iter()
+                .try_for_each(|sub_pruner| {
+                    sub_pruner.prune(max_versions, max_category_target_version)
+                })
+        })?;
+
         Ok(target_version)
     }
 
@@ -97,6 +219,9 @@ impl DBPruner for LedgerPruner {
 
     fn set_target_version(&self, target_version: Version) {
         self.target_version.store(target_version, Ordering::SeqCst);
+        if let Some(archiver) = &self.archiver {
+            archiver.set_target_version(target_version);
+        }
         PRUNER_VERSIONS
             .with_label_values(&["ledger_pruner", "target"])
             .set(target_version as i64);
@@ -112,12 +237,28 @@ impl DBPruner for LedgerPruner {
             .with_label_values(&["ledger_pruner", "progress"])
             .set(progress as i64);
     }
+
+    fn is_waiting(&self) -> bool {
+        let progress = self.progress();
+        self.target_version() > progress
+            && !matches!(self.archived_until(progress), Ok(version) if version > progress)
+    }
+
+    fn is_pruning_pending(&self) -> bool {
+        self.target_version() > self.progress()
+            || self
+                .category_sub_pruners
+                .iter()
+                .any(|sub_pruner| sub_pruner.is_pruning_pending())
+    }
 }
 
 impl LedgerPruner {
     pub fn new(
         ledger_db: Arc<LedgerDb>,
         internal_indexer_db: Option<InternalIndexerDB>,
+        retention_overrides: LedgerRetentionOverrides,
+        batch_size: usize,
     ) -> Result<Self> {
         info!(name = LEDGER_PRUNER_NAME, "Initializing...");
 
@@ -134,54 +275,91 @@ impl LedgerPruner {
         );
 
         let transaction_store = Arc::new(TransactionStore::new(Arc::clone(&ledger_db)));
+        let prune_by_hash_index = retention_overrides.transaction_by_hash.is_none();
 
-        let event_store_pruner = Box::new(EventStorePruner::new(
-            Arc::clone(&ledger_db),
-            metadata_progress,
-            internal_indexer_db.clone(),
-        )?);
-        let persisted_auxiliary_info_pruner = Box::new(PersistedAuxiliaryInfoPruner::new(
-            Arc::clone(&ledger_db),
-            metadata_progress,
-        )?);
-        let transaction_accumulator_pruner = Box::new(TransactionAccumulatorPruner::new(
-            Arc::clone(&ledger_db),
-            metadata_progress,
-        )?);
-
-        let transaction_auxiliary_data_pruner = Box::new(TransactionAuxiliaryDataPruner::new(
-            Arc::clone(&ledger_db),
-            metadata_progress,
-        )?);
-
-        let transaction_info_pruner = Box::new(TransactionInfoPruner::new(
-            Arc::clone(&ledger_db),
-            metadata_progress,
-        )?);
-        let transaction_pruner = Box::new(TransactionPruner::new(
-            Arc::clone(&transaction_store),
-            Arc::clone(&ledger_db),
-            metadata_progress,
-            internal_indexer_db,
-        )?);
-        let write_set_pruner = Box::new(WriteSetPruner::new(
-            Arc::clone(&ledger_db),
-            metadata_progress,
-        )?);
+        let has_category_overrides = LedgerDataCategory::ALL
+            .into_iter()
+            .any(|category| category_retention(category, &retention_overrides).is_some());
+
+        let mut sub_pruners: Vec<Box<dyn DBSubPruner + Send + Sync>> = Vec::new();
+        let mut category_sub_pruners = Vec::new();
+        for category in LedgerDataCategory::ALL {
+            // With tiered storage, the auxiliary data is kept, since the cold tier doesn't hold
+            // it (it's not part of the transaction backup format).
+            if category == LedgerDataCategory::TransactionAuxiliaryData
+                && ledger_db.tiered_store().is_some()
+            {
+                continue;
+            }
+            // The transaction infos prove the data of all categories, so they are kept for the
+            // longest of all the windows, which may not be that of the ledger pruner.
+            let pruned_separately = category_retention(category, &retention_overrides).is_some()
+                || (has_category_overrides && category == LedgerDataCategory::TransactionInfos);
+            if !pruned_separately {
+                sub_pruners.push(Self::create_sub_pruner(
+                    category,
+                    &ledger_db,
+                    &transaction_store,
+                    &internal_indexer_db,
+                    prune_by_hash_index,
+                    metadata_progress,
+                )?);
+            } else {
+                // Kept for longer or shorter than the rest, so the sub pruner starts from its own
+                // progress instead of catching up with the metadata pruner.
+                let progress =
+                    get_category_pruner_progress(category, &ledger_db, metadata_progress)?;
+                info!(
+                    category = category.name(),
+                    progress = progress,
+                    "Sub pruner is scheduled apart from the ledger pruner per retention overrides."
+                );
+                let mut category_pruners = vec![Self::create_sub_pruner(
+                    category,
+                    &ledger_db,
+                    &transaction_store,
+                    &internal_indexer_db,
+                    prune_by_hash_index,
+                    progress,
+                )?];
+                // The accumulator is needed along with the transaction infos for the proofs.
+                if category == LedgerDataCategory::TransactionInfos
+                    && ledger_db.tiered_store().is_none()
+                {
+                    category_pruners.push(Box::new(TransactionAccumulatorPruner::new(
+                        Arc::clone(&ledger_db),
+                        progress,
+                    )?));
+                }
+                category_sub_pruners.push(CategorySubPruner {
+                    category,
+                    sub_pruners: category_pruners,
+                    progress: AtomicVersion::new(progress),
+                    target_version: AtomicVersion::new(progress),
+                });
+            }
+        }
+        // With tiered storage, the accumulator is kept to serve proofs for archived versions.
+        if ledger_db.tiered_store().is_none() && !has_category_overrides {
+            sub_pruners.push(Box::new(TransactionAccumulatorPruner::new(
+                Arc::clone(&ledger_db),
+                metadata_progress,
+            )?));
+        }
+
+        let archiver = ledger_db
+            .tiered_store()
+            .is_some()
+            .then(|| LedgerArchiver::new(Arc::clone(&ledger_db), metadata_progress, batch_size));
 
         let pruner = LedgerPruner {
             target_version: AtomicVersion::new(metadata_progress),
             progress: AtomicVersion::new(metadata_progress),
+            ledger_db,
             ledger_metadata_pruner,
-            sub_pruners: vec![
-                event_store_pruner,
-                persisted_auxiliary_info_pruner,
-                transaction_accumulator_pruner,
-                transaction_auxiliary_data_pruner,
-                transaction_info_pruner,
-                transaction_pruner,
-                write_set_pruner,
-            ],
+            sub_pruners,
+            category_sub_pruners,
+            archiver,
         };
 
         info!(
@@ -192,4 +370,76 @@ impl LedgerPruner {
 
         Ok(pruner)
     }
+
+    fn create_sub_pruner(
+        category: LedgerDataCategory,
+        ledger_db: &Arc<LedgerDb>,
+        transaction_store: &Arc<TransactionStore>,
+        internal_indexer_db: &Option<InternalIndexerDB>,
+        prune_by_hash_index: bool,
+        metadata_progress: Version,
+    ) -> Result<Box<dyn DBSubPruner + Send + Sync>> {
+        let ledger_db = Arc::clone(ledger_db);
+        Ok(match category {
+            LedgerDataCategory::Events => Box::new(EventStorePruner::new(
+                ledger_db,
+                metadata_progress,
+                internal_indexer_db.clone(),
+            )?),
+            LedgerDataCategory::Transactions => Box::new(TransactionPruner::new(
+                Arc::clone(transaction_store),
+                ledger_db,
+                metadata_progress,
+                internal_indexer_db.clone(),
+                prune_by_hash_index,
+            )?),
+            LedgerDataCategory::TransactionInfos => {
+                Box::new(TransactionInfoPruner::new(ledger_db, metadata_progress)?)
+            },
+            LedgerDataCategory::TransactionAuxiliaryData => Box::new(
+                TransactionAuxiliaryDataPruner::new(ledger_db, metadata_progress)?,
+            ),
+            LedgerDataCategory::PersistedAuxiliaryInfo => Box::new(
+                PersistedAuxiliaryInfoPruner::new(ledger_db, metadata_progress)?,
+            ),
+            LedgerDataCategory::WriteSets => {
+                Box::new(WriteSetPruner::new(ledger_db, metadata_progress)?)
+            },
+        })
+    }
+
+    /// Returns the progress of the categories pruned separately per the retention overrides.
+    pub(crate) fn get_category_progress(&self) -> Vec<(LedgerDataCategory, Version)> {
+        self.category_sub_pruners
+            .iter()
+            .map(|p| (p.category, p.progress.load(Ordering::SeqCst)))
+            .collect()
+    }
+
+    /// Sets the target version of a category pruned separately per the retention overrides.
+    pub(crate) fn set_category_target_version(
+        &self,
+        category: LedgerDataCategory,
+        target_version: Version,
+    ) {
+        if let Some(p) = self
+            .category_sub_pruners
+            .iter()
+            .find(|p| p.category == category)
+        {
+            p.target_version.store(target_version, Ordering::SeqCst);
+            PRUNER_VERSIONS
+                .with_label_values(&[p.category.name(), "target"])
+                .set(target_version as i64);
+        }
+    }
+
+    /// Returns the first version at or after `version` that is not archived yet, which the pruner
+    /// must not go beyond if tiered storage is enabled.
+    fn archived_until(&self, version: Version) -> Result<Version> {
+        match self.ledger_db.tiered_store() {
+            Some(tiered_store) => tiered_store.store().archived_until(version),
+            None => Ok(Version::MAX),
+        }
+    }
 }
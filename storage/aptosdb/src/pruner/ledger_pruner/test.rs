@@ -52,6 +52,7 @@ fn verify_write_set_pruner(write_sets: Vec<WriteSet>) {
     let pruner = LedgerPrunerManager::new(Arc::clone(&aptos_db.ledger_db), LedgerPrunerConfig {
         enable: true,
         prune_window: 0,
+        prune_window_secs: None,
         batch_size: 1,
         user_pruning_window_offset: 0,
     });
@@ -125,6 +126,7 @@ fn verify_txn_store_pruner(
             LedgerPrunerManager::new(Arc::clone(&aptos_db.ledger_db), LedgerPrunerConfig {
                 enable: true,
                 prune_window: 0,
+                prune_window_secs: None,
                 batch_size: 1,
                 user_pruning_window_offset: 0,
             });
@@ -27,6 +27,8 @@ pub struct TransactionPruner {
     transaction_store: Arc<TransactionStore>,
     ledger_db: Arc<LedgerDb>,
     internal_indexer_db: Option<InternalIndexerDB>,
+    /// False if the transaction by hash index is kept forever.
+    prune_by_hash_index: bool,
 }
 
 impl DBSubPruner for TransactionPruner {
@@ -38,12 +40,14 @@ impl DBSubPruner for TransactionPruner {
         let mut batch = SchemaBatch::new();
         let candidate_transactions =
             self.get_pruning_candidate_transactions(current_progress, target_version)?;
-        self.ledger_db
-            .transaction_db()
-            .prune_transaction_by_hash_indices(
-                candidate_transactions.iter().map(|(_, txn)| txn.hash()),
-                &mut batch,
-            )?;
+        if self.prune_by_hash_index {
+            self.ledger_db
+                .transaction_db()
+                .prune_transaction_by_hash_indices(
+                    candidate_transactions.iter().map(|(_, txn)| txn.hash()),
+                    &mut batch,
+                )?;
+        }
         self.ledger_db.transaction_db().prune_transactions(
             current_progress,
             target_version,
@@ -80,6 +84,7 @@ impl TransactionPruner {
         ledger_db: Arc<LedgerDb>,
         metadata_progress: Version,
         internal_indexer_db: Option<InternalIndexerDB>,
+        prune_by_hash_index: bool,
     ) -> Result<Self> {
         let progress = get_or_initialize_subpruner_progress(
             ledger_db.transaction_db_raw(),
@@ -91,6 +96,7 @@ impl TransactionPruner {
             transaction_store,
             ledger_db,
             internal_indexer_db,
+            prune_by_hash_index,
         };
 
         info!(
@@ -62,7 +62,7 @@ impl PrunerWorkerInner {
                 sleep(Duration::from_millis(self.pruning_time_interval_in_ms));
                 continue;
             }
-            if !self.pruner.is_pruning_pending() {
+            if !self.pruner.is_pruning_pending() || self.pruner.is_waiting() {
                 sleep(Duration::from_millis(self.pruning_time_interval_in_ms));
             }
         }
@@ -322,8 +322,10 @@ fn verify_state_value_pruner(inputs: Vec<Vec<(StateKey, Option<StateValue>)>>) {
     let pruner = StateKvPrunerManager::new(Arc::clone(&db.state_kv_db), LedgerPrunerConfig {
         enable: true,
         prune_window: 0,
+        prune_window_secs: None,
         batch_size: 1,
         user_pruning_window_offset: 0,
+        retention_overrides: Default::default(),
     });
     for batch in inputs {
         update_store(store, batch.clone().into_iter(), version);
@@ -9,7 +9,7 @@
 //! ```
 //!
 
-use crate::schema::DB_METADATA_CF_NAME;
+use crate::{schema::DB_METADATA_CF_NAME, schema_migration::BackfillProgress};
 use anyhow::Result;
 use aptos_db_indexer_schemas::metadata::StateSnapshotProgress;
 use aptos_schemadb::{
@@ -26,6 +26,8 @@ type ShardId = usize;
 pub(crate) enum DbMetadataValue {
     Version(Version),
     StateSnapshotProgress(StateSnapshotProgress),
+    SchemaVersion(u64),
+    BackfillProgress(BackfillProgress),
 }
 
 impl DbMetadataValue {
@@ -42,6 +44,20 @@ impl DbMetadataValue {
             _ => unreachable!("expected KeyHashAndUsage, got {:?}", self),
         }
     }
+
+    pub fn expect_schema_version(self) -> u64 {
+        match self {
+            Self::SchemaVersion(schema_version) => schema_version,
+            _ => unreachable!("expected SchemaVersion, got {:?}", self),
+        }
+    }
+
+    pub fn expect_backfill_progress(self) -> BackfillProgress {
+        match self {
+            Self::BackfillProgress(progress) => progress,
+            _ => unreachable!("expected BackfillProgress, got {:?}", self),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -69,6 +85,8 @@ pub enum DbMetadataKey {
     StateMerkleShardRestoreProgress(ShardId, Version),
     TransactionAuxiliaryDataPrunerProgress,
     PersistedAuxiliaryInfoPrunerProgress,
+    SchemaVersion,
+    SchemaMigrationBackfillProgress(u64),
 }
 
 define_schema!(
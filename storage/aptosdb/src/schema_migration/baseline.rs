@@ -0,0 +1,23 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::schema_migration::{MigrationDbs, SchemaMigration};
+use aptos_storage_interface::Result;
+
+/// The schemas as of the introduction of the migration framework. Applying it only records the
+/// schema version in DBs created before that, which are considered at version 0.
+pub(crate) struct BaselineMigration;
+
+impl SchemaMigration for BaselineMigration {
+    fn version(&self) -> u64 {
+        1
+    }
+
+    fn name(&self) -> &'static str {
+        "baseline"
+    }
+
+    fn up(&self, _dbs: &MigrationDbs) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,392 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A versioned migration framework for the schemas of AptosDB.
+//!
+//! Every change to the column families or the encoding of their content is a [`SchemaMigration`]
+//! with a unique, increasing version. When the DB is opened, the pending migrations are applied
+//! in order by running their `up` step, which is expected to be cheap (e.g. rewriting some
+//! metadata). Migrations that need to rewrite the history do it in their `backfill` step, which
+//! runs in the background in batches of versions, with the progress persisted in
+//! `DbMetadataSchema` so it resumes across restarts. Versions committed after a migration is
+//! applied are supposed to be written in the new format by the regular write path.
+//!
+//! A node refuses to open a DB with a schema version newer than what it knows about, i.e. one
+//! that's been migrated by a newer release.
+//!
+//! To add a new column family, declare it along with the others of its DB (e.g. in
+//! `ledger_db`), which creates it on open, and register a migration here if the existing data
+//! needs to be reflected in it.
+
+use crate::{
+    ledger_db::LedgerDb,
+    metrics::SCHEMA_MIGRATION_BACKFILL_PROGRESS,
+    schema::db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+    schema_migration::baseline::BaselineMigration,
+    state_kv_db::StateKvDb,
+    state_merkle_db::StateMerkleDb,
+};
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_schemadb::batch::SchemaBatch;
+use aptos_storage_interface::{db_ensure as ensure, AptosDbError, Result};
+use aptos_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{mpsc, Arc},
+    thread::JoinHandle,
+    time::Duration,
+};
+
+mod baseline;
+#[cfg(test)]
+mod test;
+
+/// The number of versions backfilled in one go by the background worker.
+pub(crate) const BACKFILL_BATCH_SIZE: usize = 10_000;
+
+/// The DBs a migration operates on.
+pub struct MigrationDbs<'a> {
+    pub ledger_db: &'a LedgerDb,
+    pub state_merkle_db: &'a StateMerkleDb,
+    pub state_kv_db: &'a StateKvDb,
+}
+
+/// A change to the schemas of the DB.
+pub trait SchemaMigration: Send + Sync {
+    /// The schema version the DB is at after this migration is applied. Must be unique and
+    /// larger than that of all the migrations registered before it.
+    fn version(&self) -> u64;
+
+    fn name(&self) -> &'static str;
+
+    /// Brings the DB to the new schema version, except for the historical data handled by
+    /// `backfill`. Can be run again if the process crashes before the new schema version is
+    /// recorded, so it must be idempotent.
+    fn up(&self, dbs: &MigrationDbs) -> Result<()>;
+
+    /// Whether `backfill` needs to be run for the versions committed before the migration.
+    fn has_backfill(&self) -> bool {
+        false
+    }
+
+    /// Migrates the data of versions in `[begin, end)`. Batches are processed in order, and a
+    /// batch can be processed again if the process crashes before the progress is recorded.
+    fn backfill(&self, _dbs: &MigrationDbs, _begin: Version, _end: Version) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The migrations known to this release, in the order of their versions.
+pub fn all_migrations() -> Vec<Arc<dyn SchemaMigration>> {
+    vec![Arc::new(BaselineMigration)]
+}
+
+/// The backfill progress of a migration, persisted in `DbMetadataSchema`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(proptest_derive::Arbitrary))]
+pub struct BackfillProgress {
+    /// The first version not yet backfilled.
+    pub next_version: Version,
+    /// The first version committed after the migration was applied, which doesn't need to be
+    /// backfilled.
+    pub target_version: Version,
+}
+
+impl BackfillProgress {
+    pub fn is_done(&self) -> bool {
+        self.next_version >= self.target_version
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MigrationState {
+    Pending,
+    Backfilling(BackfillProgress),
+    Done,
+}
+
+/// Applies and backfills the migrations of a DB.
+pub struct SchemaMigrator<'a> {
+    dbs: MigrationDbs<'a>,
+    migrations: &'a [Arc<dyn SchemaMigration>],
+}
+
+impl<'a> SchemaMigrator<'a> {
+    pub fn new(dbs: MigrationDbs<'a>, migrations: &'a [Arc<dyn SchemaMigration>]) -> Self {
+        Self { dbs, migrations }
+    }
+
+    /// The schema version the DB is at once all the known migrations are applied.
+    pub fn latest_schema_version(&self) -> u64 {
+        self.migrations.last().map_or(0, |m| m.version())
+    }
+
+    /// The schema version recorded in the DB. A DB created before the migration framework was
+    /// introduced doesn't have it, and is considered at version 0.
+    pub fn get_schema_version(&self) -> Result<Option<u64>> {
+        Ok(self
+            .dbs
+            .ledger_db
+            .metadata_db()
+            .db()
+            .get::<DbMetadataSchema>(&DbMetadataKey::SchemaVersion)?
+            .map(|v| v.expect_schema_version()))
+    }
+
+    pub fn get_backfill_progress(
+        &self,
+        migration_version: u64,
+    ) -> Result<Option<BackfillProgress>> {
+        Ok(self
+            .dbs
+            .ledger_db
+            .metadata_db()
+            .db()
+            .get::<DbMetadataSchema>(&DbMetadataKey::SchemaMigrationBackfillProgress(
+                migration_version,
+            ))?
+            .map(|v| v.expect_backfill_progress()))
+    }
+
+    /// Checks that the DB is compatible with this release and applies the pending migrations,
+    /// leaving their backfills to be run separately. Nothing is written if `readonly`.
+    pub fn prepare(&self, readonly: bool) -> Result<()> {
+        // Version 0 is taken by the DBs created before the migration framework.
+        let versions: Vec<_> = self.migrations.iter().map(|m| m.version()).collect();
+        ensure!(
+            versions.first().map_or(true, |v| *v > 0) && versions.windows(2).all(|w| w[0] < w[1]),
+            "Schema migration versions must be positive and strictly increasing, got {:?}.",
+            versions,
+        );
+
+        let latest_schema_version = self.latest_schema_version();
+        let synced_version = self.dbs.ledger_db.metadata_db().get_synced_version()?;
+        let schema_version = match self.get_schema_version()? {
+            Some(schema_version) => schema_version,
+            // A new DB is created at the latest schema version.
+            None if synced_version.is_none() => {
+                if !readonly {
+                    self.write_schema_version(latest_schema_version, None)?;
+                }
+                return Ok(());
+            },
+            None => 0,
+        };
+        ensure!(
+            schema_version <= latest_schema_version,
+            "DB schema version {} is newer than the latest one known to this release ({}), it's been migrated by a newer release.",
+            schema_version,
+            latest_schema_version,
+        );
+
+        for migration in self
+            .migrations
+            .iter()
+            .filter(|m| m.version() > schema_version)
+        {
+            if readonly {
+                warn!(
+                    migration_version = migration.version(),
+                    migration = migration.name(),
+                    "Schema migration is pending, but the DB is opened readonly."
+                );
+                continue;
+            }
+
+            info!(
+                migration_version = migration.version(),
+                migration = migration.name(),
+                "Applying schema migration."
+            );
+            migration.up(&self.dbs)?;
+            let backfill_progress = migration.has_backfill().then(|| BackfillProgress {
+                next_version: 0,
+                target_version: synced_version.map_or(0, |v| v + 1),
+            });
+            self.write_schema_version(
+                migration.version(),
+                backfill_progress.map(|p| (migration.version(), p)),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn write_schema_version(
+        &self,
+        schema_version: u64,
+        backfill_progress: Option<(u64, BackfillProgress)>,
+    ) -> Result<()> {
+        let mut batch = SchemaBatch::new();
+        batch.put::<DbMetadataSchema>(
+            &DbMetadataKey::SchemaVersion,
+            &DbMetadataValue::SchemaVersion(schema_version),
+        )?;
+        if let Some((migration_version, progress)) = backfill_progress {
+            batch.put::<DbMetadataSchema>(
+                &DbMetadataKey::SchemaMigrationBackfillProgress(migration_version),
+                &DbMetadataValue::BackfillProgress(progress),
+            )?;
+        }
+        self.dbs.ledger_db.metadata_db().write_schemas(batch)
+    }
+
+    pub fn get_state(&self, migration: &dyn SchemaMigration) -> Result<MigrationState> {
+        if self.get_schema_version()?.unwrap_or(0) < migration.version() {
+            return Ok(MigrationState::Pending);
+        }
+        Ok(match self.get_backfill_progress(migration.version())? {
+            Some(progress) if !progress.is_done() => MigrationState::Backfilling(progress),
+            _ => MigrationState::Done,
+        })
+    }
+
+    /// Returns the applied migrations with backfills to be run, in order.
+    pub fn get_pending_backfills(&self) -> Result<Vec<Arc<dyn SchemaMigration>>> {
+        let mut pending = Vec::new();
+        for migration in self.migrations {
+            if let MigrationState::Backfilling(_) = self.get_state(migration.as_ref())? {
+                pending.push(Arc::clone(migration));
+            }
+        }
+        Ok(pending)
+    }
+
+    /// Backfills the next batch of up to `batch_size` versions for `migration`, returning the
+    /// updated progress.
+    pub fn backfill_batch(
+        &self,
+        migration: &dyn SchemaMigration,
+        batch_size: usize,
+    ) -> Result<BackfillProgress> {
+        let progress = self
+            .get_backfill_progress(migration.version())?
+            .ok_or_else(|| {
+                AptosDbError::NotFound(format!(
+                    "Backfill progress of schema migration {}",
+                    migration.version()
+                ))
+            })?;
+        if progress.is_done() {
+            return Ok(progress);
+        }
+
+        let end = std::cmp::min(
+            progress.next_version.saturating_add(batch_size as Version),
+            progress.target_version,
+        );
+        migration.backfill(&self.dbs, progress.next_version, end)?;
+
+        let progress = BackfillProgress {
+            next_version: end,
+            ..progress
+        };
+        self.dbs
+            .ledger_db
+            .metadata_db()
+            .db()
+            .put::<DbMetadataSchema>(
+                &DbMetadataKey::SchemaMigrationBackfillProgress(migration.version()),
+                &DbMetadataValue::BackfillProgress(progress),
+            )?;
+        SCHEMA_MIGRATION_BACKFILL_PROGRESS
+            .with_label_values(&[migration.name()])
+            .set(end as i64);
+        Ok(progress)
+    }
+}
+
+/// Runs the pending backfills in a background thread.
+pub(crate) struct SchemaMigrationWorker {
+    sender: Mutex<mpsc::Sender<()>>,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+impl SchemaMigrationWorker {
+    pub(crate) fn new(
+        ledger_db: Arc<LedgerDb>,
+        state_merkle_db: Arc<StateMerkleDb>,
+        state_kv_db: Arc<StateKvDb>,
+        migrations: Vec<Arc<dyn SchemaMigration>>,
+    ) -> Self {
+        let (send, recv) = mpsc::channel();
+        let join_handle = Some(
+            std::thread::Builder::new()
+                .name("schema_migration".into())
+                .spawn(move || {
+                    let migrator = SchemaMigrator::new(
+                        MigrationDbs {
+                            ledger_db: &ledger_db,
+                            state_merkle_db: &state_merkle_db,
+                            state_kv_db: &state_kv_db,
+                        },
+                        &migrations,
+                    );
+                    Self::work(&migrator, &recv)
+                })
+                .expect("Creating schema migration thread should succeed."),
+        );
+        Self {
+            sender: Mutex::new(send),
+            join_handle,
+        }
+    }
+
+    fn work(migrator: &SchemaMigrator, quit: &mpsc::Receiver<()>) {
+        let pending = match migrator.get_pending_backfills() {
+            Ok(pending) => pending,
+            Err(e) => {
+                error!(error = ?e, "Failed to look up pending schema migration backfills.");
+                return;
+            },
+        };
+
+        for migration in pending {
+            loop {
+                match quit.try_recv() {
+                    Err(mpsc::TryRecvError::Empty) => (),
+                    _ => return,
+                }
+                match migrator.backfill_batch(migration.as_ref(), BACKFILL_BATCH_SIZE) {
+                    Ok(progress) if progress.is_done() => {
+                        info!(
+                            migration_version = migration.version(),
+                            migration = migration.name(),
+                            "Schema migration backfill is done."
+                        );
+                        break;
+                    },
+                    Ok(_) => (),
+                    Err(e) => {
+                        sample!(
+                            SampleRate::Duration(Duration::from_secs(60)),
+                            error!(
+                                migration_version = migration.version(),
+                                error = ?e,
+                                "Schema migration backfill failed, will retry."
+                            )
+                        );
+                        if quit.recv_timeout(Duration::from_secs(1))
+                            != Err(mpsc::RecvTimeoutError::Timeout)
+                        {
+                            return;
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
+
+impl Drop for SchemaMigrationWorker {
+    fn drop(&mut self) {
+        // The thread might have exited already, in which case there's no one to notify.
+        let _ = self.sender.lock().send(());
+        self.join_handle
+            .take()
+            .expect("Schema migration thread must exist.")
+            .join()
+            .expect("Schema migration thread should join peacefully.");
+    }
+}
@@ -0,0 +1,287 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::db::AptosDB;
+use aptos_config::config::{RocksdbConfigs, StorageDirPaths};
+use aptos_temppath::TempPath;
+
+#[derive(Default)]
+struct MockMigration {
+    version: u64,
+    has_backfill: bool,
+    /// Number of times `up` is called.
+    num_up: Mutex<usize>,
+    /// The ranges passed to `backfill`.
+    backfilled: Mutex<Vec<(Version, Version)>>,
+}
+
+impl MockMigration {
+    fn new(version: u64, has_backfill: bool) -> Arc<Self> {
+        Arc::new(Self {
+            version,
+            has_backfill,
+            ..Default::default()
+        })
+    }
+}
+
+impl SchemaMigration for MockMigration {
+    fn version(&self) -> u64 {
+        self.version
+    }
+
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn up(&self, _dbs: &MigrationDbs) -> Result<()> {
+        *self.num_up.lock() += 1;
+        Ok(())
+    }
+
+    fn has_backfill(&self) -> bool {
+        self.has_backfill
+    }
+
+    fn backfill(&self, _dbs: &MigrationDbs, begin: Version, end: Version) -> Result<()> {
+        self.backfilled.lock().push((begin, end));
+        Ok(())
+    }
+}
+
+fn open_dbs(tmp_dir: &TempPath) -> (LedgerDb, StateMerkleDb, StateKvDb) {
+    AptosDB::open_dbs(
+        &StorageDirPaths::from_path(tmp_dir),
+        RocksdbConfigs::default(),
+        /*readonly=*/ false,
+        /*max_num_nodes_per_lru_cache_shard=*/ 0,
+    )
+    .unwrap()
+}
+
+fn set_synced_version(ledger_db: &LedgerDb, version: Version) {
+    ledger_db
+        .metadata_db()
+        .db()
+        .put::<DbMetadataSchema>(
+            &DbMetadataKey::OverallCommitProgress,
+            &DbMetadataValue::Version(version),
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_new_db_starts_at_latest_schema_version() {
+    let tmp_dir = TempPath::new();
+    let (ledger_db, state_merkle_db, state_kv_db) = open_dbs(&tmp_dir);
+    let migration = MockMigration::new(1, true);
+    let migrations: Vec<Arc<dyn SchemaMigration>> = vec![migration.clone()];
+    let migrator = SchemaMigrator::new(
+        MigrationDbs {
+            ledger_db: &ledger_db,
+            state_merkle_db: &state_merkle_db,
+            state_kv_db: &state_kv_db,
+        },
+        &migrations,
+    );
+
+    migrator.prepare(/*readonly=*/ false).unwrap();
+    assert_eq!(migrator.get_schema_version().unwrap(), Some(1));
+    assert_eq!(*migration.num_up.lock(), 0);
+    assert_eq!(
+        migrator.get_state(migration.as_ref()).unwrap(),
+        MigrationState::Done
+    );
+    assert!(migrator.get_pending_backfills().unwrap().is_empty());
+}
+
+#[test]
+fn test_apply_and_backfill() {
+    let tmp_dir = TempPath::new();
+    let (ledger_db, state_merkle_db, state_kv_db) = open_dbs(&tmp_dir);
+    // A DB created before the migration framework.
+    set_synced_version(&ledger_db, 99);
+
+    let migration_1 = MockMigration::new(1, false);
+    let migration_2 = MockMigration::new(2, true);
+    let migrations: Vec<Arc<dyn SchemaMigration>> = vec![migration_1.clone(), migration_2.clone()];
+    let migrator = SchemaMigrator::new(
+        MigrationDbs {
+            ledger_db: &ledger_db,
+            state_merkle_db: &state_merkle_db,
+            state_kv_db: &state_kv_db,
+        },
+        &migrations,
+    );
+    assert_eq!(
+        migrator.get_state(migration_1.as_ref()).unwrap(),
+        MigrationState::Pending
+    );
+
+    // Nothing is written when readonly.
+    migrator.prepare(/*readonly=*/ true).unwrap();
+    assert_eq!(migrator.get_schema_version().unwrap(), None);
+    assert_eq!(*migration_1.num_up.lock(), 0);
+
+    migrator.prepare(/*readonly=*/ false).unwrap();
+    assert_eq!(migrator.get_schema_version().unwrap(), Some(2));
+    assert_eq!(*migration_1.num_up.lock(), 1);
+    assert_eq!(*migration_2.num_up.lock(), 1);
+    assert_eq!(
+        migrator.get_state(migration_1.as_ref()).unwrap(),
+        MigrationState::Done
+    );
+    assert_eq!(
+        migrator.get_state(migration_2.as_ref()).unwrap(),
+        MigrationState::Backfilling(BackfillProgress {
+            next_version: 0,
+            target_version: 100,
+        })
+    );
+
+    // Applied migrations are not applied again.
+    migrator.prepare(/*readonly=*/ false).unwrap();
+    assert_eq!(*migration_2.num_up.lock(), 1);
+
+    let pending = migrator.get_pending_backfills().unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].version(), 2);
+    while !migrator
+        .backfill_batch(migration_2.as_ref(), 30)
+        .unwrap()
+        .is_done()
+    {}
+    assert_eq!(*migration_2.backfilled.lock(), vec![
+        (0, 30),
+        (30, 60),
+        (60, 90),
+        (90, 100)
+    ]);
+    assert_eq!(
+        migrator.get_state(migration_2.as_ref()).unwrap(),
+        MigrationState::Done
+    );
+    assert!(migrator.get_pending_backfills().unwrap().is_empty());
+}
+
+#[test]
+fn test_refuse_newer_schema_version() {
+    let tmp_dir = TempPath::new();
+    let (ledger_db, state_merkle_db, state_kv_db) = open_dbs(&tmp_dir);
+    set_synced_version(&ledger_db, 99);
+    let dbs = || MigrationDbs {
+        ledger_db: &ledger_db,
+        state_merkle_db: &state_merkle_db,
+        state_kv_db: &state_kv_db,
+    };
+
+    let migrations: Vec<Arc<dyn SchemaMigration>> =
+        vec![MockMigration::new(1, false), MockMigration::new(2, false)];
+    SchemaMigrator::new(dbs(), &migrations)
+        .prepare(/*readonly=*/ false)
+        .unwrap();
+
+    // An older release only knows about the first migration.
+    let old_migrations = &migrations[..1];
+    let migrator = SchemaMigrator::new(dbs(), old_migrations);
+    assert!(migrator.prepare(/*readonly=*/ false).is_err());
+    assert!(migrator.prepare(/*readonly=*/ true).is_err());
+}
+
+#[test]
+fn test_background_worker() {
+    let tmp_dir = TempPath::new();
+    let (ledger_db, state_merkle_db, state_kv_db) = open_dbs(&tmp_dir);
+    set_synced_version(&ledger_db, 2 * BACKFILL_BATCH_SIZE as Version);
+    let migration = MockMigration::new(1, true);
+    let migrations: Vec<Arc<dyn SchemaMigration>> = vec![migration.clone()];
+    SchemaMigrator::new(
+        MigrationDbs {
+            ledger_db: &ledger_db,
+            state_merkle_db: &state_merkle_db,
+            state_kv_db: &state_kv_db,
+        },
+        &migrations,
+    )
+    .prepare(/*readonly=*/ false)
+    .unwrap();
+
+    let ledger_db = Arc::new(ledger_db);
+    let state_merkle_db = Arc::new(state_merkle_db);
+    let state_kv_db = Arc::new(state_kv_db);
+    // Dropping the worker waits for it to exit, which it does once it's done.
+    let worker = SchemaMigrationWorker::new(
+        Arc::clone(&ledger_db),
+        Arc::clone(&state_merkle_db),
+        Arc::clone(&state_kv_db),
+        migrations.clone(),
+    );
+    let timeout = std::time::Instant::now() + Duration::from_secs(60);
+    while migration.backfilled.lock().len() < 3 && std::time::Instant::now() < timeout {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    drop(worker);
+
+    let migrator = SchemaMigrator::new(
+        MigrationDbs {
+            ledger_db: &ledger_db,
+            state_merkle_db: &state_merkle_db,
+            state_kv_db: &state_kv_db,
+        },
+        &migrations,
+    );
+    assert_eq!(
+        migrator.get_state(migration.as_ref()).unwrap(),
+        MigrationState::Done
+    );
+}
+
+#[test]
+fn test_migration_versions_must_increase() {
+    let tmp_dir = TempPath::new();
+    let (ledger_db, state_merkle_db, state_kv_db) = open_dbs(&tmp_dir);
+    let dbs = || MigrationDbs {
+        ledger_db: &ledger_db,
+        state_merkle_db: &state_merkle_db,
+        state_kv_db: &state_kv_db,
+    };
+
+    for versions in [vec![2, 1], vec![1, 1], vec![0]] {
+        let migrations: Vec<Arc<dyn SchemaMigration>> = versions
+            .into_iter()
+            .map(|v| MockMigration::new(v, false) as Arc<dyn SchemaMigration>)
+            .collect();
+        let migrator = SchemaMigrator::new(dbs(), &migrations);
+        assert!(migrator.prepare(/*readonly=*/ false).is_err());
+        assert_eq!(migrator.get_schema_version().unwrap(), None);
+    }
+}
+
+#[test]
+fn test_all_migrations() {
+    let tmp_dir = TempPath::new();
+    let (ledger_db, state_merkle_db, state_kv_db) = open_dbs(&tmp_dir);
+    // A DB created before the migration framework.
+    set_synced_version(&ledger_db, 99);
+
+    let migrations = all_migrations();
+    let migrator = SchemaMigrator::new(
+        MigrationDbs {
+            ledger_db: &ledger_db,
+            state_merkle_db: &state_merkle_db,
+            state_kv_db: &state_kv_db,
+        },
+        &migrations,
+    );
+    assert_eq!(migrator.latest_schema_version(), 1);
+    migrator.prepare(/*readonly=*/ false).unwrap();
+    assert_eq!(migrator.get_schema_version().unwrap(), Some(1));
+    for migration in &migrations {
+        assert_eq!(
+            migrator.get_state(migration.as_ref()).unwrap(),
+            MigrationState::Done
+        );
+    }
+    assert!(migrator.get_pending_backfills().unwrap().is_empty());
+}
@@ -403,4 +403,43 @@ impl StateKvDb {
                 .and_then(|((_, version), value_opt)| value_opt.map(|value| (version, value))))
         }
     }
+
+    /// Returns the versions at which `state_key` was changed within `[start_version, end_version]`,
+    /// together with the values written at those versions (`None` for deletions), in increasing
+    /// order of versions. At most `limit` changes are returned.
+    pub(crate) fn get_state_value_history(
+        &self,
+        state_key: &StateKey,
+        start_version: Version,
+        end_version: Version,
+        limit: usize,
+    ) -> Result<Vec<(Version, Option<StateValue>)>> {
+        // Versions are encoded in descending order, so we iterate backwards from the last entry
+        // that is not older than `start_version`.
+        let db = self.db_shard(state_key.get_shard_id());
+        let mut history = Vec::new();
+        if !self.enabled_sharding() {
+            let mut iter = db.rev_iter::<StateValueSchema>()?;
+            iter.seek_for_prev(&(state_key.clone(), start_version))?;
+            for item in iter {
+                let ((key, version), value) = item?;
+                if &key != state_key || version > end_version || history.len() >= limit {
+                    break;
+                }
+                history.push((version, value));
+            }
+        } else {
+            let key_hash = state_key.hash();
+            let mut iter = db.rev_iter::<StateValueByKeyHashSchema>()?;
+            iter.seek_for_prev(&(key_hash, start_version))?;
+            for item in iter {
+                let ((hash, version), value) = item?;
+                if hash != key_hash || version > end_version || history.len() >= limit {
+                    break;
+                }
+                history.push((version, value));
+            }
+        }
+        Ok(history)
+    }
 }
@@ -32,7 +32,7 @@ use aptos_scratchpad::get_state_shard_id;
 use aptos_storage_interface::{db_ensure as ensure, AptosDbError, Result};
 use aptos_types::{
     nibble::{nibble_path::NibblePath, ROOT_NIBBLE_HEIGHT},
-    proof::{SparseMerkleProofExt, SparseMerkleRangeProof},
+    proof::{SparseMerkleBatchProof, SparseMerkleProofExt, SparseMerkleRangeProof},
     state_store::{state_key::StateKey, NUM_STATE_SHARDS},
     transaction::Version,
 };
@@ -253,6 +253,17 @@ impl StateMerkleDb {
         JellyfishMerkleTree::new(self).get_with_proof_ext(key, version, root_depth)
     }
 
+    pub fn get_with_batch_proof(
+        &self,
+        keys: &[HashValue],
+        version: Version,
+    ) -> Result<(
+        Vec<Option<(HashValue, (StateKey, Version))>>,
+        SparseMerkleBatchProof,
+    )> {
+        JellyfishMerkleTree::new(self).get_with_batch_proof(keys, version)
+    }
+
     pub fn get_range_proof(
         &self,
         rightmost_key: HashValue,
@@ -70,7 +70,7 @@ use aptos_types::{
         state_storage_usage::StateStorageUsage,
         state_value::{
             StaleStateValueByKeyHashIndex, StaleStateValueIndex, StateValue,
-            StateValueChunkWithProof,
+            StateValueChunkWithProof, StateValuesWithBatchProof,
         },
         NUM_STATE_SHARDS,
     },
@@ -1075,6 +1075,42 @@ impl StateStore {
         })
     }
 
+    pub fn get_values_with_batch_proof(
+        self: &Arc<Self>,
+        version: Version,
+        state_keys: &[StateKey],
+    ) -> Result<StateValuesWithBatchProof> {
+        ensure!(!state_keys.is_empty(), "No state keys to prove.");
+        let mut state_keys = state_keys.to_vec();
+        state_keys.sort_by_key(|key| *key.crypto_hash_ref());
+        state_keys.dedup();
+        let key_hashes: Vec<_> = state_keys
+            .iter()
+            .map(|key| *key.crypto_hash_ref())
+            .collect();
+
+        let (leaves, proof) = self
+            .state_merkle_db
+            .get_with_batch_proof(&key_hashes, version)?;
+        let state_values = state_keys
+            .into_iter()
+            .zip(leaves)
+            .map(|(state_key, leaf)| {
+                let value = leaf
+                    .map(|(_val_hash, (key, ver))| self.expect_value_by_version(&key, ver))
+                    .transpose()?;
+                Ok((state_key, value))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let root_hash = self.get_root_hash(version)?;
+
+        Ok(StateValuesWithBatchProof {
+            state_values,
+            proof,
+            root_hash,
+        })
+    }
+
     // state sync doesn't query for the progress, but keeps its record by itself.
     // TODO: change to async comment once it does like https://github.com/aptos-labs/aptos-core/blob/159b00f3d53e4327523052c1b99dd9889bf13b03/storage/backup/backup-cli/src/backup_types/state_snapshot/restore.rs#L147 or overlap at least two chunks.
     pub fn get_snapshot_receiver(
@@ -1232,6 +1268,18 @@ impl StateValueWriter<StateKey, StateValue> for StateStore {
                         &MetadataValue::Version(version - 1),
                     )?;
                 }
+                if internal_indexer_db.account_transactions_enabled() {
+                    batch.put::<InternalIndexerMetadataSchema>(
+                        &MetadataKey::AccountTransactionVersion,
+                        &MetadataValue::Version(version - 1),
+                    )?;
+                }
+                if internal_indexer_db.event_by_type_enabled() {
+                    batch.put::<InternalIndexerMetadataSchema>(
+                        &MetadataKey::EventByTypeVersion,
+                        &MetadataValue::Version(version - 1),
+                    )?;
+                }
                 internal_indexer_db
                     .get_inner_db_ref()
                     .write_schemas(batch)?;
@@ -124,6 +124,54 @@ fn test_state_store_reader_writer() {
     verify_value_and_proof(store, key3, Some(&value3), 1, root);
 }
 
+#[test]
+fn test_get_state_value_history() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let store = &db.state_store;
+    let key1 = StateKey::raw(b"test_key1");
+    let key2 = StateKey::raw(b"test_key2");
+    let value1 = StateValue::from(String::from("test_val1").into_bytes());
+    let value1_update = StateValue::from(String::from("test_val1_update").into_bytes());
+    let value2 = StateValue::from(String::from("test_val2").into_bytes());
+
+    // key1 is created at version 0, updated at version 2 and deleted at version 3, while key2
+    // changes in between.
+    store.commit_block_for_test(0, [vec![(key1.clone(), Some(value1.clone()))]]);
+    store.commit_block_for_test(1, [vec![(key2.clone(), Some(value2.clone()))]]);
+    store.commit_block_for_test(2, [vec![(key1.clone(), Some(value1_update.clone()))]]);
+    store.commit_block_for_test(3, [vec![(key1.clone(), None)]]);
+
+    let history = db.get_state_value_history(&key1, 0, 100, 3).unwrap();
+    assert_eq!(history, vec![
+        (0, Some(value1.clone())),
+        (2, Some(value1_update.clone())),
+        (3, None),
+    ]);
+    assert_eq!(db.get_state_value_history(&key2, 0, 100, 3).unwrap(), vec![
+        (1, Some(value2))
+    ]);
+
+    // Paginate through the history of key1.
+    assert_eq!(db.get_state_value_history(&key1, 0, 2, 3).unwrap(), vec![
+        (0, Some(value1)),
+        (2, Some(value1_update.clone())),
+    ]);
+    assert_eq!(db.get_state_value_history(&key1, 3, 2, 3).unwrap(), vec![(
+        3, None
+    )]);
+
+    // The ledger version bounds the history.
+    assert_eq!(db.get_state_value_history(&key1, 1, 100, 2).unwrap(), vec![
+        (2, Some(value1_update))
+    ]);
+    assert!(db.get_state_value_history(&key1, 3, 100, 2).is_err());
+    assert!(db
+        .get_state_value_history(&StateKey::raw(b"test_key3"), 0, 100, 3)
+        .unwrap()
+        .is_empty());
+}
+
 fn traverse_values(
     store: &StateStore,
     prefix: &StateKeyPrefix,
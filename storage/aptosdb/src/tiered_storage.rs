@@ -0,0 +1,212 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tiered ledger storage: instead of dropping the ledger history that falls out of the prune
+//! window, the ledger pruner only prunes what has been handed to a [`ColdLedgerStore`] (usually
+//! backed by an object store) by a background archiver, and reads below the pruned version are
+//! served from there transparently.
+//!
+//! The transaction accumulator and the transaction auxiliary data are kept in the DB when tiered
+//! storage is enabled, so proofs for archived versions can still be generated locally, and
+//! archived transaction outputs are complete.
+
+use aptos_infallible::Mutex;
+use aptos_storage_interface::{db_ensure as ensure, AptosDbError, Result};
+use aptos_types::{
+    contract_event::ContractEvent,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::TransactionAccumulatorRangeProof,
+    transaction::{PersistedAuxiliaryInfo, Transaction, TransactionInfo, Version},
+    write_set::WriteSet,
+};
+use lru::LruCache;
+use std::{collections::BTreeMap, num::NonZeroUsize, sync::Arc};
+
+/// The ledger data of a single version, as kept in the cold tier.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArchivedTransaction {
+    pub transaction: Transaction,
+    pub persisted_info: PersistedAuxiliaryInfo,
+    pub transaction_info: TransactionInfo,
+    pub events: Vec<ContractEvent>,
+    pub write_set: WriteSet,
+}
+
+/// Storage for ledger history that has been pruned from the DB.
+///
+/// Data is archived in immutable chunks of consecutive versions, in increasing order of version.
+pub trait ColdLedgerStore: Send + Sync {
+    /// Returns the first archived version, or `None` if nothing has been archived yet.
+    fn first_version(&self) -> Result<Option<Version>>;
+
+    /// Returns the version following the last archived one, or `None` if nothing has been
+    /// archived yet.
+    fn next_version(&self) -> Result<Option<Version>>;
+
+    /// Returns whether `version` is archived. Expected to be cheap.
+    fn is_archived(&self, version: Version) -> Result<bool>;
+
+    /// Returns the first version at or after `version` that is not archived. Expected to be
+    /// cheap.
+    fn archived_until(&self, version: Version) -> Result<Version>;
+
+    /// Archives `txns` as a chunk starting at `first_version`. `proof` proves the transaction
+    /// infos in the chunk against the ledger info.
+    fn archive(
+        &self,
+        first_version: Version,
+        txns: Vec<ArchivedTransaction>,
+        proof: (TransactionAccumulatorRangeProof, LedgerInfoWithSignatures),
+    ) -> Result<()>;
+
+    /// Returns the first version and the content of the chunk containing `version`, or `None` if
+    /// `version` is not archived. Expected to be cheap in the latter case.
+    fn get_chunk(&self, version: Version) -> Result<Option<(Version, Vec<ArchivedTransaction>)>>;
+}
+
+/// A [`ColdLedgerStore`] with an in-memory cache of the recently fetched chunks.
+pub(crate) struct TieredLedgerStore {
+    store: Arc<dyn ColdLedgerStore>,
+    cache: Mutex<ChunkCache>,
+}
+
+struct ChunkCache {
+    /// Chunks keyed by their first version.
+    chunks: LruCache<Version, Arc<Vec<ArchivedTransaction>>>,
+    /// The number of transactions of each cached chunk, keyed by its first version, to find the
+    /// chunk containing a version.
+    index: BTreeMap<Version, usize>,
+}
+
+impl TieredLedgerStore {
+    pub(crate) fn new(store: Arc<dyn ColdLedgerStore>, chunk_cache_size: NonZeroUsize) -> Self {
+        Self {
+            store,
+            cache: Mutex::new(ChunkCache {
+                chunks: LruCache::new(chunk_cache_size),
+                index: BTreeMap::new(),
+            }),
+        }
+    }
+
+    pub(crate) fn store(&self) -> &dyn ColdLedgerStore {
+        self.store.as_ref()
+    }
+
+    pub(crate) fn get(&self, version: Version) -> Result<Option<ArchivedTransaction>> {
+        if let Some(txn) = self.get_cached(version) {
+            return Ok(Some(txn));
+        }
+
+        let Some((first_version, txns)) = self.store.get_chunk(version)? else {
+            return Ok(None);
+        };
+        ensure!(
+            first_version <= version && version - first_version < txns.len() as Version,
+            "Chunk starting at version {} with {} transactions doesn't contain version {}.",
+            first_version,
+            txns.len(),
+            version,
+        );
+        let txn = txns[(version - first_version) as usize].clone();
+        self.cache.lock().put(first_version, txns);
+        Ok(Some(txn))
+    }
+
+    fn get_cached(&self, version: Version) -> Option<ArchivedTransaction> {
+        let mut cache = self.cache.lock();
+        let (&first_version, &len) = cache.index.range(..=version).next_back()?;
+        if version - first_version >= len as Version {
+            return None;
+        }
+        cache
+            .chunks
+            .get(&first_version)
+            .map(|txns| txns[(version - first_version) as usize].clone())
+    }
+}
+
+impl ChunkCache {
+    fn put(&mut self, first_version: Version, txns: Vec<ArchivedTransaction>) {
+        self.index.insert(first_version, txns.len());
+        if let Some((evicted, _txns)) = self.chunks.push(first_version, Arc::new(txns)) {
+            if evicted != first_version {
+                self.index.remove(&evicted);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_helper {
+    use super::*;
+
+    /// A [`ColdLedgerStore`] that keeps everything in memory.
+    #[derive(Default)]
+    pub(crate) struct MockColdLedgerStore {
+        chunks: Mutex<BTreeMap<Version, Vec<ArchivedTransaction>>>,
+    }
+
+    impl ColdLedgerStore for MockColdLedgerStore {
+        fn first_version(&self) -> Result<Option<Version>> {
+            Ok(self.chunks.lock().first_key_value().map(|(v, _txns)| *v))
+        }
+
+        fn next_version(&self) -> Result<Option<Version>> {
+            Ok(self
+                .chunks
+                .lock()
+                .last_key_value()
+                .map(|(first_version, txns)| first_version + txns.len() as Version))
+        }
+
+        fn is_archived(&self, version: Version) -> Result<bool> {
+            Ok(self
+                .chunks
+                .lock()
+                .range(..=version)
+                .next_back()
+                .is_some_and(|(first_version, txns)| {
+                    version - *first_version < txns.len() as Version
+                }))
+        }
+
+        fn archived_until(&self, version: Version) -> Result<Version> {
+            let chunks = self.chunks.lock();
+            let mut version = version;
+            loop {
+                match chunks.range(..=version).next_back() {
+                    Some((first_version, txns))
+                        if version - first_version < txns.len() as Version =>
+                    {
+                        version = first_version + txns.len() as Version;
+                    },
+                    _ => return Ok(version),
+                }
+            }
+        }
+
+        fn archive(
+            &self,
+            first_version: Version,
+            txns: Vec<ArchivedTransaction>,
+            _proof: (TransactionAccumulatorRangeProof, LedgerInfoWithSignatures),
+        ) -> Result<()> {
+            self.chunks.lock().insert(first_version, txns);
+            Ok(())
+        }
+
+        fn get_chunk(
+            &self,
+            version: Version,
+        ) -> Result<Option<(Version, Vec<ArchivedTransaction>)>> {
+            Ok(self
+                .chunks
+                .lock()
+                .range(..=version)
+                .next_back()
+                .filter(|(first_version, txns)| version - *first_version < txns.len() as Version)
+                .map(|(first_version, txns)| (*first_version, txns.clone())))
+        }
+    }
+}
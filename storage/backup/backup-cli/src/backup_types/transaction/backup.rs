@@ -131,17 +131,17 @@ impl TransactionBackupController {
         format!("transaction_{}-", self.start_version)
     }
 
-    fn manifest_name() -> &'static ShellSafeName {
+    pub(crate) fn manifest_name() -> &'static ShellSafeName {
         static NAME: Lazy<ShellSafeName> =
             Lazy::new(|| ShellSafeName::from_str("transaction.manifest").unwrap());
         &NAME
     }
 
-    fn chunk_name(first_ver: Version) -> ShellSafeName {
+    pub(crate) fn chunk_name(first_ver: Version) -> ShellSafeName {
         format!("{}-.chunk", first_ver).try_into().unwrap()
     }
 
-    fn chunk_proof_name(first_ver: u64, last_ver: Version) -> ShellSafeName {
+    pub(crate) fn chunk_proof_name(first_ver: u64, last_ver: Version) -> ShellSafeName {
         format!("{}-{}.proof", first_ver, last_ver)
             .try_into()
             .unwrap()
@@ -0,0 +1,363 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A [`ColdLedgerStore`] on top of a [`BackupStorage`]. The ledger history pruned from the DB is
+//! written in the transaction backup format, so it can be restored or verified with the regular
+//! tooling as well.
+
+use crate::{
+    backup_types::transaction::{
+        backup::TransactionBackupController,
+        manifest::{TransactionBackup, TransactionChunk, TransactionChunkFormat},
+    },
+    metadata::Metadata,
+    storage::{
+        command_adapter::{config::CommandAdapterConfig, CommandAdapter},
+        local_fs::LocalFs,
+        BackupStorage,
+    },
+    utils::{read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt},
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use aptos_config::config::TieredStorageConfig;
+use aptos_db::tiered_storage::{ArchivedTransaction, ColdLedgerStore};
+use aptos_infallible::RwLock;
+use aptos_logger::prelude::*;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    proof::TransactionAccumulatorRangeProof,
+    transaction::{PersistedAuxiliaryInfo, Version},
+};
+use std::{collections::BTreeMap, future::Future, sync::Arc};
+use tokio::{
+    io::{AsyncWriteExt, BufReader},
+    runtime::Handle,
+};
+
+pub struct BackupStorageColdLedgerStore {
+    storage: Arc<dyn BackupStorage>,
+    /// The callers are not necessarily async, so the storage is driven by a dedicated runtime.
+    runtime: ColdStorageRuntime,
+    /// Archived chunks keyed by their first version.
+    chunks: RwLock<BTreeMap<Version, TransactionChunk>>,
+}
+
+/// A runtime owned by a dedicated thread, so that the store can be dropped from within another
+/// runtime, where dropping a runtime panics.
+struct ColdStorageRuntime {
+    handle: Handle,
+    /// Dropping it lets the thread shut the runtime down.
+    _shutdown_tx: std::sync::mpsc::Sender<()>,
+}
+
+impl ColdStorageRuntime {
+    fn new() -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .thread_name("cold-ledger")
+            .enable_all()
+            .build()?;
+        let handle = runtime.handle().clone();
+        let (shutdown_tx, shutdown_rx) = std::sync::mpsc::channel::<()>();
+        std::thread::Builder::new()
+            .name("cold-ledger-rt".to_string())
+            .spawn(move || {
+                // Only returns once the sender is dropped.
+                let _ = shutdown_rx.recv();
+                drop(runtime);
+            })?;
+        Ok(Self {
+            handle,
+            _shutdown_tx: shutdown_tx,
+        })
+    }
+
+    /// Runs `future` on the runtime and waits for the result. Unlike `Handle::block_on()`, this
+    /// doesn't panic when called from within another runtime.
+    fn run<T, F>(&self, future: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: Future<Output = Result<T>> + Send + 'static,
+    {
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.handle.spawn(async move {
+            // The receiver only goes away if the caller is gone.
+            let _ = tx.send(future.await);
+        });
+        rx.recv()
+            .map_err(|_| anyhow!("Cold ledger storage runtime shut down."))?
+    }
+}
+
+impl BackupStorageColdLedgerStore {
+    pub fn new(storage: Arc<dyn BackupStorage>) -> Result<Self> {
+        Self::new_with_runtime(ColdStorageRuntime::new()?, storage)
+    }
+
+    /// Creates the store on the backup storage specified by the node config.
+    pub fn new_from_config(config: &TieredStorageConfig) -> Result<Self> {
+        let runtime = ColdStorageRuntime::new()?;
+        let storage: Arc<dyn BackupStorage> = match (
+            config.local_fs_dir.clone(),
+            config.command_adapter_config.clone(),
+        ) {
+            (Some(dir), None) => Arc::new(LocalFs::new(dir)),
+            (None, Some(config_path)) => {
+                let config = runtime
+                    .run(async move { CommandAdapterConfig::load_from_file(&config_path).await })?;
+                Arc::new(CommandAdapter::new(config))
+            },
+            _ => bail!("Exactly one of local_fs_dir and command_adapter_config needs to be set."),
+        };
+        Self::new_with_runtime(runtime, storage)
+    }
+
+    fn new_with_runtime(
+        runtime: ColdStorageRuntime,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Result<Self> {
+        let chunks = runtime.run(Self::load_chunks(Arc::clone(&storage)))?;
+        info!(
+            num_chunks = chunks.len(),
+            "Loaded the index of the archived ledger history."
+        );
+        Ok(Self {
+            storage,
+            runtime,
+            chunks: RwLock::new(chunks),
+        })
+    }
+
+    async fn load_chunks(
+        storage: Arc<dyn BackupStorage>,
+    ) -> Result<BTreeMap<Version, TransactionChunk>> {
+        let mut chunks = BTreeMap::new();
+        for file_handle in storage.list_metadata_files().await? {
+            let lines = String::from_utf8(storage.read_all(&file_handle).await?)?;
+            for line in lines.lines() {
+                if let Metadata::TransactionBackup(meta) = serde_json::from_str(line)? {
+                    let manifest: TransactionBackup =
+                        storage.load_json_file(&meta.manifest).await?;
+                    manifest.verify()?;
+                    // Regular transaction backups in the same storage may overlap.
+                    for chunk in manifest.chunks {
+                        insert_chunk(&mut chunks, chunk);
+                    }
+                }
+            }
+        }
+        Ok(chunks)
+    }
+
+    fn archive_impl(
+        &self,
+        first_version: Version,
+        txns: Vec<ArchivedTransaction>,
+        proof: (TransactionAccumulatorRangeProof, LedgerInfoWithSignatures),
+    ) -> Result<()> {
+        ensure!(!txns.is_empty(), "Nothing to archive.");
+        let last_version = first_version + txns.len() as Version - 1;
+
+        let mut chunk_bytes = Vec::new();
+        for txn in txns {
+            let record_bytes = bcs::to_bytes(&(
+                txn.transaction,
+                txn.persisted_info,
+                txn.transaction_info,
+                txn.events,
+                txn.write_set,
+            ))?;
+            chunk_bytes.extend((record_bytes.len() as u32).to_be_bytes());
+            chunk_bytes.extend(record_bytes);
+        }
+        let proof_bytes = bcs::to_bytes(&proof)?;
+
+        let storage = Arc::clone(&self.storage);
+        let chunk = self.runtime.run(async move {
+            let backup_handle = storage
+                .create_backup_with_random_suffix(&format!("transaction_{}-", first_version))
+                .await?;
+
+            let (proof_handle, mut proof_file) = storage
+                .create_for_write(
+                    &backup_handle,
+                    &TransactionBackupController::chunk_proof_name(first_version, last_version),
+                )
+                .await?;
+            proof_file.write_all(&proof_bytes).await?;
+            proof_file.shutdown().await?;
+
+            let (chunk_handle, mut chunk_file) = storage
+                .create_for_write(
+                    &backup_handle,
+                    &TransactionBackupController::chunk_name(first_version),
+                )
+                .await?;
+            chunk_file.write_all(&chunk_bytes).await?;
+            chunk_file.shutdown().await?;
+
+            let chunk = TransactionChunk {
+                first_version,
+                last_version,
+                transactions: chunk_handle,
+                proof: proof_handle,
+                format: TransactionChunkFormat::V1,
+            };
+            let manifest = TransactionBackup {
+                first_version,
+                last_version,
+                chunks: vec![chunk.clone()],
+            };
+            let (manifest_handle, mut manifest_file) = storage
+                .create_for_write(&backup_handle, TransactionBackupController::manifest_name())
+                .await?;
+            manifest_file
+                .write_all(&serde_json::to_vec(&manifest)?)
+                .await?;
+            manifest_file.shutdown().await?;
+
+            let metadata =
+                Metadata::new_transaction_backup(first_version, last_version, manifest_handle);
+            storage
+                .save_metadata_line(&metadata.name(), &metadata.to_text_line()?)
+                .await?;
+
+            Ok(chunk)
+        })?;
+
+        insert_chunk(&mut self.chunks.write(), chunk);
+        Ok(())
+    }
+
+    /// Returns the chunk containing `version`, if any.
+    fn find_chunk(&self, version: Version) -> Option<TransactionChunk> {
+        find_chunk(&self.chunks.read(), version).cloned()
+    }
+
+    fn get_chunk_impl(
+        &self,
+        version: Version,
+    ) -> Result<Option<(Version, Vec<ArchivedTransaction>)>> {
+        let Some(chunk) = self.find_chunk(version) else {
+            return Ok(None);
+        };
+
+        let first_version = chunk.first_version;
+        let storage = Arc::clone(&self.storage);
+        let txns = self.runtime.run(async move {
+            let mut file = BufReader::new(storage.open_for_read(&chunk.transactions).await?);
+            let mut txns = Vec::new();
+            while let Some(record_bytes) = file.read_record_bytes().await? {
+                let (transaction, persisted_info, transaction_info, events, write_set) =
+                    match chunk.format {
+                        TransactionChunkFormat::V0 => {
+                            let (transaction, transaction_info, events, write_set) =
+                                bcs::from_bytes(&record_bytes)?;
+                            (
+                                transaction,
+                                PersistedAuxiliaryInfo::None,
+                                transaction_info,
+                                events,
+                                write_set,
+                            )
+                        },
+                        TransactionChunkFormat::V1 => bcs::from_bytes(&record_bytes)?,
+                    };
+                txns.push(ArchivedTransaction {
+                    transaction,
+                    persisted_info,
+                    transaction_info,
+                    events,
+                    write_set,
+                });
+            }
+            ensure!(
+                chunk.first_version + txns.len() as Version == chunk.last_version + 1,
+                "Number of items in chunk doesn't match that in manifest. first_version: {}, last_version: {}, items in chunk: {}",
+                chunk.first_version,
+                chunk.last_version,
+                txns.len(),
+            );
+            Ok(txns)
+        })?;
+
+        Ok(Some((first_version, txns)))
+    }
+}
+
+impl ColdLedgerStore for BackupStorageColdLedgerStore {
+    fn first_version(&self) -> aptos_storage_interface::Result<Option<Version>> {
+        Ok(self.chunks.read().first_key_value().map(|(v, _chunk)| *v))
+    }
+
+    fn next_version(&self) -> aptos_storage_interface::Result<Option<Version>> {
+        Ok(self
+            .chunks
+            .read()
+            .last_key_value()
+            .map(|(_first_version, chunk)| chunk.last_version + 1))
+    }
+
+    fn is_archived(&self, version: Version) -> aptos_storage_interface::Result<bool> {
+        Ok(self.find_chunk(version).is_some())
+    }
+
+    fn archived_until(&self, version: Version) -> aptos_storage_interface::Result<Version> {
+        let chunks = self.chunks.read();
+        let mut version = version;
+        while let Some(chunk) = find_chunk(&chunks, version) {
+            version = chunk.last_version + 1;
+        }
+        Ok(version)
+    }
+
+    fn archive(
+        &self,
+        first_version: Version,
+        txns: Vec<ArchivedTransaction>,
+        proof: (TransactionAccumulatorRangeProof, LedgerInfoWithSignatures),
+    ) -> aptos_storage_interface::Result<()> {
+        Ok(self.archive_impl(first_version, txns, proof)?)
+    }
+
+    fn get_chunk(
+        &self,
+        version: Version,
+    ) -> aptos_storage_interface::Result<Option<(Version, Vec<ArchivedTransaction>)>> {
+        Ok(self.get_chunk_impl(version)?)
+    }
+}
+
+/// Adds `chunk` to the index of chunks keyed by their first version, unless another chunk
+/// already covers all its versions. The chunks covered by `chunk` are dropped in turn, so that the
+/// last versions of the chunks increase with their first versions.
+fn insert_chunk(chunks: &mut BTreeMap<Version, TransactionChunk>, chunk: TransactionChunk) {
+    if chunks
+        .range(..=chunk.first_version)
+        .next_back()
+        .is_some_and(|(_first_version, c)| c.last_version >= chunk.last_version)
+    {
+        return;
+    }
+    let covered: Vec<_> = chunks
+        .range(chunk.first_version..=chunk.last_version)
+        .filter(|(_first_version, c)| c.last_version <= chunk.last_version)
+        .map(|(first_version, _c)| *first_version)
+        .collect();
+    for first_version in covered {
+        chunks.remove(&first_version);
+    }
+    chunks.insert(chunk.first_version, chunk);
+}
+
+/// Returns the chunk containing `version` in the index of chunks keyed by their first version.
+fn find_chunk(
+    chunks: &BTreeMap<Version, TransactionChunk>,
+    version: Version,
+) -> Option<&TransactionChunk> {
+    // Per `insert_chunk`, only the last chunk starting at or before `version` can contain it.
+    chunks
+        .range(..=version)
+        .next_back()
+        .map(|(_first_version, chunk)| chunk)
+        .filter(|chunk| version <= chunk.last_version)
+}
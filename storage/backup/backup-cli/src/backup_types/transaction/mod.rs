@@ -4,6 +4,7 @@
 
 mod analysis;
 pub mod backup;
+pub mod cold_storage;
 pub mod manifest;
 pub mod restore;
 
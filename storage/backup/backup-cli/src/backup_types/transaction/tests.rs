@@ -5,6 +5,7 @@
 use crate::{
     backup_types::transaction::{
         backup::{TransactionBackupController, TransactionBackupOpt},
+        cold_storage::BackupStorageColdLedgerStore,
         restore::TransactionRestoreBatchController,
     },
     storage::{local_fs::LocalFs, BackupStorage},
@@ -15,7 +16,10 @@ use crate::{
         RocksdbOpt, TrustedWaypointOpt,
     },
 };
-use aptos_db::AptosDB;
+use aptos_db::{
+    tiered_storage::{ArchivedTransaction, ColdLedgerStore},
+    AptosDB,
+};
 use aptos_executor_types::VerifyExecutionMode;
 use aptos_storage_interface::DbReader;
 use aptos_temppath::TempPath;
@@ -179,3 +183,103 @@ fn end_to_end() {
 
     rt.shutdown_timeout(Duration::from_secs(1));
 }
+
+#[test]
+fn cold_ledger_store() {
+    let (_src_db_dir, src_db, blocks) = tmp_db_with_random_content();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+    let backup_handler = src_db.get_backup_handler();
+
+    let total_txns = blocks.iter().map(|(txns, _li)| txns.len()).sum::<usize>();
+    let txns = backup_handler
+        .get_transaction_iter(0, total_txns)
+        .unwrap()
+        .map(|res| {
+            let (transaction, persisted_info, transaction_info, events, write_set) = res.unwrap();
+            ArchivedTransaction {
+                transaction,
+                persisted_info,
+                transaction_info,
+                events,
+                write_set,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let cold_store = BackupStorageColdLedgerStore::new(Arc::clone(&store)).unwrap();
+    assert_eq!(cold_store.next_version().unwrap(), None);
+    let chunk_size = std::cmp::max(total_txns / 3, 1);
+    for (i, chunk) in txns.chunks(chunk_size).enumerate() {
+        let first_version = (i * chunk_size) as Version;
+        let last_version = first_version + chunk.len() as Version - 1;
+        cold_store
+            .archive(
+                first_version,
+                chunk.to_vec(),
+                backup_handler
+                    .get_transaction_range_proof(first_version, last_version)
+                    .unwrap(),
+            )
+            .unwrap();
+    }
+    assert_eq!(
+        cold_store.next_version().unwrap(),
+        Some(total_txns as Version)
+    );
+
+    // The index is rebuilt from the backup metadata when reopened.
+    let cold_store = BackupStorageColdLedgerStore::new(Arc::clone(&store)).unwrap();
+    assert_eq!(
+        cold_store.next_version().unwrap(),
+        Some(total_txns as Version)
+    );
+    for (version, txn) in txns.iter().enumerate() {
+        let (first_version, chunk) = cold_store.get_chunk(version as Version).unwrap().unwrap();
+        assert_eq!(first_version as usize, version / chunk_size * chunk_size);
+        assert_eq!(&chunk[version - first_version as usize], txn);
+    }
+    assert!(cold_store
+        .get_chunk(total_txns as Version)
+        .unwrap()
+        .is_none());
+    assert!(!cold_store.is_archived(total_txns as Version).unwrap());
+    assert_eq!(cold_store.archived_until(0).unwrap(), total_txns as Version);
+    assert_eq!(
+        cold_store.archived_until(total_txns as Version).unwrap(),
+        total_txns as Version
+    );
+
+    // Overlapping backups in the same storage are merged: a chunk covered by another one is
+    // ignored, and a chunk covering others replaces them.
+    let last_version = total_txns as Version - 1;
+    for (first_version, last_version) in [(1, last_version.saturating_sub(1)), (0, last_version)] {
+        if first_version > last_version {
+            continue;
+        }
+        cold_store
+            .archive(
+                first_version,
+                txns[first_version as usize..=last_version as usize].to_vec(),
+                backup_handler
+                    .get_transaction_range_proof(first_version, last_version)
+                    .unwrap(),
+            )
+            .unwrap();
+    }
+    let cold_store = BackupStorageColdLedgerStore::new(store).unwrap();
+    assert_eq!(cold_store.first_version().unwrap(), Some(0));
+    for (version, txn) in txns.iter().enumerate() {
+        assert!(cold_store.is_archived(version as Version).unwrap());
+        let (first_version, chunk) = cold_store.get_chunk(version as Version).unwrap().unwrap();
+        assert_eq!(first_version, 0);
+        assert_eq!(chunk.len(), total_txns);
+        assert_eq!(&chunk[version], txn);
+    }
+
+    // The store can be dropped from within another runtime.
+    tokio::runtime::Runtime::new()
+        .unwrap()
+        .block_on(async move { drop(cold_store) });
+}
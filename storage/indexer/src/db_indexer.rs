@@ -2,14 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    event_v2_translator::EventV2TranslationEngine, metrics::TIMER,
-    utils::PrefixedStateValueIterator,
+    event_v2_translator::EventV2TranslationEngine,
+    metrics::TIMER,
+    utils::{get_involved_addresses, get_object_owner, PrefixedStateValueIterator},
 };
 use aptos_config::config::internal_indexer_db_config::InternalIndexerDBConfig;
 use aptos_db_indexer_schemas::{
     metadata::{MetadataKey, MetadataValue, StateSnapshotProgress},
     schema::{
-        event_by_key::EventByKeySchema, event_by_version::EventByVersionSchema,
+        account_transaction::AccountTransactionSchema, event_by_key::EventByKeySchema,
+        event_by_type::EventByTypeSchema, event_by_version::EventByVersionSchema,
         event_sequence_number::EventSequenceNumberSchema,
         indexer_metadata::InternalIndexerMetadataSchema,
         ordered_transaction_by_account::OrderedTransactionByAccountSchema,
@@ -20,6 +22,7 @@ use aptos_db_indexer_schemas::{
         MAX_REQUEST_LIMIT,
     },
 };
+use aptos_infallible::Mutex;
 use aptos_logger::warn;
 use aptos_metrics_core::TimerHelper;
 use aptos_schemadb::{batch::SchemaBatch, DB};
@@ -28,7 +31,7 @@ use aptos_storage_interface::{
 };
 use aptos_types::{
     account_address::AccountAddress,
-    account_config::{BURN_TYPE, MINT_TYPE},
+    account_config::{ObjectGroupResource, BURN_TYPE, MINT_TYPE},
     contract_event::{ContractEvent, ContractEventV1, ContractEventV2, EventWithVersion},
     event::EventKey,
     indexer::indexer_db_reader::Order,
@@ -36,12 +39,16 @@ use aptos_types::{
         state_key::{prefix::StateKeyPrefix, StateKey},
         state_value::StateValue,
     },
-    transaction::{AccountOrderedTransactionsWithProof, ReplayProtector, Transaction, Version},
+    transaction::{
+        AccountOrderedTransactionsWithProof, ReplayProtector, Transaction, TransactionWithProof,
+        Version,
+    },
     write_set::{TransactionWrite, WriteSet},
 };
+use move_core_types::{language_storage::TypeTag, move_resource::MoveStructType};
 use std::{
     cmp::min,
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     sync::{
         mpsc::{self, Receiver, Sender},
         Arc,
@@ -127,6 +134,14 @@ impl InternalIndexerDB {
         self.get_version(&MetadataKey::EventV2TranslationVersion)
     }
 
+    pub fn get_account_transaction_version(&self) -> Result<Option<Version>> {
+        self.get_version(&MetadataKey::AccountTransactionVersion)
+    }
+
+    pub fn get_event_by_type_version(&self) -> Result<Option<Version>> {
+        self.get_version(&MetadataKey::EventByTypeVersion)
+    }
+
     pub fn event_enabled(&self) -> bool {
         self.config.enable_event
     }
@@ -143,6 +158,14 @@ impl InternalIndexerDB {
         self.config.enable_statekeys
     }
 
+    pub fn account_transactions_enabled(&self) -> bool {
+        self.config.enable_account_transactions
+    }
+
+    pub fn event_by_type_enabled(&self) -> bool {
+        self.config.enable_event_by_type
+    }
+
     pub fn get_inner_db_ref(&self) -> &Arc<DB> {
         &self.db
     }
@@ -190,6 +213,93 @@ impl InternalIndexerDB {
         ))
     }
 
+    /// Returns the versions of the transactions that touched `address` (in ascending order),
+    /// starting at `start_version`. If `start_version` is `None`, the latest `limit` versions
+    /// are returned. Result won't contain versions < `min_version` (e.g., pruned versions) or
+    /// versions > `ledger_version`.
+    pub fn get_account_transaction_versions(
+        &self,
+        address: AccountAddress,
+        start_version: Option<Version>,
+        limit: u64,
+        min_version: Version,
+        ledger_version: Version,
+    ) -> Result<Vec<Version>> {
+        let mut versions = Vec::new();
+        match start_version {
+            Some(start_version) => {
+                let mut iter = self.db.iter::<AccountTransactionSchema>()?;
+                iter.seek(&(address, start_version.max(min_version)))?;
+                for res in iter.take(limit as usize) {
+                    let ((account, version), ()) = res?;
+                    if account != address || version > ledger_version {
+                        break;
+                    }
+                    versions.push(version);
+                }
+            },
+            None => {
+                let mut iter = self.db.rev_iter::<AccountTransactionSchema>()?;
+                iter.seek_for_prev(&(address, ledger_version))?;
+                for res in iter.take(limit as usize) {
+                    let ((account, version), ()) = res?;
+                    if account != address || version < min_version {
+                        break;
+                    }
+                    versions.push(version);
+                }
+                versions.reverse();
+            },
+        }
+        Ok(versions)
+    }
+
+    /// Returns the events of type `type_tag` identified by transaction version and index among
+    /// all events emitted by the same transaction, in ascending order. If `start_version` is
+    /// `None`, the latest `limit` events are returned. Result won't contain records with a
+    /// transaction version < `min_version` (e.g., pruned versions) or > `end_version`.
+    pub fn lookup_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Option<Version>,
+        limit: u64,
+        min_version: Version,
+        end_version: Version,
+    ) -> Result<
+        Vec<(
+            Version, // transaction version it belongs to
+            u64,     // index among events for the same transaction
+        )>,
+    > {
+        let mut result = Vec::new();
+        match start_version {
+            Some(start_version) => {
+                let mut iter = self.db.iter::<EventByTypeSchema>()?;
+                iter.seek(&(type_tag.clone(), start_version.max(min_version), 0))?;
+                for res in iter.take(limit as usize) {
+                    let ((tag, ver, idx), ()) = res?;
+                    if tag != *type_tag || ver > end_version {
+                        break;
+                    }
+                    result.push((ver, idx));
+                }
+            },
+            None => {
+                let mut iter = self.db.rev_iter::<EventByTypeSchema>()?;
+                iter.seek_for_prev(&(type_tag.clone(), end_version, u64::MAX))?;
+                for res in iter.take(limit as usize) {
+                    let ((tag, ver, idx), ()) = res?;
+                    if tag != *type_tag || ver < min_version {
+                        break;
+                    }
+                    result.push((ver, idx));
+                }
+                result.reverse();
+            },
+        }
+        Ok(result)
+    }
+
     pub fn get_latest_sequence_number(
         &self,
         ledger_version: Version,
@@ -308,6 +418,12 @@ pub struct DBIndexer {
     sender: Sender<Option<SchemaBatch>>,
     committer_handle: Option<thread::JoinHandle<()>>,
     pub event_v2_translation_engine: EventV2TranslationEngine,
+    /// The indices being backfilled in the background (by the key of their progress), with the
+    /// next version `process_a_batch` is going to index. The backfill records the progress of
+    /// the index once it has caught up with this version.
+    backfills: Mutex<HashMap<MetadataKey, Version>>,
+    /// The errors of the backfills that failed (by the key of the progress of their index)
+    failed_backfills: Mutex<HashMap<MetadataKey, String>>,
 }
 
 impl Drop for DBIndexer {
@@ -343,6 +459,8 @@ impl DBIndexer {
                 db_reader,
                 internal_indexer_db,
             ),
+            backfills: Mutex::new(HashMap::new()),
+            failed_backfills: Mutex::new(HashMap::new()),
         }
     }
 
@@ -486,6 +604,14 @@ impl DBIndexer {
                 })?;
             }
 
+            if self.indexer_db.account_transactions_enabled() {
+                self.index_account_transaction(&mut batch, version, &txn, &events, &writeset)?;
+            }
+
+            if self.indexer_db.event_by_type_enabled() {
+                Self::index_events_by_type(&mut batch, version, &events)?;
+            }
+
             if self.indexer_db.statekeys_enabled() {
                 writeset.write_op_iter().for_each(|(state_key, write_op)| {
                     if write_op.is_creation() || write_op.is_modification() {
@@ -539,6 +665,30 @@ impl DBIndexer {
                 &MetadataValue::Version(version - 1),
             )?;
         }
+        // The lock is held until the batch is sent, so that the progress recorded by a
+        // backfill is committed after the batches it covers.
+        let mut backfills = self.backfills.lock();
+        for (enabled, key) in [
+            (
+                self.indexer_db.account_transactions_enabled(),
+                MetadataKey::AccountTransactionVersion,
+            ),
+            (
+                self.indexer_db.event_by_type_enabled(),
+                MetadataKey::EventByTypeVersion,
+            ),
+        ] {
+            if !enabled {
+                continue;
+            }
+            match backfills.get_mut(&key) {
+                Some(next_version) => *next_version = version,
+                None => batch.put::<InternalIndexerMetadataSchema>(
+                    &key,
+                    &MetadataValue::Version(version - 1),
+                )?,
+            }
+        }
         batch.put::<InternalIndexerMetadataSchema>(
             &MetadataKey::LatestVersion,
             &MetadataValue::Version(version - 1),
@@ -549,6 +699,172 @@ impl DBIndexer {
         Ok(version)
     }
 
+    fn index_account_transaction(
+        &self,
+        batch: &mut SchemaBatch,
+        version: Version,
+        txn: &Transaction,
+        events: &[ContractEvent],
+        writeset: &WriteSet,
+    ) -> Result<()> {
+        let lookup_object_owner = |object| self.get_object_owner(object, version);
+        for address in get_involved_addresses(txn, events, writeset, lookup_object_owner) {
+            batch.put::<AccountTransactionSchema>(&(address, version), &())?;
+        }
+        Ok(())
+    }
+
+    /// Returns the owner of the object at the given version, if it can still be read from
+    /// the main db (i.e., it exists and hasn't been pruned)
+    fn get_object_owner(&self, object: AccountAddress, version: Version) -> Option<AccountAddress> {
+        let group_tag = ObjectGroupResource::struct_tag();
+        let state_key = StateKey::resource_group(&object, &group_tag);
+        let state_value = self
+            .main_db_reader
+            .get_state_value_by_version(&state_key, version)
+            .ok()??;
+        get_object_owner(&group_tag, state_value.bytes())
+    }
+
+    fn index_events_by_type(
+        batch: &mut SchemaBatch,
+        version: Version,
+        events: &[ContractEvent],
+    ) -> Result<()> {
+        events.iter().enumerate().try_for_each(|(idx, event)| {
+            batch.put::<EventByTypeSchema>(&(event.type_tag().clone(), version, idx as u64), &())
+        })
+    }
+
+    /// Marks the index whose progress is recorded under `progress_key` as being backfilled up to
+    /// `end_version`, so that `process_a_batch` keeps indexing new transactions without
+    /// recording the progress of the index until the backfill completes.
+    pub fn start_backfill(&self, progress_key: MetadataKey, end_version: Version) {
+        self.backfills.lock().insert(progress_key, end_version);
+    }
+
+    /// Records that the backfill of the index whose progress is recorded under `progress_key`
+    /// failed, so that requests to the index fail instead of waiting for the backfill forever.
+    pub fn fail_backfill(&self, progress_key: MetadataKey, error: String) {
+        self.failed_backfills.lock().insert(progress_key, error);
+    }
+
+    /// Ensures the index whose progress is recorded under `progress_key` covers
+    /// `ledger_version`, i.e., it isn't still being backfilled and its backfill didn't fail.
+    fn ensure_backfilled(
+        &self,
+        progress_key: MetadataKey,
+        index_name: &str,
+        ledger_version: Version,
+    ) -> Result<()> {
+        if let Some(error) = self.failed_backfills.lock().get(&progress_key) {
+            bail!("Failed to backfill the {} index: {}", index_name, error)
+        }
+        let index_version = self.indexer_db.get_version(&progress_key)?;
+        if index_version.is_none_or(|v| v < ledger_version) {
+            return Err(AptosDbError::IndexBackfilling(format!(
+                "The {} index",
+                index_name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Backfills the account transaction index for all transactions from `start_version` to
+    /// `end_version` (left inclusive, right exclusive), e.g., after the index is enabled on an
+    /// existing indexer db. Only the account transaction index and its progress are written.
+    pub fn backfill_account_transactions(
+        &self,
+        start_version: Version,
+        end_version: Version,
+    ) -> Result<Version> {
+        self.backfill(
+            MetadataKey::AccountTransactionVersion,
+            start_version,
+            end_version,
+            |batch, version, txn, events, writeset| {
+                self.index_account_transaction(batch, version, txn, events, writeset)
+            },
+        )
+    }
+
+    /// Backfills the event by type index for all transactions from `start_version` to
+    /// `end_version` (left inclusive, right exclusive), e.g., after the index is enabled on an
+    /// existing indexer db. Only the event by type index and its progress are written.
+    pub fn backfill_events_by_type(
+        &self,
+        start_version: Version,
+        end_version: Version,
+    ) -> Result<Version> {
+        self.backfill(
+            MetadataKey::EventByTypeVersion,
+            start_version,
+            end_version,
+            |batch, version, _txn, events, _writeset| {
+                Self::index_events_by_type(batch, version, events)
+            },
+        )
+    }
+
+    fn backfill(
+        &self,
+        progress_key: MetadataKey,
+        start_version: Version,
+        end_version: Version,
+        index: impl Fn(
+            &mut SchemaBatch,
+            Version,
+            &Transaction,
+            &[ContractEvent],
+            &WriteSet,
+        ) -> Result<()>,
+    ) -> Result<Version> {
+        let mut version = start_version;
+        while version < end_version {
+            let _timer = TIMER.timer_with(&["backfill"]);
+            // `get_num_of_transactions` includes `end_version` itself.
+            let num_transactions = self.get_num_of_transactions(version, end_version - 1)?;
+            if num_transactions == 0 {
+                break;
+            }
+
+            let mut batch = SchemaBatch::new();
+            self.get_main_db_iter(version, num_transactions)?
+                .try_for_each(|res| {
+                    let (txn, events, writeset) = res?;
+                    index(&mut batch, version, &txn, &events, &writeset)?;
+                    version += 1;
+                    Ok::<(), AptosDbError>(())
+                })?;
+            batch.put::<InternalIndexerMetadataSchema>(
+                &progress_key,
+                &MetadataValue::Version(version - 1),
+            )?;
+            self.sender
+                .send(Some(batch))
+                .map_err(|e| AptosDbError::Other(e.to_string()))?;
+        }
+
+        // Take over the progress of the versions indexed by `process_a_batch` meanwhile.
+        let mut backfills = self.backfills.lock();
+        if version < end_version {
+            return Ok(version);
+        }
+        if let Some(next_version) = backfills.remove(&progress_key) {
+            if next_version > end_version {
+                let mut batch = SchemaBatch::new();
+                batch.put::<InternalIndexerMetadataSchema>(
+                    &progress_key,
+                    &MetadataValue::Version(next_version - 1),
+                )?;
+                self.sender
+                    .send(Some(batch))
+                    .map_err(|e| AptosDbError::Other(e.to_string()))?;
+            }
+        }
+        Ok(version)
+    }
+
     pub fn translate_event_v2_to_v1(
         &self,
         v2: &ContractEventV2,
@@ -611,6 +927,90 @@ impl DBIndexer {
         Ok(AccountOrderedTransactionsWithProof::new(txns_with_proofs))
     }
 
+    /// Returns all transactions that touched `address` (in ascending order of version). See
+    /// [`InternalIndexerDB::get_account_transaction_versions`] for the pagination semantics.
+    pub fn get_account_transactions(
+        &self,
+        address: AccountAddress,
+        start_version: Option<Version>,
+        limit: u64,
+        include_events: bool,
+        ledger_version: Version,
+    ) -> Result<Vec<TransactionWithProof>> {
+        self.indexer_db
+            .ensure_cover_ledger_version(ledger_version)?;
+        error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+        self.ensure_backfilled(
+            MetadataKey::AccountTransactionVersion,
+            "account transaction",
+            ledger_version,
+        )?;
+
+        // Skip the transactions that have been pruned from the main db (before applying the
+        // limit, so that pruned versions don't shrink the page)
+        let lowest_viable_version = self.get_main_db_lowest_viable_version()?;
+        self.indexer_db
+            .get_account_transaction_versions(
+                address,
+                start_version,
+                limit,
+                lowest_viable_version,
+                ledger_version,
+            )?
+            .into_iter()
+            .map(|txn_version| {
+                self.main_db_reader.get_transaction_by_version(
+                    txn_version,
+                    ledger_version,
+                    include_events,
+                )
+            })
+            .collect()
+    }
+
+    /// Returns events of type `type_tag` emitted between `start_version` and `end_version`
+    /// (both inclusive), in ascending order of (version, index). If `start_version` is `None`,
+    /// the latest `limit` events up to `end_version` are returned. `end_version` defaults to
+    /// `ledger_version`.
+    pub fn get_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Option<Version>,
+        end_version: Option<Version>,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithVersion>> {
+        self.indexer_db
+            .ensure_cover_ledger_version(ledger_version)?;
+        error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+        self.ensure_backfilled(
+            MetadataKey::EventByTypeVersion,
+            "event by type",
+            ledger_version,
+        )?;
+
+        // Skip the events that have been pruned from the main db (before applying the limit, so
+        // that pruned versions don't shrink the page)
+        let lowest_viable_version = self.get_main_db_lowest_viable_version()?;
+        let end_version = end_version.map_or(ledger_version, |v| v.min(ledger_version));
+        self.indexer_db
+            .lookup_events_by_type(
+                type_tag,
+                start_version,
+                limit,
+                lowest_viable_version,
+                end_version,
+            )?
+            .into_iter()
+            .map(|(ver, idx)| {
+                let event = self
+                    .main_db_reader
+                    .get_event_by_version_and_index(ver, idx)?;
+                Ok(EventWithVersion::new(ver, event))
+            })
+            .collect()
+    }
+
     pub fn get_prefixed_state_value_iterator(
         &self,
         key_prefix: &StateKeyPrefix,
@@ -13,8 +13,9 @@ use aptos_types::{
         state_value::StateValue,
         table::{TableHandle, TableInfo},
     },
-    transaction::{AccountOrderedTransactionsWithProof, Version},
+    transaction::{AccountOrderedTransactionsWithProof, TransactionWithProof, Version},
 };
+use move_core_types::language_storage::TypeTag;
 use std::sync::Arc;
 
 #[derive(Clone)]
@@ -113,6 +114,30 @@ impl IndexerReader for IndexerReaders {
         anyhow::bail!("DB indexer reader is not available")
     }
 
+    fn get_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Option<Version>,
+        end_version: Option<Version>,
+        limit: u64,
+        ledger_version: Version,
+    ) -> anyhow::Result<Vec<EventWithVersion>> {
+        if let Some(db_indexer_reader) = &self.db_indexer_reader {
+            if db_indexer_reader.indexer_db.event_by_type_enabled() {
+                return Ok(db_indexer_reader.get_events_by_type(
+                    type_tag,
+                    start_version,
+                    end_version,
+                    limit,
+                    ledger_version,
+                )?);
+            } else {
+                anyhow::bail!("Internal event by type index is not enabled")
+            }
+        }
+        anyhow::bail!("DB indexer reader is not available")
+    }
+
     fn get_account_ordered_transactions(
         &self,
         address: AccountAddress,
@@ -137,6 +162,30 @@ impl IndexerReader for IndexerReaders {
         anyhow::bail!("DB indexer reader is not available")
     }
 
+    fn get_account_transactions(
+        &self,
+        address: AccountAddress,
+        start_version: Option<Version>,
+        limit: u64,
+        include_events: bool,
+        ledger_version: Version,
+    ) -> anyhow::Result<Vec<TransactionWithProof>> {
+        if let Some(db_indexer_reader) = &self.db_indexer_reader {
+            if db_indexer_reader.indexer_db.account_transactions_enabled() {
+                return Ok(db_indexer_reader.get_account_transactions(
+                    address,
+                    start_version,
+                    limit,
+                    include_events,
+                    ledger_version,
+                )?);
+            } else {
+                anyhow::bail!("Internal account transaction index is not enabled")
+            }
+        }
+        anyhow::bail!("DB indexer reader is not available")
+    }
+
     fn get_prefixed_state_value_iterator(
         &self,
         key_prefix: &StateKeyPrefix,
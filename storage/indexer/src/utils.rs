@@ -5,13 +5,28 @@ use aptos_db_indexer_schemas::schema::state_keys::StateKeysSchema;
 use aptos_schemadb::{iterator::SchemaIterator, ReadOptions, DB};
 use aptos_storage_interface::{DbReader, Result};
 use aptos_types::{
+    access_path::Path,
+    account_address::AccountAddress,
+    account_config::{
+        DepositFAEvent, ObjectCoreResource, ObjectGroupResource, Transfer, WithdrawFAEvent,
+        TRANSFER_TYPE,
+    },
+    contract_event::ContractEvent,
     state_store::{
-        state_key::{prefix::StateKeyPrefix, StateKey},
+        state_key::{inner::StateKeyInner, prefix::StateKeyPrefix, StateKey},
         state_value::StateValue,
     },
-    transaction::Version,
+    transaction::{Transaction, Version},
+    write_set::WriteSet,
+};
+use move_core_types::{
+    language_storage::{StructTag, TypeTag},
+    move_resource::MoveStructType,
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
 };
-use std::sync::Arc;
 
 pub struct PrefixedStateValueIterator<'a> {
     state_keys_iter: SchemaIterator<'a, StateKeysSchema>,
@@ -81,3 +96,97 @@ impl Iterator for PrefixedStateValueIterator<'_> {
         self.next_impl().transpose()
     }
 }
+
+/// Returns all addresses involved in a transaction, i.e., the signers of the transaction, the
+/// addresses whose state was written, the creators of the event handles that the transaction
+/// emitted events to, the parties of object transfers (including the new owners of objects), and
+/// the owners of the fungible stores that were deposited to or withdrawn from.
+///
+/// The owner of a fungible store is taken from the write set if the store's object group was
+/// written, and is otherwise looked up with `lookup_object_owner`.
+///
+/// Note: table items are not attributed to accounts.
+pub fn get_involved_addresses(
+    txn: &Transaction,
+    events: &[ContractEvent],
+    write_set: &WriteSet,
+    lookup_object_owner: impl Fn(AccountAddress) -> Option<AccountAddress>,
+) -> HashSet<AccountAddress> {
+    let mut addresses = HashSet::new();
+
+    if let Some(signed_txn) = txn.try_as_signed_user_txn() {
+        addresses.insert(signed_txn.sender());
+        let authenticator = signed_txn.authenticator_ref();
+        addresses.extend(authenticator.secondary_signer_addresses());
+        addresses.extend(authenticator.fee_payer_address());
+    }
+
+    let mut object_owners = HashMap::new();
+    for (state_key, write_op) in write_set.write_op_iter() {
+        if let StateKeyInner::AccessPath(access_path) = state_key.inner() {
+            addresses.insert(access_path.address);
+            if let (Path::ResourceGroup(group_tag), Some(bytes)) =
+                (access_path.get_path(), write_op.bytes())
+            {
+                if let Some(owner) = get_object_owner(&group_tag, bytes) {
+                    addresses.insert(owner);
+                    object_owners.insert(access_path.address, owner);
+                }
+            }
+        }
+    }
+
+    for event in events {
+        match event {
+            ContractEvent::V1(v1) => {
+                addresses.insert(v1.key().get_creator_address());
+            },
+            ContractEvent::V2(v2) => {
+                if v2.type_tag() == &*TRANSFER_TYPE {
+                    if let Ok(transfer) = Transfer::try_from_bytes(v2.event_data()) {
+                        addresses.extend([*transfer.object(), *transfer.from(), *transfer.to()]);
+                    }
+                } else if let Some(store) = get_fungible_store(v2.type_tag(), v2.event_data()) {
+                    addresses.insert(store);
+                    let owner = object_owners
+                        .get(&store)
+                        .copied()
+                        .or_else(|| lookup_object_owner(store));
+                    addresses.extend(owner);
+                }
+            },
+        }
+    }
+
+    addresses
+}
+
+/// Returns the store of the given fungible asset deposit or withdraw event
+fn get_fungible_store(type_tag: &TypeTag, event_data: &[u8]) -> Option<AccountAddress> {
+    let TypeTag::Struct(struct_tag) = type_tag else {
+        return None;
+    };
+    if **struct_tag == DepositFAEvent::struct_tag() {
+        bcs::from_bytes::<DepositFAEvent>(event_data)
+            .ok()
+            .map(|event| event.store)
+    } else if **struct_tag == WithdrawFAEvent::struct_tag() {
+        bcs::from_bytes::<WithdrawFAEvent>(event_data)
+            .ok()
+            .map(|event| event.store)
+    } else {
+        None
+    }
+}
+
+/// Returns the owner of the object, if the given resource group is an object group
+pub(crate) fn get_object_owner(group_tag: &StructTag, bytes: &[u8]) -> Option<AccountAddress> {
+    if group_tag != &ObjectGroupResource::struct_tag() {
+        return None;
+    }
+    let group: BTreeMap<StructTag, Vec<u8>> = bcs::from_bytes(bytes).ok()?;
+    let object_core = group.get(&ObjectCoreResource::struct_tag())?;
+    bcs::from_bytes::<ObjectCoreResource>(object_core)
+        .ok()
+        .map(|object_core| object_core.owner)
+}
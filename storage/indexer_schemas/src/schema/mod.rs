@@ -6,7 +6,9 @@
 //!
 //! All schemas are `pub(crate)` so not shown in rustdoc, refer to the source code to see details.
 
+pub mod account_transaction;
 pub mod event_by_key;
+pub mod event_by_type;
 pub mod event_by_version;
 pub mod event_sequence_number;
 pub mod indexer_metadata;
@@ -28,6 +30,8 @@ pub const ORDERED_TRANSACTION_BY_ACCOUNT_CF_NAME: ColumnFamilyName = "transactio
 pub const STATE_KEYS_CF_NAME: ColumnFamilyName = "state_keys";
 pub const TRANSLATED_V1_EVENT_CF_NAME: ColumnFamilyName = "translated_v1_event";
 pub const EVENT_SEQUENCE_NUMBER_CF_NAME: ColumnFamilyName = "event_sequence_number";
+pub const ACCOUNT_TRANSACTION_CF_NAME: ColumnFamilyName = "account_transaction";
+pub const EVENT_BY_TYPE_CF_NAME: ColumnFamilyName = "event_by_type";
 
 pub fn column_families() -> Vec<ColumnFamilyName> {
     vec![
@@ -47,6 +51,8 @@ pub fn internal_indexer_column_families() -> Vec<ColumnFamilyName> {
         STATE_KEYS_CF_NAME,
         TRANSLATED_V1_EVENT_CF_NAME,
         EVENT_SEQUENCE_NUMBER_CF_NAME,
+        ACCOUNT_TRANSACTION_CF_NAME,
+        EVENT_BY_TYPE_CF_NAME,
     ]
 }
 
@@ -90,7 +90,10 @@ use aptos_metrics_core::{IntCounterHelper, IntCounterVecHelper};
 use aptos_storage_interface::{db_ensure as ensure, db_other_bail, AptosDbError, Result};
 use aptos_types::{
     nibble::{nibble_path::NibblePath, Nibble, ROOT_NIBBLE_HEIGHT},
-    proof::{SparseMerkleProof, SparseMerkleProofExt, SparseMerkleRangeProof},
+    proof::{
+        definition::NodeInProof, SparseMerkleBatchProof, SparseMerkleLeafNode, SparseMerkleProof,
+        SparseMerkleProofExt, SparseMerkleRangeProof,
+    },
     state_store::{state_key::StateKey, state_value::StateValue},
     transaction::Version,
 };
@@ -797,6 +800,160 @@ where
         db_other_bail!("Jellyfish Merkle tree has cyclic graph inside.");
     }
 
+    /// Returns the values (if applicable) of `keys`, which must be unique and sorted, along with a
+    /// single merkle proof of all of them, in which the nodes shared by the paths of several keys
+    /// only appear once.
+    pub fn get_with_batch_proof(
+        &self,
+        keys: &[HashValue],
+        version: Version,
+    ) -> Result<(
+        Vec<Option<(HashValue, (K, Version))>>,
+        SparseMerkleBatchProof,
+    )> {
+        ensure!(
+            keys.windows(2).all(|w| w[0] < w[1]),
+            "Keys to prove must be unique and sorted."
+        );
+
+        let mut values = Vec::with_capacity(keys.len());
+        let mut proofs = Vec::with_capacity(keys.len());
+        if !keys.is_empty() {
+            let root_node_key = NodeKey::new_empty_path(version);
+            let root_node = self
+                .reader
+                .get_node_with_tag(&root_node_key, "get_proof")
+                .map_err(|_| AptosDbError::MissingRootError(version))?;
+            self.get_with_batch_proof_impl(
+                &root_node_key,
+                root_node,
+                keys,
+                0,
+                &mut Vec::new(),
+                &mut values,
+                &mut proofs,
+            )?;
+        }
+        Ok((values, SparseMerkleBatchProof::from_proofs(&proofs)?))
+    }
+
+    /// Walks down from `node` at `nibble_depth` for all of `keys`, which share the path to it, so
+    /// that every node is read once no matter how many of the keys go through it. `siblings` are
+    /// those of the path to `node`, which is where the proofs of `keys` start to diverge.
+    #[allow(clippy::too_many_arguments)]
+    fn get_with_batch_proof_impl(
+        &self,
+        node_key: &NodeKey,
+        node: Node<K>,
+        keys: &[HashValue],
+        nibble_depth: usize,
+        siblings: &mut Vec<NodeInProof>,
+        out_values: &mut Vec<Option<(HashValue, (K, Version))>>,
+        out_proofs: &mut Vec<(HashValue, SparseMerkleProof)>,
+    ) -> Result<()> {
+        match node {
+            Node::Internal(internal_node) => {
+                if internal_node.leaf_count() == 1 {
+                    // Logically this node should be a leaf node, it got pushed down for
+                    // sharding, skip the siblings.
+                    let (only_child_nibble, Child { version, .. }) =
+                        internal_node.children_sorted().next().unwrap();
+                    let child_node_key = node_key.gen_child_node_key(*version, *only_child_nibble);
+                    let child_node = self
+                        .reader
+                        .get_node_with_tag(&child_node_key, "get_proof")?;
+                    return self.get_with_batch_proof_impl(
+                        &child_node_key,
+                        child_node,
+                        keys,
+                        nibble_depth,
+                        siblings,
+                        out_values,
+                        out_proofs,
+                    );
+                }
+                ensure!(
+                    nibble_depth < ROOT_NIBBLE_HEIGHT,
+                    "Jellyfish Merkle tree has cyclic graph inside."
+                );
+
+                // The keys are sorted, so the ones under the same child are next to each other.
+                for (child_index, keys_under_child) in &keys
+                    .iter()
+                    .chunk_by(|key| NibblePath::new_even(key.to_vec()).get_nibble(nibble_depth))
+                {
+                    let keys_under_child: Vec<_> = keys_under_child.copied().collect();
+                    let num_siblings = siblings.len();
+                    let child_node_key = internal_node.get_child_with_siblings(
+                        node_key,
+                        child_index,
+                        Some(self.reader),
+                        siblings,
+                        nibble_depth * 4,
+                        0,
+                    )?;
+                    match child_node_key {
+                        Some(child_node_key) => {
+                            let child_node = self
+                                .reader
+                                .get_node_with_tag(&child_node_key, "get_proof")?;
+                            self.get_with_batch_proof_impl(
+                                &child_node_key,
+                                child_node,
+                                &keys_under_child,
+                                nibble_depth + 1,
+                                siblings,
+                                out_values,
+                                out_proofs,
+                            )?;
+                        },
+                        None => {
+                            for key in keys_under_child {
+                                out_values.push(None);
+                                out_proofs.push((
+                                    key,
+                                    SparseMerkleProofExt::new_partial(None, siblings.clone(), 0)
+                                        .into(),
+                                ));
+                            }
+                        },
+                    }
+                    siblings.truncate(num_siblings);
+                }
+            },
+            Node::Leaf(leaf_node) => {
+                for key in keys {
+                    out_values.push(
+                        if leaf_node.account_key() == key {
+                            Some((leaf_node.value_hash(), leaf_node.value_index().clone()))
+                        } else {
+                            None
+                        },
+                    );
+                    out_proofs.push((
+                        *key,
+                        SparseMerkleProofExt::new_partial(
+                            Some(SparseMerkleLeafNode::new(
+                                *leaf_node.account_key(),
+                                leaf_node.value_hash(),
+                            )),
+                            siblings.clone(),
+                            0,
+                        )
+                        .into(),
+                    ));
+                }
+            },
+            Node::Null => {
+                for key in keys {
+                    out_values.push(None);
+                    out_proofs.push((*key, SparseMerkleProofExt::new(None, vec![]).into()));
+                }
+            },
+        }
+        Ok(())
+    }
+
     /// Gets the proof that shows a list of keys up to `rightmost_key_to_prove` exist at `version`.
     pub fn get_range_proof(
         &self,
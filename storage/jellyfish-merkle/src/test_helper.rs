@@ -10,7 +10,7 @@ use aptos_crypto::{
 use aptos_crypto_derive::{BCSCryptoHash, CryptoHasher};
 use aptos_storage_interface::jmt_update_refs;
 use aptos_types::{
-    proof::{SparseMerkleInternalNode, SparseMerkleRangeProof},
+    proof::{SparseMerkleBatchProof, SparseMerkleInternalNode, SparseMerkleRangeProof},
     transaction::Version,
 };
 use proptest::{
@@ -20,7 +20,7 @@ use proptest::{
 use proptest_derive::Arbitrary;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{BTreeMap, HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     ops::Bound,
 };
 
@@ -137,6 +137,41 @@ pub fn test_get_with_proof<V: TestKey>(
     test_nonexistent_keys_impl(&tree, version, &nonexistent_keys);
 }
 
+pub fn test_get_with_batch_proof<V: TestKey>(
+    (existent_kvs, nonexistent_keys): (HashMap<HashValue, (HashValue, V)>, Vec<HashValue>),
+) {
+    let (db, version) = init_mock_db(&existent_kvs);
+    let tree = JellyfishMerkleTree::new(&db);
+    let root_hash = tree.get_root_hash(version).unwrap();
+
+    let keys: Vec<_> = existent_kvs
+        .keys()
+        .chain(nonexistent_keys.iter())
+        .copied()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let (values, proof) = tree.get_with_batch_proof(&keys, version).unwrap();
+    let elements: Vec<_> = keys
+        .iter()
+        .zip(&values)
+        .map(|(key, value)| (*key, value.as_ref().map(|v| v.0)))
+        .collect();
+    assert!(proof.verify_by_hash(root_hash, &elements).is_ok());
+    for (key, value) in keys.iter().zip(values) {
+        assert_eq!(value.map(|v| (v.0, v.1 .0)), existent_kvs.get(key).cloned());
+    }
+
+    // Same as combining the individual proofs, which it's never larger than.
+    let proofs: Vec<_> = keys
+        .iter()
+        .map(|key| (*key, tree.get_with_proof(*key, version).unwrap().1))
+        .collect();
+    assert_eq!(proof, SparseMerkleBatchProof::from_proofs(&proofs).unwrap());
+    let num_siblings: usize = proofs.iter().map(|(_, p)| p.siblings().len()).sum();
+    assert!(proof.siblings().len() <= num_siblings);
+}
+
 pub fn arb_kv_pair_with_distinct_last_nibble<V: TestKey>(
 ) -> impl Strategy<Value = ((HashValue, (HashValue, V)), (HashValue, (HashValue, V)))> {
     (
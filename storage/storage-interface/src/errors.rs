@@ -18,6 +18,9 @@ pub enum AptosDbError {
     TooManyRequested(u64, u64),
     #[error("Missing state root node at version {0}, probably pruned.")]
     MissingRootError(u64),
+    /// The index is still being backfilled, so it can't serve requests yet.
+    #[error("{0} is still being backfilled.")]
+    IndexBackfilling(String),
     /// Other non-classified error.
     #[error("AptosDB Other Error: {0}")]
     Other(String),
@@ -0,0 +1,36 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+/// The categories of ledger data whose retention can be configured separately, see
+/// `LedgerRetentionOverrides`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LedgerDataCategory {
+    Events,
+    Transactions,
+    TransactionInfos,
+    TransactionAuxiliaryData,
+    PersistedAuxiliaryInfo,
+    WriteSets,
+}
+
+impl LedgerDataCategory {
+    pub const ALL: [Self; 6] = [
+        Self::Events,
+        Self::Transactions,
+        Self::TransactionInfos,
+        Self::TransactionAuxiliaryData,
+        Self::PersistedAuxiliaryInfo,
+        Self::WriteSets,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Events => "Event",
+            Self::Transactions => "Transaction",
+            Self::TransactionInfos => "TransactionInfo",
+            Self::TransactionAuxiliaryData => "TransactionAuxiliaryData",
+            Self::PersistedAuxiliaryInfo => "PersistedAuxiliaryInfo",
+            Self::WriteSets => "WriteSet",
+        }
+    }
+}
@@ -21,7 +21,7 @@ use aptos_types::{
     state_store::{
         state_key::StateKey,
         state_storage_usage::StateStorageUsage,
-        state_value::{StateValue, StateValueChunkWithProof},
+        state_value::{StateValue, StateValueChunkWithProof, StateValuesWithBatchProof},
         table::{TableHandle, TableInfo},
     },
     transaction::{
@@ -38,6 +38,7 @@ use thiserror::Error;
 pub mod block_info;
 pub mod chunk_to_commit;
 pub mod errors;
+mod ledger_data_category;
 mod ledger_summary;
 mod metrics;
 #[cfg(any(test, feature = "fuzzing"))]
@@ -51,6 +52,7 @@ use crate::{
 pub use aptos_types::block_info::BlockHeight;
 use aptos_types::state_store::state_key::prefix::StateKeyPrefix;
 pub use errors::AptosDbError;
+pub use ledger_data_category::LedgerDataCategory;
 pub use ledger_summary::LedgerSummary;
 
 pub type Result<T, E = AptosDbError> = std::result::Result<T, E>;
@@ -195,6 +197,19 @@ pub trait DbReader: Send + Sync {
         /// [AptosDB::get_first_write_set_version]: ../aptosdb/struct.AptosDB.html#method.get_first_write_set_version
         fn get_first_write_set_version(&self) -> Result<Option<Version>>;
 
+        /// See [AptosDB::get_first_ledger_data_version].
+        ///
+        /// [AptosDB::get_first_ledger_data_version]: ../aptosdb/struct.AptosDB.html#method.get_first_ledger_data_version
+        fn get_first_ledger_data_version(
+            &self,
+            category: LedgerDataCategory,
+        ) -> Result<Option<Version>>;
+
+        /// See [AptosDB::get_first_state_kv_version].
+        ///
+        /// [AptosDB::get_first_state_kv_version]: ../aptosdb/struct.AptosDB.html#method.get_first_state_kv_version
+        fn get_first_state_kv_version(&self) -> Result<Option<Version>>;
+
         /// See [AptosDB::get_transaction_outputs].
         ///
         /// [AptosDB::get_transaction_outputs]: ../aptosdb/struct.AptosDB.html#method.get_transaction_outputs
@@ -385,6 +400,19 @@ pub trait DbReader: Send + Sync {
             version: Version,
         ) -> Result<Option<(Version, StateValue)>>;
 
+        /// Returns up to `limit` changes of the value of `state_key`, starting at `start_version`
+        /// and no later than `ledger_version`. Each change is the version at which the value was
+        /// written and the new value, or `None` if the value was deleted.
+        ///
+        /// Errors if the history starting at `start_version` has been pruned.
+        fn get_state_value_history(
+            &self,
+            state_key: &StateKey,
+            start_version: Version,
+            limit: u64,
+            ledger_version: Version,
+        ) -> Result<Vec<(Version, Option<StateValue>)>>;
+
         /// Returns the proof of the given state key and version.
         fn get_state_proof_by_version_ext(
             &self,
@@ -477,6 +505,14 @@ pub trait DbReader: Send + Sync {
             chunk_size: usize,
         ) -> Result<StateValueChunkWithProof>;
 
+        /// Gets the values of `state_keys` at `version`, with a single proof of all of them. The
+        /// values are returned in increasing order of the hashes of the keys.
+        fn get_state_values_with_batch_proof(
+            &self,
+            version: Version,
+            state_keys: &[StateKey],
+        ) -> Result<StateValuesWithBatchProof>;
+
         /// Returns an iterator of state key value pairs starting from the index.
         fn get_state_value_chunk_iter(
             &self,
@@ -15,8 +15,115 @@ use crate::{
     },
     CompiledModule,
 };
-use move_core_types::{ability::AbilitySet, vm_status::StatusCode};
-use std::collections::BTreeSet;
+use move_core_types::{
+    ability::AbilitySet, account_address::AccountAddress, identifier::Identifier,
+    language_storage::ModuleId, vm_status::StatusCode,
+};
+use std::{collections::BTreeSet, fmt};
+
+/// A single reason why a new version of a module is not compatible with the old one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Incompatibility {
+    ModuleAddressChanged(AccountAddress),
+    ModuleNameChanged(Identifier),
+    StructRemoved(Identifier),
+    StructAbilitiesRemoved {
+        struct_name: Identifier,
+        removed: AbilitySet,
+    },
+    StructTypeParametersChanged(Identifier),
+    StructLayoutChanged {
+        struct_name: Identifier,
+        change: LayoutChange,
+    },
+    FunctionRemoved(Identifier),
+    FunctionChanged {
+        function_name: Identifier,
+        change: FunctionChange,
+    },
+    FriendsRemoved(Vec<ModuleId>),
+}
+
+/// How the layout of a struct or enum changed in an incompatible way.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LayoutChange {
+    /// A struct became an enum, or an enum became a struct.
+    StructKindChanged,
+    /// The enum variant at this position of the old enum was removed or renamed.
+    VariantChanged(Identifier),
+    /// The fields of the struct, or of the given enum variant, differ starting at this position.
+    /// Fields can be removed, added, renamed, or change their type.
+    FieldsChanged {
+        variant: Option<Identifier>,
+        position: usize,
+    },
+}
+
+/// How a function exposed by a module changed in an incompatible way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FunctionChange {
+    Visibility,
+    EntryRemoved,
+    AttributesRemoved,
+    ParameterTypes,
+    ReturnType,
+    TypeParameters,
+}
+
+impl fmt::Display for FunctionChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FunctionChange::Visibility => "changed visibility",
+            FunctionChange::EntryRemoved => "removed `entry` modifier",
+            FunctionChange::AttributesRemoved => "removed required attributes",
+            FunctionChange::ParameterTypes => "changed parameter types",
+            FunctionChange::ReturnType => "changed return type",
+            FunctionChange::TypeParameters => "changed type parameters",
+        })
+    }
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Incompatibility::ModuleAddressChanged(address) => {
+                write!(f, "module address changed to `{}`", address)
+            },
+            Incompatibility::ModuleNameChanged(name) => {
+                write!(f, "module name changed to `{}`", name)
+            },
+            Incompatibility::StructRemoved(name) => write!(f, "removed struct `{}`", name),
+            Incompatibility::StructAbilitiesRemoved {
+                struct_name,
+                removed,
+            } => write!(
+                f,
+                "removed abilities `{}` from struct `{}`",
+                removed, struct_name
+            ),
+            Incompatibility::StructTypeParametersChanged(name) => {
+                write!(f, "changed type parameters of struct `{}`", name)
+            },
+            Incompatibility::StructLayoutChanged { struct_name, .. } => {
+                write!(f, "changed layout of struct `{}`", struct_name)
+            },
+            Incompatibility::FunctionRemoved(name) => write!(f, "removed function `{}`", name),
+            Incompatibility::FunctionChanged {
+                function_name,
+                change,
+            } => write!(f, "{} of function `{}`", change, function_name),
+            Incompatibility::FriendsRemoved(friends) => write!(
+                f,
+                "removed friend declaration {}",
+                friends
+                    .iter()
+                    .map(|id| format!("`{}`", id))
+                    .collect::<Vec<_>>()
+                    .join(" and ")
+            ),
+        }
+    }
+}
 
 /// The result of a linking and layout compatibility check. Here is what the different combinations. NOTE that if `check_struct_layout` is false, type safety over a series of upgrades cannot be guaranteed.
 /// mean:
@@ -90,23 +197,50 @@ impl Compatibility {
     }
 
     /// Check compatibility for `new_module` relative to old module `old_module`.
-    #[allow(clippy::nonminimal_bool)] // simplification is more unreadable
     pub fn check(
         &self,
         old_module: &CompiledModule,
         new_module: &CompiledModule,
     ) -> PartialVMResult<()> {
+        let errors = self
+            .incompatibilities(old_module, new_module)
+            .iter()
+            .map(Incompatibility::to_string)
+            .collect::<Vec<_>>();
+
+        if !errors.is_empty() {
+            Err(
+                PartialVMError::new(StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE).with_message(
+                    format!(
+                        "Module update failure: new module not compatible with \
+                        existing module in `{}`: {}",
+                        old_module.self_id(),
+                        errors.join(", ")
+                    ),
+                ),
+            )
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns all reasons why `new_module` is not compatible with the old module `old_module`.
+    #[allow(clippy::nonminimal_bool)] // simplification is more unreadable
+    pub fn incompatibilities(
+        &self,
+        old_module: &CompiledModule,
+        new_module: &CompiledModule,
+    ) -> Vec<Incompatibility> {
         let mut errors = vec![];
 
         // module's name and address are unchanged
         if old_module.address() != new_module.address() {
-            errors.push(format!(
-                "module address changed to `{}`",
-                new_module.address()
-            ));
+            errors.push(Incompatibility::ModuleAddressChanged(*new_module.address()));
         }
         if old_module.name() != new_module.name() {
-            errors.push(format!("module name changed to `{}`", new_module.name()));
+            errors.push(Incompatibility::ModuleNameChanged(
+                new_module.name().to_owned(),
+            ));
         }
 
         let old_view = ModuleView::new(old_module);
@@ -120,30 +254,33 @@ impl Compatibility {
                     // Struct not present in new . Existing modules that depend on this struct will fail to link with the new version of the module.
                     // Also, struct layout cannot be guaranteed transitively, because after
                     // removing the struct, it could be re-added later with a different layout.
-                    errors.push(format!("removed struct `{}`", old_struct.name()));
+                    errors.push(Incompatibility::StructRemoved(old_struct.name().to_owned()));
                     break;
                 },
             };
 
             if !self.struct_abilities_compatible(old_struct.abilities(), new_struct.abilities()) {
-                errors.push(format!(
-                    "removed abilities `{}` from struct `{}`",
-                    old_struct.abilities().setminus(new_struct.abilities()),
-                    old_struct.name()
-                ));
+                errors.push(Incompatibility::StructAbilitiesRemoved {
+                    struct_name: old_struct.name().to_owned(),
+                    removed: old_struct.abilities().setminus(new_struct.abilities()),
+                });
             }
             if !self.struct_type_parameters_compatible(
                 old_struct.type_parameters(),
                 new_struct.type_parameters(),
             ) {
-                errors.push(format!(
-                    "changed type parameters of struct `{}`",
-                    old_struct.name()
+                errors.push(Incompatibility::StructTypeParametersChanged(
+                    old_struct.name().to_owned(),
                 ));
             }
             // Layout of old and new struct need to be compatible
-            if self.check_struct_layout && !self.struct_layout_compatible(&old_struct, new_struct) {
-                errors.push(format!("changed layout of struct `{}`", old_struct.name()));
+            if self.check_struct_layout {
+                if let Some(change) = self.struct_layout_change(&old_struct, new_struct) {
+                    errors.push(Incompatibility::StructLayoutChanged {
+                        struct_name: old_struct.name().to_owned(),
+                        change,
+                    });
+                }
             }
         }
 
@@ -190,7 +327,7 @@ impl Compatibility {
                     // any Entry functions to be deleted, when self.treat_entry_as_public is
                     // set (treats entry as public)
                     {
-                        errors.push(format!("removed function `{}`", old_func.name()));
+                        errors.push(Incompatibility::FunctionRemoved(old_func.name().to_owned()));
                     }
                     continue;
                 },
@@ -229,36 +366,39 @@ impl Compatibility {
                 };
             let is_attribute_compatible =
                 FunctionAttribute::is_compatible_with(old_func.attributes(), new_func.attributes());
-            let error_msg = if !is_vis_compatible {
-                Some("changed visibility")
+            let change = if !is_vis_compatible {
+                Some(FunctionChange::Visibility)
             } else if !is_entry_compatible {
-                Some("removed `entry` modifier")
+                Some(FunctionChange::EntryRemoved)
             } else if !is_attribute_compatible {
-                Some("removed required attributes")
+                Some(FunctionChange::AttributesRemoved)
             } else if !self.signature_compatible(
                 old_module,
                 old_func.parameters(),
                 new_module,
                 new_func.parameters(),
             ) {
-                Some("changed parameter types")
+                Some(FunctionChange::ParameterTypes)
             } else if !self.signature_compatible(
                 old_module,
                 old_func.return_type(),
                 new_module,
                 new_func.return_type(),
             ) {
-                Some("changed return type")
+                Some(FunctionChange::ReturnType)
             } else if !self.fun_type_parameters_compatible(
                 old_func.type_parameters(),
                 new_func.type_parameters(),
             ) {
-                Some("changed type parameters")
+                Some(FunctionChange::TypeParameters)
             } else {
                 None
             };
-            if let Some(msg) = error_msg {
-                errors.push(format!("{} of function `{}`", msg, old_func.name()));
+            if let Some(change) = change {
+                errors.push(Incompatibility::FunctionChanged {
+                    function_name: old_func.name().to_owned(),
+                    change,
+                });
             }
         }
 
@@ -273,31 +413,16 @@ impl Compatibility {
             let new_friend_module_ids: BTreeSet<_> =
                 new_module.immediate_friends().iter().cloned().collect();
             if !old_friend_module_ids.is_subset(&new_friend_module_ids) {
-                errors.push(format!(
-                    "removed friend declaration {}",
+                errors.push(Incompatibility::FriendsRemoved(
                     old_friend_module_ids
                         .difference(&new_friend_module_ids)
-                        .map(|id| format!("`{}`", id))
-                        .collect::<Vec<_>>()
-                        .join(" and ")
+                        .cloned()
+                        .collect(),
                 ))
             }
         }
 
-        if !errors.is_empty() {
-            Err(
-                PartialVMError::new(StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE).with_message(
-                    format!(
-                        "Module update failure: new module not compatible with \
-                        existing module in `{}`: {}",
-                        old_view.id(),
-                        errors.join(", ")
-                    ),
-                ),
-            )
-        } else {
-            Ok(())
-        }
+        errors
     }
 
     // When upgrading, the new abilities must be a superset of the old abilities.
@@ -347,40 +472,60 @@ impl Compatibility {
             )
     }
 
-    fn struct_layout_compatible(
+    /// Returns how the layout of the struct changed, if it is not compatible.
+    fn struct_layout_change(
         &self,
         old_struct: &StructDefinitionView<CompiledModule>,
         new_struct: &StructDefinitionView<CompiledModule>,
-    ) -> bool {
+    ) -> Option<LayoutChange> {
         if old_struct.variant_count() == 0 {
             // Old is regular struct, new needs to be as well (i.e. have zero variants) and compatible
             // fields.
-            new_struct.variant_count() == 0
-                && self.fields_compatible(
-                    old_struct.fields_optional_variant(None),
-                    new_struct.fields_optional_variant(None),
-                )
+            if new_struct.variant_count() != 0 {
+                return Some(LayoutChange::StructKindChanged);
+            }
+            self.first_incompatible_field(
+                old_struct.fields_optional_variant(None),
+                new_struct.fields_optional_variant(None),
+            )
+            .map(|position| LayoutChange::FieldsChanged {
+                variant: None,
+                position,
+            })
         } else {
             // Enum: the prefix of variants in the old definition must be the same as in the new one.
             // (a) the variant names need to match
             // (b) the variant fields need to be compatible
-            old_struct.variant_count() <= new_struct.variant_count()
-                && (0..old_struct.variant_count()).all(|i| {
-                    let v_idx = i as VariantIndex;
-                    old_struct.variant_name(v_idx) == new_struct.variant_name(v_idx)
-                        && self.fields_compatible(
-                            old_struct.fields_optional_variant(Some(v_idx)),
-                            new_struct.fields_optional_variant(Some(v_idx)),
-                        )
+            if new_struct.variant_count() == 0 {
+                return Some(LayoutChange::StructKindChanged);
+            }
+            (0..old_struct.variant_count()).find_map(|i| {
+                let v_idx = i as VariantIndex;
+                let variant = old_struct.variant_name(v_idx).to_owned();
+                if i >= new_struct.variant_count()
+                    || old_struct.variant_name(v_idx) != new_struct.variant_name(v_idx)
+                {
+                    return Some(LayoutChange::VariantChanged(variant));
+                }
+                self.first_incompatible_field(
+                    old_struct.fields_optional_variant(Some(v_idx)),
+                    new_struct.fields_optional_variant(Some(v_idx)),
+                )
+                .map(|position| LayoutChange::FieldsChanged {
+                    variant: Some(variant),
+                    position,
                 })
+            })
         }
     }
 
-    fn fields_compatible<'a, 'b>(
+    /// Returns the position of the first field which differs, if fields are not compatible.
+    fn first_incompatible_field<'a, 'b>(
         &self,
         mut old_fields: impl Iterator<Item = FieldDefinitionView<'a, CompiledModule>>,
         mut new_fields: impl Iterator<Item = FieldDefinitionView<'b, CompiledModule>>,
-    ) -> bool {
+    ) -> Option<usize> {
+        let mut position = 0;
         loop {
             match (old_fields.next(), new_fields.next()) {
                 (Some(old_field), Some(new_field)) => {
@@ -396,12 +541,13 @@ impl Compatibility {
                             new_field.signature_token(),
                         )
                     {
-                        return false;
+                        return Some(position);
                     }
                 },
-                (None, None) => return true,
-                _ => return false,
+                (None, None) => return None,
+                _ => return Some(position),
             }
+            position += 1;
         }
     }
 
@@ -1,7 +1,10 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{compatibility::Compatibility, file_format::*};
+use crate::{
+    compatibility::{Compatibility, FunctionChange, Incompatibility},
+    file_format::*,
+};
 use move_core_types::{account_address::AccountAddress, identifier::Identifier};
 use std::convert::TryFrom;
 
@@ -120,3 +123,22 @@ fn deprecated_add_script_visibility() {
         .check(&friend_module, &script_module)
         .is_err());
 }
+
+#[test]
+fn incompatibilities_explain_check_failure() {
+    let public_module = mk_module(Visibility::Public as u8);
+    let private_module = mk_module(Visibility::Private as u8);
+    let incompatibilities =
+        Compatibility::full_check().incompatibilities(&public_module, &private_module);
+    assert_eq!(incompatibilities, vec![Incompatibility::FunctionChanged {
+        function_name: Identifier::new("fn").unwrap(),
+        change: FunctionChange::Visibility,
+    }]);
+    assert_eq!(
+        incompatibilities[0].to_string(),
+        "changed visibility of function `fn`"
+    );
+    assert!(Compatibility::full_check()
+        .incompatibilities(&private_module, &public_module)
+        .is_empty());
+}
@@ -660,6 +660,8 @@ pub mod known_attributes {
         TestOnly,
         // Is a test that will be run
         Test,
+        // Is a test that will be run repeatedly with generated arguments
+        TestFuzz,
         // This test is expected to fail
         ExpectedFailure,
     }
@@ -718,6 +720,7 @@ pub mod known_attributes {
             Some(match attribute_str.as_ref() {
                 TestingAttribute::TEST => Self::Testing(TestingAttribute::Test),
                 TestingAttribute::TEST_ONLY => Self::Testing(TestingAttribute::TestOnly),
+                TestingAttribute::TEST_FUZZ => Self::Testing(TestingAttribute::TestFuzz),
                 TestingAttribute::EXPECTED_FAILURE => {
                     Self::Testing(TestingAttribute::ExpectedFailure)
                 },
@@ -781,8 +784,12 @@ pub mod known_attributes {
 
     impl TestingAttribute {
         pub const ABORT_CODE_NAME: &'static str = "abort_code";
-        const ALL_ATTRIBUTE_NAMES: [&'static str; 3] =
-            [Self::TEST, Self::TEST_ONLY, Self::EXPECTED_FAILURE];
+        const ALL_ATTRIBUTE_NAMES: [&'static str; 4] = [
+            Self::TEST,
+            Self::TEST_FUZZ,
+            Self::TEST_ONLY,
+            Self::EXPECTED_FAILURE,
+        ];
         pub const ARITHMETIC_ERROR_NAME: &'static str = "arithmetic_error";
         pub const ERROR_LOCATION: &'static str = "location";
         pub const EXPECTED_FAILURE: &'static str = "expected_failure";
@@ -790,6 +797,7 @@ pub mod known_attributes {
         pub const MINOR_STATUS_NAME: &'static str = "minor_status";
         pub const OUT_OF_GAS_NAME: &'static str = "out_of_gas";
         pub const TEST: &'static str = "test";
+        pub const TEST_FUZZ: &'static str = "test_fuzz";
         pub const TEST_ONLY: &'static str = "test_only";
         pub const VECTOR_ERROR_NAME: &'static str = "vector_error";
 
@@ -813,6 +821,7 @@ pub mod known_attributes {
         fn name(&self) -> &str {
             match self {
                 Self::Test => Self::TEST,
+                Self::TestFuzz => Self::TEST_FUZZ,
                 Self::TestOnly => Self::TEST_ONLY,
                 Self::ExpectedFailure => Self::EXPECTED_FAILURE,
             }
@@ -837,7 +846,7 @@ pub mod known_attributes {
                 Lazy::new(|| IntoIterator::into_iter([AttributePosition::Function]).collect());
             match self {
                 TestingAttribute::TestOnly => &TEST_ONLY_POSITIONS,
-                TestingAttribute::Test => &TEST_POSITIONS,
+                TestingAttribute::Test | TestingAttribute::TestFuzz => &TEST_POSITIONS,
                 TestingAttribute::ExpectedFailure => &EXPECTED_FAILURE_POSITIONS,
             }
         }
@@ -9,8 +9,11 @@ use crate::{
 };
 use move_binary_format::CompiledModule;
 use move_core_types::{
-    account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
-    value::MoveValue, vm_status::StatusCode,
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::ModuleId,
+    value::{MoveTypeLayout, MoveValue},
+    vm_status::StatusCode,
 };
 use std::{collections::BTreeMap, fmt};
 
@@ -46,6 +49,17 @@ pub struct TestCase {
     pub test_name: TestName,
     pub arguments: Vec<MoveValue>,
     pub expected_failure: Option<ExpectedFailure>,
+    // For `#[test_fuzz]` tests, the per-parameter description of how arguments are produced.
+    // `arguments` is empty in this case; the test runner generates them for every run.
+    pub fuzz_arguments: Option<Vec<FuzzArgument>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum FuzzArgument {
+    // Argument fixed in the attribute, e.g. a signer address
+    Fixed(MoveValue),
+    // Argument generated by the fuzzer from the given layout
+    Generated(MoveTypeLayout),
 }
 
 #[derive(Debug, Clone)]
@@ -4,17 +4,57 @@
 //! This module contains the interface for externally specified checks
 //! that can be run by the Move compiler.
 
+use crate::Options;
+use codespan_reporting::diagnostic::Severity;
 use legacy_move_compiler::shared::known_attributes::LintAttribute;
 use move_model::{
     ast::ExpData,
     model::{FunctionEnv, GlobalEnv, Loc},
 };
 use move_stackless_bytecode::function_target::FunctionTarget;
-use std::{collections::BTreeSet, fmt, sync::Arc};
+use std::{collections::BTreeSet, fmt, str::FromStr, sync::Arc};
 
 /// Base URL for the linter documentation.
 const LINTER_URL_BASE: &str = "https://aptos.dev/en/build/smart-contracts/linter";
 
+/// The level at which the reports of a checker are emitted.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LintLevel {
+    /// Reports are dropped.
+    Allow,
+    /// Reports are emitted as warnings.
+    #[default]
+    Warn,
+    /// Reports are emitted as errors, failing the compilation.
+    Error,
+}
+
+impl FromStr for LintLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(LintLevel::Allow),
+            "warn" => Ok(LintLevel::Warn),
+            "error" => Ok(LintLevel::Error),
+            _ => Err(format!(
+                "invalid lint level `{}` (expected one of: `allow`, `warn`, `error`)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LintLevel::Allow => "allow",
+            LintLevel::Warn => "warn",
+            LintLevel::Error => "error",
+        })
+    }
+}
+
 /// Implement this trait to provide a collection of external checks.
 pub trait ExternalChecks {
     /// Get all the expression checkers.
@@ -95,9 +135,16 @@ pub fn known_checker_names(external_checkers: &Vec<Arc<dyn ExternalChecks>>) ->
     names
 }
 
+/// Get the level at which reports of `checker_name` are emitted, as configured in the options.
+pub fn lint_level(env: &GlobalEnv, checker_name: &str) -> LintLevel {
+    env.get_extension::<Options>()
+        .and_then(|options| options.lint_levels.get(checker_name).copied())
+        .unwrap_or_default()
+}
+
 /// Report the `msg` highlighting the `loc` for the `checker_name`.
 fn report(env: &GlobalEnv, loc: &Loc, msg: &str, checker_name: &str) {
-    env.lint_diag_with_notes(loc, msg, vec![
+    let notes = vec![
         format!(
         "To suppress this warning, annotate the function/module with the attribute `#[{}({})]`.",
         LintAttribute::SKIP,
@@ -107,5 +154,12 @@ fn report(env: &GlobalEnv, loc: &Loc, msg: &str, checker_name: &str) {
             "For more information, see {}#{}.",
             LINTER_URL_BASE, checker_name
         ),
-    ]);
+    ];
+    match lint_level(env, checker_name) {
+        LintLevel::Allow => {},
+        LintLevel::Warn => env.lint_diag_with_notes(loc, msg, notes),
+        LintLevel::Error => {
+            env.diag_with_notes(Severity::Error, loc, &format!("[lint] {}", msg), notes)
+        },
+    }
 }
@@ -4,7 +4,7 @@
 
 use crate::{
     experiments::{DefaultValue, EXPERIMENTS},
-    external_checks::ExternalChecks,
+    external_checks::{ExternalChecks, LintLevel},
 };
 use clap::Parser;
 use codespan_reporting::diagnostic::Severity;
@@ -129,6 +129,10 @@ pub struct Options {
     /// External checks to be performed.
     #[clap(skip)]
     pub external_checks: Vec<Arc<dyn ExternalChecks>>,
+
+    /// Levels of external checks, by checker name. Checkers not in the map report warnings.
+    #[clap(skip)]
+    pub lint_levels: BTreeMap<String, LintLevel>,
 }
 
 impl Default for Options {
@@ -252,6 +256,13 @@ impl Options {
             ..self
         }
     }
+
+    pub fn set_lint_levels(self, value: BTreeMap<String, LintLevel>) -> Self {
+        Self {
+            lint_levels: value,
+            ..self
+        }
+    }
 }
 
 /// Finds the experiment in the list of definitions. A definition
@@ -10,16 +10,23 @@
 //! Each module containing any labeled `#[test]` functions gets an item in the output list, which
 //! includes info about each '#[test]' function: name, arguments to provide, and expected failure or
 //! success.
+//!
+//! Functions labeled `#[test_fuzz]` are treated like `#[test]` functions, except that parameters
+//! which are not assigned in the attribute are generated by the test runner. For those, the plan
+//! records the layout of the value to generate.
 
 use crate::options::Options;
 use codespan_reporting::diagnostic::Severity;
 use legacy_move_compiler::{
     shared::known_attributes::{AttributeKind, TestingAttribute},
-    unit_test::{ExpectedFailure, ExpectedMoveError, ModuleTestPlan, TestCase},
+    unit_test::{ExpectedFailure, ExpectedMoveError, FuzzArgument, ModuleTestPlan, TestCase},
 };
 use move_command_line_common::{address::NumericalAddress, parser::NumberFormat};
 use move_core_types::{
-    identifier::Identifier, language_storage::ModuleId, value::MoveValue, vm_status::StatusCode,
+    identifier::Identifier,
+    language_storage::ModuleId,
+    value::{MoveStructLayout, MoveTypeLayout, MoveValue},
+    vm_status::StatusCode,
 };
 use move_model::{
     ast::{Address, Attribute, AttributeValue, ModuleName, Value},
@@ -117,11 +124,27 @@ fn build_test_info(
     let expected_failure_name = env.symbol_pool().make(TestingAttribute::EXPECTED_FAILURE);
     let test_name = env.symbol_pool().make(TestingAttribute::TEST);
     let test_only_name = env.symbol_pool().make(TestingAttribute::TEST_ONLY);
+    let test_fuzz_name = env.symbol_pool().make(TestingAttribute::TEST_FUZZ);
 
     let test_attribute_opt = attrs.iter().find(|a| a.name() == test_name);
+    let fuzz_attribute_opt = attrs.iter().find(|a| a.name() == test_fuzz_name);
     let abort_attribute_opt = attrs.iter().find(|a| a.name() == expected_failure_name);
 
-    let test_attribute = match test_attribute_opt {
+    // A #[test] function cannot also be annotated #[test_fuzz]
+    if let (Some(test_attribute), Some(fuzz_attribute)) = (test_attribute_opt, fuzz_attribute_opt) {
+        let msg = "Function annotated as both #[test(...)] and #[test_fuzz(...)]. You need to \
+                   declare it as either one or the other";
+        let fuzz_loc = env.get_node_loc(fuzz_attribute.node_id());
+        let test_loc = env.get_node_loc(test_attribute.node_id());
+        env.error_with_labels(&fn_id_loc, "invalid usage of known attribute", vec![
+            (fuzz_loc, msg.to_string()),
+            (test_loc, "Previously annotated here".to_string()),
+        ]);
+        return None;
+    }
+    let is_fuzz = fuzz_attribute_opt.is_some();
+
+    let test_attribute = match test_attribute_opt.or(fuzz_attribute_opt) {
         None => {
             // expected failures cannot be annotated on non-#[test] functions
             if let Some(abort_attribute) = abort_attribute_opt {
@@ -160,18 +183,17 @@ fn build_test_info(
     let test_annotation_params = parse_test_attribute(env, test_attribute, 0);
 
     let mut arguments = Vec::new();
+    let mut fuzz_arguments = Vec::new();
     for param in function.get_parameters_ref() {
         let Parameter(var, ty, var_loc) = &param;
 
-        match test_annotation_params.get(var) {
+        let assigned = match test_annotation_params.get(var) {
             Some(MoveValue::Address(addr)) => match ty {
-                Type::Primitive(PrimitiveType::Signer) => arguments.push(MoveValue::Signer(*addr)),
+                Type::Primitive(PrimitiveType::Signer) => Some(MoveValue::Signer(*addr)),
                 Type::Reference(_, inner) if **inner == Type::Primitive(PrimitiveType::Signer) => {
-                    arguments.push(MoveValue::Signer(*addr));
-                },
-                Type::Primitive(PrimitiveType::Address) => {
-                    arguments.push(MoveValue::Address(*addr))
+                    Some(MoveValue::Signer(*addr))
                 },
+                Type::Primitive(PrimitiveType::Address) => Some(MoveValue::Address(*addr)),
                 _ => {
                     let err_msg = "Unexpected argument type: expect an address or a signer";
                     let invalid_test = "unable to generate test";
@@ -182,9 +204,11 @@ fn build_test_info(
                             "Corresponding to this parameter".to_string(),
                         ),
                     ]);
+                    continue;
                 },
             },
-            Some(value) => arguments.push(value.clone()),
+            Some(value) => Some(value.clone()),
+            None if is_fuzz => None,
             None => {
                 let missing_param_msg = "Missing test parameter assignment in test. Expected a \
                                          parameter to be assigned in this attribute";
@@ -196,6 +220,30 @@ fn build_test_info(
                         "Corresponding to this parameter".to_string(),
                     ),
                 ]);
+                continue;
+            },
+        };
+
+        match assigned {
+            Some(value) if is_fuzz => fuzz_arguments.push(FuzzArgument::Fixed(value)),
+            Some(value) => arguments.push(value),
+            None => match fuzz_type_layout(env, ty) {
+                Some(layout) => fuzz_arguments.push(FuzzArgument::Generated(layout)),
+                None => {
+                    let err_msg = "Values of this type cannot be generated. Supported are \
+                                   primitive types, signers, vectors, and structs of those";
+                    env.error_with_labels(
+                        &fn_id_loc,
+                        "unsupported parameter type for fuzzed test",
+                        vec![
+                            (var_loc.clone(), err_msg.to_string()),
+                            (
+                                test_attribute_loc.clone(),
+                                "Declared as fuzzed test here".to_string(),
+                            ),
+                        ],
+                    );
+                },
             },
         }
     }
@@ -209,6 +257,46 @@ fn build_test_info(
         test_name: fn_name_str.to_string(),
         arguments,
         expected_failure,
+        fuzz_arguments: is_fuzz.then_some(fuzz_arguments),
+    })
+}
+
+/// Returns the layout of values the test runner generates for a fuzzed parameter of type `ty`,
+/// or `None` if values of this type cannot be generated.
+fn fuzz_type_layout(env: &GlobalEnv, ty: &Type) -> Option<MoveTypeLayout> {
+    Some(match ty {
+        Type::Primitive(prim) => match prim {
+            PrimitiveType::Bool => MoveTypeLayout::Bool,
+            PrimitiveType::U8 => MoveTypeLayout::U8,
+            PrimitiveType::U16 => MoveTypeLayout::U16,
+            PrimitiveType::U32 => MoveTypeLayout::U32,
+            PrimitiveType::U64 => MoveTypeLayout::U64,
+            PrimitiveType::U128 => MoveTypeLayout::U128,
+            PrimitiveType::U256 => MoveTypeLayout::U256,
+            PrimitiveType::I8 => MoveTypeLayout::I8,
+            PrimitiveType::I16 => MoveTypeLayout::I16,
+            PrimitiveType::I32 => MoveTypeLayout::I32,
+            PrimitiveType::I64 => MoveTypeLayout::I64,
+            PrimitiveType::I128 => MoveTypeLayout::I128,
+            PrimitiveType::I256 => MoveTypeLayout::I256,
+            PrimitiveType::Address => MoveTypeLayout::Address,
+            PrimitiveType::Signer => MoveTypeLayout::Signer,
+            PrimitiveType::Num | PrimitiveType::Range | PrimitiveType::EventStore => return None,
+        },
+        Type::Reference(_, inner) => return fuzz_type_layout(env, inner),
+        Type::Vector(elem) => MoveTypeLayout::Vector(Box::new(fuzz_type_layout(env, elem)?)),
+        Type::Struct(mid, sid, inst) => {
+            let struct_env = env.get_module(*mid).into_struct(*sid);
+            if struct_env.is_native() || struct_env.has_variants() {
+                return None;
+            }
+            let fields = struct_env
+                .get_fields()
+                .map(|field| fuzz_type_layout(env, &field.get_type().instantiate(inst)))
+                .collect::<Option<Vec<_>>>()?;
+            MoveTypeLayout::Struct(MoveStructLayout::new(fields))
+        },
+        _ => return None,
     })
 }
 
@@ -228,8 +316,9 @@ fn parse_test_attribute(
             BTreeMap::new()
         },
         Attribute::Apply(_id, sym, vec) => {
+            let attr_name = env.symbol_pool().string(*sym).to_string();
             assert!(
-                *TestingAttribute::TEST == env.symbol_pool().string(*sym).to_string(),
+                *TestingAttribute::TEST == attr_name || *TestingAttribute::TEST_FUZZ == attr_name,
                 "ICE: We should only be parsing a raw test attribute"
             );
             vec.iter()
@@ -6,5 +6,6 @@
 
 pub mod mapping;
 pub mod marking;
+pub mod source_index;
 pub mod source_map;
 pub mod utils;
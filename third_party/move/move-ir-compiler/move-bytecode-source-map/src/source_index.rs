@@ -0,0 +1,199 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Mapping between bytecode locations reported by the VM and lines in Move source files.
+
+use crate::source_map::SourceMap;
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_command_line_common::files::FileHash;
+use move_core_types::language_storage::ModuleId;
+use move_ir_types::location::Loc;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+/// A line in a source file, 1-based.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SourceLine {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// An instruction in a module function.
+pub type CodeLocation = (ModuleId, FunctionDefinitionIndex, CodeOffset);
+
+struct SourceFile {
+    path: PathBuf,
+    text: String,
+    /// Byte offsets at which each line starts.
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    fn new(path: PathBuf, text: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        Self {
+            path,
+            text: text.to_string(),
+            line_starts,
+        }
+    }
+
+    /// Returns the text of a 1-based line, without the line break.
+    fn line_text(&self, line: usize) -> Option<&str> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let end = self
+            .line_starts
+            .get(line)
+            .copied()
+            .unwrap_or(self.text.len());
+        Some(self.text[start..end].trim_end_matches(['\r', '\n']))
+    }
+
+    /// Returns the 1-based line and column of a byte offset.
+    fn line_and_column(&self, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|start| *start <= offset);
+        (line, offset - self.line_starts[line - 1] + 1)
+    }
+}
+
+/// Modules with their source maps, and the sources they were compiled from. Modules for which
+/// no source map is known are still debuggable, but only at the bytecode level.
+#[derive(Default)]
+pub struct SourceIndex {
+    modules: BTreeMap<ModuleId, (CompiledModule, SourceMap)>,
+    files: HashMap<FileHash, SourceFile>,
+}
+
+impl SourceIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_module(&mut self, module: CompiledModule, source_map: SourceMap) {
+        self.modules.insert(module.self_id(), (module, source_map));
+    }
+
+    pub fn add_file(&mut self, file_hash: FileHash, path: impl AsRef<Path>, text: &str) {
+        self.files.insert(
+            file_hash,
+            SourceFile::new(normalize_path(path.as_ref()), text),
+        );
+    }
+
+    /// Returns whether no source files are known, in which case nothing can be debugged at the
+    /// source level.
+    pub fn has_no_sources(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Returns the source line of the given instruction, if it is known.
+    pub fn source_line(
+        &self,
+        module_id: &ModuleId,
+        function_index: FunctionDefinitionIndex,
+        pc: CodeOffset,
+    ) -> Option<SourceLine> {
+        let (_, source_map) = self.modules.get(module_id)?;
+        let loc = source_map.get_code_location(function_index, pc).ok()?;
+        self.resolve(loc)
+    }
+
+    /// Returns the name of a parameter or local of a function, if it is known.
+    pub fn local_name(
+        &self,
+        module_id: &ModuleId,
+        function_index: FunctionDefinitionIndex,
+        local_index: usize,
+    ) -> Option<String> {
+        let (_, source_map) = self.modules.get(module_id)?;
+        source_map
+            .get_parameter_or_local_name(function_index, local_index as u64)
+            .ok()
+            .map(|(name, _)| name)
+    }
+
+    /// Returns the instructions at which execution enters the given line of a source file. A
+    /// breakpoint at the line stops at each of these instructions.
+    pub fn line_entries(&self, path: &Path, line: usize) -> Vec<CodeLocation> {
+        let path = normalize_path(path);
+        let mut entries = vec![];
+        for (module_id, (module, source_map)) in &self.modules {
+            for idx in 0..module.function_defs().len() {
+                let function_index = FunctionDefinitionIndex(idx as u16);
+                let Ok(function_map) = source_map.get_function_source_map(function_index) else {
+                    continue;
+                };
+                let mut previous_line = None;
+                for (offset, loc) in &function_map.code_map {
+                    let current_line = self
+                        .resolve(*loc)
+                        .filter(|source_line| source_line.path == path)
+                        .map(|source_line| source_line.line);
+                    if current_line == Some(line) && previous_line != current_line {
+                        entries.push((module_id.clone(), function_index, *offset));
+                    }
+                    previous_line = current_line;
+                }
+            }
+        }
+        entries
+    }
+
+    /// Returns the text of a line in one of the source files.
+    pub fn line_text(&self, source_line: &SourceLine) -> Option<&str> {
+        self.files
+            .values()
+            .find(|file| file.path == source_line.path)?
+            .line_text(source_line.line)
+    }
+
+    fn resolve(&self, loc: Loc) -> Option<SourceLine> {
+        let file = self.files.get(&loc.file_hash())?;
+        let (line, column) = file.line_and_column(loc.start() as usize);
+        Some(SourceLine {
+            path: file.path.clone(),
+            line,
+            column,
+        })
+    }
+}
+
+/// Paths of source files are compared in canonical form, since clients may refer to the same
+/// file by a different path than the compiler did.
+fn normalize_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_and_column() {
+        let file = SourceFile::new(PathBuf::from("a.move"), "ab\n\ncd\n");
+        assert_eq!(file.line_and_column(0), (1, 1));
+        assert_eq!(file.line_and_column(1), (1, 2));
+        assert_eq!(file.line_and_column(3), (2, 1));
+        assert_eq!(file.line_and_column(4), (3, 1));
+        assert_eq!(file.line_and_column(5), (3, 2));
+    }
+
+    #[test]
+    fn line_text() {
+        let file = SourceFile::new(PathBuf::from("a.move"), "ab\r\n\ncd");
+        assert_eq!(file.line_text(1), Some("ab"));
+        assert_eq!(file.line_text(2), Some(""));
+        assert_eq!(file.line_text(3), Some("cd"));
+        assert_eq!(file.line_text(4), None);
+        assert_eq!(file.line_text(0), None);
+    }
+}
@@ -21,9 +21,9 @@ use move_binary_format::file_format::FunctionDefinitionIndex;
 use move_model::{
     ast::TempIndex,
     code_writer::CodeWriter,
-    model::{FunId, GlobalEnv, Loc, ModuleId, NodeId, QualifiedId, StructEnv},
+    model::{FunId, GlobalEnv, Loc, ModuleId, NodeId, Parameter, QualifiedId, StructEnv},
     pragmas::INTRINSIC_TYPE_MAP,
-    ty::{PrimitiveType, Type},
+    ty::{PrimitiveType, ReferenceKind, Type},
 };
 use move_stackless_bytecode::function_target_pipeline::{FunctionTargetsHolder, FunctionVariant};
 use num::BigInt;
@@ -110,6 +110,58 @@ static INCONCLUSIVE_DIAG_STARTS: Lazy<Regex> = Lazy::new(|| {
 static INCONSISTENCY_DIAG_STARTS: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"(?m)^inconsistency_detected\((?P<args>[^)]*)\)").unwrap());
 
+// Messages of verification conditions, as generated by the spec instrumentation.
+const ENSURES_FAILS_MESSAGE: &str = "post-condition does not hold";
+const ABORTS_IF_FAILS_MESSAGE: &str = "function does not abort under this condition";
+
+/// Constructs of the specification language which cannot be used in Move code.
+const SPEC_ONLY_CONSTRUCTS: &[&str] = &["==>", "<==>", "forall ", "exists "];
+
+/// Names of builtin spec functions and constants, which cannot appear in Move code.
+const SPEC_ONLY_BUILTINS: &[&str] = &[
+    "old",
+    "global",
+    "len",
+    "update",
+    "concat",
+    "contains",
+    "index_of",
+    "range",
+    "in_range",
+    "vec",
+    "int2bv",
+    "bv2int",
+    "TRACE",
+    "EXECUTION_FAILURE",
+    "max_u8",
+    "max_u16",
+    "max_u32",
+    "max_u64",
+    "max_u128",
+    "max_u256",
+    "MAX_U8",
+    "MAX_U16",
+    "MAX_U32",
+    "MAX_U64",
+    "MAX_U128",
+    "MAX_U256",
+    "MAX_I8",
+    "MAX_I16",
+    "MAX_I32",
+    "MAX_I64",
+    "MAX_I128",
+    "MAX_I256",
+    "MIN_I8",
+    "MIN_I16",
+    "MIN_I32",
+    "MIN_I64",
+    "MIN_I128",
+    "MIN_I256",
+];
+
+/// The maximal size of a vector in a counterexample which is rendered as Move code.
+const MAX_COUNTEREXAMPLE_VECTOR_SIZE: usize = 1024;
+
 impl BoogieWrapper<'_> {
     /// Calls boogie on the given file. On success, returns a struct representing the analyzed
     /// output of boogie.
@@ -449,6 +501,12 @@ impl BoogieWrapper<'_> {
             }
 
             display.dedup();
+            if self.options.counterexample_tests {
+                if let Some(test) = self.make_counterexample_test(error) {
+                    display.push("Counterexample as unit test:".to_string());
+                    display.extend(test.into_iter().map(|s| format!("    {}", s)));
+                }
+            }
             diag = diag.with_notes(display);
         }
         self.env.add_diag(diag);
@@ -501,6 +559,229 @@ impl BoogieWrapper<'_> {
         String::from_utf8_lossy(&lines).to_string()
     }
 
+    /// Renders the counterexample of a verification error as a Move unit test. The test
+    /// publishes the resources found in the model, calls the verified function with the
+    /// parameter values of the model, and checks the violated condition. It is meant to be
+    /// added to the module of the verified function, so that it can construct the module's
+    /// structs. Values which cannot be expressed in Move are left as `TODO` comments, as are
+    /// conditions using spec-only constructs or parameters moved into the call.
+    ///
+    /// For stable test output, values and addresses are redacted.
+    fn make_counterexample_test(&self, error: &BoogieError) -> Option<Vec<String>> {
+        let model = error.model.as_ref()?;
+        // The parameters of the verified function are traced on entry, before any other
+        // temporaries.
+        let fun_id = error.execution_trace.iter().find_map(|entry| match entry {
+            TraceEntry::Temporary(fun_id, _, _) => Some(*fun_id),
+            _ => None,
+        })?;
+        let fun_env = self.env.get_function(fun_id);
+        if fun_env.get_type_parameter_count() > 0 {
+            // The model does not tell which instantiation the counterexample is about.
+            return None;
+        }
+        let module_id = fun_env.module_env.get_id();
+        let pool = self.env.symbol_pool();
+        let display_ctx = fun_env.get_type_display_ctx();
+        let redact = |s: String| {
+            if self.options.stable_test_output {
+                "<redacted>".to_string()
+            } else {
+                s
+            }
+        };
+        let raw = |value: &ModelValue, ty: &Type| {
+            self.render(value.pretty_or_raw(self, model, ty))
+                .lines()
+                .map(|s| s.trim())
+                .join(" ")
+        };
+
+        let mut param_values = BTreeMap::new();
+        let mut mem_values = BTreeMap::new();
+        for entry in &error.execution_trace {
+            match entry {
+                TraceEntry::Temporary(fun, idx, value)
+                    if *fun == fun_id && fun_env.is_parameter(*idx) =>
+                {
+                    // Only the value on entry is relevant, parameters may be reassigned later.
+                    param_values.entry(*idx).or_insert(value);
+                },
+                TraceEntry::GlobalMem(node_id, ModelValue::List(elems)) if elems.len() == 3 => {
+                    // See `add_error` for the shape of this entry.
+                    mem_values.insert(
+                        self.env.get_node_type(*node_id),
+                        (elems[1].clone(), elems[2].clone()),
+                    );
+                },
+                _ => {},
+            }
+        }
+
+        // Signers of the test, as pairs of name and address.
+        let mut signers: Vec<(String, String)> = vec![];
+        let mut body = vec![];
+        let mut args = vec![];
+        // Parameters passed by value which cannot be copied, and hence are moved by the call.
+        let mut moved_params = BTreeSet::new();
+        for (idx, Parameter(name, ty, _)) in fun_env.get_parameters().into_iter().enumerate() {
+            let name = name.display(pool).to_string();
+            let value = *param_values.get(&idx)?;
+            if ty.skip_reference().is_signer() {
+                let addr = value
+                    .extract_list("$signer")
+                    .and_then(|l| l.first())
+                    .and_then(|a| a.move_address());
+                match (&ty, addr) {
+                    (Type::Reference(..), Some(addr)) => signers.push((name.clone(), addr)),
+                    _ => body.push(format!(
+                        "// TODO: provide signer `{}`: {}",
+                        name,
+                        raw(value, &ty)
+                    )),
+                }
+                args.push(name);
+                continue;
+            }
+            let base_ty = ty.skip_reference();
+            if !ty.is_reference() && !self.env.type_abilities(&ty, &[]).has_copy() {
+                moved_params.insert(name.clone());
+            }
+            match value.to_move_exp(self, model, base_ty, module_id) {
+                Some(exp) => body.push(format!(
+                    "let {}: {} = {};",
+                    name,
+                    base_ty.display(&display_ctx),
+                    redact(exp)
+                )),
+                None => body.push(format!(
+                    "// TODO: provide `{}: {}`: {}",
+                    name,
+                    base_ty.display(&display_ctx),
+                    raw(value, base_ty)
+                )),
+            }
+            args.push(counterexample_arg(name, &ty));
+        }
+
+        if !mem_values.is_empty() {
+            let domain_info = extract_domain(model);
+            for (ty, (domain, mem)) in &mem_values {
+                let Some(values) = mem.extract_mem_vector(model, domain, &domain_info) else {
+                    continue;
+                };
+                for (addr, value) in &values.values {
+                    let addr = format!("{:x}", addr);
+                    let signer = match signers.iter().find(|(_, a)| a == &addr) {
+                        Some((name, _)) => name.clone(),
+                        None => {
+                            let name = format!("account_{}", redact(addr.clone()));
+                            signers.push((name.clone(), addr.clone()));
+                            name
+                        },
+                    };
+                    match value.to_move_exp(self, model, ty, module_id) {
+                        Some(exp) => body.push(format!("move_to({}, {});", signer, redact(exp))),
+                        None => body.push(format!(
+                            "// TODO: publish `{}` at @{}: {}",
+                            ty.display(&display_ctx),
+                            redact(format!("0x{}", addr)),
+                            raw(value, ty)
+                        )),
+                    }
+                }
+            }
+        }
+
+        let call = format!("{}({})", fun_env.get_name().display(pool), args.join(", "));
+        let results = match fun_env.get_return_count() {
+            0 => None,
+            1 => Some("result".to_string()),
+            n => Some(format!(
+                "({})",
+                (1..=n).map(|i| format!("result_{}", i)).join(", ")
+            )),
+        };
+        let mut expected_failure = false;
+        match error.message.as_str() {
+            ENSURES_FAILS_MESSAGE => {
+                let cond = self.env.get_source(&error.loc).ok()?.trim();
+                let cond = cond
+                    .strip_prefix("ensures")
+                    .unwrap_or(cond)
+                    .trim_end_matches(';')
+                    .lines()
+                    .map(|s| s.trim())
+                    .join(" ");
+                match &results {
+                    Some(results) => body.push(format!("let {} = {};", results, call)),
+                    None => body.push(format!("{};", call)),
+                }
+                // Spec functions and parameters moved into the call cannot be used in the test.
+                let spec_funs: BTreeSet<String> = self
+                    .env
+                    .get_modules()
+                    .flat_map(|module_env| {
+                        module_env
+                            .get_spec_funs()
+                            .filter(|(_, decl)| !decl.is_move_fun)
+                            .map(|(_, decl)| decl.name.display(pool).to_string())
+                            .collect_vec()
+                    })
+                    .collect();
+                let uses_spec_only = cond
+                    .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .any(|ident| {
+                        SPEC_ONLY_BUILTINS.contains(&ident)
+                            || spec_funs.contains(ident)
+                            || moved_params.contains(ident)
+                    });
+                if uses_spec_only || SPEC_ONLY_CONSTRUCTS.iter().any(|c| cond.contains(c)) {
+                    body.push(format!("// TODO: check the violated condition `{}`", cond));
+                } else {
+                    body.push(format!("assert!({}, 0);", cond));
+                }
+            },
+            ABORTS_IF_FAILS_MESSAGE => {
+                // The specification requires the function to abort, but it does not.
+                expected_failure = true;
+                body.push(format!("{};", call));
+            },
+            _ => match &results {
+                Some(results) => body.push(format!("let {} = {};", results, call)),
+                None => body.push(format!("{};", call)),
+            },
+        }
+
+        let fun_name = fun_env.get_name().display(pool).to_string();
+        let mut lines = vec![format!("// {}", error.message)];
+        if signers.is_empty() {
+            lines.push("#[test]".to_string());
+        } else {
+            lines.push(format!(
+                "#[test({})]",
+                signers
+                    .iter()
+                    .map(|(name, addr)| format!("{} = @{}", name, redact(format!("0x{}", addr))))
+                    .join(", ")
+            ));
+        }
+        if expected_failure {
+            lines.push("#[expected_failure]".to_string());
+        }
+        lines.push(format!(
+            "fun counterexample_{}({}) {{",
+            fun_name,
+            signers
+                .iter()
+                .map(|(name, _)| format!("{}: &signer", name))
+                .join(", ")
+        ));
+        lines.extend(body.into_iter().map(|s| format!("    {}", s)));
+        lines.push("}".to_string());
+        Some(lines)
+    }
+
     /// Extracts verification errors from Boogie output.
     fn extract_verification_errors(&self, out: &str) -> Vec<BoogieError> {
         let mut errors = vec![];
@@ -1682,6 +1963,132 @@ impl ModelValue {
         entries.push(PrettyDoc::text("Default: ").append(default));
         Some(Self::pretty_vec_or_struct_body(entries))
     }
+
+    /// Renders an address literal as hex digits, without the `0x` prefix.
+    fn move_address(&self) -> Option<String> {
+        let addr = BigInt::parse_bytes(self.extract_literal()?.as_bytes(), 10)?;
+        Some(addr.to_str_radix(16))
+    }
+
+    /// Renders the given model value which has given type as a Move expression, which can be
+    /// used in the module `module_id`. Returns `None` if the value cannot be expressed in Move,
+    /// e.g. because it is undefined or constructs a struct of another module.
+    pub fn to_move_exp(
+        &self,
+        wrapper: &BoogieWrapper,
+        model: &Model,
+        ty: &Type,
+        module_id: ModuleId,
+    ) -> Option<String> {
+        if self.extract_list("Error").is_some() {
+            return None;
+        }
+        let display_ctx = wrapper.env.get_type_display_ctx();
+        match ty {
+            Type::Primitive(PrimitiveType::Bool) => {
+                Some(self.extract_literal()?.parse::<bool>().ok()?.to_string())
+            },
+            Type::Primitive(
+                PrimitiveType::U8
+                | PrimitiveType::U16
+                | PrimitiveType::U32
+                | PrimitiveType::U64
+                | PrimitiveType::U128
+                | PrimitiveType::U256,
+            ) => {
+                let n = BigInt::parse_bytes(self.extract_literal()?.as_bytes(), 10)?;
+                Some(format!("{}{}", n, ty.display(&display_ctx)))
+            },
+            Type::Primitive(
+                PrimitiveType::I8
+                | PrimitiveType::I16
+                | PrimitiveType::I32
+                | PrimitiveType::I64
+                | PrimitiveType::I128,
+            ) => Some(format!(
+                "{}{}",
+                self.extract_i128()?,
+                ty.display(&display_ctx)
+            )),
+            Type::Primitive(PrimitiveType::Address) => Some(format!("@0x{}", self.move_address()?)),
+            Type::Vector(elem_ty) => {
+                let values = self.extract_vector(model, elem_ty)?;
+                if values.size > MAX_COUNTEREXAMPLE_VECTOR_SIZE {
+                    return None;
+                }
+                let elems = (0..values.size)
+                    .map(|idx| {
+                        values
+                            .values
+                            .get(&idx)
+                            .unwrap_or(&values.default)
+                            .extract_box()
+                            .to_move_exp(wrapper, model, elem_ty, module_id)
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(format!("vector[{}]", elems.join(", ")))
+            },
+            Type::Struct(mid, sid, inst) => {
+                let struct_env = wrapper.env.get_struct_qid(mid.qualified(*sid));
+                if *mid != module_id || struct_env.is_intrinsic() {
+                    // Structs can only be packed in their own module.
+                    return None;
+                }
+                let pool = struct_env.symbol_pool();
+                let struct_name = boogie_struct_name(&struct_env, inst);
+                let mut ctor_name = struct_env.get_name().display(pool).to_string();
+                let mut variant = None;
+                let mut values: &[ModelValue] = &[];
+                if struct_env.has_variants() {
+                    for vari in struct_env.get_variants() {
+                        let variant_name =
+                            boogie_helpers::boogie_struct_variant_name(&struct_env, inst, vari);
+                        if let Some(vals) = self
+                            .extract_list(&variant_name)
+                            .or_else(|| self.extract_list(&format!("|{}|", variant_name)))
+                        {
+                            variant = Some(vari);
+                            values = vals;
+                            ctor_name = format!("{}::{}", ctor_name, vari.display(pool));
+                            break;
+                        }
+                    }
+                    if variant.is_none() {
+                        return None;
+                    }
+                } else {
+                    values = self
+                        .extract_list(&struct_name)
+                        .or_else(|| self.extract_list(&format!("|{}|", struct_name)))?;
+                }
+                let fields = struct_env
+                    .get_fields_optional_variant(variant)
+                    .enumerate()
+                    .map(|(i, f)| {
+                        let ty = f.get_type().instantiate(inst);
+                        let exp = values.get(i)?.to_move_exp(wrapper, model, &ty, module_id)?;
+                        Some(format!("{}: {}", f.get_name().display(pool), exp))
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                if fields.is_empty() {
+                    Some(format!("{} {{}}", ctor_name))
+                } else {
+                    Some(format!("{} {{ {} }}", ctor_name, fields.join(", ")))
+                }
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Renders the argument for a parameter of given type in a counterexample test, where the
+/// parameter value is bound to a local of the same name.
+fn counterexample_arg(name: String, ty: &Type) -> String {
+    match ty {
+        Type::Reference(ReferenceKind::Mutable, _) => format!("&mut {}", name),
+        Type::Reference(ReferenceKind::Immutable, _) => format!("&{}", name),
+        _ => name,
+    }
 }
 
 #[allow(dead_code)]
@@ -1830,3 +2237,77 @@ fn index_range_check(max: usize) -> impl FnOnce(usize) -> Result<usize, ModelPar
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_move_exp() {
+        let env = GlobalEnv::new();
+        let targets = FunctionTargetsHolder::default();
+        let writer = CodeWriter::new(env.unknown_loc());
+        let options = BoogieOptions {
+            vector_theory: VectorTheory::SmtSeq,
+            ..Default::default()
+        };
+        let wrapper = BoogieWrapper {
+            env: &env,
+            targets: &targets,
+            writer: &writer,
+            options: &options,
+        };
+        let model = Model::new(&wrapper);
+        let module_id = ModuleId::new(0);
+        let render =
+            |value: &ModelValue, ty: &Type| value.to_move_exp(&wrapper, &model, ty, module_id);
+
+        let u64_ty = Type::new_prim(PrimitiveType::U64);
+        assert_eq!(
+            render(&ModelValue::literal("42"), &u64_ty),
+            Some("42u64".to_string())
+        );
+        assert_eq!(
+            render(
+                &ModelValue::literal("true"),
+                &Type::new_prim(PrimitiveType::Bool)
+            ),
+            Some("true".to_string())
+        );
+        assert_eq!(
+            render(
+                &ModelValue::literal("255"),
+                &Type::new_prim(PrimitiveType::Address)
+            ),
+            Some("@0xff".to_string())
+        );
+        let seq_unit = |n: &str| {
+            ModelValue::List(vec![
+                ModelValue::literal("seq.unit"),
+                ModelValue::literal(n),
+            ])
+        };
+        assert_eq!(
+            render(
+                &ModelValue::List(vec![
+                    ModelValue::literal("seq.++"),
+                    seq_unit("1"),
+                    seq_unit("2")
+                ]),
+                &Type::Vector(Box::new(Type::new_prim(PrimitiveType::U8)))
+            ),
+            Some("vector[1u8, 2u8]".to_string())
+        );
+        assert_eq!(render(&ModelValue::error(), &u64_ty), None);
+
+        // A `&mut` parameter is bound to an immutable local of the base type, which is
+        // borrowed mutably at the call.
+        let ty = Type::Reference(ReferenceKind::Mutable, Box::new(u64_ty.clone()));
+        assert_eq!(
+            render(&ModelValue::literal("7"), ty.skip_reference()),
+            Some("7u64".to_string())
+        );
+        assert_eq!(counterexample_arg("x".to_string(), &ty), "&mut x");
+        assert_eq!(counterexample_arg("x".to_string(), &u64_ty), "x");
+    }
+}
@@ -161,6 +161,8 @@ pub struct BoogieOptions {
     pub lazy_threshold: usize,
     /// Whether to use the new Boogie `{:debug ..}` attribute for tracking debug values.
     pub stable_test_output: bool,
+    /// Whether to render counterexamples of verification errors as Move unit tests.
+    pub counterexample_tests: bool,
     /// Number of Boogie instances to be run concurrently.
     pub num_instances: usize,
     /// Whether to run Boogie instances sequentially.
@@ -216,6 +218,7 @@ impl Default for BoogieOptions {
             eager_threshold: 100,
             lazy_threshold: 100,
             stable_test_output: false,
+            counterexample_tests: false,
             num_instances: 1,
             sequential_task: false,
             hard_timeout_secs: 0,
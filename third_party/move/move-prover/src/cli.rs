@@ -219,6 +219,12 @@ impl Options {
                     .short('t')
                     .help("enables automatic tracing of expressions in prover errors")
             )
+            .arg(
+                Arg::new("counterexample-tests")
+                    .long("counterexample-tests")
+                    .action(SetTrue)
+                    .help("renders counterexamples of prover errors as Move unit tests")
+            )
             .arg(
                 Arg::new("keep")
                     .long("keep")
@@ -639,6 +645,9 @@ impl Options {
         if matches.get_flag("trace") {
             options.prover.auto_trace_level = AutoTraceLevel::VerifiedFunction;
         }
+        if matches.get_flag("counterexample-tests") {
+            options.backend.counterexample_tests = true;
+        }
         if matches.get_flag("dump-bytecode") {
             options.prover.dump_bytecode = true;
         }
@@ -0,0 +1,33 @@
+// flag: --trace
+// flag: --counterexample-tests
+module 0x42::TestCounterexamples {
+
+    spec module {
+        pragma verify = true;
+    }
+
+    struct R has key {
+        x: u64
+    }
+
+    fun add_invalid(a: u64, b: u64): u64 {
+        a + b - 1
+    }
+    spec add_invalid {
+        ensures result == a + b;
+    }
+
+    fun abort_invalid(x: u64, y: u64) {
+        if (x > y) abort 1
+    }
+    spec abort_invalid {
+        aborts_if x >= y;
+    }
+
+    fun get_invalid(addr: address): u64 acquires R {
+        borrow_global<R>(addr).x
+    }
+    spec get_invalid {
+        ensures result > 0;
+    }
+}
@@ -135,6 +135,25 @@ impl TransactionDataCache {
         Ok(change_set)
     }
 
+    /// Returns the resources loaded by the transaction so far, rendered for display. Used by the
+    /// debugger to show global state at a breakpoint.
+    #[cfg(feature = "debugging")]
+    pub(crate) fn debug_resources(&self) -> Vec<(AccountAddress, StructTag, String)> {
+        let mut resources = vec![];
+        for (addr, account_data_cache) in &self.account_map {
+            for entry in account_data_cache.values() {
+                let mut value = String::new();
+                if let Err(err) =
+                    move_vm_types::values::debug::print_global_value(&mut value, &entry.value)
+                {
+                    value = format!("<error: {}>", err);
+                }
+                resources.push((*addr, entry.struct_tag.clone(), value));
+            }
+        }
+        resources
+    }
+
     /// Retrieves data from the remote on-chain storage and converts it into a [DataCacheEntry].
     /// Also returns the size of the loaded resource in bytes. This method does not add the entry
     /// to the cache - it is the caller's responsibility to add it there.
@@ -127,6 +127,13 @@ pub(crate) struct Interpreter;
 
 pub(crate) trait InterpreterDebugInterface {
     fn get_stack_frames(&self, count: usize) -> ExecutionState;
+    /// Number of frames on the call stack, not counting the currently executing one.
+    #[cfg(feature = "debugging")]
+    fn call_stack_depth(&self) -> usize;
+    /// Frames on the call stack with their locals, innermost first, not counting the currently
+    /// executing one.
+    #[cfg(feature = "debugging")]
+    fn debug_frames(&self) -> Vec<crate::tracing::DebugFrame>;
     fn debug_print_stack_trace(
         &self,
         buf: &mut String,
@@ -1758,6 +1765,27 @@ where
             .collect();
         ExecutionState::new(stack_trace)
     }
+
+    #[cfg(feature = "debugging")]
+    fn call_stack_depth(&self) -> usize {
+        self.call_stack.0.len()
+    }
+
+    #[cfg(feature = "debugging")]
+    fn debug_frames(&self) -> Vec<crate::tracing::DebugFrame> {
+        self.call_stack
+            .0
+            .iter()
+            .rev()
+            .map(|frame| crate::tracing::DebugFrame {
+                module_id: frame.function.module_id().cloned(),
+                function_name: frame.function.name_as_pretty_string(),
+                function_index: frame.function.index(),
+                pc: frame.pc,
+                locals: crate::tracing::render_locals(&frame.locals),
+            })
+            .collect()
+    }
 }
 
 // TODO Determine stack size limits based on gas limit
@@ -2030,6 +2058,8 @@ impl Frame {
                     self.pc,
                     instruction,
                     interpreter.loader.runtime_environment(),
+                    &*data_cache,
+                    gas_meter,
                     interpreter
                 );
 
@@ -5,17 +5,30 @@
 #[cfg(feature = "debugging")]
 use crate::debug::DebugContext;
 #[cfg(feature = "debugging")]
-use crate::{interpreter::InterpreterDebugInterface, loader::LoadedFunction, RuntimeEnvironment};
+use crate::{
+    data_cache::TransactionDataCache, interpreter::InterpreterDebugInterface,
+    loader::LoadedFunction, RuntimeEnvironment,
+};
+use move_binary_format::file_format::{CodeOffset, FunctionDefinitionIndex};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, StructTag},
+};
+use std::collections::BTreeSet;
 #[cfg(feature = "debugging")]
 use ::{
     move_binary_format::file_format::Bytecode,
     move_vm_types::values::Locals,
     once_cell::sync::Lazy,
     std::{
+        cell::RefCell,
         env,
         fs::{File, OpenOptions},
         io::Write,
-        sync::Mutex,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Mutex,
+        },
     },
 };
 
@@ -51,9 +64,165 @@ pub static LOGGING_FILE_WRITER: Lazy<Mutex<std::io::BufWriter<File>>> = Lazy::ne
     ))
 });
 
+#[cfg(feature = "debugging")]
+thread_local! {
+    /// Instructions executed on this thread since the last call to `start_coverage_recording`,
+    /// as pairs of function name and code offset. `None` if coverage is not being recorded.
+    static COVERAGE_RECORDING: RefCell<Option<BTreeSet<(String, u16)>>> =
+        const { RefCell::new(None) };
+
+    /// Instructions executed on this thread since the last call to `start_trace_recording`.
+    /// `None` if no trace is being recorded.
+    static TRACE_RECORDING: RefCell<Option<TraceRecording>> = const { RefCell::new(None) };
+}
+
 #[cfg(feature = "debugging")]
 static DEBUG_CONTEXT: Lazy<Mutex<DebugContext>> = Lazy::new(|| Mutex::new(DebugContext::new()));
 
+/// Set whenever a debugger is installed, so that the interpreter loop does not need to take the
+/// lock when no debugger is attached.
+#[cfg(feature = "debugging")]
+static DEBUGGER_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(feature = "debugging")]
+static DEBUGGER: Lazy<Mutex<Option<Box<dyn Debugger>>>> = Lazy::new(|| Mutex::new(None));
+
+/// A frame on the call stack, as presented to a [`Debugger`].
+#[derive(Clone, Debug)]
+pub struct DebugFrame {
+    /// The module defining the function, or `None` for scripts.
+    pub module_id: Option<ModuleId>,
+    /// The fully qualified name of the function, e.g. `0x1::coin::transfer`.
+    pub function_name: String,
+    pub function_index: FunctionDefinitionIndex,
+    pub pc: CodeOffset,
+    /// The rendered values of the locals of the frame. `None` if a local holds no value.
+    pub locals: Vec<Option<String>>,
+}
+
+/// The state of the VM when a [`Debugger`] stops execution.
+#[derive(Clone, Debug)]
+pub struct DebugState {
+    /// The call stack, innermost frame first.
+    pub frames: Vec<DebugFrame>,
+    /// The instruction which is about to be executed in the innermost frame.
+    pub instruction: String,
+    /// Resources loaded from global storage by the current session, with their rendered values.
+    pub resources: Vec<(AccountAddress, StructTag, String)>,
+}
+
+/// An instruction executed while recording a trace with [`start_trace_recording`].
+#[derive(Clone, Debug)]
+pub struct TracedInstruction {
+    /// The module defining the function, or `None` for scripts.
+    pub module_id: Option<ModuleId>,
+    /// The fully qualified name of the function, e.g. `0x1::coin::transfer`.
+    pub function_name: String,
+    pub function_index: FunctionDefinitionIndex,
+    pub pc: CodeOffset,
+    /// The number of frames on the call stack, including the current one.
+    pub depth: usize,
+    pub instruction: String,
+    /// The internal gas remaining before the instruction is executed.
+    pub gas_remaining: u64,
+}
+
+/// The maximum number of instructions recorded by [`start_trace_recording`]. Recording stops
+/// once a trace reaches it, which bounds the memory used by traces of long executions.
+pub const MAX_TRACED_INSTRUCTIONS: usize = 1_000_000;
+
+/// A trace recorded with [`start_trace_recording`].
+#[derive(Clone, Debug, Default)]
+pub struct TraceRecording {
+    pub instructions: Vec<TracedInstruction>,
+    /// Whether instructions were dropped because the trace reached [`MAX_TRACED_INSTRUCTIONS`].
+    pub truncated: bool,
+}
+
+/// The instruction which is about to be executed.
+#[derive(Clone, Copy, Debug)]
+pub struct DebugLocation<'a> {
+    pub module_id: Option<&'a ModuleId>,
+    pub function_index: FunctionDefinitionIndex,
+    pub pc: CodeOffset,
+    /// The number of frames on the call stack, including the current one.
+    pub depth: usize,
+}
+
+/// A debugger driving the VM. Once installed via [`set_debugger`], it is consulted before every
+/// instruction executed by any thread.
+pub trait Debugger: Send {
+    /// Returns whether execution should stop before the given instruction. This is called for
+    /// every instruction and should be cheap.
+    fn should_stop(&mut self, location: &DebugLocation) -> bool;
+
+    /// Called when execution stopped. Execution resumes once this returns.
+    fn stop(&mut self, state: DebugState);
+}
+
+/// Installs a debugger, replacing any previously installed one, or removes it if `None` is
+/// passed. Debuggers are only supported if the `debugging` feature is enabled, otherwise this
+/// is a no-op.
+pub fn set_debugger(debugger: Option<Box<dyn Debugger>>) {
+    #[cfg(feature = "debugging")]
+    {
+        let mut installed = DEBUGGER.lock().unwrap();
+        DEBUGGER_INSTALLED.store(debugger.is_some(), Ordering::Release);
+        *installed = debugger;
+    }
+    #[cfg(not(feature = "debugging"))]
+    let _ = debugger;
+}
+
+pub const fn is_debugger_supported() -> bool {
+    cfg!(feature = "debugging")
+}
+
+#[cfg(feature = "debugging")]
+fn run_debugger(
+    function: &LoadedFunction,
+    locals: &Locals,
+    pc: u16,
+    instr: &Bytecode,
+    data_cache: &TransactionDataCache,
+    interpreter: &dyn InterpreterDebugInterface,
+) {
+    let mut debugger = DEBUGGER.lock().unwrap();
+    let Some(debugger) = debugger.as_mut() else {
+        return;
+    };
+    let location = DebugLocation {
+        module_id: function.module_id(),
+        function_index: function.index(),
+        pc,
+        depth: interpreter.call_stack_depth() + 1,
+    };
+    if !debugger.should_stop(&location) {
+        return;
+    }
+    let current_frame = DebugFrame {
+        module_id: function.module_id().cloned(),
+        function_name: function.name_as_pretty_string(),
+        function_index: function.index(),
+        pc,
+        locals: render_locals(locals),
+    };
+    let frames = std::iter::once(current_frame)
+        .chain(interpreter.debug_frames())
+        .collect();
+    debugger.stop(DebugState {
+        frames,
+        instruction: format!("{:?}", instr),
+        resources: data_cache.debug_resources(),
+    });
+}
+
+#[cfg(feature = "debugging")]
+pub(crate) fn render_locals(locals: &Locals) -> Vec<Option<String>> {
+    move_vm_types::values::debug::locals_to_strings(locals)
+        .unwrap_or_else(|err| vec![Some(format!("<error: {}>", err))])
+}
+
 // Only include in debug builds
 #[cfg(feature = "debugging")]
 pub(crate) fn trace(
@@ -62,8 +231,32 @@ pub(crate) fn trace(
     pc: u16,
     instr: &Bytecode,
     runtime_environment: &RuntimeEnvironment,
+    data_cache: &TransactionDataCache,
+    gas_remaining: u64,
     interpreter: &dyn InterpreterDebugInterface,
 ) {
+    COVERAGE_RECORDING.with_borrow_mut(|recording| {
+        if let Some(covered) = recording {
+            covered.insert((function.name_as_pretty_string(), pc));
+        }
+    });
+    TRACE_RECORDING.with_borrow_mut(|recording| {
+        if let Some(trace) = recording {
+            if trace.instructions.len() >= MAX_TRACED_INSTRUCTIONS {
+                trace.truncated = true;
+                return;
+            }
+            trace.instructions.push(TracedInstruction {
+                module_id: function.module_id().cloned(),
+                function_name: function.name_as_pretty_string(),
+                function_index: function.index(),
+                pc,
+                depth: interpreter.call_stack_depth() + 1,
+                instruction: format!("{:?}", instr),
+                gas_remaining,
+            });
+        }
+    });
     if *TRACING_ENABLED {
         let buf_writer = &mut *LOGGING_FILE_WRITER.lock().unwrap();
         buf_writer
@@ -85,17 +278,81 @@ pub(crate) fn trace(
             interpreter,
         );
     }
+    if DEBUGGER_INSTALLED.load(Ordering::Acquire) {
+        run_debugger(function, locals, pc, instr, data_cache, interpreter);
+    }
 }
 
 #[macro_export]
 macro_rules! trace {
-    ($function_desc:expr, $locals:expr, $pc:expr, $instr:tt, $resolver:expr, $interp:expr) => {
+    (
+        $function_desc:expr,
+        $locals:expr,
+        $pc:expr,
+        $instr:tt,
+        $resolver:expr,
+        $data_cache:expr,
+        $gas_meter:expr,
+        $interp:expr
+    ) => {
         // Only include this code in debug releases
         #[cfg(feature = "debugging")]
-        $crate::tracing::trace(&$function_desc, $locals, $pc, &$instr, $resolver, $interp)
+        $crate::tracing::trace(
+            &$function_desc,
+            $locals,
+            $pc,
+            &$instr,
+            $resolver,
+            $data_cache,
+            $gas_meter.balance_internal().into(),
+            $interp,
+        )
     };
 }
 
+/// Starts recording which instructions are executed by the VM on the current thread. Recording
+/// is only supported if the `debugging` feature is enabled, otherwise this is a no-op.
+pub fn start_coverage_recording() {
+    #[cfg(feature = "debugging")]
+    COVERAGE_RECORDING.set(Some(BTreeSet::new()));
+}
+
+/// Stops recording and returns the instructions executed on the current thread since the last
+/// call to `start_coverage_recording`, as pairs of function name (`0x1::module::function`) and
+/// code offset.
+pub fn take_coverage_recording() -> BTreeSet<(String, u16)> {
+    #[cfg(feature = "debugging")]
+    if let Some(covered) = COVERAGE_RECORDING.take() {
+        return covered;
+    }
+    BTreeSet::new()
+}
+
+pub const fn is_coverage_recording_supported() -> bool {
+    cfg!(feature = "debugging")
+}
+
+/// Starts recording a trace of the instructions executed by the VM on the current thread.
+/// Recording is only supported if the `debugging` feature is enabled, otherwise this is a no-op.
+pub fn start_trace_recording() {
+    #[cfg(feature = "debugging")]
+    TRACE_RECORDING.set(Some(TraceRecording::default()));
+}
+
+/// Stops recording and returns the instructions executed on the current thread since the last
+/// call to `start_trace_recording`, in execution order.
+pub fn take_trace_recording() -> TraceRecording {
+    #[cfg(feature = "debugging")]
+    if let Some(trace) = TRACE_RECORDING.take() {
+        return trace;
+    }
+    TraceRecording::default()
+}
+
+pub const fn is_trace_recording_supported() -> bool {
+    cfg!(feature = "debugging")
+}
+
 pub const fn assert_move_vm_tracing_feature_disabled(err_msg: &str) {
     assert!(!cfg!(feature = "debugging"), "{}", err_msg)
 }
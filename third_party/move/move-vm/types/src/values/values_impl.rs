@@ -4523,6 +4523,34 @@ pub mod debug {
     pub fn print_value<B: Write>(buf: &mut B, val: &Value) -> PartialVMResult<()> {
         print_value_impl(buf, val)
     }
+
+    /// Prints every local into its own string. Locals which currently do not hold a value
+    /// are returned as `None`.
+    pub fn locals_to_strings(locals: &Locals) -> PartialVMResult<Vec<Option<String>>> {
+        locals
+            .0
+            .borrow()
+            .iter()
+            .map(|val| {
+                if matches!(val, Value::Invalid) {
+                    return Ok(None);
+                }
+                let mut buf = String::new();
+                print_value_impl(&mut buf, val)?;
+                Ok(Some(buf))
+            })
+            .collect()
+    }
+
+    /// Prints the resource stored in a global value, or `-` if there is none.
+    pub fn print_global_value<B: Write>(buf: &mut B, val: &GlobalValue) -> PartialVMResult<()> {
+        match &val.0 {
+            GlobalValueImpl::None | GlobalValueImpl::Deleted => print_invalid(buf),
+            GlobalValueImpl::Fresh { fields } | GlobalValueImpl::Cached { fields, .. } => {
+                print_list(buf, "{ ", fields.borrow().iter(), print_value_impl, " }")
+            },
+        }
+    }
 }
 
 /***************************************************************************************
@@ -81,6 +81,16 @@ pub struct Test {
     /// Collect coverage information for later use with the various `move coverage` subcommands
     #[clap(long = "coverage")]
     pub compute_coverage: bool,
+    /// Number of runs with generated arguments for each `#[test_fuzz]` test
+    #[clap(long = "fuzz-runs", default_value = "256")]
+    pub fuzz_runs: u64,
+    /// Seed for generating arguments of `#[test_fuzz]` tests. A random seed is used if not set.
+    #[clap(long = "fuzz-seed")]
+    pub fuzz_seed: Option<u64>,
+    /// Serve the Debug Adapter Protocol on this address and run the tests under the control of
+    /// the debugger which connects to it
+    #[clap(long = "debug-adapter")]
+    pub debug_adapter: Option<String>,
 }
 
 impl Test {
@@ -103,6 +113,9 @@ impl Test {
             ignore_compile_warnings,
             verbose_mode,
             compute_coverage,
+            fuzz_runs,
+            fuzz_seed,
+            debug_adapter,
         } = self;
         let unit_test_config = UnitTestingConfig {
             filter,
@@ -112,6 +125,9 @@ impl Test {
             report_storage_on_error,
             verbose: verbose_mode,
             ignore_compile_warnings,
+            fuzz_runs,
+            fuzz_seed,
+            debug_adapter,
             ..UnitTestingConfig::default()
         };
         let result = run_move_unit_tests(
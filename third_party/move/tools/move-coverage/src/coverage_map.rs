@@ -213,6 +213,34 @@ impl ExecCoverageMap {
         self.insert_multi(module_addr, module_name, func_name, pc, 1);
     }
 
+    /// Records an execution of the instruction at `pc` in the function named by `context`, which
+    /// is of the form `0x1::module::function` as written to VM traces. Returns true if the
+    /// instruction was not covered before. Scripts and malformed contexts are ignored.
+    pub fn insert_trace_entry(&mut self, context: &str, pc: u64) -> bool {
+        let segs: Vec<_> = context.split("::").collect();
+        let [addr, module_name, func_name] = segs.as_slice() else {
+            return false;
+        };
+        let (Ok(module_addr), Ok(module_name), Ok(func_name)) = (
+            AccountAddress::from_hex_literal(addr),
+            Identifier::new(*module_name),
+            Identifier::new(*func_name),
+        ) else {
+            return false;
+        };
+        let count = self
+            .module_maps
+            .entry((module_addr, module_name.clone()))
+            .or_insert_with(|| ModuleCoverageMap::new(module_addr, module_name))
+            .function_maps
+            .entry(func_name)
+            .or_default()
+            .entry(pc)
+            .or_insert(0);
+        *count += 1;
+        *count == 1
+    }
+
     pub fn into_coverage_map_with_modules(
         self,
         modules: BTreeMap<AccountAddress, BTreeMap<Identifier, (String, CompiledModule)>>,
@@ -0,0 +1,680 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Exports coverage in formats understood by CI coverage dashboards: LCOV tracefiles and
+//! Cobertura XML. Execution counts of instructions are mapped to source lines through the source
+//! maps of the modules.
+
+#![forbid(unsafe_code)]
+
+use crate::coverage_map::ExecCoverageMap;
+use anyhow::{bail, format_err, Result};
+use clap::ValueEnum;
+use codespan::Files;
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{Bytecode, CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Format to export coverage in.
+#[derive(ValueEnum, Clone, Copy, Debug, Serialize)]
+pub enum ExportFormat {
+    /// LCOV tracefile, as produced by `geninfo`
+    Lcov,
+    /// Cobertura XML
+    Cobertura,
+}
+
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportFormat::Lcov => f.write_str("lcov"),
+            ExportFormat::Cobertura => f.write_str("cobertura"),
+        }
+    }
+}
+
+/// Line and branch coverage of a module.
+#[derive(Debug, Serialize)]
+pub struct ModuleLineCoverage {
+    pub module_name: ModuleId,
+    pub source_path: PathBuf,
+    /// Coverage of the functions with code, in definition order.
+    pub functions: Vec<FunctionLineCoverage>,
+}
+
+/// Line and branch coverage of a function.
+#[derive(Debug, Serialize)]
+pub struct FunctionLineCoverage {
+    pub name: Identifier,
+    /// The (1-based) line the function is defined at.
+    pub line: u32,
+    /// The number of times the function was called.
+    pub hits: u64,
+    /// Coverage of the lines with instructions of the function, by (1-based) line number. This
+    /// includes lines of inline functions defined in the same file.
+    pub lines: BTreeMap<u32, LineCoverage>,
+}
+
+/// Coverage of a source line.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct LineCoverage {
+    /// The maximal execution count of the instructions on the line.
+    pub hits: u64,
+    /// The conditional branches on the line, in bytecode order.
+    pub branches: Vec<BranchCoverage>,
+}
+
+/// Coverage of a conditional branch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct BranchCoverage {
+    /// Whether the branch instruction was executed.
+    pub reached: bool,
+    /// How often the branch jumped to its target, and how often it fell through.
+    ///
+    /// Counts are not recorded per edge, so these are the execution counts of the first
+    /// instruction of each successor, bounded by the execution count of the branch. A successor
+    /// reached from elsewhere may hence be counted as taken.
+    pub taken: [u64; 2],
+}
+
+impl BranchCoverage {
+    fn num_taken(&self) -> usize {
+        self.taken.iter().filter(|count| **count > 0).count()
+    }
+}
+
+impl ModuleLineCoverage {
+    /// Maps the coverage of a module to the lines of its source file.
+    pub fn new(
+        module: &CompiledModule,
+        source_map: &SourceMap,
+        source_path: &Path,
+        coverage_map: &ExecCoverageMap,
+    ) -> Result<Self> {
+        let module_name = module.self_id();
+        let file_contents = fs::read_to_string(source_path)?;
+        if !source_map.check(&file_contents) {
+            bail!(
+                "File contents {} out of sync with source map",
+                source_path.display()
+            );
+        }
+        let file_hash = source_map.definition_location.file_hash();
+        let mut files = Files::new();
+        let file_id = files.add(source_path.as_os_str().to_os_string(), file_contents);
+        let line_of = |offset: u32| -> Result<u32> {
+            let location = files
+                .location(file_id, offset)
+                .map_err(|err| format_err!("{} in {}", err, source_path.display()))?;
+            Ok(location.line.0 + 1)
+        };
+
+        let module_map = coverage_map
+            .module_maps
+            .get(&(*module_name.address(), module_name.name().to_owned()));
+        let mut functions = vec![];
+        for (idx, function_def) in module.function_defs().iter().enumerate() {
+            let Some(code_unit) = &function_def.code else {
+                continue;
+            };
+            let fdef_idx = FunctionDefinitionIndex(idx as u16);
+            let name = module
+                .identifier_at(module.function_handle_at(function_def.function).name)
+                .to_owned();
+            let function_coverage =
+                module_map.and_then(|module_map| module_map.get_function_coverage(&name));
+            let count = |offset: CodeOffset| {
+                function_coverage
+                    .and_then(|coverage| coverage.get(&(offset as u64)))
+                    .copied()
+                    .unwrap_or(0)
+            };
+
+            let mut lines = BTreeMap::<u32, LineCoverage>::new();
+            for (offset, instruction) in code_unit.code.iter().enumerate() {
+                let offset = offset as CodeOffset;
+                let Ok(loc) = source_map.get_code_location(fdef_idx, offset) else {
+                    continue;
+                };
+                // Skip code inlined from other files.
+                if loc.file_hash() != file_hash {
+                    continue;
+                }
+                let line = lines.entry(line_of(loc.start())?).or_default();
+                line.hits = line.hits.max(count(offset));
+                if let Bytecode::BrTrue(target) | Bytecode::BrFalse(target) = instruction {
+                    let hits = count(offset);
+                    line.branches.push(BranchCoverage {
+                        reached: hits > 0,
+                        taken: [count(*target).min(hits), count(offset + 1).min(hits)],
+                    });
+                }
+            }
+
+            let definition_location = source_map
+                .get_function_source_map(fdef_idx)?
+                .definition_location;
+            functions.push(FunctionLineCoverage {
+                name,
+                line: line_of(definition_location.start())?,
+                hits: count(0),
+                lines,
+            });
+        }
+
+        Ok(Self {
+            module_name,
+            source_path: source_path.to_path_buf(),
+            functions,
+        })
+    }
+
+    /// Coverage of all lines of the module, merged over its functions.
+    pub fn lines(&self) -> BTreeMap<u32, LineCoverage> {
+        let mut lines = BTreeMap::<u32, LineCoverage>::new();
+        for function in &self.functions {
+            for (number, coverage) in &function.lines {
+                let line = lines.entry(*number).or_default();
+                line.hits = line.hits.max(coverage.hits);
+                line.branches.extend(coverage.branches.iter().copied());
+            }
+        }
+        lines
+    }
+}
+
+/// Writes an LCOV tracefile with one record per source file. Paths are made relative to `root`
+/// where possible.
+pub fn write_lcov<W: Write>(
+    modules: &[ModuleLineCoverage],
+    root: &Path,
+    writer: &mut W,
+) -> io::Result<()> {
+    let mut files = BTreeMap::<&Path, Vec<&ModuleLineCoverage>>::new();
+    for module in modules {
+        files.entry(&module.source_path).or_default().push(module);
+    }
+
+    for (source_path, modules) in files {
+        writeln!(writer, "TN:")?;
+        writeln!(writer, "SF:{}", relative_path(source_path, root).display())?;
+
+        let functions = modules
+            .iter()
+            .flat_map(|module| {
+                module.functions.iter().map(move |function| {
+                    (
+                        format!("{}::{}", module.module_name.name(), function.name),
+                        function,
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        for (name, function) in &functions {
+            writeln!(writer, "FN:{},{}", function.line, name)?;
+        }
+        for (name, function) in &functions {
+            writeln!(writer, "FNDA:{},{}", function.hits, name)?;
+        }
+        writeln!(writer, "FNF:{}", functions.len())?;
+        writeln!(
+            writer,
+            "FNH:{}",
+            functions.iter().filter(|(_, f)| f.hits > 0).count()
+        )?;
+
+        let mut lines = BTreeMap::<u32, LineCoverage>::new();
+        for module in modules {
+            for (number, coverage) in module.lines() {
+                let line = lines.entry(number).or_default();
+                line.hits = line.hits.max(coverage.hits);
+                line.branches.extend(coverage.branches);
+            }
+        }
+
+        let (mut branches_found, mut branches_hit) = (0, 0);
+        for (number, line) in &lines {
+            for (block, branch) in line.branches.iter().enumerate() {
+                for (index, taken) in branch.taken.iter().enumerate() {
+                    if branch.reached {
+                        writeln!(writer, "BRDA:{},{},{},{}", number, block, index, taken)?;
+                    } else {
+                        writeln!(writer, "BRDA:{},{},{},-", number, block, index)?;
+                    }
+                }
+                branches_found += branch.taken.len();
+                branches_hit += branch.num_taken();
+            }
+        }
+        writeln!(writer, "BRF:{}", branches_found)?;
+        writeln!(writer, "BRH:{}", branches_hit)?;
+
+        for (number, line) in &lines {
+            writeln!(writer, "DA:{},{}", number, line.hits)?;
+        }
+        writeln!(writer, "LF:{}", lines.len())?;
+        writeln!(
+            writer,
+            "LH:{}",
+            lines.values().filter(|line| line.hits > 0).count()
+        )?;
+        writeln!(writer, "end_of_record")?;
+    }
+    Ok(())
+}
+
+/// Writes a Cobertura XML report with one package, the modules of which are the classes.
+/// File names are made relative to `root`, which is the source directory of the report.
+pub fn write_cobertura<W: Write>(
+    package_name: &str,
+    modules: &[ModuleLineCoverage],
+    root: &Path,
+    writer: &mut W,
+) -> io::Result<()> {
+    let module_lines = modules
+        .iter()
+        .map(|module| module.lines())
+        .collect::<Vec<_>>();
+    let package_rates = Rates::of(module_lines.iter().flat_map(|lines| lines.values()));
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    writeln!(writer, r#"<?xml version="1.0" ?>"#)?;
+    writeln!(
+        writer,
+        r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+    )?;
+    writeln!(
+        writer,
+        r#"<coverage {} lines-covered="{}" lines-valid="{}" branches-covered="{}" branches-valid="{}" version="1.9" timestamp="{}">"#,
+        package_rates,
+        package_rates.lines_covered,
+        package_rates.lines_valid,
+        package_rates.branches_covered,
+        package_rates.branches_valid,
+        timestamp
+    )?;
+    writeln!(writer, "  <sources>")?;
+    writeln!(
+        writer,
+        "    <source>{}</source>",
+        escape_xml(&root.display().to_string())
+    )?;
+    writeln!(writer, "  </sources>")?;
+    writeln!(writer, "  <packages>")?;
+    writeln!(
+        writer,
+        r#"    <package name="{}" {}>"#,
+        escape_xml(package_name),
+        package_rates
+    )?;
+    writeln!(writer, "      <classes>")?;
+    for (module, lines) in modules.iter().zip(&module_lines) {
+        writeln!(
+            writer,
+            r#"        <class name="{}" filename="{}" {}>"#,
+            escape_xml(&module.module_name.short_str_lossless()),
+            escape_xml(
+                &relative_path(&module.source_path, root)
+                    .display()
+                    .to_string()
+            ),
+            Rates::of(lines.values())
+        )?;
+        writeln!(writer, "          <methods>")?;
+        for function in &module.functions {
+            writeln!(
+                writer,
+                r#"            <method name="{}" signature="" {}>"#,
+                escape_xml(function.name.as_str()),
+                Rates::of(function.lines.values())
+            )?;
+            write_cobertura_lines(&function.lines, "              ", writer)?;
+            writeln!(writer, "            </method>")?;
+        }
+        writeln!(writer, "          </methods>")?;
+        write_cobertura_lines(lines, "          ", writer)?;
+        writeln!(writer, "        </class>")?;
+    }
+    writeln!(writer, "      </classes>")?;
+    writeln!(writer, "    </package>")?;
+    writeln!(writer, "  </packages>")?;
+    writeln!(writer, "</coverage>")
+}
+
+fn write_cobertura_lines<W: Write>(
+    lines: &BTreeMap<u32, LineCoverage>,
+    indent: &str,
+    writer: &mut W,
+) -> io::Result<()> {
+    writeln!(writer, "{}<lines>", indent)?;
+    for (number, line) in lines {
+        if line.branches.is_empty() {
+            writeln!(
+                writer,
+                r#"{}  <line number="{}" hits="{}" branch="false"/>"#,
+                indent, number, line.hits
+            )?;
+        } else {
+            let total = line.branches.len() * 2;
+            let taken = line
+                .branches
+                .iter()
+                .map(BranchCoverage::num_taken)
+                .sum::<usize>();
+            writeln!(
+                writer,
+                r#"{}  <line number="{}" hits="{}" branch="true" condition-coverage="{}% ({}/{})"/>"#,
+                indent,
+                number,
+                line.hits,
+                taken * 100 / total,
+                taken,
+                total
+            )?;
+        }
+    }
+    writeln!(writer, "{}</lines>", indent)
+}
+
+/// Line and branch rates, displayed as Cobertura attributes.
+struct Rates {
+    lines_covered: usize,
+    lines_valid: usize,
+    branches_covered: usize,
+    branches_valid: usize,
+}
+
+impl Rates {
+    fn of<'a>(lines: impl Iterator<Item = &'a LineCoverage>) -> Self {
+        let mut rates = Rates {
+            lines_covered: 0,
+            lines_valid: 0,
+            branches_covered: 0,
+            branches_valid: 0,
+        };
+        for line in lines {
+            rates.lines_valid += 1;
+            if line.hits > 0 {
+                rates.lines_covered += 1;
+            }
+            for branch in &line.branches {
+                rates.branches_valid += branch.taken.len();
+                rates.branches_covered += branch.num_taken();
+            }
+        }
+        rates
+    }
+}
+
+impl Display for Rates {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let rate = |covered: usize, valid: usize| {
+            if valid == 0 {
+                1.0
+            } else {
+                covered as f64 / valid as f64
+            }
+        };
+        write!(
+            f,
+            r#"line-rate="{:.4}" branch-rate="{:.4}" complexity="0""#,
+            rate(self.lines_covered, self.lines_valid),
+            rate(self.branches_covered, self.branches_valid)
+        )
+    }
+}
+
+fn relative_path<'a>(path: &'a Path, root: &Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use legacy_move_compiler::compiled_unit::CompiledUnitEnum;
+    use move_compiler_v2::Experiment;
+    use move_core_types::account_address::AccountAddress;
+
+    fn branch(reached: bool, taken: [u64; 2]) -> BranchCoverage {
+        BranchCoverage { reached, taken }
+    }
+
+    fn module() -> ModuleLineCoverage {
+        let line = |hits, branches| LineCoverage { hits, branches };
+        ModuleLineCoverage {
+            module_name: ModuleId::new(AccountAddress::ONE, Identifier::new("m").unwrap()),
+            source_path: PathBuf::from("/pkg/sources/m.move"),
+            functions: vec![
+                FunctionLineCoverage {
+                    name: Identifier::new("f").unwrap(),
+                    line: 2,
+                    hits: 3,
+                    lines: BTreeMap::from([
+                        (3, line(3, vec![branch(true, [3, 0])])),
+                        (4, line(0, vec![])),
+                        (5, line(3, vec![])),
+                    ]),
+                },
+                FunctionLineCoverage {
+                    name: Identifier::new("g").unwrap(),
+                    line: 8,
+                    hits: 0,
+                    lines: BTreeMap::from([(9, line(0, vec![branch(false, [0, 0])]))]),
+                },
+            ],
+        }
+    }
+
+    const INLINED_SOURCE: &str = "module 0x42::a {
+    public inline fun double(x: u64): u64 {
+        x * 2
+    }
+}
+";
+
+    const SOURCE: &str = "module 0x42::b {
+    use 0x42::a;
+
+    public fun f(x: u64): u64 {
+        if (x > 10) {
+            a::double(x)
+        } else {
+            x + 1
+        }
+    }
+
+    public fun g(): u64 {
+        1
+    }
+}
+";
+
+    /// Compiles `SOURCE`, which calls an inline function of another file, and returns module
+    /// `0x42::b` with its source map.
+    fn compile(dir: &Path) -> (CompiledModule, SourceMap) {
+        let inlined_path = dir.join("a.move");
+        let path = dir.join("b.move");
+        fs::write(&inlined_path, INLINED_SOURCE).unwrap();
+        fs::write(&path, SOURCE).unwrap();
+        let options = move_compiler_v2::Options {
+            sources: vec![
+                inlined_path.display().to_string(),
+                path.display().to_string(),
+            ],
+            ..Default::default()
+        }
+        .set_experiment(Experiment::OPTIMIZE, false);
+        let (_, units) = move_compiler_v2::run_move_compiler_to_stderr(options).unwrap();
+        units
+            .into_iter()
+            .find_map(|unit| match unit {
+                CompiledUnitEnum::Module(unit)
+                    if unit.named_module.module.self_id().name().as_str() == "b" =>
+                {
+                    Some((unit.named_module.module, unit.named_module.source_map))
+                },
+                _ => None,
+            })
+            .expect("module b")
+    }
+
+    /// Records the execution of `f` for every given value of the condition, following the
+    /// control flow of its bytecode.
+    fn execute_f(module: &CompiledModule, conditions: &[bool]) -> ExecCoverageMap {
+        let name = Identifier::new("f").unwrap();
+        let code = &module
+            .function_defs()
+            .iter()
+            .find(|def| {
+                module.identifier_at(module.function_handle_at(def.function).name) == &*name
+            })
+            .and_then(|def| def.code.as_ref())
+            .unwrap()
+            .code;
+        let mut coverage_map = ExecCoverageMap::new("test".to_string());
+        for condition in conditions {
+            let mut offset = 0;
+            loop {
+                coverage_map.insert(
+                    *module.self_id().address(),
+                    module.self_id().name().to_owned(),
+                    name.clone(),
+                    offset as u64,
+                );
+                offset = match &code[offset] {
+                    Bytecode::Ret => break,
+                    Bytecode::Branch(target) => *target as usize,
+                    Bytecode::BrTrue(target) if *condition => *target as usize,
+                    Bytecode::BrFalse(target) if !*condition => *target as usize,
+                    _ => offset + 1,
+                };
+            }
+        }
+        coverage_map
+    }
+
+    #[test]
+    fn test_module_line_coverage() {
+        let dir = tempfile::tempdir().unwrap();
+        let (module, source_map) = compile(dir.path());
+        let coverage_map = execute_f(&module, &[true, false, false]);
+        let coverage = ModuleLineCoverage::new(
+            &module,
+            &source_map,
+            &dir.path().join("b.move"),
+            &coverage_map,
+        )
+        .unwrap();
+
+        let [f, g] = coverage.functions.as_slice() else {
+            panic!("expected two functions, got {:?}", coverage.functions)
+        };
+        assert_eq!((f.name.as_str(), f.line, f.hits), ("f", 4, 3));
+        assert_eq!((g.name.as_str(), g.line, g.hits), ("g", 12, 0));
+        assert!(g.lines.values().all(|line| line.hits == 0));
+
+        // Code inlined from `a.move` is skipped, rather than attributed to lines of `b.move`.
+        assert!(
+            f.lines.keys().all(|line| (5..=10).contains(line)),
+            "unexpected lines {:?}",
+            f.lines
+        );
+        let condition = &f.lines[&5];
+        assert_eq!(condition.hits, 3);
+        let [branch] = condition.branches.as_slice() else {
+            panic!("expected one branch, got {:?}", condition.branches)
+        };
+        assert!(branch.reached);
+        // Depending on the branch instruction chosen, either successor is the jump target.
+        let mut taken = branch.taken;
+        taken.sort();
+        assert_eq!(taken, [1, 2]);
+        assert_eq!(f.lines[&8].hits, 2);
+        assert!(f
+            .lines
+            .values()
+            .all(|line| line.branches.is_empty() || line == condition));
+    }
+
+    #[test]
+    fn test_lcov_export() {
+        let mut output = vec![];
+        write_lcov(&[module()], Path::new("/pkg"), &mut output).unwrap();
+        let expected = "\
+TN:
+SF:sources/m.move
+FN:2,m::f
+FN:8,m::g
+FNDA:3,m::f
+FNDA:0,m::g
+FNF:2
+FNH:1
+BRDA:3,0,0,3
+BRDA:3,0,1,0
+BRDA:9,0,0,-
+BRDA:9,0,1,-
+BRF:4
+BRH:1
+DA:3,3
+DA:4,0
+DA:5,3
+DA:9,0
+LF:4
+LH:2
+end_of_record
+";
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn test_cobertura_export() {
+        let mut output = vec![];
+        write_cobertura("P&Q", &[module()], Path::new("/pkg"), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(
+            r#"lines-covered="2" lines-valid="4" branches-covered="1" branches-valid="4""#
+        ));
+        assert!(output.contains(
+            r#"<package name="P&amp;Q" line-rate="0.5000" branch-rate="0.2500" complexity="0">"#
+        ));
+        assert!(output.contains(r#"<class name="0x1::m" filename="sources/m.move""#));
+        assert!(output.contains(
+            r#"<method name="f" signature="" line-rate="0.6667" branch-rate="0.5000" complexity="0">"#
+        ));
+        assert!(output.contains(
+            r#"<line number="3" hits="3" branch="true" condition-coverage="50% (1/2)"/>"#
+        ));
+        assert!(output.contains(r#"<line number="4" hits="0" branch="false"/>"#));
+    }
+}
@@ -0,0 +1,16 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A [Debug Adapter Protocol](https://microsoft.github.io/debug-adapter-protocol/) server for
+//! Move. Editors connect to it over TCP (e.g. via `debugServer` in a VS Code launch
+//! configuration) to set breakpoints in Move sources, step through execution, and inspect
+//! locals and global resources.
+//!
+//! The server drives the VM via the debugger hook in `move_vm_runtime::tracing`, which is only
+//! available if the VM is built with the `debugging` feature.
+
+mod protocol;
+mod server;
+
+pub use move_bytecode_source_map::source_index::{SourceIndex, SourceLine};
+pub use server::DebugServer;
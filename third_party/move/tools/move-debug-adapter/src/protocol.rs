@@ -0,0 +1,121 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Framing of Debug Adapter Protocol messages: a `Content-Length` header followed by a JSON
+//! body.
+
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+
+const CONTENT_LENGTH: &str = "Content-Length:";
+
+/// The largest message accepted, far more than any request sent by a debugger.
+const MAX_CONTENT_LENGTH: usize = 1 << 20;
+
+/// Reads the next message. Returns `None` if the stream was closed.
+pub(crate) fn read_message(reader: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(length) = header.strip_prefix(CONTENT_LENGTH) {
+            content_length = Some(
+                length
+                    .trim()
+                    .parse::<usize>()
+                    .context("invalid Content-Length header")?,
+            );
+        }
+    }
+    let Some(content_length) = content_length else {
+        bail!("message without Content-Length header")
+    };
+    if content_length > MAX_CONTENT_LENGTH {
+        bail!(
+            "message of {} bytes exceeds the limit of {} bytes",
+            content_length,
+            MAX_CONTENT_LENGTH
+        );
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+pub(crate) fn write_message(writer: &mut impl Write, message: &Value) -> Result<()> {
+    let body = serde_json::to_string(message)?;
+    write!(writer, "{} {}\r\n\r\n{}", CONTENT_LENGTH, body.len(), body)?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub(crate) fn response(seq: i64, request: &Value, body: Value) -> Value {
+    json!({
+        "seq": seq,
+        "type": "response",
+        "request_seq": request["seq"],
+        "success": true,
+        "command": request["command"],
+        "body": body,
+    })
+}
+
+pub(crate) fn error_response(seq: i64, request: &Value, message: &str) -> Value {
+    json!({
+        "seq": seq,
+        "type": "response",
+        "request_seq": request["seq"],
+        "success": false,
+        "command": request["command"],
+        "message": message,
+    })
+}
+
+pub(crate) fn event(seq: i64, event: &str, body: Value) -> Value {
+    json!({
+        "seq": seq,
+        "type": "event",
+        "event": event,
+        "body": body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    #[test]
+    fn round_trip() {
+        let request = json!({"seq": 1, "type": "request", "command": "threads"});
+        let mut buf = vec![];
+        write_message(&mut buf, &request).unwrap();
+        write_message(&mut buf, &event(2, "initialized", json!({}))).unwrap();
+
+        let mut reader = BufReader::new(buf.as_slice());
+        assert_eq!(read_message(&mut reader).unwrap(), Some(request));
+        let event = read_message(&mut reader).unwrap().unwrap();
+        assert_eq!(event["event"], "initialized");
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn content_length_too_large() {
+        let header = format!("{} {}\r\n\r\n", CONTENT_LENGTH, MAX_CONTENT_LENGTH + 1);
+        let mut reader = BufReader::new(header.as_bytes());
+        assert!(read_message(&mut reader).is_err());
+    }
+
+    #[test]
+    fn missing_content_length() {
+        let mut reader = BufReader::new("Content-Type: json\r\n\r\n{}".as_bytes());
+        assert!(read_message(&mut reader).is_err());
+    }
+}
@@ -0,0 +1,486 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The debug adapter: serves Debug Adapter Protocol requests from a client on one thread, while
+//! the VM consults it as a [`Debugger`] on the thread executing Move code.
+
+use crate::protocol;
+use anyhow::{bail, Result};
+use move_binary_format::file_format::{CodeOffset, FunctionDefinitionIndex};
+use move_bytecode_source_map::source_index::{CodeLocation, SourceIndex, SourceLine};
+use move_core_types::language_storage::ModuleId;
+use move_vm_runtime::tracing::{DebugLocation, DebugState, Debugger};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::BufReader,
+    net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc, Condvar, Mutex, MutexGuard,
+    },
+    thread,
+    time::Duration,
+};
+
+/// The VM is presented to the client as a single thread.
+const THREAD_ID: i64 = 1;
+
+/// Variables reference of the scope showing global resources. The scope showing the locals of
+/// frame `i` has reference `FIRST_FRAME_REFERENCE + i`.
+const RESOURCES_REFERENCE: usize = 1;
+const FIRST_FRAME_REFERENCE: usize = 2;
+
+/// How long to wait for the client to disconnect after execution terminated.
+const DISCONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StepMode {
+    Continue,
+    Pause,
+    StepIn,
+    StepOver,
+    StepOut,
+}
+
+/// Where execution was stopped when a step was requested.
+struct StepOrigin {
+    depth: usize,
+    line: Option<SourceLine>,
+}
+
+struct State {
+    /// Breakpoints per source file, as set by the client.
+    breakpoints: BTreeMap<PathBuf, Vec<CodeLocation>>,
+    /// All breakpoints, indexed for fast lookup while executing.
+    active_breakpoints: BTreeMap<ModuleId, BTreeSet<(FunctionDefinitionIndex, CodeOffset)>>,
+    mode: StepMode,
+    step_origin: Option<StepOrigin>,
+    /// The reason reported to the client when execution stops while pausing.
+    pause_reason: &'static str,
+    stop_reason: &'static str,
+    /// The state of the VM while it is stopped.
+    stopped: Option<DebugState>,
+    resume: bool,
+    configured: bool,
+    disconnected: bool,
+}
+
+impl State {
+    fn rebuild_active_breakpoints(&mut self) {
+        self.active_breakpoints.clear();
+        for (module_id, function_index, offset) in self.breakpoints.values().flatten() {
+            self.active_breakpoints
+                .entry(module_id.clone())
+                .or_default()
+                .insert((*function_index, *offset));
+        }
+    }
+
+    fn resume(&mut self, mode: StepMode, sources: &SourceIndex) {
+        self.step_origin = self.stopped.as_ref().map(|stopped| StepOrigin {
+            depth: stopped.frames.len(),
+            line: stopped.frames.first().and_then(|frame| {
+                sources.source_line(frame.module_id.as_ref()?, frame.function_index, frame.pc)
+            }),
+        });
+        self.mode = mode;
+        self.resume = true;
+    }
+}
+
+struct Shared {
+    state: Mutex<State>,
+    changed: Condvar,
+    writer: Mutex<TcpStream>,
+    seq: AtomicI64,
+    sources: SourceIndex,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    fn next_seq(&self) -> i64 {
+        self.seq.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Sends a message to the client. Failures are ignored, a client which went away is
+    /// detected by the request loop.
+    fn send(&self, message: Value) {
+        let _ = protocol::write_message(&mut *self.writer.lock().unwrap(), &message);
+    }
+
+    fn send_event(&self, event: &str, body: Value) {
+        self.send(protocol::event(self.next_seq(), event, body));
+    }
+
+    fn disconnect(&self) {
+        let mut state = self.lock();
+        state.disconnected = true;
+        state.breakpoints.clear();
+        state.active_breakpoints.clear();
+        state.mode = StepMode::Continue;
+        self.changed.notify_all();
+    }
+
+    fn serve(&self, stream: TcpStream) {
+        let mut reader = BufReader::new(stream);
+        while let Ok(Some(request)) = protocol::read_message(&mut reader) {
+            if request["type"] != "request" {
+                continue;
+            }
+            let command = request["command"].as_str().unwrap_or_default();
+            let response = match self.handle_request(command, &request["arguments"]) {
+                Ok(body) => protocol::response(self.next_seq(), &request, body),
+                Err(message) => protocol::error_response(self.next_seq(), &request, &message),
+            };
+            self.send(response);
+            match command {
+                "initialize" => self.send_event("initialized", json!({})),
+                "disconnect" | "terminate" => break,
+                _ => {},
+            }
+        }
+        self.disconnect();
+    }
+
+    fn handle_request(&self, command: &str, arguments: &Value) -> Result<Value, String> {
+        let mut state = self.lock();
+        match command {
+            "initialize" => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsTerminateRequest": true,
+            })),
+            "launch" | "attach" => {
+                if arguments["stopOnEntry"].as_bool().unwrap_or(false) {
+                    state.mode = StepMode::Pause;
+                    state.pause_reason = "entry";
+                }
+                Ok(json!({}))
+            },
+            "setBreakpoints" => {
+                let Some(path) = arguments["source"]["path"].as_str() else {
+                    return Err("missing source path".to_string());
+                };
+                if self.sources.has_no_sources() {
+                    return Err(
+                        "source breakpoints are not supported, no Move sources are available"
+                            .to_string(),
+                    );
+                }
+                let mut locations = vec![];
+                let mut breakpoints = vec![];
+                for breakpoint in arguments["breakpoints"].as_array().into_iter().flatten() {
+                    let line = breakpoint["line"].as_u64().unwrap_or_default() as usize;
+                    let entries = self.sources.line_entries(path.as_ref(), line);
+                    breakpoints.push(
+                        if entries.is_empty() {
+                            json!({
+                                "verified": false,
+                                "line": line,
+                                "message": "no Move code at this line",
+                            })
+                        } else {
+                            json!({ "verified": true, "line": line })
+                        },
+                    );
+                    locations.extend(entries);
+                }
+                state.breakpoints.insert(PathBuf::from(path), locations);
+                state.rebuild_active_breakpoints();
+                Ok(json!({ "breakpoints": breakpoints }))
+            },
+            "setExceptionBreakpoints" => Ok(json!({})),
+            "configurationDone" => {
+                state.configured = true;
+                self.changed.notify_all();
+                Ok(json!({}))
+            },
+            "threads" => Ok(json!({
+                "threads": [{ "id": THREAD_ID, "name": "Move VM" }],
+            })),
+            "stackTrace" => {
+                let stopped = stopped_state(&state)?;
+                let frames = stopped
+                    .frames
+                    .iter()
+                    .enumerate()
+                    .map(|(id, frame)| {
+                        let line = frame.module_id.as_ref().and_then(|module_id| {
+                            self.sources
+                                .source_line(module_id, frame.function_index, frame.pc)
+                        });
+                        match line {
+                            Some(line) => json!({
+                                "id": id,
+                                "name": frame.function_name,
+                                "source": {
+                                    "name": line.path.file_name().map(|name| name.to_string_lossy()),
+                                    "path": line.path,
+                                },
+                                "line": line.line,
+                                "column": line.column,
+                            }),
+                            None if id == 0 => json!({
+                                "id": id,
+                                "name": format!(
+                                    "{} (pc {}: {})",
+                                    frame.function_name, frame.pc, stopped.instruction
+                                ),
+                                "line": 0,
+                                "column": 0,
+                            }),
+                            None => json!({
+                                "id": id,
+                                "name": format!("{} (pc {})", frame.function_name, frame.pc),
+                                "line": 0,
+                                "column": 0,
+                            }),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                Ok(json!({ "stackFrames": frames, "totalFrames": frames.len() }))
+            },
+            "scopes" => {
+                let frame_id = arguments["frameId"].as_u64().unwrap_or_default() as usize;
+                Ok(json!({
+                    "scopes": [
+                        {
+                            "name": "Locals",
+                            "variablesReference": FIRST_FRAME_REFERENCE + frame_id,
+                            "expensive": false,
+                        },
+                        {
+                            "name": "Global Resources",
+                            "variablesReference": RESOURCES_REFERENCE,
+                            "expensive": false,
+                        },
+                    ],
+                }))
+            },
+            "variables" => {
+                let stopped = stopped_state(&state)?;
+                let reference =
+                    arguments["variablesReference"].as_u64().unwrap_or_default() as usize;
+                let variables = if reference == RESOURCES_REFERENCE {
+                    stopped
+                        .resources
+                        .iter()
+                        .map(|(address, struct_tag, value)| {
+                            json!({
+                                "name": format!(
+                                    "{} at {}",
+                                    struct_tag.to_canonical_string(),
+                                    address.to_hex_literal()
+                                ),
+                                "value": value,
+                                "variablesReference": 0,
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                } else {
+                    let Some(frame) = reference
+                        .checked_sub(FIRST_FRAME_REFERENCE)
+                        .and_then(|frame_id| stopped.frames.get(frame_id))
+                    else {
+                        return Err(format!("unknown variables reference {}", reference));
+                    };
+                    frame
+                        .locals
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(idx, value)| {
+                            let name = frame
+                                .module_id
+                                .as_ref()
+                                .and_then(|module_id| {
+                                    self.sources
+                                        .local_name(module_id, frame.function_index, idx)
+                                })
+                                .unwrap_or_else(|| format!("[{}]", idx));
+                            Some(json!({
+                                "name": name,
+                                "value": value.as_ref()?,
+                                "variablesReference": 0,
+                            }))
+                        })
+                        .collect()
+                };
+                Ok(json!({ "variables": variables }))
+            },
+            "continue" | "next" | "stepIn" | "stepOut" => {
+                stopped_state(&state)?;
+                let mode = match command {
+                    "continue" => StepMode::Continue,
+                    "next" => StepMode::StepOver,
+                    "stepIn" => StepMode::StepIn,
+                    _ => StepMode::StepOut,
+                };
+                state.resume(mode, &self.sources);
+                self.changed.notify_all();
+                Ok(json!({ "allThreadsContinued": true }))
+            },
+            "pause" => {
+                state.mode = StepMode::Pause;
+                state.pause_reason = "pause";
+                Ok(json!({}))
+            },
+            "disconnect" | "terminate" => Ok(json!({})),
+            _ => Err(format!("unsupported request `{}`", command)),
+        }
+    }
+}
+
+fn stopped_state(state: &State) -> Result<&DebugState, String> {
+    state
+        .stopped
+        .as_ref()
+        .ok_or_else(|| "execution is not stopped".to_string())
+}
+
+/// A Debug Adapter Protocol server with a connected client.
+///
+/// Once the client finished its configuration, [`DebugServer::debugger`] can be installed in the
+/// VM with `move_vm_runtime::tracing::set_debugger`. Execution then stops at breakpoints set by
+/// the client, and the client can inspect the call stack, locals and global resources, and step
+/// through the code.
+pub struct DebugServer {
+    shared: Arc<Shared>,
+}
+
+impl DebugServer {
+    /// Listens on the given address and waits until a client connected and finished its
+    /// configuration, i.e. set its initial breakpoints.
+    pub fn listen(addr: impl ToSocketAddrs, sources: SourceIndex) -> Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let (stream, _) = listener.accept()?;
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                breakpoints: BTreeMap::new(),
+                active_breakpoints: BTreeMap::new(),
+                mode: StepMode::Continue,
+                step_origin: None,
+                pause_reason: "pause",
+                stop_reason: "pause",
+                stopped: None,
+                resume: false,
+                configured: false,
+                disconnected: false,
+            }),
+            changed: Condvar::new(),
+            writer: Mutex::new(stream.try_clone()?),
+            seq: AtomicI64::new(1),
+            sources,
+        });
+        let serving = shared.clone();
+        thread::spawn(move || serving.serve(stream));
+
+        let mut state = shared.lock();
+        while !state.configured && !state.disconnected {
+            state = shared.changed.wait(state).unwrap();
+        }
+        if state.disconnected {
+            bail!("debug adapter client disconnected before finishing its configuration")
+        }
+        drop(state);
+        Ok(Self { shared })
+    }
+
+    /// Returns a debugger to be installed in the VM which is driven by the client.
+    pub fn debugger(&self) -> Box<dyn Debugger> {
+        Box::new(AdapterDebugger {
+            shared: self.shared.clone(),
+        })
+    }
+
+    /// Shows output in the debug console of the client.
+    pub fn output(&self, output: &str) {
+        self.shared
+            .send_event("output", json!({ "category": "stdout", "output": output }));
+    }
+
+    /// Tells the client that execution finished and closes the connection.
+    pub fn terminate(self, exit_code: i32) {
+        self.shared
+            .send_event("exited", json!({ "exitCode": exit_code }));
+        self.shared.send_event("terminated", json!({}));
+        let state = self.shared.lock();
+        let _ = self
+            .shared
+            .changed
+            .wait_timeout_while(state, DISCONNECT_TIMEOUT, |state| !state.disconnected);
+        let _ = self.shared.writer.lock().unwrap().shutdown(Shutdown::Both);
+    }
+}
+
+struct AdapterDebugger {
+    shared: Arc<Shared>,
+}
+
+impl Debugger for AdapterDebugger {
+    fn should_stop(&mut self, location: &DebugLocation) -> bool {
+        let mut state = self.shared.lock();
+        if state.disconnected {
+            return false;
+        }
+        let breakpoint_hit = location
+            .module_id
+            .and_then(|module_id| state.active_breakpoints.get(module_id))
+            .is_some_and(|offsets| offsets.contains(&(location.function_index, location.pc)));
+        if breakpoint_hit {
+            state.stop_reason = "breakpoint";
+            return true;
+        }
+        let mode = state.mode;
+        let stop = match (mode, &state.step_origin) {
+            (StepMode::Continue, _) => false,
+            (StepMode::Pause, _) | (_, None) => true,
+            (StepMode::StepOut, Some(origin)) => location.depth < origin.depth,
+            (StepMode::StepIn | StepMode::StepOver, Some(origin)) => {
+                let line = location.module_id.and_then(|module_id| {
+                    self.shared
+                        .sources
+                        .source_line(module_id, location.function_index, location.pc)
+                });
+                let other_line = line.is_none() || line != origin.line;
+                if mode == StepMode::StepIn {
+                    location.depth != origin.depth || other_line
+                } else {
+                    location.depth < origin.depth || (location.depth == origin.depth && other_line)
+                }
+            },
+        };
+        if stop {
+            state.stop_reason = if mode == StepMode::Pause {
+                state.pause_reason
+            } else {
+                "step"
+            };
+        }
+        stop
+    }
+
+    fn stop(&mut self, stopped: DebugState) {
+        let mut state = self.shared.lock();
+        if state.disconnected {
+            return;
+        }
+        state.stopped = Some(stopped);
+        state.resume = false;
+        self.shared.send_event(
+            "stopped",
+            json!({
+                "reason": state.stop_reason,
+                "threadId": THREAD_ID,
+                "allThreadsStopped": true,
+            }),
+        );
+        while !state.resume && !state.disconnected {
+            state = self.shared.changed.wait(state).unwrap();
+        }
+        state.stopped = None;
+    }
+}
@@ -0,0 +1,289 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Scripted Debug Adapter Protocol session: a client sets breakpoints in a Move source file and
+//! steps through the execution of the compiled module, which is simulated by driving the
+//! debugger the same way the VM does.
+
+use legacy_move_compiler::compiled_unit::CompiledUnitEnum;
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{Bytecode, CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_command_line_common::files::FileHash;
+use move_compiler_v2::Experiment;
+use move_debug_adapter::{DebugServer, SourceIndex};
+use move_vm_runtime::tracing::{DebugFrame, DebugLocation, DebugState, Debugger};
+use serde_json::{json, Value};
+use std::{
+    collections::VecDeque,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::Path,
+    thread,
+    time::Duration,
+};
+
+const SOURCE: &str = "module 0x42::m {
+    fun helper(x: u64): u64 {
+        let y = x + 1;
+        y * 2
+    }
+
+    public fun main(): u64 {
+        let a = 1;
+        let b = helper(a);
+        b + 3
+    }
+}
+";
+
+/// Compiles `SOURCE` into a module, and indexes its source map and source file.
+fn compile(path: &Path) -> (CompiledModule, SourceIndex) {
+    std::fs::write(path, SOURCE).unwrap();
+    let options = move_compiler_v2::Options {
+        sources: vec![path.display().to_string()],
+        ..Default::default()
+    }
+    .set_experiment(Experiment::OPTIMIZE, false);
+    let (_, units) = move_compiler_v2::run_move_compiler_to_stderr(options).unwrap();
+    let Some(CompiledUnitEnum::Module(unit)) = units.into_iter().next() else {
+        panic!("expected a module")
+    };
+    let mut sources = SourceIndex::new();
+    sources.add_module(
+        unit.named_module.module.clone(),
+        unit.named_module.source_map,
+    );
+    sources.add_file(FileHash::new(SOURCE), path, SOURCE);
+    (unit.named_module.module, sources)
+}
+
+fn function_index(module: &CompiledModule, name: &str) -> FunctionDefinitionIndex {
+    let idx = module
+        .function_defs()
+        .iter()
+        .position(|def| {
+            module
+                .identifier_at(module.function_handle_at(def.function).name)
+                .as_str()
+                == name
+        })
+        .unwrap();
+    FunctionDefinitionIndex(idx as u16)
+}
+
+/// Executes `function` like the VM does, consulting the debugger before each instruction.
+/// Only straight-line code calling functions of the same module is supported.
+fn execute(
+    debugger: &mut dyn Debugger,
+    module: &CompiledModule,
+    function: FunctionDefinitionIndex,
+    frames: &mut Vec<DebugFrame>,
+) {
+    let module_id = module.self_id();
+    let def = module.function_def_at(function);
+    let name = module.identifier_at(module.function_handle_at(def.function).name);
+    frames.insert(0, DebugFrame {
+        module_id: Some(module_id.clone()),
+        function_name: format!("{}::{}", module_id.short_str_lossless(), name),
+        function_index: function,
+        pc: 0,
+        locals: vec![Some("1".to_string())],
+    });
+    for (pc, instruction) in def.code.as_ref().unwrap().code.iter().enumerate() {
+        let pc = pc as CodeOffset;
+        frames[0].pc = pc;
+        let location = DebugLocation {
+            module_id: Some(&module_id),
+            function_index: function,
+            pc,
+            depth: frames.len(),
+        };
+        if debugger.should_stop(&location) {
+            debugger.stop(DebugState {
+                frames: frames.clone(),
+                instruction: format!("{:?}", instruction),
+                resources: vec![],
+            });
+        }
+        if let Bytecode::Call(handle) = instruction {
+            let callee = module
+                .function_defs()
+                .iter()
+                .position(|def| def.function == *handle)
+                .unwrap();
+            execute(
+                debugger,
+                module,
+                FunctionDefinitionIndex(callee as u16),
+                frames,
+            );
+        }
+    }
+    frames.remove(0);
+}
+
+/// A Debug Adapter Protocol client, buffering events received while waiting for responses.
+struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    seq: i64,
+    events: VecDeque<Value>,
+}
+
+impl Client {
+    fn connect(addr: &str) -> Self {
+        for _ in 0..100 {
+            if let Ok(stream) = TcpStream::connect(addr) {
+                return Self {
+                    reader: BufReader::new(stream.try_clone().unwrap()),
+                    writer: stream,
+                    seq: 0,
+                    events: VecDeque::new(),
+                };
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        panic!("debug adapter is not listening on {}", addr)
+    }
+
+    fn read(&mut self) -> Value {
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            assert_ne!(
+                self.reader.read_line(&mut header).unwrap(),
+                0,
+                "connection closed"
+            );
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(length) = header.strip_prefix("Content-Length:") {
+                content_length = length.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; content_length];
+        self.reader.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    /// Sends a request and returns the body of its response.
+    fn request(&mut self, command: &str, arguments: Value) -> Value {
+        self.seq += 1;
+        let body = json!({
+            "seq": self.seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        })
+        .to_string();
+        write!(
+            self.writer,
+            "Content-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+        loop {
+            let message = self.read();
+            if message["type"] == "event" {
+                self.events.push_back(message);
+            } else if message["request_seq"] == self.seq {
+                assert_eq!(message["success"], true, "{} failed: {}", command, message);
+                return message["body"].clone();
+            }
+        }
+    }
+
+    /// Waits for the next event, which must have the given name, and returns its body.
+    fn event(&mut self, name: &str) -> Value {
+        let event = self.events.pop_front().unwrap_or_else(|| self.read());
+        assert_eq!(event["event"], name, "unexpected event {}", event);
+        event["body"].clone()
+    }
+
+    /// Waits until execution stopped for the given reason and returns the stack frames.
+    fn stopped(&mut self, reason: &str) -> Vec<Value> {
+        assert_eq!(self.event("stopped")["reason"], reason);
+        let body = self.request("stackTrace", json!({ "threadId": 1 }));
+        body["stackFrames"].as_array().unwrap().clone()
+    }
+}
+
+fn unused_addr() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().to_string()
+}
+
+#[test]
+fn breakpoints_and_stepping() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("m.move");
+    let (module, sources) = compile(&path);
+    let path = path.canonicalize().unwrap();
+    let addr = unused_addr();
+
+    let listen_addr = addr.clone();
+    let vm = thread::spawn(move || {
+        let server = DebugServer::listen(listen_addr.as_str(), sources).unwrap();
+        let main = function_index(&module, "main");
+        execute(&mut *server.debugger(), &module, main, &mut vec![]);
+        server.terminate(0);
+    });
+
+    let mut client = Client::connect(&addr);
+    client.request("initialize", json!({ "adapterID": "move" }));
+    client.event("initialized");
+    client.request("launch", json!({}));
+    let breakpoints = client.request(
+        "setBreakpoints",
+        json!({
+            "source": { "path": path },
+            "breakpoints": [{ "line": 9 }, { "line": 6 }],
+        }),
+    );
+    assert_eq!(breakpoints["breakpoints"][0]["verified"], true);
+    assert_eq!(breakpoints["breakpoints"][1]["verified"], false);
+    client.request("configurationDone", json!({}));
+
+    // Execution stops at the breakpoint in `main`, mapped back to the source line.
+    let frames = client.stopped("breakpoint");
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0]["name"], "0x42::m::main");
+    assert_eq!(frames[0]["source"]["path"], json!(path));
+    assert_eq!(frames[0]["line"], 9);
+
+    // Stepping in enters `helper`, whose parameter is shown under its source name.
+    client.request("stepIn", json!({ "threadId": 1 }));
+    let frames = client.stopped("step");
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0]["name"], "0x42::m::helper");
+    assert_eq!(frames[0]["line"], 3);
+    assert_eq!(frames[1]["line"], 9);
+    let variables = client.request("variables", json!({ "variablesReference": 2 }));
+    assert_eq!(variables["variables"][0]["name"], "x");
+    assert_eq!(variables["variables"][0]["value"], "1");
+
+    // Stepping over moves to the next line of `helper`.
+    client.request("next", json!({ "threadId": 1 }));
+    let frames = client.stopped("step");
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0]["line"], 4);
+
+    // Stepping out returns to `main`.
+    client.request("stepOut", json!({ "threadId": 1 }));
+    let frames = client.stopped("step");
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0]["name"], "0x42::m::main");
+
+    // No further breakpoints are hit until execution terminates.
+    client.request("continue", json!({ "threadId": 1 }));
+    assert_eq!(client.event("exited")["exitCode"], 0);
+    client.event("terminated");
+    client.request("disconnect", json!({}));
+    vm.join().unwrap();
+}
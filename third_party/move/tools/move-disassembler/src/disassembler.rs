@@ -29,7 +29,7 @@ use move_core_types::{
 };
 use move_coverage::coverage_map::{ExecCoverageMap, FunctionCoverage};
 use move_ir_types::location::Loc;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Holds the various options that we support while disassembling code.
 #[derive(Debug, Default, Parser)]
@@ -1428,6 +1428,55 @@ impl<'a> Disassembler<'a> {
         ))
     }
 
+    /// Disassembles the function definition at the given index of a module.
+    fn disassemble_module_function_def(&self, def_index: usize) -> Result<String> {
+        let function_definition_index = FunctionDefinitionIndex(def_index as TableIndex);
+        let function_definition = self.get_function_def(function_definition_index)?;
+        let function_handle = self
+            .source_mapper
+            .bytecode
+            .function_handle_at(function_definition.function);
+        self.disassemble_function_def(
+            def_index,
+            self.source_mapper
+                .source_map
+                .get_function_source_map(function_definition_index)?,
+            Some((function_definition, function_handle)),
+            self.source_mapper
+                .bytecode
+                .identifier_at(function_handle.name),
+            &function_handle.type_parameters,
+            function_handle.parameters,
+            function_definition.code.as_ref(),
+        )
+    }
+
+    /// Disassembles every function defined in a module separately, keyed by function name. Returns
+    /// an empty map for scripts.
+    pub fn disassemble_function_defs(&self) -> Result<BTreeMap<String, String>> {
+        let num_function_defs = match self.source_mapper.bytecode {
+            BinaryIndexedView::Script(_) => 0,
+            BinaryIndexedView::Module(module) => module.function_defs.len(),
+        };
+        (0..num_function_defs)
+            .map(|i| {
+                let function_definition =
+                    self.get_function_def(FunctionDefinitionIndex(i as TableIndex))?;
+                let name = self
+                    .source_mapper
+                    .bytecode
+                    .identifier_at(
+                        self.source_mapper
+                            .bytecode
+                            .function_handle_at(function_definition.function)
+                            .name,
+                    )
+                    .to_string();
+                Ok((name, self.disassemble_module_function_def(i)?))
+            })
+            .collect()
+    }
+
     // The struct defs will filter out the structs that we print to only be the ones that are
     // defined in the module in question.
     pub fn disassemble_struct_def(&self, struct_def_idx: StructDefinitionIndex) -> Result<String> {
@@ -1554,27 +1603,7 @@ impl<'a> Disassembler<'a> {
                 )?]
             },
             BinaryIndexedView::Module(module) => (0..module.function_defs.len())
-                .map(|i| {
-                    let function_definition_index = FunctionDefinitionIndex(i as TableIndex);
-                    let function_definition = self.get_function_def(function_definition_index)?;
-                    let function_handle = self
-                        .source_mapper
-                        .bytecode
-                        .function_handle_at(function_definition.function);
-                    self.disassemble_function_def(
-                        i,
-                        self.source_mapper
-                            .source_map
-                            .get_function_source_map(function_definition_index)?,
-                        Some((function_definition, function_handle)),
-                        self.source_mapper
-                            .bytecode
-                            .identifier_at(function_handle.name),
-                        &function_handle.type_parameters,
-                        function_handle.parameters,
-                        function_definition.code.as_ref(),
-                    )
-                })
+                .map(|i| self.disassemble_module_function_def(i))
                 .collect::<Result<Vec<String>>>()?,
         };
 
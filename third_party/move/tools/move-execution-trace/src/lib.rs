@@ -0,0 +1,274 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A versioned, structured format for traces of Move execution: the tree of call frames with the
+//! bytecode instructions executed in each and the gas they were charged, together with the
+//! resources read and written and the events emitted.
+//!
+//! Traces are built from the instructions recorded by the VM with
+//! `move_vm_runtime::tracing::start_trace_recording`, and can be rendered against Move sources
+//! with [`render`].
+
+mod render;
+
+use anyhow::{bail, Result};
+use move_core_types::language_storage::ModuleId;
+use move_vm_runtime::tracing::TracedInstruction;
+pub use render::{render, RenderOptions};
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, path::Path};
+
+/// The version of the trace format. Bumped on every incompatible change.
+pub const TRACE_FORMAT_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionTrace {
+    pub version: u32,
+    /// The top-level calls of the execution, e.g. the prologue, the entry function and the
+    /// epilogue of a transaction.
+    pub calls: Vec<TraceFrame>,
+    pub resources_read: Vec<TraceResource>,
+    pub resources_written: Vec<TraceWrite>,
+    pub events: Vec<TraceEvent>,
+    /// Whether the recording stopped before the end of the execution because it reached the
+    /// maximum number of instructions.
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceFrame {
+    /// The module defining the function, or `None` for scripts.
+    pub module_id: Option<ModuleId>,
+    /// The fully qualified name of the function, e.g. `0x1::coin::transfer`.
+    pub function: String,
+    pub function_index: u16,
+    /// The internal gas charged for the instructions of this frame and all its callees.
+    pub gas: u64,
+    pub steps: Vec<TraceStep>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TraceStep {
+    Instruction {
+        pc: u16,
+        instruction: String,
+        /// The internal gas charged for the instruction. Gas charged for a call instruction
+        /// includes the cost of native functions.
+        gas: u64,
+    },
+    Call(TraceFrame),
+}
+
+/// A resource, identified by its canonical type, e.g. `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceResource {
+    pub address: String,
+    pub resource: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WriteKind {
+    Create,
+    Modify,
+    Delete,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceWrite {
+    #[serde(flatten)]
+    pub resource: TraceResource,
+    pub kind: WriteKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceEvent {
+    pub type_tag: String,
+    /// The BCS-encoded event data, as hex.
+    pub data: String,
+}
+
+impl ExecutionTrace {
+    /// Builds the tree of call frames from the instructions recorded by the VM. Resources and
+    /// events are not known to the VM and are left empty.
+    pub fn from_instructions(instructions: &[TracedInstruction]) -> Self {
+        let mut calls = vec![];
+        let mut stack: Vec<TraceFrame> = vec![];
+        for (idx, instruction) in instructions.iter().enumerate() {
+            let previous = idx.checked_sub(1).map(|prev| &instructions[prev]);
+            match previous {
+                Some(previous) if !starts_top_level_call(previous, instruction) => {
+                    if instruction.depth > previous.depth {
+                        stack.push(TraceFrame::new(instruction));
+                    }
+                    while stack.len() > instruction.depth {
+                        close_frame(&mut stack, &mut calls);
+                    }
+                },
+                _ => {
+                    while !stack.is_empty() {
+                        close_frame(&mut stack, &mut calls);
+                    }
+                    stack.push(TraceFrame::new(instruction));
+                },
+            }
+            // The gas charged for an instruction is only known from the gas remaining before
+            // the next instruction of the same execution.
+            let gas = instructions
+                .get(idx + 1)
+                .filter(|next| !starts_top_level_call(instruction, next))
+                .map_or(0, |next| {
+                    instruction.gas_remaining.saturating_sub(next.gas_remaining)
+                });
+            let frame = stack.last_mut().expect("frame is pushed above");
+            frame.gas += gas;
+            frame.steps.push(TraceStep::Instruction {
+                pc: instruction.pc,
+                instruction: instruction.instruction.clone(),
+                gas,
+            });
+        }
+        while !stack.is_empty() {
+            close_frame(&mut stack, &mut calls);
+        }
+        Self {
+            version: TRACE_FORMAT_VERSION,
+            calls,
+            ..Self::default()
+        }
+    }
+
+    /// The internal gas charged for all instructions of the trace.
+    pub fn total_gas(&self) -> u64 {
+        self.calls.iter().map(|frame| frame.gas).sum()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_slice(&std::fs::read(path)?)?;
+        match value["version"].as_u64() {
+            Some(version) if version == TRACE_FORMAT_VERSION as u64 => {
+                Ok(serde_json::from_value(value)?)
+            },
+            Some(version) => bail!(
+                "unsupported trace format version {} (expected {})",
+                version,
+                TRACE_FORMAT_VERSION
+            ),
+            None => bail!("not an execution trace: missing format version"),
+        }
+    }
+}
+
+impl TraceFrame {
+    fn new(instruction: &TracedInstruction) -> Self {
+        Self {
+            module_id: instruction.module_id.clone(),
+            function: instruction.function_name.clone(),
+            function_index: instruction.function_index.0,
+            gas: 0,
+            steps: vec![],
+        }
+    }
+}
+
+/// Pops the innermost frame and adds it to its caller, or to the top-level calls.
+fn close_frame(stack: &mut Vec<TraceFrame>, calls: &mut Vec<TraceFrame>) {
+    let frame = stack.pop().expect("stack is not empty");
+    match stack.last_mut() {
+        Some(caller) => {
+            caller.gas += frame.gas;
+            caller.steps.push(TraceStep::Call(frame));
+        },
+        None => calls.push(frame),
+    }
+}
+
+/// Returns whether `next` does not continue the execution of `previous`, but starts a new
+/// top-level call, i.e. it is neither the next instruction in the same frame, nor the first
+/// instruction of a callee, nor the instruction after a return.
+fn starts_top_level_call(previous: &TracedInstruction, next: &TracedInstruction) -> bool {
+    let is_return = previous.instruction.starts_with("Ret");
+    let is_abort = previous.instruction.starts_with("Abort");
+    match next.depth.cmp(&previous.depth) {
+        Ordering::Greater => next.depth != previous.depth + 1,
+        Ordering::Equal => is_return || is_abort,
+        Ordering::Less => !is_return || next.depth + 1 != previous.depth,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_binary_format::file_format::FunctionDefinitionIndex;
+    use move_core_types::{account_address::AccountAddress, ident_str};
+
+    fn instruction(
+        function: &str,
+        depth: usize,
+        pc: u16,
+        instruction: &str,
+        gas_remaining: u64,
+    ) -> TracedInstruction {
+        TracedInstruction {
+            module_id: Some(ModuleId::new(
+                AccountAddress::ONE,
+                ident_str!("m").to_owned(),
+            )),
+            function_name: format!("0x1::m::{}", function),
+            function_index: FunctionDefinitionIndex(0),
+            pc,
+            depth,
+            instruction: instruction.to_string(),
+            gas_remaining,
+        }
+    }
+
+    fn function_names(frame: &TraceFrame) -> Vec<String> {
+        let mut names = vec![frame.function.clone()];
+        for step in &frame.steps {
+            if let TraceStep::Call(callee) = step {
+                names.extend(function_names(callee));
+            }
+        }
+        names
+    }
+
+    #[test]
+    fn builds_call_tree_with_gas() {
+        let trace = ExecutionTrace::from_instructions(&[
+            instruction("f", 1, 0, "Call(1)", 100),
+            instruction("g", 2, 0, "LdU64(1)", 90),
+            instruction("g", 2, 1, "Ret", 88),
+            instruction("f", 1, 1, "Ret", 85),
+            instruction("h", 1, 0, "Ret", 50),
+        ]);
+        assert_eq!(trace.version, TRACE_FORMAT_VERSION);
+        assert_eq!(trace.calls.len(), 2);
+        let f = &trace.calls[0];
+        assert_eq!(function_names(f), vec!["0x1::m::f", "0x1::m::g"]);
+        // 10 for the call, 2 + 3 for the callee, and nothing known for the final return.
+        assert_eq!(f.gas, 15);
+        assert_eq!(f.steps.len(), 3);
+        assert!(matches!(&f.steps[1], TraceStep::Call(g) if g.gas == 5));
+        assert_eq!(trace.calls[1].function, "0x1::m::h");
+        assert_eq!(trace.total_gas(), 15);
+    }
+
+    #[test]
+    fn abort_ends_execution() {
+        let trace = ExecutionTrace::from_instructions(&[
+            instruction("f", 1, 0, "Call(1)", 100),
+            instruction("g", 2, 0, "Abort", 90),
+            instruction("epilogue", 1, 0, "Ret", 80),
+        ]);
+        assert_eq!(trace.calls.len(), 2);
+        assert_eq!(trace.calls[1].function, "0x1::m::epilogue");
+    }
+}
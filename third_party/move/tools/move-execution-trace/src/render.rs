@@ -0,0 +1,301 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Rendering of execution traces against Move sources.
+
+use crate::{ExecutionTrace, TraceFrame, TraceStep};
+use move_binary_format::file_format::FunctionDefinitionIndex;
+use move_bytecode_source_map::source_index::{SourceIndex, SourceLine};
+use move_core_types::language_storage::ModuleId;
+use std::fmt::Write;
+
+const INDENT: &str = "  ";
+
+#[derive(Clone, Debug, Default)]
+pub struct RenderOptions {
+    /// Only show frames of functions in these modules, e.g. `0x1::coin` or `coin`. Calls made
+    /// from hidden frames are still shown. All frames are shown if empty.
+    pub modules: Vec<String>,
+    /// Show calls into functions of these modules as a single line.
+    pub fold: Vec<String>,
+    /// Show the bytecode instructions executed for each source line.
+    pub bytecode: bool,
+}
+
+/// Renders a trace as a call tree, with the source lines executed in each frame and the gas
+/// charged for them, followed by the resources and events of the execution.
+pub fn render(trace: &ExecutionTrace, sources: &SourceIndex, options: &RenderOptions) -> String {
+    let mut out = String::new();
+    let renderer = Renderer { sources, options };
+    for frame in &trace.calls {
+        renderer.render_frame(&mut out, frame, 0);
+    }
+    if trace.truncated {
+        writeln!(
+            out,
+            "(trace truncated, later instructions were not recorded)"
+        )
+        .unwrap();
+    }
+    writeln!(out, "Total gas: {}", trace.total_gas()).unwrap();
+    if !trace.resources_read.is_empty() {
+        writeln!(out, "\nResources read:").unwrap();
+        for resource in &trace.resources_read {
+            writeln!(
+                out,
+                "{}{} at {}",
+                INDENT, resource.resource, resource.address
+            )
+            .unwrap();
+        }
+    }
+    if !trace.resources_written.is_empty() {
+        writeln!(out, "\nResources written:").unwrap();
+        for write in &trace.resources_written {
+            writeln!(
+                out,
+                "{}{:?} {} at {}",
+                INDENT, write.kind, write.resource.resource, write.resource.address
+            )
+            .unwrap();
+        }
+    }
+    if !trace.events.is_empty() {
+        writeln!(out, "\nEvents:").unwrap();
+        for event in &trace.events {
+            writeln!(out, "{}{} 0x{}", INDENT, event.type_tag, event.data).unwrap();
+        }
+    }
+    out
+}
+
+struct Renderer<'a> {
+    sources: &'a SourceIndex,
+    options: &'a RenderOptions,
+}
+
+/// Consecutive instructions of a frame which belong to the same source line.
+struct LineGroup<'a> {
+    source_line: Option<SourceLine>,
+    gas: u64,
+    instructions: Vec<(u16, &'a str, u64)>,
+}
+
+impl<'a> Renderer<'a> {
+    fn render_frame(&self, out: &mut String, frame: &'a TraceFrame, depth: usize) {
+        let shown = self.options.modules.is_empty()
+            || matches_any(frame.module_id.as_ref(), &self.options.modules);
+        if !shown {
+            // Hidden frames are skipped, but calls made from them may still be of interest.
+            for step in &frame.steps {
+                if let TraceStep::Call(callee) = step {
+                    self.render_frame(out, callee, depth);
+                }
+            }
+            return;
+        }
+        let indent = INDENT.repeat(depth);
+        if matches_any(frame.module_id.as_ref(), &self.options.fold) {
+            writeln!(
+                out,
+                "{}{} [folded: {} instructions, gas {}]",
+                indent,
+                frame.function,
+                count_instructions(frame),
+                frame.gas
+            )
+            .unwrap();
+            return;
+        }
+        writeln!(out, "{}{} [gas {}]", indent, frame.function, frame.gas).unwrap();
+
+        let mut group: Option<LineGroup> = None;
+        for step in &frame.steps {
+            match step {
+                TraceStep::Instruction {
+                    pc,
+                    instruction,
+                    gas,
+                } => {
+                    let source_line = frame.module_id.as_ref().and_then(|module_id| {
+                        self.sources.source_line(
+                            module_id,
+                            FunctionDefinitionIndex(frame.function_index),
+                            *pc,
+                        )
+                    });
+                    let same_line = group.as_ref().is_some_and(|group| {
+                        group.source_line.is_some() && group.source_line == source_line
+                    });
+                    if !same_line {
+                        if let Some(group) = group.take() {
+                            self.render_group(out, &group, depth + 1);
+                        }
+                    }
+                    let group = group.get_or_insert_with(|| LineGroup {
+                        source_line,
+                        gas: 0,
+                        instructions: vec![],
+                    });
+                    group.gas += gas;
+                    group.instructions.push((*pc, instruction.as_str(), *gas));
+                },
+                TraceStep::Call(callee) => {
+                    if let Some(group) = group.take() {
+                        self.render_group(out, &group, depth + 1);
+                    }
+                    self.render_frame(out, callee, depth + 1);
+                },
+            }
+        }
+        if let Some(group) = group.take() {
+            self.render_group(out, &group, depth + 1);
+        }
+    }
+
+    fn render_group(&self, out: &mut String, group: &LineGroup, depth: usize) {
+        let indent = INDENT.repeat(depth);
+        let show_bytecode = match &group.source_line {
+            Some(source_line) => {
+                let text = self.sources.line_text(source_line).unwrap_or_default();
+                writeln!(
+                    out,
+                    "{}{}:{}: {} [gas {}]",
+                    indent,
+                    source_line.path.display(),
+                    source_line.line,
+                    text.trim(),
+                    group.gas
+                )
+                .unwrap();
+                self.options.bytecode
+            },
+            None => true,
+        };
+        if show_bytecode {
+            for (pc, instruction, gas) in &group.instructions {
+                writeln!(
+                    out,
+                    "{}{}{:>4}: {} [gas {}]",
+                    indent, INDENT, pc, instruction, gas
+                )
+                .unwrap();
+            }
+        }
+    }
+}
+
+/// Returns whether a module matches one of the patterns, which are either fully qualified
+/// (`0x1::coin`) or just the module name (`coin`).
+fn matches_any(module_id: Option<&ModuleId>, patterns: &[String]) -> bool {
+    let Some(module_id) = module_id else {
+        return false;
+    };
+    let qualified = module_id.short_str_lossless();
+    patterns
+        .iter()
+        .any(|pattern| *pattern == qualified || pattern == module_id.name().as_str())
+}
+
+fn count_instructions(frame: &TraceFrame) -> usize {
+    frame
+        .steps
+        .iter()
+        .map(|step| match step {
+            TraceStep::Instruction { .. } => 1,
+            TraceStep::Call(callee) => count_instructions(callee),
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TRACE_FORMAT_VERSION;
+    use move_core_types::{account_address::AccountAddress, identifier::Identifier};
+
+    fn frame(module: &str, function: &str, steps: Vec<TraceStep>) -> TraceFrame {
+        let gas = steps
+            .iter()
+            .map(|step| match step {
+                TraceStep::Instruction { gas, .. } => *gas,
+                TraceStep::Call(callee) => callee.gas,
+            })
+            .sum();
+        TraceFrame {
+            module_id: Some(ModuleId::new(
+                AccountAddress::ONE,
+                Identifier::new(module).unwrap(),
+            )),
+            function: format!("0x1::{}::{}", module, function),
+            function_index: 0,
+            gas,
+            steps,
+        }
+    }
+
+    fn instruction(pc: u16, gas: u64) -> TraceStep {
+        TraceStep::Instruction {
+            pc,
+            instruction: "Nop".to_string(),
+            gas,
+        }
+    }
+
+    fn trace() -> ExecutionTrace {
+        let callee = frame("coin", "value", vec![instruction(0, 2), instruction(1, 3)]);
+        ExecutionTrace {
+            version: TRACE_FORMAT_VERSION,
+            calls: vec![frame("account", "transfer", vec![
+                instruction(0, 1),
+                TraceStep::Call(callee),
+            ])],
+            ..ExecutionTrace::default()
+        }
+    }
+
+    #[test]
+    fn renders_bytecode_without_sources() {
+        let out = render(&trace(), &SourceIndex::new(), &RenderOptions::default());
+        let expected = [
+            "0x1::account::transfer [gas 6]",
+            "       0: Nop [gas 1]",
+            "  0x1::coin::value [gas 5]",
+            "         0: Nop [gas 2]",
+            "         1: Nop [gas 3]",
+            "Total gas: 6",
+        ];
+        assert_eq!(out.lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn filters_and_folds_modules() {
+        let options = RenderOptions {
+            modules: vec!["0x1::coin".to_string()],
+            ..RenderOptions::default()
+        };
+        let out = render(&trace(), &SourceIndex::new(), &options);
+        assert!(!out.contains("transfer"));
+        assert!(out.starts_with("0x1::coin::value [gas 5]\n"));
+
+        let options = RenderOptions {
+            fold: vec!["coin".to_string()],
+            ..RenderOptions::default()
+        };
+        let out = render(&trace(), &SourceIndex::new(), &options);
+        assert!(out.contains("  0x1::coin::value [folded: 2 instructions, gas 5]\n"));
+        assert!(!out.contains("[gas 3]"));
+    }
+
+    #[test]
+    fn notes_truncated_traces() {
+        let trace = ExecutionTrace {
+            truncated: true,
+            ..trace()
+        };
+        let out = render(&trace, &SourceIndex::new(), &RenderOptions::default());
+        assert!(
+            out.contains("(trace truncated, later instructions were not recorded)\nTotal gas: 6")
+        );
+    }
+}
@@ -1,12 +1,80 @@
 // Copyright (c) Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+//! The Move linter, and the plugin API for lint sets defined in external crates.
+//!
+//! An external lint set implements [`ExternalChecks`] on top of the [`ExpChecker`] and
+//! [`StacklessBytecodeChecker`] traits re-exported here, and is registered under a name
+//! with [`register_lint_set`]. Since Rust has no stable ABI for dynamic loading, external
+//! lint sets are linked into a custom build of the CLI, which registers them at startup:
+//!
+//! ```ignore
+//! fn main() {
+//!     move_linter::register_lint_set("my_team", my_team_lints::make);
+//!     // ... run the CLI as usual
+//! }
+//! ```
+//!
+//! Registered lint sets are enabled with `aptos move lint --lint-sets my_team`, or in the
+//! `[lint]` section of the package manifest:
+//!
+//! ```toml
+//! [lint]
+//! sets = ["my_team"]
+//! levels = { needless_bool = "error", my_team_lint = "allow" }
+//! ```
+
 mod model_ast_lints;
 mod stackless_bytecode_lints;
 mod utils;
 
-use move_compiler_v2::external_checks::{ExpChecker, ExternalChecks, StacklessBytecodeChecker};
-use std::{collections::BTreeMap, sync::Arc};
+pub use move_compiler_v2::external_checks::{
+    known_checker_names, ExpChecker, ExternalChecks, LintLevel, StacklessBytecodeChecker,
+};
+pub use move_model;
+pub use move_stackless_bytecode;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+};
+
+/// Creates an instance of an external lint set, given the lint configuration
+/// (the same configuration that is passed to [`MoveLintChecks::make`]).
+pub type LintSetFactory = fn(&BTreeMap<String, String>) -> Arc<dyn ExternalChecks>;
+
+/// Registry of the external lint sets, by name.
+static LINT_SETS: RwLock<BTreeMap<String, LintSetFactory>> = RwLock::new(BTreeMap::new());
+
+/// Register an external lint set under `name`.
+/// Will panic if a lint set with the same name is already registered.
+pub fn register_lint_set(name: &str, factory: LintSetFactory) {
+    let mut lint_sets = LINT_SETS.write().expect("lint set registry is poisoned");
+    if lint_sets.insert(name.to_string(), factory).is_some() {
+        panic!("lint set `{}` is already registered", name);
+    }
+}
+
+/// Names of all the registered external lint sets.
+pub fn registered_lint_sets() -> Vec<String> {
+    LINT_SETS
+        .read()
+        .expect("lint set registry is poisoned")
+        .keys()
+        .cloned()
+        .collect()
+}
+
+/// Make an instance of the external lint set registered under `name`, if any.
+pub fn make_lint_set(
+    name: &str,
+    config: &BTreeMap<String, String>,
+) -> Option<Arc<dyn ExternalChecks>> {
+    let factory = *LINT_SETS
+        .read()
+        .expect("lint set registry is poisoned")
+        .get(name)?;
+    Some(factory(config))
+}
 
 /// Holds collection of lint checks for Move.
 pub struct MoveLintChecks {
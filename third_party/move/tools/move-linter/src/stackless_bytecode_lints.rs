@@ -6,7 +6,11 @@
 //! The lint checks also assume that all the correctness checks have already been performed.
 
 mod avoid_copy_on_identity_comparison;
+mod closure_reentrancy;
 mod needless_mutable_reference;
+mod unchecked_amount_arithmetic;
+mod unchecked_object_transfer;
+mod unchecked_signer;
 
 use move_compiler_v2::external_checks::StacklessBytecodeChecker;
 use std::collections::BTreeMap;
@@ -16,13 +20,22 @@ pub fn get_default_linter_pipeline(
     config: &BTreeMap<String, String>,
 ) -> Vec<Box<dyn StacklessBytecodeChecker>> {
     // Start with the default set of checks.
-    let checks: Vec<Box<dyn StacklessBytecodeChecker>> = vec![
+    let mut checks: Vec<Box<dyn StacklessBytecodeChecker>> = vec![
         Box::new(avoid_copy_on_identity_comparison::AvoidCopyOnIdentityComparison {}),
         Box::new(needless_mutable_reference::NeedlessMutableReference {}),
     ];
     let checks_category = config.get("checks").map_or("default", |s| s.as_str());
     if checks_category == "strict" || checks_category == "experimental" {
         // Push strict checks to `checks`.
+        // Security checks are heuristic, and may produce false positives.
+        checks.push(Box::new(closure_reentrancy::ClosureReentrancy {}));
+        checks.push(Box::new(
+            unchecked_amount_arithmetic::UncheckedAmountArithmetic {},
+        ));
+        checks.push(Box::new(
+            unchecked_object_transfer::UncheckedObjectTransfer {},
+        ));
+        checks.push(Box::new(unchecked_signer::UncheckedSigner {}));
     }
     if checks_category == "experimental" {
         // Push experimental checks to `checks`.
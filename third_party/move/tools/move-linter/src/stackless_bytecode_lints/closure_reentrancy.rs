@@ -0,0 +1,127 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements a stackless-bytecode linter that checks for calls to closures or
+//! dynamically dispatched functions while a global resource is mutably borrowed.
+//! The callee is not known statically, and may attempt to re-enter the current module,
+//! either aborting on the borrowed resource or observing it in an inconsistent state.
+//! For example:
+//! ```move
+//! fun withdraw(addr: address, amount: u64, on_withdraw: |u64|) acquires Vault {
+//!     let vault = borrow_global_mut<Vault>(addr);
+//!     on_withdraw(amount); // <- `vault` is still borrowed here
+//!     vault.balance -= amount;
+//! }
+//! ```
+//! Live variable analysis is used to determine whether the borrow is still held at the call.
+
+use crate::utils::{derived_temps, is_framework_module};
+use move_binary_format::file_format::CodeOffset;
+use move_compiler_v2::{
+    external_checks::StacklessBytecodeChecker,
+    pipeline::livevar_analysis_processor::LiveVarAnnotation,
+};
+use move_model::{ast::TempIndex, model::GlobalEnv};
+use move_stackless_bytecode::{
+    function_target::FunctionTarget,
+    stackless_bytecode::{AttrId, Bytecode, Operation},
+};
+use std::collections::BTreeSet;
+
+/// Framework module whose functions dispatch to user-defined hooks.
+const DISPATCH_MODULE: &str = "dispatchable_fungible_asset";
+
+pub struct ClosureReentrancy {}
+
+impl StacklessBytecodeChecker for ClosureReentrancy {
+    fn get_name(&self) -> String {
+        "closure_reentrancy".to_string()
+    }
+
+    fn check(&self, target: &FunctionTarget) {
+        let code = target.get_bytecode();
+        let env = target.global_env();
+        if !code
+            .iter()
+            .any(|instr| is_dynamic_call(env, instr).is_some())
+        {
+            return;
+        }
+        let global_refs = get_global_mut_refs(target);
+        if global_refs.is_empty() {
+            return;
+        }
+        let live_vars = target
+            .get_annotations()
+            .get::<LiveVarAnnotation>()
+            .expect("live variable annotation is a prerequisite");
+        for (offset, instr) in code.iter().enumerate() {
+            let Some((id, srcs)) = is_dynamic_call(env, instr) else {
+                continue;
+            };
+            let live_info = live_vars.get_info_at(offset as CodeOffset);
+            let borrow_held = global_refs
+                .iter()
+                .any(|temp| srcs.contains(temp) || live_info.after.contains_key(temp));
+            let loc = target.get_bytecode_loc(id);
+            if borrow_held && !loc.is_inlined() {
+                self.report(
+                    env,
+                    &loc,
+                    "Closure or dynamically dispatched function called while a global resource is mutably borrowed. \
+                    The callee may re-enter this module: consider releasing the borrow before the call.",
+                );
+            }
+        }
+    }
+}
+
+/// If `instr` calls a closure or a dynamically dispatched function, returns the attribute id
+/// and the arguments of the call.
+fn is_dynamic_call<'a>(env: &GlobalEnv, instr: &'a Bytecode) -> Option<(AttrId, &'a [TempIndex])> {
+    match instr {
+        Bytecode::Call(id, _, Operation::Invoke, srcs, _) => Some((*id, srcs.as_slice())),
+        Bytecode::Call(id, _, Operation::Function(mid, _, _), srcs, _)
+            if is_framework_module(env, *mid, DISPATCH_MODULE) =>
+        {
+            Some((*id, srcs.as_slice()))
+        },
+        _ => None,
+    }
+}
+
+/// Get the temps holding mutable references into global storage, obtained via
+/// `borrow_global_mut` and (transitively) borrowing fields of those.
+fn get_global_mut_refs(target: &FunctionTarget) -> BTreeSet<TempIndex> {
+    use Bytecode::Call;
+    use Operation::*;
+    let code = target.get_bytecode();
+    let mut refs = BTreeSet::new();
+    // Iterate to a fixpoint, since field borrows and assignments may be interleaved.
+    loop {
+        let size = refs.len();
+        for instr in code {
+            match instr {
+                Call(_, dsts, BorrowGlobal(..), _, _) => {
+                    if target.get_local_type(dsts[0]).is_mutable_reference() {
+                        refs.insert(dsts[0]);
+                    }
+                },
+                Call(_, dsts, BorrowField(..) | BorrowVariantField(..), srcs, _)
+                    if refs.contains(&srcs[0])
+                        && target.get_local_type(dsts[0]).is_mutable_reference() =>
+                {
+                    refs.insert(dsts[0]);
+                },
+                _ => {},
+            }
+        }
+        refs = derived_temps(code, refs)
+            .into_iter()
+            .filter(|temp| target.get_local_type(*temp).is_mutable_reference())
+            .collect();
+        if refs.len() == size {
+            return refs;
+        }
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements a stackless-bytecode linter that checks for arithmetic on
+//! user-supplied amounts (integer parameters of entry functions) which is not guarded
+//! by a bounds check. Overflowing (or underflowing) arithmetic aborts the transaction,
+//! and left shifts silently drop the overflowing bits. For example:
+//! ```move
+//! public entry fun deposit(account: &signer, amount: u64) acquires Vault {
+//!     let vault = borrow_global_mut<Vault>(signer::address_of(account));
+//!     vault.balance = vault.balance + amount; // <- `amount` is never bounds checked
+//! }
+//! ```
+//! An amount is considered guarded if it is compared using `<`, `<=`, `>`, or `>=`
+//! anywhere in the function.
+
+use crate::utils::derived_temps;
+use move_compiler_v2::external_checks::StacklessBytecodeChecker;
+use move_model::{ast::TempIndex, model::Parameter};
+use move_stackless_bytecode::{
+    function_target::FunctionTarget,
+    stackless_bytecode::{Bytecode, Operation},
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub struct UncheckedAmountArithmetic {}
+
+impl StacklessBytecodeChecker for UncheckedAmountArithmetic {
+    fn get_name(&self) -> String {
+        "unchecked_amount_arithmetic".to_string()
+    }
+
+    fn check(&self, target: &FunctionTarget) {
+        use Bytecode::Call;
+        use Operation::*;
+        if !target.func_env.is_entry() {
+            return;
+        }
+        let env = target.global_env();
+        let code = target.get_bytecode();
+        // Map each temp holding a user-supplied amount to the name of the parameter.
+        let mut amounts = BTreeMap::new();
+        for (param, Parameter(name, ty, _)) in
+            target.func_env.get_parameters_ref().iter().enumerate()
+        {
+            let name = env.symbol_pool().string(*name);
+            if !ty.is_unsigned_int() || name.starts_with('_') {
+                continue;
+            }
+            // Note: we assume that parameters are laid out as the initial temps.
+            for temp in derived_temps(code, [param]) {
+                amounts.entry(temp).or_insert_with(|| name.to_string());
+            }
+        }
+        let amounts_of = |srcs: &[TempIndex]| {
+            srcs.iter()
+                .filter_map(|src| amounts.get(src))
+                .collect::<Vec<_>>()
+        };
+        let guarded = code
+            .iter()
+            .flat_map(|instr| match instr {
+                Call(_, _, Lt | Le | Gt | Ge, srcs, _) => amounts_of(srcs),
+                _ => vec![],
+            })
+            .collect::<BTreeSet<_>>();
+        for instr in code {
+            let Call(id, _, Add | Sub | Mul | Shl, srcs, _) = instr else {
+                continue;
+            };
+            let Some(amount) = amounts_of(srcs)
+                .into_iter()
+                .find(|amount| !guarded.contains(amount))
+            else {
+                continue;
+            };
+            let loc = target.get_bytecode_loc(*id);
+            if loc.is_inlined() {
+                continue;
+            }
+            self.report(
+                env,
+                &loc,
+                &format!(
+                    "Arithmetic on the user-supplied amount `{}` is not guarded by a bounds check, \
+                    and may overflow or underflow. Consider checking `{}` against a maximum value first.",
+                    amount, amount
+                ),
+            );
+        }
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements a stackless-bytecode linter that checks for public and entry
+//! functions which transfer an `Object` using a transfer ref, without checking who
+//! currently owns the object. Transfers via `object::transfer_with_ref` bypass the owner
+//! check performed by `object::transfer`, so anyone calling such a function can move the
+//! object to an address of their choice. For example:
+//! ```move
+//! public entry fun claim(to: address) acquires Refs {
+//!     let refs = borrow_global<Refs>(@my_addr);
+//!     let linear_ref = object::generate_linear_transfer_ref(&refs.transfer_ref);
+//!     object::transfer_with_ref(linear_ref, to);
+//! }
+//! ```
+//! The ownership is considered to be checked if the function calls any of
+//! `object::owner`, `object::is_owner`, `object::owns`, or `object::root_owner`.
+
+use crate::utils::{is_framework_function, is_public_or_entry};
+use move_compiler_v2::external_checks::StacklessBytecodeChecker;
+use move_stackless_bytecode::{
+    function_target::FunctionTarget,
+    stackless_bytecode::{Bytecode, Operation},
+};
+
+/// Functions transferring an object without checking its owner.
+const TRANSFER_FUNCTIONS: &[&str] = &["object::transfer_with_ref"];
+
+/// Functions checking the owner of an object.
+const OWNER_CHECK_FUNCTIONS: &[&str] = &[
+    "object::owner",
+    "object::is_owner",
+    "object::owns",
+    "object::root_owner",
+];
+
+pub struct UncheckedObjectTransfer {}
+
+impl StacklessBytecodeChecker for UncheckedObjectTransfer {
+    fn get_name(&self) -> String {
+        "unchecked_object_transfer".to_string()
+    }
+
+    fn check(&self, target: &FunctionTarget) {
+        if !is_public_or_entry(target.func_env) {
+            return;
+        }
+        let env = target.global_env();
+        let calls_any = |instr: &Bytecode, names: &[&str]| match instr {
+            Bytecode::Call(_, _, Operation::Function(mid, fid, _), _, _) => names
+                .iter()
+                .any(|name| is_framework_function(env, *mid, *fid, name)),
+            _ => false,
+        };
+        let code = target.get_bytecode();
+        if code
+            .iter()
+            .any(|instr| calls_any(instr, OWNER_CHECK_FUNCTIONS))
+        {
+            return;
+        }
+        for instr in code {
+            if calls_any(instr, TRANSFER_FUNCTIONS) {
+                let loc = target.get_bytecode_loc(instr.get_attr_id());
+                if loc.is_inlined() {
+                    continue;
+                }
+                self.report(
+                    env,
+                    &loc,
+                    "Object is transferred with a transfer ref, but its current owner is never checked. \
+                    Consider checking the owner with `object::owner` or `object::is_owner`.",
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements a stackless-bytecode linter that checks for public and entry
+//! functions which take a signer, but never use it to authorize anything. A signer is
+//! considered to be used for authorization if:
+//! - it is passed on to another function (other than `signer::address_of`), moved into
+//!   global storage with `move_to`, or captured by a closure, or
+//! - its address is compared, used to access global storage, or passed on to a function.
+//!
+//! For example, the following function is flagged, as the caller's address is never checked:
+//! ```move
+//! public fun set_fee(admin: &signer, config: &mut Config, fee: u64) {
+//!     let _ = signer::address_of(admin);
+//!     config.fee = fee;
+//! }
+//! ```
+
+use crate::utils::{derived_temps, is_framework_function, is_public_or_entry};
+use move_compiler_v2::external_checks::StacklessBytecodeChecker;
+use move_model::{
+    ast::TempIndex,
+    model::{GlobalEnv, Parameter},
+};
+use move_stackless_bytecode::{
+    function_target::FunctionTarget,
+    stackless_bytecode::{Bytecode, Operation},
+};
+use std::collections::BTreeSet;
+
+pub struct UncheckedSigner {}
+
+impl StacklessBytecodeChecker for UncheckedSigner {
+    fn get_name(&self) -> String {
+        "unchecked_signer".to_string()
+    }
+
+    fn check(&self, target: &FunctionTarget) {
+        if !is_public_or_entry(target.func_env) {
+            return;
+        }
+        let env = target.global_env();
+        for (param, Parameter(name, ty, loc)) in
+            target.func_env.get_parameters_ref().iter().enumerate()
+        {
+            let name = env.symbol_pool().string(*name);
+            if !ty.skip_reference().is_signer() || name.starts_with('_') || loc.is_inlined() {
+                continue;
+            }
+            // Note: we assume that parameters are laid out as the initial temps.
+            if !is_signer_used_for_authorization(env, target.get_bytecode(), param) {
+                self.report(
+                    env,
+                    loc,
+                    &format!(
+                        "The signer `{}` is never used for authorization: its address is not checked, \
+                        and it is not passed on to another function. Anyone can call this function \
+                        with their own signer.",
+                        name
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Returns `true` if the signer `param` is used for authorization in `code`.
+fn is_signer_used_for_authorization(env: &GlobalEnv, code: &[Bytecode], param: TempIndex) -> bool {
+    use Bytecode::Call;
+    use Operation::*;
+    let signers = derived_temps(code, [param]);
+    let uses_signer = |srcs: &[TempIndex]| srcs.iter().any(|src| signers.contains(src));
+    let mut addresses = BTreeSet::new();
+    for instr in code {
+        match instr {
+            Call(_, dsts, Function(mid, fid, _), srcs, _) if uses_signer(srcs) => {
+                if is_framework_function(env, *mid, *fid, "signer::address_of")
+                    || is_framework_function(env, *mid, *fid, "signer::borrow_address")
+                {
+                    addresses.extend(dsts.iter().copied());
+                } else {
+                    // The callee is responsible for the authorization.
+                    return true;
+                }
+            },
+            Call(_, _, MoveTo(..) | Closure(..) | Invoke, srcs, _) if uses_signer(srcs) => {
+                return true;
+            },
+            _ => {},
+        }
+    }
+    let addresses = derived_temps(code, addresses);
+    code.iter().any(|instr| {
+        matches!(
+            instr,
+            Call(
+                _,
+                _,
+                Eq | Neq
+                    | Function(..)
+                    | BorrowGlobal(..)
+                    | Exists(..)
+                    | MoveFrom(..)
+                    | Closure(..)
+                    | Invoke,
+                srcs,
+                _
+            ) if srcs.iter().any(|src| addresses.contains(src))
+        )
+    })
+}
@@ -3,14 +3,17 @@
 
 //! This module holds utility functions for the Move linter.
 use legacy_move_compiler::parser::syntax::FOR_LOOP_UPDATE_ITER_FLAG;
+use move_binary_format::file_format::Visibility;
 use move_model::{
     ast::{
         ExpData,
         ExpData::{IfElse, LocalVar, Loop, Sequence},
-        Operation,
+        Operation, TempIndex,
     },
-    model::FunctionEnv,
+    model::{FunId, FunctionEnv, GlobalEnv, ModuleId},
 };
+use move_stackless_bytecode::stackless_bytecode::{self, Bytecode};
+use std::collections::BTreeSet;
 
 /// Returns `true` if two expressions represent the same simple access pattern.
 /// This compares nested `Select`, `Borrow`, and local variable references for structural equality.
@@ -106,3 +109,60 @@ pub(crate) fn detect_for_loop(expr: &ExpData, function: &FunctionEnv) -> bool {
     };
     name.display(function.symbol_pool()).to_string() == FOR_LOOP_UPDATE_ITER_FLAG
 }
+
+/// Returns `true` if `function` can be called from outside its package,
+/// i.e., it is `public` or an `entry` function.
+pub(crate) fn is_public_or_entry(function: &FunctionEnv) -> bool {
+    function.visibility() == Visibility::Public || function.is_entry()
+}
+
+/// Returns `true` if the function `mid::fid` is the framework function with the given
+/// full name (e.g., `signer::address_of`).
+pub(crate) fn is_framework_function(
+    env: &GlobalEnv,
+    mid: ModuleId,
+    fid: FunId,
+    full_name: &str,
+) -> bool {
+    is_framework_module(env, mid, full_name.split("::").next().unwrap_or_default())
+        && env.get_function(mid.qualified(fid)).get_full_name_str() == full_name
+}
+
+/// Returns `true` if `mid` is the framework module with the given name (e.g., `object`).
+pub(crate) fn is_framework_module(env: &GlobalEnv, mid: ModuleId, name: &str) -> bool {
+    let module = env.get_module(mid);
+    let module_name = module.get_name();
+    let addr = module_name.addr();
+    (addr == &env.get_stdlib_address() || addr == &env.get_extlib_address())
+        && env.symbol_pool().string(module_name.name()).as_str() == name
+}
+
+/// Returns the temps that (transitively) hold a copy of one of the `seeds`, via assignments,
+/// reads, or freezes of references. The `seeds` themselves are included in the result.
+pub(crate) fn derived_temps(
+    code: &[Bytecode],
+    seeds: impl IntoIterator<Item = TempIndex>,
+) -> BTreeSet<TempIndex> {
+    use stackless_bytecode::Operation::{FreezeRef, ReadRef};
+    let mut temps: BTreeSet<TempIndex> = seeds.into_iter().collect();
+    // Iterate to a fixpoint, since the code may contain loops.
+    loop {
+        let mut changed = false;
+        for instr in code {
+            match instr {
+                Bytecode::Assign(_, dst, src, _) if temps.contains(src) => {
+                    changed |= temps.insert(*dst);
+                },
+                Bytecode::Call(_, dsts, ReadRef | FreezeRef(_), srcs, _)
+                    if temps.contains(&srcs[0]) =>
+                {
+                    changed |= temps.insert(dsts[0]);
+                },
+                _ => {},
+            }
+        }
+        if !changed {
+            return temps;
+        }
+    }
+}
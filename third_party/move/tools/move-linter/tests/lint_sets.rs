@@ -0,0 +1,111 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the registry of external lint sets and the lint levels.
+
+use codespan_reporting::term::termcolor::Buffer;
+use move_compiler_v2::{
+    diagnostics::human::HumanEmitter, external_checks::LintLevel, run_move_compiler, Experiment,
+};
+use move_linter::{
+    known_checker_names, make_lint_set,
+    move_model::{
+        ast::{ExpData, Operation},
+        model::FunctionEnv,
+    },
+    register_lint_set, registered_lint_sets, ExpChecker, ExternalChecks, StacklessBytecodeChecker,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    sync::Arc,
+};
+
+/// A lint set with a single checker that reports every explicit `abort`.
+struct AbortChecks;
+
+impl ExternalChecks for AbortChecks {
+    fn get_exp_checkers(&self) -> Vec<Box<dyn ExpChecker>> {
+        vec![Box::new(ExplicitAbort)]
+    }
+
+    fn get_stackless_bytecode_checkers(&self) -> Vec<Box<dyn StacklessBytecodeChecker>> {
+        vec![]
+    }
+}
+
+struct ExplicitAbort;
+
+impl ExpChecker for ExplicitAbort {
+    fn get_name(&self) -> String {
+        "explicit_abort".to_string()
+    }
+
+    fn visit_expr_pre(&mut self, function: &FunctionEnv, expr: &ExpData) {
+        if let ExpData::Call(id, Operation::Abort, _) = expr {
+            let env = function.env();
+            self.report(env, &env.get_node_loc(*id), "explicit abort");
+        }
+    }
+}
+
+fn make_abort_checks(_config: &BTreeMap<String, String>) -> Arc<dyn ExternalChecks> {
+    Arc::new(AbortChecks)
+}
+
+// The registry is global to the process, so each test registers under its own name.
+
+#[test]
+fn test_register_lint_set() {
+    register_lint_set("registry_test", make_abort_checks);
+    assert!(registered_lint_sets().contains(&"registry_test".to_string()));
+
+    let config = BTreeMap::from([("checks".to_string(), "default".to_string())]);
+    let checks = make_lint_set("registry_test", &config).expect("lint set is registered");
+    assert_eq!(
+        known_checker_names(&vec![checks]),
+        BTreeSet::from(["explicit_abort".to_string()])
+    );
+
+    assert!(make_lint_set("unregistered_test", &config).is_none());
+    assert!(!registered_lint_sets().contains(&"unregistered_test".to_string()));
+}
+
+#[test]
+#[should_panic(expected = "lint set `duplicate_test` is already registered")]
+fn test_register_lint_set_twice() {
+    register_lint_set("duplicate_test", make_abort_checks);
+    register_lint_set("duplicate_test", make_abort_checks);
+}
+
+/// Compiles a module with an explicit abort, reporting it at `level`. Returns whether the
+/// compilation succeeded, and whether it had warnings.
+fn compile_with_lint_level(level: LintLevel) -> (bool, bool) {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source = temp_dir.path().join("m.move");
+    fs::write(
+        &source,
+        "module 0x42::m {\n    public fun f() {\n        abort 1\n    }\n}\n",
+    )
+    .unwrap();
+    let options = move_compiler_v2::Options {
+        sources: vec![source.display().to_string()],
+        experiments: vec![Experiment::LINT_CHECKS.to_string()],
+        external_checks: vec![Arc::new(AbortChecks)],
+        lint_levels: BTreeMap::from([("explicit_abort".to_string(), level)]),
+        ..Default::default()
+    };
+    let mut error_writer = Buffer::no_color();
+    let mut emitter = HumanEmitter::new(&mut error_writer);
+    match run_move_compiler(&mut emitter, options) {
+        Ok((env, _)) => (true, env.has_warnings()),
+        Err(_) => (false, true),
+    }
+}
+
+#[test]
+fn test_lint_levels() {
+    assert_eq!(compile_with_lint_level(LintLevel::Warn), (true, true));
+    assert_eq!(compile_with_lint_level(LintLevel::Error), (false, true));
+    assert_eq!(compile_with_lint_level(LintLevel::Allow), (true, false));
+}
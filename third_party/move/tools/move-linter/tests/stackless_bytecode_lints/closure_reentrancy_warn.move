@@ -0,0 +1,34 @@
+module 0xc0ffee::m {
+    struct Vault has key {
+        balance: u64,
+    }
+
+    fun withdraw_warn(addr: address, amount: u64, on_withdraw: |u64|) acquires Vault {
+        let vault = borrow_global_mut<Vault>(addr);
+        on_withdraw(amount);
+        vault.balance = vault.balance - amount;
+    }
+
+    fun update_warn(addr: address, f: |u64| u64) acquires Vault {
+        let balance = &mut borrow_global_mut<Vault>(addr).balance;
+        *balance = f(*balance);
+    }
+
+    fun withdraw_no_warn(addr: address, amount: u64, on_withdraw: |u64|) acquires Vault {
+        let vault = borrow_global_mut<Vault>(addr);
+        vault.balance = vault.balance - amount;
+        on_withdraw(amount);
+    }
+
+    fun read_no_warn(addr: address, f: |u64|) acquires Vault {
+        let vault = borrow_global<Vault>(addr);
+        f(vault.balance);
+    }
+
+    #[lint::skip(closure_reentrancy)]
+    fun skip_no_warn(addr: address, amount: u64, on_withdraw: |u64|) acquires Vault {
+        let vault = borrow_global_mut<Vault>(addr);
+        on_withdraw(amount);
+        vault.balance = vault.balance - amount;
+    }
+}
@@ -0,0 +1,35 @@
+module 0xc0ffee::m {
+    use std::signer;
+
+    const MAX_AMOUNT: u64 = 1000000;
+
+    struct Vault has key {
+        balance: u64,
+    }
+
+    public entry fun deposit_warn(account: &signer, amount: u64) acquires Vault {
+        let vault = borrow_global_mut<Vault>(signer::address_of(account));
+        vault.balance = vault.balance + amount;
+    }
+
+    public entry fun scale_warn(account: &signer, factor: u64) acquires Vault {
+        let vault = borrow_global_mut<Vault>(signer::address_of(account));
+        vault.balance = vault.balance * factor;
+    }
+
+    public entry fun deposit_no_warn(account: &signer, amount: u64) acquires Vault {
+        assert!(amount <= MAX_AMOUNT, 1);
+        let vault = borrow_global_mut<Vault>(signer::address_of(account));
+        vault.balance = vault.balance + amount;
+    }
+
+    public fun deposit_not_entry_no_warn(vault: &mut Vault, amount: u64) {
+        vault.balance = vault.balance + amount;
+    }
+
+    #[lint::skip(unchecked_amount_arithmetic)]
+    public entry fun skip_no_warn(account: &signer, amount: u64) acquires Vault {
+        let vault = borrow_global_mut<Vault>(signer::address_of(account));
+        vault.balance = vault.balance + amount;
+    }
+}
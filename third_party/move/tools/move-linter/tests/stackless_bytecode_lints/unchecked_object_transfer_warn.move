@@ -0,0 +1,60 @@
+module aptos_framework::object {
+    struct Object<phantom T> has copy, drop, store {
+        inner: address,
+    }
+
+    struct TransferRef has drop, store {
+        addr: address,
+    }
+
+    struct LinearTransferRef has drop {
+        addr: address,
+    }
+
+    public fun generate_linear_transfer_ref(ref: &TransferRef): LinearTransferRef {
+        LinearTransferRef { addr: ref.addr }
+    }
+
+    public fun transfer_with_ref(_ref: LinearTransferRef, _to: address) {}
+
+    public fun owner<T: key>(object: Object<T>): address {
+        object.inner
+    }
+}
+
+module 0xc0ffee::m {
+    use aptos_framework::object::{Self, Object, TransferRef};
+    use std::signer;
+
+    struct Refs has key {
+        transfer_ref: TransferRef,
+    }
+
+    struct Token has key {}
+
+    public entry fun claim_warn(to: address) acquires Refs {
+        let refs = borrow_global<Refs>(@0xc0ffee);
+        let linear_ref = object::generate_linear_transfer_ref(&refs.transfer_ref);
+        object::transfer_with_ref(linear_ref, to);
+    }
+
+    public fun claim_no_warn(owner: &signer, token: Object<Token>, to: address) acquires Refs {
+        assert!(object::owner(token) == signer::address_of(owner), 1);
+        let refs = borrow_global<Refs>(@0xc0ffee);
+        let linear_ref = object::generate_linear_transfer_ref(&refs.transfer_ref);
+        object::transfer_with_ref(linear_ref, to);
+    }
+
+    fun private_no_warn(to: address) acquires Refs {
+        let refs = borrow_global<Refs>(@0xc0ffee);
+        let linear_ref = object::generate_linear_transfer_ref(&refs.transfer_ref);
+        object::transfer_with_ref(linear_ref, to);
+    }
+
+    #[lint::skip(unchecked_object_transfer)]
+    public entry fun skip_no_warn(to: address) acquires Refs {
+        let refs = borrow_global<Refs>(@0xc0ffee);
+        let linear_ref = object::generate_linear_transfer_ref(&refs.transfer_ref);
+        object::transfer_with_ref(linear_ref, to);
+    }
+}
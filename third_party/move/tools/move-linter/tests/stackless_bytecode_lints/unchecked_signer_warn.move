@@ -0,0 +1,53 @@
+module 0xc0ffee::m {
+    use std::signer;
+
+    struct Config has key {
+        fee: u64,
+        last_caller: address,
+    }
+
+    struct Admin has key {}
+
+    public fun set_fee_warn(admin: &signer, config: &mut Config, fee: u64) {
+        let _ = signer::address_of(admin);
+        config.fee = fee;
+    }
+
+    public entry fun record_caller_warn(account: &signer) acquires Config {
+        borrow_global_mut<Config>(@0xc0ffee).last_caller = signer::address_of(account);
+    }
+
+    public fun set_fee_no_warn(admin: &signer, config: &mut Config, fee: u64) {
+        assert!(signer::address_of(admin) == @0xc0ffee, 1);
+        config.fee = fee;
+    }
+
+    public entry fun init_no_warn(account: &signer) {
+        move_to(account, Admin {});
+    }
+
+    public fun delegate_no_warn(account: &signer) {
+        helper(account);
+    }
+
+    public entry fun update_no_warn(account: &signer, fee: u64) acquires Config {
+        borrow_global_mut<Config>(signer::address_of(account)).fee = fee;
+    }
+
+    fun private_no_warn(admin: &signer, config: &mut Config, fee: u64) {
+        let _ = signer::address_of(admin);
+        config.fee = fee;
+    }
+
+    public fun underscore_no_warn(_admin: &signer, config: &mut Config, fee: u64) {
+        config.fee = fee;
+    }
+
+    #[lint::skip(unchecked_signer)]
+    public fun skip_no_warn(admin: &signer, config: &mut Config, fee: u64) {
+        let _ = signer::address_of(admin);
+        config.fee = fee;
+    }
+
+    fun helper(_account: &signer) {}
+}
@@ -7,8 +7,8 @@ mod package_name;
 mod util;
 
 pub use manifest::{
-    AddressAssignment, BuildOptions, Dependency, PackageLocation, PackageManifest, PackageMetadata,
-    Version,
+    AddressAssignment, BuildOptions, Dependency, LintLevel, LintOptions, PackageLocation,
+    PackageManifest, PackageMetadata, Version,
 };
 pub use named_address::NamedAddress;
 pub use package_name::PackageName;
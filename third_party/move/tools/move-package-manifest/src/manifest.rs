@@ -43,6 +43,9 @@ pub struct PackageManifest {
     /// Dev-only package dependencies.
     #[serde(default, rename = "dev-dependencies")]
     pub dev_dependencies: BTreeMap<PackageName, Dependency>,
+
+    /// Lint options.
+    pub lint: Option<LintOptions>,
 }
 
 /// Metadata defined in the `[package]` section of `Move.toml`.
@@ -107,6 +110,35 @@ pub struct BuildOptions {
     pub language_version: Option<LanguageVersion>,
 }
 
+/// Lint options defined in the `[lint]` section of `Move.toml`.
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LintOptions {
+    /// Names of the registered external lint sets to run, in addition to the built-in lints.
+    #[serde(default, deserialize_with = "deserialize_unique_vec")]
+    pub sets: Vec<String>,
+
+    /// Levels of individual lints, by lint name.
+    #[serde(default)]
+    pub levels: BTreeMap<String, LintLevel>,
+}
+
+/// Level at which the reports of a lint are emitted.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum LintLevel {
+    /// Reports are dropped.
+    #[serde(rename = "allow")]
+    Allow,
+
+    /// Reports are emitted as warnings.
+    #[serde(rename = "warn")]
+    Warn,
+
+    /// Reports are emitted as errors, failing the build.
+    #[serde(rename = "error")]
+    Error,
+}
+
 /// Represents a dependency entry in `[dependencies]` or `[dev-dependencies]`.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Dependency {
@@ -675,6 +675,12 @@ impl CompiledPackage {
                         compile_test_code: flags.keep_testing_functions(),
                         experiments: config.experiments.clone(),
                         external_checks,
+                        lint_levels: resolved_package
+                            .source_package
+                            .lint
+                            .as_ref()
+                            .map(|lint| lint.levels.clone())
+                            .unwrap_or_default(),
                         ..Default::default()
                     };
                     options = options.set_experiment(Experiment::ATTACH_COMPILED_MODULE, true);
@@ -22,6 +22,7 @@ const ADDRESSES_NAME: &str = "addresses";
 const DEV_ADDRESSES_NAME: &str = "dev-addresses";
 const DEPENDENCY_NAME: &str = "dependencies";
 const DEV_DEPENDENCY_NAME: &str = "dev-dependencies";
+const LINT_NAME: &str = "lint";
 
 const KNOWN_NAMES: &[&str] = &[
     PACKAGE_NAME,
@@ -30,6 +31,7 @@ const KNOWN_NAMES: &[&str] = &[
     DEV_ADDRESSES_NAME,
     DEPENDENCY_NAME,
     DEV_DEPENDENCY_NAME,
+    LINT_NAME,
 ];
 
 const REQUIRED_FIELDS: &[&str] = &[PACKAGE_NAME];
@@ -86,6 +88,11 @@ pub fn parse_source_manifest(tval: TV) -> Result<PM::SourceManifest> {
                 .transpose()
                 .context("Error parsing '[dev-dependencies]' section of manifest")?
                 .unwrap_or_default();
+            let lint = table
+                .remove(LINT_NAME)
+                .map(parse_lint_info)
+                .transpose()
+                .context("Error parsing '[lint]' section of manifest")?;
             Ok(PM::SourceManifest {
                 package,
                 addresses,
@@ -93,6 +100,7 @@ pub fn parse_source_manifest(tval: TV) -> Result<PM::SourceManifest> {
                 build,
                 dependencies,
                 dev_dependencies,
+                lint,
             })
         },
         x => {
@@ -213,6 +221,54 @@ pub fn parse_build_info(tval: TV) -> Result<PM::BuildInfo> {
     }
 }
 
+pub fn parse_lint_info(tval: TV) -> Result<PM::LintInfo> {
+    match tval {
+        TV::Table(mut table) => {
+            warn_if_unknown_field_names(&table, &["sets", "levels"]);
+            let sets = match table.remove("sets") {
+                None => Vec::new(),
+                Some(TV::Array(sets)) => sets
+                    .into_iter()
+                    .map(|set| match set {
+                        TV::String(set) => Ok(set),
+                        x => bail!("Invalid lint set {}. Expected a string", x),
+                    })
+                    .collect::<Result<_>>()?,
+                Some(x) => bail!(
+                    "Malformed 'sets' field {}. Expected an array of strings, but encountered a {}",
+                    x,
+                    x.type_str()
+                ),
+            };
+            let levels = match table.remove("levels") {
+                None => BTreeMap::new(),
+                Some(TV::Table(levels)) => levels
+                    .into_iter()
+                    .map(|(lint, level)| match level.as_str() {
+                        Some(level) => Ok((lint, level.parse().map_err(|e| format_err!("{}", e))?)),
+                        None => bail!(
+                            "Invalid level {} of lint '{}'. Expected a string",
+                            level,
+                            lint
+                        ),
+                    })
+                    .collect::<Result<_>>()?,
+                Some(x) => bail!(
+                    "Malformed 'levels' field {}. Expected a table, but encountered a {}",
+                    x,
+                    x.type_str()
+                ),
+            };
+            Ok(PM::LintInfo { sets, levels })
+        },
+        x => bail!(
+            "Malformed section in manifest {}. Expected a table, but encountered a {}",
+            x,
+            x.type_str()
+        ),
+    }
+}
+
 pub fn parse_addresses(tval: TV) -> Result<PM::AddressDeclarations> {
     match tval {
         TV::Table(table) => {
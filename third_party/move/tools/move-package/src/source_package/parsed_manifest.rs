@@ -2,6 +2,7 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use move_compiler_v2::external_checks::LintLevel;
 use move_core_types::account_address::AccountAddress;
 use move_symbol_pool::symbol::Symbol;
 use std::{collections::BTreeMap, fmt, fmt::Debug, path::PathBuf};
@@ -25,6 +26,7 @@ pub struct SourceManifest {
     pub build: Option<BuildInfo>,
     pub dependencies: Dependencies,
     pub dev_dependencies: Dependencies,
+    pub lint: Option<LintInfo>,
 }
 
 impl fmt::Display for SourceManifest {
@@ -118,6 +120,14 @@ pub struct BuildInfo {
     pub language_version: Option<Version>,
 }
 
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct LintInfo {
+    /// Names of the registered external lint sets to run, in addition to the built-in lints.
+    pub sets: Vec<String>,
+    /// Levels of individual lints, by lint name.
+    pub levels: BTreeMap<String, LintLevel>,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum SubstOrRename {
     RenameFrom(NamedAddress),
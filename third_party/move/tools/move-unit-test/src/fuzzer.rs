@@ -0,0 +1,478 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Argument generation, mutation, and shrinking for `#[test_fuzz]` tests.
+//!
+//! All randomness comes from a small deterministic generator, so a run of a fuzzed test is fully
+//! determined by its seed. Integers are biased towards boundary and small values, which is where
+//! arithmetic bugs in Move code tend to show up.
+
+use legacy_move_compiler::unit_test::FuzzArgument;
+use move_core_types::{
+    account_address::AccountAddress,
+    int256::{I256, U256},
+    value::{MoveStruct, MoveStructLayout, MoveTypeLayout, MoveValue},
+};
+
+/// Maximal length of generated vectors.
+const MAX_VECTOR_LEN: u64 = 8;
+
+/// Maximal nesting of generated vectors; deeper vectors are generated empty.
+const MAX_VECTOR_DEPTH: usize = 3;
+
+/// A SplitMix64 pseudo random number generator.
+pub struct FuzzRng(u64);
+
+impl FuzzRng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..bound`. `bound` must not be zero.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    fn bytes<const N: usize>(&mut self) -> [u8; N] {
+        let mut bytes = [0u8; N];
+        for chunk in bytes.chunks_mut(8) {
+            let len = chunk.len();
+            chunk.copy_from_slice(&self.next_u64().to_le_bytes()[..len]);
+        }
+        bytes
+    }
+}
+
+/// Derives the seed for the test named `test_name` from the seed of the whole test run, so that
+/// different tests do not all see the same inputs.
+pub fn test_seed(run_seed: u64, test_name: &str) -> u64 {
+    // FNV-1a, which unlike the std hasher is stable across Rust releases.
+    let name_hash = test_name
+        .bytes()
+        .fold(0xCBF2_9CE4_8422_2325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3)
+        });
+    FuzzRng::new(run_seed ^ name_hash).next_u64()
+}
+
+/// Generates fresh arguments for a fuzzed test.
+pub fn generate_arguments(rng: &mut FuzzRng, params: &[FuzzArgument]) -> Vec<MoveValue> {
+    params
+        .iter()
+        .map(|param| match param {
+            FuzzArgument::Fixed(value) => value.clone(),
+            FuzzArgument::Generated(layout) => generate_value(rng, layout, 0),
+        })
+        .collect()
+}
+
+/// Derives new arguments from `values` by mutating one of the generated arguments.
+pub fn mutate_arguments(
+    rng: &mut FuzzRng,
+    params: &[FuzzArgument],
+    values: &[MoveValue],
+) -> Vec<MoveValue> {
+    let generated: Vec<_> = params
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, param)| match param {
+            FuzzArgument::Generated(layout) => Some((idx, layout)),
+            FuzzArgument::Fixed(_) => None,
+        })
+        .collect();
+    let mut values = values.to_vec();
+    if !generated.is_empty() {
+        let (idx, layout) = generated[rng.below(generated.len() as u64) as usize];
+        values[idx] = mutate_value(rng, layout, &values[idx], 0);
+    }
+    values
+}
+
+/// Returns arguments which are simpler than `values`, in the order in which they should be
+/// tried. Fixed arguments are never changed.
+pub fn shrink_arguments(params: &[FuzzArgument], values: &[MoveValue]) -> Vec<Vec<MoveValue>> {
+    let mut candidates = vec![];
+    for (idx, param) in params.iter().enumerate() {
+        if let FuzzArgument::Generated(_) = param {
+            for simpler in shrink_value(&values[idx]) {
+                let mut candidate = values.to_vec();
+                candidate[idx] = simpler;
+                candidates.push(candidate);
+            }
+        }
+    }
+    candidates
+}
+
+/// Renders a value in Move syntax, for reporting failing inputs.
+pub fn format_value(value: &MoveValue) -> String {
+    match value {
+        MoveValue::Bool(b) => b.to_string(),
+        MoveValue::U8(x) => format!("{}u8", x),
+        MoveValue::U16(x) => format!("{}u16", x),
+        MoveValue::U32(x) => format!("{}u32", x),
+        MoveValue::U64(x) => format!("{}u64", x),
+        MoveValue::U128(x) => format!("{}u128", x),
+        MoveValue::U256(x) => format!("{}u256", x),
+        MoveValue::I8(x) => format!("{}i8", x),
+        MoveValue::I16(x) => format!("{}i16", x),
+        MoveValue::I32(x) => format!("{}i32", x),
+        MoveValue::I64(x) => format!("{}i64", x),
+        MoveValue::I128(x) => format!("{}i128", x),
+        MoveValue::I256(x) => format!("{}i256", x),
+        MoveValue::Address(addr) => format!("@{}", addr.to_hex_literal()),
+        MoveValue::Signer(addr) => format!("signer(@{})", addr.to_hex_literal()),
+        MoveValue::Vector(elems) => format!(
+            "vector[{}]",
+            elems
+                .iter()
+                .map(format_value)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        MoveValue::Struct(MoveStruct::Runtime(fields)) => format!(
+            "{{ {} }}",
+            fields
+                .iter()
+                .map(format_value)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        _ => format!("{:?}", value),
+    }
+}
+
+fn generate_value(rng: &mut FuzzRng, layout: &MoveTypeLayout, depth: usize) -> MoveValue {
+    use MoveTypeLayout as L;
+    match layout {
+        L::Bool => MoveValue::Bool(rng.below(2) == 1),
+        L::U8 => MoveValue::U8(generate_int(rng)),
+        L::U16 => MoveValue::U16(generate_int(rng)),
+        L::U32 => MoveValue::U32(generate_int(rng)),
+        L::U64 => MoveValue::U64(generate_int(rng)),
+        L::U128 => MoveValue::U128(generate_int(rng)),
+        L::U256 => MoveValue::U256(generate_int(rng)),
+        L::I8 => MoveValue::I8(generate_int(rng)),
+        L::I16 => MoveValue::I16(generate_int(rng)),
+        L::I32 => MoveValue::I32(generate_int(rng)),
+        L::I64 => MoveValue::I64(generate_int(rng)),
+        L::I128 => MoveValue::I128(generate_int(rng)),
+        L::I256 => MoveValue::I256(generate_int(rng)),
+        L::Address => MoveValue::Address(generate_address(rng)),
+        L::Signer => MoveValue::Signer(generate_address(rng)),
+        L::Vector(elem) => {
+            let len = if depth < MAX_VECTOR_DEPTH {
+                rng.below(MAX_VECTOR_LEN + 1)
+            } else {
+                0
+            };
+            MoveValue::Vector(
+                (0..len)
+                    .map(|_| generate_value(rng, elem, depth + 1))
+                    .collect(),
+            )
+        },
+        L::Struct(MoveStructLayout::Runtime(fields)) => MoveValue::Struct(MoveStruct::new(
+            fields
+                .iter()
+                .map(|field| generate_value(rng, field, depth))
+                .collect(),
+        )),
+        _ => unreachable!("layout `{}` is not produced for fuzzed tests", layout),
+    }
+}
+
+fn generate_address(rng: &mut FuzzRng) -> AccountAddress {
+    match rng.below(4) {
+        0 => AccountAddress::ZERO,
+        1 => AccountAddress::ONE,
+        2 => {
+            let mut bytes = [0u8; AccountAddress::LENGTH];
+            bytes[AccountAddress::LENGTH - 1] = rng.below(256) as u8;
+            AccountAddress::new(bytes)
+        },
+        _ => AccountAddress::new(rng.bytes()),
+    }
+}
+
+fn mutate_value(
+    rng: &mut FuzzRng,
+    layout: &MoveTypeLayout,
+    value: &MoveValue,
+    depth: usize,
+) -> MoveValue {
+    match (layout, value) {
+        (_, MoveValue::Bool(b)) => MoveValue::Bool(!b),
+        (_, MoveValue::U8(x)) => MoveValue::U8(mutate_int(rng, *x)),
+        (_, MoveValue::U16(x)) => MoveValue::U16(mutate_int(rng, *x)),
+        (_, MoveValue::U32(x)) => MoveValue::U32(mutate_int(rng, *x)),
+        (_, MoveValue::U64(x)) => MoveValue::U64(mutate_int(rng, *x)),
+        (_, MoveValue::U128(x)) => MoveValue::U128(mutate_int(rng, *x)),
+        (_, MoveValue::U256(x)) => MoveValue::U256(mutate_int(rng, *x)),
+        (_, MoveValue::I8(x)) => MoveValue::I8(mutate_int(rng, *x)),
+        (_, MoveValue::I16(x)) => MoveValue::I16(mutate_int(rng, *x)),
+        (_, MoveValue::I32(x)) => MoveValue::I32(mutate_int(rng, *x)),
+        (_, MoveValue::I64(x)) => MoveValue::I64(mutate_int(rng, *x)),
+        (_, MoveValue::I128(x)) => MoveValue::I128(mutate_int(rng, *x)),
+        (_, MoveValue::I256(x)) => MoveValue::I256(mutate_int(rng, *x)),
+        (MoveTypeLayout::Vector(elem), MoveValue::Vector(elems)) => {
+            let mut elems = elems.clone();
+            match rng.below(3) {
+                0 if (elems.len() as u64) < MAX_VECTOR_LEN && depth < MAX_VECTOR_DEPTH => {
+                    let pos = rng.below(elems.len() as u64 + 1) as usize;
+                    elems.insert(pos, generate_value(rng, elem, depth + 1));
+                },
+                1 if !elems.is_empty() => {
+                    elems.remove(rng.below(elems.len() as u64) as usize);
+                },
+                _ if !elems.is_empty() => {
+                    let pos = rng.below(elems.len() as u64) as usize;
+                    elems[pos] = mutate_value(rng, elem, &elems[pos], depth + 1);
+                },
+                _ => return generate_value(rng, layout, depth),
+            }
+            MoveValue::Vector(elems)
+        },
+        (
+            MoveTypeLayout::Struct(MoveStructLayout::Runtime(field_layouts)),
+            MoveValue::Struct(MoveStruct::Runtime(fields)),
+        ) if !fields.is_empty() => {
+            let mut fields = fields.clone();
+            let pos = rng.below(fields.len() as u64) as usize;
+            fields[pos] = mutate_value(rng, &field_layouts[pos], &fields[pos], depth);
+            MoveValue::Struct(MoveStruct::new(fields))
+        },
+        // Addresses, signers, and empty structs are regenerated
+        _ => generate_value(rng, layout, depth),
+    }
+}
+
+fn shrink_value(value: &MoveValue) -> Vec<MoveValue> {
+    match value {
+        MoveValue::Bool(true) => vec![MoveValue::Bool(false)],
+        MoveValue::U8(x) => shrink_int(*x).into_iter().map(MoveValue::U8).collect(),
+        MoveValue::U16(x) => shrink_int(*x).into_iter().map(MoveValue::U16).collect(),
+        MoveValue::U32(x) => shrink_int(*x).into_iter().map(MoveValue::U32).collect(),
+        MoveValue::U64(x) => shrink_int(*x).into_iter().map(MoveValue::U64).collect(),
+        MoveValue::U128(x) => shrink_int(*x).into_iter().map(MoveValue::U128).collect(),
+        MoveValue::U256(x) => shrink_int(*x).into_iter().map(MoveValue::U256).collect(),
+        MoveValue::I8(x) => shrink_int(*x).into_iter().map(MoveValue::I8).collect(),
+        MoveValue::I16(x) => shrink_int(*x).into_iter().map(MoveValue::I16).collect(),
+        MoveValue::I32(x) => shrink_int(*x).into_iter().map(MoveValue::I32).collect(),
+        MoveValue::I64(x) => shrink_int(*x).into_iter().map(MoveValue::I64).collect(),
+        MoveValue::I128(x) => shrink_int(*x).into_iter().map(MoveValue::I128).collect(),
+        MoveValue::I256(x) => shrink_int(*x).into_iter().map(MoveValue::I256).collect(),
+        MoveValue::Address(addr) if *addr != AccountAddress::ZERO => {
+            vec![MoveValue::Address(AccountAddress::ZERO)]
+        },
+        MoveValue::Signer(addr) if *addr != AccountAddress::ZERO => {
+            vec![MoveValue::Signer(AccountAddress::ZERO)]
+        },
+        MoveValue::Vector(elems) if !elems.is_empty() => {
+            let mut candidates = vec![MoveValue::Vector(vec![])];
+            if elems.len() > 2 {
+                candidates.push(MoveValue::Vector(elems[..elems.len() / 2].to_vec()));
+            }
+            for pos in 0..elems.len() {
+                let mut fewer = elems.clone();
+                fewer.remove(pos);
+                candidates.push(MoveValue::Vector(fewer));
+            }
+            for (pos, elem) in elems.iter().enumerate() {
+                for simpler in shrink_value(elem) {
+                    let mut shrunk = elems.clone();
+                    shrunk[pos] = simpler;
+                    candidates.push(MoveValue::Vector(shrunk));
+                }
+            }
+            candidates
+        },
+        MoveValue::Struct(MoveStruct::Runtime(fields)) => {
+            let mut candidates = vec![];
+            for (pos, field) in fields.iter().enumerate() {
+                for simpler in shrink_value(field) {
+                    let mut shrunk = fields.clone();
+                    shrunk[pos] = simpler;
+                    candidates.push(MoveValue::Struct(MoveStruct::new(shrunk)));
+                }
+            }
+            candidates
+        },
+        _ => vec![],
+    }
+}
+
+/// Integer operations needed for generating, mutating, and shrinking values.
+trait FuzzInt: Copy + PartialEq {
+    const ZERO: Self;
+    fn random(rng: &mut FuzzRng) -> Self;
+    fn boundaries() -> Vec<Self>;
+    /// A number close to zero, or close to the maximum for unsigned types.
+    fn small(rng: &mut FuzzRng) -> Self;
+    fn halve(self) -> Self;
+    fn toward_zero(self) -> Self;
+    fn flip_bit(self, bit: u64) -> Self;
+}
+
+macro_rules! fuzz_int {
+    ($($ty:ty),*) => {
+        $(
+            impl FuzzInt for $ty {
+                const ZERO: Self = 0;
+
+                fn random(rng: &mut FuzzRng) -> Self {
+                    Self::from_le_bytes(rng.bytes())
+                }
+
+                fn boundaries() -> Vec<Self> {
+                    vec![0, 1, Self::MAX, Self::MAX - 1, Self::MIN, Self::MIN.wrapping_add(1)]
+                }
+
+                fn small(rng: &mut FuzzRng) -> Self {
+                    let small = rng.below(64) as Self;
+                    if rng.below(2) == 0 {
+                        small
+                    } else {
+                        Self::ZERO.wrapping_sub(small)
+                    }
+                }
+
+                fn halve(self) -> Self {
+                    self / 2
+                }
+
+                fn toward_zero(self) -> Self {
+                    if self == 0 {
+                        0
+                    } else if self > 0 {
+                        self - 1
+                    } else {
+                        self + 1
+                    }
+                }
+
+                fn flip_bit(self, bit: u64) -> Self {
+                    self ^ (1 << (bit % Self::BITS as u64))
+                }
+            }
+        )*
+    };
+}
+
+fuzz_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl FuzzInt for U256 {
+    const ZERO: Self = U256::ZERO;
+
+    fn random(rng: &mut FuzzRng) -> Self {
+        Self::from_le_bytes(rng.bytes())
+    }
+
+    fn boundaries() -> Vec<Self> {
+        vec![
+            Self::ZERO,
+            Self::ONE,
+            Self::MAX,
+            Self::checked_sub(Self::MAX, Self::ONE).expect("no underflow"),
+        ]
+    }
+
+    fn small(rng: &mut FuzzRng) -> Self {
+        let small = Self::from(rng.below(64));
+        if rng.below(2) == 0 {
+            small
+        } else {
+            Self::checked_sub(Self::MAX, small).expect("no underflow")
+        }
+    }
+
+    fn halve(self) -> Self {
+        Self::checked_div(self, Self::from(2u8)).expect("no division by zero")
+    }
+
+    fn toward_zero(self) -> Self {
+        Self::checked_sub(self, Self::ONE).unwrap_or(Self::ZERO)
+    }
+
+    fn flip_bit(self, bit: u64) -> Self {
+        Self::from_le_bytes(flip_le_bit(self.to_le_bytes(), bit))
+    }
+}
+
+impl FuzzInt for I256 {
+    const ZERO: Self = I256::ZERO;
+
+    fn random(rng: &mut FuzzRng) -> Self {
+        Self::from_le_bytes(rng.bytes())
+    }
+
+    fn boundaries() -> Vec<Self> {
+        vec![Self::ZERO, Self::ONE, -Self::ONE, Self::MAX, Self::MIN]
+    }
+
+    fn small(rng: &mut FuzzRng) -> Self {
+        Self::from(rng.below(128) as i64 - 64)
+    }
+
+    fn halve(self) -> Self {
+        Self::checked_div(self, Self::from(2i8)).expect("no division by zero")
+    }
+
+    fn toward_zero(self) -> Self {
+        if self == Self::ZERO {
+            self
+        } else if self > Self::ZERO {
+            Self::checked_sub(self, Self::ONE).expect("no underflow")
+        } else {
+            Self::checked_add(self, Self::ONE).expect("no overflow")
+        }
+    }
+
+    fn flip_bit(self, bit: u64) -> Self {
+        Self::from_le_bytes(flip_le_bit(self.to_le_bytes(), bit))
+    }
+}
+
+fn flip_le_bit(mut bytes: [u8; 32], bit: u64) -> [u8; 32] {
+    let bit = bit % 256;
+    bytes[(bit / 8) as usize] ^= 1 << (bit % 8);
+    bytes
+}
+
+fn generate_int<T: FuzzInt>(rng: &mut FuzzRng) -> T {
+    match rng.below(4) {
+        0 => {
+            let boundaries = T::boundaries();
+            boundaries[rng.below(boundaries.len() as u64) as usize]
+        },
+        1 => T::small(rng),
+        _ => T::random(rng),
+    }
+}
+
+fn mutate_int<T: FuzzInt>(rng: &mut FuzzRng, value: T) -> T {
+    match rng.below(4) {
+        0 => value.flip_bit(rng.next_u64()),
+        1 => value.halve(),
+        2 => value.toward_zero(),
+        _ => generate_int(rng),
+    }
+}
+
+fn shrink_int<T: FuzzInt>(value: T) -> Vec<T> {
+    let mut candidates: Vec<T> = vec![];
+    for candidate in [T::ZERO, value.halve(), value.toward_zero()] {
+        if candidate != value && !candidates.contains(&candidate) {
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
@@ -3,11 +3,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod extensions;
+mod fuzzer;
 pub mod test_reporter;
 pub mod test_runner;
 
 use crate::test_runner::TestRunner;
 use clap::*;
+#[cfg(feature = "debug-adapter")]
+use legacy_move_compiler::unit_test::NamedOrBytecodeModule;
 use legacy_move_compiler::{
     self,
     shared::{self, NumericalAddress},
@@ -16,20 +19,28 @@ use legacy_move_compiler::{
 use move_command_line_common::files::verify_and_create_named_address_mapping;
 use move_compiler_v2::plan_builder as plan_builder_v2;
 use move_core_types::{effects::ChangeSet, language_storage::ModuleId};
+#[cfg(feature = "debug-adapter")]
+use move_debug_adapter::{DebugServer, SourceIndex};
 use move_model::metadata::{CompilerVersion, LanguageVersion};
 use move_package::compilation::compiled_package::build_and_report_v2_driver;
 use move_vm_runtime::native_functions::NativeFunctionTable;
+#[cfg(feature = "debug-adapter")]
+use move_vm_runtime::tracing;
 use std::{
     collections::BTreeMap,
-    io::{Result, Write},
+    io::{Error, Result, Write},
     marker::Send,
     sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
 };
 use test_reporter::UnitTestFactory;
 
 /// The default value bounding the amount of gas consumed in a test.
 const DEFAULT_EXECUTION_BOUND: u64 = 1_000_000;
 
+/// The default number of runs of each `#[test_fuzz]` test.
+const DEFAULT_FUZZ_RUNS: u64 = 256;
+
 #[derive(Debug, Parser, Clone)]
 #[clap(author, version, about)]
 pub struct UnitTestingConfig {
@@ -97,6 +108,20 @@ pub struct UnitTestingConfig {
     /// Verbose mode
     #[clap(short = 'v', long = "verbose")]
     pub verbose: bool,
+
+    /// Number of runs with generated arguments for each `#[test_fuzz]` test
+    #[clap(long = "fuzz-runs", default_value_t = DEFAULT_FUZZ_RUNS)]
+    pub fuzz_runs: u64,
+
+    /// Seed for generating arguments of `#[test_fuzz]` tests. A random seed is used if not set.
+    #[clap(long = "fuzz-seed")]
+    pub fuzz_seed: Option<u64>,
+
+    /// Serve the Debug Adapter Protocol on this address (e.g. `127.0.0.1:4711`) and run the
+    /// tests under the control of the debugger which connects to it. Tests are run on a single
+    /// thread.
+    #[clap(long = "debug-adapter")]
+    pub debug_adapter: Option<String>,
 }
 
 fn format_module_id(module_id: &ModuleId) -> String {
@@ -121,6 +146,9 @@ impl Default for UnitTestingConfig {
             verbose: false,
             list: false,
             named_address_values: vec![],
+            fuzz_runs: DEFAULT_FUZZ_RUNS,
+            fuzz_seed: None,
+            debug_adapter: None,
         }
     }
 }
@@ -206,8 +234,58 @@ impl UnitTestingConfig {
         }
 
         writeln!(shared_writer.lock().unwrap(), "Running Move unit tests")?;
+        let fuzz_seed = self.fuzz_seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_nanos() as u64)
+                .unwrap_or_default()
+        });
+        let has_fuzz_tests = test_plan
+            .module_tests
+            .values()
+            .flat_map(|module_tests| module_tests.tests.values())
+            .any(|test| test.fuzz_arguments.is_some());
+        if has_fuzz_tests {
+            writeln!(
+                shared_writer.lock().unwrap(),
+                "Fuzzing tests with seed {}",
+                fuzz_seed
+            )?;
+            if !tracing::is_coverage_recording_supported() {
+                writeln!(
+                    shared_writer.lock().unwrap(),
+                    "Warning: move-unit-test is built without the `debugging` feature, so fuzzing \
+                     is not guided by coverage and only generates random arguments"
+                )?;
+            }
+        }
+        #[cfg(feature = "debug-adapter")]
+        let debug_server = match &self.debug_adapter {
+            Some(addr) => {
+                writeln!(
+                    shared_writer.lock().unwrap(),
+                    "Waiting for a debugger to connect on {}",
+                    addr
+                )?;
+                let server = DebugServer::listen(addr.as_str(), debug_sources(&test_plan))
+                    .map_err(|err| Error::other(err.to_string()))?;
+                tracing::set_debugger(Some(server.debugger()));
+                Some(server)
+            },
+            None => None,
+        };
+        #[cfg(not(feature = "debug-adapter"))]
+        if self.debug_adapter.is_some() {
+            return Err(Error::other(
+                "debugging requires move-unit-test to be built with the `debug-adapter` feature",
+            ));
+        }
         let mut test_runner = TestRunner::new(
-            self.num_threads,
+            if self.debug_adapter.is_some() {
+                1
+            } else {
+                self.num_threads
+            },
             self.report_storage_on_error,
             self.report_stacktrace_on_abort,
             test_plan,
@@ -215,6 +293,8 @@ impl UnitTestingConfig {
             genesis_state,
             self.verbose,
             enable_enum_option,
+            self.fuzz_runs,
+            fuzz_seed,
         )
         .unwrap();
 
@@ -232,12 +312,33 @@ impl UnitTestingConfig {
         }
 
         let ok = test_results.summarize(&shared_writer)?;
+        #[cfg(feature = "debug-adapter")]
+        if let Some(server) = debug_server {
+            tracing::set_debugger(None);
+            server.terminate(if ok { 0 } else { 1 });
+        }
 
         let writer = shared_writer.into_inner().unwrap();
         Ok((writer, ok))
     }
 }
 
+/// Collects the modules compiled from source and the source files of a test plan, for mapping
+/// executed instructions back to source lines while debugging.
+#[cfg(feature = "debug-adapter")]
+fn debug_sources(test_plan: &TestPlan) -> SourceIndex {
+    let mut sources = SourceIndex::new();
+    for module in test_plan.module_info.values() {
+        if let NamedOrBytecodeModule::Named(named_module) = module {
+            sources.add_module(named_module.module.clone(), named_module.source_map.clone());
+        }
+    }
+    for (file_hash, (file_name, text)) in &test_plan.files {
+        sources.add_file(*file_hash, file_name.as_str(), text);
+    }
+    sources
+}
+
 #[test]
 fn verify_tool() {
     use clap::CommandFactory;
@@ -42,6 +42,15 @@ fn unit_cost_table() -> CostTable {
 pub trait UnitTestFactory {
     type GasMeter: GasMeter;
     fn new_gas_meter(&self) -> Self::GasMeter;
+    /// Creates the gas meter for running the given test function. Factories which need to know
+    /// the test, e.g., to profile it, can override this.
+    fn new_gas_meter_for_test(
+        &self,
+        _module_id: &ModuleId,
+        _function_name: &str,
+    ) -> Self::GasMeter {
+        self.new_gas_meter()
+    }
     fn finalize_test_run_info(
         &self,
         change_set: &ChangeSet,
@@ -107,6 +116,12 @@ pub enum FailureReason {
     },
     // Property checking failed
     Property(String),
+    // A fuzzed test failed for generated arguments
+    Fuzz {
+        reason: Box<FailureReason>,
+        seed: u64,
+        arguments: Vec<String>,
+    },
 }
 
 #[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq)]
@@ -193,6 +208,14 @@ impl FailureReason {
     pub fn property(details: String) -> Self {
         FailureReason::Property(details)
     }
+
+    pub fn fuzz(reason: FailureReason, seed: u64, arguments: Vec<String>) -> Self {
+        FailureReason::Fuzz {
+            reason: Box::new(reason),
+            seed,
+            arguments,
+        }
+    }
 }
 
 impl TestFailure {
@@ -211,7 +234,30 @@ impl TestFailure {
     }
 
     pub fn render_error(&self, test_plan: &TestPlan) -> String {
-        let error_string = match &self.failure_reason {
+        let error_string = self.render_failure_reason(&self.failure_reason, test_plan);
+
+        match &self.storage_state {
+            None => error_string,
+            Some(storage_state) => {
+                format!(
+                    "{}\n────── Storage state at point of failure ──────\n{}",
+                    error_string,
+                    if storage_state.is_empty() {
+                        "<empty>"
+                    } else {
+                        storage_state
+                    }
+                )
+            },
+        }
+    }
+
+    fn render_failure_reason(
+        &self,
+        failure_reason: &FailureReason,
+        test_plan: &TestPlan,
+    ) -> String {
+        match failure_reason {
             FailureReason::NoError(message) => message.to_string(),
             FailureReason::Timeout(message) => message.to_string(),
             FailureReason::WrongError(message, expected, actual) => {
@@ -276,20 +322,24 @@ impl TestFailure {
                 )
             },
             FailureReason::Property(message) => message.clone(),
-        };
-
-        match &self.storage_state {
-            None => error_string,
-            Some(storage_state) => {
-                format!(
-                    "{}\n────── Storage state at point of failure ──────\n{}",
-                    error_string,
-                    if storage_state.is_empty() {
-                        "<empty>"
-                    } else {
-                        storage_state
-                    }
-                )
+            FailureReason::Fuzz {
+                reason,
+                seed,
+                arguments,
+            } => {
+                let mut message = self.render_failure_reason(reason, test_plan);
+                while !message.ends_with("\n\n") {
+                    message.push('\n');
+                }
+                message.push_str("Fuzzing found failing arguments (shrunk):\n");
+                for (idx, argument) in arguments.iter().enumerate() {
+                    message.push_str(&format!("\targument {}: {}\n", idx, argument));
+                }
+                message.push_str(&format!(
+                    "Rerun with `--fuzz-seed {}` to reproduce this failure.\n",
+                    seed
+                ));
+                message
             },
         }
     }
@@ -4,6 +4,7 @@
 
 use crate::{
     extensions, format_module_id,
+    fuzzer::{self, FuzzRng},
     test_reporter::{
         FailureReason, MoveError, TestFailure, TestResults, TestRunInfo, TestStatistics,
         UnitTestFactory,
@@ -12,10 +13,10 @@ use crate::{
 use anyhow::Result;
 use colored::*;
 use legacy_move_compiler::unit_test::{
-    ExpectedFailure, ModuleTestPlan, NamedOrBytecodeModule, TestCase, TestPlan,
+    ExpectedFailure, FuzzArgument, ModuleTestPlan, NamedOrBytecodeModule, TestCase, TestPlan,
 };
 use move_binary_format::{
-    errors::{Location, VMResult},
+    errors::{Location, VMError, VMResult},
     file_format::CompiledModule,
 };
 use move_bytecode_utils::Modules;
@@ -23,9 +24,10 @@ use move_core_types::{
     account_address::AccountAddress,
     effects::{ChangeSet, Op},
     identifier::IdentStr,
-    value::serialize_values,
+    value::{serialize_values, MoveValue},
     vm_status::StatusCode,
 };
+use move_coverage::coverage_map::ExecCoverageMap;
 use move_resource_viewer::MoveValueAnnotator;
 use move_vm_runtime::{
     data_cache::TransactionDataCache,
@@ -34,12 +36,21 @@ use move_vm_runtime::{
     move_vm::MoveVM,
     native_extensions::NativeContextExtensions,
     native_functions::NativeFunctionTable,
+    tracing::{start_coverage_recording, take_coverage_recording},
     AsFunctionValueExtension, AsUnsyncModuleStorage, InstantiatedFunctionLoader,
     LegacyLoaderConfig, RuntimeEnvironment,
 };
 use move_vm_test_utils::InMemoryStorage;
 use rayon::prelude::*;
-use std::{io::Write, marker::Send, sync::Mutex, time::Instant};
+use std::{
+    io::Write,
+    marker::Send,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Maximal number of executions spent on shrinking the arguments of a failing fuzzed test.
+const MAX_SHRINK_RUNS: usize = 1024;
 
 /// Test state common to all tests
 pub struct SharedTestingConfig {
@@ -49,6 +60,8 @@ pub struct SharedTestingConfig {
     #[allow(dead_code)] // used by some features
     source_files: Vec<String>,
     record_writeset: bool,
+    fuzz_runs: u64,
+    fuzz_seed: u64,
 }
 
 pub struct TestRunner {
@@ -119,6 +132,8 @@ impl TestRunner {
         genesis_state: Option<ChangeSet>,
         record_writeset: bool,
         enable_enum_option: bool,
+        fuzz_runs: u64,
+        fuzz_seed: u64,
     ) -> Result<Self> {
         let native_function_table = native_function_table.unwrap_or_else(|| {
             move_stdlib::natives::all_natives(
@@ -152,6 +167,8 @@ impl TestRunner {
                 starting_storage_state,
                 source_files,
                 record_writeset,
+                fuzz_runs,
+                fuzz_seed,
             },
             num_threads,
             tests,
@@ -248,7 +265,7 @@ impl SharedTestingConfig {
         &self,
         test_plan: &ModuleTestPlan,
         function_name: &str,
-        test_info: &TestCase,
+        arguments: &[MoveValue],
         factory: &Mutex<F>,
     ) -> (
         VMResult<ChangeSet>,
@@ -259,7 +276,10 @@ impl SharedTestingConfig {
         let module_storage = self.starting_storage_state.as_unsync_module_storage();
 
         let mut extensions = extensions::new_extensions();
-        let mut gas_meter = factory.lock().unwrap().new_gas_meter();
+        let mut gas_meter = factory
+            .lock()
+            .unwrap()
+            .new_gas_meter_for_test(&test_plan.module_id, function_name);
         let traversal_storage = TraversalStorage::new();
         let mut traversal_context = TraversalContext::new(&traversal_storage);
         let mut data_cache = TransactionDataCache::empty();
@@ -279,7 +299,7 @@ impl SharedTestingConfig {
                     &[],
                 )
                 .and_then(|function| {
-                    let args = serialize_values(test_info.arguments.iter());
+                    let args = serialize_values(arguments);
                     MoveVM::execute_loaded_function(
                         function,
                         args,
@@ -330,6 +350,28 @@ impl SharedTestingConfig {
         }
     }
 
+    /// Renders the storage state after a failing test, if requested.
+    fn session_state(
+        &self,
+        cs_result: VMResult<ChangeSet>,
+        ext_result: VMResult<NativeContextExtensions>,
+    ) -> Option<String> {
+        if self.save_storage_state_on_failure {
+            cs_result.ok().and_then(|changeset| {
+                ext_result.ok().and_then(|mut extensions| {
+                    print_resources_and_extensions(
+                        &changeset,
+                        &mut extensions,
+                        &self.starting_storage_state,
+                    )
+                    .ok()
+                })
+            })
+        } else {
+            None
+        }
+    }
+
     fn exec_module_tests_move_vm_and_stackless_vm<F: UnitTestFactory>(
         &self,
         test_plan: &ModuleTestPlan,
@@ -339,8 +381,20 @@ impl SharedTestingConfig {
         let mut stats = TestStatistics::new();
 
         for (function_name, test_info) in &test_plan.tests {
+            if test_info.fuzz_arguments.is_some() {
+                self.exec_fuzz_test(
+                    test_plan,
+                    function_name,
+                    test_info,
+                    output,
+                    factory,
+                    &mut stats,
+                );
+                continue;
+            }
+
             let (cs_result, ext_result, exec_result, test_run_info) =
-                self.execute_via_move_vm(test_plan, function_name, test_info, factory);
+                self.execute_via_move_vm(test_plan, function_name, &test_info.arguments, factory);
 
             if self.record_writeset {
                 stats.test_output(
@@ -350,129 +404,131 @@ impl SharedTestingConfig {
                 );
             }
 
-            let save_session_state = || {
-                if self.save_storage_state_on_failure {
-                    cs_result.ok().and_then(|changeset| {
-                        ext_result.ok().and_then(|mut extensions| {
-                            print_resources_and_extensions(
-                                &changeset,
-                                &mut extensions,
-                                &self.starting_storage_state,
-                            )
-                            .ok()
-                        })
-                    })
-                } else {
-                    None
-                }
+            let verdict = judge_test_result(test_info.expected_failure.as_ref(), exec_result);
+            report_verdict(
+                output,
+                &mut stats,
+                test_plan,
+                function_name,
+                verdict,
+                test_run_info,
+                || self.session_state(cs_result, ext_result),
+            );
+        }
+
+        stats
+    }
+
+    /// Runs a `#[test_fuzz]` test with generated arguments until it fails or the configured
+    /// number of runs passed. Arguments which reach code not covered by earlier runs are kept
+    /// and mutated to derive arguments for later runs. Arguments of a failing run are shrunk
+    /// to simpler ones which still fail before the failure is reported.
+    fn exec_fuzz_test<F: UnitTestFactory>(
+        &self,
+        test_plan: &ModuleTestPlan,
+        function_name: &str,
+        test_info: &TestCase,
+        output: &TestOutput<impl Write>,
+        factory: &Mutex<F>,
+        stats: &mut TestStatistics,
+    ) {
+        let params: &[FuzzArgument] = test_info.fuzz_arguments.as_deref().unwrap_or_default();
+        let qualified_name = format!(
+            "{}::{}",
+            format_module_id(&test_plan.module_id),
+            function_name
+        );
+        let mut rng = FuzzRng::new(fuzzer::test_seed(self.fuzz_seed, &qualified_name));
+        let mut coverage = ExecCoverageMap::new(qualified_name);
+        let mut corpus: Vec<Vec<MoveValue>> = vec![];
+        let mut elapsed_time = Duration::ZERO;
+        let mut gas_used = 0;
+        let mut last_output = None;
+
+        for _ in 0..self.fuzz_runs {
+            let arguments = if !corpus.is_empty() && rng.below(4) != 0 {
+                let parent = &corpus[rng.below(corpus.len() as u64) as usize];
+                fuzzer::mutate_arguments(&mut rng, params, parent)
+            } else {
+                fuzzer::generate_arguments(&mut rng, params)
             };
 
-            match exec_result {
-                Err(err) => {
-                    let actual_err = MoveError(
-                        err.major_status(),
-                        err.sub_status(),
-                        err.location().clone(),
-                        err.message().cloned(),
-                    );
-                    assert!(err.major_status() != StatusCode::EXECUTED);
-                    match test_info.expected_failure.as_ref() {
-                        Some(ExpectedFailure::Expected) => {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        },
-                        Some(ExpectedFailure::ExpectedWithError(expected_err))
-                            if expected_err == &actual_err =>
-                        {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        },
-                        Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(code))
-                            if actual_err.0 == StatusCode::ABORTED
-                                && actual_err.1.is_some()
-                                && actual_err.1.unwrap() == *code =>
-                        {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        },
-                        // incorrect cases
-                        Some(ExpectedFailure::ExpectedWithError(expected_err)) => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::wrong_error(expected_err.clone(), actual_err),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                        Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(expected_code)) => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::wrong_abort_deprecated(
-                                        *expected_code,
-                                        actual_err,
-                                    ),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                        None if err.major_status() == StatusCode::OUT_OF_GAS => {
-                            // Ran out of ticks, report a test timeout and log a test failure
-                            output.timeout(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::timeout(),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                        None => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::unexpected_error(actual_err),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
+            start_coverage_recording();
+            let (cs_result, ext_result, exec_result, test_run_info) =
+                self.execute_via_move_vm(test_plan, function_name, &arguments, factory);
+            let mut reached_new_code = false;
+            for (function, offset) in take_coverage_recording() {
+                reached_new_code |= coverage.insert_trace_entry(&function, offset as u64);
+            }
+            elapsed_time += test_run_info.elapsed_time;
+            gas_used += test_run_info.gas_used;
+            if self.record_writeset {
+                last_output = Some(format!("{:?}", cs_result));
+            }
+
+            match judge_test_result(test_info.expected_failure.as_ref(), exec_result) {
+                TestVerdict::Pass => {
+                    if reached_new_code {
+                        corpus.push(arguments);
                     }
                 },
-                Ok(_) => {
-                    // Expected the test to fail, but it executed
-                    if test_info.expected_failure.is_some() {
-                        output.fail(function_name);
-                        stats.test_failure(
-                            TestFailure::new(
-                                FailureReason::no_error(),
-                                test_run_info,
-                                None,
-                                save_session_state(),
-                            ),
+                verdict => {
+                    let mut failing = (arguments, verdict, cs_result, ext_result, test_run_info);
+                    let mut shrink_runs = 0;
+                    'shrink: while shrink_runs < MAX_SHRINK_RUNS {
+                        for candidate in fuzzer::shrink_arguments(params, &failing.0) {
+                            if shrink_runs == MAX_SHRINK_RUNS {
+                                break 'shrink;
+                            }
+                            shrink_runs += 1;
+                            let (cs_result, ext_result, exec_result, test_run_info) = self
+                                .execute_via_move_vm(test_plan, function_name, &candidate, factory);
+                            let verdict =
+                                judge_test_result(test_info.expected_failure.as_ref(), exec_result);
+                            // Only a failure for the same reason shrinks the failing arguments,
+                            // the others are different bugs.
+                            if verdict.fails_like(&failing.1) {
+                                failing =
+                                    (candidate, verdict, cs_result, ext_result, test_run_info);
+                                continue 'shrink;
+                            }
+                        }
+                        break;
+                    }
+
+                    let (arguments, verdict, cs_result, ext_result, test_run_info) = failing;
+                    if self.record_writeset {
+                        stats.test_output(
+                            function_name.to_string(),
                             test_plan,
-                        )
-                    } else {
-                        // Expected the test to execute fully and it did
-                        output.pass(function_name);
-                        stats.test_success(test_run_info, test_plan);
+                            format!("{:?}", cs_result),
+                        );
                     }
+                    let arguments = arguments.iter().map(fuzzer::format_value).collect();
+                    let verdict = verdict.map_failure(|reason| {
+                        FailureReason::fuzz(reason, self.fuzz_seed, arguments)
+                    });
+                    report_verdict(
+                        output,
+                        stats,
+                        test_plan,
+                        function_name,
+                        verdict,
+                        test_run_info,
+                        || self.session_state(cs_result, ext_result),
+                    );
+                    return;
                 },
             }
         }
 
-        stats
+        if let Some(last_output) = last_output {
+            stats.test_output(function_name.to_string(), test_plan, last_output);
+        }
+        let mut test_run_info = TestRunInfo::new(function_name.to_string(), elapsed_time);
+        test_run_info.gas_used = gas_used;
+        output.pass(function_name);
+        stats.test_success(test_run_info, test_plan);
     }
 
     fn exec_module_tests<F: UnitTestFactory>(
@@ -485,3 +541,116 @@ impl SharedTestingConfig {
         self.exec_module_tests_move_vm_and_stackless_vm(test_plan, &output, factory)
     }
 }
+
+/// The outcome of a single execution of a test.
+enum TestVerdict {
+    Pass,
+    Fail(FailureReason, Option<VMError>),
+    Timeout(FailureReason, VMError),
+}
+
+impl TestVerdict {
+    /// Returns whether `self` is a failure for the same reason as `other`.
+    fn fails_like(&self, other: &TestVerdict) -> bool {
+        match (self, other) {
+            (TestVerdict::Fail(reason, _), TestVerdict::Fail(other_reason, _))
+            | (TestVerdict::Timeout(reason, _), TestVerdict::Timeout(other_reason, _)) => {
+                reason == other_reason
+            },
+            _ => false,
+        }
+    }
+
+    fn map_failure(self, f: impl FnOnce(FailureReason) -> FailureReason) -> Self {
+        match self {
+            TestVerdict::Pass => TestVerdict::Pass,
+            TestVerdict::Fail(reason, err) => TestVerdict::Fail(f(reason), err),
+            TestVerdict::Timeout(reason, err) => TestVerdict::Timeout(f(reason), err),
+        }
+    }
+}
+
+/// Judges the result of executing a test against the failure the test expects, if any.
+fn judge_test_result(
+    expected_failure: Option<&ExpectedFailure>,
+    exec_result: VMResult<Vec<Vec<u8>>>,
+) -> TestVerdict {
+    match exec_result {
+        Err(err) => {
+            let actual_err = MoveError(
+                err.major_status(),
+                err.sub_status(),
+                err.location().clone(),
+                err.message().cloned(),
+            );
+            assert!(err.major_status() != StatusCode::EXECUTED);
+            match expected_failure {
+                Some(ExpectedFailure::Expected) => TestVerdict::Pass,
+                Some(ExpectedFailure::ExpectedWithError(expected_err))
+                    if expected_err == &actual_err =>
+                {
+                    TestVerdict::Pass
+                },
+                Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(code))
+                    if actual_err.0 == StatusCode::ABORTED
+                        && actual_err.1.is_some()
+                        && actual_err.1.unwrap() == *code =>
+                {
+                    TestVerdict::Pass
+                },
+                // incorrect cases
+                Some(ExpectedFailure::ExpectedWithError(expected_err)) => TestVerdict::Fail(
+                    FailureReason::wrong_error(expected_err.clone(), actual_err),
+                    Some(err),
+                ),
+                Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(expected_code)) => {
+                    TestVerdict::Fail(
+                        FailureReason::wrong_abort_deprecated(*expected_code, actual_err),
+                        Some(err),
+                    )
+                },
+                None if err.major_status() == StatusCode::OUT_OF_GAS => {
+                    // Ran out of ticks, report a test timeout
+                    TestVerdict::Timeout(FailureReason::timeout(), err)
+                },
+                None => TestVerdict::Fail(FailureReason::unexpected_error(actual_err), Some(err)),
+            }
+        },
+        // Expected the test to fail, but it executed
+        Ok(_) if expected_failure.is_some() => TestVerdict::Fail(FailureReason::no_error(), None),
+        // Expected the test to execute fully and it did
+        Ok(_) => TestVerdict::Pass,
+    }
+}
+
+/// Prints the verdict for a test and records it in `stats`.
+fn report_verdict(
+    output: &TestOutput<impl Write>,
+    stats: &mut TestStatistics,
+    test_plan: &ModuleTestPlan,
+    function_name: &str,
+    verdict: TestVerdict,
+    test_run_info: TestRunInfo,
+    save_session_state: impl FnOnce() -> Option<String>,
+) {
+    match verdict {
+        TestVerdict::Pass => {
+            output.pass(function_name);
+            stats.test_success(test_run_info, test_plan);
+        },
+        TestVerdict::Fail(reason, err) => {
+            output.fail(function_name);
+            stats.test_failure(
+                TestFailure::new(reason, test_run_info, err, save_session_state()),
+                test_plan,
+            )
+        },
+        TestVerdict::Timeout(reason, err) => {
+            output.timeout(function_name);
+            stats.test_failure(
+                TestFailure::new(reason, test_run_info, Some(err), save_session_state()),
+                test_plan,
+            )
+        },
+    }
+}
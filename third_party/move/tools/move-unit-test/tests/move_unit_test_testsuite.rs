@@ -98,6 +98,8 @@ fn run_test_impl(path: &Path) -> anyhow::Result<()> {
             .into_iter()
             .collect(),
         verbose: true,
+        // Fuzzed tests must generate the same arguments on every run to match the baselines
+        fuzz_seed: Some(0),
 
         ..UnitTestingConfig::default()
     };
@@ -0,0 +1,41 @@
+address 0x1 {
+module M {
+    use std::signer;
+    use std::vector;
+
+    struct Pair has drop {
+        x: u64,
+        y: bool,
+    }
+
+    #[test_fuzz]
+    fun add_commutes(a: u64, b: u64) {
+        assert!((a as u128) + (b as u128) == (b as u128) + (a as u128), 0);
+    }
+
+    #[test_fuzz]
+    fun reverse_twice(v: vector<u8>) {
+        let w = copy v;
+        vector::reverse(&mut w);
+        vector::reverse(&mut w);
+        assert!(w == v, 0);
+    }
+
+    #[test_fuzz(s = @0x42)]
+    fun fixed_signer(s: &signer, p: Pair) {
+        assert!(signer::address_of(s) == @0x42, 0);
+        let Pair { x: _, y: _ } = p;
+    }
+
+    #[test_fuzz]
+    #[expected_failure(abort_code = 7, location = Self)]
+    fun always_aborts(x: u8) {
+        assert!(x != x, 7);
+    }
+
+    #[test_fuzz]
+    fun bounded(x: u64) {
+        if (x >= 100) abort 1
+    }
+}
+}
@@ -10,9 +10,10 @@ use crate::{
         state_value::StateValue,
         table::{TableHandle, TableInfo},
     },
-    transaction::{AccountOrderedTransactionsWithProof, Version},
+    transaction::{AccountOrderedTransactionsWithProof, TransactionWithProof, Version},
 };
 use anyhow::Result;
+use move_core_types::language_storage::TypeTag;
 
 #[derive(Clone, Copy, Eq, PartialEq)]
 pub enum Order {
@@ -43,6 +44,18 @@ pub trait IndexerReader: Send + Sync {
         ledger_version: Version,
     ) -> Result<Vec<EventWithVersion>>;
 
+    /// Returns the events of type `type_tag` emitted between `start_version` and `end_version`
+    /// (both inclusive), in ascending order. If `start_version` is `None`, the latest events are
+    /// returned.
+    fn get_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Option<Version>,
+        end_version: Option<Version>,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithVersion>>;
+
     fn get_account_ordered_transactions(
         &self,
         address: AccountAddress,
@@ -52,6 +65,18 @@ pub trait IndexerReader: Send + Sync {
         ledger_version: Version,
     ) -> Result<AccountOrderedTransactionsWithProof>;
 
+    /// Returns the transactions that touched the account (i.e., not only those sent by it), in
+    /// ascending order of version. If `start_version` is `None`, the latest transactions are
+    /// returned.
+    fn get_account_transactions(
+        &self,
+        address: AccountAddress,
+        start_version: Option<Version>,
+        limit: u64,
+        include_events: bool,
+        ledger_version: Version,
+    ) -> Result<Vec<TransactionWithProof>>;
+
     fn get_prefixed_state_value_iterator(
         &self,
         key_prefix: &StateKeyPrefix,
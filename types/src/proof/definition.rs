@@ -341,63 +341,12 @@ impl SparseMerkleProof {
             self.siblings.len(),
         );
 
-        match (element_hash, self.leaf) {
-            (Some(hash), Some(leaf)) => {
-                // This is an inclusion proof, so the key and value hash provided in the proof
-                // should match element_key and element_value_hash. `siblings` should prove the
-                // route from the leaf node to the root.
-                ensure!(
-                    element_key == leaf.key,
-                    "Keys do not match. Key in proof: {:x}. Expected key: {:x}. \
-                     Element hash: {:x}. Value hash in proof {:x}",
-                    leaf.key,
-                    element_key,
-                    hash,
-                    leaf.value_hash
-                );
-                ensure!(
-                    hash == leaf.value_hash,
-                    "Value hashes do not match for key {:x}. Value hash in proof: {:x}. \
-                     Expected value hash: {:x}. ",
-                    element_key,
-                    leaf.value_hash,
-                    hash
-                );
-            },
-            (Some(hash), None) => {
-                bail!(
-                    "Expected inclusion proof, value hash: {:x}. Found non-inclusion proof.",
-                    hash
-                )
-            },
-            (None, Some(leaf)) => {
-                // This is a non-inclusion proof. The proof intends to show that if a leaf node
-                // representing `element_key` is inserted, it will break a currently existing leaf
-                // node represented by `proof_key` into a branch. `siblings` should prove the
-                // route from that leaf node to the root.
-                ensure!(
-                    element_key != leaf.key,
-                    "Expected non-inclusion proof, but key exists in proof. \
-                     Key: {:x}. Key in proof: {:x}.",
-                    element_key,
-                    leaf.key,
-                );
-                ensure!(
-                    element_key.common_prefix_bits_len(leaf.key)
-                        >= root_depth + self.siblings.len(),
-                    "Key would not have ended up in the subtree where the provided key in proof \
-                     is the only existing key, if it existed. So this is not a valid \
-                     non-inclusion proof. Key: {:x}. Key in proof: {:x}.",
-                    element_key,
-                    leaf.key
-                );
-            },
-            (None, None) => {
-                // This is a non-inclusion proof. The proof intends to show that if a leaf node
-                // representing `element_key` is inserted, it will show up at a currently empty
-                // position. `sibling` should prove the route from this empty position to the root.
-            },
-        }
+        verify_leaf_of_path(
+            self.leaf,
+            element_key,
+            element_hash,
+            root_depth + self.siblings.len(),
+        )?;
 
         let current_hash = self
             .leaf
@@ -431,6 +380,75 @@ impl SparseMerkleProof {
     }
 }
 
+/// Checks that `leaf`, found at `bottom_depth` on the path of `element_key`, proves the
+/// inclusion of `element_key` with `element_hash`, or its non-inclusion if `element_hash` is
+/// `None`.
+fn verify_leaf_of_path(
+    leaf: Option<SparseMerkleLeafNode>,
+    element_key: HashValue,
+    element_hash: Option<HashValue>,
+    bottom_depth: usize,
+) -> Result<()> {
+    match (element_hash, leaf) {
+        (Some(hash), Some(leaf)) => {
+            // This is an inclusion proof, so the key and value hash provided in the proof
+            // should match element_key and element_value_hash. `siblings` should prove the
+            // route from the leaf node to the root.
+            ensure!(
+                element_key == leaf.key,
+                "Keys do not match. Key in proof: {:x}. Expected key: {:x}. \
+                 Element hash: {:x}. Value hash in proof {:x}",
+                leaf.key,
+                element_key,
+                hash,
+                leaf.value_hash
+            );
+            ensure!(
+                hash == leaf.value_hash,
+                "Value hashes do not match for key {:x}. Value hash in proof: {:x}. \
+                 Expected value hash: {:x}. ",
+                element_key,
+                leaf.value_hash,
+                hash
+            );
+        },
+        (Some(hash), None) => {
+            bail!(
+                "Expected inclusion proof, value hash: {:x}. Found non-inclusion proof.",
+                hash
+            )
+        },
+        (None, Some(leaf)) => {
+            // This is a non-inclusion proof. The proof intends to show that if a leaf node
+            // representing `element_key` is inserted, it will break a currently existing leaf
+            // node represented by `proof_key` into a branch. `siblings` should prove the
+            // route from that leaf node to the root.
+            ensure!(
+                element_key != leaf.key,
+                "Expected non-inclusion proof, but key exists in proof. \
+                 Key: {:x}. Key in proof: {:x}.",
+                element_key,
+                leaf.key,
+            );
+            ensure!(
+                element_key.common_prefix_bits_len(leaf.key) >= bottom_depth,
+                "Key would not have ended up in the subtree where the provided key in proof \
+                 is the only existing key, if it existed. So this is not a valid \
+                 non-inclusion proof. Key: {:x}. Key in proof: {:x}.",
+                element_key,
+                leaf.key
+            );
+        },
+        (None, None) => {
+            // This is a non-inclusion proof. The proof intends to show that if a leaf node
+            // representing `element_key` is inserted, it will show up at a currently empty
+            // position. `sibling` should prove the route from this empty position to the root.
+        },
+    }
+
+    Ok(())
+}
+
 /// An in-memory accumulator for storing a summary of the core transaction info
 /// accumulator. It is a summary in the sense that it only stores maximally
 /// frozen subtree nodes rather than storing all leaves and internal nodes.
@@ -827,6 +845,202 @@ impl SparseMerkleRangeProof {
     }
 }
 
+/// A proof that authenticates a set of keys in a Sparse Merkle Tree against the same root hash.
+///
+/// It carries the same information as one `SparseMerkleProof` per key, except that a node on the
+/// path of several keys is only included once, and a sibling that is on the path of another key
+/// in the set is not included at all, since the verifier computes it. For example, to prove `b`
+/// and `e` in the tree shown for `SparseMerkleRangeProof`, only `a`, `c`, `d`, `X` and `h` are
+/// needed.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
+pub struct SparseMerkleBatchProof {
+    /// For each key, in increasing order of the keys, the leaf at the bottom of its path (see
+    /// `SparseMerkleProof::leaf`) and the depth of the bottom.
+    leaves: Vec<(Option<SparseMerkleLeafNode>, u16)>,
+    /// The siblings that are not on the path of any of the keys, in the order they are met by a
+    /// depth-first, left to right traversal of the paths.
+    siblings: Vec<HashValue>,
+}
+
+impl SparseMerkleBatchProof {
+    /// Constructs a new `SparseMerkleBatchProof` using the leaves and siblings.
+    pub fn new(leaves: Vec<(Option<SparseMerkleLeafNode>, u16)>, siblings: Vec<HashValue>) -> Self {
+        Self { leaves, siblings }
+    }
+
+    /// Combines the proofs of individual keys, which must be in strictly increasing order of the
+    /// keys and prove them against the same root.
+    pub fn from_proofs(proofs: &[(HashValue, SparseMerkleProof)]) -> Result<Self> {
+        ensure!(!proofs.is_empty(), "Cannot build a batch proof of no keys.");
+        ensure!(
+            proofs.windows(2).all(|w| w[0].0 < w[1].0),
+            "Keys in a batch proof must be unique and sorted.",
+        );
+
+        let mut siblings = Vec::new();
+        Self::collect_siblings(proofs, 0, &mut siblings)?;
+        let leaves = proofs
+            .iter()
+            .map(|(_key, proof)| (proof.leaf(), proof.siblings().len() as u16))
+            .collect();
+        Ok(Self::new(leaves, siblings))
+    }
+
+    fn collect_siblings(
+        proofs: &[(HashValue, SparseMerkleProof)],
+        depth: usize,
+        siblings: &mut Vec<HashValue>,
+    ) -> Result<()> {
+        let (_key, first_proof) = &proofs[0];
+        if proofs
+            .iter()
+            .any(|(_key, proof)| proof.siblings().len() == depth)
+        {
+            ensure!(
+                proofs.iter().all(|(_key, proof)| {
+                    proof.siblings().len() == depth && proof.leaf() == first_proof.leaf()
+                }),
+                "Proofs of keys in the same subtree disagree at depth {}.",
+                depth,
+            );
+            return Ok(());
+        }
+
+        let num_left = proofs.partition_point(|(key, _proof)| !key.bit(depth));
+        if num_left == 0 || num_left == proofs.len() {
+            // All the keys are on the same side, so the sibling is needed.
+            siblings.push(first_proof.siblings()[depth]);
+            Self::collect_siblings(proofs, depth + 1, siblings)
+        } else {
+            Self::collect_siblings(&proofs[..num_left], depth + 1, siblings)?;
+            Self::collect_siblings(&proofs[num_left..], depth + 1, siblings)
+        }
+    }
+
+    /// Returns the leaves in this proof, with the depths they are at.
+    pub fn leaves(&self) -> &[(Option<SparseMerkleLeafNode>, u16)] {
+        &self.leaves
+    }
+
+    /// Returns the siblings in this proof.
+    pub fn siblings(&self) -> &[HashValue] {
+        &self.siblings
+    }
+
+    pub fn verify<V: CryptoHash>(
+        &self,
+        expected_root_hash: HashValue,
+        elements: &[(HashValue, Option<&V>)],
+    ) -> Result<()> {
+        let elements: Vec<_> = elements
+            .iter()
+            .map(|(key, value)| (*key, value.map(|v| v.hash())))
+            .collect();
+        self.verify_by_hash(expected_root_hash, &elements)
+    }
+
+    /// Verifies, for each element in `elements`, which must be in strictly increasing order of the
+    /// keys, that the key exists in the tree with the value authenticated by the hash if the hash
+    /// is present, or doesn't exist otherwise, as `SparseMerkleProof::verify_by_hash` does.
+    pub fn verify_by_hash(
+        &self,
+        expected_root_hash: HashValue,
+        elements: &[(HashValue, Option<HashValue>)],
+    ) -> Result<()> {
+        ensure!(
+            !elements.is_empty(),
+            "Cannot verify a batch proof of no keys."
+        );
+        ensure!(
+            elements.len() == self.leaves.len(),
+            "Batch proof has {} leaves, but {} elements are verified.",
+            self.leaves.len(),
+            elements.len(),
+        );
+        ensure!(
+            elements.windows(2).all(|w| w[0].0 < w[1].0),
+            "Keys in a batch proof must be unique and sorted.",
+        );
+        for ((element_key, element_hash), (leaf, depth)) in elements.iter().zip(&self.leaves) {
+            verify_leaf_of_path(*leaf, *element_key, *element_hash, *depth as usize)?;
+        }
+
+        let keys: Vec<_> = elements.iter().map(|(key, _hash)| *key).collect();
+        let mut siblings = self.siblings.iter();
+        let actual_root_hash = Self::compute_subtree_hash(&keys, &self.leaves, 0, &mut siblings)?;
+        ensure!(
+            siblings.next().is_none(),
+            "Batch proof has more siblings than needed."
+        );
+        ensure!(
+            actual_root_hash == expected_root_hash,
+            "{}: Root hashes do not match. Actual root hash: {:x}. Expected root hash: {:x}.",
+            type_name::<Self>(),
+            actual_root_hash,
+            expected_root_hash,
+        );
+
+        Ok(())
+    }
+
+    fn compute_subtree_hash<'a>(
+        keys: &[HashValue],
+        leaves: &[(Option<SparseMerkleLeafNode>, u16)],
+        depth: usize,
+        siblings: &mut impl Iterator<Item = &'a HashValue>,
+    ) -> Result<HashValue> {
+        let (first_leaf, _depth) = leaves[0];
+        if leaves
+            .iter()
+            .any(|(_leaf, leaf_depth)| *leaf_depth as usize == depth)
+        {
+            ensure!(
+                leaves.iter().all(|(leaf, leaf_depth)| {
+                    *leaf_depth as usize == depth && *leaf == first_leaf
+                }),
+                "Leaves of keys in the same subtree disagree at depth {}.",
+                depth,
+            );
+            return Ok(first_leaf.map_or(*SPARSE_MERKLE_PLACEHOLDER_HASH, |leaf| leaf.hash()));
+        }
+        ensure!(
+            depth < HashValue::LENGTH_IN_BITS,
+            "Sparse Merkle Tree batch proof is deeper than {} levels.",
+            HashValue::LENGTH_IN_BITS,
+        );
+
+        let num_left = keys.partition_point(|key| !key.bit(depth));
+        let (left_hash, right_hash) = if num_left == 0 || num_left == keys.len() {
+            let sibling = *siblings
+                .next()
+                .ok_or_else(|| format_err!("Missing sibling at depth {}.", depth))?;
+            let child = Self::compute_subtree_hash(keys, leaves, depth + 1, siblings)?;
+            if num_left == 0 {
+                (sibling, child)
+            } else {
+                (child, sibling)
+            }
+        } else {
+            (
+                Self::compute_subtree_hash(
+                    &keys[..num_left],
+                    &leaves[..num_left],
+                    depth + 1,
+                    siblings,
+                )?,
+                Self::compute_subtree_hash(
+                    &keys[num_left..],
+                    &leaves[num_left..],
+                    depth + 1,
+                    siblings,
+                )?,
+            )
+        };
+        Ok(SparseMerkleInternalNode::new(left_hash, right_hash).hash())
+    }
+}
+
 /// `TransactionInfo` and a `TransactionAccumulatorProof` connecting it to the ledger root.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
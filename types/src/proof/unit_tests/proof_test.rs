@@ -11,9 +11,9 @@ use crate::{
     ledger_info::LedgerInfo,
     proof::{
         definition::MAX_ACCUMULATOR_PROOF_DEPTH, AccumulatorExtensionProof, AccumulatorRangeProof,
-        SparseMerkleInternalNode, SparseMerkleLeafNode, TestAccumulatorInternalNode,
-        TestAccumulatorProof, TransactionAccumulatorInternalNode, TransactionAccumulatorProof,
-        TransactionInfoListWithProof, TransactionInfoWithProof,
+        SparseMerkleBatchProof, SparseMerkleInternalNode, SparseMerkleLeafNode,
+        TestAccumulatorInternalNode, TestAccumulatorProof, TransactionAccumulatorInternalNode,
+        TransactionAccumulatorProof, TransactionInfoListWithProof, TransactionInfoWithProof,
     },
     state_store::state_value::StateValue,
     transaction::{
@@ -267,6 +267,118 @@ fn test_verify_three_element_sparse_merkle() {
     }
 }
 
+#[test]
+fn test_verify_sparse_merkle_batch_proof() {
+    // The same tree as in `test_verify_three_element_sparse_merkle`.
+    //            root
+    //           /    \
+    //          a      default
+    //         / \
+    //     key1   b
+    //           / \
+    //       key2   key3
+    let key1 = b"hello".test_only_hash();
+    let key2 = b"world".test_only_hash();
+    let key3 = b"!".test_only_hash();
+    let non_existing_key1 = b"abc".test_only_hash();
+    let non_existing_key2 = b"def".test_only_hash();
+
+    let blob1 = StateValue::from(b"1".to_vec());
+    let blob2 = StateValue::from(b"2".to_vec());
+    let blob3 = StateValue::from(b"3".to_vec());
+
+    let leaf1 = SparseMerkleLeafNode::new(key1, blob1.hash());
+    let leaf2 = SparseMerkleLeafNode::new(key2, blob2.hash());
+    let leaf3 = SparseMerkleLeafNode::new(key3, blob3.hash());
+    let internal_b_hash = SparseMerkleInternalNode::new(leaf2.hash(), leaf3.hash()).hash();
+    let internal_a_hash = SparseMerkleInternalNode::new(leaf1.hash(), internal_b_hash).hash();
+    let root_hash =
+        SparseMerkleInternalNode::new(internal_a_hash, *SPARSE_MERKLE_PLACEHOLDER_HASH).hash();
+
+    let proof1 = SparseMerkleProof::new(Some(leaf1), vec![
+        *SPARSE_MERKLE_PLACEHOLDER_HASH,
+        internal_b_hash,
+    ]);
+    let proof2 = SparseMerkleProof::new(Some(leaf2), vec![
+        *SPARSE_MERKLE_PLACEHOLDER_HASH,
+        leaf1.hash(),
+        leaf3.hash(),
+    ]);
+    let proof3 = SparseMerkleProof::new(Some(leaf3), vec![
+        *SPARSE_MERKLE_PLACEHOLDER_HASH,
+        leaf1.hash(),
+        leaf2.hash(),
+    ]);
+    let default_proof = SparseMerkleProof::new(None, vec![internal_a_hash]);
+
+    {
+        // Only the siblings off the paths of key1 and key3 are included.
+        let proof =
+            SparseMerkleBatchProof::from_proofs(&[(key1, proof1.clone()), (key3, proof3.clone())])
+                .unwrap();
+        assert_eq!(proof.siblings(), &[
+            *SPARSE_MERKLE_PLACEHOLDER_HASH,
+            leaf2.hash()
+        ]);
+
+        assert!(proof
+            .verify(root_hash, &[(key1, Some(&blob1)), (key3, Some(&blob3))])
+            .is_ok());
+        // Trying to show that a key has another value.
+        assert!(proof
+            .verify(root_hash, &[(key1, Some(&blob1)), (key3, Some(&blob2))])
+            .is_err());
+        // Trying to show that a key doesn't exist.
+        assert!(proof
+            .verify(root_hash, &[(key1, None), (key3, Some(&blob3))])
+            .is_err());
+        // Keys must be sorted.
+        assert!(proof
+            .verify(root_hash, &[(key3, Some(&blob3)), (key1, Some(&blob1))])
+            .is_err());
+        // All the keys in the proof must be verified.
+        assert!(proof.verify(root_hash, &[(key1, Some(&blob1))]).is_err());
+        assert!(proof
+            .verify(*SPARSE_MERKLE_PLACEHOLDER_HASH, &[
+                (key1, Some(&blob1)),
+                (key3, Some(&blob3))
+            ])
+            .is_err());
+    }
+
+    {
+        // All the siblings are computed when every leaf is proved, along with both non-existing
+        // keys.
+        let proof = SparseMerkleBatchProof::from_proofs(&[
+            (key1, proof1.clone()),
+            (non_existing_key1, proof1),
+            (key2, proof2),
+            (key3, proof3),
+            (non_existing_key2, default_proof),
+        ])
+        .unwrap();
+        assert!(proof.siblings().is_empty());
+        assert!(proof
+            .verify(root_hash, &[
+                (key1, Some(&blob1)),
+                (non_existing_key1, None),
+                (key2, Some(&blob2)),
+                (key3, Some(&blob3)),
+                (non_existing_key2, None),
+            ])
+            .is_ok());
+        assert!(proof
+            .verify(root_hash, &[
+                (key1, Some(&blob1)),
+                (non_existing_key1, Some(&blob1)),
+                (key2, Some(&blob2)),
+                (key3, Some(&blob3)),
+                (non_existing_key2, None),
+            ])
+            .is_err());
+    }
+}
+
 #[test]
 fn test_verify_transaction() {
     //            root
@@ -2,10 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    on_chain_config::CurrentTimeMicroseconds, proof::SparseMerkleRangeProof,
-    state_store::state_key::StateKey, transaction::Version,
+    on_chain_config::CurrentTimeMicroseconds,
+    proof::{SparseMerkleBatchProof, SparseMerkleRangeProof},
+    state_store::state_key::StateKey,
+    transaction::Version,
+};
+use aptos_crypto::{
+    hash::{CryptoHash, SPARSE_MERKLE_PLACEHOLDER_HASH},
+    HashValue,
 };
-use aptos_crypto::{hash::SPARSE_MERKLE_PLACEHOLDER_HASH, HashValue};
 use aptos_crypto_derive::{BCSCryptoHash, CryptoHasher};
 use bytes::Bytes;
 #[cfg(any(test, feature = "fuzzing"))]
@@ -363,6 +368,31 @@ impl StateValueChunkWithProof {
     }
 }
 
+/// The values of a set of state keys, with a single proof of all of them against the root hash of
+/// the state tree.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(proptest_derive::Arbitrary))]
+pub struct StateValuesWithBatchProof {
+    /// The state keys and their values (`None` if the key doesn't exist), in increasing order of
+    /// the hashes of the keys.
+    pub state_values: Vec<(StateKey, Option<StateValue>)>,
+    pub proof: SparseMerkleBatchProof, // The proof of all the state values
+    pub root_hash: HashValue,          // The root hash of the sparse merkle tree
+}
+
+impl StateValuesWithBatchProof {
+    /// Verifies the state values against `root_hash`, which is expected to be authenticated
+    /// separately (e.g., by the state checkpoint hash of a verified `TransactionInfo`).
+    pub fn verify(&self) -> anyhow::Result<()> {
+        let elements: Vec<_> = self
+            .state_values
+            .iter()
+            .map(|(key, value)| (*key.crypto_hash_ref(), value.as_ref().map(|v| v.hash())))
+            .collect();
+        self.proof.verify_by_hash(self.root_hash, &elements)
+    }
+}
+
 /// Indicates a state value becomes stale since `stale_since_version`.
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 #[cfg_attr(any(test, feature = "fuzzing"), derive(proptest_derive::Arbitrary))]